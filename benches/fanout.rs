@@ -0,0 +1,97 @@
+// benches/fanout.rs
+
+//! # 扇出基准
+//!
+//! 比较按值发布（`publish` / `subscribe`）与共享发布（`publish_shared` / `subscribe_shared`）
+//! 把一条消息扇出给 64 个订阅者的耗时：一次发布，加上每个订阅者接收一次。
+//! broadcast 通道在接收时克隆消息，按值发布时每个订阅者都会完整克隆一次，共享发布只克隆 `Arc`。
+//!
+//! ```bash
+//! cargo bench --bench fanout
+//! ```
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use message_bus::bus::{MessageBus, Receiver};
+use message_bus::message::{Bar, Message, OrderBookSnapshot, PriceLevel, DEFAULT_BAR_TIMEFRAME};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+const SUBSCRIBERS: usize = 64;
+
+fn bar() -> Bar {
+    Bar {
+        id: Uuid::new_v4(),
+        ts_event: 0,
+        symbol: "BTC-USD".to_string(),
+        open: 100.0,
+        high: 101.0,
+        low: 99.0,
+        close: 100.5,
+        volume: 10.0,
+        timeframe: DEFAULT_BAR_TIMEFRAME,
+    }
+}
+
+/// 每侧 `depth` 档的订单簿快照。
+fn book(depth: usize) -> OrderBookSnapshot {
+    let level = |i: usize, sign: f64| PriceLevel { price: 100.0 + sign * i as f64 * 0.01, size: 1.0 };
+    OrderBookSnapshot {
+        symbol: "BTC-USD".to_string(),
+        bids: (1..=depth).map(|i| level(i, -1.0)).collect(),
+        asks: (1..=depth).map(|i| level(i, 1.0)).collect(),
+        ts_event: 0,
+    }
+}
+
+/// 每个订阅者接收一条消息。
+fn drain<M: Message>(receivers: &mut [Receiver<M>]) {
+    for rx in receivers {
+        std::hint::black_box(rx.try_recv().unwrap());
+    }
+}
+
+fn bench_message<M: Message>(c: &mut Criterion, runtime: &Runtime, name: &str, msg: M) {
+    let mut group = c.benchmark_group(format!("fanout_{}", name));
+
+    let bus = MessageBus::new(16);
+    let mut by_value: Vec<_> = runtime.block_on(async { futures_subscribe(&bus, |bus| bus.subscribe::<M>()).await });
+    group.bench_function(BenchmarkId::new("value", SUBSCRIBERS), |b| {
+        b.iter(|| {
+            runtime.block_on(bus.publish(msg.clone())).unwrap();
+            drain(&mut by_value);
+        })
+    });
+
+    let shared = Arc::new(msg);
+    let mut by_arc: Vec<_> = runtime.block_on(async { futures_subscribe(&bus, |bus| bus.subscribe_shared::<M>()).await });
+    group.bench_function(BenchmarkId::new("arc", SUBSCRIBERS), |b| {
+        b.iter(|| {
+            runtime.block_on(bus.publish_shared(shared.clone())).unwrap();
+            drain(&mut by_arc);
+        })
+    });
+    group.finish();
+}
+
+/// 建立 `SUBSCRIBERS` 个订阅者。
+async fn futures_subscribe<'a, R, F, Fut>(bus: &'a MessageBus, subscribe: F) -> Vec<R>
+where
+    F: Fn(&'a MessageBus) -> Fut,
+    Fut: std::future::Future<Output = R>,
+{
+    let mut receivers = Vec::with_capacity(SUBSCRIBERS);
+    for _ in 0..SUBSCRIBERS {
+        receivers.push(subscribe(bus).await);
+    }
+    receivers
+}
+
+fn fanout(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    bench_message(c, &runtime, "bar", bar());
+    bench_message(c, &runtime, "book_200", book(200));
+}
+
+criterion_group!(benches, fanout);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+// build.rs
+
+//! 启用 `grpc` feature 时由 `proto/control.proto` 生成 gRPC 服务代码，
+//! 启用 `protobuf` feature 时由 `proto/wire.proto` 生成线上消息类型。
+//! 使用纯 Rust 的 `protox` 解析 proto 文件，构建环境不需要安装 `protoc`。
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/control.proto");
+        let descriptors = protox::compile(["proto/control.proto"], ["proto"]).expect("failed to parse control.proto");
+        tonic_build::configure().compile_fds(descriptors).expect("failed to generate gRPC code");
+    }
+    #[cfg(feature = "protobuf")]
+    {
+        println!("cargo:rerun-if-changed=proto/wire.proto");
+        let descriptors = protox::compile(["proto/wire.proto"], ["proto"]).expect("failed to parse wire.proto");
+        prost_build::Config::new().compile_fds(descriptors).expect("failed to generate wire types");
+    }
+}
@@ -0,0 +1,52 @@
+"""用 Python 编写的趋势策略：收盘价连续上涨时买入，连续下跌时卖出。
+
+构建扩展模块后运行：
+
+    cd python && maturin develop && python examples/trend_strategy.py
+"""
+
+import time
+
+from message_bus_py import Bar, MessageBus, OrderRequest
+
+SYMBOL = "BTC-USD"
+
+
+class TrendStrategy:
+    def __init__(self, bus, lookback=3, quantity=1.0):
+        self.bus = bus
+        self.lookback = lookback
+        self.quantity = quantity
+        self.closes = []
+        self.position = 0.0
+
+    def on_bar(self, bar):
+        self.closes.append(bar.close)
+        recent = self.closes[-(self.lookback + 1):]
+        if len(recent) <= self.lookback:
+            return
+        rising = all(a < b for a, b in zip(recent, recent[1:]))
+        falling = all(a > b for a, b in zip(recent, recent[1:]))
+        if rising and self.position <= 0:
+            self.bus.publish_order(OrderRequest(bar.symbol, "buy", self.quantity, bar.close))
+        elif falling and self.position > 0:
+            self.bus.publish_order(OrderRequest(bar.symbol, "sell", self.position, bar.close))
+
+    def on_fill(self, fill):
+        self.position += fill.quantity if fill.side == "buy" else -fill.quantity
+        print(f"filled {fill.side} {fill.quantity} {fill.symbol} @ {fill.price}, position {self.position}")
+
+
+def main():
+    with MessageBus() as bus:
+        bus.start_simulated_execution()
+        bus.register_strategy(TrendStrategy(bus), name="STRATEGY.PY")
+        prices = [100, 101, 102, 103, 104, 103, 102, 101, 100]
+        for i, price in enumerate(prices):
+            bus.publish_bar(Bar(SYMBOL, price, price, price, price, volume=1.0, ts_event=i))
+            time.sleep(0.1)
+        time.sleep(0.5)
+
+
+if __name__ == "__main__":
+    main()
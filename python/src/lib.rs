@@ -0,0 +1,15 @@
+// python/src/lib.rs
+
+//! # message_bus_py
+//!
+//! 导出 `message_bus::python` 中的类作为 Python 扩展模块。
+
+use pyo3::prelude::*;
+
+/// ## `message_bus_py`
+///
+/// 扩展模块的入口，Python 中 `import message_bus_py` 时调用，登记 `Bar`、`OrderRequest`、`FillEvent` 与 `MessageBus` 等类。
+#[pymodule]
+fn message_bus_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    message_bus::python::register(m)
+}
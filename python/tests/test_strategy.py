@@ -0,0 +1,92 @@
+"""Python 策略绑定测试：`cd python && maturin develop && pytest`。"""
+
+import sys
+import threading
+
+import pytest
+
+from message_bus_py import Bar, MessageBus, OrderRequest
+
+TIMEOUT = 2.0
+
+
+def bar(close, ts_event=0):
+    return Bar("BTC-USD", close, close, close, close, volume=1.0, ts_event=ts_event)
+
+
+class BuyOnceStrategy:
+    """第一根 Bar 到达时买入，记录收到的 Bar 与成交。"""
+
+    def __init__(self, bus, expected_bars=2):
+        self.bus = bus
+        self.expected_bars = expected_bars
+        self.bars = []
+        self.fills = []
+        self.filled = threading.Event()
+        self.done = threading.Event()
+
+    def on_bar(self, bar):
+        self.bars.append(bar.close)
+        if len(self.bars) == self.expected_bars:
+            self.done.set()
+        if len(self.bars) == 1:
+            self.bus.publish_order(OrderRequest(bar.symbol, "buy", 2.0, bar.close))
+
+    def on_fill(self, fill):
+        self.fills.append(fill)
+        if fill.is_final:
+            self.filled.set()
+
+
+def test_strategy_receives_bars_and_its_fills():
+    with MessageBus() as bus:
+        bus.start_simulated_execution()
+        strategy = BuyOnceStrategy(bus)
+        bus.register_strategy(strategy)
+        bus.publish_bar(bar(100.0))
+        assert strategy.filled.wait(TIMEOUT)
+        bus.publish_bar(bar(101.0))
+        assert strategy.done.wait(TIMEOUT)
+
+    assert strategy.bars == [100.0, 101.0]
+    assert sum(fill.quantity for fill in strategy.fills) == 2.0
+    assert {fill.side for fill in strategy.fills} == {"buy"}
+
+
+def test_callback_errors_do_not_stop_the_strategy():
+    class Flaky:
+        def __init__(self):
+            self.bars = []
+            self.done = threading.Event()
+
+        def on_bar(self, bar):
+            self.bars.append(bar.close)
+            if len(self.bars) == 2:
+                self.done.set()
+            if len(self.bars) == 1:
+                raise ValueError("boom")
+
+    strategy = Flaky()
+    with MessageBus() as bus:
+        bus.register_strategy(strategy)
+        bus.publish_bar(bar(1.0))
+        bus.publish_bar(bar(2.0))
+        assert strategy.done.wait(TIMEOUT)
+    assert strategy.bars == [1.0, 2.0]
+
+
+def test_shutdown_releases_the_strategy_and_rejects_publishing():
+    strategy = BuyOnceStrategy(None)
+    before = sys.getrefcount(strategy)
+    bus = MessageBus()
+    bus.register_strategy(strategy)
+    assert sys.getrefcount(strategy) == before + 1
+    bus.shutdown()
+    assert sys.getrefcount(strategy) == before
+    with pytest.raises(RuntimeError):
+        bus.publish_bar(bar(1.0))
+
+
+def test_invalid_order_side_is_rejected():
+    with pytest.raises(ValueError):
+        OrderRequest("BTC-USD", "hold", 1.0, 100.0)
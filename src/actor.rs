@@ -2,10 +2,35 @@
 
 //! # Actor 模块
 //!
-//! 定义了系统中所有独立组件（Actor）的通用生命周期 trait。
+//! 定义了系统中所有独立组件（Actor）的通用生命周期 trait 及其运行上下文 `ActorContext`，
+//! 以及由异步闭包直接构造简单 Actor 的 `FnActor` / `FnActor2`，
+//! 和按 Actor 名称记录处理数量与耗时的 `ActorMetrics`。
 
-use std::sync::Arc;
+use crate::bus::{BusError, MessageBus, Receiver};
+use crate::message::Message;
+use futures::future::{join_all, BoxFuture};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{oneshot, watch};
 use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+/// ## `Affinity`
+///
+/// Actor 希望在哪个运行时上运行的提示，由 `ActorSystem` 在启动时采纳。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Affinity {
+    /// 与大多数 Actor 共享默认的运行时（数据、日志等批量工作）。
+    #[default]
+    Shared,
+    /// 运行在专用的运行时线程上，不会被共享运行时上的 CPU 密集型 Actor 抢占（执行、风控等延迟敏感工作）。
+    Dedicated,
+}
 
 /// ## `Actor` Trait
 ///
@@ -15,5 +40,450 @@ pub trait Actor: Send + Sync {
     /// 启动 Actor 的主逻辑。
     /// Actor 应该在 `start` 方法内部订阅它所需的消息。
     /// 返回一个 `JoinHandle` 向量，以便主程序可以等待其完成。
-    async fn start(self: Arc<Self>) -> Vec<JoinHandle<()>>;
-}
\ No newline at end of file
+    /// `actor_name` 是编排者为 Actor 取的名称，派生任务时交给 `spawn_named`，使任务在 `tokio-console` 中可辨认。
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>>;
+
+    /// ## `run`
+    ///
+    /// 以单个 future 运行 Actor 的全部循环，由编排者（例如 `ActorSystem`）决定在哪里派生与如何监管。
+    /// 实现应在完成订阅后调用 `ctx.ready()`，并在 `ctx.shutdown_requested()` 完成时退出。
+    ///
+    /// 默认实现是基于 `start` 的兼容层：调用 `start` 后即视为就绪，等待它派生的任务全部结束；
+    /// 收到停止信号或 future 被丢弃时中止这些任务。
+    /// 只实现了 `run` 的 Actor 可以用 `spawn_run` 实现 `start`。
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        let mut tasks = AbortOnDrop(self.start(ctx.name()).await);
+        ctx.ready();
+        tokio::select! {
+            _ = ctx.shutdown_requested() => {},
+            _ = join_all(tasks.0.iter_mut()) => {},
+        }
+    }
+
+    /// 运行时亲和性提示，默认为 `Affinity::Shared`。
+    /// 直接调用 `start` 时任务总是运行在调用方的运行时上，只有 `ActorSystem` 会采纳此提示。
+    fn affinity(&self) -> Affinity {
+        Affinity::Shared
+    }
+}
+
+/// 被丢弃时中止所有任务，使 `run` 的兼容层被中止时不会留下 `start` 派生的任务。
+struct AbortOnDrop(Vec<JoinHandle<()>>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        for handle in &self.0 {
+            handle.abort();
+        }
+    }
+}
+
+/// ## `ShutdownToken`
+///
+/// 通知 Actor 停止的信号。克隆共享同一个信号：任意一个克隆调用 `shutdown` 后，
+/// 所有克隆的 `cancelled` 都会完成，之后调用 `cancelled` 也会立即完成。
+///
+/// `child` 派生的信号在自身或任一上级发出信号时都算已停止，编排者借此既能逐个停止 Actor，也能一次停止全部。
+#[derive(Clone)]
+pub struct ShutdownToken {
+    signal: Arc<watch::Sender<bool>>,
+    parent: Option<Arc<ShutdownToken>>,
+}
+
+impl ShutdownToken {
+    pub fn new() -> Self {
+        Self { signal: Arc::new(watch::Sender::new(false)), parent: None }
+    }
+
+    /// 派生一个子信号：它的 `shutdown` 只影响自身（及其子信号），`self` 发出信号时它也随之停止。
+    pub fn child(&self) -> ShutdownToken {
+        Self { signal: Arc::new(watch::Sender::new(false)), parent: Some(Arc::new(self.clone())) }
+    }
+
+    /// 发出停止信号。
+    pub fn shutdown(&self) {
+        self.signal.send_replace(true);
+    }
+
+    /// 是否已发出停止信号（自身或任一上级）。
+    pub fn is_shutdown(&self) -> bool {
+        *self.signal.borrow() || self.parent.as_ref().is_some_and(|parent| parent.is_shutdown())
+    }
+
+    /// 等待停止信号（自身或任一上级）。取消安全。
+    pub async fn cancelled(&self) {
+        let mut rx = self.signal.subscribe();
+        // 发送端由自身持有，不会关闭
+        let own = rx.wait_for(|stopped| *stopped);
+        match &self.parent {
+            Some(parent) => {
+                tokio::select! {
+                    _ = own => {},
+                    _ = Box::pin(parent.cancelled()) => {},
+                }
+            },
+            None => {
+                let _ = own.await;
+            },
+        }
+    }
+}
+
+impl Default for ShutdownToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// ## `ActorContext`
+///
+/// 编排者交给 `Actor::run` 的运行上下文：所在系统的总线、Actor 的名称与停止信号。
+///
+/// 通过 `ActorContext::subscribe` 订阅的 `Receiver` 会被登记，编排者在重启或停止 Actor 后
+/// 据此检查它们是否都已被丢弃（见 `ActorSystemHandle::restart`）。
+pub struct ActorContext {
+    bus: MessageBus,
+    name: Arc<str>,
+    shutdown: ShutdownToken,
+    /// `ready` 时通知编排者，只通知一次。
+    ready: Mutex<Option<oneshot::Sender<()>>>,
+    /// `subscribe` 发放的订阅，编排者持有一份克隆。
+    receivers: ReceiverLeases,
+}
+
+/// `ActorContext::subscribe` 发放的订阅：消息类型名与随 `Receiver` 一起释放的租约。
+#[derive(Clone, Default)]
+pub(crate) struct ReceiverLeases(Arc<Mutex<Vec<Lease>>>);
+
+/// 消息类型名与租约的弱引用，`Receiver` 被丢弃后弱引用失效。
+type Lease = (&'static str, Weak<()>);
+
+impl ReceiverLeases {
+    fn lease(&self, type_name: &'static str) -> Arc<()> {
+        let lease = Arc::new(());
+        let mut leases = self.0.lock().unwrap();
+        leases.retain(|(_, weak)| weak.strong_count() > 0);
+        leases.push((type_name, Arc::downgrade(&lease)));
+        lease
+    }
+
+    /// 仍未被丢弃的订阅的消息类型名，按发放顺序排列。
+    pub(crate) fn live(&self) -> Vec<&'static str> {
+        let mut leases = self.0.lock().unwrap();
+        leases.retain(|(_, weak)| weak.strong_count() > 0);
+        leases.iter().map(|(type_name, _)| *type_name).collect()
+    }
+}
+
+impl ActorContext {
+    /// 创建带有独立停止信号的上下文。
+    pub fn new(bus: MessageBus, name: &str) -> Self {
+        Self {
+            bus,
+            name: Arc::from(name),
+            shutdown: ShutdownToken::new(),
+            ready: Mutex::new(None),
+            receivers: ReceiverLeases::default(),
+        }
+    }
+
+    /// 使用共享的停止信号，例如整个系统共用一个。
+    pub fn with_shutdown(mut self, shutdown: ShutdownToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// Actor 所在系统的总线。
+    pub fn bus(&self) -> &MessageBus {
+        &self.bus
+    }
+
+    /// 编排者为 Actor 登记的名称。
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 停止信号。
+    pub fn shutdown_token(&self) -> &ShutdownToken {
+        &self.shutdown
+    }
+
+    /// 等待停止信号，通常作为 `tokio::select!` 的一个分支。取消安全。
+    pub async fn shutdown_requested(&self) {
+        self.shutdown.cancelled().await
+    }
+
+    /// 在总线上订阅 `M`，并登记返回的 `Receiver`。
+    /// Actor 应通过它而不是 `bus().subscribe` 订阅，编排者才能发现重启或停止后仍未释放的订阅。
+    pub async fn subscribe<M: Message>(&self) -> Receiver<M> {
+        let lease = self.receivers.lease(std::any::type_name::<M>());
+        self.bus.subscribe::<M>().await.with_lease(lease)
+    }
+
+    /// 通过 `subscribe` 订阅、且仍未被丢弃的 `Receiver` 的消息类型名。
+    pub fn live_receivers(&self) -> Vec<&'static str> {
+        self.receivers.live()
+    }
+
+    /// 与编排者共享的订阅登记。
+    pub(crate) fn receiver_leases(&self) -> ReceiverLeases {
+        self.receivers.clone()
+    }
+
+    /// 告知编排者订阅已完成。重复调用无效果。
+    pub fn ready(&self) {
+        if let Some(tx) = self.ready.lock().unwrap().take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+/// ## `spawn_run`
+///
+/// 在 `runtime` 上派生 `actor.run(ctx)`，等到它调用 `ActorContext::ready`（或提前结束）后返回任务句柄，
+/// 因此返回时 Actor 已完成订阅。只实现了 `run` 的 Actor 可以这样实现 `start`：
+///
+/// ```ignore
+/// async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+///     let ctx = ActorContext::new(self.bus.clone(), actor_name);
+///     vec![spawn_run(self, ctx, &Handle::current()).await]
+/// }
+/// ```
+pub async fn spawn_run(actor: Arc<dyn Actor>, ctx: ActorContext, runtime: &Handle) -> JoinHandle<()> {
+    let (tx, rx) = oneshot::channel();
+    *ctx.ready.lock().unwrap() = Some(tx);
+    let name = ctx.name.clone();
+    let handle = spawn_named_on(&name, actor.run(ctx), runtime);
+    // run 未调用 ready 就结束时发送端被丢弃，同样返回
+    let _ = rx.await;
+    handle
+}
+
+/// ## `spawn_named`
+///
+/// 在当前运行时上派生属于 `actor_name` 的任务，用于代替 `Actor::start` 中的 `tokio::spawn`：
+/// - 任务运行在 `actor` span（字段 `name` 为 Actor 名称）中，任务内的日志与新建的 span 都挂在它下面。
+///   `tracing` 的 span 名称必须是编译期常量，无法按 Actor 生成 `actor.{actor_name}` 这样的名称，
+///   因此名称固定为 `actor`，按 Actor 区分时过滤字段 `name`（例如 `RUST_LOG="[actor{name=STRATEGY}]=debug"`）；
+/// - 以 `RUSTFLAGS="--cfg tokio_unstable"` 构建时通过 `tokio::task::Builder` 以 Actor 名称命名任务，
+///   `tokio-console` 按名称列出每个 Actor 的任务（需启用 `console` feature 注册采集层）。
+pub fn spawn_named<F>(actor_name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    spawn_named_on(actor_name, future, &Handle::current())
+}
+
+/// `spawn_named` 派生到指定运行时的版本。
+fn spawn_named_on<F>(actor_name: &str, future: F, runtime: &Handle) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let future = future.instrument(tracing::info_span!("actor", name = actor_name));
+    #[cfg(tokio_unstable)]
+    return tokio::task::Builder::new().name(actor_name).spawn_on(future, runtime).expect("failed to spawn actor task");
+    #[cfg(not(tokio_unstable))]
+    runtime.spawn(future)
+}
+
+/// 订阅一种消息并在以 Actor 名称命名的后台任务中逐条调用处理函数；由 `start` 调用，返回时订阅已完成。
+type StartFn = Box<dyn Fn(MessageBus, Arc<str>) -> BoxFuture<'static, Vec<JoinHandle<()>>> + Send + Sync>;
+
+/// 订阅 `M`，派生一个属于 `actor_name` 的任务按到达顺序对每条消息调用 `handler`。
+/// 落后时记录警告后继续，总线关闭时任务结束。
+async fn spawn_handler<M, F>(bus: MessageBus, handler: Arc<F>, actor_name: Arc<str>) -> JoinHandle<()>
+where
+    M: Message,
+    F: Fn(M) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+{
+    let mut rx = bus.subscribe::<M>().await;
+    let name = actor_name.clone();
+    spawn_named(&name, async move {
+        loop {
+            match rx.recv_traced().await {
+                Ok(traced) => traced.handle(&actor_name, |msg| handler(msg)).await,
+                Err(RecvError::Lagged(n)) => tracing::warn!(target: "ACTOR", "{}", BusError::lagged::<M>(n)),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    })
+}
+
+/// ## `FnActor`
+///
+/// 由一个异步闭包构成的 Actor：订阅消息类型 `M`，对每条消息调用处理函数。
+/// 适合日志、监控等不需要自己状态结构体的观察者。
+///
+/// ```ignore
+/// let logger = FnActor::new::<Bar>(bus.clone(), |bar| Box::pin(async move { info!("Bar: {}", bar.close); }));
+/// logger.start("MONITOR").await;
+/// ```
+pub struct FnActor {
+    bus: MessageBus,
+    start: StartFn,
+}
+
+impl FnActor {
+    /// 创建订阅 `M` 的 Actor，`handler` 按消息到达顺序依次执行。
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<M: Message>(
+        bus: MessageBus,
+        handler: impl Fn(M) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    ) -> Arc<dyn Actor> {
+        let handler = Arc::new(handler);
+        let start: StartFn = Box::new(move |bus, actor_name| {
+            let handler = handler.clone();
+            Box::pin(async move { vec![spawn_handler::<M, _>(bus, handler, actor_name).await] })
+        });
+        Arc::new(Self { bus, start })
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for FnActor {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        (self.start)(self.bus.clone(), Arc::from(actor_name)).await
+    }
+}
+
+/// ## `FnActor2`
+///
+/// 订阅两种消息类型的 `FnActor`。两个处理函数运行在各自的任务中，互不阻塞。
+pub struct FnActor2 {
+    bus: MessageBus,
+    start: StartFn,
+}
+
+impl FnActor2 {
+    /// 创建订阅 `M1` 与 `M2` 的 Actor，分别交给 `handler1` 与 `handler2` 处理。
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<M1: Message, M2: Message>(
+        bus: MessageBus,
+        handler1: impl Fn(M1) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+        handler2: impl Fn(M2) -> BoxFuture<'static, ()> + Send + Sync + 'static,
+    ) -> Arc<dyn Actor> {
+        let handler1 = Arc::new(handler1);
+        let handler2 = Arc::new(handler2);
+        let start: StartFn = Box::new(move |bus, actor_name| {
+            let (handler1, handler2) = (handler1.clone(), handler2.clone());
+            Box::pin(async move {
+                vec![
+                    spawn_handler::<M1, _>(bus.clone(), handler1, actor_name.clone()).await,
+                    spawn_handler::<M2, _>(bus, handler2, actor_name).await,
+                ]
+            })
+        });
+        Arc::new(Self { bus, start })
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for FnActor2 {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        (self.start)(self.bus.clone(), Arc::from(actor_name)).await
+    }
+}
+
+/// 处理耗时直方图各个桶的上界（纳秒）：1µs 到 1s，每档相差 10 倍。
+pub const HANDLER_LATENCY_BUCKETS: [u64; 7] = [1_000, 10_000, 100_000, 1_000_000, 10_000_000, 100_000_000, 1_000_000_000];
+
+/// 单个 Actor 的计数器，记录时只做原子加法。
+#[derive(Default)]
+struct HandlerCounters {
+    processed: AtomicU64,
+    total_nanos: AtomicU64,
+    /// 每个桶只计入落在该桶内的次数，快照时再累加。
+    buckets: [AtomicU64; HANDLER_LATENCY_BUCKETS.len()],
+}
+
+/// ## `HandlerStats`
+///
+/// 一个 Actor 的处理统计快照。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HandlerStats {
+    /// 已处理的消息数。
+    pub processed: u64,
+    /// 处理耗时之和（纳秒）。
+    pub total_nanos: u64,
+    /// 耗时不超过 `HANDLER_LATENCY_BUCKETS` 对应上界的消息数（累计值，与 Prometheus 的 `le` 桶一致）。
+    pub buckets: [u64; HANDLER_LATENCY_BUCKETS.len()],
+}
+
+/// ## `ActorMetrics`
+///
+/// 按 Actor 名称记录处理的消息数与每条消息的处理耗时。克隆得到的实例共享同一份记录，
+/// 每条 `MessageBus`（及其所有视图）都带有一份，通过 `MessageBus::actor_metrics` 取得。
+/// Actor 第一次记录时才会出现，名称应为固定的少数几个（例如与 tracing target 相同），以限制导出的标签数量。
+///
+/// ```ignore
+/// let _timer = self.bus.actor_metrics().start_timer("STRATEGY");
+/// // 处理消息，计时器被丢弃时记录耗时
+/// ```
+#[derive(Clone, Default)]
+pub struct ActorMetrics {
+    actors: Arc<RwLock<HashMap<&'static str, Arc<HandlerCounters>>>>,
+}
+
+impl ActorMetrics {
+    /// 记录 `actor` 处理了一条消息，耗时 `elapsed`。
+    pub fn record(&self, actor: &'static str, elapsed: Duration) {
+        let counters = self.counters(actor);
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        counters.processed.fetch_add(1, Ordering::Relaxed);
+        counters.total_nanos.fetch_add(nanos, Ordering::Relaxed);
+        if let Some(bucket) = HANDLER_LATENCY_BUCKETS.iter().position(|upper| nanos <= *upper) {
+            counters.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 开始为 `actor` 计时，返回的计时器被丢弃时记录一条消息及其耗时。
+    pub fn start_timer(&self, actor: &'static str) -> HandlerTimer {
+        HandlerTimer { metrics: self.clone(), actor, started: Instant::now() }
+    }
+
+    /// 所有已记录过的 Actor 的统计，按名称排序。
+    pub fn snapshot(&self) -> Vec<(&'static str, HandlerStats)> {
+        let actors = self.actors.read().unwrap();
+        let mut stats: Vec<_> = actors
+            .iter()
+            .map(|(name, counters)| {
+                let mut buckets = [0; HANDLER_LATENCY_BUCKETS.len()];
+                let mut cumulative = 0;
+                for (bucket, count) in buckets.iter_mut().zip(&counters.buckets) {
+                    cumulative += count.load(Ordering::Relaxed);
+                    *bucket = cumulative;
+                }
+                let stats = HandlerStats {
+                    processed: counters.processed.load(Ordering::Relaxed),
+                    total_nanos: counters.total_nanos.load(Ordering::Relaxed),
+                    buckets,
+                };
+                (*name, stats)
+            })
+            .collect();
+        stats.sort_by_key(|(name, _)| *name);
+        stats
+    }
+
+    fn counters(&self, actor: &'static str) -> Arc<HandlerCounters> {
+        if let Some(counters) = self.actors.read().unwrap().get(actor) {
+            return counters.clone();
+        }
+        self.actors.write().unwrap().entry(actor).or_default().clone()
+    }
+}
+
+/// `ActorMetrics::start_timer` 返回的计时器，被丢弃时记录耗时。
+pub struct HandlerTimer {
+    metrics: ActorMetrics,
+    actor: &'static str,
+    started: Instant,
+}
+
+impl Drop for HandlerTimer {
+    fn drop(&mut self) {
+        self.metrics.record(self.actor, self.started.elapsed());
+    }
+}
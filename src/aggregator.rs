@@ -0,0 +1,219 @@
+// src/aggregator.rs
+
+//! # 聚合模块 (aggregator)
+//!
+//! 把逐笔成交 `TradeTick` 按固定时间窗口聚合成 `Bar`，同一份成交流可以同时产出多个周期。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::clock::{Clock, LiveClock};
+use crate::message::{Bar, TradeTick};
+use crate::startup::StartupBarrierHandle;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// 每个周期任务的成交队列容量。
+const TRADE_QUEUE_CAPACITY: usize = 1024;
+
+/// ## `TimeWindowAggregator`
+///
+/// 单个周期的聚合状态。窗口按 `ts_event` 对齐到周期的整数倍，
+/// 产出的 `Bar` 以窗口结束时间作为 `ts_event`。
+#[derive(Debug)]
+pub struct TimeWindowAggregator {
+    symbol: String,
+    timeframe: Duration,
+    /// 正在累积的 `Bar` 及其窗口开始时间。
+    current: Option<(u64, Bar)>,
+    /// 最近一个已完成窗口的结束时间，早于它的成交视为迟到。
+    closed_until: u64,
+}
+
+impl TimeWindowAggregator {
+    pub fn new(symbol: &str, timeframe: Duration) -> Self {
+        Self { symbol: symbol.to_string(), timeframe, current: None, closed_until: 0 }
+    }
+
+    pub fn timeframe(&self) -> Duration {
+        self.timeframe
+    }
+
+    /// 当前窗口的结束时间；还没有成交时返回 `None`。
+    pub fn window_end(&self) -> Option<u64> {
+        self.current.as_ref().map(|(start, _)| start + self.timeframe_nanos())
+    }
+
+    /// 加入一笔成交。成交落入新窗口时返回上一个已完成的 `Bar`。
+    /// 早于已完成窗口的迟到成交被忽略。
+    pub fn on_trade(&mut self, trade: &TradeTick) -> Option<Bar> {
+        if trade.ts_event < self.closed_until {
+            tracing::warn!(
+                target: "AGGREGATOR",
+                "Ignoring late trade for {} at {} ({:?} window already closed)",
+                trade.symbol,
+                trade.ts_event,
+                self.timeframe
+            );
+            return None;
+        }
+        let start = trade.ts_event - trade.ts_event % self.timeframe_nanos();
+        match &mut self.current {
+            Some((current_start, bar)) if *current_start == start => {
+                bar.high = bar.high.max(trade.price);
+                bar.low = bar.low.min(trade.price);
+                bar.close = trade.price;
+                bar.volume += trade.size;
+                None
+            }
+            // `closed_until` 保证新窗口不会早于当前窗口
+            _ => {
+                let completed = self.close_current();
+                self.current = Some((start, self.open_bar(start, trade)));
+                completed
+            }
+        }
+    }
+
+    /// 时钟到达 `now` 时，若当前窗口已经结束则返回它的 `Bar`。
+    pub fn flush_until(&mut self, now: u64) -> Option<Bar> {
+        match self.window_end() {
+            Some(end) if now >= end => self.close_current(),
+            _ => None,
+        }
+    }
+
+    fn close_current(&mut self) -> Option<Bar> {
+        let (start, bar) = self.current.take()?;
+        self.closed_until = start + self.timeframe_nanos();
+        Some(bar)
+    }
+
+    fn open_bar(&self, start: u64, trade: &TradeTick) -> Bar {
+        Bar {
+            id: Uuid::new_v4(),
+            ts_event: start + self.timeframe_nanos(),
+            symbol: self.symbol.clone(),
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.size,
+            timeframe: self.timeframe,
+        }
+    }
+
+    fn timeframe_nanos(&self) -> u64 {
+        self.timeframe.as_nanos() as u64
+    }
+}
+
+/// ## `TickAggregator`
+///
+/// 一个 Actor，订阅某个 symbol 的 `TradeTick`，为每个周期维护一个 `TimeWindowAggregator`，
+/// 并发布带 `timeframe` 的 `Bar`。窗口在下一笔成交到来或时钟越过窗口结束时关闭。
+pub struct TickAggregator {
+    bus: MessageBus,
+    symbol: String,
+    timeframes: Vec<Duration>,
+    clock: Arc<dyn Clock>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl TickAggregator {
+    pub fn new(bus: MessageBus, symbol: &str, timeframes: &[Duration]) -> Self {
+        let timeframes = timeframes
+            .iter()
+            .copied()
+            .filter(|timeframe| {
+                let valid = !timeframe.is_zero();
+                if !valid {
+                    tracing::warn!(target: "AGGREGATOR", "Ignoring zero timeframe for {}", symbol);
+                }
+                valid
+            })
+            .collect();
+        Self {
+            bus,
+            symbol: symbol.to_string(),
+            timeframes,
+            clock: Arc::new(LiveClock),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 设置判断窗口结束所用的时钟，默认 `LiveClock`。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置启动屏障，完成订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 单个周期的聚合循环：处理成交，并在时钟越过窗口结束时主动关闭窗口。
+    async fn run_timeframe(self: Arc<Self>, mut aggregator: TimeWindowAggregator, mut rx: mpsc::Receiver<TradeTick>) {
+        loop {
+            let deadline = aggregator.window_end();
+            let completed = tokio::select! {
+                trade = rx.recv() => match trade {
+                    Some(trade) => aggregator.on_trade(&trade),
+                    None => break,
+                },
+                _ = self.clock.sleep_until(deadline.unwrap_or_default()), if deadline.is_some() => {
+                    aggregator.flush_until(self.clock.now_nanos())
+                }
+            };
+            if let Some(bar) = completed {
+                if let Err(e) = self.bus.publish(bar).await {
+                    tracing::error!(target: "AGGREGATOR", "Failed to publish bar: {}", e);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for TickAggregator {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut trade_rx = self.bus.subscribe::<TradeTick>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let mut handles = Vec::new();
+        let mut senders = Vec::new();
+        for &timeframe in &self.timeframes {
+            let (tx, rx) = mpsc::channel(TRADE_QUEUE_CAPACITY);
+            senders.push(tx);
+            let aggregator = TimeWindowAggregator::new(&self.symbol, timeframe);
+            handles.push(spawn_named(actor_name, self.clone().run_timeframe(aggregator, rx)));
+        }
+
+        // 一个订阅循环把本 symbol 的成交分发给各周期任务
+        let self_clone = self.clone();
+        handles.push(spawn_named(actor_name, async move {
+            loop {
+                match trade_rx.recv().await {
+                    Ok(trade) => {
+                        if trade.symbol != self_clone.symbol {
+                            continue;
+                        }
+                        for tx in &senders {
+                            let _ = tx.send(trade.clone()).await;
+                        }
+                    }
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "AGGREGATOR", "{}", BusError::lagged::<TradeTick>(n)),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        }));
+        handles
+    }
+}
@@ -0,0 +1,150 @@
+// src/bin/replay.rs
+
+//! # 日志回放工具 (replay)
+//!
+//! 把 `EventExporter` 写出的事件日志按类型与时间范围筛选后重新发布：
+//!
+//! - `--target local`（默认）：发布到本地构造的 `MessageBus`，其上运行与演示程序相同的组合、撮合、预热与策略，
+//!   用于在录制的数据上重新运行策略。此时通常只回放行情，例如 `--types Bar`，
+//!   否则录制的订单与成交会与重新产生的重复。
+//! - `--target tcp://host:port`：连接到该地址，以 JSON lines 发送选中的日志行。
+//!
+//! ```bash
+//! cargo run --bin replay -- events.jsonl --types Bar --speed 10x
+//! ```
+
+use clap::Parser;
+use message_bus::actor::Actor;
+use message_bus::bus::MessageBus;
+use message_bus::config::{DEFAULT_BUS_CAPACITY, DEFAULT_SYMBOL};
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::portfolio::PortfolioTracker;
+use message_bus::replay::{
+    register_journal_types, JournalReader, ReplayError, ReplayFilter, ReplaySpeed, ReplayStats, ReplayTarget, Replayer,
+    TcpSink,
+};
+use message_bus::strategy::SimpleTrendFollower;
+use message_bus::warmup::WarmupGuard;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+use tracing_subscriber::{fmt, EnvFilter};
+
+/// 策略开始交易前需要看到的 `Bar` 数量，与演示程序相同。
+const WARMUP_BARS: usize = 3;
+
+/// 本地回放结束后，总线在这段时间内没有新的发布即视为处理完毕。
+const SETTLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// ## `ReplayCli`
+///
+/// 回放工具的命令行参数。未给出 `--types` 时回放全部类型，未给出 `--from` / `--to` 时不限制时间范围。
+#[derive(Debug, Parser)]
+#[command(name = "replay", version, about = "回放录制的事件日志", long_about = None)]
+struct ReplayCli {
+    /// EventExporter 写出的日志文件，同目录下轮转出的分段先于它回放
+    #[arg(value_name = "PATH")]
+    journal: PathBuf,
+
+    /// 要回放的消息类型，以逗号分隔，例如 Bar,FillEvent [默认: 全部]
+    #[arg(long, value_name = "TYPES", value_delimiter = ',')]
+    types: Vec<String>,
+
+    /// 只回放 ts_event 不早于该时间戳（纳秒）的事件
+    #[arg(long, value_name = "TS")]
+    from: Option<u64>,
+
+    /// 只回放 ts_event 不晚于该时间戳（纳秒）的事件
+    #[arg(long, value_name = "TS")]
+    to: Option<u64>,
+
+    /// 回放节奏：realtime、unthrottled，或 10x 这样的加速倍数
+    #[arg(long, value_name = "SPEED", default_value = "unthrottled")]
+    speed: ReplaySpeed,
+
+    /// 发布目标：local 或 tcp://host:port
+    #[arg(long, value_name = "TARGET", default_value = "local")]
+    target: ReplayTarget,
+
+    /// 本地策略交易的 symbol，多个 symbol 时重复给出 [默认: BTC-USD]
+    #[arg(long = "symbol", value_name = "SYMBOL")]
+    symbols: Vec<String>,
+
+    /// 本地总线上每个消息通道的容量
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_BUS_CAPACITY)]
+    bus_capacity: usize,
+
+    /// 日志过滤规则，例如 info 或 info,REPLAY=debug
+    #[arg(long, value_name = "LEVEL", default_value = "info")]
+    log_level: String,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = ReplayCli::parse();
+    fmt::Subscriber::builder().with_env_filter(EnvFilter::new(&cli.log_level)).with_target(true).init();
+
+    let result = match JournalReader::open(&cli.journal) {
+        Ok(reader) => {
+            info!(target: "REPLAY", "Replaying {} journal segments to {:?}", reader.segments().len(), cli.target);
+            let filter = ReplayFilter { types: cli.types.iter().cloned().collect(), from: cli.from, to: cli.to };
+            let replayer = Replayer::new(reader).with_filter(filter).with_speed(cli.speed);
+            match &cli.target {
+                ReplayTarget::Local => replay_locally(replayer, &cli).await,
+                ReplayTarget::Tcp(addr) => match TcpSink::connect(addr).await {
+                    Ok(mut sink) => replayer.run(&mut sink).await,
+                    Err(e) => Err(e),
+                },
+            }
+        },
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        tracing::error!(target: "REPLAY", "Replay failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// 在本地总线上启动标准 Actor 后回放，等待级联消息处理完毕再输出组合盈亏。
+async fn replay_locally(replayer: Replayer, cli: &ReplayCli) -> Result<ReplayStats, ReplayError> {
+    let mut bus = MessageBus::new(cli.bus_capacity);
+    register_journal_types(&bus);
+
+    let symbols = if cli.symbols.is_empty() { vec![DEFAULT_SYMBOL.to_string()] } else { cli.symbols.clone() };
+    let portfolio = Arc::new(PortfolioTracker::new(bus.clone()));
+    let mut actors: Vec<(String, Arc<dyn Actor>)> = vec![
+        ("PORTFOLIO".to_string(), portfolio.clone()),
+        ("EXECUTION".to_string(), Arc::new(SimulatedExecutionEngine::new(bus.clone()))),
+    ];
+    for symbol in &symbols {
+        actors.push((format!("WARMUP.{}", symbol), WarmupGuard::new(bus.clone(), symbol, WARMUP_BARS)));
+        actors.push((format!("STRATEGY.{}", symbol), Arc::new(SimpleTrendFollower::new(bus.clone(), symbol.clone()))));
+    }
+    let mut handles = Vec::new();
+    for (name, actor) in actors {
+        handles.extend(actor.start(&name).await);
+    }
+
+    let stats = replayer.run(&mut bus).await;
+
+    let mut published = bus.publish_count();
+    loop {
+        tokio::time::sleep(SETTLE_INTERVAL).await;
+        let now = bus.publish_count();
+        if now == published {
+            break;
+        }
+        published = now;
+    }
+    for handle in &handles {
+        handle.abort();
+    }
+    info!(
+        target: "REPLAY",
+        "Net PnL {:.4} (commission paid {:.4})",
+        portfolio.net_pnl(),
+        portfolio.total_commission()
+    );
+    stats
+}
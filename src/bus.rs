@@ -5,12 +5,898 @@
 //! 提供了整个系统的核心通信中枢 `MessageBus`。
 //! 这是一个高性能、类型安全的异步发布/订阅实现。
 
-use crate::message::Message;
+use crate::actor::ActorMetrics;
+use crate::clock::{Clock, LiveClock};
+use crate::message::{HasCorrelationId, HasId, Message, SystemEvent, Timestamped};
+use crate::store::{BusState, ChannelState, Envelope, MessageStore, SharedStore};
+use crate::sync::RwLock;
+use crate::topic::{DynamicTopic, TopicRegistry};
+use crate::trace::{TraceContext, Traced};
+use futures::stream::{BoxStream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// ## `BusError`
+///
+/// 总线操作失败时返回的错误。每个变体都携带定位问题所需的上下文（消息类型名、名称等），
+/// 由底层错误引起的变体通过 `source()` 返回该错误。
+#[derive(Debug)]
+pub enum BusError {
+    /// 类型擦除的通道收到了与其类型不符的消息。这表示总线内部状态损坏。
+    TypeMismatch { expected_type: &'static str, actual_type: &'static str },
+    /// 恢复或重放时遇到未通过 `with_message_store` 登记的消息类型。
+    UnknownType { type_name: String },
+    /// 名称未通过 `register_message` 登记。
+    UnknownTopic { name: String },
+    /// 无法把缓存的消息或 `publish_json` 的输入反序列化为 `type_name`。
+    Deserialize { type_name: &'static str, source: serde_json::Error },
+    /// 无法把 `type_name` 的消息序列化为 JSON。
+    Serialize { type_name: &'static str, source: serde_json::Error },
+    /// 在异步上下文中调用了 `blocking_publish` / `blocking_subscribe`。
+    BlockingInAsyncContext,
+    /// 调用阻塞接口时总线没有可用的运行时句柄。
+    NoRuntime,
+    /// `type_name` 的订阅者已达到 `with_max_subscribers` 设置的上限。
+    SubscriberLimit { type_name: &'static str, limit: usize },
+    /// `try_publish` 调用时通道表正被写入（例如某个类型的第一次订阅），无法不等待地投递。
+    ChannelsBusy,
+    /// 当前命名空间还没有 `type_name` 的通道（从未被订阅过）。
+    ChannelNotFound { type_name: &'static str },
+    /// `type_alias` 会让 `alias` 与 `canonical` 的发布互相转发（两者相同，或 `alias` 的发布已经会到达 `canonical`）。
+    CircularAlias { alias: &'static str, canonical: &'static str },
+    /// `type_name` 的接收者落后，`count` 条消息在被读取之前已被覆盖；之后仍可继续接收。
+    Lagged { type_name: &'static str, count: u64 },
+    /// `type_name` 的熔断自 `since` 起处于打开状态（见 `with_circuit_breaker`），发布被拒绝。
+    CircuitOpen { type_name: &'static str, since: Instant },
+}
+
+impl fmt::Display for BusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BusError::TypeMismatch { expected_type, actual_type } => {
+                write!(f, "channel for {} received a message of type {}", expected_type, actual_type)
+            },
+            BusError::UnknownType { type_name } => {
+                write!(f, "message type {} is not registered with the message store", type_name)
+            },
+            BusError::UnknownTopic { name } => write!(f, "no message type registered under the name {:?}", name),
+            BusError::Deserialize { type_name, source } => write!(f, "failed to deserialize {}: {}", type_name, source),
+            BusError::Serialize { type_name, source } => write!(f, "failed to serialize {}: {}", type_name, source),
+            BusError::BlockingInAsyncContext => {
+                write!(f, "MessageBus blocking API called from within an async context; use the async methods instead")
+            },
+            BusError::NoRuntime => {
+                write!(f, "MessageBus has no runtime handle; create it inside a tokio runtime or call `with_runtime`")
+            },
+            BusError::SubscriberLimit { type_name, limit } => {
+                write!(f, "{} already has the maximum of {} subscribers", type_name, limit)
+            },
+            BusError::ChannelsBusy => write!(f, "MessageBus channel table is being modified; retry or use `publish`"),
+            BusError::ChannelNotFound { type_name } => write!(f, "no channel for {} in this namespace", type_name),
+            BusError::CircularAlias { alias, canonical } => {
+                write!(f, "aliasing {} to {} would relay messages in a cycle", alias, canonical)
+            },
+            BusError::Lagged { type_name, count } => write!(f, "{} receiver lagged by {} messages", type_name, count),
+            BusError::CircuitOpen { type_name, since } => {
+                write!(f, "circuit for {} has been open for {:?}", type_name, since.elapsed())
+            },
+        }
+    }
+}
+
+impl Error for BusError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BusError::Deserialize { source, .. } | BusError::Serialize { source, .. } => Some(source),
+            BusError::TypeMismatch { .. }
+            | BusError::UnknownType { .. }
+            | BusError::UnknownTopic { .. }
+            | BusError::BlockingInAsyncContext
+            | BusError::NoRuntime
+            | BusError::SubscriberLimit { .. }
+            | BusError::ChannelsBusy
+            | BusError::ChannelNotFound { .. }
+            | BusError::CircularAlias { .. }
+            | BusError::Lagged { .. }
+            | BusError::CircuitOpen { .. } => None,
+        }
+    }
+}
+
+impl BusError {
+    /// 把 JSON 解析错误包装为 `M` 的 `Deserialize` 错误。
+    pub(crate) fn deserialize<M>(source: serde_json::Error) -> Self {
+        BusError::Deserialize { type_name: std::any::type_name::<M>(), source }
+    }
+
+    /// `M` 的接收者落后 `count` 条消息。
+    pub fn lagged<M>(count: u64) -> Self {
+        BusError::Lagged { type_name: std::any::type_name::<M>(), count }
+    }
+}
+
+/// ## `PublishTarget` Trait
+///
+/// 可以接收某种消息 `M` 的发布目标。
+/// `MessageBus` 是单一目标，`MulticastGroup` 则会把消息分发给多条总线。
+/// 每个目标总线的结果单独返回，部分失败不会影响其他目标。
+#[async_trait::async_trait]
+pub trait PublishTarget<M: Message>: Send + Sync {
+    async fn publish(&self, msg: M) -> Vec<Result<usize, BusError>>;
+
+    /// 发布时会收到消息的订阅者总数，为 0 时发布者可以跳过构造消息的开销。
+    async fn subscriber_count(&self) -> usize;
+
+    /// 丢弃每个目标中已缓冲的 `M`（见 `MessageBus::purge`），返回每个目标的结果。
+    async fn purge(&self) -> Vec<Result<usize, BusError>>;
+}
+
+#[async_trait::async_trait]
+impl<M: Message> PublishTarget<M> for MessageBus {
+    async fn publish(&self, msg: M) -> Vec<Result<usize, BusError>> {
+        vec![MessageBus::publish(self, msg).await]
+    }
+
+    async fn subscriber_count(&self) -> usize {
+        MessageBus::subscriber_count::<M>(self).await
+    }
+
+    async fn purge(&self) -> Vec<Result<usize, BusError>> {
+        vec![MessageBus::purge::<M>(self).await]
+    }
+}
+
+/// ## `MessageBusTrait`
+///
+/// Actor 所需的总线接口：发布、订阅与订阅者计数。`MessageBus` 与 `ShardedMessageBus` 都实现了它，
+/// 以它为泛型参数编写的 Actor 可以在两者之间切换而无需修改。
+#[async_trait::async_trait]
+pub trait MessageBusTrait: Clone + Send + Sync + 'static {
+    /// 发布一条消息，返回收到消息的订阅者数量，语义同 `MessageBus::publish`。
+    async fn publish<M: Message>(&self, msg: M) -> Result<usize, BusError>;
+
+    /// 订阅一种消息类型，语义同 `MessageBus::subscribe`。
+    async fn subscribe<M: Message>(&self) -> Receiver<M>;
+
+    /// 发布 `M` 时会收到消息的订阅者数量，语义同 `MessageBus::subscriber_count`。
+    async fn subscriber_count<M: Message>(&self) -> usize;
+}
+
+#[async_trait::async_trait]
+impl MessageBusTrait for MessageBus {
+    async fn publish<M: Message>(&self, msg: M) -> Result<usize, BusError> {
+        MessageBus::publish(self, msg).await
+    }
+
+    async fn subscribe<M: Message>(&self) -> Receiver<M> {
+        MessageBus::subscribe::<M>(self).await
+    }
+
+    async fn subscriber_count<M: Message>(&self) -> usize {
+        MessageBus::subscriber_count::<M>(self).await
+    }
+}
+
+/// ## `Receiver`
+///
+/// `subscribe` 返回的订阅端，用法与 `broadcast::Receiver` 相同。
+/// 通道中传递的是带追踪上下文的 `Traced<M>`：`recv` 只返回消息本身，
+/// `recv_traced` 同时返回上下文，用于在 `handle` span 中处理消息（见 `trace` 模块）。
+/// `recv_fresh` 在此基础上丢弃超过有效期的消息。
+pub struct Receiver<M> {
+    inner: broadcast::Receiver<Traced<M>>,
+    /// 总线的时钟，`recv_fresh` 以它判断消息是否过期。
+    clock: Arc<dyn Clock>,
+    /// `recv_fresh` 丢弃的过期消息数。
+    expired: u64,
+    /// `ActorContext::subscribe` 发放的租约，随 `Receiver` 一起被丢弃，编排者据此发现未释放的订阅。
+    lease: Option<Arc<()>>,
+    /// 所在通道的清除水位，序号低于它的消息被跳过（见 `MessageBus::purge`）。
+    purge: Arc<PurgeMark>,
+}
+
+impl<M> fmt::Debug for Receiver<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver").field("len", &self.inner.len()).field("expired", &self.expired).finish()
+    }
+}
+
+impl<M: Message> Receiver<M> {
+    /// 附上租约，`Receiver` 被丢弃时租约随之释放。
+    pub(crate) fn with_lease(mut self, lease: Arc<()>) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    /// 接收下一条消息。取消安全。
+    pub async fn recv(&mut self) -> Result<M, broadcast::error::RecvError> {
+        self.recv_traced().await.map(|traced| traced.message)
+    }
+
+    /// 接收下一条消息及其追踪上下文。取消安全。
+    pub async fn recv_traced(&mut self) -> Result<Traced<M>, broadcast::error::RecvError> {
+        loop {
+            let traced = self.inner.recv().await?;
+            if !self.purge.is_purged(traced.seq) {
+                return Ok(traced);
+            }
+        }
+    }
+
+    /// 接收下一条未过期的消息及其追踪上下文。取消安全。
+    ///
+    /// 带有有效期的消息在 `ts_recv + ttl` 早于总线时钟的当前时间时被丢弃并计入 `expired_count`，
+    /// 落后的消费者因此不会按早已过时的行情行动；没有有效期的消息总是返回。
+    pub async fn recv_fresh(&mut self) -> Result<Traced<M>, broadcast::error::RecvError> {
+        loop {
+            let traced = self.recv_traced().await?;
+            if !traced.is_expired(self.clock.now_nanos()) {
+                return Ok(traced);
+            }
+            self.expired += 1;
+            tracing::debug!(
+                target: "BUS",
+                "Dropped expired {} received at {}",
+                std::any::type_name::<M>(),
+                traced.ts_recv
+            );
+        }
+    }
+
+    /// `recv_fresh` 丢弃的过期消息数。
+    pub fn expired_count(&self) -> u64 {
+        self.expired
+    }
+
+    /// 非阻塞地接收一条消息。
+    pub fn try_recv(&mut self) -> Result<M, broadcast::error::TryRecvError> {
+        loop {
+            let traced = self.inner.try_recv()?;
+            if !self.purge.is_purged(traced.seq) {
+                return Ok(traced.message);
+            }
+        }
+    }
+
+    /// 在同步代码中阻塞地接收一条消息，不能在异步上下文中调用。
+    pub fn blocking_recv(&mut self) -> Result<M, broadcast::error::RecvError> {
+        loop {
+            let traced = self.inner.blocking_recv()?;
+            if !self.purge.is_purged(traced.seq) {
+                return Ok(traced.message);
+            }
+        }
+    }
+
+    /// 通道中尚未被该订阅者读取的消息数，包括已被 `purge` 清除、读取时会跳过的消息。
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// 是否没有尚未读取的消息。
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+/// `ReceiverExt::recv_timeout` 的错误。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvTimeout {
+    /// `type_name` 的接收者落后，跳过了 `count` 条消息；之后仍可继续接收。
+    Lagged { type_name: &'static str, count: u64 },
+    /// 所有发送端都已关闭。
+    Closed,
+    /// 在给定时间内没有收到消息。
+    Timeout,
+}
+
+impl fmt::Display for RecvTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeout::Lagged { type_name, count } => {
+                BusError::Lagged { type_name, count: *count }.fmt(f)
+            },
+            RecvTimeout::Closed => write!(f, "channel closed"),
+            RecvTimeout::Timeout => write!(f, "timed out waiting for a message"),
+        }
+    }
+}
+
+impl Error for RecvTimeout {}
+
+impl RecvTimeout {
+    /// 落后时返回对应的 `BusError::Lagged`，关闭与超时不是总线错误，返回 `None`。
+    pub fn into_bus_error(self) -> Option<BusError> {
+        match self {
+            RecvTimeout::Lagged { type_name, count } => Some(BusError::Lagged { type_name, count }),
+            RecvTimeout::Closed | RecvTimeout::Timeout => None,
+        }
+    }
+}
+
+/// `MessageBus::publish_and_await_reply` 的错误。
+#[derive(Debug)]
+pub enum RequestError {
+    /// 请求发布失败。
+    Publish(BusError),
+    /// 无法订阅回复类型，例如其订阅者已达到 `with_max_subscribers` 的上限。
+    Subscribe(BusError),
+    /// 在给定时间内没有收到关联的回复。
+    Timeout(Duration),
+    /// 回复通道已关闭。
+    Closed,
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Publish(e) => write!(f, "failed to publish request: {}", e),
+            RequestError::Subscribe(e) => write!(f, "failed to subscribe to replies: {}", e),
+            RequestError::Timeout(timeout) => write!(f, "no reply within {:?}", timeout),
+            RequestError::Closed => write!(f, "reply channel closed"),
+        }
+    }
+}
+
+impl Error for RequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RequestError::Publish(e) | RequestError::Subscribe(e) => Some(e),
+            RequestError::Timeout(_) | RequestError::Closed => None,
+        }
+    }
+}
+
+/// ## `ReceiverExt` Trait
+///
+/// 订阅者的扩展方法，便于 Actor 实现“处理消息，或每隔一段时间做一次清理”的循环，
+/// 而不必每次手写 `select!` 加定时器。
+#[async_trait::async_trait]
+pub trait ReceiverExt<M> {
+    /// 等待下一条消息，最多等待 `dur`。
+    async fn recv_timeout(&mut self, dur: Duration) -> Result<M, RecvTimeout>;
+
+    /// 非阻塞地丢弃当前缓冲的所有消息，返回丢弃的数量（包括因滞后而被跳过的消息）。
+    /// 暂停后恢复的 Actor 可以用它跳过过期数据，而不是逐条处理积压。
+    fn drain_backlog(&mut self) -> usize;
+}
+
+/// 各订阅端 `recv_timeout` 的公共实现：最多等待 `dur`，把 `recv` 的错误转换为 `RecvTimeout`。
+async fn recv_within<M>(
+    dur: Duration,
+    recv: impl std::future::Future<Output = Result<M, broadcast::error::RecvError>>,
+) -> Result<M, RecvTimeout> {
+    match tokio::time::timeout(dur, recv).await {
+        Ok(Ok(msg)) => Ok(msg),
+        Ok(Err(broadcast::error::RecvError::Lagged(count))) => {
+            Err(RecvTimeout::Lagged { type_name: std::any::type_name::<M>(), count })
+        },
+        Ok(Err(broadcast::error::RecvError::Closed)) => Err(RecvTimeout::Closed),
+        Err(_) => Err(RecvTimeout::Timeout),
+    }
+}
+
+/// 各订阅端 `drain_backlog` 的公共实现：反复调用 `try_recv` 直到通道为空或关闭，
+/// 返回丢弃的消息数（包括因滞后而被跳过的消息）。
+fn drain_with<M>(mut try_recv: impl FnMut() -> Result<M, broadcast::error::TryRecvError>) -> usize {
+    let mut drained = 0;
+    loop {
+        match try_recv() {
+            Ok(_) => drained += 1,
+            Err(broadcast::error::TryRecvError::Lagged(n)) => drained += n as usize,
+            Err(broadcast::error::TryRecvError::Empty | broadcast::error::TryRecvError::Closed) => return drained,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message> ReceiverExt<M> for Receiver<M> {
+    async fn recv_timeout(&mut self, dur: Duration) -> Result<M, RecvTimeout> {
+        recv_within(dur, self.recv()).await
+    }
+
+    fn drain_backlog(&mut self) -> usize {
+        drain_with(|| self.try_recv())
+    }
+}
+
+/// ## `SampledReceiver`
+///
+/// `subscribe_sampled` 返回的订阅端：只交付每 `every_n` 条消息中的第 `every_n` 条，其余的计数后丢弃。
+/// 被丢弃的消息在 `recv` 内部读掉，不会返回给调用者；落后（`Lagged`）照常返回，跳过的消息不参与计数。
+pub struct SampledReceiver<M> {
+    inner: Receiver<M>,
+    every_n: u64,
+    /// 自上一次交付以来读到的消息数。
+    since_last: u64,
+    /// 被抽样丢弃的消息数。
+    discarded: u64,
+}
+
+impl<M> fmt::Debug for SampledReceiver<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SampledReceiver").field("every_n", &self.every_n).field("discarded", &self.discarded).finish()
+    }
+}
+
+impl<M: Message> SampledReceiver<M> {
+    /// 接收下一条被抽中的消息。取消安全。
+    pub async fn recv(&mut self) -> Result<M, broadcast::error::RecvError> {
+        loop {
+            let msg = self.inner.recv().await?;
+            self.since_last += 1;
+            if self.since_last == self.every_n {
+                self.since_last = 0;
+                return Ok(msg);
+            }
+            self.discarded += 1;
+        }
+    }
+
+    /// 被抽样丢弃的消息数。
+    pub fn discarded_count(&self) -> u64 {
+        self.discarded
+    }
+
+    /// 转换为被抽中消息的流。落后时跳过丢失的消息并记录警告，通道关闭时流结束。
+    pub fn into_stream(self) -> BoxStream<'static, M> {
+        futures::stream::unfold(self, |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(msg) => return Some((msg, rx)),
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(target: "BUS", "Sampled subscriber of {} lagged by {}", std::any::type_name::<M>(), n)
+                    },
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+/// ## `GroupPolicy`
+///
+/// `subscribe_group` 中同一组成员之间分配消息的方式。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GroupPolicy {
+    /// 每个成员都收到每条消息，与 `subscribe` 相同。
+    #[default]
+    Broadcast,
+    /// 每条消息只交给组内的一个成员，按加入顺序轮流分配。
+    RoundRobin,
+}
+
+/// 轮询组的成员：每个成员一个 `mpsc::Sender`，由组的转发任务轮流写入。
+type GroupMembers<M> = Arc<std::sync::Mutex<Vec<mpsc::Sender<M>>>>;
+
+/// 轮询组的键：（消息类型，命名空间，组名）。
+type GroupKey = (TypeId, Arc<str>, String);
+
+/// 登记在总线上的一个轮询组：成员列表（`GroupMembers<M>`，按类型擦除），以及成员共同持有的转发任务。
+struct RoundRobinGroup {
+    members: Box<dyn Any + Send>,
+    relay: std::sync::Weak<GroupRelay>,
+}
+
+/// 所有轮询组。
+type GroupTable = HashMap<GroupKey, RoundRobinGroup>;
+
+/// 一个运行中的轮询组转发任务，由组内所有成员的 `GroupReceiver` 共同持有。
+/// 最后一个成员被丢弃时中止任务（释放它对 `M` 的订阅）并注销该组，之后加入同名组的成员会重新创建它。
+#[derive(Debug)]
+struct GroupRelay {
+    key: GroupKey,
+    groups: std::sync::Weak<std::sync::Mutex<GroupTable>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for GroupRelay {
+    fn drop(&mut self) {
+        self.task.abort();
+        let Some(groups) = self.groups.upgrade() else { return };
+        let mut groups = groups.lock().unwrap();
+        // 同名的组可能已被重新创建，只移除转发任务已经失效的登记
+        if groups.get(&self.key).is_some_and(|group| group.relay.strong_count() == 0) {
+            groups.remove(&self.key);
+        }
+    }
+}
+
+/// 有序订阅的成员，按优先级从高到低排列，同一优先级按订阅顺序排列。
+type OrderedMembers<M> = Arc<std::sync::Mutex<Vec<OrderedMember<M>>>>;
+
+/// 有序订阅的键：（消息类型，命名空间）。
+type OrderedKey = (TypeId, Arc<str>);
+
+/// 交给有序订阅者的消息及其处理完成的确认。
+type OrderedDelivery<M> = (M, oneshot::Sender<()>);
+
+/// `type_alias` 登记的转发：（命名空间，原类型）→（别名类型 → 转发任务）。
+type AliasGraph = HashMap<(Arc<str>, TypeId), HashMap<TypeId, std::sync::Weak<AliasRelay>>>;
+
+/// 一个运行中的别名转发任务。最后一个 `TypeAlias` 被丢弃时中止任务（释放它对原类型的订阅）并注销转发。
+#[derive(Debug)]
+struct AliasRelay {
+    key: (Arc<str>, TypeId),
+    alias: TypeId,
+    aliases: std::sync::Weak<std::sync::Mutex<AliasGraph>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for AliasRelay {
+    fn drop(&mut self) {
+        self.task.abort();
+        let Some(aliases) = self.aliases.upgrade() else { return };
+        let mut aliases = aliases.lock().unwrap();
+        if let Some(targets) = aliases.get_mut(&self.key) {
+            // 同一对类型可能已被重新登记，只移除已经失效的登记
+            if targets.get(&self.alias).is_some_and(|relay| relay.strong_count() == 0) {
+                targets.remove(&self.alias);
+            }
+            if targets.is_empty() {
+                aliases.remove(&self.key);
+            }
+        }
+    }
+}
+
+/// ## `TypeAlias`
+///
+/// `type_alias` 返回的登记句柄。同一对类型的所有句柄共享一个转发任务，
+/// 最后一个句柄被丢弃时转发停止：任务被中止，不再计为原类型的订阅者，之后可以重新登记。
+#[must_use = "the alias is removed as soon as the handle is dropped"]
+#[derive(Clone, Debug)]
+pub struct TypeAlias {
+    _relay: Arc<AliasRelay>,
+}
+
+/// ## `GroupReceiver`
+///
+/// `subscribe_group` 返回的订阅端。`Broadcast` 组的成员是普通的 `Receiver`；
+/// `RoundRobin` 组的成员从组的转发任务接收分配给自己的消息，落后只发生在转发任务上（记录警告），成员不会收到 `Lagged`。
+/// `RoundRobin` 组的成员同时持有组的转发任务，组内最后一个成员被丢弃时转发停止，组被注销。
+pub struct GroupReceiver<M> {
+    inner: GroupInner<M>,
+}
+
+enum GroupInner<M> {
+    Broadcast(Receiver<M>),
+    /// `_relay` 只为让转发任务随最后一个成员一起停止。
+    RoundRobin { rx: mpsc::Receiver<M>, _relay: Arc<GroupRelay> },
+}
+
+impl<M> fmt::Debug for GroupReceiver<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupReceiver").field("policy", &self.policy()).finish()
+    }
+}
+
+impl<M> GroupReceiver<M> {
+    /// 所在组的分配方式。
+    pub fn policy(&self) -> GroupPolicy {
+        match self.inner {
+            GroupInner::Broadcast(_) => GroupPolicy::Broadcast,
+            GroupInner::RoundRobin { .. } => GroupPolicy::RoundRobin,
+        }
+    }
+}
+
+impl<M: Message> GroupReceiver<M> {
+    /// 接收下一条分配给该成员的消息。取消安全。
+    pub async fn recv(&mut self) -> Result<M, broadcast::error::RecvError> {
+        match &mut self.inner {
+            GroupInner::Broadcast(rx) => rx.recv().await,
+            GroupInner::RoundRobin { rx, .. } => rx.recv().await.ok_or(broadcast::error::RecvError::Closed),
+        }
+    }
+
+    /// 非阻塞地接收一条消息。
+    pub fn try_recv(&mut self) -> Result<M, broadcast::error::TryRecvError> {
+        match &mut self.inner {
+            GroupInner::Broadcast(rx) => rx.try_recv(),
+            GroupInner::RoundRobin { rx, .. } => rx.try_recv().map_err(|e| match e {
+                mpsc::error::TryRecvError::Empty => broadcast::error::TryRecvError::Empty,
+                mpsc::error::TryRecvError::Disconnected => broadcast::error::TryRecvError::Closed,
+            }),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message> ReceiverExt<M> for GroupReceiver<M> {
+    async fn recv_timeout(&mut self, dur: Duration) -> Result<M, RecvTimeout> {
+        recv_within(dur, self.recv()).await
+    }
+
+    fn drain_backlog(&mut self) -> usize {
+        drain_with(|| self.try_recv())
+    }
+}
+
+/// 查找仍在运行的轮询组，返回它的成员列表与转发任务。最后一个成员刚被丢弃、正在注销的组视为不存在。
+fn lookup_group<M: Message>(groups: &GroupTable, key: &GroupKey) -> Option<(GroupMembers<M>, Arc<GroupRelay>)> {
+    let group = groups.get(key)?;
+    let members = group.members.downcast_ref::<GroupMembers<M>>()?.clone();
+    Some((members, group.relay.upgrade()?))
+}
+
+/// 轮询组的转发任务：从总线读取 `M`，依次交给组内的下一个成员；已离开的成员被移除，
+/// 消息改交给再下一个成员。组内暂时没有成员时消息被丢弃。总线通道关闭时移除全部成员，成员的 `recv` 随之返回 `Closed`。
+async fn relay_round_robin<M: Message>(mut rx: Receiver<M>, members: GroupMembers<M>, group_id: String) {
+    let mut next = 0;
+    loop {
+        let mut msg = match rx.recv().await {
+            Ok(msg) => msg,
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                tracing::warn!(target: "BUS", "Group {} of {} lagged by {}", group_id, std::any::type_name::<M>(), n);
+                continue;
+            },
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        loop {
+            let member = {
+                let members = members.lock().unwrap();
+                if members.is_empty() {
+                    None
+                } else {
+                    next %= members.len();
+                    Some(members[next].clone())
+                }
+            };
+            let Some(member) = member else {
+                tracing::debug!(target: "BUS", "Group {} has no members, dropped {}", group_id, std::any::type_name::<M>());
+                break;
+            };
+            match member.send(msg).await {
+                Ok(()) => {
+                    next += 1;
+                    break;
+                },
+                Err(mpsc::error::SendError(returned)) => {
+                    msg = returned;
+                    members.lock().unwrap().retain(|sender| !sender.same_channel(&member));
+                },
+            }
+        }
+    }
+    members.lock().unwrap().clear();
+}
+
+/// 有序订阅的一个成员。
+struct OrderedMember<M> {
+    priority: i32,
+    sender: mpsc::Sender<OrderedDelivery<M>>,
+}
+
+impl<M> Clone for OrderedMember<M> {
+    fn clone(&self) -> Self {
+        Self { priority: self.priority, sender: self.sender.clone() }
+    }
+}
+
+/// ## `OrderedReceiver`
+///
+/// `subscribe_ordered` 返回的订阅端。每条消息在所有优先级更高的有序订阅者确认之后才会到达。
+/// 确认表示“处理完了上一条消息”：再次调用 `recv` / `try_recv`、调用 `ack` 或丢弃接收端时发出。
+/// 落后只发生在协调任务上（记录警告），成员不会收到 `Lagged`。
+///
+/// 有序订阅不返回 `SubscriptionHandle`：后者直接读取总线通道，既不经过协调任务，也没有发出确认的途径，
+/// 无法保证先于低优先级订阅者处理；因此有序订阅也不支持 `replay_from_store`。
+pub struct OrderedReceiver<M> {
+    rx: mpsc::Receiver<OrderedDelivery<M>>,
+    /// 最近收到的消息的确认，尚未发出。
+    pending: Option<oneshot::Sender<()>>,
+    priority: i32,
+}
+
+impl<M> fmt::Debug for OrderedReceiver<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrderedReceiver").field("priority", &self.priority).field("pending", &self.pending.is_some()).finish()
+    }
+}
+
+impl<M> OrderedReceiver<M> {
+    /// 订阅时指定的优先级。
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    /// 确认最近收到的消息已处理完，优先级更低的订阅者随即收到它。没有待确认的消息时什么也不做。
+    pub fn ack(&mut self) {
+        if let Some(ack) = self.pending.take() {
+            let _ = ack.send(());
+        }
+    }
+}
+
+impl<M: Message> OrderedReceiver<M> {
+    /// 确认上一条消息，接收下一条。取消安全。
+    pub async fn recv(&mut self) -> Result<M, broadcast::error::RecvError> {
+        self.ack();
+        let (msg, ack) = self.rx.recv().await.ok_or(broadcast::error::RecvError::Closed)?;
+        self.pending = Some(ack);
+        Ok(msg)
+    }
+
+    /// 确认上一条消息，非阻塞地接收下一条。
+    pub fn try_recv(&mut self) -> Result<M, broadcast::error::TryRecvError> {
+        self.ack();
+        let (msg, ack) = self.rx.try_recv().map_err(|e| match e {
+            mpsc::error::TryRecvError::Empty => broadcast::error::TryRecvError::Empty,
+            mpsc::error::TryRecvError::Disconnected => broadcast::error::TryRecvError::Closed,
+        })?;
+        self.pending = Some(ack);
+        Ok(msg)
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message> ReceiverExt<M> for OrderedReceiver<M> {
+    async fn recv_timeout(&mut self, dur: Duration) -> Result<M, RecvTimeout> {
+        recv_within(dur, self.recv()).await
+    }
+
+    fn drain_backlog(&mut self) -> usize {
+        let drained = drain_with(|| self.try_recv());
+        self.ack();
+        drained
+    }
+}
+
+/// `type_alias` 的转发任务：把 `Canonical` 转换为 `Alias` 后重新发布，直到被 `TypeAlias` 中止或通道关闭。
+async fn relay_alias<Alias, Canonical>(mut rx: Receiver<Canonical>, bus: MessageBus)
+where
+    Alias: Message + From<Canonical>,
+    Canonical: Message,
+{
+    loop {
+        let msg = match rx.recv().await {
+            Ok(msg) => msg,
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                tracing::warn!(target: "BUS", "Alias relay to {}: {}", std::any::type_name::<Alias>(), BusError::lagged::<Canonical>(n));
+                continue;
+            },
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        if let Err(e) = bus.publish(Alias::from(msg)).await {
+            tracing::error!(target: "BUS", "Alias relay failed to publish {}: {}", std::any::type_name::<Alias>(), e);
+        }
+    }
+}
+
+/// 有序订阅的协调任务：从总线读取 `M`，按优先级从高到低逐级交给成员，
+/// 同一优先级的成员同时收到，全部确认之后才交给下一级；最低一级也确认之后才处理下一条消息。
+/// 已离开的成员被移除，其确认视为已发出。总线通道关闭时移除全部成员，成员的 `recv` 随之返回 `Closed`。
+async fn relay_ordered<M: Message>(mut rx: Receiver<M>, members: OrderedMembers<M>) {
+    loop {
+        let msg = match rx.recv().await {
+            Ok(msg) => msg,
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+                tracing::warn!(target: "BUS", "Ordered subscribers of {} lagged by {}", std::any::type_name::<M>(), n);
+                continue;
+            },
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let snapshot = members.lock().unwrap().clone();
+        for level in snapshot.chunk_by(|a, b| a.priority == b.priority) {
+            let mut acks = Vec::with_capacity(level.len());
+            for member in level {
+                let (ack_tx, ack_rx) = oneshot::channel();
+                if member.sender.send((msg.clone(), ack_tx)).await.is_err() {
+                    members.lock().unwrap().retain(|m| !m.sender.same_channel(&member.sender));
+                    continue;
+                }
+                acks.push(ack_rx);
+            }
+            for ack in acks {
+                // 接收端被丢弃时确认随之失效，同样视为已处理
+                let _ = ack.await;
+            }
+        }
+    }
+    members.lock().unwrap().clear();
+}
+
+/// ## `PublishGuard`
+///
+/// 离开作用域时发布一条消息的守卫，与 `scopeguard` 类似但限定为总线消息：
+/// 即使函数因错误提前返回或任务被中止，消息也会被发布，适合 `ActorStopped` 之类的生命周期事件。
+///
+/// ```ignore
+/// let _guard = PublishGuard::new(bus.clone(), ActorStopped { actor_name: actor_name.to_string() });
+/// ```
+///
+/// 被丢弃时通过 `try_publish` 同步发布；通道表恰好正被写入时改为在总线的运行时上异步发布。
+/// 调用 `cancel` 后不再发布。
+#[must_use = "the message is published as soon as the guard is dropped"]
+pub struct PublishGuard<M: Message> {
+    bus: MessageBus,
+    msg: Option<M>,
+}
+
+impl<M: Message> PublishGuard<M> {
+    pub fn new(bus: MessageBus, msg: M) -> PublishGuard<M> {
+        Self { bus, msg: Some(msg) }
+    }
+
+    /// 取消发布，返回守卫持有的消息。
+    pub fn cancel(mut self) -> M {
+        self.msg.take().expect("message is only taken by cancel or drop")
+    }
+}
+
+impl<M: Message> fmt::Debug for PublishGuard<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PublishGuard").field("msg", &self.msg).finish()
+    }
+}
+
+impl<M: Message> Drop for PublishGuard<M> {
+    fn drop(&mut self) {
+        let Some(msg) = self.msg.take() else { return };
+        match self.bus.try_publish(msg.clone()) {
+            Ok(_) => {},
+            Err(BusError::ChannelsBusy) => match Handle::try_current().ok().or_else(|| self.bus.runtime.clone()) {
+                Some(runtime) => {
+                    let bus = self.bus.clone();
+                    runtime.spawn(async move {
+                        if let Err(e) = bus.publish(msg).await {
+                            tracing::error!(target: "BUS", "PublishGuard failed to publish {}: {}", std::any::type_name::<M>(), e);
+                        }
+                    });
+                },
+                None => tracing::error!(target: "BUS", "PublishGuard dropped {}: {}", std::any::type_name::<M>(), BusError::ChannelsBusy),
+            },
+            Err(e) => tracing::error!(target: "BUS", "PublishGuard failed to publish {}: {}", std::any::type_name::<M>(), e),
+        }
+    }
+}
+
+/// ## `SubscriptionHandle`
+///
+/// 可以接收重放消息的订阅：除了总线上的实时消息，还有一条只属于该订阅者的回环通道，
+/// `MessageBus::replay_from_store` 把历史消息注入回环通道，不会影响其他订阅者。
+///
+/// `recv` 优先返回回环通道中的重放消息（`is_replay == true`），再返回实时消息。
+/// 实时消息的 `seq` 为 0，`namespace` 为订阅者所在的命名空间。
+pub struct SubscriptionHandle<M: Message> {
+    live: Receiver<M>,
+    loopback_tx: mpsc::UnboundedSender<Envelope<M>>,
+    loopback_rx: mpsc::UnboundedReceiver<Envelope<M>>,
+    namespace: Arc<str>,
+    /// 订阅时消息存储的下一个序号，序号更小的消息已无法从实时通道收到，需要重放。
+    live_from_seq: u64,
+}
+
+impl<M: Message> SubscriptionHandle<M> {
+    /// 接收下一条消息。取消安全。
+    pub async fn recv(&mut self) -> Result<Envelope<M>, broadcast::error::RecvError> {
+        if let Ok(envelope) = self.loopback_rx.try_recv() {
+            return Ok(envelope);
+        }
+        let Traced { message, trace, .. } = self.live.recv_traced().await?;
+        Ok(Envelope { seq: 0, namespace: self.namespace.to_string(), is_replay: false, message, trace: Some(trace) })
+    }
+
+    /// 重放消息是否来自该订阅者可见的命名空间（自身或子命名空间）。
+    fn sees(&self, namespace: &str) -> bool {
+        self.namespace.is_empty()
+            || namespace == &*self.namespace
+            || namespace.strip_prefix(&*self.namespace).is_some_and(|rest| rest.starts_with(NAMESPACE_SEPARATOR))
+    }
+}
 
 /// ## `AnyChannel` Trait
 ///
@@ -19,51 +905,735 @@ use tokio::sync::{broadcast, RwLock};
 trait AnyChannel: Send + Sync {
     /// 发送一个类型擦除的消息。
     /// 内部会尝试将 `&dyn Any` 向下转型回具体的 `M` 类型。
-    fn send_any(&self, msg: &dyn Any) -> Result<usize, Box<dyn Error + Send + Sync>>;
+    fn send_any(&self, msg: &dyn Any, actual_type: &'static str) -> Result<usize, BusError>;
     
     /// 创建一个新的订阅者，返回一个类型擦除的 `Receiver`。
     fn subscribe_any(&self) -> Box<dyn Any + Send>;
+
+    /// 与订阅者共享的清除水位。
+    fn purge_mark(&self) -> Arc<PurgeMark>;
+
+    /// 清除当前缓冲的所有消息，返回清除的数量。
+    fn purge(&self) -> usize;
+
+    /// 消息类型名，用于导出 `BusState`。
+    fn type_name(&self) -> &'static str;
+
+    /// 创建通道时使用的容量。
+    fn capacity(&self) -> usize;
+
+    /// 当前存活的订阅者数量。
+    fn receiver_count(&self) -> usize;
+
+    /// 通道中尚未被所有订阅者读取的消息数量。
+    fn buffered(&self) -> usize;
+
+    /// 通道已满且仍有订阅者：再发送一条会覆盖最旧的、尚有订阅者未读的消息。
+    fn is_full(&self) -> bool;
+
+    /// 尚未被所有订阅者读取的消息占通道实际槽位的百分比。
+    fn fill_pct(&self) -> f64;
+}
+
+/// 一个 broadcast 通道及其创建时的容量（`broadcast::Sender` 本身不提供容量）。
+struct Channel<M: Message> {
+    sender: broadcast::Sender<Traced<M>>,
+    capacity: usize,
+    purge: Arc<PurgeMark>,
+}
+
+/// 通道的发送序号与清除水位，由通道与它的所有 `Receiver` 共享。
+/// broadcast 通道无法从发送端删除已缓冲的消息，`purge` 改为提高水位，由各订阅者在读取时跳过水位以下的消息。
+#[derive(Default)]
+struct PurgeMark {
+    /// 下一条发送的消息的序号。
+    next_seq: AtomicU64,
+    /// 序号低于此值的消息已被清除。
+    purged_below: AtomicU64,
+}
+
+impl PurgeMark {
+    fn is_purged(&self, seq: u64) -> bool {
+        seq < self.purged_below.load(Ordering::Acquire)
+    }
+}
+
+/// ## `AnyChannel` 实现
+///
+/// 为泛型的 `Channel<M>` 实现 `AnyChannel` trait。
+impl<M: Message> AnyChannel for Channel<M> {
+    fn send_any(&self, msg: &dyn Any, actual_type: &'static str) -> Result<usize, BusError> {
+        // 1. 尝试将 `&dyn Any` 向下转型为 `&Traced<M>`
+        let concrete_msg = msg
+            .downcast_ref::<Traced<M>>()
+            .ok_or(BusError::TypeMismatch { expected_type: std::any::type_name::<M>(), actual_type })?;
+        
+        // 2. 发送带有本通道序号的克隆。如果没有任何订阅者，`send` 会返回 Err，
+        //    但在 Pub/Sub 模式中这不应被视为错误，所以我们忽略它。
+        let mut msg = concrete_msg.clone();
+        msg.seq = self.purge.next_seq.fetch_add(1, Ordering::Relaxed);
+        Ok(self.sender.send(msg).unwrap_or(0))
+    }
+
+    fn subscribe_any(&self) -> Box<dyn Any + Send> {
+        // 将强类型的 broadcast::Receiver 包装在 Box<dyn Any> 中返回
+        Box::new(self.sender.subscribe())
+    }
+
+    fn purge_mark(&self) -> Arc<PurgeMark> {
+        self.purge.clone()
+    }
+
+    fn purge(&self) -> usize {
+        let buffered = self.sender.len();
+        self.purge.purged_below.store(self.purge.next_seq.load(Ordering::Relaxed), Ordering::Release);
+        buffered
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn receiver_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    fn buffered(&self) -> usize {
+        self.sender.len()
+    }
+
+    fn is_full(&self) -> bool {
+        // broadcast 通道的实际槽位数是容量向上取整到 2 的幂
+        self.sender.receiver_count() > 0 && self.sender.len() >= self.capacity.next_power_of_two()
+    }
+
+    fn fill_pct(&self) -> f64 {
+        self.sender.len() as f64 / self.capacity.next_power_of_two() as f64 * 100.0
+    }
+}
+
+/// ## `ChannelStats`
+///
+/// 一种消息类型在总线上所有命名空间中的通道汇总。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChannelStats {
+    /// 该类型已创建通道的命名空间数量。
+    pub channels: usize,
+    /// 所有通道的存活订阅者总数。
+    pub subscribers: usize,
+    /// 所有通道中最大的容量。
+    pub capacity: usize,
+    /// 所有通道中尚未被所有订阅者读取的消息数之和。
+    pub buffered: usize,
+}
+
+/// ## `ChannelFill`
+///
+/// 一种消息类型的通道积压程度，由 `channel_fill_ratios` 返回。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChannelFill {
+    pub type_name: &'static str,
+    /// 积压最多的命名空间中，尚未被所有订阅者读取的消息占通道槽位的百分比（0–100）。
+    /// 达到 100 时再发布会让最慢的订阅者收到 `Lagged`。
+    pub fill_pct: f64,
+    /// 所有命名空间的订阅者总数。
+    pub receiver_count: usize,
+}
+
+/// ## `TypeMetrics`
+///
+/// 一种消息类型自总线创建以来的发布计数，所有命名空间合计。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TypeMetrics {
+    /// `publish` 的次数。
+    pub published: u64,
+    /// 没有任何订阅者收到的发布次数。
+    pub dropped: u64,
+    /// 发布时通道已满、覆盖了尚有订阅者未读的消息的次数，这些订阅者随后会收到 `Lagged`。
+    pub lagged: u64,
+}
+
+/// 一种消息类型的发布计数器。
+struct TypeCounters {
+    type_name: &'static str,
+    published: AtomicU64,
+    dropped: AtomicU64,
+    lagged: AtomicU64,
+}
+
+/// 一种消息类型的熔断状态（见 `MessageBus::with_circuit_breaker`）。
+#[derive(Clone)]
+struct CircuitBreaker {
+    /// 连续多少次发布写满通道后打开熔断。
+    threshold: u32,
+    /// 打开后拒绝发布的时长，之后放行一次试探发布。
+    cooldown: Duration,
+    /// 连续写满通道的发布次数。
+    consecutive_full: u32,
+    /// 熔断打开的时刻；冷却结束后保留，直到试探发布成功。
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    fn new(threshold: u32, cooldown: Duration) -> Self {
+        Self { threshold: threshold.max(1), cooldown, consecutive_full: 0, opened_at: None }
+    }
+
+    /// 熔断打开且仍在冷却中时返回打开的时刻。
+    fn open_since(&self, now: Instant) -> Option<Instant> {
+        self.opened_at.filter(|since| now.duration_since(*since) < self.cooldown)
+    }
+
+    /// 记录一次投递是否写满了通道。冷却结束后的试探发布再次写满时立即重新打开。
+    fn record(&mut self, full: bool, type_name: &'static str, now: Instant) {
+        if !full {
+            if self.opened_at.take().is_some() {
+                tracing::info!(target: "BUS", "Circuit for {} closed", type_name);
+            }
+            self.consecutive_full = 0;
+            return;
+        }
+        self.consecutive_full = self.consecutive_full.saturating_add(1);
+        if self.opened_at.is_some() || self.consecutive_full >= self.threshold {
+            tracing::warn!(
+                target: "BUS",
+                "Circuit for {} opened after {} publishes into a full channel",
+                type_name,
+                self.consecutive_full
+            );
+            self.opened_at = Some(now);
+        }
+    }
+}
+
+/// 所有视图共享的按类型熔断状态。
+type SharedCircuits = Arc<std::sync::Mutex<HashMap<TypeId, CircuitBreaker>>>;
+
+/// `publish_idempotent` 记住消息 id 的默认时长。
+pub const DEFAULT_DEDUP_TTL: Duration = Duration::from_secs(60);
+
+/// `publish_idempotent` 的去重缓存：按类型记录窗口内见过的 id 及其记录时间（纳秒）。
+/// 每种类型的 id 按记录时间先后排列，过期的 id 总是从队首淘汰。
+#[derive(Default)]
+struct DedupCache {
+    default_ttl: Duration,
+    ttls: HashMap<TypeId, Duration>,
+    seen: HashMap<TypeId, SeenIds>,
+}
+
+/// 一种类型在窗口内见过的 id，以及按记录时间排列的（记录时间，id）。
+type SeenIds = (HashSet<Uuid>, VecDeque<(u64, Uuid)>);
+
+impl DedupCache {
+    fn new(default_ttl: Duration) -> Self {
+        Self { default_ttl, ..Default::default() }
+    }
+
+    /// 只复制有效期配置，不复制已记录的 id。
+    fn config_copy(&self) -> Self {
+        Self { default_ttl: self.default_ttl, ttls: self.ttls.clone(), seen: HashMap::new() }
+    }
+
+    fn ttl(&self, type_id: &TypeId) -> Duration {
+        self.ttls.get(type_id).copied().unwrap_or(self.default_ttl)
+    }
+
+    /// 按各类型自己的有效期淘汰所有类型中过期的 id。
+    fn expire(&mut self, now: u64) {
+        let Self { default_ttl, ttls, seen } = self;
+        seen.retain(|type_id, (ids, order)| {
+            let ttl = ttls.get(type_id).copied().unwrap_or(*default_ttl).as_nanos() as u64;
+            while let Some(&(recorded, id)) = order.front() {
+                if now.saturating_sub(recorded) <= ttl {
+                    break;
+                }
+                order.pop_front();
+                ids.remove(&id);
+            }
+            !order.is_empty()
+        });
+    }
+
+    /// 记录 `M` 的一个 id。窗口内第一次出现时返回 `true`。
+    fn insert(&mut self, type_id: TypeId, id: Uuid, now: u64) -> bool {
+        self.expire(now);
+        if self.ttl(&type_id).is_zero() {
+            return true;
+        }
+        let (ids, order) = self.seen.entry(type_id).or_default();
+        if !ids.insert(id) {
+            return false;
+        }
+        order.push_back((now, id));
+        true
+    }
 }
 
-/// ## `AnyChannel` 实现
-///
-/// 为泛型的 `broadcast::Sender<M>` 实现 `AnyChannel` trait。
-impl<M: Message> AnyChannel for broadcast::Sender<M> {
-    fn send_any(&self, msg: &dyn Any) -> Result<usize, Box<dyn Error + Send + Sync>> {
-        // 1. 尝试将 `&dyn Any` 向下转型为 `&M`
-        let concrete_msg = msg.downcast_ref::<M>().ok_or("Type mismatch")?;
-        
-        // 2. 发送克隆的消息。如果没有任何订阅者，`send` 会返回 Err，
-        //    但在 Pub/Sub 模式中这不应被视为错误，所以我们忽略它。
-        Ok(self.send(concrete_msg.clone()).unwrap_or(0))
+/// 通道的键：消息的 `TypeId` 加上命名空间（根命名空间为空字符串）。
+type ChannelKey = (TypeId, Arc<str>);
+
+/// 命名空间的分隔符，例如 `"paper/btc"`。
+const NAMESPACE_SEPARATOR: char = '/';
+
+/// 通道容量的下限。`broadcast::channel(0)` 会 panic，因此更小的值会被提升到此值。
+pub const MIN_CHANNEL_CAPACITY: usize = 1;
+
+/// 通道容量的上限，与 `tokio::sync::broadcast` 允许的最大容量一致。
+pub const MAX_CHANNEL_CAPACITY: usize = usize::MAX >> 1;
+
+/// 单个通道的订阅者数量超过多少时记录警告的默认值，见 `with_receiver_warning_threshold`。
+pub const DEFAULT_RECEIVER_WARNING_THRESHOLD: usize = 64;
+
+/// 将容量限制在 `[MIN_CHANNEL_CAPACITY, MAX_CHANNEL_CAPACITY]` 内，超出范围时记录警告。
+fn clamp_capacity(capacity: usize) -> usize {
+    let clamped = capacity.clamp(MIN_CHANNEL_CAPACITY, MAX_CHANNEL_CAPACITY);
+    if clamped != capacity {
+        tracing::warn!(target: "BUS", "Channel capacity {} out of range, clamped to {}", capacity, clamped);
+    }
+    clamped
+}
+
+/// ## `MessageBus`
+///
+/// 系统的中央通信枢纽。
+#[derive(Clone)]
+pub struct MessageBus {
+    /// 核心数据结构：
+    /// Key: 消息的 `TypeId` 与命名空间。
+    /// Value: 一个类型擦除的 `broadcast::Sender`，包装在 `AnyChannel` trait object 中。
+    channels: Arc<RwLock<HashMap<ChannelKey, Box<dyn AnyChannel>>>>,
+    default_capacity: usize,
+    /// 按消息类型覆盖的通道容量。
+    capacity_overrides: HashMap<TypeId, usize>,
+    /// 按消息类型限制的订阅者数量（所有命名空间合计）。
+    max_subscribers: HashMap<TypeId, usize>,
+    /// 单个通道的订阅者数量达到此值（及其每次翻倍）时记录警告，0 表示不警告。
+    receiver_warning_threshold: usize,
+    /// 当前视图的命名空间，原始总线为空字符串。
+    namespace: Arc<str>,
+    /// 供 `blocking_*` 方法在同步代码中驱动异步操作的运行时句柄。
+    runtime: Option<Handle>,
+    /// 自创建以来 `publish` 被调用的总次数（所有视图共享）。
+    publish_count: Arc<AtomicU64>,
+    /// 最近消息的缓存，仅在调用 `with_message_store` 后存在（所有视图共享）。
+    store: Option<SharedStore>,
+    /// `restore_state` 恢复的、尚未创建的通道容量，键为（类型名，命名空间）。
+    restored_capacities: Arc<std::sync::Mutex<HashMap<(String, String), usize>>>,
+    /// `register_message` 登记的名称表（所有视图共享）。
+    topics: TopicRegistry,
+    /// 每次发布时收到消息副本的回调，仅由 `TestBus` 设置（所有视图共享）。
+    tap: Option<PublishTap>,
+    /// `on_new_type` 登记的回调（所有视图共享）。
+    new_type_hooks: Arc<std::sync::RwLock<Vec<NewTypeHook>>>,
+    /// `add_interceptor` 登记的发布拦截器（所有视图共享）。
+    interceptors: Arc<std::sync::RwLock<Vec<Arc<dyn PublishInterceptor>>>>,
+    /// 按消息类型的发布计数，在类型第一次被发布时创建（所有视图共享）。
+    type_counters: Arc<std::sync::RwLock<HashMap<TypeId, Arc<TypeCounters>>>>,
+    /// Actor 的处理统计（所有视图共享）。
+    actor_metrics: ActorMetrics,
+    /// 为每条消息打上 `ts_recv` 的时钟，`Receiver::recv_fresh` 以它判断过期（所有视图共享）。
+    clock: Arc<dyn Clock>,
+    /// 按消息类型的默认有效期，`publish` 的消息带上它。
+    ttl_defaults: HashMap<TypeId, Duration>,
+    /// `publish_idempotent` 的去重缓存与按类型的去重时长（所有视图共享）。
+    dedup: Arc<std::sync::Mutex<DedupCache>>,
+    /// `subscribe_group` 创建的轮询组（所有视图共享）。
+    groups: Arc<std::sync::Mutex<GroupTable>>,
+    /// `subscribe_ordered` 的成员，值为 `OrderedMembers<M>`（所有视图共享）。
+    ordered: Arc<std::sync::Mutex<HashMap<OrderedKey, Box<dyn Any + Send>>>>,
+    /// `type_alias` 登记的转发（所有视图共享）。
+    aliases: Arc<std::sync::Mutex<AliasGraph>>,
+    /// `with_circuit_breaker` 登记的熔断，仅在调用后存在（所有视图共享）。
+    circuits: Option<SharedCircuits>,
+}
+
+/// 消息类型第一次在总线上创建通道时调用的回调，参数为类型的 `TypeId` 与 `std::any::type_name`。
+pub type NewTypeHook = Arc<dyn Fn(TypeId, &'static str) + Send + Sync>;
+
+/// ## `Interception`
+///
+/// `PublishInterceptor` 对一次发布的处置。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Interception {
+    /// 正常投递。
+    Deliver,
+    /// 丢弃，订阅者收不到这条消息。
+    Drop,
+    /// 在后台等待给定时间后再投递，`publish` 立即返回。
+    Delay(Duration),
+    /// 连续投递两次。
+    Duplicate,
+}
+
+/// ## `PublishInterceptor` Trait
+///
+/// 在消息投递给订阅者之前决定如何处置它，例如注入故障（见 `chaos` 模块）。
+/// 拦截发生在消息存储与发布计数之后，因此被丢弃的消息仍然计为已发布；
+/// `restore_state` 与 `replay_from_store` 的重放不经过拦截器。
+pub trait PublishInterceptor: Send + Sync {
+    /// `type_name` 为 `std::any::type_name`，`namespace` 为发布所在的命名空间（根命名空间为空字符串）。
+    fn intercept(&self, type_id: TypeId, type_name: &'static str, namespace: &str) -> Interception;
+}
+
+/// 发布旁路保存的消息副本：可以向下转型，也可以在需要时再格式化。
+trait TappedMessage: Any + fmt::Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<M: Message> TappedMessage for M {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// ## `PublishedMessage`
+///
+/// 发布旁路收到的一条已发布消息（`testkit::TestBus` 以它记录发布历史）。
+/// 只有安装了旁路的总线才会在发布时创建它；消息本身只复制一次 `Arc`，`Debug` 输出在调用 `debug` 时才生成。
+#[derive(Clone)]
+pub struct PublishedMessage {
+    pub(crate) type_id: TypeId,
+    /// 不含模块路径的消息类型名。
+    pub type_name: &'static str,
+    /// 发布者所在的命名空间，根命名空间为空字符串。
+    pub namespace: String,
+    /// 记录时间（纳秒），由旁路的安装者补上，创建时为 0。
+    pub ts_nanos: u64,
+    message: Arc<dyn TappedMessage>,
+}
+
+impl PublishedMessage {
+    /// 复制一条正在发布的消息。
+    fn capture<M: Message>(msg: &M, namespace: &str) -> Self {
+        let full = std::any::type_name::<M>();
+        Self {
+            type_id: TypeId::of::<M>(),
+            type_name: full.rsplit("::").next().unwrap_or(full),
+            namespace: namespace.to_string(),
+            ts_nanos: 0,
+            message: Arc::new(msg.clone()),
+        }
+    }
+
+    /// 若这条消息的类型为 `M`，返回它的副本。
+    pub fn downcast<M: Message>(&self) -> Option<M> {
+        self.message.as_any().downcast_ref::<M>().cloned()
     }
 
-    fn subscribe_any(&self) -> Box<dyn Any + Send> {
-        // 将强类型的 Receiver 包装在 Box<dyn Any> 中返回
-        Box::new(self.subscribe())
+    /// 消息的 `Debug` 输出，用于失败信息。
+    pub fn debug(&self) -> String {
+        format!("{:?}", self.message)
     }
 }
 
-/// ## `MessageBus`
-///
-/// 系统的中央通信枢纽。
-#[derive(Clone)]
-pub struct MessageBus {
-    /// 核心数据结构：
-    /// Key: 消息的 `TypeId`。
-    /// Value: 一个类型擦除的 `broadcast::Sender`，包装在 `AnyChannel` trait object 中。
-    channels: Arc<RwLock<HashMap<TypeId, Box<dyn AnyChannel>>>>,
-    default_capacity: usize,
-}
+/// 接收每条已发布消息副本的回调。
+pub(crate) type PublishTap = Arc<dyn Fn(PublishedMessage) + Send + Sync>;
 
 impl MessageBus {
     /// 创建一个新的 `MessageBus` 实例。
     /// `default_capacity`: 为每种新消息类型创建的 broadcast 通道的容量。
+    /// 容量在构造时被限制到 `[MIN_CHANNEL_CAPACITY, MAX_CHANNEL_CAPACITY]`，
+    /// 因此 `MessageBus::new(0)` 会得到容量为 1 的通道，而不是在首次 `subscribe` 时 panic。
+    ///
+    /// 如果在 tokio 运行时内创建，会记录当前运行时的句柄，供 `blocking_*` 方法使用。
     pub fn new(default_capacity: usize) -> Self {
         Self {
             channels: Arc::new(RwLock::new(HashMap::new())),
-            default_capacity,
+            default_capacity: clamp_capacity(default_capacity),
+            capacity_overrides: HashMap::new(),
+            max_subscribers: HashMap::new(),
+            receiver_warning_threshold: DEFAULT_RECEIVER_WARNING_THRESHOLD,
+            namespace: Arc::from(""),
+            runtime: Handle::try_current().ok(),
+            publish_count: Arc::new(AtomicU64::new(0)),
+            store: None,
+            restored_capacities: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            topics: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            tap: None,
+            new_type_hooks: Arc::new(std::sync::RwLock::new(Vec::new())),
+            interceptors: Arc::new(std::sync::RwLock::new(Vec::new())),
+            type_counters: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            actor_metrics: ActorMetrics::default(),
+            clock: Arc::new(LiveClock),
+            ttl_defaults: HashMap::new(),
+            dedup: Arc::new(std::sync::Mutex::new(DedupCache::new(DEFAULT_DEDUP_TTL))),
+            groups: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            ordered: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            aliases: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            circuits: None,
+        }
+    }
+
+    /// 为消息类型 `M` 指定通道容量，覆盖 `default_capacity`。
+    /// 容量同样会被限制到 `[MIN_CHANNEL_CAPACITY, MAX_CHANNEL_CAPACITY]`。
+    /// 只对尚未创建的通道生效，应在任何 `subscribe::<M>` 之前调用。
+    pub fn with_type_capacity<M: Message>(mut self, capacity: usize) -> Self {
+        self.capacity_overrides.insert(TypeId::of::<M>(), clamp_capacity(capacity));
+        self
+    }
+
+    /// 限制消息类型 `M` 同时存在的订阅者数量（所有命名空间合计），每个订阅者都会增加一次发布的克隆开销。
+    /// 达到上限后 `try_subscribe::<M>` 返回 `BusError::SubscriberLimit`，`subscribe::<M>` panic，
+    /// 被丢弃的 `Receiver` 会让出名额。用于发现反复订阅却不丢弃 `Receiver` 的泄漏。
+    /// 应在创建任何命名空间视图之前调用。
+    pub fn with_max_subscribers<M: Message>(mut self, max: usize) -> Self {
+        self.max_subscribers.insert(TypeId::of::<M>(), max);
+        self
+    }
+
+    /// 单个通道的订阅者数量达到 `threshold`（默认 `DEFAULT_RECEIVER_WARNING_THRESHOLD`）以及此后每次翻倍时记录警告，
+    /// 与 `with_max_subscribers` 不同，不拒绝订阅。0 表示不警告。应在创建任何命名空间视图之前调用。
+    pub fn with_receiver_warning_threshold(mut self, threshold: usize) -> Self {
+        self.receiver_warning_threshold = threshold;
+        self
+    }
+
+    /// 为消息类型 `M` 保留最近 `retain` 条已发布的消息，供 `capture_state` 导出。
+    /// 应在创建任何命名空间视图之前调用。
+    pub fn with_message_store<M: Message + Serialize + DeserializeOwned>(mut self, retain: usize) -> Self {
+        let store = self.store.get_or_insert_with(|| Arc::new(std::sync::Mutex::new(MessageStore::default())));
+        store.lock().unwrap().register::<M>(retain);
+        self
+    }
+
+    /// 让每次 `publish` 把消息副本交给 `tap`。应在创建任何命名空间视图之前调用。
+    pub(crate) fn with_publish_tap(mut self, tap: PublishTap) -> Self {
+        self.tap = Some(tap);
+        self
+    }
+
+    /// ## `on_new_type`
+    ///
+    /// 登记一个回调，在某种消息类型第一次在总线上（任意命名空间）创建通道时调用，
+    /// 例如让记录器自动订阅每一种出现的类型。同一类型在其他命名空间再创建通道时不会再次调用，
+    /// 登记之前已存在的类型也不会补发。
+    ///
+    /// 回调在释放通道表的锁之后调用，因此可以在回调中订阅；但它运行在 `subscribe` 的调用方中，应当足够快。
+    /// 没有登记回调时，创建通道的慢路径上只多一次空列表检查。
+    pub fn on_new_type(&self, hook: NewTypeHook) {
+        self.new_type_hooks.write().unwrap().push(hook);
+    }
+
+    /// ## `add_interceptor`
+    ///
+    /// 登记一个发布拦截器，对所有命名空间视图之后的每次 `publish` 生效。
+    /// 有多个拦截器时按登记顺序询问，第一个不是 `Interception::Deliver` 的结果生效。
+    /// 没有登记拦截器时，发布路径上只多一次空列表检查。
+    pub fn add_interceptor(&self, interceptor: Arc<dyn PublishInterceptor>) {
+        self.interceptors.write().unwrap().push(interceptor);
+    }
+
+    /// 指定为消息打上 `ts_recv` 的时钟，默认为 `LiveClock`。回测中传入驱动器的 `VirtualClock`，
+    /// 有效期随虚拟时间计算。应在克隆总线之前调用。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 为消息类型 `M` 设置默认有效期：`publish` 的 `M` 在总线收到它 `ttl` 之后过期，
+    /// 使用 `Receiver::recv_fresh` 的消费者不再收到它。`publish_with_ttl` 可以为单条消息覆盖。
+    /// 适合行情等过时即无用的消息，订单与成交不应设置。
+    pub fn with_type_ttl<M: Message>(mut self, ttl: Duration) -> Self {
+        self.ttl_defaults.insert(TypeId::of::<M>(), ttl);
+        self
+    }
+
+    /// ## `with_circuit_breaker`
+    ///
+    /// 为消息类型 `M` 设置发布熔断：连续 `threshold` 次发布时都有通道已满（会覆盖尚未读取的消息，
+    /// 订阅者随后收到 `Lagged`），熔断打开，之后 `cooldown` 内 `publish` / `try_publish` 的 `M`
+    /// 返回 `BusError::CircuitOpen`，不投递也不计入发布计数。冷却结束后放行一次试探发布，
+    /// 通道不再写满时熔断关闭，否则立即重新打开。用于防止生产者持续淹没跟不上的消费者。
+    ///
+    /// - `threshold` 为 0 时按 1 处理。
+    /// - 熔断状态按类型记录、由所有命名空间视图共享；`restore_state` 的重放不受熔断限制。
+    /// - 应在克隆总线之前调用。
+    pub fn with_circuit_breaker<M: Message>(mut self, threshold: u32, cooldown: Duration) -> Self {
+        let circuits = self.circuits.get_or_insert_with(|| Arc::new(std::sync::Mutex::new(HashMap::new())));
+        circuits.lock().unwrap().insert(TypeId::of::<M>(), CircuitBreaker::new(threshold, cooldown));
+        self
+    }
+
+    /// 设置 `publish_idempotent` 记住消息 id 的默认时长，默认 `DEFAULT_DEDUP_TTL`。
+    /// `set_dedup_ttl` 按类型覆盖。应在克隆总线之前调用。
+    pub fn with_default_dedup_ttl(self, ttl: Duration) -> Self {
+        self.dedup.lock().unwrap().default_ttl = ttl;
+        self
+    }
+
+    /// 为消息类型 `M` 设置 `publish_idempotent` 的去重时长，覆盖默认值，对所有视图立即生效。
+    /// 例如订单 id 记住一小时，`Bar` 只记住 100 毫秒；为零时 `M` 不去重。
+    /// 已记录的 id 按新的时长过期。
+    pub fn set_dedup_ttl<M: HasId>(&self, ttl: Duration) {
+        self.dedup.lock().unwrap().ttls.insert(TypeId::of::<M>(), ttl);
+    }
+
+    /// 指定 `blocking_*` 方法所使用的运行时句柄。
+    /// 当总线在运行时之外创建时，必须调用此方法才能使用阻塞 API。
+    pub fn with_runtime(mut self, handle: Handle) -> Self {
+        self.runtime = Some(handle);
+        self
+    }
+
+    /// ## `clone_with_prefix`
+    ///
+    /// 返回一个共享底层通道表、但带有命名空间前缀的总线视图。
+    ///
+    /// - 通过该视图的 `subscribe` 只会收到同一命名空间内发布的消息。
+    /// - 通过该视图的 `publish` 会同时投递给本命名空间及其所有上级命名空间，
+    ///   因此原始总线（根命名空间）可以看到所有子视图的消息。
+    /// - 在带前缀的视图上再次调用会得到嵌套的命名空间，例如 `"paper/btc"`。
+    ///
+    /// 这使得同一组 Actor 可以在一条总线上并行运行多份而互不干扰。
+    pub fn clone_with_prefix(&self, prefix: &str) -> MessageBus {
+        let namespace = if self.namespace.is_empty() {
+            prefix.to_string()
+        } else {
+            format!("{}{}{}", self.namespace, NAMESPACE_SEPARATOR, prefix)
+        };
+        self.with_namespace(&namespace)
+    }
+
+    /// ## `fork`
+    ///
+    /// 创建一条与本总线完全隔离的新总线，用于“如果下这笔单会怎样”之类的情景模拟。
+    ///
+    /// - 与 `clone` / `clone_with_prefix` 不同，分叉不共享任何通道：在分叉上发布的消息不会到达本总线的订阅者，反之亦然。
+    /// - 继承配置：默认容量与按类型覆盖的容量、订阅者上限与警告阈值、运行时句柄、时钟与按类型的有效期和去重时长、
+    ///   `with_message_store` 登记的类型（不含已缓存的消息）、`register_message` 登记的名称以及熔断设置（熔断处于关闭状态）。
+    /// - 不继承运行期状态：通道、计数、去重缓存、`on_new_type` 回调、发布拦截器与 `TestBus` 的记录都从空开始。
+    /// - 分叉总是位于根命名空间。
+    ///
+    /// 需要从本总线的最近消息出发时，先在分叉上启动 Actor 完成订阅，再调用 `seed_from`。
+    pub fn fork(&self) -> MessageBus {
+        let mut fork = MessageBus::new(self.default_capacity);
+        fork.capacity_overrides = self.capacity_overrides.clone();
+        fork.max_subscribers = self.max_subscribers.clone();
+        fork.receiver_warning_threshold = self.receiver_warning_threshold;
+        fork.runtime = self.runtime.clone();
+        fork.clock = self.clock.clone();
+        fork.ttl_defaults = self.ttl_defaults.clone();
+        fork.dedup = Arc::new(std::sync::Mutex::new(self.dedup.lock().unwrap().config_copy()));
+        fork.store = self.store.as_ref().map(|store| Arc::new(std::sync::Mutex::new(store.lock().unwrap().empty_copy())));
+        fork.topics = Arc::new(std::sync::RwLock::new(self.topics.read().unwrap().clone()));
+        fork.circuits = self.circuits.as_ref().map(|circuits| {
+            let configs = circuits.lock().unwrap().iter().map(|(type_id, breaker)| {
+                (*type_id, CircuitBreaker::new(breaker.threshold, breaker.cooldown))
+            }).collect();
+            Arc::new(std::sync::Mutex::new(configs))
+        });
+        fork
+    }
+
+    /// ## `seed_from`
+    ///
+    /// 把 `parent` 消息存储中的最近消息按原顺序与命名空间重放到本总线（通常是 `parent.fork()` 得到的分叉），
+    /// 重放的消息标记为 `is_replay`。与 `restore_state` 相同，只有已订阅的 Actor 能收到，
+    /// 且 `parent` 中缓存的类型都必须已在本总线上登记（`fork` 会继承登记）。
+    pub async fn seed_from(&self, parent: &MessageBus) -> Result<(), BusError> {
+        self.restore_state(parent.capture_state().await).await
+    }
+
+    /// 返回一个位于绝对命名空间 `namespace` 的视图。
+    fn with_namespace(&self, namespace: &str) -> MessageBus {
+        Self {
+            channels: self.channels.clone(),
+            default_capacity: self.default_capacity,
+            capacity_overrides: self.capacity_overrides.clone(),
+            max_subscribers: self.max_subscribers.clone(),
+            receiver_warning_threshold: self.receiver_warning_threshold,
+            namespace: Arc::from(namespace),
+            runtime: self.runtime.clone(),
+            publish_count: self.publish_count.clone(),
+            store: self.store.clone(),
+            restored_capacities: self.restored_capacities.clone(),
+            topics: self.topics.clone(),
+            tap: self.tap.clone(),
+            new_type_hooks: self.new_type_hooks.clone(),
+            interceptors: self.interceptors.clone(),
+            type_counters: self.type_counters.clone(),
+            actor_metrics: self.actor_metrics.clone(),
+            clock: self.clock.clone(),
+            ttl_defaults: self.ttl_defaults.clone(),
+            dedup: self.dedup.clone(),
+            groups: self.groups.clone(),
+            ordered: self.ordered.clone(),
+            aliases: self.aliases.clone(),
+            circuits: self.circuits.clone(),
+        }
+    }
+
+    /// 自创建以来 `publish` 被调用的总次数。
+    /// 可用于判断系统是否已处于静止状态（例如 `SimulationDriver`）。
+    pub fn publish_count(&self) -> u64 {
+        self.publish_count.load(Ordering::Relaxed)
+    }
+
+    /// ## `type_metrics`
+    ///
+    /// 每种被发布过的消息类型的发布、丢弃（无人接收）与落后（覆盖未读消息）计数，按类型名排序。
+    /// 类型名为 `std::any::type_name` 给出的完整路径。
+    pub fn type_metrics(&self) -> Vec<(&'static str, TypeMetrics)> {
+        let counters = self.type_counters.read().unwrap();
+        let mut metrics: Vec<_> = counters
+            .values()
+            .map(|counters| {
+                let metrics = TypeMetrics {
+                    published: counters.published.load(Ordering::Relaxed),
+                    dropped: counters.dropped.load(Ordering::Relaxed),
+                    lagged: counters.lagged.load(Ordering::Relaxed),
+                };
+                (counters.type_name, metrics)
+            })
+            .collect();
+        metrics.sort_by_key(|(type_name, _)| *type_name);
+        metrics
+    }
+
+    /// 总线上所有 Actor 共享的处理统计，Actor 通过它记录每条消息的处理耗时。
+    pub fn actor_metrics(&self) -> &ActorMetrics {
+        &self.actor_metrics
+    }
+
+    /// 消息类型 `M` 的发布计数器，第一次发布时创建。
+    fn counters<M: Message>(&self) -> Arc<TypeCounters> {
+        let type_id = TypeId::of::<M>();
+        if let Some(counters) = self.type_counters.read().unwrap().get(&type_id) {
+            return counters.clone();
+        }
+        let mut type_counters = self.type_counters.write().unwrap();
+        let counters = type_counters.entry(type_id).or_insert_with(|| {
+            Arc::new(TypeCounters {
+                type_name: std::any::type_name::<M>(),
+                published: AtomicU64::new(0),
+                dropped: AtomicU64::new(0),
+                lagged: AtomicU64::new(0),
+            })
+        });
+        counters.clone()
+    }
+
+    /// 当前视图的命名空间，原始总线返回空字符串。
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// 从当前命名空间开始，依次列出自身及所有上级命名空间，直到根命名空间。
+    fn namespace_chain(&self) -> Vec<Arc<str>> {
+        let mut chain = vec![self.namespace.clone()];
+        let mut current: &str = &self.namespace;
+        while !current.is_empty() {
+            current = match current.rfind(NAMESPACE_SEPARATOR) {
+                Some(idx) => &current[..idx],
+                None => "",
+            };
+            chain.push(Arc::from(current));
         }
+        chain
     }
 
     /// ## `publish`
@@ -72,55 +1642,870 @@ impl MessageBus {
     ///
     /// - `msg`: 要发布的消息，必须实现 `Message` trait。
     /// - 如果没有订阅者订阅此消息类型，此操作将无声地成功 (返回 `Ok(0)`)。
+    /// - 消息会投递到当前命名空间及其所有上级命名空间，返回值为收到消息的订阅者总数。
     /// - 此操作是非阻塞的，发布后立即返回。
-    pub async fn publish<M: Message>(&self, msg: M) -> Result<usize, Box<dyn Error + Send + Sync>> {
-        let type_id = TypeId::of::<M>();
+    /// - 登记了拦截器时，投递可能被丢弃、延迟或重复（见 `add_interceptor`）。
+    /// - `M` 设置了默认有效期时消息带上它（见 `with_type_ttl`）。
+    pub async fn publish<M: Message>(&self, msg: M) -> Result<usize, BusError> {
+        let ttl = self.ttl_defaults.get(&TypeId::of::<M>()).copied();
+        self.publish_inner(msg, false, ttl).await
+    }
+
+    /// 发布一条有效期为 `ttl` 的消息，覆盖 `with_type_ttl` 的默认值。
+    /// 总线收到它 `ttl` 之后，使用 `Receiver::recv_fresh` 的消费者不再收到它；`recv` 与 `recv_traced` 不受影响。
+    pub async fn publish_with_ttl<M: Message>(&self, msg: M, ttl: Duration) -> Result<usize, BusError> {
+        self.publish_inner(msg, false, Some(ttl)).await
+    }
+
+    /// ## `publish_idempotent`
+    ///
+    /// 发布 `msg`，但同一类型、同一 `HasId::id` 的消息在去重时长内只发布一次，重复的发布返回 `Ok(None)`。
+    /// 用于重试与多个数据源桥接时防止同一条逻辑消息被处理两次。
+    ///
+    /// - 去重时长按类型取 `set_dedup_ttl` 的值，没有设置时为 `with_default_dedup_ttl` 的默认值；
+    ///   时间取自总线的时钟（见 `with_clock`），回测中随虚拟时间过期。
+    /// - 每次调用按各类型自己的时长淘汰所有类型中过期的 id，缓存大小受去重时长内的发布量限制。
+    /// - 缓存由所有命名空间视图共享，同一个 id 在任何视图发布过都算重复。
+    pub async fn publish_idempotent<M: HasId>(&self, msg: M) -> Result<Option<usize>, BusError> {
+        let now = self.clock.now_nanos();
+        if !self.dedup.lock().unwrap().insert(TypeId::of::<M>(), msg.id(), now) {
+            tracing::debug!(target: "BUS", "Dropping duplicate {} {}", std::any::type_name::<M>(), msg.id());
+            return Ok(None);
+        }
+        self.publish(msg).await.map(Some)
+    }
+
+    /// ## `publish_if`
+    ///
+    /// 发布前求值 `cond`，为 `false` 时跳过本次发布并返回 `Ok(None)`，否则与 `publish` 相同。
+    /// 用于由共享状态统一把关的发布，例如交易暂停时不再发布订单：
+    ///
+    /// ```ignore
+    /// let halted = Arc::new(AtomicBool::new(false));
+    /// bus.publish_if(order, || !halted.load(Ordering::Acquire)).await?;
+    /// ```
+    ///
+    /// - `cond` 在发布的任何记账之前求值，跳过的消息不进入消息存储与旁路，也不计入发布计数。
+    /// - `cond` 应当很快返回，不能在其中访问总线。
+    pub async fn publish_if<M: Message>(&self, msg: M, cond: impl FnOnce() -> bool) -> Result<Option<usize>, BusError> {
+        if !cond() {
+            tracing::debug!(target: "BUS", "Condition not met, skipping {}", std::any::type_name::<M>());
+            return Ok(None);
+        }
+        self.publish(msg).await.map(Some)
+    }
+
+    /// ## `publish_as`
+    ///
+    /// 把 `msg` 包装为联合类型 `E`（例如 `TradingEvent`）后发布到 `E` 的通道。
+    /// 同一个生产者以 `E` 发布的不同变体按发布顺序到达 `subscribe::<E>` 的订阅者；
+    /// 它们不会出现在 `M` 自己的通道中。
+    ///
+    /// ```ignore
+    /// bus.publish_as::<TradingEvent, _>(order).await?;
+    /// bus.publish_as::<TradingEvent, _>(fill).await?;
+    /// ```
+    pub async fn publish_as<E, M>(&self, msg: M) -> Result<usize, BusError>
+    where
+        E: Message + From<M>,
+    {
+        self.publish(E::from(msg)).await
+    }
+
+    /// ## `publish_shared`
+    ///
+    /// 发布一条共享的消息：每个订阅者只克隆 `Arc`，消息本身只有一份。
+    /// 适合体积大、订阅者多的类型；小消息直接使用 `publish` 即可。
+    ///
+    /// 共享消息走 `Arc<M>` 自己的通道，只有 `subscribe_shared::<M>` 的订阅者能收到，
+    /// `subscribe::<M>` 的订阅者收不到，反之亦然。同一种类型应统一使用一种方式。
+    pub async fn publish_shared<M: Message>(&self, msg: Arc<M>) -> Result<usize, BusError> {
+        self.publish(msg).await
+    }
+
+    /// 发布一条由 `restore_state` 重放的消息，在消息存储中标记为 `is_replay`。重放的消息不会过期。
+    pub(crate) async fn publish_replay<M: Message>(&self, msg: M) -> Result<usize, BusError> {
+        self.publish_inner(msg, true, None).await
+    }
+
+    async fn publish_inner<M: Message>(&self, msg: M, is_replay: bool, ttl: Option<Duration>) -> Result<usize, BusError> {
+        if !is_replay {
+            self.check_circuit::<M>()?;
+        }
+        let (counters, msg) = self.record_publish(msg, is_replay, ttl);
+        let interception = if is_replay { Interception::Deliver } else { self.intercept::<M>() };
+        let delivered = match interception {
+            Interception::Deliver => self.deliver(&msg).await?,
+            Interception::Drop => 0,
+            Interception::Delay(delay) => {
+                self.deliver_later(msg, delay, &Handle::current());
+                return Ok(0);
+            },
+            Interception::Duplicate => {
+                let delivered = self.deliver(&msg).await?;
+                self.deliver(&msg).await?;
+                delivered
+            },
+        };
+        if delivered == 0 {
+            counters.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(delivered)
+    }
+
+    /// ## `try_publish`
+    ///
+    /// `publish` 的同步版本，不等待任何锁，可以在 `Drop` 等无法 `.await` 的地方调用，同步或异步上下文均可。
+    ///
+    /// - 通道表正被写入（某个类型第一次被订阅等，持续时间很短）时返回 `BusError::ChannelsBusy`，
+    ///   消息没有被发布，也不计入发布计数。
+    /// - 拦截器要求延迟投递时，需要当前运行时或总线的运行时句柄，两者都没有时返回 `BusError::NoRuntime`。
+    pub fn try_publish<M: Message>(&self, msg: M) -> Result<usize, BusError> {
+        self.check_circuit::<M>()?;
+        let channels = self.channels.try_read().map_err(|_| BusError::ChannelsBusy)?;
+        let ttl = self.ttl_defaults.get(&TypeId::of::<M>()).copied();
+        let (counters, msg) = self.record_publish(msg, false, ttl);
+        let delivered = match self.intercept::<M>() {
+            Interception::Deliver => self.deliver_to(&channels, &msg)?,
+            Interception::Drop => 0,
+            Interception::Delay(delay) => {
+                let runtime = Handle::try_current().ok().or_else(|| self.runtime.clone()).ok_or(BusError::NoRuntime)?;
+                self.deliver_later(msg, delay, &runtime);
+                return Ok(0);
+            },
+            Interception::Duplicate => {
+                let delivered = self.deliver_to(&channels, &msg)?;
+                self.deliver_to(&channels, &msg)?;
+                delivered
+            },
+        };
+        if delivered == 0 {
+            counters.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(delivered)
+    }
+
+    /// `M` 的熔断打开时返回 `BusError::CircuitOpen`。
+    fn check_circuit<M: Message>(&self) -> Result<(), BusError> {
+        let Some(circuits) = &self.circuits else { return Ok(()) };
+        match circuits.lock().unwrap().get(&TypeId::of::<M>()).and_then(|breaker| breaker.open_since(Instant::now())) {
+            Some(since) => Err(BusError::CircuitOpen { type_name: std::any::type_name::<M>(), since }),
+            None => Ok(()),
+        }
+    }
+
+    /// 发布的记账部分：写入消息存储与旁路、更新计数，并附上追踪上下文。
+    fn record_publish<M: Message>(&self, msg: M, is_replay: bool, ttl: Option<Duration>) -> (Arc<TypeCounters>, Traced<M>) {
+        if let Some(store) = &self.store {
+            store.lock().unwrap().record(&msg, &self.namespace, is_replay);
+        }
+        if let Some(tap) = &self.tap {
+            tap(PublishedMessage::capture(&msg, &self.namespace));
+        }
+        self.publish_count.fetch_add(1, Ordering::Relaxed);
+        let counters = self.counters::<M>();
+        counters.published.fetch_add(1, Ordering::Relaxed);
+
+        let msg = Traced {
+            message: msg,
+            trace: TraceContext::publish::<M>(&self.namespace),
+            ts_recv: self.clock.now_nanos(),
+            ttl,
+            seq: 0,
+        };
+        (counters, msg)
+    }
+
+    /// 在 `runtime` 上派生一个任务，在 `delay` 之后投递拦截器延迟的消息。
+    fn deliver_later<M: Message>(&self, msg: Traced<M>, delay: Duration, runtime: &Handle) {
+        let bus = self.clone();
+        runtime.spawn(async move {
+            tokio::time::sleep(delay).await;
+            if let Err(e) = bus.deliver(&msg).await {
+                tracing::error!(target: "BUS", "Failed to deliver delayed message: {}", e);
+            }
+        });
+    }
+
+    /// 依次询问拦截器，返回第一个不是 `Deliver` 的处置。
+    fn intercept<M: Message>(&self) -> Interception {
+        let interceptors = self.interceptors.read().unwrap();
+        interceptors
+            .iter()
+            .map(|interceptor| interceptor.intercept(TypeId::of::<M>(), std::any::type_name::<M>(), &self.namespace))
+            .find(|interception| *interception != Interception::Deliver)
+            .unwrap_or(Interception::Deliver)
+    }
+
+    /// 把消息投递到当前命名空间及其所有上级命名空间的通道，返回收到消息的订阅者总数。
+    async fn deliver<M: Message>(&self, msg: &Traced<M>) -> Result<usize, BusError> {
         let channels = self.channels.read().await; // 获取读锁
+        self.deliver_to(&channels, msg)
+    }
+
+    /// `deliver` 持有通道表读锁之后的部分。
+    fn deliver_to<M: Message>(&self, channels: &HashMap<ChannelKey, Box<dyn AnyChannel>>, msg: &Traced<M>) -> Result<usize, BusError> {
+        let type_id = TypeId::of::<M>();
+        let counters = self.counters::<M>();
+
+        let mut delivered = 0;
+        let mut full = false;
+        for namespace in self.namespace_chain() {
+            if let Some(channel) = channels.get(&(type_id, namespace)) {
+                if channel.is_full() {
+                    counters.lagged.fetch_add(1, Ordering::Relaxed);
+                    full = true;
+                }
+                delivered += channel.send_any(msg, std::any::type_name::<M>())?;
+            }
+            // 没有订阅者的命名空间直接跳过
+        }
+        if let Some(circuits) = &self.circuits {
+            if let Some(breaker) = circuits.lock().unwrap().get_mut(&type_id) {
+                breaker.record(full, std::any::type_name::<M>(), Instant::now());
+            }
+        }
+        Ok(delivered)
+    }
+
+    /// ## `publish_and_await_reply`
+    ///
+    /// 发布请求 `req`，等待第一条 `correlation_id` 等于 `req.id()` 的 `Resp` 并返回它。
+    ///
+    /// - 先订阅 `Resp` 再发布请求，不会错过发布后立即产生的回复。
+    /// - 与其他请求关联的 `Resp` 被忽略；接收者落后时记录警告并继续等待。
+    /// - `timeout` 内没有关联的回复时返回 `RequestError::Timeout`。
+    /// - `Resp` 的订阅者已达到 `with_max_subscribers` 的上限时返回 `RequestError::Subscribe`，请求不会发布。
+    ///
+    /// ```ignore
+    /// let fill: FillEvent = bus.publish_and_await_reply(order, Duration::from_secs(1)).await?;
+    /// ```
+    pub async fn publish_and_await_reply<Req, Resp>(&self, req: Req, timeout: Duration) -> Result<Resp, RequestError>
+    where
+        Req: HasId,
+        Resp: HasCorrelationId,
+    {
+        let id = req.id();
+        let mut rx = self.try_subscribe::<Resp>().await.map_err(RequestError::Subscribe)?;
+        self.publish(req).await.map_err(RequestError::Publish)?;
+
+        let wait = async {
+            loop {
+                match rx.recv().await {
+                    Ok(reply) if reply.correlation_id() == Some(id) => return Ok(reply),
+                    Ok(_) => {},
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(target: "BUS", "Waiting for reply to {}: {}", id, BusError::lagged::<Resp>(n));
+                    },
+                    Err(broadcast::error::RecvError::Closed) => return Err(RequestError::Closed),
+                }
+            }
+        };
+        tokio::time::timeout(timeout, wait).await.unwrap_or(Err(RequestError::Timeout(timeout)))
+    }
 
-        match channels.get(&type_id) {
-            Some(channel) => channel.send_any(&msg),
-            None => Ok(0), // 没有订阅者，正常返回
+    /// ## `subscriber_count`
+    ///
+    /// 从当前视图发布 `M` 时会收到消息的订阅者数量，即本命名空间及所有上级命名空间的订阅者之和。
+    /// 已被丢弃的 `Receiver` 不计入。
+    ///
+    /// 只获取通道表的读锁，不创建通道，在无竞争时开销可以忽略。
+    /// 发布前需要昂贵准备工作（序列化、订单簿计算等）的发布者可以在结果为 0 时跳过这些工作。
+    pub async fn subscriber_count<M: Message>(&self) -> usize {
+        let type_id = TypeId::of::<M>();
+        let channels = self.channels.read().await;
+        if channels.is_empty() {
+            return 0;
         }
+        self.namespace_chain()
+            .into_iter()
+            .filter_map(|namespace| channels.get(&(type_id, namespace)))
+            .map(|channel| channel.receiver_count())
+            .sum()
+    }
+
+    /// ## `purge`
+    ///
+    /// 丢弃当前命名空间中 `M` 的通道里已缓冲的所有消息，返回丢弃的数量（尚未被所有订阅者读取的消息数）。
+    /// 通道保持打开，现有订阅者不受影响，之后发布的消息照常收到。
+    /// 适合重启后不再需要重启前的陈旧行情的场景。
+    ///
+    /// broadcast 通道无法从发送端删除消息：被清除的消息仍占用槽位，直到各订阅者下次接收时跳过它们，
+    /// 因此 `Receiver::len` 在此之前仍把它们计算在内。只清除当前命名空间的通道，上级命名空间中的副本不受影响。
+    ///
+    /// 当前命名空间还没有 `M` 的通道时返回 `BusError::ChannelNotFound`。
+    pub async fn purge<M: Message>(&self) -> Result<usize, BusError> {
+        let channels = self.channels.read().await;
+        let channel = channels
+            .get(&(TypeId::of::<M>(), self.namespace.clone()))
+            .ok_or(BusError::ChannelNotFound { type_name: std::any::type_name::<M>() })?;
+        let purged = channel.purge();
+        tracing::debug!(target: "BUS", "Purged {} buffered {} in namespace '{}'", purged, std::any::type_name::<M>(), self.namespace);
+        Ok(purged)
+    }
+
+    /// ## `channel_exists`
+    ///
+    /// 当前命名空间中 `M` 的通道是否已经存在，即之前是否有人在这里订阅过 `M`。
+    /// 只获取通道表的读锁，不创建通道；订阅者全部被丢弃后通道仍然存在。
+    ///
+    /// 只想旁听已有流量的 Actor（例如分析组件）可以先检查，避免仅因订阅就创建通道：
+    ///
+    /// ```ignore
+    /// if bus.channel_exists::<OrderRequest>().await {
+    ///     let rx = bus.subscribe::<OrderRequest>().await;
+    /// }
+    /// ```
+    pub async fn channel_exists<M: Message>(&self) -> bool {
+        let key: ChannelKey = (TypeId::of::<M>(), self.namespace.clone());
+        self.channels.read().await.contains_key(&key)
     }
 
     /// ## `subscribe`
     ///
-    /// 订阅一种消息类型，返回一个强类型的 `broadcast::Receiver`。
+    /// 订阅一种消息类型，返回一个强类型的 `Receiver`。
     ///
     /// - `M`: 要订阅的消息类型。
     /// - 如果这是第一次订阅此消息类型，将自动创建一个新的 broadcast 通道。
+    /// - 只会收到当前命名空间（及其子命名空间）发布的消息。
     /// - 使用了高效的“双重检查锁定”模式来最小化写锁的争用。
-    pub async fn subscribe<M: Message>(&self) -> broadcast::Receiver<M> {
-        let type_id = TypeId::of::<M>();
+    ///
+    /// **Panics**：`M` 的订阅者已达到 `with_max_subscribers` 的上限时 panic，设置了上限的类型应使用 `try_subscribe`。
+    /// 总线内部需要订阅的接口（`subscribe_group`、`subscribe_ordered`、`type_alias`、`subscribe_json`、
+    /// `publish_and_await_reply`）一律经由 `try_subscribe`，达到上限时返回错误而不是 panic。
+    pub async fn subscribe<M: Message>(&self) -> Receiver<M> {
+        self.try_subscribe::<M>().await.unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// ## `subscribe_sampled`
+    ///
+    /// 订阅 `M`，但只交付每 `every_n` 条中的一条（第 `every_n`、`2 × every_n`……条），
+    /// 供只需要低分辨率数据的看板与监控使用。`every_n` 为 1 时与 `subscribe` 相同。
+    ///
+    /// **Panics**：`every_n` 为 0 时 panic；其余与 `subscribe` 相同。
+    pub async fn subscribe_sampled<M: Message>(&self, every_n: usize) -> SampledReceiver<M> {
+        assert!(every_n > 0, "subscribe_sampled requires every_n > 0");
+        SampledReceiver { inner: self.subscribe::<M>().await, every_n: every_n as u64, since_last: 0, discarded: 0 }
+    }
+
+    /// ## `subscribe_group`
+    ///
+    /// 以组 `group_id` 的成员身份订阅 `M`：
+    /// - `GroupPolicy::Broadcast`：每个成员都收到每条消息，与 `subscribe` 相同，例如各自维护持仓的多个 `PortfolioActor`；
+    /// - `GroupPolicy::RoundRobin`：每条消息只交给组内的一个成员，用于冗余部署、分担负载的实例。
+    ///   组在第一个成员加入时创建一个转发任务，它是该组在通道上唯一的订阅者（`publish` 的返回值把整个组计为一个），
+    ///   按成员加入的顺序轮流转发；被丢弃的成员在下次轮到时移除。最后一个成员被丢弃时转发任务停止、组被注销，
+    ///   不再计为 `M` 的订阅者；之后再加入同名组会重新创建它。
+    ///
+    /// 组按消息类型、命名空间与 `group_id` 区分。`RoundRobin` 必须在 tokio 运行时中调用。
+    /// `M` 的订阅者已达到 `with_max_subscribers` 的上限（`RoundRobin` 组的转发任务计为一个）时返回 `BusError::SubscriberLimit`。
+    pub async fn subscribe_group<M: Message>(&self, group_id: &str, policy: GroupPolicy) -> Result<GroupReceiver<M>, BusError> {
+        if policy == GroupPolicy::Broadcast {
+            return Ok(GroupReceiver { inner: GroupInner::Broadcast(self.try_subscribe::<M>().await?) });
+        }
+        let capacity = self.capacity_overrides.get(&TypeId::of::<M>()).copied().unwrap_or(self.default_capacity);
+        let (tx, rx) = mpsc::channel(capacity);
+        let key: GroupKey = (TypeId::of::<M>(), self.namespace.clone(), group_id.to_string());
+        let existing = lookup_group::<M>(&self.groups.lock().unwrap(), &key);
+        let (members, relay) = match existing {
+            Some(group) => group,
+            None => {
+                // 先订阅再登记，转发任务不会漏掉登记之后发布的消息
+                let relay_rx = self.try_subscribe::<M>().await?;
+                let mut groups = self.groups.lock().unwrap();
+                match lookup_group::<M>(&groups, &key) {
+                    // 并发加入的另一个成员已创建了组，丢弃多余的订阅
+                    Some(group) => group,
+                    None => {
+                        let members: GroupMembers<M> = Arc::new(std::sync::Mutex::new(Vec::new()));
+                        let task = tokio::spawn(relay_round_robin(relay_rx, members.clone(), group_id.to_string()));
+                        let relay = Arc::new(GroupRelay { key: key.clone(), groups: Arc::downgrade(&self.groups), task });
+                        let group = RoundRobinGroup { members: Box::new(members.clone()), relay: Arc::downgrade(&relay) };
+                        groups.insert(key, group);
+                        (members, relay)
+                    },
+                }
+            },
+        };
+        members.lock().unwrap().push(tx);
+        Ok(GroupReceiver { inner: GroupInner::RoundRobin { rx, _relay: relay } })
+    }
+
+    /// ## `subscribe_ordered`
+    ///
+    /// 以优先级 `priority` 订阅 `M`：每条消息先交给优先级更高的有序订阅者，等它们确认处理完（见 `OrderedReceiver`）
+    /// 之后才交给优先级更低的订阅者，例如 `RiskManagerActor` 必须先于 `SimulatedExecutionEngine` 处理 `OrderRequest`。
+    /// 优先级相同的订阅者同时收到，相互之间没有顺序。
+    ///
+    /// - 第一个有序订阅者加入时创建一个协调任务，它是有序订阅者在通道上唯一的订阅者（`publish` 的返回值把它们计为一个）。
+    /// - 刻意偏向保守：一条消息在所有有序订阅者确认之前，下一条消息不会交给任何有序订阅者，
+    ///   一个不再调用 `recv` 的订阅者会阻塞整条链，等待低优先级订阅者处理同类型消息的订阅者会死锁。
+    /// - 只在有序订阅者之间排序，普通 `subscribe` 的订阅者照常直接收到消息。
+    ///
+    /// 有序订阅按消息类型与命名空间区分。必须在 tokio 运行时中调用。
+    /// 第一个有序订阅者加入时 `M` 的订阅者已达到 `with_max_subscribers` 的上限则返回 `BusError::SubscriberLimit`。
+    pub async fn subscribe_ordered<M: Message>(&self, priority: i32) -> Result<OrderedReceiver<M>, BusError> {
+        // 协调任务在收到确认前不会发送下一条，每个成员最多只有一条未处理的消息
+        let (sender, rx) = mpsc::channel(1);
+        let key: OrderedKey = (TypeId::of::<M>(), self.namespace.clone());
+        let existing = self.ordered.lock().unwrap().get(&key).and_then(|members| members.downcast_ref::<OrderedMembers<M>>().cloned());
+        let members = match existing {
+            Some(members) => members,
+            None => {
+                // 先订阅再登记，协调任务不会漏掉登记之后发布的消息
+                let relay_rx = self.try_subscribe::<M>().await?;
+                let mut ordered = self.ordered.lock().unwrap();
+                match ordered.get(&key).and_then(|members| members.downcast_ref::<OrderedMembers<M>>().cloned()) {
+                    // 并发订阅的另一个成员已创建了协调任务，丢弃多余的订阅
+                    Some(members) => members,
+                    None => {
+                        let members: OrderedMembers<M> = Arc::new(std::sync::Mutex::new(Vec::new()));
+                        ordered.insert(key, Box::new(members.clone()));
+                        tokio::spawn(relay_ordered(relay_rx, members.clone()));
+                        members
+                    },
+                }
+            },
+        };
+        let mut members = members.lock().unwrap();
+        let position = members.partition_point(|member| member.priority >= priority);
+        members.insert(position, OrderedMember { priority, sender });
+        Ok(OrderedReceiver { rx, pending: None, priority })
+    }
+
+    /// ## `type_alias`
+    ///
+    /// 让发布到 `Canonical` 的消息也以 `Alias::from` 转换后送达 `Alias` 的订阅者，
+    /// 例如只认识旧类型的 Actor 继续订阅旧类型，而生产者只发布新类型。
+    /// 登记时启动一个订阅 `Canonical` 的转发任务（它计为 `Canonical` 的一个订阅者），
+    /// 把消息重新发布到当前命名空间的 `Alias` 通道；登记之后发布的消息才会被转发，直接发布到 `Alias` 的消息不会反向转发。
+    ///
+    /// 转发在返回的 `TypeAlias` 句柄存续期间有效，丢弃最后一个句柄即中止转发任务并注销登记，
+    /// 因此不再需要的别名不会一直占用一个任务与 `Canonical` 的订阅者名额（`subscriber_count` 随之减少）。
+    /// 同一对类型在句柄仍存续时重复登记不会再启动转发任务，而是返回共享同一任务的句柄。
+    ///
+    /// 两者是同一类型，或 `Alias` 的发布已经会（直接或经过其他别名）转发到 `Canonical` 时返回 `BusError::CircularAlias`；
+    /// `Canonical` 的订阅者已达到 `with_max_subscribers` 的上限时返回 `BusError::SubscriberLimit`。
+    /// 必须在 tokio 运行时中调用。
+    pub async fn type_alias<Alias, Canonical>(&self) -> Result<TypeAlias, BusError>
+    where
+        Alias: Message + From<Canonical>,
+        Canonical: Message,
+    {
+        let (alias, canonical) = (TypeId::of::<Alias>(), TypeId::of::<Canonical>());
+        let circular = BusError::CircularAlias {
+            alias: std::any::type_name::<Alias>(),
+            canonical: std::any::type_name::<Canonical>(),
+        };
+        if alias == canonical {
+            return Err(circular);
+        }
+        // 先订阅再登记，登记成功后发布的消息都会被转发
+        let rx = self.try_subscribe::<Canonical>().await?;
+        let key = (self.namespace.clone(), canonical);
+        let mut aliases = self.aliases.lock().unwrap();
+        // 从 `Alias` 出发沿仍然有效的转发能到达 `Canonical` 时，新的转发会构成环
+        let mut pending = vec![alias];
+        let mut visited = HashSet::new();
+        while let Some(type_id) = pending.pop() {
+            if type_id == canonical {
+                return Err(circular);
+            }
+            if visited.insert(type_id) {
+                if let Some(next) = aliases.get(&(self.namespace.clone(), type_id)) {
+                    pending.extend(next.iter().filter(|(_, relay)| relay.strong_count() > 0).map(|(type_id, _)| *type_id));
+                }
+            }
+        }
+        if let Some(relay) = aliases.get(&key).and_then(|targets| targets.get(&alias)).and_then(std::sync::Weak::upgrade) {
+            return Ok(TypeAlias { _relay: relay });
+        }
+        let relay = Arc::new(AliasRelay {
+            key: key.clone(),
+            alias,
+            aliases: Arc::downgrade(&self.aliases),
+            task: tokio::spawn(relay_alias::<Alias, Canonical>(rx, self.clone())),
+        });
+        aliases.entry(key).or_default().insert(alias, Arc::downgrade(&relay));
+        Ok(TypeAlias { _relay: relay })
+    }
+
+    /// ## `subscribe_shared`
+    ///
+    /// 订阅 `publish_shared::<M>` 发布的共享消息。
+    pub async fn subscribe_shared<M: Message>(&self) -> Receiver<Arc<M>> {
+        self.subscribe::<Arc<M>>().await
+    }
+
+    /// ## `try_subscribe`
+    ///
+    /// 与 `subscribe` 相同，但在 `M` 的订阅者已达到 `with_max_subscribers` 的上限时
+    /// 返回 `BusError::SubscriberLimit`，不创建通道。
+    pub async fn try_subscribe<M: Message>(&self) -> Result<Receiver<M>, BusError> {
+        let key: ChannelKey = (TypeId::of::<M>(), self.namespace.clone());
 
         // --- 快速路径：使用读锁 ---
         // 大多数情况下，通道已经存在，此路径将被采用。
-        let channels_read = self.channels.read().await;
-        if let Some(channel) = channels_read.get(&type_id) {
-            return channel
-                .subscribe_any()
-                .downcast::<broadcast::Receiver<M>>()
-                .map(|boxed_rx| *boxed_rx) // 从 Box<Receiver> 中取出 Receiver
-                .expect("FATAL: MessageBus internal type corruption. This is a bug.");
+        // 设置了订阅者上限的类型跳过快速路径：多个持有读锁的订阅者可能同时通过检查而超出上限。
+        if !self.max_subscribers.contains_key(&TypeId::of::<M>()) {
+            let channels_read = self.channels.read().await;
+            if let Some(channel) = channels_read.get(&key) {
+                let receiver = self.receiver(
+                    channel
+                        .subscribe_any()
+                        .downcast::<broadcast::Receiver<Traced<M>>>()
+                        .map(|boxed_rx| *boxed_rx) // 从 Box<Receiver> 中取出 Receiver
+                        .expect("FATAL: MessageBus internal type corruption. This is a bug."),
+                    channel.purge_mark(),
+                );
+                self.warn_receiver_growth::<M>(channel.receiver_count());
+                return Ok(receiver);
+            }
+            drop(channels_read); // 释放读锁，准备进入慢路径
         }
-        drop(channels_read); // 释放读锁，准备进入慢路径
 
         // --- 慢路径：使用写锁 ---
         // 仅在通道不存在时才需要获取写锁。
         let mut channels_write = self.channels.write().await;
         
-        // **双重检查**：在等待写锁时，可能有另一个线程已经创建了通道。
-        if let Some(channel) = channels_write.get(&type_id) {
-             return channel
-                .subscribe_any()
-                .downcast::<broadcast::Receiver<M>>()
-                .map(|boxed_rx| *boxed_rx)
-                .expect("FATAL: MessageBus internal type corruption. This is a bug.");
+        // **双重检查**：在等待写锁时，可能有另一个线程已经创建了通道或订阅了该类型。
+        self.check_subscriber_limit::<M>(&channels_write)?;
+        if let Some(channel) = channels_write.get(&key) {
+             let receiver = self.receiver(
+                channel
+                    .subscribe_any()
+                    .downcast::<broadcast::Receiver<Traced<M>>>()
+                    .map(|boxed_rx| *boxed_rx)
+                    .expect("FATAL: MessageBus internal type corruption. This is a bug."),
+                channel.purge_mark(),
+            );
+            self.warn_receiver_growth::<M>(channel.receiver_count());
+            return Ok(receiver);
         }
 
         // 通道确实不存在，创建并插入它。
-        let (sender, receiver) = broadcast::channel::<M>(self.default_capacity);
-        channels_write.insert(type_id, Box::new(sender));
-        receiver
+        // 优先使用 `restore_state` 恢复的容量，其次是按类型覆盖的容量
+        let restored = self
+            .restored_capacities
+            .lock()
+            .unwrap()
+            .get(&(std::any::type_name::<M>().to_string(), self.namespace.to_string()))
+            .copied();
+        let capacity = restored
+            .or_else(|| self.capacity_overrides.get(&TypeId::of::<M>()).copied())
+            .unwrap_or(self.default_capacity);
+        let type_id = TypeId::of::<M>();
+        let is_new_type = !self.new_type_hooks.read().unwrap().is_empty()
+            && !channels_write.keys().any(|(existing, _)| *existing == type_id);
+        let (sender, inner) = broadcast::channel::<Traced<M>>(capacity);
+        let purge = Arc::new(PurgeMark::default());
+        channels_write.insert(key, Box::new(Channel { sender, capacity, purge: purge.clone() }));
+        drop(channels_write);
+
+        if is_new_type {
+            let hooks = self.new_type_hooks.read().unwrap().clone();
+            for hook in hooks {
+                hook(type_id, std::any::type_name::<M>());
+            }
+        }
+        Ok(self.receiver(inner, purge))
+    }
+
+    /// 把通道的接收端包装为带有总线时钟的 `Receiver`。
+    fn receiver<M: Message>(&self, inner: broadcast::Receiver<Traced<M>>, purge: Arc<PurgeMark>) -> Receiver<M> {
+        Receiver { inner, clock: self.clock.clone(), expired: 0, lease: None, purge }
+    }
+
+    /// 同一通道的订阅者数量跨过 `with_receiver_warning_threshold` 的阈值及其每次翻倍时记录警告。
+    fn warn_receiver_growth<M: Message>(&self, receivers: usize) {
+        let threshold = self.receiver_warning_threshold;
+        if threshold > 0 && receivers >= threshold && receivers.is_multiple_of(threshold) && (receivers / threshold).is_power_of_two() {
+            tracing::warn!(
+                target: "BUS",
+                "{} has {} receivers in namespace '{}', check for subscriptions that are never dropped",
+                std::any::type_name::<M>(),
+                receivers,
+                self.namespace
+            );
+        }
+    }
+
+    /// `M` 设置了订阅者上限且所有命名空间的订阅者之和已达到上限时返回 `BusError::SubscriberLimit`。
+    fn check_subscriber_limit<M: Message>(&self, channels: &HashMap<ChannelKey, Box<dyn AnyChannel>>) -> Result<(), BusError> {
+        let type_id = TypeId::of::<M>();
+        let Some(&limit) = self.max_subscribers.get(&type_id) else { return Ok(()) };
+        let subscribers: usize = channels
+            .iter()
+            .filter(|((existing, _), _)| *existing == type_id)
+            .map(|(_, channel)| channel.receiver_count())
+            .sum();
+        if subscribers >= limit {
+            return Err(BusError::SubscriberLimit { type_name: std::any::type_name::<M>(), limit });
+        }
+        Ok(())
+    }
+
+    /// ## `subscribe_handle`
+    ///
+    /// 与 `subscribe` 相同，但返回可以接收重放消息的 `SubscriptionHandle`。
+    pub async fn subscribe_handle<M: Message>(&self) -> SubscriptionHandle<M> {
+        let live = self.subscribe::<M>().await;
+        let live_from_seq = self.store.as_ref().map_or(0, |store| store.lock().unwrap().next_seq());
+        let (loopback_tx, loopback_rx) = mpsc::unbounded_channel();
+        SubscriptionHandle { live, loopback_tx, loopback_rx, namespace: self.namespace.clone(), live_from_seq }
+    }
+
+    /// ## `replay_from_store`
+    ///
+    /// 供中途重启、晚加入的 Actor 追赶状态：从消息存储中取出事件时间不早于 `from_ts`、
+    /// 且在订阅之前发布的 `M` 消息，按原顺序注入 `subscriber` 的回环通道，标记为 `is_replay`。
+    /// 只有该订阅者会收到这些消息。返回重放的数量。
+    ///
+    /// `M` 必须已通过 `with_message_store` 登记，否则返回 `BusError::UnknownType`。
+    /// 存储只保留最近 N 条消息，更早的消息无法重放。
+    pub fn replay_from_store<M: Message + Timestamped>(
+        &self,
+        subscriber: &SubscriptionHandle<M>,
+        from_ts: u64,
+    ) -> Result<usize, BusError> {
+        let unknown = || BusError::UnknownType { type_name: std::any::type_name::<M>().to_string() };
+        let store = self.store.as_ref().ok_or_else(unknown)?.lock().unwrap();
+        if !store.is_registered::<M>() {
+            return Err(unknown());
+        }
+        let mut replayed = 0;
+        for mut envelope in store.recent::<M>() {
+            if envelope.seq >= subscriber.live_from_seq
+                || envelope.message.ts_event() < from_ts
+                || !subscriber.sees(&envelope.namespace)
+            {
+                continue;
+            }
+            envelope.is_replay = true;
+            if subscriber.loopback_tx.send(envelope).is_err() {
+                break;
+            }
+            replayed += 1;
+        }
+        Ok(replayed)
+    }
+
+    /// 消息存储中类型 `M` 的最近消息，按发布顺序排列。未登记的类型返回空列表。
+    pub fn recent<M: Message>(&self) -> Vec<Envelope<M>> {
+        match &self.store {
+            Some(store) => store.lock().unwrap().recent::<M>(),
+            None => Vec::new(),
+        }
+    }
+
+    /// ## `capture_state`
+    ///
+    /// 导出所有通道（包括所有命名空间）的类型名与容量，以及消息存储中的最近消息。
+    /// 结果可以序列化后写入磁盘，在进程重启后交给 `restore_state`。
+    pub async fn capture_state(&self) -> BusState {
+        let mut channels: Vec<ChannelState> = self
+            .channels
+            .read()
+            .await
+            .iter()
+            .map(|((_, namespace), channel)| ChannelState {
+                type_name: channel.type_name().to_string(),
+                namespace: namespace.to_string(),
+                capacity: channel.capacity(),
+            })
+            .collect();
+        channels.sort_by(|a, b| (&a.type_name, &a.namespace).cmp(&(&b.type_name, &b.namespace)));
+        let messages = match &self.store {
+            Some(store) => store.lock().unwrap().snapshot(),
+            None => Vec::new(),
+        };
+        BusState { channels, messages }
+    }
+
+    /// ## `connected_types`
+    ///
+    /// 当前在总线上（包括所有命名空间）已创建通道的消息类型及其统计，按类型名排序。
+    /// 类型名为 `std::any::type_name` 给出的完整路径。通道在首次订阅时创建，只被发布过的类型不会出现。
+    pub async fn connected_types(&self) -> Vec<(&'static str, ChannelStats)> {
+        self.channel_stats().await.into_iter().map(|(_, type_name, stats)| (type_name, stats)).collect()
+    }
+
+    /// 按消息类型汇总的通道统计，附带 `TypeId`，按类型名排序。
+    pub(crate) async fn channel_stats(&self) -> Vec<(TypeId, &'static str, ChannelStats)> {
+        let mut by_type: HashMap<TypeId, (&'static str, ChannelStats)> = HashMap::new();
+        for ((type_id, _), channel) in self.channels.read().await.iter() {
+            let (_, stats) = by_type.entry(*type_id).or_insert((channel.type_name(), ChannelStats::default()));
+            stats.channels += 1;
+            stats.subscribers += channel.receiver_count();
+            stats.capacity = stats.capacity.max(channel.capacity());
+            stats.buffered += channel.buffered();
+        }
+        let mut stats: Vec<_> = by_type.into_iter().map(|(type_id, (type_name, stats))| (type_id, type_name, stats)).collect();
+        stats.sort_by_key(|(_, type_name, _)| *type_name);
+        stats
+    }
+
+    /// ## `channel_fill_ratios`
+    ///
+    /// 每种已有通道的消息类型的积压程度，按类型名排序。
+    pub async fn channel_fill_ratios(&self) -> Vec<ChannelFill> {
+        let mut by_type: HashMap<TypeId, ChannelFill> = HashMap::new();
+        for ((type_id, _), channel) in self.channels.read().await.iter() {
+            let fill = by_type.entry(*type_id).or_insert(ChannelFill {
+                type_name: channel.type_name(),
+                fill_pct: 0.0,
+                receiver_count: 0,
+            });
+            fill.fill_pct = fill.fill_pct.max(channel.fill_pct());
+            fill.receiver_count += channel.receiver_count();
+        }
+        let mut fills: Vec<_> = by_type.into_values().collect();
+        fills.sort_by_key(|fill| fill.type_name);
+        fills
+    }
+
+    /// ## `enable_hotspot_detection`
+    ///
+    /// 启动一个后台任务，每隔 `sample_interval` 采样一次 `channel_fill_ratios`，
+    /// 为积压超过 `threshold_fill_pct`（0–100）的每种类型在根命名空间发布一条 `SystemEvent::ChannelHotSpot`。
+    /// 通道持续过热时每次采样都会发布；`SystemEvent` 自身的通道不参与检测，避免事件自我放大。
+    ///
+    /// 必须在 tokio 运行时中调用。中止返回的句柄即停止检测。
+    pub fn enable_hotspot_detection(&self, threshold_fill_pct: f64, sample_interval: Duration) -> JoinHandle<()> {
+        let bus = self.with_namespace("");
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(sample_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                for fill in bus.channel_fill_ratios().await {
+                    if fill.fill_pct <= threshold_fill_pct || fill.type_name == std::any::type_name::<SystemEvent>() {
+                        continue;
+                    }
+                    tracing::warn!(target: "BUS", "Channel hot spot: {} is {:.1}% full", fill.type_name, fill.fill_pct);
+                    let event = SystemEvent::ChannelHotSpot {
+                        type_name: fill.type_name,
+                        fill_pct: fill.fill_pct,
+                        receiver_count: fill.receiver_count,
+                    };
+                    // 没有订阅者时事件被丢弃，不是错误
+                    let _ = bus.publish(event).await;
+                }
+            }
+        })
+    }
+
+    /// ## `restore_state`
+    ///
+    /// 从 `capture_state` 的快照恢复：
+    /// 1. 尚未创建的通道在首次 `subscribe` 时使用快照中的原始容量；已存在的通道保持不变。
+    /// 2. 缓存的消息按原顺序重新发布到各自的命名空间，在消息存储中标记为 `is_replay`。
+    ///
+    /// broadcast 通道不会把订阅之前的消息交给新订阅者，
+    /// 因此应在所有 Actor 完成订阅后（例如 `StartupBarrier` 之后）再调用。
+    /// 消息类型必须已在本总线上通过 `with_message_store` 登记，否则返回 `BusError::UnknownType`，且不会重放任何消息。
+    pub async fn restore_state(&self, state: BusState) -> Result<(), BusError> {
+        let replays = {
+            let store = self.store.as_ref().map(|store| store.lock().unwrap());
+            state
+                .messages
+                .iter()
+                .map(|stored| {
+                    store
+                        .as_ref()
+                        .and_then(|store| store.replay_fn(&stored.type_name))
+                        .ok_or_else(|| BusError::UnknownType { type_name: stored.type_name.clone() })
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        {
+            let channels = self.channels.read().await;
+            let existing: HashSet<(&str, &str)> =
+                channels.iter().map(|((_, namespace), channel)| (channel.type_name(), namespace.as_ref())).collect();
+            let mut restored = self.restored_capacities.lock().unwrap();
+            for channel in state.channels {
+                if !existing.contains(&(channel.type_name.as_str(), channel.namespace.as_str())) {
+                    restored.insert((channel.type_name, channel.namespace), clamp_capacity(channel.capacity));
+                }
+            }
+        }
+
+        for (stored, replay) in state.messages.into_iter().zip(replays) {
+            replay(self.with_namespace(&stored.envelope.namespace), stored.envelope.message).await?;
+        }
+        Ok(())
+    }
+
+    /// ## `register_message`
+    ///
+    /// 以名称 `name` 登记消息类型 `M`，供 `publish_json` / `subscribe_json` 按名称使用。
+    /// 同一名称再次登记时覆盖之前的类型。登记对所有命名空间视图生效。
+    pub fn register_message<M: Message + Serialize + DeserializeOwned>(&self, name: &str) {
+        let topic = DynamicTopic::of::<M>();
+        let previous = self.topics.write().unwrap().insert(name.to_string(), topic);
+        if let Some(previous) = previous.filter(|previous| previous.type_id != topic.type_id) {
+            tracing::warn!(target: "BUS", "Topic {:?} re-registered from {} to {}", name, previous.type_name, topic.type_name);
+        }
+    }
+
+    /// 所有已登记的名称，按字母顺序排列。
+    pub fn registered_topics(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.topics.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    fn topic(&self, name: &str) -> Result<DynamicTopic, BusError> {
+        self.topics.read().unwrap().get(name).copied().ok_or_else(|| BusError::UnknownTopic { name: name.to_string() })
+    }
+
+    /// ## `publish_json`
+    ///
+    /// 把 `json` 反序列化为 `name` 对应的消息类型后发布，强类型订阅者收到的是解析后的消息。
+    /// 名称未登记时返回 `BusError::UnknownTopic`，JSON 无法解析时返回 `BusError::Deserialize`。
+    pub async fn publish_json(&self, name: &str, json: &str) -> Result<usize, BusError> {
+        let topic = self.topic(name)?;
+        (topic.publish)(self.clone(), json.to_string()).await
+    }
+
+    /// ## `subscribe_json`
+    ///
+    /// 订阅 `name` 对应的消息类型，每条消息序列化为一个 JSON 字符串。
+    /// 落后时跳过丢失的消息并记录警告，通道关闭时流结束。
+    /// 名称未登记时返回 `BusError::UnknownTopic`，该类型的订阅者已达到上限时返回 `BusError::SubscriberLimit`。
+    pub async fn subscribe_json(&self, name: &str) -> Result<BoxStream<'static, String>, BusError> {
+        let topic = self.topic(name)?;
+        (topic.subscribe)(self.clone()).await
+    }
+
+    /// ## `blocking_publish`
+    ///
+    /// `publish` 的同步版本，供 GUI 线程、FFI 边界等非异步代码使用。
+    ///
+    /// **重入限制**：此方法会阻塞当前线程直到发布完成，
+    /// 因此绝不能在异步上下文（任何 tokio 运行时的工作线程或 `async` 块）中调用。
+    /// 在异步上下文中调用会直接返回错误，而不是死锁或 panic。
+    pub fn blocking_publish<M: Message>(&self, msg: M) -> Result<usize, BusError> {
+        let handle = self.blocking_handle()?;
+        handle.block_on(self.publish(msg))
+    }
+
+    /// ## `blocking_subscribe`
+    ///
+    /// `subscribe` 的同步版本。返回的 `Receiver` 可以用 `blocking_recv` 在同步代码中接收消息。
+    ///
+    /// **重入限制**：与 `blocking_publish` 相同，不能在异步上下文中调用。
+    pub fn blocking_subscribe<M: Message>(&self) -> Result<Receiver<M>, BusError> {
+        let handle = self.blocking_handle()?;
+        handle.block_on(self.try_subscribe::<M>())
+    }
+
+    /// 获取用于阻塞调用的运行时句柄，并拒绝来自异步上下文的调用。
+    fn blocking_handle(&self) -> Result<&Handle, BusError> {
+        if Handle::try_current().is_ok() {
+            return Err(BusError::BlockingInAsyncContext);
+        }
+        self.runtime.as_ref().ok_or(BusError::NoRuntime)
     }
 }
\ No newline at end of file
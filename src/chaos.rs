@@ -0,0 +1,133 @@
+// src/chaos.rs
+
+//! # 故障注入模块 (chaos)
+//!
+//! 模拟不可靠的网络：按消息类型以给定概率丢弃、延迟或重复发布的消息，
+//! 用来验证去重、缺口检测与重试等机制在恶劣条件下确实有效。
+//! 通过总线的 `PublishInterceptor` 钩子生效，随机数由种子决定，同一发布序列的故障可以复现。
+//! 只有启用 `chaos` feature 或设置环境变量 `MESSAGE_BUS_CHAOS` 时才会安装，避免误用于生产环境。
+
+use crate::bus::{Interception, MessageBus, PublishInterceptor};
+use crate::message::Message;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 启用故障注入的环境变量，值不为空且不为 `0` 时生效。
+pub const CHAOS_ENV: &str = "MESSAGE_BUS_CHAOS";
+
+/// 当前是否允许注入故障：启用了 `chaos` feature，或设置了 `MESSAGE_BUS_CHAOS`。
+pub fn chaos_enabled() -> bool {
+    cfg!(feature = "chaos") || std::env::var(CHAOS_ENV).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+/// ## `ChaosRule`
+///
+/// 一种消息类型的故障概率，取值 `[0, 1]`。每条消息至多遭遇一种故障，
+/// 依次判定丢弃、延迟、重复，三者之和不应超过 1。
+#[derive(Clone, Debug, Default)]
+pub struct ChaosRule {
+    /// 丢弃的概率。
+    pub drop: f64,
+    /// 延迟投递的概率。
+    pub delay: f64,
+    /// 延迟时长在 `[0, max_delay]` 内均匀分布。
+    pub max_delay: Duration,
+    /// 重复投递的概率。
+    pub duplicate: f64,
+}
+
+/// 已注入的故障计数。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    pub dropped: u64,
+    pub delayed: u64,
+    pub duplicated: u64,
+}
+
+/// ## `ChaosInterceptor`
+///
+/// 按消息类型注入故障的发布拦截器。没有规则的类型正常投递。
+///
+/// ```ignore
+/// let chaos = ChaosInterceptor::new(42)
+///     .with_rule::<OrderRequest>(ChaosRule { duplicate: 0.1, ..Default::default() })
+///     .install(&bus);
+/// ```
+pub struct ChaosInterceptor {
+    rules: HashMap<TypeId, ChaosRule>,
+    namespace: Option<String>,
+    state: Mutex<(StdRng, ChaosStats)>,
+}
+
+impl ChaosInterceptor {
+    /// 以 `seed` 初始化随机数，相同的种子与发布顺序得到相同的故障序列。
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rules: HashMap::new(),
+            namespace: None,
+            state: Mutex::new((StdRng::seed_from_u64(seed), ChaosStats::default())),
+        }
+    }
+
+    /// 为消息类型 `M` 设置故障规则，覆盖之前的规则。
+    pub fn with_rule<M: Message>(mut self, rule: ChaosRule) -> Self {
+        self.rules.insert(TypeId::of::<M>(), rule);
+        self
+    }
+
+    /// 只对在命名空间 `namespace` 中发布的消息注入故障（默认所有命名空间）。
+    /// 例如只扰动数据源所在的视图，而不影响 `Deduplicator` 转发到其他视图的消息。
+    pub fn in_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// 在 `bus` 上安装拦截器，返回的句柄可以读取 `stats`。
+    /// 未启用故障注入（见 `chaos_enabled`）时不安装并返回 `None`。
+    pub fn install(self, bus: &MessageBus) -> Option<Arc<Self>> {
+        if !chaos_enabled() {
+            tracing::warn!(target: "CHAOS", "Chaos injection disabled; enable the `chaos` feature or set {}", CHAOS_ENV);
+            return None;
+        }
+        let interceptor = Arc::new(self);
+        bus.add_interceptor(interceptor.clone());
+        tracing::warn!(target: "CHAOS", "Chaos injection enabled for {} message types", interceptor.rules.len());
+        Some(interceptor)
+    }
+
+    pub fn stats(&self) -> ChaosStats {
+        self.state.lock().unwrap().1
+    }
+}
+
+impl PublishInterceptor for ChaosInterceptor {
+    fn intercept(&self, type_id: TypeId, type_name: &'static str, namespace: &str) -> Interception {
+        let Some(rule) = self.rules.get(&type_id) else { return Interception::Deliver };
+        if self.namespace.as_deref().is_some_and(|only| only != namespace) {
+            return Interception::Deliver;
+        }
+        let mut state = self.state.lock().unwrap();
+        let (rng, stats) = &mut *state;
+        let roll: f64 = rng.gen();
+        if roll < rule.drop {
+            stats.dropped += 1;
+            tracing::debug!(target: "CHAOS", "Dropped {}", type_name);
+            Interception::Drop
+        } else if roll < rule.drop + rule.delay {
+            stats.delayed += 1;
+            let delay = Duration::from_nanos(rng.gen_range(0..=rule.max_delay.as_nanos() as u64));
+            tracing::debug!(target: "CHAOS", "Delayed {} by {:?}", type_name, delay);
+            Interception::Delay(delay)
+        } else if roll < rule.drop + rule.delay + rule.duplicate {
+            stats.duplicated += 1;
+            tracing::debug!(target: "CHAOS", "Duplicated {}", type_name);
+            Interception::Duplicate
+        } else {
+            Interception::Deliver
+        }
+    }
+}
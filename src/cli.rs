@@ -0,0 +1,90 @@
+// src/cli.rs
+
+//! # 命令行模块 (cli)
+//!
+//! 演示程序的命令行参数。命令行只覆盖配置文件中对应的项，
+//! 合并后的结果仍是 `AppConfig`，与只使用配置文件时走同一条校验路径。
+
+use crate::config::{parse_duration, AppConfig, ConfigError, RunMode};
+use clap::Parser;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// ## `Cli`
+///
+/// 未给出的参数保持配置文件（或默认配置）中的值；`--symbol` 给出任意一次即替换配置中的整个列表。
+#[derive(Debug, Parser)]
+#[command(name = "message-bus", version, about = "运行消息总线交易演示程序", long_about = None)]
+pub struct Cli {
+    /// TOML 配置文件，命令行参数覆盖其中的值
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// 运行模式 [默认: live]
+    #[arg(long, value_enum)]
+    pub mode: Option<RunMode>,
+
+    /// 交易的 symbol，多个 symbol 时重复给出 [默认: BTC-USD]
+    #[arg(long = "symbol", value_name = "SYMBOL")]
+    pub symbols: Vec<String>,
+
+    /// 运行时长，例如 500ms、5s、2m [默认: 5s；回测模式下不可用]
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub duration: Option<Duration>,
+
+    /// 连续这么久没有消息发布时提前结束，例如 2s [仅 live 模式]
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub quiet_period: Option<Duration>,
+
+    /// 每个消息通道的容量 [默认: 1024]
+    #[arg(long, value_name = "N")]
+    pub bus_capacity: Option<usize>,
+
+    /// 模拟价格与成交随机性的种子
+    #[arg(long, value_name = "N")]
+    pub seed: Option<u64>,
+
+    /// 回测模式重放的 Bar 文件，每行一个 JSON 编码的 Bar
+    #[arg(long, value_name = "PATH")]
+    pub data_file: Option<PathBuf>,
+
+    /// 日志过滤规则，例如 info 或 info,DATA=debug [默认: info]
+    #[arg(long, value_name = "LEVEL")]
+    pub log_level: Option<String>,
+}
+
+impl Cli {
+    /// 读取 `--config`（若有），用命令行参数覆盖，再校验合并后的配置。
+    pub fn into_config(self) -> Result<AppConfig, ConfigError> {
+        let mut config = match &self.config {
+            Some(path) => AppConfig::load(path)?,
+            None => AppConfig::default(),
+        };
+        if let Some(mode) = self.mode {
+            config.mode = mode;
+        }
+        if !self.symbols.is_empty() {
+            config.symbols = self.symbols;
+        }
+        if let Some(duration) = self.duration {
+            config.duration = Some(duration);
+        }
+        if let Some(quiet_period) = self.quiet_period {
+            config.quiet_period = Some(quiet_period);
+        }
+        if let Some(bus_capacity) = self.bus_capacity {
+            config.bus_capacity = bus_capacity;
+        }
+        if let Some(seed) = self.seed {
+            config.seed = Some(seed);
+        }
+        if let Some(data_file) = self.data_file {
+            config.data_file = Some(data_file);
+        }
+        if let Some(log_level) = self.log_level {
+            config.log_level = log_level;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
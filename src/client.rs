@@ -0,0 +1,225 @@
+// src/client.rs
+
+//! # 执行客户端模块 (client)
+//!
+//! 把“订单如何到达交易场所”抽象为 `ExecutionClient` trait，
+//! 由通用的 `ExecutionEngine` Actor 负责与总线交互。
+//! 模拟与实盘之间的切换只需要在组装时换一个客户端。
+
+use crate::actor::{spawn_named, Actor, Affinity};
+use crate::bus::{BusError, MessageBus};
+use crate::message::{
+    Bar, CancelOrderRequest, CancelRejectReason, CancelRejected, FillEvent, Message, OrderAccepted, OrderCanceled,
+    OrderRejected, OrderRequest, OrderTriggered, RejectReason,
+};
+use crate::startup::StartupBarrierHandle;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::info;
+use uuid::Uuid;
+
+/// 交易场所已收到订单的同步确认。订单是否被接受仍以异步的 `ExecutionReport` 为准。
+#[derive(Clone, Debug)]
+pub struct ExecAck {
+    pub order_id: Uuid,
+    /// 交易场所分配的订单 ID（如果有）。
+    pub venue_order_id: Option<String>,
+    pub ts_event: u64,
+}
+
+/// ## `ExecError`
+///
+/// 提交或撤销订单失败的原因。
+#[derive(Debug)]
+pub enum ExecError {
+    /// 交易场所同步拒绝了订单。
+    Rejected { reason: RejectReason, detail: String },
+    /// 交易场所不认识该订单。
+    UnknownOrder(Uuid),
+    /// 与交易场所通信失败。
+    Transport(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for ExecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecError::Rejected { reason, detail } => write!(f, "order rejected ({}): {}", reason, detail),
+            ExecError::UnknownOrder(order_id) => write!(f, "unknown order {}", order_id),
+            ExecError::Transport(e) => write!(f, "transport error: {}", e),
+        }
+    }
+}
+
+impl Error for ExecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExecError::Transport(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// 交易场所异步推送的订单回报。
+#[derive(Clone, Debug)]
+pub enum ExecutionReport {
+    Accepted(OrderAccepted),
+    Triggered(OrderTriggered),
+    Fill(FillEvent),
+    Rejected(OrderRejected),
+    Canceled(OrderCanceled),
+    CancelRejected(CancelRejected),
+}
+
+/// ## `ExecutionClient` Trait
+///
+/// 与一个交易场所（模拟或实盘）交互的客户端。
+#[async_trait::async_trait]
+pub trait ExecutionClient: Send + Sync + 'static {
+    /// 建立连接并启动客户端自己的后台任务（例如轮询成交）。
+    /// `actor_name` 为所属 `ExecutionEngine` 的 Actor 名称，后台任务应以 `spawn_named` 用它命名。
+    async fn connect(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>>;
+
+    /// 提交订单。返回 `Ok` 只表示交易场所已收到，后续状态通过 `next_report` 获得。
+    async fn submit(&self, order: OrderRequest) -> Result<ExecAck, ExecError>;
+
+    /// 请求撤单。撤单结果通过 `next_report` 获得。
+    async fn cancel(&self, cancel: CancelOrderRequest) -> Result<(), ExecError>;
+
+    /// 行情更新。模拟客户端用它撮合挂单，实盘客户端通常忽略。
+    async fn on_bar(&self, _bar: &Bar) {}
+
+    /// 等待下一条异步回报，连接关闭时返回 `None`。必须是取消安全的。
+    async fn next_report(&self) -> Option<ExecutionReport>;
+}
+
+/// ## `ExecutionEngine`
+///
+/// 通用执行 Actor，在总线与 `ExecutionClient` 之间转换：
+/// - 消费 `OrderRequest` / `CancelOrderRequest` 消息，调用客户端的 `submit` / `cancel`；
+///   同步失败分别以 `OrderRejected` / `CancelRejected` 发布。
+/// - 消费 `Bar` 消息并转交给客户端。
+/// - 把客户端的 `ExecutionReport` 作为对应的消息发布到总线。
+///
+/// 切换模拟与实盘只需替换客户端：
+///
+/// ```ignore
+/// let engine = ExecutionEngine::new(bus.clone(), SimulatedExecutionEngine::new(MessageBus::new(1024)));
+/// let engine = ExecutionEngine::new(bus.clone(), RestExecutionClient::new(config));
+/// ```
+pub struct ExecutionEngine<C: ExecutionClient> {
+    bus: MessageBus,
+    client: Arc<C>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl<C: ExecutionClient> ExecutionEngine<C> {
+    pub fn new(bus: MessageBus, client: C) -> Self {
+        Self { bus, client: Arc::new(client), barrier: Mutex::new(None) }
+    }
+
+    /// 设置启动屏障，连接客户端并完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 底层客户端。
+    pub fn client(&self) -> &Arc<C> {
+        &self.client
+    }
+
+    async fn publish<M: Message>(&self, msg: M) {
+        info!(target: "EXECUTION", "Publishing {:?}", msg);
+        if let Err(e) = self.bus.publish(msg).await {
+            tracing::error!(target: "EXECUTION", "Failed to publish: {}", e);
+        }
+    }
+
+    async fn handle_order(&self, order: OrderRequest) {
+        let _timer = self.bus.actor_metrics().start_timer("EXECUTION");
+        let (order_id, symbol) = (order.id, order.symbol.clone());
+        match self.client.submit(order).await {
+            Ok(ack) => tracing::debug!(target: "EXECUTION", "Venue received order {} ({:?})", ack.order_id, ack.venue_order_id),
+            Err(ExecError::Rejected { reason, detail }) => {
+                self.publish(OrderRejected { order_id, symbol, reason, detail }).await;
+            },
+            Err(e) => {
+                let detail = e.to_string();
+                self.publish(OrderRejected { order_id, symbol, reason: RejectReason::VenueRejected, detail }).await;
+            },
+        }
+    }
+
+    async fn handle_cancel(&self, cancel: CancelOrderRequest) {
+        let _timer = self.bus.actor_metrics().start_timer("EXECUTION");
+        let order_id = cancel.order_id;
+        match self.client.cancel(cancel).await {
+            Ok(()) => {},
+            Err(ExecError::UnknownOrder(_)) => {
+                self.publish(CancelRejected { order_id, reason: CancelRejectReason::UnknownOrder }).await;
+            },
+            Err(e) => tracing::error!(target: "EXECUTION", "Cancel for order {} failed: {}", order_id, e),
+        }
+    }
+
+    async fn handle_report(&self, report: ExecutionReport) {
+        let _timer = self.bus.actor_metrics().start_timer("EXECUTION");
+        match report {
+            ExecutionReport::Accepted(accepted) => self.publish(accepted).await,
+            ExecutionReport::Triggered(triggered) => self.publish(triggered).await,
+            ExecutionReport::Fill(fill) => self.publish(fill).await,
+            ExecutionReport::Rejected(rejected) => self.publish(rejected).await,
+            ExecutionReport::Canceled(canceled) => self.publish(canceled).await,
+            ExecutionReport::CancelRejected(rejected) => self.publish(rejected).await,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: ExecutionClient> Actor for ExecutionEngine<C> {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut handles = self.client.clone().connect(actor_name).await;
+        let mut order_rx = self.bus.subscribe::<OrderRequest>().await;
+        let mut cancel_rx = self.bus.subscribe::<CancelOrderRequest>().await;
+        let mut bar_rx = self.bus.subscribe::<Bar>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        handles.push(spawn_named(actor_name, async move {
+            loop {
+                tokio::select! {
+                    report = self.client.next_report() => match report {
+                        Some(report) => self.handle_report(report).await,
+                        None => break,
+                    },
+                    result = order_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("EXECUTION", |order| self.handle_order(order)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<OrderRequest>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = cancel_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("EXECUTION", |cancel| self.handle_cancel(cancel)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<CancelOrderRequest>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = bar_rx.recv() => match result {
+                        Ok(bar) => self.client.on_bar(&bar).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<Bar>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        }));
+
+        handles
+    }
+
+    /// 执行路径对延迟敏感，由 `ActorSystem` 启动时运行在专用运行时上。
+    fn affinity(&self) -> Affinity {
+        Affinity::Dedicated
+    }
+}
@@ -0,0 +1,162 @@
+// src/clock.rs
+
+//! # 时钟模块 (clock)
+//!
+//! 为系统提供统一的时间来源。
+//! 实时运行使用 `LiveClock`，回测/模拟使用由事件驱动的 `VirtualClock`。
+//! 生产者的 `ts_event` 应统一取自 `Clock::now_nanos`，而不是各自读取系统时间。
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+
+/// ## `Clock` Trait
+///
+/// 返回自 UNIX 纪元以来的纳秒数，并支持等待到某个时间点。
+/// 实现应保证 `now_nanos` 不会倒退，事件时间的先后顺序依赖于此。
+/// 需要定时行为的 Actor（例如执行延迟）应通过此 trait 而不是直接使用 tokio 定时器，
+/// 这样在模拟模式下它们会跟随虚拟时间运行。
+#[async_trait::async_trait]
+pub trait Clock: Send + Sync {
+    fn now_nanos(&self) -> u64;
+
+    /// 等待直到时钟到达 `ts_nanos`。如果已经到达则立即返回。
+    async fn sleep_until(&self, ts_nanos: u64);
+}
+
+/// ## `LiveClock`
+///
+/// 基于系统时间的实时时钟。系统时间被向后调整时，返回值停留在此前见过的最大值，
+/// 直到系统时间重新追上；所有 `LiveClock` 实例共享同一个下限，进程内的时间戳整体单调不减。
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LiveClock;
+
+/// 所有 `LiveClock` 返回过的最大时间。
+static LIVE_LAST_NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// 当前系统时间的纳秒数。早于 UNIX 纪元时为 0，超出 `u64` 范围（约 2554 年之后）时为 `u64::MAX`。
+fn system_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX))
+        .unwrap_or(0)
+}
+
+/// 把 `raw` 与 `last` 中记录的最大值比较，返回两者中较大的一个并更新 `last`。
+fn clamp_monotonic(last: &AtomicU64, raw: u64) -> u64 {
+    let previous = last.fetch_max(raw, Ordering::AcqRel);
+    previous.max(raw)
+}
+
+#[async_trait::async_trait]
+impl Clock for LiveClock {
+    fn now_nanos(&self) -> u64 {
+        clamp_monotonic(&LIVE_LAST_NANOS, system_nanos())
+    }
+
+    async fn sleep_until(&self, ts_nanos: u64) {
+        let now = self.now_nanos();
+        if ts_nanos > now {
+            tokio::time::sleep(Duration::from_nanos(ts_nanos - now)).await;
+        }
+    }
+}
+
+/// ## `Monotonic`
+///
+/// 为任意时钟加上单调性保证：`now_nanos` 返回内部时钟与此前返回过的最大值中较大的一个。
+/// 用于包装来源不可靠（例如外部注入、可能被调整）的时钟。
+#[derive(Debug)]
+pub struct Monotonic<C> {
+    inner: C,
+    last: AtomicU64,
+}
+
+impl<C: Clock> Monotonic<C> {
+    pub fn new(inner: C) -> Self {
+        Self { inner, last: AtomicU64::new(0) }
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: Clock> Clock for Monotonic<C> {
+    fn now_nanos(&self) -> u64 {
+        clamp_monotonic(&self.last, self.inner.now_nanos())
+    }
+
+    async fn sleep_until(&self, ts_nanos: u64) {
+        self.inner.sleep_until(ts_nanos).await
+    }
+}
+
+/// ## `VirtualClock`
+///
+/// 由外部驱动的虚拟时钟，时间只会在调用 `advance_to` 时前进。
+/// 所有 `sleep_until` 的截止时间都会被记录下来，
+/// 驱动器（`SimulationDriver`）据此把时间推进到下一个需要唤醒的时刻。
+#[derive(Debug)]
+pub struct VirtualClock {
+    now: watch::Sender<u64>,
+    deadlines: Mutex<BinaryHeap<Reverse<u64>>>,
+}
+
+impl VirtualClock {
+    pub fn new(start_nanos: u64) -> Self {
+        Self {
+            now: watch::Sender::new(start_nanos),
+            deadlines: Mutex::new(BinaryHeap::new()),
+        }
+    }
+
+    /// 将时钟推进到 `ts_nanos` 并唤醒所有已到期的等待者。
+    /// 时间不会倒退：早于当前时间的值会被忽略。
+    pub fn advance_to(&self, ts_nanos: u64) {
+        self.now.send_if_modified(|now| {
+            if ts_nanos > *now {
+                *now = ts_nanos;
+                true
+            } else {
+                false
+            }
+        });
+        let now = *self.now.borrow();
+        let mut deadlines = self.deadlines.lock().unwrap();
+        while deadlines.peek().is_some_and(|Reverse(ts)| *ts <= now) {
+            deadlines.pop();
+        }
+    }
+
+    /// 最早的一个尚未到期的等待截止时间。
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.deadlines.lock().unwrap().peek().map(|Reverse(ts)| *ts)
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for VirtualClock {
+    fn now_nanos(&self) -> u64 {
+        *self.now.borrow()
+    }
+
+    async fn sleep_until(&self, ts_nanos: u64) {
+        let mut rx = self.now.subscribe();
+        if *rx.borrow_and_update() >= ts_nanos {
+            return;
+        }
+        self.deadlines.lock().unwrap().push(Reverse(ts_nanos));
+        while *rx.borrow_and_update() < ts_nanos {
+            if rx.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,630 @@
+// src/codec.rs
+
+//! # 线上编码模块 (codec)
+//!
+//! 在进程之间传输总线消息时使用的编码。每条消息外面包一层信封，记录消息类型名与格式版本，
+//! 接收端据此选择消息类型并拒绝不认识的版本。
+//! - `JsonCodec`：信封与消息都是 JSON，便于调试。
+//! - `MessagePackCodec` / `BincodeCodec`：经 serde 派生编码为 MessagePack 或 bincode，适合高频的流式消费者。
+//! - `ProtobufCodec`：按 `proto/wire.proto` 编码，体积小、类型严格，适合跨语言的消费者（需启用 `protobuf` feature）。
+//!
+//! 网桥通过配置中的 `wire_format = "json" | "messagepack" | "bincode" | "protobuf"`（`WireFormat`）选择编码。
+//! 面向连接的传输可以在每条连接开始时握手：客户端用 `request_format` 声明偏好的格式，
+//! 服务端用 `accept_format` 确认（或拒绝）后双方使用同一个 `Codec`。
+
+use crate::bus::{BusError, MessageBus};
+use crate::message::{Bar, FillEvent, OrderRequest};
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// 当前的线上格式版本，写入每个信封的 `schema_version`。
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// 握手行的前缀：客户端发送 `WIRE <格式>\n`，服务端回复 `OK <格式>\n` 或 `ERR <原因>\n`。
+pub const HANDSHAKE_PREFIX: &str = "WIRE";
+
+/// 握手行的最大长度（字节，含换行）。
+const MAX_HANDSHAKE_LINE: usize = 128;
+
+/// ## `WireMessage`
+///
+/// 可以在线上传输的消息。
+#[derive(Clone, Debug, PartialEq)]
+pub enum WireMessage {
+    Bar(Bar),
+    OrderRequest(OrderRequest),
+    FillEvent(FillEvent),
+}
+
+impl WireMessage {
+    /// 信封中的消息类型名。
+    pub fn type_id(&self) -> &'static str {
+        match self {
+            WireMessage::Bar(_) => "Bar",
+            WireMessage::OrderRequest(_) => "OrderRequest",
+            WireMessage::FillEvent(_) => "FillEvent",
+        }
+    }
+
+    /// 消息所属的 symbol。
+    pub fn symbol(&self) -> &str {
+        match self {
+            WireMessage::Bar(bar) => &bar.symbol,
+            WireMessage::OrderRequest(order) => &order.symbol,
+            WireMessage::FillEvent(fill) => &fill.symbol,
+        }
+    }
+
+    /// 以具体的消息类型发布到总线。
+    pub async fn publish(self, bus: &MessageBus) -> Result<usize, BusError> {
+        match self {
+            WireMessage::Bar(bar) => bus.publish(bar).await,
+            WireMessage::OrderRequest(order) => bus.publish(order).await,
+            WireMessage::FillEvent(fill) => bus.publish(fill).await,
+        }
+    }
+}
+
+impl From<Bar> for WireMessage {
+    fn from(bar: Bar) -> Self {
+        WireMessage::Bar(bar)
+    }
+}
+
+impl From<OrderRequest> for WireMessage {
+    fn from(order: OrderRequest) -> Self {
+        WireMessage::OrderRequest(order)
+    }
+}
+
+impl From<FillEvent> for WireMessage {
+    fn from(fill: FillEvent) -> Self {
+        WireMessage::FillEvent(fill)
+    }
+}
+
+/// ## `CodecError`
+///
+/// 编码或解码失败的原因。
+#[derive(Debug)]
+pub enum CodecError {
+    /// 信封中的消息类型名不认识。
+    UnknownType(String),
+    /// 信封的格式版本与 `SCHEMA_VERSION` 不同。
+    UnsupportedVersion(u32),
+    /// 字段的值无法转换为消息类型，例如长度不是 16 字节的 UUID。
+    InvalidField { field: &'static str, reason: String },
+    Json(serde_json::Error),
+    MessagePackEncode(rmp_serde::encode::Error),
+    MessagePackDecode(rmp_serde::decode::Error),
+    Bincode(bincode::Error),
+    #[cfg(feature = "protobuf")]
+    Protobuf(prost::DecodeError),
+    /// 选择了未编译进来的格式（`protobuf` feature 未启用）。
+    FormatUnavailable(WireFormat),
+    /// 握手失败：对方的握手行无法解析，或拒绝了请求的格式。
+    Handshake(String),
+    /// 握手时读写连接失败。
+    Io(io::Error),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::UnknownType(type_id) => write!(f, "unknown message type {:?}", type_id),
+            CodecError::UnsupportedVersion(version) => {
+                write!(f, "unsupported schema version {} (expected {})", version, SCHEMA_VERSION)
+            },
+            CodecError::InvalidField { field, reason } => write!(f, "invalid field {}: {}", field, reason),
+            CodecError::Json(e) => write!(f, "invalid JSON: {}", e),
+            CodecError::MessagePackEncode(e) => write!(f, "failed to encode MessagePack: {}", e),
+            CodecError::MessagePackDecode(e) => write!(f, "invalid MessagePack: {}", e),
+            CodecError::Bincode(e) => write!(f, "invalid bincode: {}", e),
+            #[cfg(feature = "protobuf")]
+            CodecError::Protobuf(e) => write!(f, "invalid protobuf: {}", e),
+            CodecError::FormatUnavailable(format) => {
+                write!(f, "wire format {:?} is not available; enable the `protobuf` feature", format)
+            },
+            CodecError::Handshake(reason) => write!(f, "wire format handshake failed: {}", reason),
+            CodecError::Io(e) => write!(f, "wire format handshake I/O error: {}", e),
+        }
+    }
+}
+
+impl Error for CodecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CodecError::Json(e) => Some(e),
+            CodecError::MessagePackEncode(e) => Some(e),
+            CodecError::MessagePackDecode(e) => Some(e),
+            CodecError::Bincode(e) => Some(e),
+            #[cfg(feature = "protobuf")]
+            CodecError::Protobuf(e) => Some(e),
+            CodecError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for CodecError {
+    fn from(e: serde_json::Error) -> Self {
+        CodecError::Json(e)
+    }
+}
+
+impl From<io::Error> for CodecError {
+    fn from(e: io::Error) -> Self {
+        CodecError::Io(e)
+    }
+}
+
+/// ## `Codec` Trait
+///
+/// 在 `WireMessage` 与线上字节之间转换，每次调用处理一条完整的消息（分帧由传输层负责）。
+pub trait Codec: Send + Sync {
+    fn format(&self) -> WireFormat;
+
+    fn encode(&self, msg: &WireMessage) -> Result<Vec<u8>, CodecError>;
+
+    fn decode(&self, bytes: &[u8]) -> Result<WireMessage, CodecError>;
+}
+
+/// ## `WireFormat`
+///
+/// 网桥配置中的 `wire_format`。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireFormat {
+    #[default]
+    Json,
+    #[serde(alias = "msgpack")]
+    MessagePack,
+    Bincode,
+    Protobuf,
+}
+
+impl WireFormat {
+    /// 配置与握手中使用的名称。
+    pub fn name(self) -> &'static str {
+        match self {
+            WireFormat::Json => "json",
+            WireFormat::MessagePack => "messagepack",
+            WireFormat::Bincode => "bincode",
+            WireFormat::Protobuf => "protobuf",
+        }
+    }
+
+    /// 按名称查找格式，接受与配置相同的名称（以及 `msgpack`）。
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(WireFormat::Json),
+            "messagepack" | "msgpack" => Some(WireFormat::MessagePack),
+            "bincode" => Some(WireFormat::Bincode),
+            "protobuf" => Some(WireFormat::Protobuf),
+            _ => None,
+        }
+    }
+
+    /// 创建该格式的编解码器。未启用 `protobuf` feature 时选择 `Protobuf` 返回 `CodecError::FormatUnavailable`。
+    pub fn codec(self) -> Result<Box<dyn Codec>, CodecError> {
+        match self {
+            WireFormat::Json => Ok(Box::new(JsonCodec)),
+            WireFormat::MessagePack => Ok(Box::new(MessagePackCodec)),
+            WireFormat::Bincode => Ok(Box::new(BincodeCodec)),
+            #[cfg(feature = "protobuf")]
+            WireFormat::Protobuf => Ok(Box::new(ProtobufCodec)),
+            #[cfg(not(feature = "protobuf"))]
+            WireFormat::Protobuf => Err(CodecError::FormatUnavailable(self)),
+        }
+    }
+}
+
+/// JSON 信封：`{"type_id": "Bar", "schema_version": 1, "payload": {...}}`。
+#[derive(Serialize, Deserialize)]
+struct JsonEnvelope {
+    type_id: String,
+    schema_version: u32,
+    payload: serde_json::Value,
+}
+
+/// ## `JsonCodec`
+///
+/// 以 JSON 编码，消息体与 `serde` 的序列化结果相同。
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn format(&self) -> WireFormat {
+        WireFormat::Json
+    }
+
+    fn encode(&self, msg: &WireMessage) -> Result<Vec<u8>, CodecError> {
+        let payload = match msg {
+            WireMessage::Bar(bar) => serde_json::to_value(bar)?,
+            WireMessage::OrderRequest(order) => serde_json::to_value(order)?,
+            WireMessage::FillEvent(fill) => serde_json::to_value(fill)?,
+        };
+        let envelope = JsonEnvelope { type_id: msg.type_id().to_string(), schema_version: SCHEMA_VERSION, payload };
+        Ok(serde_json::to_vec(&envelope)?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<WireMessage, CodecError> {
+        let envelope: JsonEnvelope = serde_json::from_slice(bytes)?;
+        if envelope.schema_version != SCHEMA_VERSION {
+            return Err(CodecError::UnsupportedVersion(envelope.schema_version));
+        }
+        Ok(match envelope.type_id.as_str() {
+            "Bar" => WireMessage::Bar(serde_json::from_value(envelope.payload)?),
+            "OrderRequest" => WireMessage::OrderRequest(serde_json::from_value(envelope.payload)?),
+            "FillEvent" => WireMessage::FillEvent(serde_json::from_value(envelope.payload)?),
+            _ => return Err(CodecError::UnknownType(envelope.type_id)),
+        })
+    }
+}
+
+/// 二进制 serde 格式的读写，`MessagePackCodec` 与 `BincodeCodec` 共用同一套信封逻辑。
+trait SerdeFormat {
+    fn write<T: Serialize>(out: &mut Vec<u8>, value: &T) -> Result<(), CodecError>;
+
+    /// 从 `input` 读出一个值并前移到该值之后。
+    fn read<T: DeserializeOwned>(input: &mut &[u8]) -> Result<T, CodecError>;
+}
+
+/// 二进制信封：依次写入 `(type_id, schema_version)` 与消息本身，接收端先读前者再按类型读消息。
+fn encode_envelope<F: SerdeFormat>(msg: &WireMessage) -> Result<Vec<u8>, CodecError> {
+    let mut out = Vec::new();
+    F::write(&mut out, &(msg.type_id(), SCHEMA_VERSION))?;
+    match msg {
+        WireMessage::Bar(bar) => F::write(&mut out, bar)?,
+        WireMessage::OrderRequest(order) => F::write(&mut out, order)?,
+        WireMessage::FillEvent(fill) => F::write(&mut out, fill)?,
+    }
+    Ok(out)
+}
+
+fn decode_envelope<F: SerdeFormat>(mut bytes: &[u8]) -> Result<WireMessage, CodecError> {
+    let (type_id, schema_version): (String, u32) = F::read(&mut bytes)?;
+    if schema_version != SCHEMA_VERSION {
+        return Err(CodecError::UnsupportedVersion(schema_version));
+    }
+    Ok(match type_id.as_str() {
+        "Bar" => WireMessage::Bar(F::read(&mut bytes)?),
+        "OrderRequest" => WireMessage::OrderRequest(F::read(&mut bytes)?),
+        "FillEvent" => WireMessage::FillEvent(F::read(&mut bytes)?),
+        _ => return Err(CodecError::UnknownType(type_id)),
+    })
+}
+
+/// ## `MessagePackCodec`
+///
+/// 以 MessagePack 编码，结构体按字段顺序写成数组（不带字段名）。
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MessagePackCodec;
+
+impl SerdeFormat for MessagePackCodec {
+    fn write<T: Serialize>(out: &mut Vec<u8>, value: &T) -> Result<(), CodecError> {
+        rmp_serde::encode::write(out, value).map_err(CodecError::MessagePackEncode)
+    }
+
+    fn read<T: DeserializeOwned>(input: &mut &[u8]) -> Result<T, CodecError> {
+        rmp_serde::from_read(input).map_err(CodecError::MessagePackDecode)
+    }
+}
+
+impl Codec for MessagePackCodec {
+    fn format(&self) -> WireFormat {
+        WireFormat::MessagePack
+    }
+
+    fn encode(&self, msg: &WireMessage) -> Result<Vec<u8>, CodecError> {
+        encode_envelope::<Self>(msg)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<WireMessage, CodecError> {
+        decode_envelope::<Self>(bytes)
+    }
+}
+
+/// ## `BincodeCodec`
+///
+/// 以 bincode 编码（变长整数、小端序），是体积最小的格式，但只能被同样使用 serde 派生的 Rust 程序读取。
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BincodeCodec;
+
+impl SerdeFormat for BincodeCodec {
+    fn write<T: Serialize>(out: &mut Vec<u8>, value: &T) -> Result<(), CodecError> {
+        bincode::DefaultOptions::new().serialize_into(out, value).map_err(CodecError::Bincode)
+    }
+
+    fn read<T: DeserializeOwned>(input: &mut &[u8]) -> Result<T, CodecError> {
+        bincode::DefaultOptions::new().deserialize_from(input).map_err(CodecError::Bincode)
+    }
+}
+
+impl Codec for BincodeCodec {
+    fn format(&self) -> WireFormat {
+        WireFormat::Bincode
+    }
+
+    fn encode(&self, msg: &WireMessage) -> Result<Vec<u8>, CodecError> {
+        encode_envelope::<Self>(msg)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<WireMessage, CodecError> {
+        decode_envelope::<Self>(bytes)
+    }
+}
+
+/// 逐字节读取一行握手（不含换行），避免把握手之后的数据读进缓冲。
+async fn read_handshake_line<S: AsyncRead + Unpin>(stream: &mut S) -> Result<String, CodecError> {
+    let mut line = Vec::new();
+    loop {
+        let byte = stream.read_u8().await?;
+        if byte == b'\n' {
+            break;
+        }
+        if line.len() >= MAX_HANDSHAKE_LINE {
+            return Err(CodecError::Handshake("handshake line too long".to_string()));
+        }
+        line.push(byte);
+    }
+    String::from_utf8(line).map_err(|_| CodecError::Handshake("handshake line is not UTF-8".to_string()))
+}
+
+/// 客户端握手：声明偏好的格式并等待服务端确认，返回该格式的编解码器。
+/// 服务端拒绝时返回 `CodecError::Handshake`，其中带有服务端给出的原因。
+pub async fn request_format<S>(stream: &mut S, preferred: WireFormat) -> Result<Box<dyn Codec>, CodecError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let codec = preferred.codec()?;
+    stream.write_all(format!("{} {}\n", HANDSHAKE_PREFIX, preferred.name()).as_bytes()).await?;
+    stream.flush().await?;
+    let reply = read_handshake_line(stream).await?;
+    match reply.split_once(' ') {
+        Some(("OK", name)) if name == preferred.name() => Ok(codec),
+        Some(("ERR", reason)) => Err(CodecError::Handshake(format!("server rejected {}: {}", preferred.name(), reason))),
+        _ => Err(CodecError::Handshake(format!("unexpected reply {:?}", reply))),
+    }
+}
+
+/// 服务端握手：读取客户端声明的格式，在 `supported` 之内且已编译进来时确认并返回编解码器，
+/// 否则回复 `ERR` 并返回 `CodecError::Handshake`。
+pub async fn accept_format<S>(stream: &mut S, supported: &[WireFormat]) -> Result<Box<dyn Codec>, CodecError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let line = read_handshake_line(stream).await?;
+    let requested = match line.split_once(' ') {
+        Some((HANDSHAKE_PREFIX, name)) => WireFormat::from_name(name).ok_or_else(|| format!("unknown wire format {:?}", name)),
+        _ => Err(format!("malformed handshake {:?}", line)),
+    };
+    let codec = requested.and_then(|format| {
+        if !supported.contains(&format) {
+            return Err(format!("wire format {} is not supported", format.name()));
+        }
+        format.codec().map_err(|e| e.to_string())
+    });
+    match codec {
+        Ok(codec) => {
+            stream.write_all(format!("OK {}\n", codec.format().name()).as_bytes()).await?;
+            stream.flush().await?;
+            Ok(codec)
+        },
+        Err(reason) => {
+            stream.write_all(format!("ERR {}\n", reason).as_bytes()).await?;
+            stream.flush().await?;
+            Err(CodecError::Handshake(reason))
+        },
+    }
+}
+
+#[cfg(feature = "protobuf")]
+pub use protobuf::ProtobufCodec;
+
+/// 由 `proto/wire.proto` 生成的线上消息类型。
+#[cfg(feature = "protobuf")]
+pub mod proto {
+    include!(concat!(env!("OUT_DIR"), "/wire.rs"));
+}
+
+#[cfg(feature = "protobuf")]
+mod protobuf {
+    use super::{proto, Codec, CodecError, WireFormat, WireMessage, SCHEMA_VERSION};
+    use crate::message::{Bar, FillEvent, Liquidity, OrderRequest, OrderSide, OrderType};
+    use prost::Message as _;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    /// ## `ProtobufCodec`
+    ///
+    /// 按 `proto/wire.proto` 编码：外层为 `wire.Envelope`，`payload` 为对应消息的编码。
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct ProtobufCodec;
+
+    impl Codec for ProtobufCodec {
+        fn format(&self) -> WireFormat {
+            WireFormat::Protobuf
+        }
+
+        fn encode(&self, msg: &WireMessage) -> Result<Vec<u8>, CodecError> {
+            let payload = match msg {
+                WireMessage::Bar(bar) => bar_to_proto(bar).encode_to_vec(),
+                WireMessage::OrderRequest(order) => order_to_proto(order).encode_to_vec(),
+                WireMessage::FillEvent(fill) => fill_to_proto(fill).encode_to_vec(),
+            };
+            let envelope =
+                proto::Envelope { type_id: msg.type_id().to_string(), schema_version: SCHEMA_VERSION, payload };
+            Ok(envelope.encode_to_vec())
+        }
+
+        fn decode(&self, bytes: &[u8]) -> Result<WireMessage, CodecError> {
+            let envelope = proto::Envelope::decode(bytes).map_err(CodecError::Protobuf)?;
+            if envelope.schema_version != SCHEMA_VERSION {
+                return Err(CodecError::UnsupportedVersion(envelope.schema_version));
+            }
+            let payload = envelope.payload.as_slice();
+            match envelope.type_id.as_str() {
+                "Bar" => bar_from_proto(proto::Bar::decode(payload).map_err(CodecError::Protobuf)?).map(WireMessage::Bar),
+                "OrderRequest" => order_from_proto(proto::OrderRequest::decode(payload).map_err(CodecError::Protobuf)?)
+                    .map(WireMessage::OrderRequest),
+                "FillEvent" => fill_from_proto(proto::FillEvent::decode(payload).map_err(CodecError::Protobuf)?)
+                    .map(WireMessage::FillEvent),
+                _ => Err(CodecError::UnknownType(envelope.type_id)),
+            }
+        }
+    }
+
+    fn uuid_from_bytes(field: &'static str, bytes: &[u8]) -> Result<Uuid, CodecError> {
+        Uuid::from_slice(bytes).map_err(|e| CodecError::InvalidField { field, reason: e.to_string() })
+    }
+
+    fn side_to_proto(side: &OrderSide) -> i32 {
+        match side {
+            OrderSide::Buy => proto::Side::Buy as i32,
+            OrderSide::Sell => proto::Side::Sell as i32,
+        }
+    }
+
+    fn side_from_proto(side: i32) -> Result<OrderSide, CodecError> {
+        match proto::Side::try_from(side) {
+            Ok(proto::Side::Buy) => Ok(OrderSide::Buy),
+            Ok(proto::Side::Sell) => Ok(OrderSide::Sell),
+            _ => Err(CodecError::InvalidField { field: "side", reason: format!("invalid side {}", side) }),
+        }
+    }
+
+    fn order_type_to_proto(order_type: OrderType) -> i32 {
+        let order_type = match order_type {
+            OrderType::Market => proto::OrderType::Market,
+            OrderType::Limit => proto::OrderType::Limit,
+            OrderType::StopMarket => proto::OrderType::StopMarket,
+            OrderType::StopLimit => proto::OrderType::StopLimit,
+        };
+        order_type as i32
+    }
+
+    fn order_type_from_proto(order_type: i32) -> Result<OrderType, CodecError> {
+        match proto::OrderType::try_from(order_type) {
+            Ok(proto::OrderType::Market) => Ok(OrderType::Market),
+            Ok(proto::OrderType::Limit) => Ok(OrderType::Limit),
+            Ok(proto::OrderType::StopMarket) => Ok(OrderType::StopMarket),
+            Ok(proto::OrderType::StopLimit) => Ok(OrderType::StopLimit),
+            _ => Err(CodecError::InvalidField {
+                field: "order_type",
+                reason: format!("invalid order type {}", order_type),
+            }),
+        }
+    }
+
+    fn liquidity_to_proto(liquidity: Liquidity) -> i32 {
+        match liquidity {
+            Liquidity::Maker => proto::Liquidity::Maker as i32,
+            Liquidity::Taker => proto::Liquidity::Taker as i32,
+        }
+    }
+
+    fn liquidity_from_proto(liquidity: i32) -> Result<Liquidity, CodecError> {
+        match proto::Liquidity::try_from(liquidity) {
+            Ok(proto::Liquidity::Maker) => Ok(Liquidity::Maker),
+            Ok(proto::Liquidity::Taker) => Ok(Liquidity::Taker),
+            _ => Err(CodecError::InvalidField {
+                field: "liquidity",
+                reason: format!("invalid liquidity {}", liquidity),
+            }),
+        }
+    }
+
+    fn bar_to_proto(bar: &Bar) -> proto::Bar {
+        proto::Bar {
+            id: bar.id.as_bytes().to_vec(),
+            ts_event: bar.ts_event,
+            symbol: bar.symbol.clone(),
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+            timeframe_nanos: bar.timeframe.as_nanos() as u64,
+        }
+    }
+
+    fn bar_from_proto(bar: proto::Bar) -> Result<Bar, CodecError> {
+        Ok(Bar {
+            id: uuid_from_bytes("id", &bar.id)?,
+            ts_event: bar.ts_event,
+            symbol: bar.symbol,
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+            timeframe: Duration::from_nanos(bar.timeframe_nanos),
+        })
+    }
+
+    fn order_to_proto(order: &OrderRequest) -> proto::OrderRequest {
+        proto::OrderRequest {
+            id: order.id.as_bytes().to_vec(),
+            symbol: order.symbol.clone(),
+            side: side_to_proto(&order.side),
+            order_type: order_type_to_proto(order.order_type),
+            price: order.price,
+            quantity: order.quantity,
+            trigger_price: order.trigger_price,
+        }
+    }
+
+    fn order_from_proto(order: proto::OrderRequest) -> Result<OrderRequest, CodecError> {
+        Ok(OrderRequest {
+            id: uuid_from_bytes("id", &order.id)?,
+            symbol: order.symbol,
+            side: side_from_proto(order.side)?,
+            order_type: order_type_from_proto(order.order_type)?,
+            price: order.price,
+            quantity: order.quantity,
+            trigger_price: order.trigger_price,
+        })
+    }
+
+    fn fill_to_proto(fill: &FillEvent) -> proto::FillEvent {
+        proto::FillEvent {
+            order_id: fill.order_id.as_bytes().to_vec(),
+            symbol: fill.symbol.clone(),
+            side: side_to_proto(&fill.side),
+            price: fill.price,
+            quantity: fill.quantity,
+            leaves_qty: fill.leaves_qty,
+            is_final: fill.is_final,
+            commission: fill.commission,
+            commission_currency: fill.commission_currency.clone(),
+            liquidity: liquidity_to_proto(fill.liquidity),
+            ts_event: fill.ts_event,
+            venue_fill_id: fill.venue_fill_id.clone(),
+            correlation_id: fill.correlation_id.map(|id| id.as_bytes().to_vec()),
+        }
+    }
+
+    fn fill_from_proto(fill: proto::FillEvent) -> Result<FillEvent, CodecError> {
+        Ok(FillEvent {
+            order_id: uuid_from_bytes("order_id", &fill.order_id)?,
+            symbol: fill.symbol,
+            side: side_from_proto(fill.side)?,
+            price: fill.price,
+            quantity: fill.quantity,
+            leaves_qty: fill.leaves_qty,
+            is_final: fill.is_final,
+            commission: fill.commission,
+            commission_currency: fill.commission_currency,
+            liquidity: liquidity_from_proto(fill.liquidity)?,
+            ts_event: fill.ts_event,
+            venue_fill_id: fill.venue_fill_id,
+            correlation_id: fill.correlation_id.map(|id| uuid_from_bytes("correlation_id", &id)).transpose()?,
+        })
+    }
+}
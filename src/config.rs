@@ -0,0 +1,233 @@
+// src/config.rs
+
+//! # 配置模块 (config)
+//!
+//! 演示程序的运行配置 `AppConfig`。配置可以来自 TOML 文件，也可以由命令行覆盖（见 `cli` 模块），
+//! 两者最终都汇总为同一个 `AppConfig`，并在启动前统一校验。
+
+#[cfg(feature = "kafka")]
+use crate::kafka_bridge::KafkaConfig;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing_subscriber::EnvFilter;
+
+/// 未配置时运行的 symbol。
+pub const DEFAULT_SYMBOL: &str = "BTC-USD";
+
+/// 未配置时每种消息类型的通道容量。
+pub const DEFAULT_BUS_CAPACITY: usize = 1024;
+
+/// 实时与模拟模式未配置 `duration` 时的运行时长。
+pub const DEFAULT_RUN_DURATION: Duration = Duration::from_secs(5);
+
+/// ## `RunMode`
+///
+/// 演示程序的运行模式。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RunMode {
+    /// 在虚拟时钟上回放 `data_file` 中录制的行情，以 CPU 允许的最快速度运行。
+    #[value(help = "在虚拟时钟上回放 --data-file 中录制的 Bar")]
+    Backtest,
+    /// 使用实时时钟与模拟数据源，运行 `duration` 后退出。
+    #[default]
+    #[value(help = "使用实时时钟与模拟行情运行")]
+    Live,
+    /// 在虚拟时钟上运行随机游走生成的行情，相同 `seed` 的两次运行结果一致。
+    #[value(help = "在虚拟时钟上运行随机游走行情，相同 --seed 可复现")]
+    Sim,
+}
+
+impl fmt::Display for RunMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunMode::Backtest => write!(f, "backtest"),
+            RunMode::Live => write!(f, "live"),
+            RunMode::Sim => write!(f, "sim"),
+        }
+    }
+}
+
+/// ## `AppConfig`
+///
+/// 演示程序的全部可配置项。TOML 中的字段名与结构体字段相同，未出现的字段取默认值，
+/// 未知字段视为错误。`duration` 写作 `"500ms"`、`"5s"`、`"2m"`、`"1h"` 或秒数。
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    pub mode: RunMode,
+    /// 交易的 symbol，每个 symbol 运行一组数据源、预热与策略。
+    pub symbols: Vec<String>,
+    /// 运行时长；为 `None` 时实时与模拟模式使用 `DEFAULT_RUN_DURATION`。回测的时长由数据文件决定。
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub duration: Option<Duration>,
+    /// 实时模式下总线连续这么久没有新消息时提前结束（见 `quiescence::QuiescenceMonitor`），
+    /// `duration` 仍是运行时长的上限。为 `None` 时按 `duration` 运行。
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub quiet_period: Option<Duration>,
+    pub bus_capacity: usize,
+    /// 随机数种子：用于模拟行情的随机游走与执行引擎的随机成交和延迟抖动。为 `None` 时使用系统熵。
+    pub seed: Option<u64>,
+    /// 回测数据文件，每行一个 JSON 序列化的 `Bar`；扩展名为 `.csv` 时按 `csv_io::load_bars_csv` 的格式读取。
+    /// 只在回测模式下使用。
+    pub data_file: Option<PathBuf>,
+    /// 日志过滤指令，例如 `info` 或 `info,DATA=debug`。
+    pub log_level: String,
+    /// `[kafka]` 表：设置后实时模式启动 `KafkaBridge`（需启用 `kafka` feature）。
+    #[cfg(feature = "kafka")]
+    pub kafka: Option<KafkaConfig>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            mode: RunMode::default(),
+            symbols: vec![DEFAULT_SYMBOL.to_string()],
+            duration: None,
+            quiet_period: None,
+            bus_capacity: DEFAULT_BUS_CAPACITY,
+            seed: None,
+            data_file: None,
+            log_level: "info".to_string(),
+            #[cfg(feature = "kafka")]
+            kafka: None,
+        }
+    }
+}
+
+/// 读取或校验配置时的错误。
+#[derive(Debug)]
+pub enum ConfigError {
+    /// 无法读取配置文件。
+    Io(PathBuf, std::io::Error),
+    /// 配置文件不是合法的 TOML，或字段类型不符。
+    Parse(PathBuf, toml::de::Error),
+    /// 配置项之间相互冲突或取值无效。
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "cannot read config file {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid config file {}: {}", path.display(), e),
+            ConfigError::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(_, e) => Some(e),
+            ConfigError::Parse(_, e) => Some(e),
+            ConfigError::Invalid(_) => None,
+        }
+    }
+}
+
+impl AppConfig {
+    /// 从 TOML 文件读取配置，不做校验。
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    /// 实际运行时长。
+    pub fn run_duration(&self) -> Duration {
+        self.duration.unwrap_or(DEFAULT_RUN_DURATION)
+    }
+
+    /// 检查取值与模式之间的冲突，错误信息同时给出配置字段与对应的命令行参数。
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: String| Err(ConfigError::Invalid(reason));
+        if self.symbols.is_empty() {
+            return invalid("at least one symbol is required (symbols / --symbol)".to_string());
+        }
+        if self.symbols.iter().any(|symbol| symbol.trim().is_empty()) {
+            return invalid("symbols must not be empty (symbols / --symbol)".to_string());
+        }
+        if self.bus_capacity == 0 {
+            return invalid("bus capacity must be positive (bus_capacity / --bus-capacity)".to_string());
+        }
+        if self.duration.is_some_and(|duration| duration.is_zero()) {
+            return invalid("duration must be positive (duration / --duration)".to_string());
+        }
+        if self.quiet_period.is_some_and(|quiet_period| quiet_period.is_zero()) {
+            return invalid("quiet period must be positive (quiet_period / --quiet-period)".to_string());
+        }
+        if self.quiet_period.is_some() && self.mode != RunMode::Live {
+            return invalid(format!(
+                "a quiet period is only used in live mode, {} mode ends when its bars are exhausted \
+                 (quiet_period / --quiet-period)",
+                self.mode
+            ));
+        }
+        match self.mode {
+            RunMode::Backtest => {
+                if self.data_file.is_none() {
+                    return invalid("backtest mode requires a data file (data_file / --data-file)".to_string());
+                }
+                if self.duration.is_some() {
+                    return invalid(
+                        "duration cannot be set in backtest mode, the data file determines the run length \
+                         (duration / --duration)"
+                            .to_string(),
+                    );
+                }
+            },
+            RunMode::Live | RunMode::Sim => {
+                if self.data_file.is_some() {
+                    return invalid(format!(
+                        "a data file is only used in backtest mode, not {} mode (data_file / --data-file)",
+                        self.mode
+                    ));
+                }
+            },
+        }
+        if let Err(e) = EnvFilter::try_new(&self.log_level) {
+            return invalid(format!("invalid log level {:?} (log_level / --log-level): {}", self.log_level, e));
+        }
+        #[cfg(feature = "kafka")]
+        if let Some(kafka) = &self.kafka {
+            kafka.validate().map_err(ConfigError::Invalid)?;
+        }
+        Ok(())
+    }
+}
+
+/// 解析时长：`500ms`、`5s`、`2m`、`1h`，不带单位时为秒。
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+    let (value, unit) = text.split_at(split);
+    let value: f64 = value.parse().map_err(|_| format!("invalid duration {:?}", text))?;
+    let seconds = match unit.trim() {
+        "ms" => value / 1_000.0,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3_600.0,
+        unit => return Err(format!("unknown duration unit {:?} in {:?}, expected ms, s, m or h", unit, text)),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|e| format!("invalid duration {:?}: {}", text, e))
+}
+
+/// TOML 中的时长既可以是带单位的字符串，也可以是秒数。
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Seconds(f64),
+        Text(String),
+    }
+    let raw = Option::<Raw>::deserialize(deserializer)?;
+    raw.map(|raw| match raw {
+        Raw::Seconds(seconds) => Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string()),
+        Raw::Text(text) => parse_duration(&text),
+    })
+    .transpose()
+    .map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,176 @@
+// src/costs.rs
+
+//! # 交易成本模块 (costs)
+//!
+//! 定义模拟成交时使用的滑点模型 (`SlippageModel`) 和手续费模型 (`FeeModel`)。
+//! 模型可以直接实现 trait 插入执行引擎，也可以通过配置枚举选择和参数化。
+
+use crate::message::{Liquidity, OrderSide};
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// ## `SlippageModel` Trait
+///
+/// 根据请求价格计算实际成交价。买单的滑点向上，卖单的滑点向下。
+/// 需要随机性的模型使用执行引擎传入的共享随机数生成器，以保证可复现。
+pub trait SlippageModel: Send + Sync {
+    fn fill_price(&self, side: &OrderSide, price: f64, quantity: f64, rng: &mut StdRng) -> f64;
+}
+
+/// 将一个非负的价格偏移按方向施加到价格上。
+fn slip(side: &OrderSide, price: f64, offset: f64) -> f64 {
+    match side {
+        OrderSide::Buy => price + offset,
+        OrderSide::Sell => price - offset,
+    }
+}
+
+/// 无滑点，按请求价格成交。
+pub struct NoSlippage;
+
+impl SlippageModel for NoSlippage {
+    fn fill_price(&self, _side: &OrderSide, price: f64, _quantity: f64, _rng: &mut StdRng) -> f64 {
+        price
+    }
+}
+
+/// 固定跳数滑点：成交价偏移 `ticks * tick_size`。
+pub struct FixedTickSlippage {
+    pub tick_size: f64,
+    pub ticks: u32,
+}
+
+impl SlippageModel for FixedTickSlippage {
+    fn fill_price(&self, side: &OrderSide, price: f64, _quantity: f64, _rng: &mut StdRng) -> f64 {
+        slip(side, price, self.tick_size * self.ticks as f64)
+    }
+}
+
+/// 百分比滑点：成交价偏移价格的 `pct`（例如 `0.001` 表示 0.1%）。
+pub struct PercentageSlippage {
+    pub pct: f64,
+}
+
+impl SlippageModel for PercentageSlippage {
+    fn fill_price(&self, side: &OrderSide, price: f64, _quantity: f64, _rng: &mut StdRng) -> f64 {
+        slip(side, price, price * self.pct)
+    }
+}
+
+/// 与成交量相关的滑点：每单位数量产生 `impact_bps_per_unit` 个基点的冲击，
+/// 再叠加 `[0, noise_bps)` 之间的随机噪声。
+pub struct VolumeDependentSlippage {
+    pub impact_bps_per_unit: f64,
+    pub noise_bps: f64,
+}
+
+impl SlippageModel for VolumeDependentSlippage {
+    fn fill_price(&self, side: &OrderSide, price: f64, quantity: f64, rng: &mut StdRng) -> f64 {
+        let noise = if self.noise_bps > 0.0 { rng.gen_range(0.0..self.noise_bps) } else { 0.0 };
+        let bps = self.impact_bps_per_unit * quantity + noise;
+        slip(side, price, price * bps / 10_000.0)
+    }
+}
+
+/// ## `FeeModel` Trait
+///
+/// 根据成交价、数量和流动性方向计算手续费（以报价货币计）。
+pub trait FeeModel: Send + Sync {
+    fn commission(&self, price: f64, quantity: f64, liquidity: Liquidity) -> f64;
+}
+
+/// 不收取手续费。
+pub struct NoFees;
+
+impl FeeModel for NoFees {
+    fn commission(&self, _price: f64, _quantity: f64, _liquidity: Liquidity) -> f64 {
+        0.0
+    }
+}
+
+/// 每笔成交收取固定费用。
+pub struct FixedFee {
+    pub per_trade: f64,
+}
+
+impl FeeModel for FixedFee {
+    fn commission(&self, _price: f64, _quantity: f64, _liquidity: Liquidity) -> f64 {
+        self.per_trade
+    }
+}
+
+/// 按成交名义价值的基点收费。
+pub struct BpsFee {
+    pub bps: f64,
+}
+
+impl FeeModel for BpsFee {
+    fn commission(&self, price: f64, quantity: f64, _liquidity: Liquidity) -> f64 {
+        price * quantity * self.bps / 10_000.0
+    }
+}
+
+/// 按流动性方向区分费率：挂单成交收取 `maker_bps`，吃单成交收取 `taker_bps`。
+/// `maker_bps` 可以为负数，表示返佣。
+pub struct MakerTakerFee {
+    pub maker_bps: f64,
+    pub taker_bps: f64,
+}
+
+impl FeeModel for MakerTakerFee {
+    fn commission(&self, price: f64, quantity: f64, liquidity: Liquidity) -> f64 {
+        let bps = match liquidity {
+            Liquidity::Maker => self.maker_bps,
+            Liquidity::Taker => self.taker_bps,
+        };
+        price * quantity * bps / 10_000.0
+    }
+}
+
+/// ## `SlippageConfig`
+///
+/// 通过配置选择并参数化滑点模型。
+#[derive(Clone, Debug, Default)]
+pub enum SlippageConfig {
+    #[default]
+    None,
+    FixedTicks { tick_size: f64, ticks: u32 },
+    Percentage { pct: f64 },
+    VolumeDependent { impact_bps_per_unit: f64, noise_bps: f64 },
+}
+
+impl SlippageConfig {
+    pub fn build(&self) -> Box<dyn SlippageModel> {
+        match *self {
+            SlippageConfig::None => Box::new(NoSlippage),
+            SlippageConfig::FixedTicks { tick_size, ticks } => Box::new(FixedTickSlippage { tick_size, ticks }),
+            SlippageConfig::Percentage { pct } => Box::new(PercentageSlippage { pct }),
+            SlippageConfig::VolumeDependent { impact_bps_per_unit, noise_bps } => {
+                Box::new(VolumeDependentSlippage { impact_bps_per_unit, noise_bps })
+            }
+        }
+    }
+}
+
+/// ## `FeeConfig`
+///
+/// 通过配置选择并参数化手续费模型。
+#[derive(Clone, Debug, Default)]
+pub enum FeeConfig {
+    #[default]
+    None,
+    FixedPerTrade { per_trade: f64 },
+    Bps { bps: f64 },
+    MakerTaker { maker_bps: f64, taker_bps: f64 },
+}
+
+impl FeeConfig {
+    pub fn build(&self) -> Box<dyn FeeModel> {
+        match *self {
+            FeeConfig::None => Box::new(NoFees),
+            FeeConfig::FixedPerTrade { per_trade } => Box::new(FixedFee { per_trade }),
+            FeeConfig::Bps { bps } => Box::new(BpsFee { bps }),
+            FeeConfig::MakerTaker { maker_bps, taker_bps } => Box::new(MakerTakerFee { maker_bps, taker_bps }),
+        }
+    }
+}
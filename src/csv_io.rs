@@ -0,0 +1,249 @@
+// src/csv_io.rs
+
+//! # CSV 读写模块 (csv_io)
+//!
+//! 与使用 CSV 的工具互通：
+//! - `CsvBarWriter` 把总线上的行情写成 CSV，供运行结束后用电子表格或 pandas 分析；
+//! - `BarCsvReader` / `load_bars_csv` 读取表头为 `ts_event,symbol,open,high,low,close,volume` 的行情文件作为数据源；
+//! - `FillCsvWriter` 把成交追加到 CSV，列与 `FillEvent` 的 serde 字段相同，可以原样读回。
+//!
+//! 读取时无法解析的行记录警告（带行号）后跳过，不会中断运行。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::message::{Bar, FillEvent, DEFAULT_BAR_TIMEFRAME};
+use serde::Deserialize;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::runtime::Handle;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// `CsvBarWriter` 写入的表头。
+pub const BAR_CSV_HEADER: &str = "timestamp,symbol,open,high,low,close,volume";
+
+/// `CsvBarWriter` 每写入多少行刷新一次。
+const FLUSH_EVERY_ROWS: usize = 100;
+
+/// ## `CsvBarWriter`
+///
+/// 订阅 `Bar` 并逐行写入 CSV 文件（`timestamp` 为 `ts_event` 纳秒），设置了 `symbol_filter` 时只写该 symbol。
+/// 每写入 `FLUSH_EVERY_ROWS` 行刷新一次；被丢弃时在当前运行时上尽力刷新剩余的行，
+/// 需要确保全部落盘时调用 `flush`。
+pub struct CsvBarWriter {
+    bus: MessageBus,
+    writer: Arc<Mutex<BufWriter<tokio::fs::File>>>,
+    symbol_filter: Option<String>,
+}
+
+impl CsvBarWriter {
+    /// 创建（或清空）`path` 并写入表头。`symbol` 为 `None` 时写入所有 symbol 的 `Bar`。
+    pub fn new(bus: MessageBus, path: &Path, symbol: Option<String>) -> Result<Arc<CsvBarWriter>, io::Error> {
+        let mut file = std::fs::File::create(path)?;
+        writeln!(file, "{}", BAR_CSV_HEADER)?;
+        let writer = BufWriter::new(tokio::fs::File::from_std(file));
+        Ok(Arc::new(Self { bus, writer: Arc::new(Mutex::new(writer)), symbol_filter: symbol }))
+    }
+
+    /// 把缓冲中的行写入文件。
+    pub async fn flush(&self) -> io::Result<()> {
+        self.writer.lock().await.flush().await
+    }
+}
+
+impl Drop for CsvBarWriter {
+    fn drop(&mut self) {
+        if let Ok(runtime) = Handle::try_current() {
+            let writer = self.writer.clone();
+            runtime.spawn(async move {
+                if let Err(e) = writer.lock().await.flush().await {
+                    tracing::warn!(target: "CSV", "Failed to flush bar CSV: {}", e);
+                }
+            });
+        }
+    }
+}
+
+fn bar_row(bar: &Bar) -> String {
+    format!("{},{},{},{},{},{},{}\n", bar.ts_event, bar.symbol, bar.open, bar.high, bar.low, bar.close, bar.volume)
+}
+
+#[async_trait::async_trait]
+impl Actor for CsvBarWriter {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut rx = self.bus.subscribe::<Bar>().await;
+        let (writer, symbol_filter) = (self.writer.clone(), self.symbol_filter.clone());
+        let handle = spawn_named(actor_name, async move {
+            let mut unflushed = 0;
+            loop {
+                let bar = match rx.recv().await {
+                    Ok(bar) => bar,
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(target: "CSV", "{}, they are missing from the CSV", BusError::lagged::<Bar>(n));
+                        continue;
+                    },
+                    Err(RecvError::Closed) => break,
+                };
+                if symbol_filter.as_ref().is_some_and(|symbol| *symbol != bar.symbol) {
+                    continue;
+                }
+                let mut writer = writer.lock().await;
+                if let Err(e) = writer.write_all(bar_row(&bar).as_bytes()).await {
+                    tracing::error!(target: "CSV", "Failed to write bar: {}", e);
+                    continue;
+                }
+                unflushed += 1;
+                if unflushed >= FLUSH_EVERY_ROWS {
+                    unflushed = 0;
+                    if let Err(e) = writer.flush().await {
+                        tracing::error!(target: "CSV", "Failed to flush bar CSV: {}", e);
+                    }
+                }
+            }
+            if let Err(e) = writer.lock().await.flush().await {
+                tracing::warn!(target: "CSV", "Failed to flush bar CSV: {}", e);
+            }
+        });
+        vec![handle]
+    }
+}
+
+/// `load_bars_csv` 读取的一行。文件中没有 `id` 与 `timeframe`，读取时生成。
+#[derive(Deserialize)]
+struct BarRow {
+    ts_event: u64,
+    symbol: String,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+impl BarRow {
+    fn into_bar(self, timeframe: Duration) -> Bar {
+        Bar {
+            id: Uuid::new_v4(),
+            ts_event: self.ts_event,
+            symbol: self.symbol,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume,
+            timeframe,
+        }
+    }
+}
+
+/// 读取表头为 `ts_event,symbol,open,high,low,close,volume` 的 CSV 行情文件（列顺序以表头为准），
+/// 每根 `Bar` 生成新的 `id`，`timeframe` 为 `DEFAULT_BAR_TIMEFRAME`。
+/// 无法解析的行记录警告后跳过；只有文件无法打开或表头无法读取时返回错误。
+pub fn load_bars_csv(path: &Path) -> io::Result<Vec<Bar>> {
+    let mut reader = csv::Reader::from_path(path).map_err(io::Error::other)?;
+    reader.headers().map_err(io::Error::other)?;
+    let mut bars = Vec::new();
+    for row in reader.deserialize::<BarRow>() {
+        match row {
+            Ok(row) => bars.push(row.into_bar(DEFAULT_BAR_TIMEFRAME)),
+            Err(e) => {
+                let line = e.position().map_or(0, |position| position.line());
+                tracing::warn!(target: "CSV", "Skipped malformed row {}:{}: {}", path.display(), line, e);
+            },
+        }
+    }
+    Ok(bars)
+}
+
+/// ## `BarCsvReader`
+///
+/// 以 CSV 行情文件为数据源的 Actor：`open` 时读入全部 `Bar`（跳过无法解析的行），
+/// 启动后按文件顺序发布，发布完毕后任务结束。
+/// 在虚拟时钟上回测时可以改为把 `bars()` 交给 `SimulationDriver::schedule_bars`。
+pub struct BarCsvReader {
+    bus: MessageBus,
+    bars: Vec<Bar>,
+}
+
+impl BarCsvReader {
+    pub fn open(bus: MessageBus, path: &Path) -> io::Result<Self> {
+        Ok(Self { bus, bars: load_bars_csv(path)? })
+    }
+
+    /// 设置发布的 `Bar` 标注的聚合周期，默认 `DEFAULT_BAR_TIMEFRAME`。
+    pub fn with_timeframe(mut self, timeframe: Duration) -> Self {
+        for bar in &mut self.bars {
+            bar.timeframe = timeframe;
+        }
+        self
+    }
+
+    /// 读入的 `Bar`，按文件顺序排列。
+    pub fn bars(&self) -> &[Bar] {
+        &self.bars
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for BarCsvReader {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let handle = spawn_named(actor_name, async move {
+            for bar in &self.bars {
+                if let Err(e) = self.bus.publish(bar.clone()).await {
+                    tracing::error!(target: "CSV", "Failed to publish bar: {}", e);
+                }
+            }
+            tracing::info!(target: "CSV", "Published {} bars from CSV", self.bars.len());
+        });
+        vec![handle]
+    }
+}
+
+/// ## `FillCsvWriter`
+///
+/// 订阅 `FillEvent`，把每笔成交追加为 CSV 的一行并立即刷新，列与 `FillEvent` 的 serde 字段相同。
+/// 文件为空时先写表头，已有内容时直接追加，因此多次运行可以写入同一个文件。
+pub struct FillCsvWriter {
+    bus: MessageBus,
+    writer: Arc<std::sync::Mutex<csv::Writer<std::fs::File>>>,
+}
+
+impl FillCsvWriter {
+    pub fn new(bus: MessageBus, path: &Path) -> io::Result<Arc<FillCsvWriter>> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let is_empty = file.metadata()?.len() == 0;
+        let writer = csv::WriterBuilder::new().has_headers(is_empty).from_writer(file);
+        Ok(Arc::new(Self { bus, writer: Arc::new(std::sync::Mutex::new(writer)) }))
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for FillCsvWriter {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut rx = self.bus.subscribe::<FillEvent>().await;
+        let writer = self.writer.clone();
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                let fill = match rx.recv().await {
+                    Ok(fill) => fill,
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(target: "CSV", "{}, they are missing from the CSV", BusError::lagged::<FillEvent>(n));
+                        continue;
+                    },
+                    Err(RecvError::Closed) => break,
+                };
+                let mut writer = writer.lock().unwrap();
+                if let Err(e) = writer.serialize(&fill).and_then(|()| writer.flush().map_err(csv::Error::from)) {
+                    tracing::error!(target: "CSV", "Failed to write fill for order {}: {}", fill.order_id, e);
+                }
+            }
+        });
+        vec![handle]
+    }
+}
@@ -3,49 +3,394 @@
 //! # 数据引擎模块 (data)
 //!
 //! 模拟一个实时数据源，作为消息的生产者。
+//! 价格可以按单一资产的几何布朗运动（`RandomWalk`）生成，也可以按多个相关资产的联合模型（`CorrelatedWalk`）生成，
+//! 后者用相关矩阵的 Cholesky 分解把独立的正态样本变换为相关的布朗运动。
 
-use crate::actor::Actor;
-use crate::bus::MessageBus;
-use crate::message::Bar;
-use std::sync::Arc;
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus, PublishTarget};
+use crate::clock::{Clock, LiveClock};
+use crate::message::{Bar, DEFAULT_BAR_TIMEFRAME};
+use crate::symbol::SymbolRegistry;
+use nalgebra::{DMatrix, DVector};
+use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::error::Error;
+use std::fmt;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::task::JoinHandle;
 use tracing::info;
 use uuid::Uuid;
 
+/// 随机游走下每根 `Bar` 的基准成交量。
+const BASE_VOLUME: f64 = 1_000.0;
+
+/// 校验相关矩阵的对称性与对角线时允许的误差。
+const CORRELATION_TOLERANCE: f64 = 1e-9;
+
+fn seeded_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// 标准正态分布样本（Box-Muller 变换）。
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    // 左开区间，避免 ln(0)
+    let u1 = 1.0 - Uniform::new(0.0, 1.0).sample(rng);
+    let u2: f64 = Uniform::new(0.0, 1.0).sample(rng);
+    (-2.0 * f64::ln(u1)).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// 由开盘价与本根的对数收益生成 `(open, high, low, close, volume)`：
+/// 最高价/最低价在开盘价与收盘价之外再各自延伸半个波动率量级的随机幅度，
+/// 成交量为 `BASE_VOLUME` 乘以一个对数正态随机因子，并随本根收益的绝对值放大。
+fn ohlcv(open: f64, log_return: f64, volatility: f64, rng: &mut StdRng) -> (f64, f64, f64, f64, f64) {
+    let close = open * log_return.exp();
+    let wick = volatility / 2.0;
+    let high = open.max(close) * (wick * standard_normal(rng).abs()).exp();
+    let low = open.min(close) * (-wick * standard_normal(rng).abs()).exp();
+    let activity = if volatility > 0.0 { 1.0 + log_return.abs() / volatility } else { 1.0 };
+    let volume = BASE_VOLUME * activity * (0.5 * standard_normal(rng)).exp();
+    (open, high, low, close, volume)
+}
+
+/// ## `RandomWalkConfig`
+///
+/// 几何布朗运动价格模型：`price[t+1] = price[t] * exp(drift + volatility * N(0,1))`。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RandomWalkConfig {
+    pub initial_price: f64,
+    /// 每根 `Bar` 的对数收益漂移。
+    pub drift_per_bar: f64,
+    /// 每根 `Bar` 的对数收益标准差。
+    pub volatility_per_bar: f64,
+    /// 随机数种子，相同种子产生完全相同的 `Bar` 序列；为 `None` 时使用系统熵。
+    pub seed: Option<u64>,
+}
+
+/// ## `RandomWalk`
+///
+/// 按 `RandomWalkConfig` 逐根生成 OHLCV：
+/// - 开盘价为上一根的收盘价，收盘价按几何布朗运动演化。
+/// - 最高价/最低价在开盘价与收盘价之外再各自延伸半个波动率量级的随机幅度。
+/// - 成交量为 `BASE_VOLUME` 乘以一个对数正态随机因子，并随本根收益的绝对值放大。
+#[derive(Debug)]
+pub struct RandomWalk {
+    config: RandomWalkConfig,
+    rng: StdRng,
+    price: f64,
+}
+
+impl RandomWalk {
+    pub fn new(config: RandomWalkConfig) -> Self {
+        Self { config, rng: seeded_rng(config.seed), price: config.initial_price }
+    }
+
+    /// 生成下一根 `Bar` 的 `(open, high, low, close, volume)`。
+    pub fn next_ohlcv(&mut self) -> (f64, f64, f64, f64, f64) {
+        let RandomWalkConfig { drift_per_bar, volatility_per_bar, .. } = self.config;
+        let log_return = drift_per_bar + volatility_per_bar * standard_normal(&mut self.rng);
+        let bar = ohlcv(self.price, log_return, volatility_per_bar, &mut self.rng);
+        self.price = bar.3;
+        bar
+    }
+}
+
+/// ## `CorrelatedRandomWalk`
+///
+/// 多个相关资产的几何布朗运动：每根 `Bar` 先抽取独立的标准正态样本 `z`，
+/// 以相关矩阵的 Cholesky 因子 `L` 变换为相关样本 `L · z`，再按各资产的漂移与波动率演化。
+/// 各字段按 `assets` 的顺序一一对应，漂移与波动率同样以每根 `Bar` 的对数收益计。
+#[derive(Clone, Debug, PartialEq)]
+pub struct CorrelatedRandomWalk {
+    pub assets: Vec<String>,
+    pub initial_prices: Vec<f64>,
+    pub drift: Vec<f64>,
+    pub volatility: Vec<f64>,
+    /// 对数收益的相关矩阵：对称、对角线为 1、正定。
+    pub correlation_matrix: Vec<Vec<f64>>,
+    /// 随机数种子，相同种子产生完全相同的价格路径；为 `None` 时使用系统熵。
+    pub seed: Option<u64>,
+}
+
+/// ## `CorrelatedWalkError`
+///
+/// `CorrelatedRandomWalk` 配置无效的原因。
+#[derive(Clone, Debug, PartialEq)]
+pub enum CorrelatedWalkError {
+    /// 没有配置任何资产。
+    NoAssets,
+    /// `field` 的长度（或相关矩阵某一行的长度）与资产数量不一致。
+    LengthMismatch { field: &'static str, expected: usize, actual: usize },
+    /// 相关矩阵不对称、对角线不为 1 或有元素超出 `[-1, 1]`。
+    InvalidCorrelation(String),
+    /// 相关矩阵不是正定的，无法做 Cholesky 分解。
+    NotPositiveDefinite,
+}
+
+impl fmt::Display for CorrelatedWalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorrelatedWalkError::NoAssets => write!(f, "a correlated walk needs at least one asset"),
+            CorrelatedWalkError::LengthMismatch { field, expected, actual } => {
+                write!(f, "{} has {} entries, expected one per asset ({})", field, actual, expected)
+            },
+            CorrelatedWalkError::InvalidCorrelation(reason) => write!(f, "invalid correlation matrix: {}", reason),
+            CorrelatedWalkError::NotPositiveDefinite => write!(f, "correlation matrix is not positive definite"),
+        }
+    }
+}
+
+impl Error for CorrelatedWalkError {}
+
+/// ## `CorrelatedWalk`
+///
+/// 按 `CorrelatedRandomWalk` 逐根为每个资产生成 OHLCV，单个资产的 K 线形状与 `RandomWalk` 相同。
+#[derive(Debug)]
+pub struct CorrelatedWalk {
+    assets: Vec<String>,
+    drift: Vec<f64>,
+    volatility: Vec<f64>,
+    /// 相关矩阵的下三角 Cholesky 因子。
+    cholesky: DMatrix<f64>,
+    rng: StdRng,
+    prices: Vec<f64>,
+}
+
+impl CorrelatedWalk {
+    /// 校验配置并分解相关矩阵。
+    pub fn new(config: CorrelatedRandomWalk) -> Result<Self, CorrelatedWalkError> {
+        let n = config.assets.len();
+        if n == 0 {
+            return Err(CorrelatedWalkError::NoAssets);
+        }
+        let lengths = [
+            ("initial_prices", config.initial_prices.len()),
+            ("drift", config.drift.len()),
+            ("volatility", config.volatility.len()),
+            ("correlation_matrix", config.correlation_matrix.len()),
+        ];
+        let rows = config.correlation_matrix.iter().map(|row| ("correlation_matrix row", row.len()));
+        if let Some((field, actual)) = lengths.into_iter().chain(rows).find(|(_, len)| *len != n) {
+            return Err(CorrelatedWalkError::LengthMismatch { field, expected: n, actual });
+        }
+        let matrix = DMatrix::from_fn(n, n, |i, j| config.correlation_matrix[i][j]);
+        for i in 0..n {
+            if (matrix[(i, i)] - 1.0).abs() > CORRELATION_TOLERANCE {
+                return Err(CorrelatedWalkError::InvalidCorrelation(format!("diagonal entry {} is not 1", i)));
+            }
+            for j in 0..i {
+                if (matrix[(i, j)] - matrix[(j, i)]).abs() > CORRELATION_TOLERANCE {
+                    return Err(CorrelatedWalkError::InvalidCorrelation(format!("entries ({}, {}) and ({}, {}) differ", i, j, j, i)));
+                }
+                if !(-1.0..=1.0).contains(&matrix[(i, j)]) {
+                    return Err(CorrelatedWalkError::InvalidCorrelation(format!("entry ({}, {}) is outside [-1, 1]", i, j)));
+                }
+            }
+        }
+        let cholesky = matrix.cholesky().ok_or(CorrelatedWalkError::NotPositiveDefinite)?.l();
+        Ok(Self {
+            assets: config.assets,
+            drift: config.drift,
+            volatility: config.volatility,
+            cholesky,
+            rng: seeded_rng(config.seed),
+            prices: config.initial_prices,
+        })
+    }
+
+    /// 资产名称，顺序与 `next_ohlcv` 的结果一致。
+    pub fn assets(&self) -> &[String] {
+        &self.assets
+    }
+
+    /// 为每个资产生成下一根 `Bar` 的 `(open, high, low, close, volume)`，顺序与 `assets` 相同。
+    pub fn next_ohlcv(&mut self) -> Vec<(f64, f64, f64, f64, f64)> {
+        let independent = DVector::from_fn(self.assets.len(), |_, _| standard_normal(&mut self.rng));
+        let correlated = &self.cholesky * independent;
+        (0..self.assets.len())
+            .map(|i| {
+                let log_return = self.drift[i] + self.volatility[i] * correlated[i];
+                let bar = ohlcv(self.prices[i], log_return, self.volatility[i], &mut self.rng);
+                self.prices[i] = bar.3;
+                bar
+            })
+            .collect()
+    }
+}
+
+/// 数据引擎的价格模型，由引擎与发布任务共享，重启后沿同一条路径继续。
+enum PriceModel {
+    /// 默认序列：从 100 起每根 `Bar` 加 1，保存下一根的价格。
+    Sequential(f64),
+    /// 单个资产的随机游走。
+    Single(RandomWalk),
+    /// 多个相关资产，每根 `Bar` 为每个资产各发布一根。
+    Correlated(CorrelatedWalk),
+}
+
+/// 读取回测数据文件：每行一个 JSON 序列化的 `Bar`（与消息存储、`capture_state` 的格式相同），空行被忽略。
+/// 无法解析的行以 `InvalidData` 错误返回，并指出行号。
+pub fn load_bars(path: &Path) -> std::io::Result<Vec<Bar>> {
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut bars = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let bar = serde_json::from_str(&line).map_err(|e| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}:{}: {}", path.display(), i + 1, e))
+        })?;
+        bars.push(bar);
+    }
+    Ok(bars)
+}
+
 /// ## `SimulatedDataEngine`
 ///
-/// 一个 Actor，周期性地生成 `Bar` 消息并将其发布到 `MessageBus`。
+/// 一个 Actor，周期性地生成 `Bar` 消息并将其发布到 `PublishTarget`
+/// （一条 `MessageBus`，或通过 `MulticastGroup` 同时发布到多条总线）。
+///
+/// 默认价格从 100 起每根 `Bar` 加 1；设置 `with_random_walk` 后按几何布朗运动随机游走，
+/// 指定种子时两次运行产生相同的价格序列，便于蒙特卡洛测试与参数优化的复现。
+/// 设置 `with_correlated_walk` 后改为多资产模式：每次为每个资产各发布一根 `Bar`，价格路径按相关矩阵相关，
+/// 此时构造时的 `symbol` 不再使用。
+///
+/// 数据引擎是数据接入的边界：设置 `SymbolRegistry` 后，发布的 `Bar` 只使用规范 symbol。
+///
+/// 重启（再次调用 `start`）时先通过 `PublishTarget::purge` 丢弃目标中缓冲的陈旧 `Bar`；
+/// 价格模型在重启之间保留，新任务从上一个任务停下的位置继续，同一种子的路径不会重新开始。
 pub struct SimulatedDataEngine {
-    bus: MessageBus,
+    target: Arc<dyn PublishTarget<Bar>>,
     symbol: String,
+    model: Arc<Mutex<PriceModel>>,
+    clock: Arc<dyn Clock>,
+    timeframe: Duration,
+    /// 是否已经启动过，再次启动即为重启。
+    started: AtomicBool,
 }
 
 impl SimulatedDataEngine {
     pub fn new(bus: MessageBus, symbol: String) -> Self {
-        Self { bus, symbol }
+        Self::with_target(Arc::new(bus), symbol)
+    }
+
+    /// 使用任意发布目标创建数据引擎，例如 `MulticastGroup<Bar>`。
+    pub fn with_target(target: Arc<dyn PublishTarget<Bar>>, symbol: String) -> Self {
+        Self {
+            target,
+            symbol,
+            model: Arc::new(Mutex::new(PriceModel::Sequential(100.0))),
+            clock: Arc::new(LiveClock),
+            timeframe: DEFAULT_BAR_TIMEFRAME,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// 设置 `Bar` 的 `ts_event` 的时间来源，默认为 `LiveClock`。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置发布的 `Bar` 标注的聚合周期，默认 `DEFAULT_BAR_TIMEFRAME`。
+    pub fn with_timeframe(mut self, timeframe: Duration) -> Self {
+        self.timeframe = timeframe;
+        self
+    }
+
+    /// 使用几何布朗运动随机游走生成价格。
+    pub fn with_random_walk(mut self, config: RandomWalkConfig) -> Self {
+        self.model = Arc::new(Mutex::new(PriceModel::Single(RandomWalk::new(config))));
+        self
+    }
+
+    /// 改为多资产模式，按 `config` 生成相关的价格路径，取代单资产的价格模型。
+    ///
+    /// **Panics**：配置无效时 panic；需要先得到错误时用 `CorrelatedWalk::new` 校验。
+    pub fn with_correlated_walk(mut self, config: CorrelatedRandomWalk) -> Self {
+        let walk = CorrelatedWalk::new(config).unwrap_or_else(|e| panic!("{}", e));
+        self.model = Arc::new(Mutex::new(PriceModel::Correlated(walk)));
+        self
+    }
+
+    /// 通过 `registry` 将数据源的 symbol 解析为规范形式后再发布。
+    /// 多资产模式下解析各资产的名称，应在 `with_correlated_walk` 之后调用。
+    pub fn with_symbol_registry(mut self, registry: &SymbolRegistry) -> Self {
+        self.symbol = registry.resolve(&self.symbol);
+        if let PriceModel::Correlated(walk) = &mut *self.model.lock().unwrap() {
+            for asset in &mut walk.assets {
+                *asset = registry.resolve(asset);
+            }
+        }
+        self
     }
 }
 
 #[async_trait::async_trait]
 impl Actor for SimulatedDataEngine {
-    async fn start(self: Arc<Self>) -> Vec<JoinHandle<()>> {
-        let handle = tokio::spawn(async move {
-            let mut price = 100.0;
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        if self.started.swap(true, Ordering::SeqCst) {
+            for result in self.target.purge().await {
+                match result {
+                    Ok(purged) => info!(target: "DATA", "Restarting: purged {} stale bars", purged),
+                    // 还没有人订阅过 Bar，没有需要清除的消息
+                    Err(BusError::ChannelNotFound { .. }) => {},
+                    Err(e) => tracing::warn!(target: "DATA", "Failed to purge stale bars: {}", e),
+                }
+            }
+        }
+        let model = self.model.clone();
+        let handle = spawn_named(actor_name, async move {
             loop {
-                let bar = Bar {
-                    id: Uuid::new_v4(),
-                    ts_event: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos() as u64,
-                    symbol: self.symbol.clone(),
-                    close: price,
+                // 在锁内推进价格模型，锁不跨越 await
+                let bars: Vec<(String, (f64, f64, f64, f64, f64))> = match &mut *model.lock().unwrap() {
+                    PriceModel::Sequential(price) => {
+                        let bar = (self.symbol.clone(), (*price, *price, *price, *price, 0.0));
+                        *price += 1.0;
+                        vec![bar]
+                    },
+                    PriceModel::Single(walk) => vec![(self.symbol.clone(), walk.next_ohlcv())],
+                    PriceModel::Correlated(walk) => {
+                        let next = walk.next_ohlcv();
+                        walk.assets.iter().cloned().zip(next).collect()
+                    },
                 };
+                // 没有订阅者时跳过构造与发布，价格序列照常推进，不受订阅者是否存在影响
+                if self.target.subscriber_count().await == 0 {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    continue;
+                }
+                let ts_event = self.clock.now_nanos();
+                for (symbol, (open, high, low, close, volume)) in bars {
+                    let bar = Bar {
+                        id: Uuid::new_v4(),
+                        ts_event,
+                        symbol,
+                        open,
+                        high,
+                        low,
+                        close,
+                        volume,
+                        timeframe: self.timeframe,
+                    };
 
-                info!(target: "DATA", "Publishing {:?}", bar);
-                if let Err(e) = self.bus.publish(bar).await {
-                    tracing::error!(target: "DATA", "Failed to publish bar: {}", e);
+                    info!(target: "DATA", "Publishing {:?}", bar);
+                    // 多播时部分目标失败不影响其他目标，逐个记录错误
+                    for result in self.target.publish(bar).await {
+                        if let Err(e) = result {
+                            tracing::error!(target: "DATA", "Failed to publish bar: {}", e);
+                        }
+                    }
                 }
-                
-                price += 1.0;
+
                 tokio::time::sleep(Duration::from_millis(500)).await;
             }
         });
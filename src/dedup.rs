@@ -0,0 +1,129 @@
+// src/dedup.rs
+
+//! # 去重模块 (dedup)
+//!
+//! 桥接多个数据源或重试发布时，同一条逻辑消息（相同 `Uuid`）可能出现多次。
+//! `Deduplicator` 在一个有界窗口内记录最近见过的消息 id，只把首次出现的消息转发到去重后的总线。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::message::{Bar, Message, OrderRequest};
+use std::collections::{HashSet, VecDeque};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// ## `Identified` Trait
+///
+/// 携带唯一 id 的消息，去重以该 id 为依据。
+pub trait Identified: Message {
+    fn message_id(&self) -> Uuid;
+}
+
+impl Identified for Bar {
+    fn message_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Identified for OrderRequest {
+    fn message_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+/// ## `SeenWindow`
+///
+/// 最多记住 `capacity` 个最近见过的 id，超出时淘汰最早插入的 id。
+#[derive(Debug)]
+pub struct SeenWindow {
+    capacity: usize,
+    ids: HashSet<Uuid>,
+    order: VecDeque<Uuid>,
+}
+
+impl SeenWindow {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ids: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// 记录一个 id。首次出现（或已被淘汰出窗口）时返回 `true`，重复时返回 `false`。
+    pub fn insert(&mut self, id: Uuid) -> bool {
+        if !self.ids.insert(id) {
+            return false;
+        }
+        self.order.push_back(id);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    pub fn contains(&self, id: &Uuid) -> bool {
+        self.ids.contains(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+/// ## `Deduplicator`
+///
+/// 一个 Actor，订阅 `input` 上的消息 `M`，把窗口内首次出现的消息重新发布到 `output`。
+///
+/// `output` 通常是 `input.clone_with_prefix("deduped")`，消费者订阅该视图即可保证每个 id 只处理一次。
+/// 由于发布会冒泡到上级命名空间，当 `input` 是 `output` 的上级时，
+/// 转发出去的消息会再次回到 `input`，此时它已在窗口内，会被当作重复消息丢弃。
+pub struct Deduplicator<M: Identified> {
+    input: MessageBus,
+    output: MessageBus,
+    window: usize,
+    _marker: PhantomData<fn(M)>,
+}
+
+impl<M: Identified> Deduplicator<M> {
+    pub fn new(input: MessageBus, output: MessageBus, window: usize) -> Self {
+        Self { input, output, window, _marker: PhantomData }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Identified> Actor for Deduplicator<M> {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut rx = self.input.subscribe::<M>().await;
+
+        let handle = spawn_named(actor_name, async move {
+            let mut seen = SeenWindow::new(self.window);
+            loop {
+                match rx.recv().await {
+                    Ok(msg) => {
+                        if !seen.insert(msg.message_id()) {
+                            tracing::debug!(target: "DEDUP", "Suppressed duplicate {}", msg.message_id());
+                            continue;
+                        }
+                        if let Err(e) = self.output.publish(msg).await {
+                            tracing::error!(target: "DEDUP", "Failed to republish message: {}", e);
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "DEDUP", "{}", BusError::lagged::<M>(n)),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
@@ -0,0 +1,266 @@
+// src/divergence.rs
+
+//! # 分歧检测模块 (divergence)
+//!
+//! 验证模拟的确定性：把一次运行中某种消息的实时序列与此前录制的序列逐条比较，
+//! 报告第一处不一致（字段不同、顺序不同、多出或缺少的消息）。
+//! 录制来自 `MessageBus::with_message_store` 与 `capture_state`。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::message::Message;
+use crate::startup::StartupBarrierHandle;
+use crate::store::BusState;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// 分歧的种类。消息以序列化后的 JSON 表示。
+#[derive(Clone, Debug, PartialEq)]
+pub enum DivergenceKind {
+    /// 同一位置的消息在 `field` 处不同。`field` 为点号分隔的路径，例如 `price` 或 `legs.0.quantity`；
+    /// 为空时表示整条消息不同（例如实时消息无法序列化）。
+    Mismatch { field: String, recorded: Value, live: Value },
+    /// 录制已经结束，实时运行却产生了更多消息。
+    Unexpected { live: Value },
+    /// 实时运行结束时仍有录制的消息没有出现，由 `finish` 报告。
+    Missing { recorded: Value },
+    /// 检测器落后于总线，跳过了 `skipped` 条消息，之后的比较不再可靠。
+    Lagged { skipped: u64 },
+}
+
+/// ## `Divergence`
+///
+/// 实时序列与录制序列的第一处分歧，`index` 为该消息在序列中的位置（从 0 开始）。
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    pub index: usize,
+    pub kind: DivergenceKind,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            DivergenceKind::Mismatch { field, recorded, live } if field.is_empty() => {
+                write!(f, "message #{} differs: recorded {}, live {}", self.index, recorded, live)
+            },
+            DivergenceKind::Mismatch { field, recorded, live } => {
+                write!(f, "message #{} differs at {}: recorded {}, live {}", self.index, field, recorded, live)
+            },
+            DivergenceKind::Unexpected { live } => {
+                write!(f, "message #{} was not recorded: live {}", self.index, live)
+            },
+            DivergenceKind::Missing { recorded } => {
+                write!(f, "message #{} never arrived: recorded {}", self.index, recorded)
+            },
+            DivergenceKind::Lagged { skipped } => {
+                write!(f, "checker lagged by {} messages after #{}", skipped, self.index)
+            },
+        }
+    }
+}
+
+/// 检测器的比较进度。
+#[derive(Debug, Default)]
+struct CheckState {
+    /// 已经比较且一致的消息数量，也是下一条实时消息在序列中的位置。
+    matched: usize,
+    divergence: Option<Divergence>,
+}
+
+/// ## `DivergenceChecker`
+///
+/// 一个 Actor，订阅消息类型 `M`，把每条实时消息与录制序列中同一位置的消息比较。
+///
+/// - 比较的是序列化后的 JSON，因此不要求 `M` 实现 `PartialEq`，并能指出第一个不同的字段。
+/// - 每次运行都会重新生成的字段（例如随机的订单 ID）用 `with_ignored_fields` 排除，只作用于顶层字段。
+/// - 只记录第一处分歧，之后的消息不再比较。`divergence` 随时查询，`finish` 在运行结束后
+///   额外检查是否缺少消息，`assert_no_divergence` 在有分歧时 panic。
+pub struct DivergenceChecker<M> {
+    bus: MessageBus,
+    recorded: Vec<Value>,
+    ignored_fields: HashSet<String>,
+    state: Mutex<CheckState>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+    _message: PhantomData<fn() -> M>,
+}
+
+impl<M: Message + Serialize + DeserializeOwned> DivergenceChecker<M> {
+    /// 以给定的录制序列创建检测器。
+    pub fn new(bus: MessageBus, recorded: impl IntoIterator<Item = M>) -> Result<Self, serde_json::Error> {
+        let recorded = recorded.into_iter().map(|msg| serde_json::to_value(&msg)).collect::<Result<_, _>>()?;
+        Ok(Self::from_values(bus, recorded))
+    }
+
+    /// 从 `capture_state` 得到的快照中取出 `M` 的录制序列（按 `seq` 顺序，所有命名空间）。
+    /// `M` 必须在录制时已通过 `with_message_store` 登记，否则快照中没有它的消息。
+    pub fn from_state(bus: MessageBus, state: &BusState) -> Result<Self, BusError> {
+        let type_name = std::any::type_name::<M>();
+        let mut recorded = Vec::new();
+        for stored in state.messages.iter().filter(|stored| stored.type_name == type_name) {
+            // 先反序列化确认快照中的消息确实是 `M`
+            let msg: M = serde_json::from_value(stored.envelope.message.clone()).map_err(BusError::deserialize::<M>)?;
+            recorded.push(
+                serde_json::to_value(&msg).map_err(|source| BusError::Serialize { type_name, source })?,
+            );
+        }
+        Ok(Self::from_values(bus, recorded))
+    }
+
+    fn from_values(bus: MessageBus, recorded: Vec<Value>) -> Self {
+        Self {
+            bus,
+            recorded,
+            ignored_fields: HashSet::new(),
+            state: Mutex::new(CheckState::default()),
+            barrier: Mutex::new(None),
+            _message: PhantomData,
+        }
+    }
+
+    /// 比较时忽略的顶层字段，例如每次运行都不同的 `id`。
+    pub fn with_ignored_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ignored_fields.extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// 设置启动屏障，完成订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 录制序列的长度。
+    pub fn recorded_len(&self) -> usize {
+        self.recorded.len()
+    }
+
+    /// 目前为止与录制一致的消息数量。
+    pub fn matched(&self) -> usize {
+        self.state.lock().unwrap().matched
+    }
+
+    /// 目前为止发现的第一处分歧。
+    pub fn divergence(&self) -> Option<Divergence> {
+        self.state.lock().unwrap().divergence.clone()
+    }
+
+    /// 运行结束后调用：没有分歧且录制的消息全部出现时返回一致的消息数量，
+    /// 否则返回第一处分歧（包括缺少的第一条录制消息）。
+    pub fn finish(&self) -> Result<usize, Divergence> {
+        let state = self.state.lock().unwrap();
+        if let Some(divergence) = &state.divergence {
+            return Err(divergence.clone());
+        }
+        match self.recorded.get(state.matched) {
+            Some(recorded) => {
+                Err(Divergence { index: state.matched, kind: DivergenceKind::Missing { recorded: recorded.clone() } })
+            },
+            None => Ok(state.matched),
+        }
+    }
+
+    /// `finish` 发现分歧时 panic，消息中描述第一处分歧。
+    pub fn assert_no_divergence(&self) {
+        if let Err(divergence) = self.finish() {
+            panic!("{} diverged from the recording: {}", std::any::type_name::<M>(), divergence);
+        }
+    }
+
+    fn check(&self, msg: &M) {
+        let mut state = self.state.lock().unwrap();
+        if state.divergence.is_some() {
+            return;
+        }
+        let index = state.matched;
+        let live = match serde_json::to_value(msg) {
+            Ok(live) => self.without_ignored(live),
+            Err(e) => {
+                tracing::error!(target: "DIVERGENCE", "Cannot serialize live message #{}: {}", index, e);
+                Value::Null
+            },
+        };
+        let kind = match self.recorded.get(index) {
+            Some(recorded) => {
+                let recorded = self.without_ignored(recorded.clone());
+                first_difference(String::new(), &recorded, &live)
+                    .map(|(field, recorded, live)| DivergenceKind::Mismatch { field, recorded, live })
+            },
+            None => Some(DivergenceKind::Unexpected { live }),
+        };
+        match kind {
+            Some(kind) => self.diverge(&mut state, Divergence { index, kind }),
+            None => state.matched += 1,
+        }
+    }
+
+    fn diverge(&self, state: &mut CheckState, divergence: Divergence) {
+        if state.divergence.is_none() {
+            tracing::error!(target: "DIVERGENCE", "{} diverged: {}", std::any::type_name::<M>(), divergence);
+            state.divergence = Some(divergence);
+        }
+    }
+
+    fn without_ignored(&self, mut value: Value) -> Value {
+        if let Value::Object(fields) = &mut value {
+            fields.retain(|name, _| !self.ignored_fields.contains(name));
+        }
+        value
+    }
+}
+
+/// 深度优先查找两个 JSON 值的第一个不同之处，返回（路径，录制值，实时值）。
+/// 对象按字段名排序比较，数组按下标比较，缺少的一侧为 `null`。
+fn first_difference(path: String, recorded: &Value, live: &Value) -> Option<(String, Value, Value)> {
+    let join = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+    match (recorded, live) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            keys.into_iter().find_map(|key| {
+                let (a, b) = (a.get(key).unwrap_or(&Value::Null), b.get(key).unwrap_or(&Value::Null));
+                first_difference(join(key), a, b)
+            })
+        },
+        (Value::Array(a), Value::Array(b)) => (0..a.len().max(b.len())).find_map(|i| {
+            let (a, b) = (a.get(i).unwrap_or(&Value::Null), b.get(i).unwrap_or(&Value::Null));
+            first_difference(join(&i.to_string()), a, b)
+        }),
+        _ if recorded == live => None,
+        _ => Some((path, recorded.clone(), live.clone())),
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message + Serialize + DeserializeOwned> Actor for DivergenceChecker<M> {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut rx = self.bus.subscribe::<M>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                match rx.recv().await {
+                    Ok(msg) => self.check(&msg),
+                    Err(RecvError::Lagged(n)) => {
+                        let mut state = self.state.lock().unwrap();
+                        let index = state.matched;
+                        self.diverge(&mut state, Divergence { index, kind: DivergenceKind::Lagged { skipped: n } });
+                    },
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        vec![handle]
+    }
+}
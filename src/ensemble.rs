@@ -0,0 +1,187 @@
+// src/ensemble.rs
+
+//! # 策略组合模块 (ensemble)
+//!
+//! 多个策略对同一 symbol 发出 `StrategySignal` 时，`SignalAggregator` 在一个短窗口内收集它们，
+//! 按配置的合并规则得出一个净订单，而不是每个策略各下一单。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::message::{OrderRequest, OrderSide, OrderType, StrategySignal};
+use crate::startup::StartupBarrierHandle;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::info;
+use uuid::Uuid;
+
+/// ## `CombinationPolicy`
+///
+/// 把一个窗口内的信号合并为一个方向与数量的规则。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CombinationPolicy {
+    /// 每个策略一票，票数多的方向胜出，平票不下单。数量为 `order_quantity`。
+    MajorityVote,
+    /// 按策略权重对 `strength` 做带方向的加权平均，结果的绝对值不低于 `threshold` 时下单，
+    /// 数量为 `order_quantity` 乘以该绝对值。
+    WeightedAverage { threshold: f64 },
+    /// 所有策略方向一致时才下单，任何一个反向信号都会否决。数量为 `order_quantity`。
+    Veto,
+}
+
+/// ## `SignalAggregator`
+///
+/// 一个 Actor，消费 `StrategySignal` 消息并发布合并后的 `OrderRequest`（市价单）：
+/// - 某个 symbol 的第一个信号开启一个 `window` 长的收集窗口，窗口结束时合并并清空。
+/// - 同一窗口内同一策略的多个信号只保留最新的一个。
+/// - 订单价格取胜出方向上最新信号的参考价格。
+pub struct SignalAggregator {
+    bus: MessageBus,
+    window: Duration,
+    policy: CombinationPolicy,
+    order_quantity: f64,
+    /// 按策略 ID 的权重，未设置的策略权重为 1。
+    weights: HashMap<String, f64>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl SignalAggregator {
+    pub fn new(bus: MessageBus, window: Duration, policy: CombinationPolicy) -> Self {
+        Self {
+            bus,
+            window,
+            policy,
+            order_quantity: 1.0,
+            weights: HashMap::new(),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 设置合并后订单的基础数量，默认 1。
+    pub fn with_order_quantity(mut self, quantity: f64) -> Self {
+        self.order_quantity = quantity;
+        self
+    }
+
+    /// 设置某个策略在 `WeightedAverage` 规则下的权重。
+    pub fn with_weight(mut self, strategy_id: &str, weight: f64) -> Self {
+        self.weights.insert(strategy_id.to_string(), weight);
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 按合并规则得出方向与数量，不下单时返回 `None`。
+    fn combine(&self, signals: &[StrategySignal]) -> Option<(OrderSide, f64)> {
+        let buys = signals.iter().filter(|signal| signal.direction == OrderSide::Buy).count();
+        let sells = signals.len() - buys;
+        match self.policy {
+            CombinationPolicy::MajorityVote => match buys.cmp(&sells) {
+                std::cmp::Ordering::Greater => Some((OrderSide::Buy, self.order_quantity)),
+                std::cmp::Ordering::Less => Some((OrderSide::Sell, self.order_quantity)),
+                std::cmp::Ordering::Equal => None,
+            },
+            CombinationPolicy::WeightedAverage { threshold } => {
+                let (weighted, total_weight) = signals.iter().fold((0.0, 0.0), |(weighted, total), signal| {
+                    let weight = self.weights.get(&signal.strategy_id).copied().unwrap_or(1.0);
+                    let direction = match signal.direction {
+                        OrderSide::Buy => 1.0,
+                        OrderSide::Sell => -1.0,
+                    };
+                    (weighted + weight * signal.strength * direction, total + weight)
+                });
+                if total_weight <= 0.0 {
+                    return None;
+                }
+                let score = weighted / total_weight;
+                if score.abs() < threshold || score == 0.0 {
+                    return None;
+                }
+                let side = if score > 0.0 { OrderSide::Buy } else { OrderSide::Sell };
+                Some((side, self.order_quantity * score.abs()))
+            },
+            CombinationPolicy::Veto => match (buys, sells) {
+                (0, 0) => None,
+                (_, 0) => Some((OrderSide::Buy, self.order_quantity)),
+                (0, _) => Some((OrderSide::Sell, self.order_quantity)),
+                _ => None,
+            },
+        }
+    }
+
+    /// 合并一个窗口的信号，得出订单时发布。
+    async fn flush(&self, symbol: String, signals: HashMap<String, StrategySignal>) {
+        let signals: Vec<StrategySignal> = signals.into_values().collect();
+        let Some((side, quantity)) = self.combine(&signals) else {
+            info!(target: "ENSEMBLE", "{} signals for {} produced no order under {:?}", signals.len(), symbol, self.policy);
+            return;
+        };
+        let price = signals
+            .iter()
+            .filter(|signal| signal.direction == side)
+            .max_by_key(|signal| signal.ts_event)
+            .map_or(0.0, |signal| signal.price);
+        let order = OrderRequest {
+            id: Uuid::new_v4(),
+            symbol,
+            side,
+            order_type: OrderType::Market,
+            price,
+            quantity,
+            trigger_price: None,
+        };
+        info!(target: "ENSEMBLE", "Combined {} signals, publishing {:?}", signals.len(), order);
+        if let Err(e) = self.bus.publish(order).await {
+            tracing::error!(target: "ENSEMBLE", "Failed to publish order: {}", e);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for SignalAggregator {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut signal_rx = self.bus.subscribe::<StrategySignal>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let handle = spawn_named(actor_name, async move {
+            // symbol -> (窗口结束时间, 策略 ID -> 最新信号)
+            let mut pending: HashMap<String, (Instant, HashMap<String, StrategySignal>)> = HashMap::new();
+            loop {
+                let next_deadline = pending.values().map(|(deadline, _)| *deadline).min();
+                tokio::select! {
+                    result = signal_rx.recv() => match result {
+                        Ok(signal) => {
+                            let (_, signals) = pending
+                                .entry(signal.symbol.clone())
+                                .or_insert_with(|| (Instant::now() + self.window, HashMap::new()));
+                            signals.insert(signal.strategy_id.clone(), signal);
+                        },
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "ENSEMBLE", "{}", BusError::lagged::<StrategySignal>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = tokio::time::sleep_until(next_deadline.unwrap_or_else(Instant::now)), if next_deadline.is_some() => {
+                        let now = Instant::now();
+                        let due: Vec<String> =
+                            pending.iter().filter(|(_, (deadline, _))| *deadline <= now).map(|(symbol, _)| symbol.clone()).collect();
+                        for symbol in due {
+                            if let Some((_, signals)) = pending.remove(&symbol) {
+                                self.flush(symbol, signals).await;
+                            }
+                        }
+                    },
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
@@ -0,0 +1,283 @@
+// src/event_sourcing.rs
+
+//! # 事件溯源模块 (event_sourcing)
+//!
+//! 把 Actor 的状态定义为事件序列的折叠结果：状态只通过 `EventState::apply` 改变，
+//! Actor 崩溃重启后，从最近的快照出发，重放消息存储中快照之后的事件即可重建状态。
+//! 事件来自 `MessageBus::with_message_store` 保留的最近消息，快照保存在可替换的 `SnapshotStore` 中。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::message::Timestamped;
+use crate::startup::StartupBarrierHandle;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// ## `EventState` Trait
+///
+/// 可以由事件 `E` 折叠出来的状态。`Default` 是尚未处理任何事件时的状态，
+/// `apply` 必须是确定性的：同一事件序列总是得到同一状态。
+pub trait EventState<E>: Default {
+    fn apply(&mut self, event: &E);
+}
+
+/// ## `EventCursor`
+///
+/// 状态已经处理到的位置：事件时间 `ts` 以及其中已处理的事件数量 `count`。
+/// 同一时间戳可能有多条事件，只记录时间戳无法区分哪些已经计入状态。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventCursor {
+    pub ts: u64,
+    pub count: usize,
+}
+
+impl EventCursor {
+    /// 处理一条事件后前进。事件时间倒退时保持不变（见 `EventSourcingActor` 的时间要求）。
+    fn advance(&mut self, ts: u64) {
+        if ts > self.ts {
+            *self = EventCursor { ts, count: 1 };
+        } else if ts == self.ts {
+            self.count += 1;
+        }
+    }
+}
+
+/// ## `Snapshot`
+///
+/// 某一时刻的状态，以及它所包含的最后一条事件的位置；`cursor` 为 `None` 表示还没有处理过事件。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot<S> {
+    pub state: S,
+    pub cursor: Option<EventCursor>,
+}
+
+/// ## `SnapshotStore` Trait
+///
+/// 快照的存放位置。只需要保留最新的一份快照。
+pub trait SnapshotStore<S>: Send + Sync {
+    /// 保存快照，替换之前的快照。
+    fn save(&self, snapshot: &Snapshot<S>) -> std::io::Result<()>;
+
+    /// 读取最新的快照，没有快照时返回 `None`。
+    fn load_latest(&self) -> std::io::Result<Option<Snapshot<S>>>;
+}
+
+/// ## `InMemorySnapshotStore`
+///
+/// 保存在内存中的快照，可以在 Actor 的多个实例之间共享（Actor 崩溃后由新的实例读取）。
+pub struct InMemorySnapshotStore<S> {
+    latest: Mutex<Option<Snapshot<S>>>,
+}
+
+impl<S> Default for InMemorySnapshotStore<S> {
+    fn default() -> Self {
+        Self { latest: Mutex::new(None) }
+    }
+}
+
+impl<S: Clone + Send> SnapshotStore<S> for InMemorySnapshotStore<S> {
+    fn save(&self, snapshot: &Snapshot<S>) -> std::io::Result<()> {
+        *self.latest.lock().unwrap() = Some(snapshot.clone());
+        Ok(())
+    }
+
+    fn load_latest(&self) -> std::io::Result<Option<Snapshot<S>>> {
+        Ok(self.latest.lock().unwrap().clone())
+    }
+}
+
+/// ## `JsonFileSnapshotStore`
+///
+/// 把快照以 JSON 写入文件，进程重启后仍然可用。先写入临时文件再重命名，避免写到一半的快照。
+pub struct JsonFileSnapshotStore {
+    path: PathBuf,
+}
+
+impl JsonFileSnapshotStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl<S: Serialize + DeserializeOwned> SnapshotStore<S> for JsonFileSnapshotStore {
+    fn save(&self, snapshot: &Snapshot<S>) -> std::io::Result<()> {
+        let json = serde_json::to_vec(snapshot)?;
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &self.path)
+    }
+
+    fn load_latest(&self) -> std::io::Result<Option<Snapshot<S>>> {
+        match std::fs::read(&self.path) {
+            Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// 状态与它的位置一起加锁，快照总是一致的。
+struct Sourced<S> {
+    state: S,
+    cursor: Option<EventCursor>,
+}
+
+/// ## `StateGuard`
+///
+/// `current_state` 返回的只读视图，持有状态锁直到被丢弃。
+pub struct StateGuard<'a, S>(MutexGuard<'a, Sourced<S>>);
+
+impl<S> Deref for StateGuard<'_, S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.0.state
+    }
+}
+
+/// ## `EventSourcingActor`
+///
+/// 一个 Actor，订阅事件 `E` 并把它们依次应用到状态 `S`。
+/// - 启动时先从 `SnapshotStore` 读取最新快照，再从总线的消息存储重放快照之后、订阅之前发布的事件，
+///   `start` 返回时状态已经追上；之后的事件实时应用。
+/// - `take_snapshot` 保存当前状态及其位置，重启时只需重放此后的事件。
+/// - 总线需要通过 `with_message_store::<E>` 保留事件，且保留数量要覆盖两次快照之间的事件，
+///   否则更早的事件已被丢弃，重建的状态会缺少它们。
+/// - 事件位置以事件时间记录，要求 `E` 的 `ts_event` 不倒退（例如由 `Monotonic` 时钟生成）。
+pub struct EventSourcingActor<E, S> {
+    bus: MessageBus,
+    sourced: Mutex<Sourced<S>>,
+    snapshots: Arc<dyn SnapshotStore<S>>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+    _event: PhantomData<fn(E)>,
+}
+
+impl<E, S> EventSourcingActor<E, S>
+where
+    E: Timestamped,
+    S: EventState<E> + Clone + Send + 'static,
+{
+    /// 使用内存快照存储创建 Actor。
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            sourced: Mutex::new(Sourced { state: S::default(), cursor: None }),
+            snapshots: Arc::new(InMemorySnapshotStore::default()),
+            barrier: Mutex::new(None),
+            _event: PhantomData,
+        }
+    }
+
+    /// 设置快照存储。与崩溃前的实例共享同一个存储，新实例才能从快照恢复。
+    pub fn with_snapshot_store(mut self, snapshots: Arc<dyn SnapshotStore<S>>) -> Self {
+        self.snapshots = snapshots;
+        self
+    }
+
+    /// 设置启动屏障，状态恢复完成后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 当前状态。返回的视图持有状态锁，不要跨 `await` 持有。
+    pub fn current_state(&self) -> StateGuard<'_, S> {
+        StateGuard(self.sourced.lock().unwrap())
+    }
+
+    /// 当前状态已经处理到的事件位置。
+    pub fn cursor(&self) -> Option<EventCursor> {
+        self.sourced.lock().unwrap().cursor
+    }
+
+    /// 把当前状态保存到快照存储并返回它。保存失败只记录错误，重启时退回到更早的快照。
+    pub fn take_snapshot(&self) -> S {
+        let snapshot = {
+            let sourced = self.sourced.lock().unwrap();
+            Snapshot { state: sourced.state.clone(), cursor: sourced.cursor }
+        };
+        if let Err(e) = self.snapshots.save(&snapshot) {
+            tracing::error!(target: "EVENT_SOURCING", "Failed to save {} snapshot: {}", std::any::type_name::<S>(), e);
+        }
+        snapshot.state
+    }
+
+    fn apply(&self, event: &E) {
+        let mut sourced = self.sourced.lock().unwrap();
+        sourced.state.apply(event);
+        sourced.cursor.get_or_insert_with(EventCursor::default).advance(event.ts_event());
+    }
+
+    /// 用最新快照替换当前状态，没有快照（或读取失败）时回到初始状态。返回快照的位置。
+    fn restore_snapshot(&self) -> Option<EventCursor> {
+        let snapshot = match self.snapshots.load_latest() {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                tracing::warn!(target: "EVENT_SOURCING", "Cannot load snapshot, rebuilding from events: {}", e);
+                None
+            },
+        };
+        let mut sourced = self.sourced.lock().unwrap();
+        *sourced = match snapshot {
+            Some(snapshot) => Sourced { state: snapshot.state, cursor: snapshot.cursor },
+            None => Sourced { state: S::default(), cursor: None },
+        };
+        sourced.cursor
+    }
+}
+
+#[async_trait::async_trait]
+impl<E, S> Actor for EventSourcingActor<E, S>
+where
+    E: Timestamped,
+    S: EventState<E> + Clone + Send + 'static,
+{
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut events = self.bus.subscribe_handle::<E>().await;
+        let cursor = self.restore_snapshot();
+
+        // 快照之后、订阅之前的事件从存储重放；它们被注入回环通道，`recv` 优先返回，这里同步处理完
+        let from_ts = cursor.map_or(0, |cursor| cursor.ts);
+        match self.bus.replay_from_store(&events, from_ts) {
+            Ok(replayed) => {
+                // 与快照位置同一时间戳的事件中，前 `count` 条已经计入快照
+                let mut already_applied = cursor.map_or(0, |cursor| cursor.count);
+                let mut applied = 0;
+                for _ in 0..replayed {
+                    let Ok(envelope) = events.recv().await else { break };
+                    if already_applied > 0 && envelope.message.ts_event() == from_ts {
+                        already_applied -= 1;
+                        continue;
+                    }
+                    self.apply(&envelope.message);
+                    applied += 1;
+                }
+                info!(target: "EVENT_SOURCING", "Restored {} from snapshot {:?} and {} events", std::any::type_name::<S>(), cursor, applied);
+            },
+            Err(e) => tracing::warn!(target: "EVENT_SOURCING", "Cannot replay events, starting from snapshot: {}", e),
+        }
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                match events.recv().await {
+                    Ok(envelope) => self.apply(&envelope.message),
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::error!(target: "EVENT_SOURCING", "{}, state is incomplete", BusError::lagged::<E>(n))
+                    },
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        vec![handle]
+    }
+}
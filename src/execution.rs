@@ -4,54 +4,1182 @@
 //!
 //! 模拟与交易所的交互，处理订单请求并产生撮合成交事件。
 
-use crate::actor::Actor;
-use crate::bus::MessageBus;
-use crate::message::{FillEvent, OrderRequest};
-use std::sync::Arc;
+use crate::actor::{spawn_named, Actor, Affinity};
+use crate::bus::{BusError, MessageBus};
+use crate::client::{ExecAck, ExecError, ExecutionClient, ExecutionReport};
+use crate::clock::{Clock, LiveClock};
+use crate::costs::{FeeModel, NoFees, NoSlippage, SlippageModel};
+use crate::dedup::SeenWindow;
+use crate::message::{
+    Bar, CancelOrderRequest, CancelRejectReason, CancelRejected, FillEvent, FlattenAll, Liquidity, Message, OpenOrdersQuery,
+    OpenOrdersReport, OrderAccepted, OrderBookSnapshot, OrderCanceled, OrderRejected, OrderRequest, OrderSide,
+    OrderStatus, OrderStatusChanged, OrderTriggered, OrderType, RejectReason, TrackedOrder, TradeTick, TradingHalted,
+    TradingResumed,
+};
+use crate::startup::StartupBarrierHandle;
+use crate::symbol::SymbolRegistry;
+use crate::throttle::{Admission, OrderThrottle, ThrottleConfig, ThrottleStats};
+use crate::validation::{OrderIdCheck, OrderValidator, ValidationConfig};
+use crate::trace::{self, TraceContext};
+use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 use tracing::info;
+use uuid::Uuid;
+
+/// 剩余数量低于此值即视为完全成交，避免浮点误差导致永远无法结束。
+const QTY_EPSILON: f64 = 1e-9;
+
+/// 为撤单拒绝原因记住的已完成（成交或撤销）订单数量上限。
+const CLOSED_ORDER_MEMORY: usize = 10_000;
+
+/// 终结状态的订单默认保留在订单表中的时间。
+const DEFAULT_ORDER_RETENTION: Duration = Duration::from_secs(60);
+
+/// ## `FillModel`
+///
+/// 决定一个订单如何被拆分成一个或多个 `FillEvent`。
+#[derive(Clone, Debug)]
+pub enum FillModel {
+    /// 一次性全部成交（默认行为）。
+    Immediate,
+    /// 每笔子成交最多 `max_child_qty`，相邻两笔之间间隔 `delay_between`。
+    Partial { max_child_qty: f64, delay_between: Duration },
+    /// 每笔子成交的数量为订单总量乘以从 `fill_ratio_distribution` 中抽取的比例，
+    /// 相邻两笔之间间隔 `delay_between`。
+    Probabilistic { fill_ratio_distribution: Uniform<f64>, delay_between: Duration },
+    /// 按参与率成交（POV）：市价单在确认后等待行情，每根 `Bar` 成交该 `Bar` 成交量的
+    /// `participation_rate` 倍（例如 0.1 为 10%），成交价为该 `Bar` 的收盘价，直到全部成交。
+    Participation { participation_rate: f64 },
+}
+
+impl FillModel {
+    /// 检查参数能让订单在有限笔成交内完成：`Partial` 的 `max_child_qty` 与 `Participation` 的参与率必须是正的有限值。
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            FillModel::Partial { max_child_qty, .. } if !(max_child_qty.is_finite() && *max_child_qty > 0.0) => {
+                Err(format!("max_child_qty must be positive, got {}", max_child_qty))
+            },
+            FillModel::Participation { participation_rate } if !(participation_rate.is_finite() && *participation_rate > 0.0) => {
+                Err(format!("participation_rate must be positive, got {}", participation_rate))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// 计算下一笔子成交的数量，不会超过 `leaves_qty`。
+    /// 至少为 `QTY_EPSILON`，概率模型抽到 0 或负的比例时剩余数量仍然递减，不会无限调度空成交。
+    fn next_child_qty(&self, order: &OrderRequest, leaves_qty: f64, rng: &mut StdRng) -> f64 {
+        let qty = match self {
+            FillModel::Immediate => leaves_qty,
+            FillModel::Partial { max_child_qty, .. } => *max_child_qty,
+            FillModel::Probabilistic { fill_ratio_distribution, .. } => {
+                order.quantity * fill_ratio_distribution.sample(rng)
+            }
+            FillModel::Participation { .. } => leaves_qty,
+        };
+        qty.max(QTY_EPSILON).min(leaves_qty)
+    }
+
+    /// 相邻两笔子成交之间的间隔。
+    fn delay_between(&self) -> Duration {
+        match self {
+            FillModel::Immediate | FillModel::Participation { .. } => Duration::ZERO,
+            FillModel::Partial { delay_between, .. } | FillModel::Probabilistic { delay_between, .. } => *delay_between,
+        }
+    }
+
+    /// 按参与率成交时的参与率，其他模型为 `None`。
+    fn participation_rate(&self) -> Option<f64> {
+        match self {
+            FillModel::Participation { participation_rate } => Some(*participation_rate),
+            _ => None,
+        }
+    }
+}
+
+/// ## `LatencyModel`
+///
+/// 订单从提交到确认 (`OrderAccepted`)、从确认到首笔成交之间的模拟延迟。
+/// 每段延迟都会额外加上 `[0, jitter]` 之间的随机抖动（来自引擎的共享随机数生成器）。
+///
+/// 同一 symbol 的订单始终按提交顺序确认和成交，即使抖动本会使它们交换顺序。
+#[derive(Clone, Debug, Default)]
+pub struct LatencyModel {
+    pub ack_latency: Duration,
+    pub fill_latency: Duration,
+    pub jitter: Duration,
+}
+
+impl LatencyModel {
+    fn sample(&self, base: Duration, rng: &mut StdRng) -> u64 {
+        let jitter = if self.jitter.is_zero() { 0 } else { rng.gen_range(0..=self.jitter.as_nanos() as u64) };
+        base.as_nanos() as u64 + jitter
+    }
+}
+
+/// ## `StopTrigger`
+///
+/// 止损单的触发来源。买入止损在价格上涨到触发价及以上时触发，卖出止损在下跌到触发价及以下时触发。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StopTrigger {
+    /// `Bar` 的收盘价越过触发价时触发，参考价为收盘价。
+    #[default]
+    Close,
+    /// `Bar` 的最高/最低价触及触发价时触发，参考价为触发价；
+    /// 开盘价已经越过触发价（跳空）时参考价为开盘价。
+    HighLow,
+    /// 逐笔成交 `TradeTick` 的价格越过触发价时触发，参考价为该笔成交价。
+    LastTrade,
+    /// 订单簿快照的对手价越过触发价时触发（买入看卖一，卖出看买一），参考价为该对手价。
+    /// 某个 symbol 尚未收到任何快照时按 `HighLow` 规则使用 `Bar`。
+    BidAsk,
+}
+
+/// 可能触发止损单的一次行情更新。
+enum MarketEvent<'a> {
+    Bar(&'a Bar),
+    Trade(&'a TradeTick),
+    Quote { bid: Option<f64>, ask: Option<f64> },
+}
+
+impl StopTrigger {
+    /// 若触发价为 `stop` 的止损单被该行情触发，返回触发时的参考价（尚未计入滑点）。
+    /// `quoted` 表示该 symbol 是否已有订单簿快照。
+    fn triggered_price(&self, side: &OrderSide, stop: f64, event: &MarketEvent, quoted: bool) -> Option<f64> {
+        let crossed = |price: f64| match side {
+            OrderSide::Buy => price >= stop,
+            OrderSide::Sell => price <= stop,
+        };
+        match (self, event) {
+            (StopTrigger::Close, MarketEvent::Bar(bar)) => crossed(bar.close).then_some(bar.close),
+            (StopTrigger::HighLow, MarketEvent::Bar(bar)) => bar_extreme_trigger(side, stop, bar),
+            (StopTrigger::BidAsk, MarketEvent::Bar(bar)) if !quoted => bar_extreme_trigger(side, stop, bar),
+            (StopTrigger::LastTrade, MarketEvent::Trade(trade)) => crossed(trade.price).then_some(trade.price),
+            (StopTrigger::BidAsk, MarketEvent::Quote { bid, ask }) => match side {
+                OrderSide::Buy => ask.filter(|ask| crossed(*ask)),
+                OrderSide::Sell => bid.filter(|bid| crossed(*bid)),
+            },
+            _ => None,
+        }
+    }
+}
+
+/// 按 `Bar` 的最高/最低价判断触发。开盘价已越过触发价时按开盘价（跳空后的价格）而不是触发价成交。
+/// 没有开盘价的 `Bar`（`open` 为 0）不做跳空判断。
+fn bar_extreme_trigger(side: &OrderSide, stop: f64, bar: &Bar) -> Option<f64> {
+    let has_open = bar.open > 0.0;
+    match side {
+        OrderSide::Buy if has_open && bar.open >= stop => Some(bar.open),
+        OrderSide::Buy => (bar.high >= stop).then_some(stop),
+        OrderSide::Sell if has_open && bar.open <= stop => Some(bar.open),
+        OrderSide::Sell => (bar.low <= stop).then_some(stop),
+    }
+}
+
+/// 一个尚未完全成交的订单。
+struct WorkingOrder {
+    order: OrderRequest,
+    leaves_qty: f64,
+    /// 处理该订单时的追踪上下文，延后发布的确认与成交沿用它，归入订单的因果链。
+    trace: Option<TraceContext>,
+}
+
+/// 调度队列中的动作。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ScheduledAction {
+    /// 发布 `OrderAccepted`，并调度首笔成交。
+    Ack,
+    /// 生成下一笔子成交。
+    Fill,
+}
+
+/// 调度条目：(到期时间, 序号, 订单 ID, 动作)。序号保证到期时间相同时按调度顺序出队。
+type ScheduleEntry = Reverse<(u64, u64, Uuid, ScheduledAction)>;
 
 /// ## `SimulatedExecutionEngine`
 ///
-/// - 消费 `OrderRequest` 消息。
-/// - 生产 `FillEvent` 消息来模拟成交回报。
+/// - 消费 `OrderRequest` 消息，校验失败时发布 `OrderRejected`，
+///   否则经过 `LatencyModel` 的确认延迟后发布 `OrderAccepted`。
+///   保留时间内重复的订单 ID 以 `DuplicateOrderId` 拒绝，原订单不受影响；
+///   启用 `ValidationConfig::with_idempotent_resubmission` 后，完全相同的重复提交
+///   改为回复一次原订单当前状态的 `OrderStatusChanged`。
+/// - 启用 `with_throttle` 后，超出速率或未结束订单数限制的订单按 `ThrottleMode`
+///   以 `Throttled` 原因拒绝，或排队等待容量释放后再确认；排队中的订单可以被撤单。
+/// - 消费 `TradingHalted` / `TradingResumed` 消息，暂停期间的订单以 `TradingHalted` 原因被拒绝。
+/// - 通过 `with_cash_balance` 设置可用资金后，所需资金超过可用资金的买单以 `InsufficientFunds` 原因被拒绝。
+/// - 消费 `FlattenAll` 消息，撤销所有未结束的订单，再为每个净持仓不为零的 symbol 提交反向市价单，
+///   价格为该 symbol 最近的行情价（没有行情时为最近成交价）。同时到达的 `FlattenAll` 总是先于
+///   `TradingHalted` 处理，因此紧跟其后的暂停不会拒绝平仓单。
+/// - 消费 `CancelOrderRequest` 消息，撤销尚未完全成交的订单（包括挂单）并发布 `OrderCanceled`；
+///   订单已成交、已撤销或未知时发布带原因的 `CancelRejected`。
+/// - 市价单根据 `FillModel` 生产一个或多个 `FillEvent` 消息来模拟成交回报，
+///   每笔成交都经过滑点模型和手续费模型的处理。
+///   市价单与触发后的止损单按 `Taker` 计费，挂单后成交的限价单按 `Maker` 计费；
+///   每笔成交带有 `SIM-` 前缀的 `venue_fill_id`。
+///   使用 `FillModel::Participation` 时市价单（包括触发后的 `StopMarket`）改为挂单，
+///   每根 `Bar` 按参与率以收盘价成交一部分，直到全部成交。
+/// - 限价单和止损单在确认后按 symbol 挂单，消费 `Bar` 消息判断是否可成交：
+///   买入限价单在 `low` 低于限价时、卖出限价单在 `high` 高于限价时按限价全部成交。
+///   同一根 `Bar` 上可成交的挂单按确认顺序（时间优先）成交，成交时间为该 `Bar` 的 `ts_event`。
+/// - 止损单按 `with_stop_trigger` 选择的来源（`Bar`、`TradeTick` 或 `OrderBookSnapshot`）判断触发，
+///   触发时发布 `OrderTriggered` 并进入 `Triggered` 状态：
+///   `StopMarket` 以触发时的参考价转为市价单，沿用市价单的成交延迟、`FillModel` 与滑点；
+///   `StopLimit` 转为限价单，参考价不劣于限价时立即以参考价成交（滑点不超过限价），否则挂单等待。
+///   行情跳空越过触发价时参考价为跳空后的价格，而不是触发价。
+/// - 维护权威的订单表：每次状态变化都发布 `OrderStatusChanged`，
+///   收到 `OpenOrdersQuery` 时发布包含所有未结束订单的 `OpenOrdersReport`。
+///   终结状态的订单在 `with_order_retention` 设置的保留时间后从订单表中移除。
+///
+/// 所有定时行为都通过 `Clock` 完成，使用 `VirtualClock` 时回测既快速又可复现。
+///
+/// 作为 `ExecutionClient` 使用时（交给 `ExecutionEngine`），引擎应使用一条独立的 `MessageBus`
+/// 作为“场所总线”：订单、撤单和行情由 `ExecutionEngine` 转发进来，回报再被转发回系统总线。
+///
+/// **撤单竞争规则**：引擎总是先处理所有到期时间不晚于当前时间的确认/成交，
+/// 再处理新到达的消息。因此撤单到达时，到期时间早于或等于撤单到达时间的成交先发生，
+/// 撤单只作用于剩余数量（全部成交后撤单会收到 `CancelRejected { reason: AlreadyFilled }`）；
+/// 到期时间晚于撤单到达时间的成交被撤单取消，不会再发生。
 pub struct SimulatedExecutionEngine {
     bus: MessageBus,
+    fill_model: FillModel,
+    latency: LatencyModel,
+    validation: ValidationConfig,
+    slippage: Box<dyn SlippageModel>,
+    fees: Box<dyn FeeModel>,
+    clock: Arc<dyn Clock>,
+    seed: Option<u64>,
+    stop_trigger: StopTrigger,
+    order_retention: Duration,
+    throttle: Option<ThrottleConfig>,
+    throttle_stats: Mutex<ThrottleStats>,
+    /// 每个 symbol 由本引擎的成交累计的净持仓，供 `FlattenAll` 平仓。
+    positions: Mutex<HashMap<String, NetPosition>>,
+    /// 可用资金，未设置时为 `None`，不检查 `InsufficientFunds`。
+    cash: Mutex<Option<f64>>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+    /// 下一笔成交的 `venue_fill_id` 序号。
+    next_fill_id: AtomicU64,
+    /// 作为 `ExecutionClient` 使用时的回报队列。
+    report_tx: mpsc::UnboundedSender<ExecutionReport>,
+    report_rx: tokio::sync::Mutex<mpsc::UnboundedReceiver<ExecutionReport>>,
 }
 
 impl SimulatedExecutionEngine {
-    pub fn new(bus: MessageBus) -> Self { Self { bus } }
+    pub fn new(bus: MessageBus) -> Self {
+        let (report_tx, report_rx) = mpsc::unbounded_channel();
+        Self {
+            bus,
+            fill_model: FillModel::Immediate,
+            latency: LatencyModel::default(),
+            validation: ValidationConfig::default(),
+            slippage: Box::new(NoSlippage),
+            fees: Box::new(NoFees),
+            clock: Arc::new(LiveClock),
+            seed: None,
+            stop_trigger: StopTrigger::default(),
+            order_retention: DEFAULT_ORDER_RETENTION,
+            throttle: None,
+            throttle_stats: Mutex::new(ThrottleStats::default()),
+            positions: Mutex::new(HashMap::new()),
+            cash: Mutex::new(None),
+            barrier: Mutex::new(None),
+            next_fill_id: AtomicU64::new(1),
+            report_tx,
+            report_rx: tokio::sync::Mutex::new(report_rx),
+        }
+    }
+
+    /// 设置成交模型。
+    ///
+    /// **Panics**：`FillModel::validate` 失败时 panic。
+    pub fn with_fill_model(mut self, fill_model: FillModel) -> Self {
+        if let Err(e) = fill_model.validate() {
+            panic!("invalid fill model: {}", e);
+        }
+        self.fill_model = fill_model;
+        self
+    }
+
+    /// 设置确认与成交延迟模型。
+    pub fn with_latency(mut self, latency: LatencyModel) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// 设置订单校验配置，可以禁用部分拒绝原因。
+    pub fn with_validation(mut self, validation: ValidationConfig) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// 设置滑点模型，可以通过 `SlippageConfig::build` 从配置创建。
+    pub fn with_slippage_model(mut self, slippage: Box<dyn SlippageModel>) -> Self {
+        self.slippage = slippage;
+        self
+    }
+
+    /// 设置手续费模型，可以通过 `FeeConfig::build` 从配置创建。
+    pub fn with_fee_model(mut self, fees: Box<dyn FeeModel>) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// 设置时间来源。模拟模式下应传入 `SimulationDriver::clock()`。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置随机数种子，使概率成交模型、滑点模型和延迟抖动可以复现。
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// 设置止损单的触发规则。
+    pub fn with_stop_trigger(mut self, stop_trigger: StopTrigger) -> Self {
+        self.stop_trigger = stop_trigger;
+        self
+    }
+
+    /// 设置终结状态（成交、撤销、拒绝）的订单在订单表中保留的时间，默认 60 秒。
+    pub fn with_order_retention(mut self, retention: Duration) -> Self {
+        self.order_retention = retention;
+        self
+    }
+
+    /// 启用下单限流。时间取自引擎的 `Clock`，回测中的限流行为是确定的。
+    pub fn with_throttle(mut self, throttle: ThrottleConfig) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// 设置初始可用资金。买入成交扣除成交额与手续费，卖出成交加回成交额并扣除手续费；
+    /// 参考价乘以数量超过可用资金的买单以 `InsufficientFunds` 原因拒绝。
+    pub fn with_cash_balance(self, cash: f64) -> Self {
+        *self.cash.lock().unwrap() = Some(cash);
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 因限流被拒绝或排队的订单数量。
+    pub fn throttle_stats(&self) -> ThrottleStats {
+        *self.throttle_stats.lock().unwrap()
+    }
+
+    /// 当前可用资金，未通过 `with_cash_balance` 设置时为 `None`。
+    pub fn cash_balance(&self) -> Option<f64> {
+        *self.cash.lock().unwrap()
+    }
+
+    /// 本引擎的成交累计的 `symbol` 净持仓，多头为正、空头为负。
+    pub fn net_position(&self, symbol: &str) -> f64 {
+        self.positions.lock().unwrap().get(symbol).map_or(0.0, |position| position.quantity)
+    }
+
+    async fn publish<M: Message>(&self, msg: M) {
+        info!(target: "EXECUTION", "Publishing {:?}", msg);
+        if let Err(e) = self.bus.publish(msg).await {
+             tracing::error!(target: "EXECUTION", "Failed to publish: {}", e);
+        }
+    }
+
+    /// 为工作中的订单生成下一笔子成交。
+    /// 返回 `true` 表示订单仍有剩余数量，需要继续调度。
+    async fn fill_next(&self, working: &mut WorkingOrder, rng: &mut StdRng, ts_event: u64) -> bool {
+        let qty = self.fill_model.next_child_qty(&working.order, working.leaves_qty, rng);
+        let price = self.slippage.fill_price(&working.order.side, working.order.price, qty, rng);
+        self.publish_fill(working, qty, price, Liquidity::Taker, ts_event).await
+    }
+
+    /// 以给定价格成交 `qty`（剩余数量足够小时视为全部成交）并发布 `FillEvent`，
+    /// 手续费由手续费模型按流动性方向计算，以 symbol 的计价资产计价。
+    /// 返回 `true` 表示订单仍有剩余数量。
+    async fn publish_fill(
+        &self,
+        working: &mut WorkingOrder,
+        mut qty: f64,
+        price: f64,
+        liquidity: Liquidity,
+        ts_event: u64,
+    ) -> bool {
+        let mut leaves_qty = working.leaves_qty - qty;
+        if leaves_qty <= QTY_EPSILON {
+            qty = working.leaves_qty;
+            leaves_qty = 0.0;
+        }
+        working.leaves_qty = leaves_qty;
+
+        let fill = FillEvent {
+            order_id: working.order.id,
+            symbol: working.order.symbol.clone(),
+            side: working.order.side.clone(),
+            price,
+            quantity: qty,
+            leaves_qty,
+            is_final: leaves_qty == 0.0,
+            commission: self.fees.commission(price, qty, liquidity),
+            commission_currency: SymbolRegistry::quote_currency(&working.order.symbol).to_string(),
+            liquidity,
+            ts_event,
+            venue_fill_id: Some(format!("SIM-{}", self.next_fill_id.fetch_add(1, Ordering::Relaxed))),
+            correlation_id: Some(working.order.id),
+        };
+        {
+            let mut positions = self.positions.lock().unwrap();
+            let position = positions.entry(fill.symbol.clone()).or_default();
+            position.quantity += match fill.side {
+                OrderSide::Buy => qty,
+                OrderSide::Sell => -qty,
+            };
+            position.last_fill_price = price;
+        }
+        if let Some(cash) = self.cash.lock().unwrap().as_mut() {
+            *cash += match fill.side {
+                OrderSide::Buy => -price * qty,
+                OrderSide::Sell => price * qty,
+            } - fill.commission;
+        }
+        self.publish(fill).await;
+        leaves_qty > 0.0
+    }
+}
+
+/// 一个 symbol 由引擎成交累计的净持仓。
+#[derive(Clone, Copy, Debug, Default)]
+struct NetPosition {
+    quantity: f64,
+    last_fill_price: f64,
+}
+
+/// 执行引擎任务内部的可变状态。
+struct EngineState {
+    rng: StdRng,
+    validator: OrderValidator,
+    working: HashMap<Uuid, WorkingOrder>,
+    schedule: BinaryHeap<ScheduleEntry>,
+    /// 每个 symbol 已确认、等待行情的限价/止损单，按确认顺序排列。
+    /// 已撤销的订单不会立即移除，而是在该 symbol 下一次行情到达时跳过。
+    resting: HashMap<String, Vec<Uuid>>,
+    seq: u64,
+    /// 每个 symbol 最近一次调度的确认/成交时间，用于保证同一 symbol 内的先后顺序。
+    last_ack_due: HashMap<String, u64>,
+    last_fill_due: HashMap<String, u64>,
+    /// 最近完全成交与已撤销的订单，用于给出撤单拒绝原因。
+    filled: SeenWindow,
+    canceled: SeenWindow,
+    /// 权威订单表，包括尚在保留期内的终结订单。
+    orders: HashMap<Uuid, TrackedOrder>,
+    /// 终结订单的 (终结时间, 订单 ID)，按时间先后排列，用于过期移除。
+    terminal: VecDeque<(u64, Uuid)>,
+    /// 下单限流，未配置时为 `None`。
+    throttle: Option<OrderThrottle>,
+    /// 已收到过订单簿快照的 symbol，`StopTrigger::BidAsk` 据此决定是否退回到 `Bar`。
+    quoted: HashSet<String>,
+    /// 每个 symbol 最近的行情价（`Bar` 收盘价、成交价或买卖中间价），作为平仓单的价格。
+    last_price: HashMap<String, f64>,
+}
+
+impl EngineState {
+    fn new(engine: &SimulatedExecutionEngine) -> Self {
+        Self {
+            rng: match engine.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
+            validator: OrderValidator::new(engine.validation.clone()),
+            working: HashMap::new(),
+            schedule: BinaryHeap::new(),
+            resting: HashMap::new(),
+            seq: 0,
+            last_ack_due: HashMap::new(),
+            last_fill_due: HashMap::new(),
+            filled: SeenWindow::new(CLOSED_ORDER_MEMORY),
+            canceled: SeenWindow::new(CLOSED_ORDER_MEMORY),
+            orders: HashMap::new(),
+            terminal: VecDeque::new(),
+            throttle: engine.throttle.clone().map(OrderThrottle::new),
+            quoted: HashSet::new(),
+            last_price: HashMap::new(),
+        }
+    }
+
+    /// 记录一次订单状态变化并发布 `OrderStatusChanged`。
+    async fn transition(&mut self, engine: &SimulatedExecutionEngine, working: &WorkingOrder, status: OrderStatus, ts: u64) {
+        self.evict_expired(engine, ts);
+        let filled_qty = working.order.quantity - working.leaves_qty;
+        let tracked = self.orders.entry(working.order.id).or_insert_with(|| TrackedOrder {
+            order: working.order.clone(),
+            status,
+            filled_qty,
+            leaves_qty: working.leaves_qty,
+            transitions: Vec::new(),
+        });
+        let previous = (!tracked.transitions.is_empty()).then_some(tracked.status);
+        tracked.status = status;
+        tracked.filled_qty = filled_qty;
+        tracked.leaves_qty = working.leaves_qty;
+        tracked.transitions.push((status, ts));
+        if status.is_terminal() {
+            self.terminal.push_back((ts, working.order.id));
+            if let Some(throttle) = &mut self.throttle {
+                throttle.on_closed(&working.order);
+            }
+        }
+
+        let changed = OrderStatusChanged {
+            order_id: working.order.id,
+            symbol: working.order.symbol.clone(),
+            previous,
+            status,
+            filled_qty,
+            leaves_qty: working.leaves_qty,
+            ts_event: ts,
+        };
+        engine.publish(changed).await;
+    }
+
+    /// 移除超过保留时间的终结订单。
+    fn evict_expired(&mut self, engine: &SimulatedExecutionEngine, now: u64) {
+        let retention = engine.order_retention.as_nanos() as u64;
+        while let Some(&(ts, order_id)) = self.terminal.front() {
+            if ts.saturating_add(retention) > now {
+                break;
+            }
+            self.terminal.pop_front();
+            self.orders.remove(&order_id);
+        }
+    }
+
+    async fn on_query(&mut self, engine: &SimulatedExecutionEngine, query: OpenOrdersQuery) {
+        let now = engine.clock.now_nanos();
+        self.evict_expired(engine, now);
+        let mut orders: Vec<TrackedOrder> = self
+            .orders
+            .values()
+            .filter(|tracked| !tracked.status.is_terminal())
+            .filter(|tracked| query.symbol.as_ref().is_none_or(|symbol| *symbol == tracked.order.symbol))
+            .cloned()
+            .collect();
+        orders.sort_by_key(|tracked| (tracked.transitions.first().map(|(_, ts)| *ts), tracked.order.id));
+        let report = OpenOrdersReport { symbol: query.symbol, orders, ts_event: now };
+        engine.publish(report).await;
+    }
+
+    fn push(&mut self, due: u64, order_id: Uuid, action: ScheduledAction) {
+        self.seq += 1;
+        self.schedule.push(Reverse((due, self.seq, order_id, action)));
+    }
+
+    fn next_due(&self) -> Option<u64> {
+        self.schedule.peek().map(|Reverse((due, ..))| *due)
+    }
+
+    async fn on_order(&mut self, engine: &SimulatedExecutionEngine, order: OrderRequest) {
+        info!(target: "EXECUTION", "Received {:?}. Simulating fill...", order);
+        let now = engine.clock.now_nanos();
+        if self.validator.idempotent_resubmission() && self.validator.check_order_id(&order, now) == OrderIdCheck::Resubmission {
+            self.replay_outcome(engine, &order, now).await;
+            return;
+        }
+        if let Err((reason, detail)) = self.validator.validate(&order, now, engine.cash_balance()) {
+            let rejected = OrderRejected { order_id: order.id, symbol: order.symbol.clone(), reason, detail };
+            if reason == RejectReason::DuplicateOrderId {
+                // 原订单的状态不受影响，因此不发布状态变化
+                tracing::warn!(target: "EXECUTION", "Duplicate order id {} rejected", order.id);
+                engine.publish(rejected).await;
+                return;
+            }
+            // 被拒绝的订单不进入订单表，以免重复 ID 覆盖已有订单，但仍发布状态变化
+            let changed = OrderStatusChanged {
+                order_id: order.id,
+                symbol: order.symbol.clone(),
+                previous: None,
+                status: OrderStatus::Rejected,
+                filled_qty: 0.0,
+                leaves_qty: 0.0,
+                ts_event: now,
+            };
+            engine.publish(changed).await;
+            engine.publish(rejected).await;
+            return;
+        }
+
+        let working = WorkingOrder { leaves_qty: order.quantity, order, trace: trace::current() };
+        self.transition(engine, &working, OrderStatus::Submitted, now).await;
+        let admission = match &mut self.throttle {
+            Some(throttle) => {
+                let admission = throttle.admit(&working.order, now);
+                *engine.throttle_stats.lock().unwrap() = throttle.stats();
+                admission
+            },
+            None => Admission::Admit,
+        };
+        match admission {
+            Admission::Admit => self.accept(engine, working, now),
+            Admission::Queued => {
+                tracing::warn!(target: "EXECUTION", "Order {} throttled, queued", working.order.id);
+            },
+            Admission::Rejected(detail) => {
+                tracing::warn!(target: "EXECUTION", "Order {} throttled, rejected", working.order.id);
+                self.transition(engine, &working, OrderStatus::Rejected, now).await;
+                let rejected = OrderRejected {
+                    order_id: working.order.id,
+                    symbol: working.order.symbol,
+                    reason: RejectReason::Throttled,
+                    detail,
+                };
+                engine.publish(rejected).await;
+            },
+        }
+    }
+
+    /// 接受一个已通过校验与限流的订单，调度它的确认。
+    fn accept(&mut self, engine: &SimulatedExecutionEngine, working: WorkingOrder, now: u64) {
+        let latency = engine.latency.sample(engine.latency.ack_latency, &mut self.rng);
+        // 不早于同一 symbol 上一笔订单的确认时间
+        let last = self.last_ack_due.entry(working.order.symbol.clone()).or_insert(0);
+        let due = (now + latency).max(*last);
+        *last = due;
+
+        let order_id = working.order.id;
+        self.working.insert(order_id, working);
+        self.push(due, order_id, ScheduledAction::Ack);
+    }
+
+    /// 放行限流队列中已有容量的订单。
+    fn release_throttled(&mut self, engine: &SimulatedExecutionEngine) {
+        let now = engine.clock.now_nanos();
+        let Some(throttle) = &mut self.throttle else { return };
+        for order in throttle.release(now) {
+            info!(target: "EXECUTION", "Releasing throttled order {}", order.id);
+            self.accept(engine, WorkingOrder { leaves_qty: order.quantity, order, trace: None }, now);
+        }
+    }
+
+    /// 限流队列下一次需要检查的时间。
+    fn next_release_at(&self) -> Option<u64> {
+        self.throttle.as_ref().and_then(OrderThrottle::next_release_at)
+    }
+
+    /// 幂等重放：以原订单当前的状态回复一次 `OrderStatusChanged`，不产生新的成交。
+    async fn replay_outcome(&mut self, engine: &SimulatedExecutionEngine, order: &OrderRequest, now: u64) {
+        self.evict_expired(engine, now);
+        let Some(tracked) = self.orders.get(&order.id) else {
+            tracing::warn!(target: "EXECUTION", "Resubmitted order {} is no longer in the order table", order.id);
+            return;
+        };
+        info!(target: "EXECUTION", "Order {} resubmitted, replaying status {}", order.id, tracked.status);
+        let changed = OrderStatusChanged {
+            order_id: order.id,
+            symbol: order.symbol.clone(),
+            previous: Some(tracked.status),
+            status: tracked.status,
+            filled_qty: tracked.filled_qty,
+            leaves_qty: tracked.leaves_qty,
+            ts_event: now,
+        };
+        engine.publish(changed).await;
+    }
+
+    async fn on_cancel(&mut self, engine: &SimulatedExecutionEngine, cancel: CancelOrderRequest) {
+        let queued = self.throttle.as_mut().and_then(|throttle| throttle.remove_queued(cancel.order_id));
+        let working = queued.map(|order| WorkingOrder { leaves_qty: order.quantity, order, trace: None });
+        match working.or_else(|| self.working.remove(&cancel.order_id)) {
+            Some(order_state) => {
+                // 调度队列中该订单剩余的条目会在出队时因找不到订单而被忽略
+                self.canceled.insert(cancel.order_id);
+                self.transition(engine, &order_state, OrderStatus::Canceled, engine.clock.now_nanos()).await;
+                let canceled = OrderCanceled {
+                    order_id: order_state.order.id,
+                    symbol: order_state.order.symbol,
+                    remaining_qty: order_state.leaves_qty,
+                };
+                engine.publish(canceled).await;
+            },
+            None => {
+                let reason = if self.filled.contains(&cancel.order_id) {
+                    CancelRejectReason::AlreadyFilled
+                } else if self.canceled.contains(&cancel.order_id) {
+                    CancelRejectReason::AlreadyCanceled
+                } else {
+                    CancelRejectReason::UnknownOrder
+                };
+                tracing::warn!(target: "EXECUTION", "Cancel for order {} rejected: {}", cancel.order_id, reason);
+                engine.publish(CancelRejected { order_id: cancel.order_id, reason }).await;
+            },
+        }
+    }
+
+    /// 用新到达的行情检查该 symbol 的挂单，按时间优先成交可成交的限价单、触发越过触发价的止损单。
+    /// 限价单只由 `Bar` 撮合，止损单的触发来源由 `StopTrigger` 决定。
+    async fn on_market_event(&mut self, engine: &SimulatedExecutionEngine, symbol: &str, event: MarketEvent<'_>, ts: u64) {
+        let Some(order_ids) = self.resting.remove(symbol) else { return };
+        let quoted = self.quoted.contains(symbol);
+        let mut still_resting = Vec::with_capacity(order_ids.len());
+        for order_id in order_ids {
+            // 订单可能已被撤销
+            let Some(order_state) = self.working.remove(&order_id) else { continue };
+            let trace = order_state.trace.clone();
+            if trace::in_context(trace, self.on_resting(engine, order_state, &event, quoted, ts)).await {
+                still_resting.push(order_id);
+            }
+        }
+        if !still_resting.is_empty() {
+            self.resting.insert(symbol.to_string(), still_resting);
+        }
+    }
+
+    /// 用行情检查一笔挂单，返回 `true` 表示订单继续挂单。在订单的追踪上下文中运行。
+    async fn on_resting(
+        &mut self,
+        engine: &SimulatedExecutionEngine,
+        mut order_state: WorkingOrder,
+        event: &MarketEvent<'_>,
+        quoted: bool,
+        ts: u64,
+    ) -> bool {
+        let order_id = order_state.order.id;
+        let order = &order_state.order;
+        match order.order_type {
+            OrderType::Limit => {
+                // 挂单的限价单被动成交
+                let fills = match (event, &order.side) {
+                    (MarketEvent::Bar(bar), OrderSide::Buy) => bar.low < order.price,
+                    (MarketEvent::Bar(bar), OrderSide::Sell) => bar.high > order.price,
+                    _ => false,
+                };
+                if fills {
+                    let price = order.price;
+                    self.complete(engine, order_state, price, Liquidity::Maker, ts).await;
+                    return false;
+                }
+            },
+            OrderType::StopMarket | OrderType::StopLimit => {
+                let stop = order.stop_price().unwrap_or(order.price);
+                if let Some(reference) = engine.stop_trigger.triggered_price(&order.side, stop, event, quoted) {
+                    return self.trigger(engine, order_state, stop, reference, ts).await;
+                }
+            },
+            OrderType::Market => {
+                // 只有按参与率成交的市价单会挂单，每根 Bar 成交其成交量的一部分
+                let rate = engine.fill_model.participation_rate().unwrap_or(0.0);
+                if let MarketEvent::Bar(bar) = event {
+                    let slice = bar.volume * rate;
+                    if slice > 0.0 {
+                        if !engine.publish_fill(&mut order_state, slice, bar.close, Liquidity::Taker, ts).await {
+                            self.filled.insert(order_id);
+                            self.transition(engine, &order_state, OrderStatus::Filled, ts).await;
+                            return false;
+                        }
+                        self.transition(engine, &order_state, OrderStatus::PartiallyFilled, ts).await;
+                    }
+                }
+            },
+        }
+        self.working.insert(order_id, order_state);
+        true
+    }
+
+    /// 触发一笔止损单：发布 `OrderTriggered`，`StopMarket` 转为市价单调度成交，
+    /// `StopLimit` 转为限价单，可成交时立即主动成交。返回 `true` 表示订单作为限价单继续挂单。
+    async fn trigger(
+        &mut self,
+        engine: &SimulatedExecutionEngine,
+        mut order_state: WorkingOrder,
+        stop: f64,
+        reference: f64,
+        ts: u64,
+    ) -> bool {
+        let order_id = order_state.order.id;
+        let converted = match order_state.order.order_type {
+            OrderType::StopMarket => OrderType::Market,
+            _ => OrderType::Limit,
+        };
+        info!(target: "EXECUTION", "Stop order {} triggered at {} (stop {})", order_id, reference, stop);
+        let triggered = OrderTriggered {
+            order_id,
+            symbol: order_state.order.symbol.clone(),
+            order_type: converted,
+            trigger_price: stop,
+            reference_price: reference,
+            ts_event: ts,
+        };
+        engine.publish(triggered).await;
+        self.transition(engine, &order_state, OrderStatus::Triggered, ts).await;
+        order_state.order.order_type = converted;
+
+        if converted == OrderType::Market {
+            // 市价单以参考价为基准，沿用成交延迟、成交模型与滑点
+            order_state.order.price = reference;
+            if engine.fill_model.participation_rate().is_some() {
+                self.working.insert(order_id, order_state);
+                return true;
+            }
+            let symbol = order_state.order.symbol.clone();
+            self.working.insert(order_id, order_state);
+            self.schedule_fill(order_id, &symbol, engine.clock.now_nanos(), engine);
+            return false;
+        }
+
+        let limit = order_state.order.price;
+        let marketable = match order_state.order.side {
+            OrderSide::Buy => reference <= limit,
+            OrderSide::Sell => reference >= limit,
+        };
+        if !marketable {
+            self.working.insert(order_id, order_state);
+            return true;
+        }
+        let side = order_state.order.side.clone();
+        let slipped = engine.slippage.fill_price(&side, reference, order_state.leaves_qty, &mut self.rng);
+        // 滑点不会越过限价
+        let price = match side {
+            OrderSide::Buy => slipped.min(limit),
+            OrderSide::Sell => slipped.max(limit),
+        };
+        self.complete(engine, order_state, price, Liquidity::Taker, ts).await;
+        false
+    }
+
+    /// 以给定价格成交订单的全部剩余数量，订单进入 `Filled`。
+    async fn complete(
+        &mut self,
+        engine: &SimulatedExecutionEngine,
+        mut order_state: WorkingOrder,
+        price: f64,
+        liquidity: Liquidity,
+        ts: u64,
+    ) {
+        let qty = order_state.leaves_qty;
+        engine.publish_fill(&mut order_state, qty, price, liquidity, ts).await;
+        self.filled.insert(order_state.order.id);
+        self.transition(engine, &order_state, OrderStatus::Filled, ts).await;
+    }
+
+    /// 在 `from` 之后按成交延迟调度一笔市价成交，不早于同一 symbol 上一笔成交的时间。
+    fn schedule_fill(&mut self, order_id: Uuid, symbol: &str, from: u64, engine: &SimulatedExecutionEngine) {
+        let latency = engine.latency.sample(engine.latency.fill_latency, &mut self.rng);
+        let last = self.last_fill_due.entry(symbol.to_string()).or_insert(0);
+        let fill_due = (from + latency).max(*last);
+        *last = fill_due;
+        self.push(fill_due, order_id, ScheduledAction::Fill);
+    }
+
+    async fn on_bar(&mut self, engine: &SimulatedExecutionEngine, bar: Bar) {
+        self.last_price.insert(bar.symbol.clone(), bar.close);
+        self.on_market_event(engine, &bar.symbol, MarketEvent::Bar(&bar), bar.ts_event).await;
+    }
+
+    async fn on_trade(&mut self, engine: &SimulatedExecutionEngine, trade: TradeTick) {
+        self.last_price.insert(trade.symbol.clone(), trade.price);
+        self.on_market_event(engine, &trade.symbol, MarketEvent::Trade(&trade), trade.ts_event).await;
+    }
+
+    /// 记录该 symbol 已有报价，并以最优买价/卖价检查止损单。
+    async fn on_book_snapshot(&mut self, engine: &SimulatedExecutionEngine, book: OrderBookSnapshot) {
+        self.quoted.insert(book.symbol.clone());
+        let bid = book.bids.iter().map(|level| level.price).reduce(f64::max);
+        let ask = book.asks.iter().map(|level| level.price).reduce(f64::min);
+        if let (Some(bid), Some(ask)) = (bid, ask) {
+            self.last_price.insert(book.symbol.clone(), (bid + ask) / 2.0);
+        }
+        self.on_market_event(engine, &book.symbol, MarketEvent::Quote { bid, ask }, book.ts_event).await;
+    }
+
+    /// 撤销所有未结束的订单，再以反向市价单平掉每个 symbol 的净持仓。
+    async fn on_flatten(&mut self, engine: &SimulatedExecutionEngine, flatten: FlattenAll) {
+        tracing::warn!(target: "EXECUTION", "Flattening all positions: {}", flatten.reason);
+        let mut open: Vec<(Uuid, String)> = self
+            .orders
+            .values()
+            .filter(|tracked| !tracked.status.is_terminal())
+            .map(|tracked| (tracked.order.id, tracked.order.symbol.clone()))
+            .collect();
+        open.sort();
+        for (order_id, symbol) in open {
+            self.on_cancel(engine, CancelOrderRequest { order_id, symbol }).await;
+        }
+
+        let mut positions: Vec<(String, NetPosition)> =
+            engine.positions.lock().unwrap().iter().map(|(symbol, position)| (symbol.clone(), *position)).collect();
+        positions.sort_by(|a, b| a.0.cmp(&b.0));
+        for (symbol, position) in positions {
+            if position.quantity.abs() <= QTY_EPSILON {
+                continue;
+            }
+            let side = if position.quantity > 0.0 { OrderSide::Sell } else { OrderSide::Buy };
+            let price = self.last_price.get(&symbol).copied().unwrap_or(position.last_fill_price);
+            let order = OrderRequest {
+                id: Uuid::new_v4(),
+                symbol,
+                side,
+                order_type: OrderType::Market,
+                price,
+                quantity: position.quantity.abs(),
+                trigger_price: None,
+            };
+            self.on_order(engine, order).await;
+        }
+    }
+
+    /// 处理所有到期时间不晚于当前时间的调度条目。
+    async fn run_due(&mut self, engine: &SimulatedExecutionEngine) {
+        while self.next_due().is_some_and(|due| due <= engine.clock.now_nanos()) {
+            let Some(Reverse((due, _, order_id, action))) = self.schedule.pop() else { break };
+            // 订单可能已在等待期间被撤销
+            let Some(order_state) = self.working.remove(&order_id) else { continue };
+            let trace = order_state.trace.clone();
+            trace::in_context(trace, self.run_action(engine, order_state, due, action)).await;
+        }
+    }
+
+    /// 执行一个到期的调度条目。在订单的追踪上下文中运行。
+    async fn run_action(
+        &mut self,
+        engine: &SimulatedExecutionEngine,
+        mut order_state: WorkingOrder,
+        due: u64,
+        action: ScheduledAction,
+    ) {
+        let order_id = order_state.order.id;
+        match action {
+            ScheduledAction::Ack => {
+                let accepted = OrderAccepted {
+                    order_id,
+                    symbol: order_state.order.symbol.clone(),
+                    ts_event: due,
+                };
+                engine.publish(accepted).await;
+                self.transition(engine, &order_state, OrderStatus::Accepted, due).await;
+                let participates = engine.fill_model.participation_rate().is_some();
+                if order_state.order.order_type != OrderType::Market || participates {
+                    self.resting.entry(order_state.order.symbol.clone()).or_default().push(order_id);
+                    self.working.insert(order_id, order_state);
+                    return;
+                }
+                let symbol = order_state.order.symbol.clone();
+                self.working.insert(order_id, order_state);
+                self.schedule_fill(order_id, &symbol, due, engine);
+            },
+            ScheduledAction::Fill => {
+                if engine.fill_next(&mut order_state, &mut self.rng, due).await {
+                    self.transition(engine, &order_state, OrderStatus::PartiallyFilled, due).await;
+                    let next_due = due + engine.fill_model.delay_between().as_nanos() as u64;
+                    self.working.insert(order_id, order_state);
+                    self.push(next_due, order_id, ScheduledAction::Fill);
+                } else {
+                    self.filled.insert(order_id);
+                    self.transition(engine, &order_state, OrderStatus::Filled, due).await;
+                }
+            },
+        }
+    }
 }
 
 #[async_trait::async_trait]
 impl Actor for SimulatedExecutionEngine {
-    async fn start(self: Arc<Self>) -> Vec<JoinHandle<()>> {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
         let mut order_rx = self.bus.subscribe::<OrderRequest>().await;
+        let mut cancel_rx = self.bus.subscribe::<CancelOrderRequest>().await;
+        let mut halt_rx = self.bus.subscribe::<TradingHalted>().await;
+        let mut flatten_rx = self.bus.subscribe::<FlattenAll>().await;
+        let mut resume_rx = self.bus.subscribe::<TradingResumed>().await;
+        let mut bar_rx = self.bus.subscribe::<Bar>().await;
+        let mut trade_rx = self.bus.subscribe::<TradeTick>().await;
+        let mut book_rx = self.bus.subscribe::<OrderBookSnapshot>().await;
+        let mut query_rx = self.bus.subscribe::<OpenOrdersQuery>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let handle = spawn_named(actor_name, async move {
+            let mut state = EngineState::new(&self);
 
-        let handle = tokio::spawn(async move {
             loop {
-                match order_rx.recv().await {
-                    Ok(order) => {
-                        info!(target: "EXECUTION", "Received {:?}. Simulating fill...", order);
-                        // 模拟成交，创建一个 FillEvent
-                        let fill = FillEvent {
-                            order_id: order.id,
-                            symbol: order.symbol.clone(),
-                            price: order.price,
-                            quantity: order.quantity,
-                        };
-                        info!(target: "EXECUTION", "Publishing {:?}", fill);
-                        if let Err(e) = self.bus.publish(fill).await {
-                             tracing::error!(target: "EXECUTION", "Failed to publish fill: {}", e);
-                        }
+                // 先处理所有已到期的确认/成交，再接收新消息，保证竞争结果确定
+                state.run_due(&self).await;
+                state.release_throttled(&self);
+                let next_due = match (state.next_due(), state.next_release_at()) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+                tokio::select! {
+                    biased;
+                    _ = async { self.clock.sleep_until(next_due.unwrap()).await }, if next_due.is_some() => {},
+                    result = order_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("EXECUTION", |order| state.on_order(&self, order)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<OrderRequest>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = cancel_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("EXECUTION", |cancel| state.on_cancel(&self, cancel)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<CancelOrderRequest>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = bar_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("EXECUTION", |bar| state.on_bar(&self, bar)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<Bar>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = trade_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("EXECUTION", |trade| state.on_trade(&self, trade)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<TradeTick>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = book_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("EXECUTION", |book| state.on_book_snapshot(&self, book)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<OrderBookSnapshot>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = query_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("EXECUTION", |query| state.on_query(&self, query)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<OpenOrdersQuery>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    // 排在暂停之前：先发布的 FlattenAll 与随后的 TradingHalted 同时就绪时先平仓
+                    result = flatten_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("EXECUTION", |flatten| state.on_flatten(&self, flatten)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<FlattenAll>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = halt_rx.recv() => match result {
+                        Ok(halt) => {
+                            tracing::warn!(target: "EXECUTION", "Trading halted: {:?}", halt);
+                            state.validator.on_halt(&halt);
+                        },
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<TradingHalted>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = resume_rx.recv() => match result {
+                        Ok(resume) => {
+                            info!(target: "EXECUTION", "Trading resumed: {:?}", resume);
+                            state.validator.on_resume(&resume);
+                        },
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<TradingResumed>(n)),
+                        Err(RecvError::Closed) => break,
                     },
-                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "EXECUTION", "Lagged by {} orders", n),
-                    Err(RecvError::Closed) => break,
                 }
             }
         });
 
         vec![handle]
     }
-}
\ No newline at end of file
+
+    /// 执行路径对延迟敏感，由 `ActorSystem` 启动时运行在专用运行时上。
+    fn affinity(&self) -> Affinity {
+        Affinity::Dedicated
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutionClient for SimulatedExecutionEngine {
+    /// 订阅场所总线上的回报并以 `actor_name` 启动引擎，回报的转发任务同样以它命名。
+    async fn connect(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut accepted_rx = self.bus.subscribe::<OrderAccepted>().await;
+        let mut triggered_rx = self.bus.subscribe::<OrderTriggered>().await;
+        let mut fill_rx = self.bus.subscribe::<FillEvent>().await;
+        let mut rejected_rx = self.bus.subscribe::<OrderRejected>().await;
+        let mut canceled_rx = self.bus.subscribe::<OrderCanceled>().await;
+        let mut cancel_rejected_rx = self.bus.subscribe::<CancelRejected>().await;
+
+        let report_tx = self.report_tx.clone();
+        let forwarder = spawn_named(actor_name, async move {
+            loop {
+                // 落后时记录丢失的回报数量后继续，任一通道关闭（总线关闭）时结束
+                let report = tokio::select! {
+                    result = accepted_rx.recv() => match result {
+                        Ok(accepted) => ExecutionReport::Accepted(accepted),
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<OrderAccepted>(n));
+                            continue;
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = triggered_rx.recv() => match result {
+                        Ok(triggered) => ExecutionReport::Triggered(triggered),
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<OrderTriggered>(n));
+                            continue;
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = fill_rx.recv() => match result {
+                        Ok(fill) => ExecutionReport::Fill(fill),
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<FillEvent>(n));
+                            continue;
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = rejected_rx.recv() => match result {
+                        Ok(rejected) => ExecutionReport::Rejected(rejected),
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<OrderRejected>(n));
+                            continue;
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = canceled_rx.recv() => match result {
+                        Ok(canceled) => ExecutionReport::Canceled(canceled),
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<OrderCanceled>(n));
+                            continue;
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = cancel_rejected_rx.recv() => match result {
+                        Ok(rejected) => ExecutionReport::CancelRejected(rejected),
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(target: "EXECUTION", "{}", BusError::lagged::<CancelRejected>(n));
+                            continue;
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+                if report_tx.send(report).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut handles = Actor::start(self, actor_name).await;
+        handles.push(forwarder);
+        handles
+    }
+
+    async fn submit(&self, order: OrderRequest) -> Result<ExecAck, ExecError> {
+        let order_id = order.id;
+        self.bus.publish(order).await.map_err(|e| ExecError::Transport(Box::new(e)))?;
+        Ok(ExecAck { order_id, venue_order_id: None, ts_event: self.clock.now_nanos() })
+    }
+
+    async fn cancel(&self, cancel: CancelOrderRequest) -> Result<(), ExecError> {
+        self.bus.publish(cancel).await.map_err(|e| ExecError::Transport(Box::new(e)))?;
+        Ok(())
+    }
+
+    async fn on_bar(&self, bar: &Bar) {
+        if let Err(e) = self.bus.publish(bar.clone()).await {
+            tracing::error!(target: "EXECUTION", "Failed to forward bar: {}", e);
+        }
+    }
+
+    async fn next_report(&self) -> Option<ExecutionReport> {
+        self.report_rx.lock().await.recv().await
+    }
+}
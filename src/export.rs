@@ -0,0 +1,470 @@
+// src/export.rs
+
+//! # 事件导出模块 (export)
+//!
+//! 把总线上选定类型的消息写成 JSON lines（每行一个 JSON 对象），供下游分析流水线读取。
+//! 输出到文件时可以按大小或按日期轮转；写盘跟不上时丢弃最旧的行并计数，不会拖慢总线。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::MessageBus;
+use crate::clock::{Clock, LiveClock};
+use crate::message::Message;
+use crate::startup::StartupBarrierHandle;
+use futures::future::BoxFuture;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// 默认缓冲区最多容纳的待写行数。
+pub const DEFAULT_EXPORT_BUFFER: usize = 10_000;
+
+/// 默认的刷新间隔。
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+const NANOS_PER_DAY: u64 = 86_400 * 1_000_000_000;
+
+/// ## `Rotation`
+///
+/// 输出文件的轮转策略，只对文件输出生效。当前文件总是写在配置的路径上，
+/// 轮转时旧文件改名为在扩展名前插入序号或日期的文件，例如 `events.1.jsonl`、`events.2026-10-16.jsonl`。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rotation {
+    /// 不轮转。
+    #[default]
+    Never,
+    /// 再写一行会超过给定字节数时轮转。单行超过上限时独占一个文件。
+    Size(u64),
+    /// 时钟跨过 UTC 零点时轮转，旧文件以它所记录的日期命名。
+    Daily,
+}
+
+/// 导出统计。
+#[derive(Debug, Default)]
+struct ExportStats {
+    exported: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// 接收任务与写入任务之间的有界缓冲区。
+struct ExportBuffer {
+    capacity: usize,
+    state: Mutex<BufferState>,
+    ready: Condvar,
+    stats: Arc<ExportStats>,
+}
+
+struct BufferState {
+    lines: VecDeque<String>,
+    /// 仍在运行的接收任务数量，降为 0 后写入任务写完剩余的行并退出。
+    producers: usize,
+}
+
+impl ExportBuffer {
+    /// 放入一行；缓冲区已满时丢弃最旧的一行。
+    fn push(&self, line: String) {
+        let mut state = self.state.lock().unwrap();
+        if state.lines.len() == self.capacity {
+            state.lines.pop_front();
+            self.stats.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        state.lines.push_back(line);
+        self.ready.notify_one();
+    }
+}
+
+/// 接收任务持有的句柄，任务结束（包括被中止）时减少生产者计数。
+struct Producer(Arc<ExportBuffer>);
+
+impl Producer {
+    fn new(buffer: Arc<ExportBuffer>) -> Self {
+        buffer.state.lock().unwrap().producers += 1;
+        Self(buffer)
+    }
+}
+
+impl Drop for Producer {
+    fn drop(&mut self) {
+        self.0.state.lock().unwrap().producers -= 1;
+        self.0.ready.notify_one();
+    }
+}
+
+/// 订阅一种消息类型并把它们转为行放入缓冲区，任务以最后一个参数（Actor 名称）命名；返回的 future 在订阅完成后才结束。
+type SubscribeFn = fn(MessageBus, Producer, Arc<str>, Arc<dyn Clock>, Arc<str>) -> BoxFuture<'static, Vec<JoinHandle<()>>>;
+
+/// 一种要导出的消息类型。
+struct ExportType {
+    name: Arc<str>,
+    subscribe: SubscribeFn,
+}
+
+/// 输出位置。
+enum Output {
+    Stdout(BufWriter<io::Stdout>),
+    File { path: PathBuf, writer: BufWriter<File> },
+    /// 调用方提供的 `Write`，不额外缓冲。
+    Writer(Box<dyn Write + Send>),
+}
+
+/// 写入任务持有的输出与轮转状态。
+struct Sink {
+    output: Output,
+    rotation: Rotation,
+    clock: Arc<dyn Clock>,
+    /// 当前文件的字节数。
+    bytes: u64,
+    /// 当前文件开始记录的 UTC 日期（自纪元起的天数），首次写入时确定。
+    day: Option<u64>,
+}
+
+impl Sink {
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        self.rotate_if_needed(len)?;
+        let writer: &mut dyn Write = match &mut self.output {
+            Output::Stdout(writer) => writer,
+            Output::File { writer, .. } => writer,
+            Output::Writer(writer) => writer,
+        };
+        // 换行与内容一次写入，避免其他写入者插在中间
+        writer.write_all(format!("{}\n", line).as_bytes())?;
+        self.bytes += len;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.output {
+            Output::Stdout(writer) => writer.flush(),
+            Output::File { writer, .. } => writer.flush(),
+            Output::Writer(writer) => writer.flush(),
+        }
+    }
+
+    fn rotate_if_needed(&mut self, len: u64) -> io::Result<()> {
+        let Output::File { path, writer } = &mut self.output else {
+            return Ok(());
+        };
+        let rotated = match self.rotation {
+            Rotation::Never => None,
+            Rotation::Size(max) if self.bytes > 0 && self.bytes + len > max => Some(numbered_path(path)),
+            Rotation::Size(_) => None,
+            Rotation::Daily => {
+                let today = self.clock.now_nanos() / NANOS_PER_DAY;
+                match self.day.replace(today) {
+                    Some(day) if day != today && self.bytes > 0 => Some(dated_path(path, day)),
+                    _ => None,
+                }
+            },
+        };
+        if let Some(rotated) = rotated {
+            writer.flush()?;
+            std::fs::rename(&*path, &rotated)?;
+            *writer = BufWriter::new(open_append(path)?);
+            self.bytes = 0;
+            tracing::info!(target: "EXPORT", "Rotated {} to {}", path.display(), rotated.display());
+        }
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// 在扩展名前插入 `label`：`events.jsonl` → `events.<label>.jsonl`。
+fn labeled_path(path: &Path, label: &str) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}.{}.{}", stem, label, ext.to_string_lossy()),
+        None => format!("{}.{}", stem, label),
+    };
+    path.with_file_name(name)
+}
+
+/// 按大小轮转的文件名：第一个尚不存在的 `events.N.jsonl`（N 从 1 开始）。
+fn numbered_path(path: &Path) -> PathBuf {
+    (1..).map(|n| labeled_path(path, &n.to_string())).find(|candidate| !candidate.exists()).unwrap()
+}
+
+/// 按日期轮转的文件名 `events.YYYY-MM-DD.jsonl`；同一天已有文件（例如进程重启过）时追加序号。
+fn dated_path(path: &Path, day: u64) -> PathBuf {
+    let (year, month, date) = civil_date(day);
+    let label = format!("{:04}-{:02}-{:02}", year, month, date);
+    std::iter::once(label.clone())
+        .chain((1..).map(|n| format!("{}.{}", label, n)))
+        .map(|label| labeled_path(path, &label))
+        .find(|candidate| !candidate.exists())
+        .unwrap()
+}
+
+/// 自 1970-01-01 起的天数转为公历日期（年、月、日）。
+fn civil_date(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let date = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, date)
+}
+
+/// ## `EventExporter`
+///
+/// 一个 Actor，订阅通过 `with_type` 选定的消息类型，把每条消息写成一行 JSON：
+/// `type` 为类型名，`ts_event` 为事件时间，其余为 serde 序列化的全部字段。
+/// 没有 `ts_event` 字段的消息使用收到时的时钟时间；序列化结果不是对象时放在 `message` 字段中。
+/// 消息自身名为 `type` 的字段会被类型名覆盖。
+///
+/// - 接收任务只把序列化好的行放入有界缓冲区（`with_buffer_capacity`），由独立的写入任务写出；
+///   缓冲区满时丢弃最旧的行并计入 `dropped`，总线上的接收永远不会因磁盘变慢而阻塞。
+/// - 写入任务每隔 `with_flush_interval` 刷新一次输出，关闭时写完缓冲区中剩余的行并刷新。
+/// - 输出到文件时追加写入，可按 `Rotation` 轮转。
+pub struct EventExporter {
+    bus: MessageBus,
+    output: Mutex<Option<Output>>,
+    types: Vec<ExportType>,
+    rotation: Rotation,
+    flush_interval: Duration,
+    capacity: usize,
+    clock: Arc<dyn Clock>,
+    stats: Arc<ExportStats>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl EventExporter {
+    fn with_output(bus: MessageBus, output: Output) -> Self {
+        Self {
+            bus,
+            output: Mutex::new(Some(output)),
+            types: Vec::new(),
+            rotation: Rotation::Never,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            capacity: DEFAULT_EXPORT_BUFFER,
+            clock: Arc::new(LiveClock),
+            stats: Arc::new(ExportStats::default()),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 导出到标准输出。
+    pub fn to_stdout(bus: MessageBus) -> Self {
+        Self::with_output(bus, Output::Stdout(BufWriter::new(io::stdout())))
+    }
+
+    /// 追加写入 `path` 处的文件（不存在时创建）。
+    pub fn to_file(bus: MessageBus, path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let writer = BufWriter::new(open_append(&path)?);
+        Ok(Self::with_output(bus, Output::File { path, writer }))
+    }
+
+    /// 导出到任意 `Write`，例如网络连接或测试中的内存缓冲。不额外缓冲，也不轮转。
+    pub fn to_writer(bus: MessageBus, writer: impl Write + Send + 'static) -> Self {
+        Self::with_output(bus, Output::Writer(Box::new(writer)))
+    }
+
+    /// 导出消息类型 `M`，`type` 字段为类型名的最后一段，例如 `Bar`。
+    pub fn with_type<M: Message + Serialize>(self) -> Self {
+        let name = std::any::type_name::<M>().rsplit("::").next().unwrap_or_default().to_string();
+        self.with_type_named::<M>(name)
+    }
+
+    /// 导出消息类型 `M`，并指定 `type` 字段的值。
+    pub fn with_type_named<M: Message + Serialize>(mut self, name: impl Into<String>) -> Self {
+        self.types.push(ExportType { name: name.into().into(), subscribe: subscribe::<M> });
+        self
+    }
+
+    /// 设置文件输出的轮转策略，默认不轮转。
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// 设置刷新间隔，默认 1 秒。
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// 设置缓冲区最多容纳的待写行数，默认 10000。
+    pub fn with_buffer_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// 设置时间来源：没有 `ts_event` 的消息的时间，以及按日期轮转时的日期。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 已经写出的行数。
+    pub fn exported(&self) -> u64 {
+        self.stats.exported.load(Ordering::Relaxed)
+    }
+
+    /// 因缓冲区已满、接收落后或写入失败而丢失的行数。
+    pub fn dropped(&self) -> u64 {
+        self.stats.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// 把一条消息转为一行 JSON。
+fn to_line<M: Serialize>(type_name: &str, msg: &M, clock: &dyn Clock) -> serde_json::Result<String> {
+    let mut record = Map::new();
+    match serde_json::to_value(msg)? {
+        Value::Object(fields) => {
+            if !fields.contains_key("ts_event") {
+                record.insert("ts_event".to_string(), clock.now_nanos().into());
+            }
+            record.extend(fields);
+        },
+        other => {
+            record.insert("ts_event".to_string(), clock.now_nanos().into());
+            record.insert("message".to_string(), other);
+        },
+    }
+    record.insert("type".to_string(), type_name.into());
+    serde_json::to_string(&record)
+}
+
+fn subscribe<M: Message + Serialize>(
+    bus: MessageBus,
+    producer: Producer,
+    type_name: Arc<str>,
+    clock: Arc<dyn Clock>,
+    actor_name: Arc<str>,
+) -> BoxFuture<'static, Vec<JoinHandle<()>>> {
+    Box::pin(async move {
+        let mut rx = bus.subscribe::<M>().await;
+        let handle = spawn_named(&actor_name, async move {
+            loop {
+                match rx.recv().await {
+                    Ok(msg) => match to_line(&type_name, &msg, clock.as_ref()) {
+                        Ok(line) => producer.0.push(line),
+                        Err(e) => tracing::error!(target: "EXPORT", "Cannot serialize {}: {}", type_name, e),
+                    },
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(target: "EXPORT", "Lagged by {} {} messages, not exported", n, type_name);
+                        producer.0.stats.dropped.fetch_add(n, Ordering::Relaxed);
+                    },
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        vec![handle]
+    })
+}
+
+/// 写入任务：等待新的行或刷新时间到达，写出缓冲区中的所有行；所有接收任务结束后写完剩余的行并退出。
+fn write_loop(buffer: Arc<ExportBuffer>, mut sink: Sink, flush_interval: Duration) {
+    let stats = buffer.stats.clone();
+    let mut last_flush = Instant::now();
+    let mut reported_drops = 0;
+    loop {
+        let (lines, closed) = {
+            let mut state = buffer.state.lock().unwrap();
+            while state.lines.is_empty() && state.producers > 0 {
+                let timeout = flush_interval.saturating_sub(last_flush.elapsed());
+                if timeout.is_zero() {
+                    break;
+                }
+                state = buffer.ready.wait_timeout(state, timeout).unwrap().0;
+            }
+            (std::mem::take(&mut state.lines), state.producers == 0)
+        };
+
+        for (i, line) in lines.iter().enumerate() {
+            if let Err(e) = sink.write_line(line) {
+                let lost = (lines.len() - i) as u64;
+                tracing::error!(target: "EXPORT", "Failed to write, {} lines lost: {}", lost, e);
+                stats.dropped.fetch_add(lost, Ordering::Relaxed);
+                break;
+            }
+            stats.exported.fetch_add(1, Ordering::Relaxed);
+        }
+
+        if closed || last_flush.elapsed() >= flush_interval {
+            if let Err(e) = sink.flush() {
+                tracing::error!(target: "EXPORT", "Failed to flush: {}", e);
+            }
+            last_flush = Instant::now();
+            let dropped = stats.dropped.load(Ordering::Relaxed);
+            if dropped > reported_drops {
+                tracing::warn!(target: "EXPORT", "{} lines dropped since last flush", dropped - reported_drops);
+                reported_drops = dropped;
+            }
+        }
+        if closed {
+            break;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for EventExporter {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let Some(output) = self.output.lock().unwrap().take() else {
+            tracing::error!(target: "EXPORT", "EventExporter already started");
+            return Vec::new();
+        };
+        if self.rotation != Rotation::Never && !matches!(output, Output::File { .. }) {
+            tracing::warn!(target: "EXPORT", "Rotation only applies to file output, ignored");
+        }
+        if self.types.is_empty() {
+            tracing::warn!(target: "EXPORT", "No message types selected, nothing will be exported");
+        }
+
+        let buffer = Arc::new(ExportBuffer {
+            capacity: self.capacity,
+            state: Mutex::new(BufferState { lines: VecDeque::new(), producers: 0 }),
+            ready: Condvar::new(),
+            stats: self.stats.clone(),
+        });
+        let mut handles = Vec::new();
+        for export_type in &self.types {
+            let producer = Producer::new(buffer.clone());
+            handles.extend(
+                (export_type.subscribe)(
+                    self.bus.clone(),
+                    producer,
+                    export_type.name.clone(),
+                    self.clock.clone(),
+                    Arc::from(actor_name),
+                )
+                .await,
+            );
+        }
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let sink = Sink { output, rotation: self.rotation, clock: self.clock.clone(), bytes: 0, day: None };
+        let sink = match &sink.output {
+            // 追加写入已有文件时，按大小轮转要计入文件原有的内容
+            Output::File { writer, .. } => Sink { bytes: writer.get_ref().metadata().map_or(0, |m| m.len()), ..sink },
+            _ => sink,
+        };
+        let flush_interval = self.flush_interval;
+        handles.push(tokio::task::spawn_blocking(move || write_loop(buffer, sink, flush_interval)));
+        handles
+    }
+}
@@ -0,0 +1,301 @@
+// src/fix.rs
+
+//! # FIX 模块 (fix)
+//!
+//! 定义与机构券商交互所用的 FIX 4.2 消息类型，以及在内部订单消息与 FIX 消息之间转换的桥接 Actor。
+//! 枚举的取值与 FIX 4.2 对应 tag 的取值一致，可通过 `tag_value` / `from_tag_value` 互相转换。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::clock::{Clock, LiveClock};
+use crate::message::{FillEvent, Liquidity, Message, OrderRequest, OrderSide, OrderType};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::info;
+use uuid::Uuid;
+
+/// 定义一个取值为 FIX tag 字符的枚举。
+macro_rules! fix_enum {
+    ($(#[$meta:meta])* $name:ident { $($(#[$vmeta:meta])* $variant:ident = $value:literal,)+ }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$vmeta])* $variant,)+
+        }
+
+        impl $name {
+            /// 该取值在 FIX 消息中的字符表示。
+            pub fn tag_value(self) -> char {
+                match self {
+                    $($name::$variant => $value,)+
+                }
+            }
+
+            /// 从 FIX 消息中的字符解析，未知取值返回 `None`。
+            pub fn from_tag_value(value: char) -> Option<Self> {
+                match value {
+                    $($value => Some($name::$variant),)+
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+fix_enum! {
+    /// Side (54)。
+    FixSide {
+        Buy = '1',
+        Sell = '2',
+    }
+}
+
+fix_enum! {
+    /// OrdType (40)。
+    FixOrdType {
+        Market = '1',
+        Limit = '2',
+        Stop = '3',
+        StopLimit = '4',
+    }
+}
+
+fix_enum! {
+    /// TimeInForce (59)。
+    FixTimeInForce {
+        Day = '0',
+        GoodTillCancel = '1',
+        AtTheOpening = '2',
+        ImmediateOrCancel = '3',
+        FillOrKill = '4',
+        GoodTillCrossing = '5',
+        GoodTillDate = '6',
+    }
+}
+
+fix_enum! {
+    /// ExecType (150)。
+    FixExecType {
+        New = '0',
+        PartialFill = '1',
+        Fill = '2',
+        DoneForDay = '3',
+        Canceled = '4',
+        Replace = '5',
+        PendingCancel = '6',
+        Stopped = '7',
+        Rejected = '8',
+        Suspended = '9',
+        PendingNew = 'A',
+        Calculated = 'B',
+        Expired = 'C',
+        Restated = 'D',
+        PendingReplace = 'E',
+    }
+}
+
+fix_enum! {
+    /// OrdStatus (39)。
+    FixOrdStatus {
+        New = '0',
+        PartiallyFilled = '1',
+        Filled = '2',
+        DoneForDay = '3',
+        Canceled = '4',
+        Replaced = '5',
+        PendingCancel = '6',
+        Stopped = '7',
+        Rejected = '8',
+        Suspended = '9',
+        PendingNew = 'A',
+        Calculated = 'B',
+        Expired = 'C',
+        AcceptedForBidding = 'D',
+        PendingReplace = 'E',
+    }
+}
+
+/// NewOrderSingle (35=D)。
+#[derive(Clone, Debug)]
+pub struct FixNewOrderSingle {
+    pub clordid: String,
+    pub symbol: String,
+    pub side: FixSide,
+    pub ordtype: FixOrdType,
+    /// 限价（44），市价单与止损市价单为 `None`。
+    pub price: Option<f64>,
+    /// 止损触发价 StopPx（99），非止损单为 `None`。
+    pub stop_px: Option<f64>,
+    pub qty: f64,
+    pub time_in_force: FixTimeInForce,
+    pub account: Option<String>,
+}
+impl Message for FixNewOrderSingle {}
+
+/// ExecutionReport (35=8)。
+#[derive(Clone, Debug)]
+pub struct FixExecutionReport {
+    pub orderid: String,
+    pub execid: String,
+    pub exec_type: FixExecType,
+    pub ord_status: FixOrdStatus,
+    pub symbol: String,
+    pub cum_qty: f64,
+    pub leaves_qty: f64,
+    pub avg_px: f64,
+    pub last_px: f64,
+    pub last_qty: f64,
+}
+impl Message for FixExecutionReport {}
+
+/// ## `FixBridgeActor`
+///
+/// 一个 Actor，在内部订单消息与 FIX 消息之间转换。
+/// - 消费 `OrderRequest` 消息，转换为 `FixNewOrderSingle` 后发布，`clordid` 为订单 ID。
+/// - 消费 `FixExecutionReport` 消息，把成交回报（`PartialFill` / `Fill`）转换为 `FillEvent`。
+///
+/// 回报的 `orderid` 需要能解析回桥接发出的订单 ID（即对端以 ClOrdID 作为 OrderID 回报），
+/// 不属于本桥接发出的订单的回报会被忽略。FIX 4.2 回报不携带手续费与流动性方向，
+/// 转换出的 `commission` 为 0、`liquidity` 为 `Taker`，`venue_fill_id` 取自 `ExecID`。
+pub struct FixBridgeActor {
+    bus: MessageBus,
+    time_in_force: FixTimeInForce,
+    account: Option<String>,
+    clock: Arc<dyn Clock>,
+    /// 已发出、尚未结束的订单方向。
+    open_orders: Mutex<HashMap<Uuid, OrderSide>>,
+}
+
+impl FixBridgeActor {
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            time_in_force: FixTimeInForce::Day,
+            account: None,
+            clock: Arc::new(LiveClock),
+            open_orders: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 设置发出订单的 TimeInForce，默认为 `Day`。
+    pub fn with_time_in_force(mut self, time_in_force: FixTimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    /// 设置发出订单的账户。
+    pub fn with_account(mut self, account: &str) -> Self {
+        self.account = Some(account.to_string());
+        self
+    }
+
+    /// 设置 `FillEvent` 时间戳的时间来源。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 将内部订单转换为 `FixNewOrderSingle`。
+    pub fn to_new_order_single(&self, order: &OrderRequest) -> FixNewOrderSingle {
+        let (ordtype, price) = match order.order_type {
+            OrderType::Market => (FixOrdType::Market, None),
+            OrderType::Limit => (FixOrdType::Limit, Some(order.price)),
+            OrderType::StopMarket => (FixOrdType::Stop, None),
+            OrderType::StopLimit => (FixOrdType::StopLimit, Some(order.price)),
+        };
+        FixNewOrderSingle {
+            clordid: order.id.to_string(),
+            symbol: order.symbol.clone(),
+            side: match order.side {
+                OrderSide::Buy => FixSide::Buy,
+                OrderSide::Sell => FixSide::Sell,
+            },
+            ordtype,
+            price,
+            stop_px: order.stop_price(),
+            qty: order.quantity,
+            time_in_force: self.time_in_force,
+            account: self.account.clone(),
+        }
+    }
+
+    /// 将成交回报转换为 `FillEvent`。非成交回报或未知订单返回 `None`。
+    fn to_fill(&self, report: &FixExecutionReport) -> Option<FillEvent> {
+        let order_id = Uuid::parse_str(&report.orderid).ok()?;
+        let mut open_orders = self.open_orders.lock().unwrap();
+        let side = open_orders.get(&order_id)?.clone();
+        let is_final = matches!(
+            report.ord_status,
+            FixOrdStatus::Filled | FixOrdStatus::Canceled | FixOrdStatus::Rejected | FixOrdStatus::Expired | FixOrdStatus::DoneForDay
+        );
+        if is_final {
+            open_orders.remove(&order_id);
+        }
+        if !matches!(report.exec_type, FixExecType::PartialFill | FixExecType::Fill) {
+            return None;
+        }
+        Some(FillEvent {
+            order_id,
+            symbol: report.symbol.clone(),
+            side,
+            price: report.last_px,
+            quantity: report.last_qty,
+            leaves_qty: report.leaves_qty,
+            is_final: report.ord_status == FixOrdStatus::Filled,
+            commission: 0.0,
+            commission_currency: String::new(),
+            liquidity: Liquidity::Taker,
+            ts_event: self.clock.now_nanos(),
+            venue_fill_id: Some(report.execid.clone()),
+            correlation_id: Some(order_id),
+        })
+    }
+
+    async fn handle_order(&self, order: OrderRequest) {
+        let new_order = self.to_new_order_single(&order);
+        self.open_orders.lock().unwrap().insert(order.id, order.side);
+        info!(target: "FIX", "Sending {:?}", new_order);
+        if let Err(e) = self.bus.publish(new_order).await {
+            tracing::error!(target: "FIX", "Failed to publish NewOrderSingle: {}", e);
+        }
+    }
+
+    async fn handle_report(&self, report: FixExecutionReport) {
+        let Some(fill) = self.to_fill(&report) else {
+            tracing::debug!(target: "FIX", "Ignoring execution report {} ({:?})", report.execid, report.exec_type);
+            return;
+        };
+        if let Err(e) = self.bus.publish(fill).await {
+            tracing::error!(target: "FIX", "Failed to publish fill: {}", e);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for FixBridgeActor {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut order_rx = self.bus.subscribe::<OrderRequest>().await;
+        let mut report_rx = self.bus.subscribe::<FixExecutionReport>().await;
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                tokio::select! {
+                    result = order_rx.recv() => match result {
+                        Ok(order) => self.handle_order(order).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "FIX", "{}", BusError::lagged::<OrderRequest>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = report_rx.recv() => match result {
+                        Ok(report) => self.handle_report(report).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "FIX", "{}", BusError::lagged::<FixExecutionReport>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
@@ -0,0 +1,478 @@
+// src/grpc.rs
+
+//! # gRPC 控制模块 (grpc)
+//!
+//! 供运维工具从外部操作运行中系统的 gRPC 服务（`grpc` feature，基于 `tonic`）。
+//! 服务定义见 `proto/control.proto`，每个调用都翻译为总线上的消息：
+//! 下单与撤单发布 `OrderRequest` / `CancelOrderRequest`，暂停与恢复发布 `TradingHalted` / `TradingResumed`，
+//! 查询与推送则来自总线上的 `PositionUpdate`、`OpenOrdersReport` 与 `FillEvent`。
+
+// `tonic::Status` 由服务接口与拦截器的签名决定，无法装箱
+#![allow(clippy::result_large_err)]
+
+use crate::actor::{spawn_run, Actor, ActorContext, ShutdownToken};
+use crate::bus::{BusError, MessageBus, Receiver};
+use crate::clock::{Clock, LiveClock};
+use crate::message::{
+    CancelOrderRequest, FillEvent, Message, OpenOrdersQuery, OpenOrdersReport, OrderRequest, OrderSide, OrderType,
+    PositionUpdate, RejectReason, TrackedOrder, TradingHalted, TradingResumed,
+};
+use crate::startup::StartupBarrierHandle;
+use crate::validation::{OrderValidator, ValidationConfig};
+use futures::stream::{self, BoxStream};
+use proto::control_server::{Control, ControlServer};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::runtime::Handle;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tonic::metadata::MetadataValue;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tracing::info;
+use uuid::Uuid;
+
+/// 由 `proto/control.proto` 生成的消息类型、服务端与客户端。
+pub mod proto {
+    tonic::include_proto!("control");
+}
+
+/// 默认监听端口。
+pub const DEFAULT_GRPC_PORT: u16 = 50051;
+
+/// `GetOpenOrders` 等待执行端回复的默认时间。
+const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// ## `GrpcControl`
+///
+/// 一个 Actor，在 `with_addr` 指定的地址（默认 `127.0.0.1:50051`）上提供 `Control` 服务。
+///
+/// - 每个调用都必须在 metadata 中携带 `authorization: Bearer <token>`，否则返回 `Unauthenticated`。
+/// - `SubmitOrder` 先按与执行端相同的 `ValidationConfig` 规则校验，不通过的订单直接返回错误而不会发布。
+/// - 由 `ActorContext` 的停止信号关闭服务：不再接受新连接，进行中的 `StreamFills` 随之结束。
+pub struct GrpcControl {
+    bus: MessageBus,
+    token: Arc<str>,
+    addr: SocketAddr,
+    validation: ValidationConfig,
+    query_timeout: Duration,
+    clock: Arc<dyn Clock>,
+    local_addr: Mutex<Option<SocketAddr>>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl GrpcControl {
+    pub fn new(bus: MessageBus, token: &str) -> Self {
+        Self {
+            bus,
+            token: token.into(),
+            addr: SocketAddr::from((Ipv4Addr::LOCALHOST, DEFAULT_GRPC_PORT)),
+            validation: ValidationConfig::default(),
+            query_timeout: DEFAULT_QUERY_TIMEOUT,
+            clock: Arc::new(LiveClock),
+            local_addr: Mutex::new(None),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 设置监听地址。端口为 0 时由系统分配，通过 `local_addr` 查询。
+    pub fn with_addr(mut self, addr: SocketAddr) -> Self {
+        self.addr = addr;
+        self
+    }
+
+    /// 设置下单校验规则，应与执行端使用的配置一致。
+    pub fn with_validation(mut self, config: ValidationConfig) -> Self {
+        self.validation = config;
+        self
+    }
+
+    /// 设置 `GetOpenOrders` 等待 `OpenOrdersReport` 的时间，默认 1 秒。
+    pub fn with_query_timeout(mut self, timeout: Duration) -> Self {
+        self.query_timeout = timeout;
+        self
+    }
+
+    /// 设置校验订单 ID 重复窗口所用的时钟，默认 `LiveClock`。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置启动屏障，开始监听后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 实际监听的地址，开始监听之前为 `None`。
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.lock().unwrap()
+    }
+
+    fn ready(&self, ctx: &ActorContext) {
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(ctx.name());
+        }
+        ctx.ready();
+    }
+}
+
+/// ## `ControlService`
+///
+/// `Control` 服务的实现，由 `GrpcControl::run` 创建，持有总线上同步来的持仓与暂停状态。
+struct ControlService {
+    bus: MessageBus,
+    query_timeout: Duration,
+    clock: Arc<dyn Clock>,
+    validator: Arc<Mutex<OrderValidator>>,
+    positions: Arc<Mutex<HashMap<String, PositionUpdate>>>,
+    shutdown: ShutdownToken,
+}
+
+impl ControlService {
+    /// 发布一条命令；没有任何订阅者时返回 `Unavailable`，因为命令不会被执行。
+    async fn send<M: Message>(&self, msg: M) -> Result<(), Status> {
+        match self.bus.publish(msg).await {
+            Ok(0) => Err(Status::unavailable(format!("no subscriber for {}", std::any::type_name::<M>()))),
+            Ok(_) => Ok(()),
+            Err(e) => Err(Status::internal(format!("publish failed: {}", e))),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Control for ControlService {
+    async fn submit_order(
+        &self,
+        request: Request<proto::SubmitOrderRequest>,
+    ) -> Result<Response<proto::SubmitOrderResponse>, Status> {
+        let order = order_from_proto(request.into_inner())?;
+        let checked = self.validator.lock().unwrap().validate(&order, self.clock.now_nanos(), None);
+        if let Err((reason, detail)) = checked {
+            return Err(reject_status(reason, detail));
+        }
+        let order_id = order.id;
+        info!(target: "GRPC", "Submitting {:?}", order);
+        self.send(order).await?;
+        Ok(Response::new(proto::SubmitOrderResponse { order_id: order_id.to_string() }))
+    }
+
+    async fn cancel_order(
+        &self,
+        request: Request<proto::CancelOrderRequest>,
+    ) -> Result<Response<proto::CancelOrderResponse>, Status> {
+        let request = request.into_inner();
+        let cancel = CancelOrderRequest { order_id: parse_order_id(&request.order_id)?, symbol: request.symbol };
+        info!(target: "GRPC", "Canceling order {}", cancel.order_id);
+        self.send(cancel).await?;
+        Ok(Response::new(proto::CancelOrderResponse {}))
+    }
+
+    async fn get_positions(
+        &self,
+        request: Request<proto::GetPositionsRequest>,
+    ) -> Result<Response<proto::GetPositionsResponse>, Status> {
+        let symbol = request.into_inner().symbol;
+        let mut positions: Vec<proto::Position> = self
+            .positions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|update| symbol.as_ref().is_none_or(|symbol| *symbol == update.symbol))
+            .map(|update| proto::Position {
+                symbol: update.symbol.clone(),
+                net_position: update.net_position,
+                avg_price: update.avg_price,
+                ts: update.ts,
+            })
+            .collect();
+        positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Ok(Response::new(proto::GetPositionsResponse { positions }))
+    }
+
+    async fn get_open_orders(
+        &self,
+        request: Request<proto::GetOpenOrdersRequest>,
+    ) -> Result<Response<proto::GetOpenOrdersResponse>, Status> {
+        let symbol = request.into_inner().symbol;
+        // 先订阅回复，避免执行端在订阅完成之前就已回复
+        let mut report_rx = self.bus.subscribe::<OpenOrdersReport>().await;
+        self.send(OpenOrdersQuery { symbol: symbol.clone() }).await?;
+        let wait = async {
+            loop {
+                match report_rx.recv().await {
+                    Ok(report) if report.symbol == symbol => return Ok(report),
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "GRPC", "{}", BusError::lagged::<OpenOrdersReport>(n)),
+                    Err(RecvError::Closed) => return Err(Status::unavailable("message bus closed")),
+                }
+            }
+        };
+        let report = match tokio::time::timeout(self.query_timeout, wait).await {
+            Ok(report) => report?,
+            Err(_) => {
+                return Err(Status::deadline_exceeded(format!("no open orders report within {:?}", self.query_timeout)))
+            },
+        };
+        let orders = report.orders.into_iter().map(open_order_to_proto).collect();
+        Ok(Response::new(proto::GetOpenOrdersResponse { orders }))
+    }
+
+    async fn pause_strategy(
+        &self,
+        request: Request<proto::StrategyControlRequest>,
+    ) -> Result<Response<proto::StrategyControlResponse>, Status> {
+        let request = request.into_inner();
+        let reason = if request.reason.is_empty() { "paused via gRPC".to_string() } else { request.reason };
+        info!(target: "GRPC", "Pausing {:?}: {}", request.symbol, reason);
+        self.send(TradingHalted { symbol: request.symbol, reason }).await?;
+        Ok(Response::new(proto::StrategyControlResponse {}))
+    }
+
+    async fn resume_strategy(
+        &self,
+        request: Request<proto::StrategyControlRequest>,
+    ) -> Result<Response<proto::StrategyControlResponse>, Status> {
+        let symbol = request.into_inner().symbol;
+        info!(target: "GRPC", "Resuming {:?}", symbol);
+        self.send(TradingResumed { symbol }).await?;
+        Ok(Response::new(proto::StrategyControlResponse {}))
+    }
+
+    type StreamFillsStream = BoxStream<'static, Result<proto::Fill, Status>>;
+
+    /// 每个调用独立订阅 `FillEvent`。客户端落后于总线时以 `DataLoss` 结束流，
+    /// 而不是静默跳过成交；服务关闭时流正常结束。
+    async fn stream_fills(
+        &self,
+        request: Request<proto::StreamFillsRequest>,
+    ) -> Result<Response<Self::StreamFillsStream>, Status> {
+        let symbol = request.into_inner().symbol;
+        let fill_rx = self.bus.subscribe::<FillEvent>().await;
+        let state = Some((fill_rx, self.shutdown.clone()));
+        let fills = stream::unfold(state, move |state| {
+            let symbol = symbol.clone();
+            async move {
+                let (mut fill_rx, shutdown) = state?;
+                loop {
+                    let result = tokio::select! {
+                        _ = shutdown.cancelled() => return None,
+                        result = fill_rx.recv() => result,
+                    };
+                    match result {
+                        Ok(fill) if symbol.as_ref().is_none_or(|symbol| *symbol == fill.symbol) => {
+                            return Some((Ok(fill_to_proto(fill)), Some((fill_rx, shutdown))));
+                        },
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(n)) => {
+                            return Some((Err(Status::data_loss(format!("lagged by {} fills", n))), None));
+                        },
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+        Ok(Response::new(Box::pin(fills)))
+    }
+}
+
+/// 检查 `authorization: Bearer <token>`。
+fn check_token(token: &str, request: &Request<()>) -> Result<(), Status> {
+    let expected = format!("Bearer {}", token);
+    match request.metadata().get("authorization").map(MetadataValue::as_bytes) {
+        Some(provided) if constant_time_eq(provided, expected.as_bytes()) => Ok(()),
+        Some(_) => Err(Status::unauthenticated("invalid token")),
+        None => Err(Status::unauthenticated("missing authorization metadata")),
+    }
+}
+
+/// 比较时间只取决于长度，不泄露第一个不同字节的位置。
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// 与执行端的拒绝原因对应的 gRPC 状态码。
+fn reject_status(reason: RejectReason, detail: String) -> Status {
+    let message = format!("{}: {}", reason, detail);
+    match reason {
+        RejectReason::DuplicateOrderId => Status::already_exists(message),
+        RejectReason::TradingHalted => Status::failed_precondition(message),
+        RejectReason::OrderIdWindowFull | RejectReason::Throttled => Status::resource_exhausted(message),
+        _ => Status::invalid_argument(message),
+    }
+}
+
+fn parse_order_id(order_id: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(order_id).map_err(|e| Status::invalid_argument(format!("invalid order id {:?}: {}", order_id, e)))
+}
+
+fn order_from_proto(request: proto::SubmitOrderRequest) -> Result<OrderRequest, Status> {
+    let id = if request.order_id.is_empty() { Uuid::new_v4() } else { parse_order_id(&request.order_id)? };
+    let side = match proto::Side::try_from(request.side) {
+        Ok(proto::Side::Buy) => OrderSide::Buy,
+        Ok(proto::Side::Sell) => OrderSide::Sell,
+        _ => return Err(Status::invalid_argument(format!("invalid side {}", request.side))),
+    };
+    let order_type = match proto::OrderType::try_from(request.order_type) {
+        Ok(proto::OrderType::Market) => OrderType::Market,
+        Ok(proto::OrderType::Limit) => OrderType::Limit,
+        Ok(proto::OrderType::StopMarket) => OrderType::StopMarket,
+        Ok(proto::OrderType::StopLimit) => OrderType::StopLimit,
+        _ => return Err(Status::invalid_argument(format!("invalid order type {}", request.order_type))),
+    };
+    Ok(OrderRequest {
+        id,
+        symbol: request.symbol,
+        side,
+        order_type,
+        price: request.price,
+        quantity: request.quantity,
+        trigger_price: request.trigger_price,
+    })
+}
+
+fn side_to_proto(side: &OrderSide) -> proto::Side {
+    match side {
+        OrderSide::Buy => proto::Side::Buy,
+        OrderSide::Sell => proto::Side::Sell,
+    }
+}
+
+fn order_type_to_proto(order_type: OrderType) -> proto::OrderType {
+    match order_type {
+        OrderType::Market => proto::OrderType::Market,
+        OrderType::Limit => proto::OrderType::Limit,
+        OrderType::StopMarket => proto::OrderType::StopMarket,
+        OrderType::StopLimit => proto::OrderType::StopLimit,
+    }
+}
+
+fn open_order_to_proto(tracked: TrackedOrder) -> proto::OpenOrder {
+    let order = tracked.order;
+    proto::OpenOrder {
+        order_id: order.id.to_string(),
+        side: side_to_proto(&order.side).into(),
+        order_type: order_type_to_proto(order.order_type).into(),
+        symbol: order.symbol,
+        price: order.price,
+        quantity: order.quantity,
+        status: tracked.status.to_string(),
+        filled_qty: tracked.filled_qty,
+        leaves_qty: tracked.leaves_qty,
+    }
+}
+
+fn fill_to_proto(fill: FillEvent) -> proto::Fill {
+    proto::Fill {
+        order_id: fill.order_id.to_string(),
+        side: side_to_proto(&fill.side).into(),
+        symbol: fill.symbol,
+        price: fill.price,
+        quantity: fill.quantity,
+        leaves_qty: fill.leaves_qty,
+        is_final: fill.is_final,
+        commission: fill.commission,
+        commission_currency: fill.commission_currency,
+        ts_event: fill.ts_event,
+    }
+}
+
+/// 把总线上的持仓与暂停状态同步到服务，直到总线关闭。
+async fn track_state(
+    mut position_rx: Receiver<PositionUpdate>,
+    mut halt_rx: Receiver<TradingHalted>,
+    mut resume_rx: Receiver<TradingResumed>,
+    positions: Arc<Mutex<HashMap<String, PositionUpdate>>>,
+    validator: Arc<Mutex<OrderValidator>>,
+) {
+    loop {
+        tokio::select! {
+            result = position_rx.recv() => match result {
+                Ok(update) => {
+                    positions.lock().unwrap().insert(update.symbol.clone(), update);
+                },
+                Err(RecvError::Lagged(n)) => tracing::warn!(target: "GRPC", "{}", BusError::lagged::<PositionUpdate>(n)),
+                Err(RecvError::Closed) => break,
+            },
+            result = halt_rx.recv() => match result {
+                Ok(halt) => validator.lock().unwrap().on_halt(&halt),
+                Err(RecvError::Lagged(n)) => tracing::warn!(target: "GRPC", "{}", BusError::lagged::<TradingHalted>(n)),
+                Err(RecvError::Closed) => break,
+            },
+            result = resume_rx.recv() => match result {
+                Ok(resume) => validator.lock().unwrap().on_resume(&resume),
+                Err(RecvError::Lagged(n)) => tracing::warn!(target: "GRPC", "{}", BusError::lagged::<TradingResumed>(n)),
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for GrpcControl {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let ctx = ActorContext::new(self.bus.clone(), actor_name);
+        vec![spawn_run(self, ctx, &Handle::current()).await]
+    }
+
+    /// 服务与状态同步都在 `run` 中运行，收到停止信号后服务不再接受新连接，
+    /// 等待进行中的调用结束后返回。
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        let position_rx = self.bus.subscribe::<PositionUpdate>().await;
+        let halt_rx = self.bus.subscribe::<TradingHalted>().await;
+        let resume_rx = self.bus.subscribe::<TradingResumed>().await;
+
+        let incoming = match TcpListener::bind(self.addr).await {
+            Ok(listener) => {
+                let addr = listener.local_addr().ok();
+                *self.local_addr.lock().unwrap() = addr;
+                TcpIncoming::from_listener(listener, true, None)
+            },
+            Err(e) => Err(e.into()),
+        };
+        let incoming = match incoming {
+            Ok(incoming) => incoming,
+            Err(e) => {
+                tracing::error!(target: "GRPC", "Cannot listen on {}: {}", self.addr, e);
+                self.ready(&ctx);
+                return;
+            },
+        };
+        info!(target: "GRPC", "Serving control API on {:?}", self.local_addr());
+
+        let positions = Arc::new(Mutex::new(HashMap::new()));
+        let validator = Arc::new(Mutex::new(OrderValidator::new(self.validation.clone())));
+        let service = ControlService {
+            bus: self.bus.clone(),
+            query_timeout: self.query_timeout,
+            clock: self.clock.clone(),
+            validator: validator.clone(),
+            positions: positions.clone(),
+            shutdown: ctx.shutdown_token().clone(),
+        };
+        let token = self.token.clone();
+        let service = ControlServer::with_interceptor(service, move |request: Request<()>| {
+            check_token(&token, &request)?;
+            Ok(request)
+        });
+        self.ready(&ctx);
+
+        let server = Server::builder().add_service(service).serve_with_incoming_shutdown(incoming, ctx.shutdown_requested());
+        let tracking = async {
+            tokio::select! {
+                _ = track_state(position_rx, halt_rx, resume_rx, positions, validator) => {},
+                _ = ctx.shutdown_requested() => {},
+            }
+        };
+        let (result, ()) = tokio::join!(server, tracking);
+        match result {
+            Ok(()) => info!(target: "GRPC", "Control API stopped"),
+            Err(e) => tracing::error!(target: "GRPC", "Control API failed: {}", e),
+        }
+    }
+}
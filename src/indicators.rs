@@ -0,0 +1,76 @@
+// src/indicators.rs
+
+//! # 指标模块 (indicators)
+//!
+//! 基于 `Bar` 序列计算技术指标，以及保存最近若干根 `Bar` 的滚动窗口 `BarBuffer`，
+//! 供策略的指标计算、预热与止损跟踪共用，而不必各自维护一份 `VecDeque<Bar>`。
+
+use crate::message::Bar;
+use std::collections::VecDeque;
+
+/// 最近 `period` 根 `Bar` 收盘价的简单移动平均。`period` 为 0 或 `Bar` 不足 `period` 根时返回 `None`。
+pub fn sma(bars: &[Bar], period: usize) -> Option<f64> {
+    if period == 0 || bars.len() < period {
+        return None;
+    }
+    let sum: f64 = bars[bars.len() - period..].iter().map(|bar| bar.close).sum();
+    Some(sum / period as f64)
+}
+
+/// ## `BarBuffer`
+///
+/// 最多保存 `capacity` 根最近的 `Bar`，按到达顺序从旧到新排列，满了之后每次 `push` 淘汰最旧的一根。
+#[derive(Clone, Debug)]
+pub struct BarBuffer {
+    capacity: usize,
+    bars: VecDeque<Bar>,
+}
+
+impl BarBuffer {
+    /// `capacity` 至少为 1。
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self { capacity, bars: VecDeque::with_capacity(capacity) }
+    }
+
+    /// 追加一根 `Bar`，已满时淘汰最旧的一根。
+    pub fn push(&mut self, bar: Bar) {
+        if self.bars.len() == self.capacity {
+            self.bars.pop_front();
+        }
+        self.bars.push_back(bar);
+        // 保持连续存储，`as_slice` 才能不复制地借出全部 `Bar`
+        self.bars.make_contiguous();
+    }
+
+    /// 按从旧到新的顺序借出所有 `Bar`。
+    pub fn as_slice(&self) -> &[Bar] {
+        self.bars.as_slices().0
+    }
+
+    /// 最新的一根 `Bar`。
+    pub fn latest(&self) -> Option<&Bar> {
+        self.bars.back()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.bars.len() == self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.bars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bars.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 最近 `period` 根的简单移动平均，见 `sma`。
+    pub fn sma(&self, period: usize) -> Option<f64> {
+        sma(self.as_slice(), period)
+    }
+}
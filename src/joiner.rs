@@ -0,0 +1,237 @@
+// src/joiner.rs
+
+//! # 订单成交关联模块 (joiner)
+//!
+//! `OrderFillJoiner` 按订单 ID 把 `OrderRequest` 与它的 `FillEvent` 关联起来，
+//! 在订单结束时发布一条汇总的 `OrderComplete`，下游不必各自按 `order_id` 匹配零散的成交。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::clock::{Clock, LiveClock};
+use crate::message::{CompletionStatus, FillEvent, OrderCanceled, OrderComplete, OrderRejected, OrderRequest};
+use crate::startup::StartupBarrierHandle;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::info;
+use uuid::Uuid;
+
+/// 订单等待结束的默认时间。
+const DEFAULT_ORDER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// 一个尚未结束的订单及其已收到的成交。
+struct OpenOrder {
+    order: OrderRequest,
+    fills: Vec<FillEvent>,
+}
+
+#[derive(Default)]
+struct JoinState {
+    open: HashMap<Uuid, OpenOrder>,
+    /// 按到达顺序排列的（截止时间，订单 ID），已结束的订单在到期时跳过。
+    deadlines: VecDeque<(u64, Uuid)>,
+}
+
+impl JoinState {
+    /// 最早的截止时间。
+    fn next_deadline(&mut self) -> Option<u64> {
+        while let Some((deadline, id)) = self.deadlines.front() {
+            if self.open.contains_key(id) {
+                return Some(*deadline);
+            }
+            self.deadlines.pop_front();
+        }
+        None
+    }
+
+    /// 取出所有截止时间不晚于 `now` 的订单。
+    fn expire(&mut self, now: u64) -> Vec<OpenOrder> {
+        let mut expired = Vec::new();
+        while let Some((deadline, id)) = self.deadlines.front().copied() {
+            if deadline > now {
+                break;
+            }
+            self.deadlines.pop_front();
+            expired.extend(self.open.remove(&id));
+        }
+        expired
+    }
+}
+
+/// ## `OrderFillJoiner`
+///
+/// 一个 Actor，为每个订单发布恰好一条 `OrderComplete`。
+/// - 消费 `OrderRequest` 消息，开始跟踪该订单。
+/// - 消费 `FillEvent` 消息，累积到所属订单；`is_final` 的成交使订单以 `Filled` 结束。
+/// - 消费 `OrderCanceled` 与 `OrderRejected` 消息，订单以 `Canceled` / `Rejected` 结束，附带此前的部分成交。
+/// - 超过 `with_order_timeout` 仍未结束的订单以 `TimedOut` 结束。
+///
+/// 未跟踪的订单（`start` 之前下的单或已经结束的订单）的成交与回报被忽略。
+pub struct OrderFillJoiner {
+    bus: MessageBus,
+    clock: Arc<dyn Clock>,
+    order_timeout: Duration,
+    state: Mutex<JoinState>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl OrderFillJoiner {
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            clock: Arc::new(LiveClock),
+            order_timeout: DEFAULT_ORDER_TIMEOUT,
+            state: Mutex::new(JoinState::default()),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 设置超时判定与 `OrderComplete` 时间戳的时间来源。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置订单等待结束的时间，默认 5 分钟。
+    pub fn with_order_timeout(mut self, timeout: Duration) -> Self {
+        self.order_timeout = timeout;
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 尚未结束的订单数量。
+    pub fn open_orders(&self) -> usize {
+        self.state.lock().unwrap().open.len()
+    }
+
+    fn handle_order(&self, order: OrderRequest) {
+        let deadline = self.clock.now_nanos().saturating_add(self.order_timeout.as_nanos() as u64);
+        let mut state = self.state.lock().unwrap();
+        state.deadlines.push_back((deadline, order.id));
+        state.open.insert(order.id, OpenOrder { order, fills: Vec::new() });
+    }
+
+    async fn handle_fill(&self, fill: FillEvent) {
+        let completed = {
+            let mut state = self.state.lock().unwrap();
+            let Some(open) = state.open.get_mut(&fill.order_id) else {
+                tracing::debug!(target: "JOINER", "Ignoring fill for untracked order {}", fill.order_id);
+                return;
+            };
+            let is_final = fill.is_final;
+            open.fills.push(fill);
+            if !is_final {
+                return;
+            }
+            let id = open.order.id;
+            state.open.remove(&id)
+        };
+        if let Some(open) = completed {
+            self.complete(open, CompletionStatus::Filled).await;
+        }
+    }
+
+    /// 撤单或拒绝使订单结束。
+    async fn handle_end(&self, order_id: Uuid, status: CompletionStatus) {
+        let ended = self.state.lock().unwrap().open.remove(&order_id);
+        if let Some(open) = ended {
+            self.complete(open, status).await;
+        }
+    }
+
+    async fn handle_timeouts(&self) {
+        let expired = self.state.lock().unwrap().expire(self.clock.now_nanos());
+        for open in expired {
+            tracing::warn!(target: "JOINER", "Order {} did not complete within {:?}", open.order.id, self.order_timeout);
+            self.complete(open, CompletionStatus::TimedOut).await;
+        }
+    }
+
+    async fn complete(&self, open: OpenOrder, status: CompletionStatus) {
+        let filled_qty: f64 = open.fills.iter().map(|fill| fill.quantity).sum();
+        let avg_fill_price = if filled_qty > 0.0 {
+            open.fills.iter().map(|fill| fill.price * fill.quantity).sum::<f64>() / filled_qty
+        } else {
+            0.0
+        };
+        let complete = OrderComplete {
+            order: open.order,
+            fills: open.fills,
+            filled_qty,
+            avg_fill_price,
+            status,
+            ts_event: self.clock.now_nanos(),
+        };
+        info!(
+            target: "JOINER",
+            "Order {} {:?}: {} in {} fills @ {:.4}",
+            complete.order.id,
+            status,
+            filled_qty,
+            complete.fills.len(),
+            avg_fill_price
+        );
+        if let Err(e) = self.bus.publish(complete).await {
+            tracing::error!(target: "JOINER", "Failed to publish order completion: {}", e);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for OrderFillJoiner {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut order_rx = self.bus.subscribe::<OrderRequest>().await;
+        let mut fill_rx = self.bus.subscribe::<FillEvent>().await;
+        let mut canceled_rx = self.bus.subscribe::<OrderCanceled>().await;
+        let mut rejected_rx = self.bus.subscribe::<OrderRejected>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                let next_deadline = self.state.lock().unwrap().next_deadline();
+                tokio::select! {
+                    // 订单总是先于它的成交与回报发布，优先读取订单，避免把成交当作未跟踪订单的成交丢弃
+                    biased;
+                    result = order_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle_sync("JOINER", |order| self.handle_order(order)),
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "JOINER", "{}", BusError::lagged::<OrderRequest>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = fill_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("JOINER", |fill| self.handle_fill(fill)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "JOINER", "{}", BusError::lagged::<FillEvent>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = canceled_rx.recv_traced() => match result {
+                        Ok(traced) => {
+                            traced.handle("JOINER", |canceled| self.handle_end(canceled.order_id, CompletionStatus::Canceled)).await
+                        },
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "JOINER", "{}", BusError::lagged::<OrderCanceled>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = rejected_rx.recv_traced() => match result {
+                        Ok(traced) => {
+                            traced.handle("JOINER", |rejected| self.handle_end(rejected.order_id, CompletionStatus::Rejected)).await
+                        },
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "JOINER", "{}", BusError::lagged::<OrderRejected>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = self.clock.sleep_until(next_deadline.unwrap_or(u64::MAX)), if next_deadline.is_some() => {
+                        self.handle_timeouts().await;
+                    },
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
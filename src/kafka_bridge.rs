@@ -0,0 +1,548 @@
+// src/kafka_bridge.rs
+
+//! # Kafka 网桥模块 (kafka_bridge)
+//!
+//! 通过 Kafka 与其他系统持久地交换事件（归档交易事件、接收其他团队的信号），需要启用 `kafka` feature。
+//!
+//! - 出站：`KafkaConfig::outbound` 列出的消息类型被编码后写入对应的主题，记录的 key 为 symbol，
+//!   同一 symbol 的消息落在同一个分区并保持顺序。生产者最多缓冲 `buffer_capacity` 条未送达的消息，
+//!   broker 不可用时缓冲满后的新消息被丢弃并计入 `KafkaStats::dropped`；送达结果由投递报告计入 `delivered` / `failed`。
+//! - 入站：以消费组 `group_id` 消费 `inbound` 中的主题，解码后发布到本地总线，发布成功后才提交 offset。
+//!   无法解码的记录计入 `malformed` 后提交并跳过；发布失败时入站停止且不再提交，该记录在重启后重新投递。
+//!
+//! `KafkaBridge::connect` 在 `connect_timeout_ms` 内获取集群元数据，broker 不可达时返回 `KafkaBridgeError::Unreachable`；
+//! `on_unreachable = "retry"` 时改为每隔 `retry_interval_ms` 重试直到连上。
+//!
+//! 与 `ZmqBridge` 相同，同一个网桥不应出站又入站同一种类型。
+
+use crate::actor::{spawn_named, Actor, ActorContext};
+use crate::bus::{BusError, MessageBus};
+use crate::codec::{Codec, CodecError, WireFormat, WireMessage};
+use crate::message::{Bar, FillEvent, Message, OrderRequest};
+use futures::future::{join_all, BoxFuture};
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::message::{BorrowedMessage, DeliveryResult, Message as _};
+use rdkafka::producer::{BaseProducer, BaseRecord, Producer, ProducerContext, ThreadedProducer};
+use rdkafka::ClientContext;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// 停止时等待缓冲中的消息送达的时间。
+const FLUSH_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// ## `UnreachablePolicy`
+///
+/// 启动时 broker 不可达的处理方式。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnreachablePolicy {
+    /// `connect` 返回 `KafkaBridgeError::Unreachable`。
+    #[default]
+    Fail,
+    /// 每隔 `retry_interval_ms` 重试，直到连上。
+    Retry,
+}
+
+/// ## `KafkaConfig`
+///
+/// 网桥的 broker、主题、认证与缓冲设置，对应应用配置中的 `[kafka]` 表：
+///
+/// ```toml
+/// [kafka]
+/// brokers = "kafka-1:9092,kafka-2:9092"
+/// group_id = "trading"
+/// inbound = ["signals"]
+/// security_protocol = "SASL_SSL"
+/// sasl_mechanism = "SCRAM-SHA-512"
+/// sasl_username = "trader"
+/// sasl_password = "..."
+///
+/// [kafka.outbound]
+/// OrderRequest = "trading.orders"
+/// FillEvent = "trading.fills"
+/// ```
+#[derive(Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KafkaConfig {
+    /// `bootstrap.servers`：逗号分隔的 `host:port`。
+    pub brokers: String,
+    pub client_id: String,
+    /// 出站的消息类型名（`Bar`、`OrderRequest`、`FillEvent`）到主题的映射，为空时不出站。
+    pub outbound: BTreeMap<String, String>,
+    /// 入站消费的主题，为空时不入站。
+    pub inbound: Vec<String>,
+    /// 入站的消费组。组内没有已提交的 offset 时从最早的记录开始消费。
+    pub group_id: String,
+    pub wire_format: WireFormat,
+    /// 生产者最多缓冲的未送达消息数（`queue.buffering.max.messages`）。
+    pub buffer_capacity: usize,
+    /// 消息在缓冲中等待送达的最长时间（`message.timeout.ms`），超时后投递报告为失败。
+    pub message_timeout_ms: u64,
+    /// 启动时获取集群元数据的超时。
+    pub connect_timeout_ms: u64,
+    pub on_unreachable: UnreachablePolicy,
+    pub retry_interval_ms: u64,
+    /// `security.protocol`，例如 `SASL_SSL`。
+    pub security_protocol: Option<String>,
+    /// `sasl.mechanism`，例如 `PLAIN` 或 `SCRAM-SHA-512`。
+    pub sasl_mechanism: Option<String>,
+    pub sasl_username: Option<String>,
+    pub sasl_password: Option<String>,
+    /// 直接交给 librdkafka 的其他属性，覆盖以上字段生成的同名属性。
+    pub properties: BTreeMap<String, String>,
+}
+
+impl Default for KafkaConfig {
+    fn default() -> Self {
+        Self {
+            brokers: "localhost:9092".to_string(),
+            client_id: "message-bus".to_string(),
+            outbound: BTreeMap::new(),
+            inbound: Vec::new(),
+            group_id: "message-bus".to_string(),
+            wire_format: WireFormat::default(),
+            buffer_capacity: 100_000,
+            message_timeout_ms: 30_000,
+            connect_timeout_ms: 5_000,
+            on_unreachable: UnreachablePolicy::default(),
+            retry_interval_ms: 5_000,
+            security_protocol: None,
+            sasl_mechanism: None,
+            sasl_username: None,
+            sasl_password: None,
+            properties: BTreeMap::new(),
+        }
+    }
+}
+
+/// 不输出 `sasl_password`，配置可能被写入日志。
+impl fmt::Debug for KafkaConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KafkaConfig")
+            .field("brokers", &self.brokers)
+            .field("client_id", &self.client_id)
+            .field("outbound", &self.outbound)
+            .field("inbound", &self.inbound)
+            .field("group_id", &self.group_id)
+            .field("wire_format", &self.wire_format)
+            .field("buffer_capacity", &self.buffer_capacity)
+            .field("message_timeout_ms", &self.message_timeout_ms)
+            .field("connect_timeout_ms", &self.connect_timeout_ms)
+            .field("on_unreachable", &self.on_unreachable)
+            .field("retry_interval_ms", &self.retry_interval_ms)
+            .field("security_protocol", &self.security_protocol)
+            .field("sasl_mechanism", &self.sasl_mechanism)
+            .field("sasl_username", &self.sasl_username)
+            .field("sasl_password", &self.sasl_password.as_ref().map(|_| "***"))
+            .field("properties", &self.properties)
+            .finish()
+    }
+}
+
+impl KafkaConfig {
+    /// 检查取值，错误信息给出对应的配置字段。
+    pub fn validate(&self) -> Result<(), String> {
+        if self.brokers.trim().is_empty() {
+            return Err("kafka brokers must not be empty (kafka.brokers)".to_string());
+        }
+        if let Some(type_name) = self.outbound.keys().find(|type_name| forwarder(type_name).is_none()) {
+            return Err(format!(
+                "kafka cannot publish message type {:?}, expected Bar, OrderRequest or FillEvent (kafka.outbound)",
+                type_name
+            ));
+        }
+        if !self.inbound.is_empty() && self.group_id.trim().is_empty() {
+            return Err("kafka inbound topics require a consumer group (kafka.group_id)".to_string());
+        }
+        if self.buffer_capacity == 0 {
+            return Err("kafka buffer capacity must be positive (kafka.buffer_capacity)".to_string());
+        }
+        Ok(())
+    }
+
+    /// librdkafka 属性：共用的连接与认证属性、生产者或消费者专用的 `specific`，最后是 `properties`。
+    fn client_config(&self, specific: &[(&str, String)]) -> ClientConfig {
+        let mut client = ClientConfig::new();
+        client.set("bootstrap.servers", &self.brokers).set("client.id", &self.client_id);
+        let security = [
+            ("security.protocol", &self.security_protocol),
+            ("sasl.mechanism", &self.sasl_mechanism),
+            ("sasl.username", &self.sasl_username),
+            ("sasl.password", &self.sasl_password),
+        ];
+        for (key, value) in security {
+            if let Some(value) = value {
+                client.set(key, value);
+            }
+        }
+        for (key, value) in specific {
+            client.set(*key, value);
+        }
+        for (key, value) in &self.properties {
+            client.set(key, value);
+        }
+        client
+    }
+}
+
+/// ## `KafkaBridgeError`
+///
+/// 创建网桥时的错误。
+#[derive(Debug)]
+pub enum KafkaBridgeError {
+    /// 在 `connect_timeout_ms` 内无法从 `brokers` 获取集群元数据。
+    Unreachable { brokers: String, source: KafkaError },
+    /// 创建生产者或消费者、订阅主题失败，通常是配置的属性无效。
+    Client(KafkaError),
+    /// 配置了未知的出站类型，或选择的线上格式不可用。
+    Codec(CodecError),
+}
+
+impl fmt::Display for KafkaBridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KafkaBridgeError::Unreachable { brokers, source } => {
+                write!(f, "Kafka brokers {} are unreachable: {}", brokers, source)
+            },
+            KafkaBridgeError::Client(e) => write!(f, "Kafka client error: {}", e),
+            KafkaBridgeError::Codec(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for KafkaBridgeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            KafkaBridgeError::Unreachable { source, .. } => Some(source),
+            KafkaBridgeError::Client(e) => Some(e),
+            KafkaBridgeError::Codec(e) => Some(e),
+        }
+    }
+}
+
+/// ## `KafkaStats`
+///
+/// 网桥的收发计数。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KafkaStats {
+    /// 交给生产者缓冲的消息数。
+    pub sent: u64,
+    /// 投递报告确认送达的消息数。
+    pub delivered: u64,
+    /// 投递报告为失败（例如超过 `message_timeout_ms`）或无法交给生产者的消息数。
+    pub failed: u64,
+    /// 缓冲已满而被丢弃的消息数。
+    pub dropped: u64,
+    /// 解码后发布到本地总线的记录数。
+    pub received: u64,
+    /// 无法解码而被跳过的入站记录数。
+    pub malformed: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    sent: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+    received: AtomicU64,
+    malformed: AtomicU64,
+}
+
+/// 生产者的上下文，在 librdkafka 的轮询线程中接收投递报告。
+struct DeliveryReports {
+    counters: Arc<Counters>,
+}
+
+impl ClientContext for DeliveryReports {}
+
+impl ProducerContext for DeliveryReports {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &DeliveryResult<'_>, _: Self::DeliveryOpaque) {
+        match result {
+            Ok(_) => {
+                self.counters.delivered.fetch_add(1, Ordering::Relaxed);
+            },
+            Err((e, record)) => {
+                self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(target: "KAFKA", "Delivery to {} failed: {}", record.topic(), e);
+            },
+        }
+    }
+}
+
+type KafkaProducer = ThreadedProducer<DeliveryReports>;
+
+/// 出站转发任务共享的生产者与编码器。
+#[derive(Clone)]
+struct Outbound {
+    producer: Arc<KafkaProducer>,
+    codec: Arc<dyn Codec>,
+    topic: Arc<str>,
+    counters: Arc<Counters>,
+}
+
+type Forwarder = fn(MessageBus, Outbound, Arc<str>) -> BoxFuture<'static, Vec<JoinHandle<()>>>;
+
+/// 出站类型名对应的转发函数。
+fn forwarder(type_name: &str) -> Option<Forwarder> {
+    match type_name {
+        "Bar" => Some(forward::<Bar>),
+        "OrderRequest" => Some(forward::<OrderRequest>),
+        "FillEvent" => Some(forward::<FillEvent>),
+        _ => None,
+    }
+}
+
+/// ## `KafkaBridge`
+///
+/// 一个 Actor，在本地总线与 Kafka 主题之间转发消息。生产者与消费者在 `connect` 中创建，错误在构造时返回；
+/// 每个出站类型一个转发任务负责编码并交给生产者，入站由一个任务消费并发布。
+/// 通过 `run` 运行时收到停止信号后中止这些任务，并等待最多 `FLUSH_TIMEOUT` 让缓冲中的消息送达。
+pub struct KafkaBridge {
+    bus: MessageBus,
+    codec: Arc<dyn Codec>,
+    producer: Option<Arc<KafkaProducer>>,
+    consumer: Option<Arc<StreamConsumer>>,
+    outbound: Vec<(Forwarder, Arc<str>)>,
+    counters: Arc<Counters>,
+}
+
+impl KafkaBridge {
+    /// 按 `config` 创建生产者与消费者。先确认 broker 可达，不可达时按 `on_unreachable` 返回错误或持续重试。
+    pub async fn connect(bus: MessageBus, config: KafkaConfig) -> Result<Self, KafkaBridgeError> {
+        let codec: Arc<dyn Codec> = Arc::from(config.wire_format.codec().map_err(KafkaBridgeError::Codec)?);
+        let outbound = config
+            .outbound
+            .iter()
+            .map(|(type_name, topic)| {
+                forwarder(type_name)
+                    .map(|forward| (forward, Arc::from(topic.as_str())))
+                    .ok_or_else(|| KafkaBridgeError::Codec(CodecError::UnknownType(type_name.clone())))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        wait_for_brokers(&config).await?;
+
+        let counters = Arc::new(Counters::default());
+        let producer = match outbound.is_empty() {
+            true => None,
+            false => {
+                let producer = config
+                    .client_config(&[
+                        ("queue.buffering.max.messages", config.buffer_capacity.to_string()),
+                        ("message.timeout.ms", config.message_timeout_ms.to_string()),
+                    ])
+                    .create_with_context(DeliveryReports { counters: counters.clone() })
+                    .map_err(KafkaBridgeError::Client)?;
+                Some(Arc::new(producer))
+            },
+        };
+
+        let consumer = match config.inbound.is_empty() {
+            true => None,
+            false => {
+                let consumer: StreamConsumer = config
+                    .client_config(&[
+                        ("group.id", config.group_id.clone()),
+                        ("enable.auto.commit", "false".to_string()),
+                        ("auto.offset.reset", "earliest".to_string()),
+                    ])
+                    .create()
+                    .map_err(KafkaBridgeError::Client)?;
+                let topics: Vec<&str> = config.inbound.iter().map(String::as_str).collect();
+                consumer.subscribe(&topics).map_err(KafkaBridgeError::Client)?;
+                Some(Arc::new(consumer))
+            },
+        };
+
+        Ok(Self { bus, codec, producer, consumer, outbound, counters })
+    }
+
+    /// 当前的收发计数。
+    pub fn stats(&self) -> KafkaStats {
+        KafkaStats {
+            sent: self.counters.sent.load(Ordering::Relaxed),
+            delivered: self.counters.delivered.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            received: self.counters.received.load(Ordering::Relaxed),
+            malformed: self.counters.malformed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 获取集群元数据以确认 broker 可达。librdkafka 的调用是阻塞的，放到阻塞线程中执行。
+async fn wait_for_brokers(config: &KafkaConfig) -> Result<(), KafkaBridgeError> {
+    let probe: Arc<BaseProducer> = Arc::new(config.client_config(&[]).create().map_err(KafkaBridgeError::Client)?);
+    let timeout = Duration::from_millis(config.connect_timeout_ms);
+    loop {
+        let client = probe.clone();
+        let result = tokio::task::spawn_blocking(move || client.client().fetch_metadata(None, timeout).map(|_| ()))
+            .await
+            .expect("Kafka metadata probe panicked");
+        match (result, config.on_unreachable) {
+            (Ok(()), _) => return Ok(()),
+            (Err(source), UnreachablePolicy::Fail) => {
+                return Err(KafkaBridgeError::Unreachable { brokers: config.brokers.clone(), source })
+            },
+            (Err(e), UnreachablePolicy::Retry) => {
+                let interval = Duration::from_millis(config.retry_interval_ms);
+                tracing::warn!(target: "KAFKA", "Brokers {} unreachable, retrying in {:?}: {}", config.brokers, interval, e);
+                tokio::time::sleep(interval).await;
+            },
+        }
+    }
+}
+
+/// 订阅 `M`，把每条消息编码后以 symbol 为 key 交给生产者；转发任务以 `actor_name` 命名。
+fn forward<M: Message + Into<WireMessage>>(
+    bus: MessageBus,
+    outbound: Outbound,
+    actor_name: Arc<str>,
+) -> BoxFuture<'static, Vec<JoinHandle<()>>> {
+    Box::pin(async move {
+        let mut rx = bus.subscribe::<M>().await;
+        let handle = spawn_named(&actor_name, async move {
+            loop {
+                match rx.recv().await {
+                    Ok(msg) => produce(&outbound, msg.into()),
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "KAFKA", "Outbound {}", BusError::lagged::<M>(n)),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        vec![handle]
+    })
+}
+
+fn produce(outbound: &Outbound, wire: WireMessage) {
+    let payload = match outbound.codec.encode(&wire) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::error!(target: "KAFKA", "Failed to encode {}: {}", wire.type_id(), e);
+            return;
+        },
+    };
+    let record = BaseRecord::to(&outbound.topic).key(wire.symbol()).payload(&payload);
+    match outbound.producer.send(record) {
+        Ok(()) => {
+            outbound.counters.sent.fetch_add(1, Ordering::Relaxed);
+        },
+        Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) => {
+            outbound.counters.dropped.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(target: "KAFKA", "Producer buffer full, dropped {} for {}", wire.type_id(), outbound.topic);
+        },
+        Err((e, _)) => {
+            outbound.counters.failed.fetch_add(1, Ordering::Relaxed);
+            tracing::error!(target: "KAFKA", "Failed to produce {} to {}: {}", wire.type_id(), outbound.topic, e);
+        },
+    }
+}
+
+/// 入站任务：逐条解码并发布，发布成功（或记录无法解码）后提交 offset；发布失败时停止，不再提交。
+async fn consume_loop(consumer: Arc<StreamConsumer>, codec: Arc<dyn Codec>, bus: MessageBus, counters: Arc<Counters>) {
+    loop {
+        let record = match consumer.recv().await {
+            Ok(record) => record,
+            Err(e) => {
+                tracing::warn!(target: "KAFKA", "Consume error: {}", e);
+                continue;
+            },
+        };
+        let decoded = match record.payload() {
+            Some(payload) => codec.decode(payload).map_err(|e| e.to_string()),
+            None => Err("empty payload".to_string()),
+        };
+        match decoded {
+            Ok(wire) => match wire.publish(&bus).await {
+                Ok(_) => {
+                    counters.received.fetch_add(1, Ordering::Relaxed);
+                },
+                Err(e) => {
+                    tracing::error!(
+                        target: "KAFKA",
+                        "Failed to publish record {}[{}]@{}, inbound bridge stopping without committing: {}",
+                        record.topic(),
+                        record.partition(),
+                        record.offset(),
+                        e
+                    );
+                    break;
+                },
+            },
+            Err(reason) => {
+                counters.malformed.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(
+                    target: "KAFKA",
+                    "Skipped malformed record {}[{}]@{}: {}",
+                    record.topic(),
+                    record.partition(),
+                    record.offset(),
+                    reason
+                );
+            },
+        }
+        commit(&consumer, &record);
+    }
+}
+
+fn commit(consumer: &StreamConsumer, record: &BorrowedMessage<'_>) {
+    if let Err(e) = consumer.commit_message(record, CommitMode::Async) {
+        tracing::warn!(target: "KAFKA", "Failed to commit {}[{}]@{}: {}", record.topic(), record.partition(), record.offset(), e);
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for KafkaBridge {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut handles = Vec::new();
+
+        if let Some(producer) = &self.producer {
+            for (forward, topic) in &self.outbound {
+                let outbound = Outbound {
+                    producer: producer.clone(),
+                    codec: self.codec.clone(),
+                    topic: topic.clone(),
+                    counters: self.counters.clone(),
+                };
+                handles.extend(forward(self.bus.clone(), outbound, Arc::from(actor_name)).await);
+            }
+        }
+
+        if let Some(consumer) = &self.consumer {
+            let (consumer, codec, bus, counters) =
+                (consumer.clone(), self.codec.clone(), self.bus.clone(), self.counters.clone());
+            handles.push(spawn_named(actor_name, consume_loop(consumer, codec, bus, counters)));
+        }
+
+        handles
+    }
+
+    /// 收到停止信号后中止转发与消费任务，并等待缓冲中的消息送达（最多 `FLUSH_TIMEOUT`）。
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        let handles = self.clone().start(ctx.name()).await;
+        ctx.ready();
+        ctx.shutdown_requested().await;
+        for handle in &handles {
+            handle.abort();
+        }
+        join_all(handles).await;
+        if let Some(producer) = self.producer.clone() {
+            let flushed = tokio::task::spawn_blocking(move || producer.flush(FLUSH_TIMEOUT)).await;
+            if let Ok(Err(e)) = flushed {
+                tracing::warn!(target: "KAFKA", "Undelivered messages remain after {:?}: {}", FLUSH_TIMEOUT, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,412 @@
+// src/latency.rs
+
+//! # 延迟统计模块 (latency)
+//!
+//! 用高动态范围直方图（`hdrhistogram`）记录从行情到成交的延迟，
+//! 在分布偏斜时也能给出准确的尾部分位数。
+//!
+//! - `LatencyTracker` 只记录“最近一根 `Bar` → 成交”的总延迟。
+//! - `LatencyProbe` 把每个订单关联到触发它的 `Bar`，分别记录每一跳与端到端的延迟，并定期发布 `LatencyReport`。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::clock::{Clock, LiveClock};
+use crate::message::{Bar, FillEvent, Message, OrderRequest};
+use crate::startup::StartupBarrierHandle;
+use hdrhistogram::Histogram;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::info;
+use uuid::Uuid;
+
+/// 直方图可记录的最大延迟（纳秒），更大的值按此值记录。
+const MAX_TRACKABLE_NANOS: u64 = 3_600_000_000_000;
+
+/// 直方图的有效数字位数，3 位意味着相对误差不超过 0.1%。
+const SIGNIFICANT_FIGURES: u8 = 3;
+
+/// ## `LatencySummary`
+///
+/// 常用分位数的快照。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub count: u64,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    pub p999: Duration,
+    pub max: Duration,
+}
+
+impl fmt::Display for LatencySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} p50={:?} p90={:?} p95={:?} p99={:?} p99.9={:?} max={:?}",
+            self.count, self.p50, self.p90, self.p95, self.p99, self.p999, self.max
+        )
+    }
+}
+
+/// 创建一个覆盖 1ns 到 `MAX_TRACKABLE_NANOS` 的直方图。
+fn new_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, MAX_TRACKABLE_NANOS, SIGNIFICANT_FIGURES).expect("histogram bounds are valid")
+}
+
+/// 记录一个纳秒值，超出可记录范围的值按边界记录。
+fn record_nanos(histogram: &mut Histogram<u64>, nanos: u64) {
+    histogram.saturating_record(nanos.max(1));
+}
+
+/// 直方图的常用分位数，没有样本时全部为零。
+fn summarize(histogram: &Histogram<u64>) -> LatencySummary {
+    let at = |p: f64| if histogram.is_empty() { Duration::ZERO } else { Duration::from_nanos(histogram.value_at_percentile(p)) };
+    LatencySummary {
+        count: histogram.len(),
+        p50: at(50.0),
+        p90: at(90.0),
+        p95: at(95.0),
+        p99: at(99.0),
+        p999: at(99.9),
+        max: if histogram.is_empty() { Duration::ZERO } else { Duration::from_nanos(histogram.max()) },
+    }
+}
+
+/// ## `LatencyTracker`
+///
+/// 一个 Actor，记录每个 symbol 从收到 `Bar` 到收到 `FillEvent` 的延迟：
+/// - 消费 `Bar` 消息，记下该 symbol 最近一根 `Bar` 的到达时间。
+/// - 消费 `FillEvent` 消息，把“成交到达时间 - 最近一根 `Bar` 的到达时间”记入直方图。
+///
+/// 到达时间取自 `Clock`，使用 `VirtualClock` 时统计结果可复现。
+pub struct LatencyTracker {
+    bus: MessageBus,
+    clock: Arc<dyn Clock>,
+    histogram: Mutex<Histogram<u64>>,
+    /// 每个 symbol 最近一根 `Bar` 的到达时间（纳秒）。
+    last_bar: Mutex<HashMap<String, u64>>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl LatencyTracker {
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            clock: Arc::new(LiveClock),
+            histogram: Mutex::new(new_histogram()),
+            last_bar: Mutex::new(HashMap::new()),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 设置到达时间的时间来源。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 记录一次延迟。超出可记录范围的值按边界记录。
+    pub fn record(&self, latency: Duration) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        record_nanos(&mut self.histogram.lock().unwrap(), nanos);
+    }
+
+    /// 已记录的样本数。
+    pub fn count(&self) -> u64 {
+        self.histogram.lock().unwrap().len()
+    }
+
+    /// 第 `p` 百分位（`0.0..=100.0`）的延迟，没有样本时为零。
+    pub fn percentile(&self, p: f64) -> Duration {
+        let histogram = self.histogram.lock().unwrap();
+        if histogram.is_empty() {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(histogram.value_at_percentile(p))
+    }
+
+    /// p50 / p90 / p95 / p99 / p99.9 与最大值。
+    pub fn summary(&self) -> LatencySummary {
+        summarize(&self.histogram.lock().unwrap())
+    }
+
+    fn handle_bar(&self, bar: Bar) {
+        self.last_bar.lock().unwrap().insert(bar.symbol, self.clock.now_nanos());
+    }
+
+    fn handle_fill(&self, fill: FillEvent) {
+        let Some(bar_arrival) = self.last_bar.lock().unwrap().get(&fill.symbol).copied() else {
+            return;
+        };
+        let latency = self.clock.now_nanos().saturating_sub(bar_arrival);
+        self.record(Duration::from_nanos(latency));
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for LatencyTracker {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut bar_rx = self.bus.subscribe::<Bar>().await;
+        let mut fill_rx = self.bus.subscribe::<FillEvent>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                tokio::select! {
+                    result = bar_rx.recv() => match result {
+                        Ok(bar) => self.handle_bar(bar),
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "LATENCY", "{}", BusError::lagged::<Bar>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = fill_rx.recv() => match result {
+                        Ok(fill) => self.handle_fill(fill),
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "LATENCY", "{}", BusError::lagged::<FillEvent>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
+
+/// 未设置时 `LatencyReport` 的发布间隔。
+const DEFAULT_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 未设置时等待成交的超时时间，超时的订单被移除且不计入统计。
+const DEFAULT_ORDER_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// ## `LatencyReport`
+///
+/// `LatencyProbe` 定期发布的各跳延迟分位数。
+#[derive(Clone, Debug, PartialEq)]
+pub struct LatencyReport {
+    /// 策略收到 `Bar` 到发出 `OrderRequest`。
+    pub bar_to_order: LatencySummary,
+    /// `OrderRequest` 发出到第一笔 `FillEvent`。
+    pub order_to_fill: LatencySummary,
+    /// `Bar` 到第一笔 `FillEvent` 的端到端延迟。
+    pub bar_to_fill: LatencySummary,
+    /// 仍在等待成交的订单数。
+    pub pending_orders: usize,
+    /// 因超时未成交而被移除的订单累计数。
+    pub expired_orders: u64,
+    pub ts_event: u64,
+}
+impl Message for LatencyReport {}
+
+/// 一个等待成交的订单。
+struct PendingOrder {
+    /// 发出订单时该 symbol 最近一根 `Bar` 的到达时间，此前没有 `Bar` 时为 `None`。
+    bar_arrival: Option<u64>,
+    order_arrival: u64,
+}
+
+/// `LatencyProbe` 的可变状态。
+struct ProbeState {
+    last_bar: HashMap<String, u64>,
+    pending: HashMap<Uuid, PendingOrder>,
+    /// (到达时间, 订单 ID)，按到达顺序排列，用于超时移除。
+    arrivals: VecDeque<(u64, Uuid)>,
+    expired: u64,
+    bar_to_order: Histogram<u64>,
+    order_to_fill: Histogram<u64>,
+    bar_to_fill: Histogram<u64>,
+}
+
+impl ProbeState {
+    /// 移除到达时间早于 `now - timeout` 的等待中订单。
+    fn expire(&mut self, now: u64, timeout: u64) {
+        while let Some(&(arrival, order_id)) = self.arrivals.front() {
+            if arrival.saturating_add(timeout) > now {
+                break;
+            }
+            self.arrivals.pop_front();
+            // 已成交的订单已不在 `pending` 中
+            if self.pending.remove(&order_id).is_some() {
+                self.expired += 1;
+            }
+        }
+    }
+}
+
+/// ## `LatencyProbe`
+///
+/// 一个 Actor，测量从 `Bar` 发布到对应 `FillEvent` 出现在总线上的流水线延迟：
+/// - 消费 `Bar` 消息，记下每个 symbol 最近一根 `Bar` 的到达时间。
+/// - 消费 `OrderRequest` 消息，把订单关联到同一 symbol 最近的一根 `Bar`（即策略据以下单的行情），
+///   记录“`Bar` → 订单”的延迟。
+/// - 消费 `FillEvent` 消息，按订单 ID 找到对应订单，记录“订单 → 成交”与“`Bar` → 成交”的延迟。
+///   同一订单只有第一笔成交计入统计。
+/// - 每隔 `with_report_interval` 发布一条 `LatencyReport` 并记录日志。
+///
+/// 超过 `with_order_timeout` 仍未成交的订单被移除，只计入 `expired_orders`，不影响分位数。
+/// 每条消息只做哈希表查找与直方图记录，可以在生产环境中常驻。
+pub struct LatencyProbe {
+    bus: MessageBus,
+    clock: Arc<dyn Clock>,
+    report_interval: Duration,
+    order_timeout: Duration,
+    state: Mutex<ProbeState>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl LatencyProbe {
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            clock: Arc::new(LiveClock),
+            report_interval: DEFAULT_REPORT_INTERVAL,
+            order_timeout: DEFAULT_ORDER_TIMEOUT,
+            state: Mutex::new(ProbeState {
+                last_bar: HashMap::new(),
+                pending: HashMap::new(),
+                arrivals: VecDeque::new(),
+                expired: 0,
+                bar_to_order: new_histogram(),
+                order_to_fill: new_histogram(),
+                bar_to_fill: new_histogram(),
+            }),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 设置到达时间与报告定时的时间来源。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置 `LatencyReport` 的发布间隔，默认 10 秒。
+    pub fn with_report_interval(mut self, interval: Duration) -> Self {
+        self.report_interval = interval;
+        self
+    }
+
+    /// 设置等待成交的超时时间，默认 60 秒。
+    pub fn with_order_timeout(mut self, timeout: Duration) -> Self {
+        self.order_timeout = timeout;
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 当前的统计快照。
+    pub fn report(&self) -> LatencyReport {
+        let now = self.clock.now_nanos();
+        let mut state = self.state.lock().unwrap();
+        state.expire(now, self.order_timeout.as_nanos() as u64);
+        LatencyReport {
+            bar_to_order: summarize(&state.bar_to_order),
+            order_to_fill: summarize(&state.order_to_fill),
+            bar_to_fill: summarize(&state.bar_to_fill),
+            pending_orders: state.pending.len(),
+            expired_orders: state.expired,
+            ts_event: now,
+        }
+    }
+
+    fn handle_bar(&self, bar: Bar) {
+        let now = self.clock.now_nanos();
+        self.state.lock().unwrap().last_bar.insert(bar.symbol, now);
+    }
+
+    fn handle_order(&self, order: OrderRequest) {
+        let now = self.clock.now_nanos();
+        let mut state = self.state.lock().unwrap();
+        state.expire(now, self.order_timeout.as_nanos() as u64);
+        let bar_arrival = state.last_bar.get(&order.symbol).copied();
+        if let Some(bar_arrival) = bar_arrival {
+            record_nanos(&mut state.bar_to_order, now.saturating_sub(bar_arrival));
+        }
+        state.pending.insert(order.id, PendingOrder { bar_arrival, order_arrival: now });
+        state.arrivals.push_back((now, order.id));
+    }
+
+    fn handle_fill(&self, fill: FillEvent) {
+        let now = self.clock.now_nanos();
+        let mut state = self.state.lock().unwrap();
+        // 未跟踪的订单、已记录过第一笔成交的订单或已超时的订单
+        let Some(pending) = state.pending.remove(&fill.order_id) else { return };
+        record_nanos(&mut state.order_to_fill, now.saturating_sub(pending.order_arrival));
+        if let Some(bar_arrival) = pending.bar_arrival {
+            record_nanos(&mut state.bar_to_fill, now.saturating_sub(bar_arrival));
+        }
+    }
+
+    async fn publish_report(&self) {
+        let report = self.report();
+        info!(
+            target: "LATENCY",
+            "bar->order {} | order->fill {} | bar->fill {} | pending {} expired {}",
+            report.bar_to_order,
+            report.order_to_fill,
+            report.bar_to_fill,
+            report.pending_orders,
+            report.expired_orders
+        );
+        if let Err(e) = self.bus.publish(report).await {
+            tracing::error!(target: "LATENCY", "Failed to publish latency report: {}", e);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for LatencyProbe {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut bar_rx = self.bus.subscribe::<Bar>().await;
+        let mut order_rx = self.bus.subscribe::<OrderRequest>().await;
+        let mut fill_rx = self.bus.subscribe::<FillEvent>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let handle = spawn_named(actor_name, async move {
+            let interval = self.report_interval.as_nanos() as u64;
+            let mut next_report = self.clock.now_nanos().saturating_add(interval);
+            loop {
+                tokio::select! {
+                    _ = self.clock.sleep_until(next_report) => {
+                        self.publish_report().await;
+                        next_report = self.clock.now_nanos().saturating_add(interval);
+                    },
+                    result = bar_rx.recv() => match result {
+                        Ok(bar) => self.handle_bar(bar),
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "LATENCY", "{}", BusError::lagged::<Bar>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = order_rx.recv() => match result {
+                        Ok(order) => self.handle_order(order),
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "LATENCY", "{}", BusError::lagged::<OrderRequest>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = fill_rx.recv() => match result {
+                        Ok(fill) => self.handle_fill(fill),
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "LATENCY", "{}", BusError::lagged::<FillEvent>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
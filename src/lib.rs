@@ -0,0 +1,73 @@
+// src/lib.rs
+
+//! # message-bus
+//!
+//! 一个基于 Rust 实现的高性能、类型安全的异步发布/订阅消息总线。
+//! 所有模块都在此处声明，`main.rs` 只负责组装和启动。
+
+pub mod actor;
+pub mod aggregator;
+pub mod bus;
+pub mod chaos;
+pub mod cli;
+pub mod client;
+pub mod clock;
+pub mod codec;
+pub mod config;
+pub mod costs;
+pub mod csv_io;
+pub mod data;
+pub mod dedup;
+pub mod divergence;
+pub mod ensemble;
+pub mod event_sourcing;
+pub mod execution;
+pub mod export;
+pub mod fix;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod indicators;
+pub mod joiner;
+#[cfg(feature = "kafka")]
+pub mod kafka_bridge;
+pub mod latency;
+pub mod message;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod multicast;
+pub mod orderbook;
+pub mod orderflow;
+#[cfg(feature = "sqlite")]
+pub mod persistence;
+pub mod pipeline;
+pub mod portfolio;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod quiescence;
+pub mod replay;
+#[cfg(feature = "rest")]
+pub mod rest;
+pub mod risk;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod sharded;
+pub mod simulation;
+pub mod sizing;
+pub mod startup;
+#[cfg(feature = "status")]
+pub mod status;
+pub mod store;
+pub mod strategy;
+pub mod symbol;
+mod sync;
+pub mod system;
+pub mod testkit;
+pub mod throttle;
+pub mod topic;
+pub mod trace;
+pub mod trade_log;
+pub mod validation;
+pub mod vwap;
+pub mod warmup;
+#[cfg(feature = "zmq")]
+pub mod zmq_bridge;
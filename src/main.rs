@@ -3,67 +3,370 @@
 //! # 主程序 (main)
 //!
 //! 负责组装和启动整个系统，是所有组件的编排器。
+//! 运行参数由命令行与配置文件合并为 `AppConfig`（见 `cli` 与 `config` 模块）。
 
-// 声明所有模块
-mod actor;
-mod bus;
-mod data;
-mod execution;
-mod message;
-mod strategy;
-
-use actor::Actor;
-use bus::MessageBus;
-use data::SimulatedDataEngine;
-use execution::SimulatedExecutionEngine;
-use strategy::SimpleTrendFollower;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use message_bus::actor::{Actor, FnActor};
+use message_bus::bus::MessageBus;
+use message_bus::cli::Cli;
+use message_bus::client::ExecutionEngine;
+use message_bus::config::{AppConfig, ConfigError, RunMode};
+use message_bus::costs::{FeeConfig, SlippageConfig};
+use message_bus::csv_io::load_bars_csv;
+use message_bus::data::{load_bars, RandomWalk, RandomWalkConfig, SimulatedDataEngine};
+use message_bus::execution::SimulatedExecutionEngine;
+#[cfg(feature = "grpc")]
+use message_bus::grpc::GrpcControl;
+use message_bus::latency::{LatencyProbe, LatencyReport, LatencyTracker};
+use message_bus::message::{Bar, SystemEvent, DEFAULT_BAR_TIMEFRAME};
+#[cfg(feature = "kafka")]
+use message_bus::kafka_bridge::KafkaBridge;
+#[cfg(feature = "metrics")]
+use message_bus::metrics::MetricsExporter;
+use message_bus::portfolio::PortfolioTracker;
+use message_bus::quiescence::QuiescenceMonitor;
+use message_bus::simulation::SimulationDriver;
+use message_bus::startup::{StartupBarrier, StartupBarrierHandle};
+#[cfg(feature = "status")]
+use message_bus::status::StatusServer;
+use message_bus::strategy::SimpleTrendFollower;
+#[cfg(feature = "otlp")]
+use message_bus::trace;
+use message_bus::warmup::WarmupGuard;
 
 use futures::future::join_all;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::task::JoinHandle;
 use tracing::info;
+use tracing_subscriber::layer::{Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{fmt, EnvFilter};
+use uuid::Uuid;
+
+/// 策略开始交易前需要看到的 `Bar` 数量。
+const WARMUP_BARS: usize = 3;
+
+/// 模拟模式下相邻两根 `Bar` 的虚拟时间间隔，与实时数据源的发布间隔相同。
+const SIM_BAR_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 实时模式下通道积压超过该百分比时发布 `SystemEvent::ChannelHotSpot`。
+const HOTSPOT_THRESHOLD_PCT: f64 = 80.0;
+
+/// 运行结束时输出统计的组件。
+struct Reporters {
+    portfolio: Arc<PortfolioTracker>,
+    latency: Arc<LatencyTracker>,
+    probe: Arc<LatencyProbe>,
+}
 
-#[tokio::main]
-async fn main() {
+fn main() {
     // --- 1. 初始化 ---
-    // 设置日志，可以通过 RUST_LOG 环境变量控制级别, e.g., RUST_LOG=info,data=debug
-    fmt::Subscriber::builder()
-        .with_env_filter(EnvFilter::from_default_env().add_directive("info".parse().unwrap()))
-        .with_target(true) // 打印 target
-        .init();
+    // 命令行覆盖配置文件，配置冲突时按 clap 的格式报错并退出
+    let config = match Cli::parse().into_config() {
+        Ok(config) => config,
+        Err(e @ ConfigError::Invalid(_)) => Cli::command().error(ErrorKind::ArgumentConflict, e).exit(),
+        Err(e) => Cli::command().error(ErrorKind::Io, e).exit(),
+    };
+    // SimulationDriver 要求单线程运行时，实时模式使用多线程运行时
+    let mut builder = match config.mode {
+        RunMode::Live => tokio::runtime::Builder::new_multi_thread(),
+        RunMode::Backtest | RunMode::Sim => tokio::runtime::Builder::new_current_thread(),
+    };
+    let runtime = builder.enable_all().build().expect("failed to build the tokio runtime");
+
+    // 日志级别来自 --log-level，例如 info,DATA=debug。
+    // 日志带有消息的 span 前缀（见 `trace` 模块），启用 otlp 特性时 span 同时导出到 OTLP collector。
+    // 级别过滤只作用于日志与导出，tokio-console 的采集层需要看到 tokio 自身的 trace 级事件
+    let registry = tracing_subscriber::registry()
+        .with(fmt::layer().with_target(true).with_filter(EnvFilter::new(&config.log_level))); // 打印 target
+    // 启用 console 特性并以 RUSTFLAGS="--cfg tokio_unstable" 构建时，tokio-console 可连接到默认端口 6669
+    #[cfg(feature = "console")]
+    let registry = registry.with(console_subscriber::spawn());
+    #[cfg(feature = "otlp")]
+    let _otlp_guard = {
+        let _enter = runtime.enter();
+        match trace::otlp_layer("message-bus") {
+            Ok((layer, guard)) => {
+                registry.with(layer.with_filter(EnvFilter::new(&config.log_level))).init();
+                Some(guard)
+            },
+            Err(e) => {
+                registry.init();
+                tracing::error!(target: "MAIN", "Cannot start the OTLP exporter: {}", e);
+                None
+            },
+        }
+    };
+    #[cfg(not(feature = "otlp"))]
+    registry.init();
+
+    info!(target: "MAIN", "Starting in {} mode for {:?}", config.mode, config.symbols);
+    match config.mode {
+        RunMode::Live => runtime.block_on(run_live(config)),
+        RunMode::Backtest => {
+            let path = config.data_file.clone().expect("validated: backtest mode has a data file");
+            let loaded = if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv")) {
+                load_bars_csv(&path)
+            } else {
+                load_bars(&path)
+            };
+            match loaded {
+                Ok(bars) => runtime.block_on(run_simulation(config, bars)),
+                Err(e) => {
+                    tracing::error!(target: "MAIN", "Cannot load data file {}: {}", path.display(), e);
+                    std::process::exit(1);
+                },
+            }
+        },
+        RunMode::Sim => {
+            let bars = simulated_bars(&config);
+            runtime.block_on(run_simulation(config, bars));
+        },
+    }
+}
+
+/// Actor 及其名称，名称用于命名它派生的任务（见 `actor::spawn_named`）。
+type NamedActor = (String, Arc<dyn Actor>);
 
+fn named(name: &str, actor: Arc<dyn Actor>) -> NamedActor {
+    (name.to_string(), actor)
+}
+
+/// 交易成本配置：0.01% 滑点，10 bps 手续费。
+fn execution_engine(bus: MessageBus, config: &AppConfig) -> SimulatedExecutionEngine {
+    let slippage = SlippageConfig::Percentage { pct: 0.0001 };
+    let fees = FeeConfig::Bps { bps: 10.0 };
+    let engine = SimulatedExecutionEngine::new(bus).with_slippage_model(slippage.build()).with_fee_model(fees.build());
+    match config.seed {
+        Some(seed) => engine.with_seed(seed),
+        None => engine,
+    }
+}
+
+/// 每个 symbol 一组预热与策略，以及只记录收盘价的观察者。
+fn trading_actors(bus: &MessageBus, config: &AppConfig, barrier: Option<&StartupBarrierHandle>) -> Vec<NamedActor> {
+    // 观察者无需单独的结构体
+    let mut actors: Vec<NamedActor> = vec![named(
+        "MONITOR",
+        FnActor::new::<Bar>(bus.clone(), |bar| {
+            Box::pin(async move { info!(target: "MONITOR", "Bar {}: {}", bar.symbol, bar.close) })
+        }),
+    )];
+    for symbol in &config.symbols {
+        let mut warmup = WarmupGuard::new(bus.clone(), symbol, WARMUP_BARS);
+        let mut strategy = SimpleTrendFollower::new(bus.clone(), symbol.clone());
+        if let Some(barrier) = barrier {
+            warmup = warmup.with_startup_barrier(barrier.clone());
+            strategy = strategy.with_startup_barrier(barrier.clone());
+        }
+        actors.push((format!("WARMUP.{}", symbol), warmup));
+        actors.push((format!("STRATEGY.{}", symbol), Arc::new(strategy)));
+    }
+    actors
+}
+
+/// 模拟模式的行情：每个 symbol 一条随机游走，`seed` 相同时两次运行完全一致。
+fn simulated_bars(config: &AppConfig) -> Vec<Bar> {
+    let count = (config.run_duration().as_nanos() / SIM_BAR_INTERVAL.as_nanos()) as u64;
+    let mut bars = Vec::new();
+    for (i, symbol) in config.symbols.iter().enumerate() {
+        let mut walk = RandomWalk::new(RandomWalkConfig {
+            initial_price: 100.0,
+            drift_per_bar: 0.0,
+            volatility_per_bar: 0.01,
+            // 每个 symbol 使用不同但确定的种子
+            seed: config.seed.map(|seed| seed.wrapping_add(i as u64)),
+        });
+        for n in 1..=count {
+            let (open, high, low, close, volume) = walk.next_ohlcv();
+            bars.push(Bar {
+                id: Uuid::new_v4(),
+                ts_event: n * SIM_BAR_INTERVAL.as_nanos() as u64,
+                symbol: symbol.clone(),
+                open,
+                high,
+                low,
+                close,
+                volume,
+                timeframe: DEFAULT_BAR_TIMEFRAME,
+            });
+        }
+    }
+    bars
+}
+
+/// 实时模式：实时时钟与模拟数据源，运行 `duration` 后关闭；配置了 `quiet_period` 时总线静止后提前关闭。
+async fn run_live(config: AppConfig) {
     // 创建核心 MessageBus
-    let bus = MessageBus::new(1024);
-    let symbol = "BTC-USD".to_string();
+    let bus = MessageBus::new(config.bus_capacity);
 
     // --- 2. 组装 Actors ---
-    // 将所有 Actor 放入一个向量中，便于统一管理
-    let actors: Vec<Arc<dyn Actor>> = vec![
-        Arc::new(SimulatedDataEngine::new(bus.clone(), symbol.clone())),
-        Arc::new(SimpleTrendFollower::new(bus.clone(), symbol.clone())),
-        Arc::new(SimulatedExecutionEngine::new(bus.clone())),
+    // 所有消费者完成订阅之后数据源才开始发布，避免最早的行情无人接收
+    let (barrier, barrier_wait) = StartupBarrier::new();
+    let portfolio = Arc::new(PortfolioTracker::new(bus.clone()).with_startup_barrier(barrier.clone()));
+    let latency = Arc::new(LatencyTracker::new(bus.clone()).with_startup_barrier(barrier.clone()));
+    let probe = Arc::new(LatencyProbe::new(bus.clone()).with_startup_barrier(barrier.clone()));
+    // 将所有消费者 Actor 放入一个向量中，便于统一管理
+    let mut actors: Vec<NamedActor> = vec![
+        named("PORTFOLIO", portfolio.clone()),
+        named("LATENCY", latency.clone()),
+        named("PROBE", probe.clone()),
+        // 模拟撮合运行在独立的场所总线上；接入实盘时换成 `RestExecutionClient` 即可
+        named(
+            "EXECUTION",
+            Arc::new(
+                ExecutionEngine::new(bus.clone(), execution_engine(MessageBus::new(config.bus_capacity), &config))
+                    .with_startup_barrier(barrier.clone()),
+            ),
+        ),
     ];
+    actors.extend(trading_actors(&bus, &config, Some(&barrier)));
+    // 启用 metrics feature 时在默认端口导出 Prometheus 指标
+    #[cfg(feature = "metrics")]
+    {
+        let exporter = MetricsExporter::new(bus.clone()).with_portfolio(portfolio.clone());
+        actors.push(named("METRICS", Arc::new(exporter.with_startup_barrier(barrier.clone()))));
+    }
+    // 启用 grpc feature 且设置了 CONTROL_API_TOKEN 时提供外部控制接口
+    #[cfg(feature = "grpc")]
+    match std::env::var("CONTROL_API_TOKEN") {
+        Ok(token) if !token.is_empty() => {
+            actors.push(named("GRPC", Arc::new(GrpcControl::new(bus.clone(), &token).with_startup_barrier(barrier.clone()))));
+        },
+        _ => info!(target: "MAIN", "CONTROL_API_TOKEN not set, gRPC control API disabled"),
+    }
+    // 启用 status feature 时提供 JSON 状态端点，STATUS_ADDR 可覆盖默认的监听地址
+    #[cfg(feature = "status")]
+    {
+        let mut server = StatusServer::new(bus.clone()).watch_actor("STRATEGY");
+        if let Ok(addr) = std::env::var("STATUS_ADDR") {
+            match addr.parse() {
+                Ok(addr) => server = server.with_addr(addr),
+                Err(e) => tracing::warn!(target: "MAIN", "Ignoring invalid STATUS_ADDR {:?}: {}", addr, e),
+            }
+        }
+        actors.push(named("STATUS", Arc::new(server.with_startup_barrier(barrier.clone()))));
+    }
+    // 启用 kafka feature 且配置了 [kafka] 时与 Kafka 交换事件，broker 不可达时启动失败（或按配置重试）
+    #[cfg(feature = "kafka")]
+    if let Some(kafka) = config.kafka.clone() {
+        match KafkaBridge::connect(bus.clone(), kafka).await {
+            Ok(bridge) => actors.push(named("KAFKA", Arc::new(bridge))),
+            Err(e) => {
+                tracing::error!(target: "MAIN", "Startup failed: {}", e);
+                return;
+            },
+        }
+    }
+    // 原始句柄已分发完毕，不参与等待
+    drop(barrier);
+    let data_engines: Vec<(String, Arc<SimulatedDataEngine>)> = config
+        .symbols
+        .iter()
+        .map(|symbol| (format!("DATA.{}", symbol), Arc::new(SimulatedDataEngine::new(bus.clone(), symbol.clone()))))
+        .collect();
 
     info!(target: "MAIN", "System starting up...");
 
     // --- 3. 启动 Actors ---
     // 启动所有 Actor 并收集它们的任务句柄
     let mut handles = Vec::new();
-    for actor in actors {
-        handles.extend(actor.start().await);
+    for (name, actor) in actors {
+        handles.extend(actor.start(&name).await);
+    }
+    if let Err(e) = barrier_wait.await_all_ready(Duration::from_secs(10)).await {
+        tracing::error!(target: "MAIN", "Startup failed: {}", e);
+        return;
+    }
+    let mut sources = Vec::new();
+    for (name, data_engine) in data_engines {
+        let started = data_engine.start(&name).await;
+        sources.extend(started.iter().map(JoinHandle::abort_handle));
+        handles.extend(started);
     }
+    handles.push(bus.enable_hotspot_detection(HOTSPOT_THRESHOLD_PCT, Duration::from_secs(1)));
 
-    info!(target: "MAIN", "All actors started. Running for 5 seconds...");
-    tokio::time::sleep(Duration::from_secs(5)).await;
+    // 周期性的报告与热点事件不算作活动
+    let idle = config.quiet_period.map(|quiet_period| {
+        let mut monitor =
+            QuiescenceMonitor::new(bus.clone(), quiet_period).ignore::<LatencyReport>().ignore::<SystemEvent>();
+        for source in sources {
+            monitor = monitor.with_source(source);
+        }
+        Arc::new(monitor)
+    });
+    let idle_token = idle.as_ref().map(|monitor| monitor.shutdown_token());
+    if let Some(monitor) = idle {
+        handles.extend(monitor.start("QUIESCENCE").await);
+    }
+
+    info!(target: "MAIN", "All actors started. Running for {:?}...", config.run_duration());
+    match idle_token {
+        Some(idle_token) => {
+            tokio::select! {
+                _ = tokio::time::sleep(config.run_duration()) => {},
+                _ = idle_token.cancelled() => info!(target: "MAIN", "System is idle"),
+            }
+        },
+        None => tokio::time::sleep(config.run_duration()).await,
+    }
 
     // --- 4. 优雅关闭 ---
     info!(target: "MAIN", "Shutting down...");
+    shutdown(handles).await;
+    report(&Reporters { portfolio, latency, probe });
+}
+
+/// 回测与模拟模式：在虚拟时钟上按时间顺序处理全部行情，结束后关闭。
+async fn run_simulation(config: AppConfig, bars: Vec<Bar>) {
+    let bus = MessageBus::new(config.bus_capacity);
+    let mut driver = SimulationDriver::new(bus.clone());
+    let clock = driver.clock();
+
+    // 虚拟时钟下撮合直接运行在主总线上，驱动器才能观察到它引发的所有级联消息。
+    // 延迟统计衡量真实的处理耗时，保留实时时钟；周期报告的定时器若挂在虚拟时钟上，模拟将永远不会结束
+    let portfolio = Arc::new(PortfolioTracker::new(bus.clone()).with_clock(clock.clone()));
+    let latency = Arc::new(LatencyTracker::new(bus.clone()));
+    let probe = Arc::new(LatencyProbe::new(bus.clone()));
+    let mut actors: Vec<NamedActor> = vec![
+        named("PORTFOLIO", portfolio.clone()),
+        named("LATENCY", latency.clone()),
+        named("PROBE", probe.clone()),
+        named("EXECUTION", Arc::new(execution_engine(bus.clone(), &config).with_clock(clock))),
+    ];
+    actors.extend(trading_actors(&bus, &config, None));
+
+    let mut handles = Vec::new();
+    for (name, actor) in actors {
+        handles.extend(actor.start(&name).await);
+    }
+    info!(target: "MAIN", "Replaying {} bars...", bars.len());
+    driver.schedule_bars(bars);
+    driver.run().await;
+
+    shutdown(handles).await;
+    report(&Reporters { portfolio, latency, probe });
+}
+
+async fn shutdown(handles: Vec<JoinHandle<()>>) {
     for handle in &handles {
         handle.abort(); // 中止所有后台任务
     }
     // 等待所有任务确认中止
     let _ = join_all(handles).await;
-    
+}
+
+fn report(reporters: &Reporters) {
+    info!(
+        target: "MAIN",
+        "Net PnL {:.4} (commission paid {:.4})",
+        reporters.portfolio.net_pnl(),
+        reporters.portfolio.total_commission()
+    );
+    info!(target: "MAIN", "Bar-to-fill latency: {}", reporters.latency.summary());
+    let report = reporters.probe.report();
+    info!(target: "MAIN", "Bar-to-order {} | order-to-fill {}", report.bar_to_order, report.order_to_fill);
     info!(target: "MAIN", "System shut down gracefully.");
-}
\ No newline at end of file
+}
@@ -5,7 +5,12 @@
 //! 定义了系统内部通信所使用的所有消息类型。
 //! 它们是整个事件驱动架构的血液。
 
-use std::fmt::Debug;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 /// ## `Message` Trait
@@ -16,40 +21,785 @@ use uuid::Uuid;
 /// `Send + Sync + 'static`: 确保消息可以在多线程/多任务环境中安全地传递。
 pub trait Message: Clone + Debug + Send + Sync + 'static {}
 
+/// 共享的消息本身也是消息：通道中克隆的只是 `Arc`，见 `MessageBus::publish_shared`。
+impl<M: Message> Message for Arc<M> {}
+
+/// ## `Timestamped` Trait
+///
+/// 携带事件时间（纳秒）的消息，`MessageBus::replay_from_store` 以它筛选需要重放的消息。
+pub trait Timestamped: Message {
+    fn ts_event(&self) -> u64;
+}
+
+/// ## `HasId` Trait
+///
+/// 带有唯一 ID 的消息，`MessageBus::publish_and_await_reply` 以它关联回复，`publish_idempotent` 以它去重。
+pub trait HasId: Message {
+    fn id(&self) -> Uuid;
+}
+
+/// ## `HasCorrelationId` Trait
+///
+/// 可以作为某个请求的回复的消息。`correlation_id` 为对应请求的 `HasId::id`，不是回复时为 `None`。
+pub trait HasCorrelationId: Message {
+    fn correlation_id(&self) -> Option<Uuid>;
+}
+
+/// ## `Keyed` Trait
+///
+/// 可以按键建立索引的消息。价格、数量等浮点字段使 `Bar`、`OrderRequest`、`FillEvent` 无法按全部字段
+/// 实现 `Eq`/`Hash`（`NaN != NaN`），放入 `HashMap`、`BTreeMap` 或去重时以 `key()` 为键。
+pub trait Keyed: Message {
+    type Key: Clone + Debug + Eq + Hash + Ord + Send + Sync;
+    fn key(&self) -> Self::Key;
+}
+
 // --- 行情数据消息 ---
 
-#[derive(Clone, Debug)]
+/// 一根 K 线。
+///
+/// 相等、哈希与排序都只看 `BarKey` 与 `id`，不比较价格和成交量：同一条消息的克隆相等，
+/// 排序按 `ts_event` 升序，时间相同时再按 symbol、周期和 `id`，使任意顺序的 `Vec<Bar>` 排序后结果唯一。
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Bar {
     pub id: Uuid,
     pub ts_event: u64,
     pub symbol: String,
+    /// 该周期的开盘价。
+    #[serde(default)]
+    pub open: f64,
+    /// 该周期内的最高价。
+    pub high: f64,
+    /// 该周期内的最低价。
+    pub low: f64,
     pub close: f64,
+    /// 该周期的成交量。
+    #[serde(default)]
+    pub volume: f64,
+    /// 聚合周期，例如 1 分钟、5 分钟或 1 小时。缺省时为 `DEFAULT_BAR_TIMEFRAME`。
+    #[serde(default = "default_bar_timeframe")]
+    pub timeframe: Duration,
 }
 impl Message for Bar {}
 
+/// 未指定周期时 `Bar` 的聚合周期（1 分钟）。
+pub const DEFAULT_BAR_TIMEFRAME: Duration = Duration::from_secs(60);
+
+fn default_bar_timeframe() -> Duration {
+    DEFAULT_BAR_TIMEFRAME
+}
+
+impl Timestamped for Bar {
+    fn ts_event(&self) -> u64 {
+        self.ts_event
+    }
+}
+
+/// ## `BarKey`
+///
+/// 一根 `Bar` 所属的窗口：symbol、周期与窗口结束时间。同一窗口修订后重新发布的 `Bar` 键相同。
+/// 字段顺序即排序顺序，先按时间。
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BarKey {
+    pub ts_event: u64,
+    pub symbol: String,
+    pub timeframe: Duration,
+}
+
+impl Keyed for Bar {
+    type Key = BarKey;
+
+    fn key(&self) -> BarKey {
+        BarKey { ts_event: self.ts_event, symbol: self.symbol.clone(), timeframe: self.timeframe }
+    }
+}
+
+impl Bar {
+    /// 参与比较的字段，借用而不克隆 symbol。
+    fn ordering_fields(&self) -> (u64, &str, Duration, Uuid) {
+        (self.ts_event, &self.symbol, self.timeframe, self.id)
+    }
+}
+
+impl PartialEq for Bar {
+    fn eq(&self, other: &Self) -> bool {
+        self.ordering_fields() == other.ordering_fields()
+    }
+}
+
+impl Eq for Bar {}
+
+impl Hash for Bar {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ordering_fields().hash(state);
+    }
+}
+
+impl PartialOrd for Bar {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Bar {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ordering_fields().cmp(&other.ordering_fields())
+    }
+}
+
+/// 逐笔成交行情。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TradeTick {
+    pub id: Uuid,
+    pub symbol: String,
+    pub price: f64,
+    pub size: f64,
+    pub ts_event: u64,
+}
+impl Message for TradeTick {}
+
+impl Timestamped for TradeTick {
+    fn ts_event(&self) -> u64 {
+        self.ts_event
+    }
+}
+
+/// 最优买卖报价。`PortfolioTracker` 以它对持仓保守估值：多头按买价，空头按卖价。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Quote {
+    pub symbol: String,
+    pub bid: f64,
+    pub ask: f64,
+    pub ts_event: u64,
+}
+impl Message for Quote {}
+
+impl Timestamped for Quote {
+    fn ts_event(&self) -> u64 {
+        self.ts_event
+    }
+}
+
+/// 滚动时间窗口内的成交量加权平均价。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VwapUpdate {
+    pub symbol: String,
+    pub vwap: f64,
+    /// 窗口内的总成交量。
+    pub window_volume: f64,
+    pub ts_event: u64,
+}
+impl Message for VwapUpdate {}
+
+/// 滚动时间窗口内的主动买卖成交量与订单流不平衡。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderFlowMetric {
+    pub symbol: String,
+    /// `(buy_volume - sell_volume) / (buy_volume + sell_volume)`，取值在 `[-1, 1]`，正值表示买方主导。
+    pub imbalance: f64,
+    pub buy_volume: f64,
+    pub sell_volume: f64,
+    /// 窗口结束的时间，即触发本次更新的成交的 `ts_event`。
+    pub window_end_ts: u64,
+}
+impl Message for OrderFlowMetric {}
+
+impl Timestamped for OrderFlowMetric {
+    fn ts_event(&self) -> u64 {
+        self.window_end_ts
+    }
+}
+
+// --- 订单簿消息 ---
+
+/// 订单簿中的一个价位。
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PriceLevel {
+    pub price: f64,
+    pub size: f64,
+}
+
+/// 订单簿的一侧。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+/// 增量更新的动作。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BookAction {
+    /// 新增一个此前不存在的价位。
+    Add,
+    /// 修改已有价位的数量。
+    Update,
+    /// 删除已有价位。
+    Delete,
+}
+
+/// 订单簿全量快照。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderBookSnapshot {
+    pub symbol: String,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+    pub ts_event: u64,
+}
+impl Message for OrderBookSnapshot {}
+
+/// 订单簿增量更新。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderBookDelta {
+    pub symbol: String,
+    pub side: BookSide,
+    pub action: BookAction,
+    pub price: f64,
+    /// `Delete` 时忽略。
+    pub size: f64,
+    pub ts_event: u64,
+}
+impl Message for OrderBookDelta {}
+
 // --- 交易执行消息 ---
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OrderSide {
     Buy,
     Sell,
 }
 
-#[derive(Clone, Debug)]
+/// 订单类型，决定 `OrderRequest::price` 的含义。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderType {
+    /// 市价单：确认后立即按 `price`（参考价）成交。
+    Market,
+    /// 限价单：挂单等待行情，价格达到或优于 `price` 时成交。
+    Limit,
+    /// 止损市价单：行情触及触发价后转为市价单成交。触发价为 `trigger_price`，未设置时为 `price`。
+    #[serde(alias = "Stop")]
+    StopMarket,
+    /// 止损限价单：行情触及触发价 `trigger_price` 后转为限价为 `price` 的限价单。
+    StopLimit,
+}
+
+/// 下单请求。含浮点价格，只实现 `PartialEq`；按 `id` 建立索引见 `Keyed`。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct OrderRequest {
     pub id: Uuid,
     pub symbol: String,
     pub side: OrderSide,
+    pub order_type: OrderType,
     pub price: f64,
     pub quantity: f64,
+    /// 止损单的触发价，其他订单类型忽略。
+    #[serde(default)]
+    pub trigger_price: Option<f64>,
 }
 impl Message for OrderRequest {}
 
-#[derive(Clone, Debug)]
+impl HasId for Bar {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl HasId for OrderRequest {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl Keyed for OrderRequest {
+    type Key = Uuid;
+
+    fn key(&self) -> Uuid {
+        self.id
+    }
+}
+
+impl OrderRequest {
+    /// 止损单的触发价：`StopMarket` 未设置 `trigger_price` 时以 `price` 为触发价，
+    /// `StopLimit` 必须设置 `trigger_price`。非止损单返回 `None`。
+    pub fn stop_price(&self) -> Option<f64> {
+        match self.order_type {
+            OrderType::StopMarket => Some(self.trigger_price.unwrap_or(self.price)),
+            OrderType::StopLimit => self.trigger_price,
+            OrderType::Market | OrderType::Limit => None,
+        }
+    }
+}
+
+/// 止损单被行情触发，转为市价单或限价单。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderTriggered {
+    pub order_id: Uuid,
+    pub symbol: String,
+    /// 触发后的订单类型：`Market` 或 `Limit`。
+    pub order_type: OrderType,
+    pub trigger_price: f64,
+    /// 触发时的行情参考价。跳空越过触发价时为跳空后的价格，而不是触发价。
+    pub reference_price: f64,
+    pub ts_event: u64,
+}
+impl Message for OrderTriggered {}
+
+/// 订单确认：执行端已接受订单。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderAccepted {
+    pub order_id: Uuid,
+    pub symbol: String,
+    pub ts_event: u64,
+}
+impl Message for OrderAccepted {}
+
+/// 订单被拒绝的原因。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RejectReason {
+    /// 数量为零、负数或非有限值。
+    InvalidQuantity,
+    /// 价格为零、负数或非有限值。
+    InvalidPrice,
+    /// 不在允许交易的 symbol 列表中。
+    UnknownSymbol,
+    /// 订单 ID 已被使用。
+    DuplicateOrderId,
+    /// 重复检测记住的订单 ID 已达上限且都仍在保留时间内，无法确认该 ID 不是重复。
+    OrderIdWindowFull,
+    /// 可用资金不足（需要执行引擎设置可用资金）。
+    InsufficientFunds,
+    /// 交易已被暂停。
+    TradingHalted,
+    /// 超出执行端的下单速率或未结束订单数限制。
+    Throttled,
+    /// 执行端（交易场所）拒绝或无法处理该订单，详见 `detail`。
+    VenueRejected,
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+/// 订单拒绝回报：执行端拒绝了订单，不会产生任何成交。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderRejected {
+    pub order_id: Uuid,
+    pub symbol: String,
+    pub reason: RejectReason,
+    /// 便于排查的可读说明。
+    pub detail: String,
+}
+impl Message for OrderRejected {}
+
+/// 成交的流动性方向：挂单被动成交为 `Maker`，主动吃单为 `Taker`。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Liquidity {
+    Maker,
+    #[default]
+    Taker,
+}
+
+/// 成交回报。一个订单可能对应多个 `FillEvent`（部分成交）。
+///
+/// 手续费相关字段在旧格式的成交中不存在，反序列化时使用默认值（无手续费、`Taker`）。
+/// 含浮点价格，只实现 `PartialEq`；按订单建立索引见 `Keyed`。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct FillEvent {
     pub order_id: Uuid,
     pub symbol: String,
+    pub side: OrderSide,
     pub price: f64,
+    /// 本次成交的数量。
     pub quantity: f64,
+    /// 成交后订单剩余未成交的数量。
+    pub leaves_qty: f64,
+    /// 是否为该订单的最后一笔成交。
+    pub is_final: bool,
+    /// 本次成交产生的手续费。
+    #[serde(default)]
+    pub commission: f64,
+    /// 手续费的计价货币，例如 `"USD"`。未知时为空字符串。
+    #[serde(default)]
+    pub commission_currency: String,
+    #[serde(default)]
+    pub liquidity: Liquidity,
+    /// 成交发生的时间（纳秒）。
+    pub ts_event: u64,
+    /// 交易场所分配的成交 ID（如果有）。
+    #[serde(default)]
+    pub venue_fill_id: Option<String>,
+    /// 这笔成交所回复的请求，即产生它的 `OrderRequest` 的 ID。
+    #[serde(default)]
+    pub correlation_id: Option<Uuid>,
+}
+impl Message for FillEvent {}
+
+impl HasCorrelationId for FillEvent {
+    fn correlation_id(&self) -> Option<Uuid> {
+        self.correlation_id
+    }
+}
+
+impl Timestamped for FillEvent {
+    fn ts_event(&self) -> u64 {
+        self.ts_event
+    }
+}
+
+/// 成交按所属订单建立索引：同一订单的多笔部分成交键相同。
+impl Keyed for FillEvent {
+    type Key = Uuid;
+
+    fn key(&self) -> Uuid {
+        self.order_id
+    }
+}
+
+/// 持仓变化事件，由组合跟踪器在订单成交完成后发布。
+/// 同一订单的多笔部分成交会被汇总为一次更新。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PositionUpdate {
+    pub symbol: String,
+    /// 净持仓，多头为正、空头为负。
+    pub net_position: f64,
+    pub avg_price: f64,
+    pub ts: u64,
+}
+impl Message for PositionUpdate {}
+
+/// 组合权益快照，由设置了初始资金的组合跟踪器在估值或成交后发布，供仓位计算使用。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PortfolioSnapshot {
+    /// 总权益 = 初始资金 + 所有持仓的净盈亏。
+    pub total_equity: f64,
+    /// 所有持仓的净盈亏（已实现 + 未实现 - 手续费）。
+    pub net_pnl: f64,
+    pub ts: u64,
+}
+impl Message for PortfolioSnapshot {}
+
+/// `TradeLogActor::export_csv` 把交易记录写入了 `path`。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TradeLogExported {
+    pub path: String,
+    pub trade_count: usize,
+}
+impl Message for TradeLogExported {}
+
+/// 撤单请求。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CancelOrderRequest {
+    pub order_id: Uuid,
+    pub symbol: String,
+}
+impl Message for CancelOrderRequest {}
+
+/// 撤单成功回报，携带撤单时剩余未成交的数量。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderCanceled {
+    pub order_id: Uuid,
+    pub symbol: String,
+    pub remaining_qty: f64,
+}
+impl Message for OrderCanceled {}
+
+/// 撤单被拒绝的原因。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CancelRejectReason {
+    /// 订单已经完全成交。
+    AlreadyFilled,
+    /// 订单已经被撤销。
+    AlreadyCanceled,
+    /// 执行端不认识该订单（从未提交、被拒绝，或早已超出记录窗口）。
+    UnknownOrder,
+}
+
+impl fmt::Display for CancelRejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+/// 撤单拒绝回报：订单没有可撤销的剩余数量。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CancelRejected {
+    pub order_id: Uuid,
+    pub reason: CancelRejectReason,
+}
+impl Message for CancelRejected {}
+
+// --- 订单状态消息 ---
+
+/// 订单在执行端的生命周期状态。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderStatus {
+    /// 已收到，等待确认。
+    Submitted,
+    /// 已确认，等待成交（市价单等待成交延迟，限价/止损单挂单等待行情）。
+    Accepted,
+    /// 止损单已被触发，正在按市价成交或作为限价单挂单。
+    Triggered,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Rejected,
+}
+
+impl OrderStatus {
+    /// 是否为终结状态，之后不会再有任何变化。
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderStatus::Filled | OrderStatus::Canceled | OrderStatus::Rejected)
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+/// 执行端跟踪的一个订单及其状态。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrackedOrder {
+    pub order: OrderRequest,
+    pub status: OrderStatus,
+    pub filled_qty: f64,
+    pub leaves_qty: f64,
+    /// 每次状态变化及其发生时间（纳秒），按时间先后排列。
+    pub transitions: Vec<(OrderStatus, u64)>,
+}
+
+/// 订单状态变化事件，订阅者可以据此镜像执行端的订单状态。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderStatusChanged {
+    pub order_id: Uuid,
+    pub symbol: String,
+    /// 变化前的状态，新订单为 `None`。
+    pub previous: Option<OrderStatus>,
+    pub status: OrderStatus,
+    pub filled_qty: f64,
+    pub leaves_qty: f64,
+    pub ts_event: u64,
+}
+impl Message for OrderStatusChanged {}
+
+/// 订单以何种方式结束，见 `OrderComplete`。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CompletionStatus {
+    Filled,
+    Canceled,
+    Rejected,
+    /// 超过等待时间仍未结束，`fills` 为此前收到的部分成交。
+    TimedOut,
+}
+
+/// 一个订单的完整生命周期：原始订单与它的全部成交，由 `OrderFillJoiner` 在订单结束时发布。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderComplete {
+    pub order: OrderRequest,
+    /// 按到达顺序排列的成交。
+    pub fills: Vec<FillEvent>,
+    pub filled_qty: f64,
+    /// 按成交数量加权的平均成交价，没有成交时为 0。
+    pub avg_fill_price: f64,
+    pub status: CompletionStatus,
+    pub ts_event: u64,
+}
+impl Message for OrderComplete {}
+
+impl Timestamped for OrderComplete {
+    fn ts_event(&self) -> u64 {
+        self.ts_event
+    }
+}
+
+/// 查询执行端当前未结束的订单。`symbol` 为 `None` 时查询所有 symbol。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpenOrdersQuery {
+    pub symbol: Option<String>,
+}
+impl Message for OpenOrdersQuery {}
+
+/// 对 `OpenOrdersQuery` 的回复，按提交时间排列。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OpenOrdersReport {
+    pub symbol: Option<String>,
+    pub orders: Vec<TrackedOrder>,
+    pub ts_event: u64,
+}
+impl Message for OpenOrdersReport {}
+
+// --- 系统控制消息 ---
+
+/// 暂停交易。`symbol` 为 `None` 时暂停所有 symbol。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TradingHalted {
+    pub symbol: Option<String>,
+    pub reason: String,
+}
+impl Message for TradingHalted {}
+
+/// 平掉所有持仓：撤销所有未结束的订单，并以市价单平掉每个 symbol 的净持仓。
+/// 通常由 `DrawdownGuard` 在回撤超限时发布，随后紧跟一条全局 `TradingHalted`。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FlattenAll {
+    pub reason: String,
+    pub ts_event: u64,
+}
+impl Message for FlattenAll {}
+
+/// 恢复交易。`symbol` 为 `None` 时解除全局暂停。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TradingResumed {
+    pub symbol: Option<String>,
+}
+impl Message for TradingResumed {}
+
+/// 某个 symbol 的预热阶段已完成，策略可以开始产生订单。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WarmupComplete {
+    pub symbol: String,
+    pub bars_seen: usize,
+    pub ts_event: u64,
+}
+impl Message for WarmupComplete {}
+
+// --- 系统事件 ---
+
+/// 总线自身的运行状况事件，供运维监控。只实现 `Serialize`：类型名是 `&'static str`。
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum SystemEvent {
+    /// 某类消息的通道积压超过阈值（见 `MessageBus::enable_hotspot_detection`）。
+    /// 可以加大该类型的容量、降低发布速率或改用 `ShardedMessageBus`。
+    ChannelHotSpot {
+        /// `std::any::type_name`。
+        type_name: &'static str,
+        /// 积压最多的命名空间中，尚未被所有订阅者读取的消息占通道槽位的百分比。
+        fill_pct: f64,
+        /// 所有命名空间的订阅者总数。
+        receiver_count: usize,
+    },
+}
+impl Message for SystemEvent {}
+
+/// Actor 的任务已经结束（正常返回、提前出错返回或被中止），通常由任务开头创建的 `PublishGuard` 发布。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ActorStopped {
+    pub actor_name: String,
+}
+impl Message for ActorStopped {}
+
+/// Actor 处理某条消息失败并跳过了它，例如 `ScriptedStrategy` 的脚本处理函数运行出错。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ErrorEvent {
+    /// 出错的 Actor 名称。
+    pub source: String,
+    pub error: String,
+    pub ts_event: u64,
+}
+impl Message for ErrorEvent {}
+
+/// `ScriptedStrategy` 在脚本文件变化后重新加载了脚本，脚本保存的状态（顶层变量）已被重置。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScriptReloaded {
+    pub source: String,
+    pub path: String,
+    pub ts_event: u64,
+}
+impl Message for ScriptReloaded {}
+
+// --- 复合消息 ---
+
+/// ## `TradingEvent`
+///
+/// 订单、成交与撤单的带标签联合。每种消息类型各有独立的通道，类型之间没有先后保证；
+/// 需要按发布顺序看到“订单 → 成交 → 撤单”的消费者订阅 `TradingEvent`，在同一个有序的流中按变体匹配。
+/// 生产者用 `MessageBus::publish_as::<TradingEvent, _>(order)` 发布。
+///
+/// 以 `TradingEvent` 发布的消息只进入 `TradingEvent` 的通道，`subscribe::<OrderRequest>` 等收不到；
+/// 这是以按类型订阅的粒度换取跨类型的顺序。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum TradingEvent {
+    Order(OrderRequest),
+    Fill(FillEvent),
+    Cancel(CancelOrderRequest),
+}
+impl Message for TradingEvent {}
+
+impl TradingEvent {
+    pub fn symbol(&self) -> &str {
+        match self {
+            TradingEvent::Order(order) => &order.symbol,
+            TradingEvent::Fill(fill) => &fill.symbol,
+            TradingEvent::Cancel(cancel) => &cancel.symbol,
+        }
+    }
+
+    /// 事件所属的订单 ID。
+    pub fn order_id(&self) -> Uuid {
+        match self {
+            TradingEvent::Order(order) => order.id,
+            TradingEvent::Fill(fill) => fill.order_id,
+            TradingEvent::Cancel(cancel) => cancel.order_id,
+        }
+    }
+}
+
+impl From<OrderRequest> for TradingEvent {
+    fn from(order: OrderRequest) -> Self {
+        TradingEvent::Order(order)
+    }
+}
+
+impl From<FillEvent> for TradingEvent {
+    fn from(fill: FillEvent) -> Self {
+        TradingEvent::Fill(fill)
+    }
+}
+
+impl From<CancelOrderRequest> for TradingEvent {
+    fn from(cancel: CancelOrderRequest) -> Self {
+        TradingEvent::Cancel(cancel)
+    }
+}
+
+// --- 信号消息 ---
+
+/// 交易信号：信号生成与下单逻辑之间的中间消息。
+/// 由 `TrendSignalGenerator` 等信号源发布，`SignalOrderConverter` 把它转换为订单。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Signal {
+    pub id: Uuid,
+    pub symbol: String,
+    pub direction: OrderSide,
+    /// 信号强度，通常在 `[0, 1]` 之间，决定订单数量。
+    pub strength: f64,
+    /// 产生信号时的参考价格，作为订单的价格。
+    pub price: f64,
+    pub ts_event: u64,
+    /// 产生信号的信号源，例如 `"trend"`。
+    pub source: String,
+}
+impl Message for Signal {}
+
+impl Timestamped for Signal {
+    fn ts_event(&self) -> u64 {
+        self.ts_event
+    }
+}
+
+/// 某个策略对一个 symbol 的方向性观点，由 `SignalAggregator` 合并为一个订单。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StrategySignal {
+    /// 产生信号的策略，同一窗口内同一策略只保留最新的信号。
+    pub strategy_id: String,
+    pub symbol: String,
+    pub direction: OrderSide,
+    /// 信号强度，通常在 `[0, 1]` 之间。
+    pub strength: f64,
+    /// 产生信号时的参考价格，作为合并后订单的价格。
+    pub price: f64,
+    pub ts_event: u64,
 }
-impl Message for FillEvent {}
\ No newline at end of file
+impl Message for StrategySignal {}
@@ -0,0 +1,347 @@
+// src/metrics.rs
+
+//! # 指标导出模块 (metrics)
+//!
+//! `MetricsExporter` 以 Prometheus 文本格式在 `/metrics` 上导出总线与 Actor 的运行指标，
+//! 需要启用 `metrics` feature：`cargo build --features metrics`。
+//!
+//! - 总线：每种消息类型的发布、丢弃（无人接收）与落后（覆盖未读消息）计数。
+//! - Actor：每个 Actor 处理的消息数与处理耗时直方图（`ActorMetrics`）。
+//! - 执行：每个 symbol 未结束的订单数、成交笔数与成交数量。
+//! - 组合：每个 symbol 的持仓、均价与净盈亏（通过 `with_portfolio` 接入）。
+//!
+//! 指标在类型、Actor 或 symbol 第一次出现时才会导出；标签只有消息类型、Actor 名称与 symbol，
+//! 不含订单 ID 等无界的值。
+
+use crate::actor::{spawn_named, Actor, HandlerStats, HANDLER_LATENCY_BUCKETS};
+use crate::bus::{BusError, MessageBus, TypeMetrics};
+use crate::dedup::SeenWindow;
+use crate::message::{FillEvent, OrderCanceled, OrderRejected, OrderRequest};
+use crate::portfolio::{PortfolioTracker, Position};
+use crate::startup::StartupBarrierHandle;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as _;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::info;
+use uuid::Uuid;
+
+/// 默认的监听端口。
+pub const DEFAULT_METRICS_PORT: u16 = 9898;
+
+/// 单次抓取（读取请求并写回响应）的最长时间，超时的连接被直接关闭。
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `accept` 出错后第一次重试前的等待时间，连续出错时逐次加倍，直到 `MAX_ACCEPT_BACKOFF`。
+/// 文件描述符耗尽（EMFILE）之类的错误会立即重复出现，不等待就会变成空转。
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(10);
+
+/// `accept` 出错后等待时间的上限。
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// 请求头的最大长度，`/metrics` 的请求不需要更多。
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// 为乱序到达的回报记住的已结束订单数量上限。
+const CLOSED_ORDER_MEMORY: usize = 10_000;
+
+/// 一组同类指标中的一个：（指标名，说明，从 `T` 取值的函数）。
+type Series<T, V> = (&'static str, &'static str, fn(&T) -> V);
+
+/// 从订单与回报消息汇总出的执行指标。
+#[derive(Default)]
+struct ExecutionStats {
+    /// 每个 symbol 未结束的订单。出现过的 symbol 保留空集合，使其指标显示为 0。
+    open_orders: BTreeMap<String, HashSet<Uuid>>,
+    /// 每个 symbol 的（成交笔数，成交数量）。
+    fills: BTreeMap<String, (u64, f64)>,
+}
+
+/// ## `MetricsExporter`
+///
+/// 在 `0.0.0.0:<port>` 上提供 Prometheus 抓取端点 `/metrics` 的 Actor。
+/// - 总线与 Actor 指标在每次抓取时从 `MessageBus::type_metrics` 与 `MessageBus::actor_metrics` 读取。
+/// - 消费 `OrderRequest`、`FillEvent`、`OrderRejected` 与 `OrderCanceled`，统计未结束订单与成交；
+///   订单在完全成交、被拒绝或被撤销时结束。
+/// - 持仓与盈亏在每次抓取时从 `with_portfolio` 给出的 `PortfolioTracker` 读取。
+///
+/// HTTP 服务与消息处理都运行在 `start` 返回的任务中，随其他 Actor 的任务一起被中止，不会阻止运行时退出。
+/// 同一时间只处理一个抓取请求。
+pub struct MetricsExporter {
+    bus: MessageBus,
+    port: u16,
+    portfolio: Option<Arc<PortfolioTracker>>,
+    stats: Mutex<ExecutionStats>,
+    local_addr: Mutex<Option<SocketAddr>>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl MetricsExporter {
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            port: DEFAULT_METRICS_PORT,
+            portfolio: None,
+            stats: Mutex::new(ExecutionStats::default()),
+            local_addr: Mutex::new(None),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 设置监听端口，默认 `DEFAULT_METRICS_PORT`。为 0 时由系统分配，通过 `local_addr` 查询。
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// 导出该组合的持仓与盈亏。
+    pub fn with_portfolio(mut self, portfolio: Arc<PortfolioTracker>) -> Self {
+        self.portfolio = Some(portfolio);
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 实际监听的地址，`start` 成功绑定端口之后才有值。
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.lock().unwrap()
+    }
+
+    /// ## `render`
+    ///
+    /// 以 Prometheus 文本格式（0.0.4）输出当前所有指标，即 `/metrics` 的响应体。
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        let types = self.bus.type_metrics();
+        let type_counters: [Series<TypeMetrics, u64>; 3] = [
+            ("message_bus_published_total", "Messages published, by message type.", |m| m.published),
+            ("message_bus_dropped_total", "Messages published with no subscriber to receive them.", |m| m.dropped),
+            ("message_bus_lagged_total", "Publishes that overwrote a message some subscriber had not read yet.", |m| m.lagged),
+        ];
+        for (name, help, value) in type_counters {
+            header(&mut out, name, help, "counter");
+            for (type_name, metrics) in &types {
+                let _ = writeln!(out, "{}{{type=\"{}\"}} {}", name, escape(short_type_name(type_name)), value(metrics));
+            }
+        }
+
+        let actors = self.bus.actor_metrics().snapshot();
+        header(&mut out, "actor_messages_processed_total", "Messages handled, by actor.", "counter");
+        for (actor, stats) in &actors {
+            let _ = writeln!(out, "actor_messages_processed_total{{actor=\"{}\"}} {}", escape(actor), stats.processed);
+        }
+        header(&mut out, "actor_handler_duration_seconds", "Time spent handling one message, by actor.", "histogram");
+        for (actor, stats) in &actors {
+            write_histogram(&mut out, &escape(actor), stats);
+        }
+
+        {
+            let stats = self.stats.lock().unwrap();
+            header(&mut out, "execution_open_orders", "Orders submitted and not yet filled, rejected or canceled.", "gauge");
+            for (symbol, open) in &stats.open_orders {
+                let _ = writeln!(out, "execution_open_orders{{symbol=\"{}\"}} {}", escape(symbol), open.len());
+            }
+            header(&mut out, "execution_fills_total", "Fill events, by symbol.", "counter");
+            for (symbol, (count, _)) in &stats.fills {
+                let _ = writeln!(out, "execution_fills_total{{symbol=\"{}\"}} {}", escape(symbol), count);
+            }
+            header(&mut out, "execution_filled_quantity_total", "Filled quantity, by symbol.", "counter");
+            for (symbol, (_, quantity)) in &stats.fills {
+                let _ = writeln!(out, "execution_filled_quantity_total{{symbol=\"{}\"}} {}", escape(symbol), quantity);
+            }
+        }
+
+        if let Some(portfolio) = &self.portfolio {
+            let positions = portfolio.positions();
+            let gauges: [Series<Position, f64>; 3] = [
+                ("portfolio_position", "Net position, positive for long.", |p| p.quantity),
+                ("portfolio_avg_price", "Average entry price of the open position.", |p| p.avg_price),
+                ("portfolio_net_pnl", "Realized plus unrealized PnL, net of commission.", |p| p.net_pnl()),
+            ];
+            for (name, help, value) in gauges {
+                header(&mut out, name, help, "gauge");
+                for position in &positions {
+                    let _ = writeln!(out, "{}{{symbol=\"{}\"}} {}", name, escape(&position.symbol), value(position));
+                }
+            }
+        }
+        out
+    }
+
+    fn on_order(&self, order: OrderRequest, closed: &SeenWindow) {
+        let mut stats = self.stats.lock().unwrap();
+        let open = stats.open_orders.entry(order.symbol).or_default();
+        // 回报可能先于订单被处理
+        if !closed.contains(&order.id) {
+            open.insert(order.id);
+        }
+    }
+
+    fn on_fill(&self, fill: FillEvent, closed: &mut SeenWindow) {
+        let mut stats = self.stats.lock().unwrap();
+        let (count, quantity) = stats.fills.entry(fill.symbol.clone()).or_default();
+        *count += 1;
+        *quantity += fill.quantity;
+        if fill.is_final {
+            Self::close(&mut stats, &fill.symbol, fill.order_id, closed);
+        }
+    }
+
+    fn close(stats: &mut ExecutionStats, symbol: &str, order_id: Uuid, closed: &mut SeenWindow) {
+        closed.insert(order_id);
+        if let Some(open) = stats.open_orders.get_mut(symbol) {
+            open.remove(&order_id);
+        }
+    }
+
+    async fn serve(&self, listener: TcpListener) {
+        let mut backoff = ACCEPT_BACKOFF;
+        loop {
+            let (mut stream, peer) = match listener.accept().await {
+                Ok(accepted) => {
+                    backoff = ACCEPT_BACKOFF;
+                    accepted
+                },
+                Err(e) => {
+                    tracing::warn!(target: "METRICS", "Failed to accept connection: {}; retrying in {:?}", e, backoff);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_ACCEPT_BACKOFF);
+                    continue;
+                },
+            };
+            match tokio::time::timeout(SCRAPE_TIMEOUT, self.respond(&mut stream)).await {
+                Ok(Ok(())) => {},
+                Ok(Err(e)) => tracing::warn!(target: "METRICS", "Scrape from {} failed: {}", peer, e),
+                Err(_) => tracing::warn!(target: "METRICS", "Scrape from {} timed out", peer),
+            }
+        }
+    }
+
+    /// 读取一个 HTTP 请求并写回响应，之后关闭连接。只有 `GET /metrics` 返回指标，其他请求返回 404。
+    async fn respond(&self, stream: &mut TcpStream) -> io::Result<()> {
+        let mut request = Vec::with_capacity(1024);
+        let mut buf = [0u8; 1024];
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+        }
+        let request = String::from_utf8_lossy(&request);
+        let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+        let path = request_line.nth(1).map(|target| target.split('?').next().unwrap_or(target));
+        let (status, content_type, body) = match (request.starts_with("GET "), path) {
+            (true, Some("/metrics")) => ("200 OK", "text/plain; version=0.0.4; charset=utf-8", self.render()),
+            _ => ("404 Not Found", "text/plain; charset=utf-8", "not found\n".to_string()),
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
+
+/// 写入一个指标的 `# HELP` 与 `# TYPE` 行。
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// 写入一个 Actor 的处理耗时直方图（`_bucket`、`_sum` 与 `_count`）。
+fn write_histogram(out: &mut String, actor: &str, stats: &HandlerStats) {
+    let name = "actor_handler_duration_seconds";
+    for (upper, count) in HANDLER_LATENCY_BUCKETS.iter().zip(stats.buckets) {
+        let le = Duration::from_nanos(*upper).as_secs_f64();
+        let _ = writeln!(out, "{}_bucket{{actor=\"{}\",le=\"{}\"}} {}", name, actor, le, count);
+    }
+    let _ = writeln!(out, "{}_bucket{{actor=\"{}\",le=\"+Inf\"}} {}", name, actor, stats.processed);
+    let _ = writeln!(out, "{}_sum{{actor=\"{}\"}} {}", name, actor, Duration::from_nanos(stats.total_nanos).as_secs_f64());
+    let _ = writeln!(out, "{}_count{{actor=\"{}\"}} {}", name, actor, stats.processed);
+}
+
+/// 去掉模块路径的类型名，例如 `message_bus::message::Bar` → `Bar`。
+fn short_type_name(type_name: &str) -> &str {
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
+/// 按 Prometheus 文本格式转义标签值。
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[async_trait::async_trait]
+impl Actor for MetricsExporter {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut order_rx = self.bus.subscribe::<OrderRequest>().await;
+        let mut fill_rx = self.bus.subscribe::<FillEvent>().await;
+        let mut rejected_rx = self.bus.subscribe::<OrderRejected>().await;
+        let mut canceled_rx = self.bus.subscribe::<OrderCanceled>().await;
+
+        let mut handles = Vec::new();
+        match TcpListener::bind((Ipv4Addr::UNSPECIFIED, self.port)).await {
+            Ok(listener) => {
+                let addr = listener.local_addr().ok();
+                *self.local_addr.lock().unwrap() = addr;
+                info!(target: "METRICS", "Serving metrics on {:?}", addr);
+                let exporter = self.clone();
+                handles.push(spawn_named(actor_name, async move { exporter.serve(listener).await }));
+            },
+            Err(e) => tracing::error!(target: "METRICS", "Cannot bind metrics port {}: {}", self.port, e),
+        }
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        handles.push(spawn_named(actor_name, async move {
+            let mut closed = SeenWindow::new(CLOSED_ORDER_MEMORY);
+            loop {
+                tokio::select! {
+                    result = order_rx.recv() => match result {
+                        Ok(order) => self.on_order(order, &closed),
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "METRICS", "{}", BusError::lagged::<OrderRequest>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = fill_rx.recv() => match result {
+                        Ok(fill) => self.on_fill(fill, &mut closed),
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "METRICS", "{}", BusError::lagged::<FillEvent>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = rejected_rx.recv() => match result {
+                        Ok(rejected) => {
+                            let mut stats = self.stats.lock().unwrap();
+                            Self::close(&mut stats, &rejected.symbol, rejected.order_id, &mut closed);
+                        },
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "METRICS", "{}", BusError::lagged::<OrderRejected>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = canceled_rx.recv() => match result {
+                        Ok(canceled) => {
+                            let mut stats = self.stats.lock().unwrap();
+                            Self::close(&mut stats, &canceled.symbol, canceled.order_id, &mut closed);
+                        },
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "METRICS", "{}", BusError::lagged::<OrderCanceled>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        }));
+        handles
+    }
+}
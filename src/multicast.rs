@@ -0,0 +1,62 @@
+// src/multicast.rs
+
+//! # 多播模块 (multicast)
+//!
+//! 将同一条消息发布到多条相互独立的 `MessageBus`，
+//! 例如让模拟盘与实盘共享同一个行情源。
+
+use crate::bus::{BusError, MessageBus, PublishTarget};
+use crate::message::Message;
+use std::marker::PhantomData;
+
+/// ## `MulticastGroup`
+///
+/// 一组目标总线。每次 `publish` 都会为每条总线克隆一份消息，
+/// 并按目标顺序返回各自的结果，而不是在第一个失败处中止。
+pub struct MulticastGroup<M: Message> {
+    targets: Vec<MessageBus>,
+    _marker: PhantomData<fn(M)>,
+}
+
+impl<M: Message> MulticastGroup<M> {
+    pub fn new(targets: Vec<MessageBus>) -> Self {
+        Self { targets, _marker: PhantomData }
+    }
+
+    /// 添加一条目标总线。
+    pub fn add_target(&mut self, bus: MessageBus) {
+        self.targets.push(bus);
+    }
+
+    /// 向所有目标总线发布消息，返回每个目标的结果（顺序与添加顺序一致）。
+    pub async fn publish(&self, msg: M) -> Vec<Result<usize, BusError>> {
+        let mut results = Vec::with_capacity(self.targets.len());
+        for bus in &self.targets {
+            results.push(bus.publish(msg.clone()).await);
+        }
+        results
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Message> PublishTarget<M> for MulticastGroup<M> {
+    async fn publish(&self, msg: M) -> Vec<Result<usize, BusError>> {
+        MulticastGroup::publish(self, msg).await
+    }
+
+    async fn subscriber_count(&self) -> usize {
+        let mut total = 0;
+        for target in &self.targets {
+            total += target.subscriber_count::<M>().await;
+        }
+        total
+    }
+
+    async fn purge(&self) -> Vec<Result<usize, BusError>> {
+        let mut results = Vec::with_capacity(self.targets.len());
+        for bus in &self.targets {
+            results.push(bus.purge::<M>().await);
+        }
+        results
+    }
+}
@@ -0,0 +1,176 @@
+// src/orderbook.rs
+
+//! # 订单簿模块 (orderbook)
+//!
+//! 根据 `OrderBookSnapshot` 与 `OrderBookDelta` 在本地维护买卖盘，并提供常用查询。
+
+use crate::message::{BookAction, BookSide, OrderBookDelta, OrderBookSnapshot, PriceLevel};
+use ordered_float::OrderedFloat;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+/// ## `BookError`
+///
+/// 应用增量更新失败的原因。失败时订单簿保持更新前的状态。
+#[derive(Clone, Debug, PartialEq)]
+pub enum BookError {
+    /// 增量属于其他 symbol。
+    SymbolMismatch { expected: String, actual: String },
+    /// 动作与当前状态不符，例如 `Update`/`Delete` 一个不存在的价位，或 `Add` 一个已存在的价位。
+    InvalidDeltaAction { action: BookAction, side: BookSide, price: f64 },
+    /// 数量不是正的有限值。
+    InvalidSize { size: f64 },
+    /// 应用后最优买价不低于最优卖价。
+    CrossedBook { best_bid: f64, best_ask: f64 },
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookError::SymbolMismatch { expected, actual } => {
+                write!(f, "delta for {} applied to book {}", actual, expected)
+            }
+            BookError::InvalidDeltaAction { action, side, price } => {
+                write!(f, "invalid {:?} on {:?} level {}", action, side, price)
+            }
+            BookError::InvalidSize { size } => write!(f, "invalid level size {}", size),
+            BookError::CrossedBook { best_bid, best_ask } => {
+                write!(f, "crossed book: best bid {} >= best ask {}", best_bid, best_ask)
+            }
+        }
+    }
+}
+
+impl Error for BookError {}
+
+/// ## `LocalOrderBook`
+///
+/// 单个 symbol 的本地订单簿。价格使用 `OrderedFloat` 作为 `BTreeMap` 的键，
+/// 买盘按价格从高到低、卖盘按价格从低到高读取。
+#[derive(Clone, Debug)]
+pub struct LocalOrderBook {
+    pub symbol: String,
+    pub bids: BTreeMap<OrderedFloat<f64>, f64>,
+    pub asks: BTreeMap<OrderedFloat<f64>, f64>,
+    pub last_update_ts: u64,
+}
+
+impl LocalOrderBook {
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_update_ts: 0,
+        }
+    }
+
+    /// 用快照整体替换买卖盘。数量不为正的价位会被忽略。
+    pub fn apply_snapshot(&mut self, snap: &OrderBookSnapshot) {
+        let to_map = |levels: &[PriceLevel]| {
+            levels
+                .iter()
+                .filter(|level| level.size > 0.0)
+                .map(|level| (OrderedFloat(level.price), level.size))
+                .collect()
+        };
+        self.bids = to_map(&snap.bids);
+        self.asks = to_map(&snap.asks);
+        self.last_update_ts = snap.ts_event;
+    }
+
+    /// 应用一条增量更新。失败时订单簿不会被修改。
+    pub fn apply_delta(&mut self, delta: &OrderBookDelta) -> Result<(), BookError> {
+        if delta.symbol != self.symbol {
+            return Err(BookError::SymbolMismatch { expected: self.symbol.clone(), actual: delta.symbol.clone() });
+        }
+        if delta.action != BookAction::Delete && !(delta.size.is_finite() && delta.size > 0.0) {
+            return Err(BookError::InvalidSize { size: delta.size });
+        }
+
+        let price = OrderedFloat(delta.price);
+        let levels = match delta.side {
+            BookSide::Bid => &mut self.bids,
+            BookSide::Ask => &mut self.asks,
+        };
+        let invalid = BookError::InvalidDeltaAction { action: delta.action, side: delta.side, price: delta.price };
+        let previous = match delta.action {
+            BookAction::Add => {
+                if levels.contains_key(&price) {
+                    return Err(invalid);
+                }
+                levels.insert(price, delta.size)
+            }
+            BookAction::Update => match levels.get_mut(&price) {
+                Some(size) => Some(std::mem::replace(size, delta.size)),
+                None => return Err(invalid),
+            },
+            BookAction::Delete => match levels.remove(&price) {
+                Some(size) => Some(size),
+                None => return Err(invalid),
+            },
+        };
+
+        if let (Some(bid), Some(ask)) = (self.best_bid(), self.best_ask()) {
+            if bid.price >= ask.price {
+                // 回滚本次修改
+                let levels = match delta.side {
+                    BookSide::Bid => &mut self.bids,
+                    BookSide::Ask => &mut self.asks,
+                };
+                match previous {
+                    Some(size) => levels.insert(price, size),
+                    None => levels.remove(&price),
+                };
+                return Err(BookError::CrossedBook { best_bid: bid.price, best_ask: ask.price });
+            }
+        }
+
+        self.last_update_ts = delta.ts_event;
+        Ok(())
+    }
+
+    /// 最优买价。
+    pub fn best_bid(&self) -> Option<PriceLevel> {
+        self.bids.iter().next_back().map(|(price, size)| PriceLevel { price: price.0, size: *size })
+    }
+
+    /// 最优卖价。
+    pub fn best_ask(&self) -> Option<PriceLevel> {
+        self.asks.iter().next().map(|(price, size)| PriceLevel { price: price.0, size: *size })
+    }
+
+    /// 买卖中间价。
+    pub fn mid_price(&self) -> Option<f64> {
+        Some((self.best_bid()?.price + self.best_ask()?.price) / 2.0)
+    }
+
+    /// 买卖价差。
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    /// 前 `levels` 档买盘（价格从高到低）与卖盘（价格从低到高）。
+    pub fn depth(&self, levels: usize) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+        let bids = self
+            .bids
+            .iter()
+            .rev()
+            .take(levels)
+            .map(|(price, size)| PriceLevel { price: price.0, size: *size })
+            .collect();
+        let asks = self
+            .asks
+            .iter()
+            .take(levels)
+            .map(|(price, size)| PriceLevel { price: price.0, size: *size })
+            .collect();
+        (bids, asks)
+    }
+
+    /// 前 `levels` 档买盘的总数量。
+    pub fn total_bid_volume(&self, levels: usize) -> f64 {
+        self.bids.values().rev().take(levels).sum()
+    }
+}
@@ -0,0 +1,130 @@
+// src/orderflow.rs
+
+//! # 订单流模块 (orderflow)
+//!
+//! 根据逐笔成交计算滚动时间窗口内的主动买入量、主动卖出量与订单流不平衡。
+//! `TradeTick` 不携带主动方，因此按 tick rule 判断：成交价高于上一笔为主动买入，低于上一笔为主动卖出，
+//! 价格不变时沿用上一笔的方向；每个 symbol 的第一笔成交方向未知，不计入窗口。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::message::{OrderFlowMetric, OrderSide, TradeTick};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// ## `FlowState`
+///
+/// 单个 symbol 的订单流状态：窗口内已分类的成交 `(ts, side, volume)`，按时间先后排列，
+/// 以及用于 tick rule 的上一笔成交价与方向。
+#[derive(Clone, Debug, Default)]
+pub struct FlowState {
+    pub history: VecDeque<(u64, OrderSide, f64)>,
+    last_price: Option<f64>,
+    last_side: Option<OrderSide>,
+}
+
+impl FlowState {
+    /// 按 tick rule 判断一笔成交的主动方，返回 `None` 表示方向未知。
+    pub fn classify(&mut self, price: f64) -> Option<OrderSide> {
+        let side = match self.last_price {
+            Some(last) if price > last => Some(OrderSide::Buy),
+            Some(last) if price < last => Some(OrderSide::Sell),
+            _ => self.last_side.clone(),
+        };
+        self.last_price = Some(price);
+        self.last_side = side.clone();
+        side
+    }
+
+    /// 加入一笔成交，并移除已超出窗口（`entry_ts + window <= ts`）的记录。
+    /// 方向未知的成交只更新 tick rule 的状态。
+    pub fn push(&mut self, ts: u64, price: f64, volume: f64, window: Duration) {
+        if let Some(side) = self.classify(price) {
+            self.history.push_back((ts, side, volume));
+        }
+        let window = window.as_nanos() as u64;
+        while self.history.front().is_some_and(|(entry_ts, ..)| entry_ts + window <= ts) {
+            self.history.pop_front();
+        }
+    }
+
+    /// 窗口内 `side` 方向的成交量。
+    pub fn volume(&self, side: OrderSide) -> f64 {
+        self.history.iter().filter(|(_, s, _)| *s == side).map(|(_, _, volume)| volume).sum()
+    }
+
+    /// `(buy_volume - sell_volume) / (buy_volume + sell_volume)`，窗口内没有成交量时返回 `None`。
+    pub fn imbalance(&self) -> Option<f64> {
+        let buy = self.volume(OrderSide::Buy);
+        let sell = self.volume(OrderSide::Sell);
+        let total = buy + sell;
+        if total <= 0.0 {
+            return None;
+        }
+        Some((buy - sell) / total)
+    }
+}
+
+/// ## `OrderFlowActor`
+///
+/// 一个 Actor，消费 `TradeTick` 消息，按 symbol 维护 `window` 时间窗口内的主动买卖量，
+/// 每笔成交后生产一条 `OrderFlowMetric` 消息。窗口以成交的 `ts_event` 为准，因此在模拟中同样确定。
+pub struct OrderFlowActor {
+    bus: MessageBus,
+    window: Duration,
+    state: Mutex<HashMap<String, FlowState>>,
+}
+
+impl OrderFlowActor {
+    pub fn new(bus: MessageBus, window: Duration) -> Self {
+        Self { bus, window, state: Mutex::new(HashMap::new()) }
+    }
+
+    /// 某个 symbol 当前的订单流不平衡。
+    pub fn imbalance(&self, symbol: &str) -> Option<f64> {
+        self.state.lock().unwrap().get(symbol).and_then(FlowState::imbalance)
+    }
+
+    async fn handle_tick(&self, tick: TradeTick) {
+        let metric = {
+            let mut state = self.state.lock().unwrap();
+            let entry = state.entry(tick.symbol.clone()).or_default();
+            entry.push(tick.ts_event, tick.price, tick.size, self.window);
+            entry.imbalance().map(|imbalance| OrderFlowMetric {
+                symbol: tick.symbol,
+                imbalance,
+                buy_volume: entry.volume(OrderSide::Buy),
+                sell_volume: entry.volume(OrderSide::Sell),
+                window_end_ts: tick.ts_event,
+            })
+        };
+
+        if let Some(metric) = metric {
+            if let Err(e) = self.bus.publish(metric).await {
+                tracing::error!(target: "ORDERFLOW", "Failed to publish order flow metric: {}", e);
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for OrderFlowActor {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut tick_rx = self.bus.subscribe::<TradeTick>().await;
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                match tick_rx.recv().await {
+                    Ok(tick) => self.handle_tick(tick).await,
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "ORDERFLOW", "{}", BusError::lagged::<TradeTick>(n)),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
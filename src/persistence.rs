@@ -0,0 +1,366 @@
+// src/persistence.rs
+
+//! # 交易持久化模块 (persistence)
+//!
+//! 把订单、订单生命周期事件与成交写入 SQLite，进程重启后仍可用 SQL 查询交易记录。
+//! 需要启用 `sqlite` feature。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::clock::{Clock, LiveClock};
+use crate::message::{FillEvent, OrderCanceled, OrderRejected, OrderRequest, OrderStatusChanged};
+use crate::startup::StartupBarrierHandle;
+use rusqlite::types::Type;
+use rusqlite::{params, Connection};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// 默认每个事务最多写入的记录数。
+const DEFAULT_BATCH_SIZE: usize = 256;
+
+/// 按版本顺序排列的 schema 迁移，第 N 条把 `user_version` 从 N 升到 N + 1。
+const MIGRATIONS: &[&str] = &["
+    CREATE TABLE IF NOT EXISTS orders (
+        order_id    TEXT PRIMARY KEY,
+        symbol      TEXT NOT NULL,
+        side        TEXT NOT NULL,
+        order_type  TEXT NOT NULL,
+        price       REAL NOT NULL,
+        quantity    REAL NOT NULL,
+        ts_received INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_orders_symbol_ts ON orders (symbol, ts_received);
+
+    CREATE TABLE IF NOT EXISTS order_events (
+        id          INTEGER PRIMARY KEY AUTOINCREMENT,
+        order_id    TEXT NOT NULL,
+        symbol      TEXT NOT NULL,
+        event       TEXT NOT NULL,
+        status      TEXT,
+        filled_qty  REAL,
+        leaves_qty  REAL,
+        detail      TEXT,
+        ts          INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_order_events_order ON order_events (order_id);
+    CREATE INDEX IF NOT EXISTS idx_order_events_symbol_ts ON order_events (symbol, ts);
+
+    CREATE TABLE IF NOT EXISTS fills (
+        id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+        order_id            TEXT NOT NULL,
+        symbol              TEXT NOT NULL,
+        side                TEXT NOT NULL,
+        price               REAL NOT NULL,
+        quantity            REAL NOT NULL,
+        leaves_qty          REAL NOT NULL,
+        is_final            INTEGER NOT NULL,
+        commission          REAL NOT NULL,
+        commission_currency TEXT NOT NULL,
+        liquidity           TEXT NOT NULL,
+        venue_fill_id       TEXT,
+        ts_event            INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_fills_order ON fills (order_id);
+    CREATE INDEX IF NOT EXISTS idx_fills_symbol_ts ON fills (symbol, ts_event);
+"];
+
+/// 等待写入的一条记录。没有事件时间的消息使用收到时的时钟时间。
+enum Record {
+    Order(OrderRequest, u64),
+    Status(OrderStatusChanged),
+    Rejected(OrderRejected, u64),
+    Canceled(OrderCanceled, u64),
+    Fill(FillEvent),
+}
+
+/// ## `TradePersistence`
+///
+/// 一个 Actor，消费 `OrderRequest`、`OrderStatusChanged`、`OrderRejected`、`OrderCanceled`
+/// 与 `FillEvent` 消息，写入 `orders`、`order_events`、`fills` 三张表：
+/// - 打开数据库时按 `PRAGMA user_version` 依次执行尚未应用的迁移，重复打开是幂等的。
+/// - 接收任务只把记录放入无界缓冲区，由独立的写入任务批量写入，SQLite 的磁盘 I/O 不会阻塞总线。
+/// - 写入任务每次取出缓冲区中已有的记录（最多 `batch_size` 条），在一个事务中提交。
+/// - 同一订单 ID 的重复提交只保留第一次的 `orders` 记录。
+pub struct TradePersistence {
+    bus: MessageBus,
+    clock: Arc<dyn Clock>,
+    conn: Arc<Mutex<Connection>>,
+    batch_size: usize,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl TradePersistence {
+    /// 打开（或创建）`path` 处的数据库并迁移 schema。
+    pub fn open(bus: MessageBus, path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        Self::with_connection(bus, Connection::open(path)?)
+    }
+
+    /// 使用内存数据库，进程结束后数据丢失，用于测试。
+    pub fn in_memory(bus: MessageBus) -> rusqlite::Result<Self> {
+        Self::with_connection(bus, Connection::open_in_memory()?)
+    }
+
+    fn with_connection(bus: MessageBus, mut conn: Connection) -> rusqlite::Result<Self> {
+        migrate(&mut conn)?;
+        Ok(Self {
+            bus,
+            clock: Arc::new(LiveClock),
+            conn: Arc::new(Mutex::new(conn)),
+            batch_size: DEFAULT_BATCH_SIZE,
+            barrier: Mutex::new(None),
+        })
+    }
+
+    /// 设置没有事件时间的消息（订单请求、拒绝、撤单）所用的时间来源。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置每个事务最多写入的记录数，默认 256。
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 当前的 schema 版本。
+    pub fn schema_version(&self) -> rusqlite::Result<usize> {
+        schema_version(&self.conn.lock().unwrap())
+    }
+
+    /// ## `fills_for_symbol`
+    ///
+    /// 查询 `symbol` 在 `range`（纳秒，左闭右开）内的成交，按事件时间排列。
+    /// 只能看到写入任务已经提交的成交。
+    pub fn fills_for_symbol(&self, symbol: &str, range: Range<u64>) -> rusqlite::Result<Vec<FillEvent>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT order_id, symbol, side, price, quantity, leaves_qty, is_final, commission,
+                    commission_currency, liquidity, venue_fill_id, ts_event
+             FROM fills WHERE symbol = ?1 AND ts_event >= ?2 AND ts_event < ?3
+             ORDER BY ts_event, id",
+        )?;
+        let rows = stmt.query_map(params![symbol, sql_ts(range.start), sql_ts(range.end)], |row| {
+            let order_id: String = row.get(0)?;
+            let order_id: Uuid =
+                order_id.parse().map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(e)))?;
+            Ok(FillEvent {
+                order_id,
+                symbol: row.get(1)?,
+                side: enum_from_text(2, row.get(2)?)?,
+                price: row.get(3)?,
+                quantity: row.get(4)?,
+                leaves_qty: row.get(5)?,
+                is_final: row.get(6)?,
+                commission: row.get(7)?,
+                commission_currency: row.get(8)?,
+                liquidity: enum_from_text(9, row.get(9)?)?,
+                venue_fill_id: row.get(10)?,
+                ts_event: row.get::<_, i64>(11)? as u64,
+                // 成交总是回复产生它的订单
+                correlation_id: Some(order_id),
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+fn schema_version(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0)).map(|version| version as usize)
+}
+
+/// 在一个事务中执行所有尚未应用的迁移。
+fn migrate(conn: &mut Connection) -> rusqlite::Result<()> {
+    let version = schema_version(conn)?;
+    let tx = conn.transaction()?;
+    for (idx, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", (idx + 1) as i64)?;
+    }
+    tx.commit()
+}
+
+/// SQLite 的整数是有符号 64 位，超出范围的纳秒时间戳按 `i64::MAX` 保存。
+fn sql_ts(ts: u64) -> i64 {
+    i64::try_from(ts).unwrap_or(i64::MAX)
+}
+
+/// 把单元枚举保存为它的 serde 名称，例如 `"Buy"`。
+fn enum_text<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(text)) => text,
+        other => format!("{:?}", other),
+    }
+}
+
+fn enum_from_text<T: DeserializeOwned>(column: usize, text: String) -> rusqlite::Result<T> {
+    serde_json::from_value(serde_json::Value::String(text))
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(column, Type::Text, Box::new(e)))
+}
+
+/// 在一个事务中写入一批记录。
+fn write_batch(conn: &mut Connection, batch: &[Record]) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    for record in batch {
+        match record {
+            Record::Order(order, ts) => {
+                tx.prepare_cached(
+                    "INSERT OR IGNORE INTO orders (order_id, symbol, side, order_type, price, quantity, ts_received)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )?
+                .execute(params![
+                    order.id.to_string(),
+                    order.symbol,
+                    enum_text(&order.side),
+                    enum_text(&order.order_type),
+                    order.price,
+                    order.quantity,
+                    sql_ts(*ts),
+                ])?;
+            },
+            Record::Status(changed) => {
+                tx.prepare_cached(
+                    "INSERT INTO order_events (order_id, symbol, event, status, filled_qty, leaves_qty, ts)
+                     VALUES (?1, ?2, 'status', ?3, ?4, ?5, ?6)",
+                )?
+                .execute(params![
+                    changed.order_id.to_string(),
+                    changed.symbol,
+                    enum_text(&changed.status),
+                    changed.filled_qty,
+                    changed.leaves_qty,
+                    sql_ts(changed.ts_event),
+                ])?;
+            },
+            Record::Rejected(rejected, ts) => {
+                tx.prepare_cached(
+                    "INSERT INTO order_events (order_id, symbol, event, detail, ts) VALUES (?1, ?2, 'rejected', ?3, ?4)",
+                )?
+                .execute(params![
+                    rejected.order_id.to_string(),
+                    rejected.symbol,
+                    format!("{}: {}", enum_text(&rejected.reason), rejected.detail),
+                    sql_ts(*ts),
+                ])?;
+            },
+            Record::Canceled(canceled, ts) => {
+                tx.prepare_cached(
+                    "INSERT INTO order_events (order_id, symbol, event, leaves_qty, ts) VALUES (?1, ?2, 'canceled', ?3, ?4)",
+                )?
+                .execute(params![canceled.order_id.to_string(), canceled.symbol, canceled.remaining_qty, sql_ts(*ts)])?;
+            },
+            Record::Fill(fill) => {
+                tx.prepare_cached(
+                    "INSERT INTO fills (order_id, symbol, side, price, quantity, leaves_qty, is_final, commission,
+                                        commission_currency, liquidity, venue_fill_id, ts_event)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                )?
+                .execute(params![
+                    fill.order_id.to_string(),
+                    fill.symbol,
+                    enum_text(&fill.side),
+                    fill.price,
+                    fill.quantity,
+                    fill.leaves_qty,
+                    fill.is_final,
+                    fill.commission,
+                    fill.commission_currency,
+                    enum_text(&fill.liquidity),
+                    fill.venue_fill_id,
+                    sql_ts(fill.ts_event),
+                ])?;
+            },
+        }
+    }
+    tx.commit()
+}
+
+/// 写入任务：取出缓冲区中已有的记录组成一批，逐批提交，直到所有发送端关闭。
+fn write_loop(conn: Arc<Mutex<Connection>>, mut rx: mpsc::UnboundedReceiver<Record>, batch_size: usize) {
+    while let Some(first) = rx.blocking_recv() {
+        let mut batch = vec![first];
+        while batch.len() < batch_size {
+            match rx.try_recv() {
+                Ok(record) => batch.push(record),
+                Err(_) => break,
+            }
+        }
+        if let Err(e) = write_batch(&mut conn.lock().unwrap(), &batch) {
+            tracing::error!(target: "PERSISTENCE", "Failed to write {} records: {}", batch.len(), e);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for TradePersistence {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut order_rx = self.bus.subscribe::<OrderRequest>().await;
+        let mut status_rx = self.bus.subscribe::<OrderStatusChanged>().await;
+        let mut rejected_rx = self.bus.subscribe::<OrderRejected>().await;
+        let mut canceled_rx = self.bus.subscribe::<OrderCanceled>().await;
+        let mut fill_rx = self.bus.subscribe::<FillEvent>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let conn = self.conn.clone();
+        let batch_size = self.batch_size;
+        let writer = tokio::task::spawn_blocking(move || write_loop(conn, rx, batch_size));
+
+        let receiver = spawn_named(actor_name, async move {
+            macro_rules! lagged {
+                ($n:expr, $ty:ty) => {
+                    tracing::warn!(target: "PERSISTENCE", "{}, records lost", BusError::lagged::<$ty>($n))
+                };
+            }
+            loop {
+                let record = tokio::select! {
+                    result = order_rx.recv() => match result {
+                        Ok(order) => Record::Order(order, self.clock.now_nanos()),
+                        Err(RecvError::Lagged(n)) => { lagged!(n, OrderRequest); continue },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = status_rx.recv() => match result {
+                        Ok(changed) => Record::Status(changed),
+                        Err(RecvError::Lagged(n)) => { lagged!(n, OrderStatusChanged); continue },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = rejected_rx.recv() => match result {
+                        Ok(rejected) => Record::Rejected(rejected, self.clock.now_nanos()),
+                        Err(RecvError::Lagged(n)) => { lagged!(n, OrderRejected); continue },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = canceled_rx.recv() => match result {
+                        Ok(canceled) => Record::Canceled(canceled, self.clock.now_nanos()),
+                        Err(RecvError::Lagged(n)) => { lagged!(n, OrderCanceled); continue },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = fill_rx.recv() => match result {
+                        Ok(fill) => Record::Fill(fill),
+                        Err(RecvError::Lagged(n)) => { lagged!(n, FillEvent); continue },
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+                if tx.send(record).is_err() {
+                    break;
+                }
+            }
+        });
+
+        vec![receiver, writer]
+    }
+}
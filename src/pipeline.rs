@@ -0,0 +1,105 @@
+// src/pipeline.rs
+
+//! # 流水线模块 (pipeline)
+//!
+//! 将一系列 `PipelineActor<In, Out>` 串联起来，例如 `Bar -> Signal -> OrderRequest`。
+//! 借助 type-state 模式，相邻两级的类型不匹配会在编译期报错。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::message::Message;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// 尚未添加任何阶段时的流水线状态，第一级可以接受任意输入类型。
+pub struct Start;
+
+/// ## `Connects` Trait
+///
+/// 描述“上一级的输出可以作为下一级的输入”。
+/// 只有输出类型与输入类型完全相同（或流水线处于 `Start` 状态）时才成立。
+pub trait Connects<In> {}
+
+impl<M: Message> Connects<M> for M {}
+impl<M: Message> Connects<M> for Start {}
+
+/// ## `PipelineActor`
+///
+/// 流水线中的一级：订阅 `In`，对每条消息调用转换函数，
+/// 返回 `Some(out)` 时发布 `Out`，返回 `None` 时丢弃该消息。
+pub struct PipelineActor<In: Message, Out: Message> {
+    bus: MessageBus,
+    transform: Box<dyn Fn(In) -> Option<Out> + Send + Sync>,
+}
+
+impl<In: Message, Out: Message> PipelineActor<In, Out> {
+    pub fn new(bus: MessageBus, transform: impl Fn(In) -> Option<Out> + Send + Sync + 'static) -> Self {
+        Self { bus, transform: Box::new(transform) }
+    }
+}
+
+#[async_trait::async_trait]
+impl<In: Message, Out: Message> Actor for PipelineActor<In, Out> {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut rx = self.bus.subscribe::<In>().await;
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                match rx.recv().await {
+                    Ok(input) => {
+                        if let Some(output) = (self.transform)(input) {
+                            if let Err(e) = self.bus.publish(output).await {
+                                tracing::error!(target: "PIPELINE", "Failed to publish stage output: {}", e);
+                            }
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "PIPELINE", "{}", BusError::lagged::<In>(n)),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
+
+/// ## `Pipeline`
+///
+/// 流水线构建器。类型参数 `Last` 记录最后一级的输出类型，
+/// `stage::<In, Out>` 只有在 `In` 与 `Last` 相同时才能通过编译。
+pub struct Pipeline<Last> {
+    bus: MessageBus,
+    stages: Vec<Arc<dyn Actor>>,
+    _last: PhantomData<fn() -> Last>,
+}
+
+impl Pipeline<Start> {
+    pub fn new(bus: MessageBus) -> Self {
+        Self { bus, stages: Vec::new(), _last: PhantomData }
+    }
+}
+
+impl<Last> Pipeline<Last> {
+    /// 追加一级，输入类型必须与上一级的输出类型一致。
+    pub fn stage<In, Out>(mut self, transform: impl Fn(In) -> Option<Out> + Send + Sync + 'static) -> Pipeline<Out>
+    where
+        In: Message,
+        Out: Message,
+        Last: Connects<In>,
+    {
+        self.stages.push(Arc::new(PipelineActor::new(self.bus.clone(), transform)));
+        Pipeline { bus: self.bus, stages: self.stages, _last: PhantomData }
+    }
+
+    /// 启动所有阶段并返回它们的任务句柄。
+    /// 下游阶段先启动，保证上游发布第一条消息时下游已完成订阅。
+    pub async fn start(self) -> Vec<JoinHandle<()>> {
+        let mut handles = Vec::new();
+        for stage in self.stages.into_iter().rev() {
+            handles.extend(stage.start("PIPELINE").await);
+        }
+        handles
+    }
+}
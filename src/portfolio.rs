@@ -0,0 +1,359 @@
+// src/portfolio.rs
+
+//! # 组合模块 (portfolio)
+//!
+//! 根据成交回报维护每个 symbol 的持仓、均价和盈亏，是 `FillEvent` 的消费者。
+//! 持仓状态 `Portfolio` 只由成交折叠而来（`EventState<FillEvent>`），
+//! 可以交给 `EventSourcingActor<FillEvent, Portfolio>` 在崩溃后从快照与成交记录重建。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::clock::{Clock, LiveClock};
+use crate::event_sourcing::EventState;
+use crate::message::{Bar, FillEvent, OrderSide, PortfolioSnapshot, PositionUpdate, Quote};
+use crate::startup::StartupBarrierHandle;
+use crate::trace;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// ## `Position`
+///
+/// 单个 symbol 的持仓状态。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub symbol: String,
+    /// 净持仓，多头为正、空头为负。
+    pub quantity: f64,
+    /// 当前持仓的平均开仓价格。
+    pub avg_price: f64,
+    /// 已实现盈亏（不含手续费）。
+    pub realized_pnl: f64,
+    /// 累计支付的手续费。
+    pub commission: f64,
+    /// 最近的成交价或收盘价，没有报价时用于估值。
+    pub last_price: Option<f64>,
+    /// 最近一次 `Quote` 的买价。
+    #[serde(default)]
+    pub bid: Option<f64>,
+    /// 最近一次 `Quote` 的卖价。
+    #[serde(default)]
+    pub ask: Option<f64>,
+}
+
+impl Position {
+    fn new(symbol: &str) -> Self {
+        Self { symbol: symbol.to_string(), ..Default::default() }
+    }
+
+    /// 将一笔成交计入持仓，正确处理加仓、减仓和反手。
+    pub fn apply_fill(&mut self, fill: &FillEvent) {
+        let signed_qty = match fill.side {
+            OrderSide::Buy => fill.quantity,
+            OrderSide::Sell => -fill.quantity,
+        };
+        self.commission += fill.commission;
+
+        if self.quantity == 0.0 || self.quantity.signum() == signed_qty.signum() {
+            // 开仓或加仓：更新加权平均价
+            let new_qty = self.quantity + signed_qty;
+            self.avg_price = (self.avg_price * self.quantity.abs() + fill.price * signed_qty.abs()) / new_qty.abs();
+            self.quantity = new_qty;
+        } else {
+            // 减仓：按平均价结算已实现盈亏，超出部分视为反向开仓
+            let closed_qty = signed_qty.abs().min(self.quantity.abs());
+            self.realized_pnl += (fill.price - self.avg_price) * closed_qty * self.quantity.signum();
+            let new_qty = self.quantity + signed_qty;
+            if new_qty == 0.0 {
+                self.avg_price = 0.0;
+            } else if new_qty.signum() != self.quantity.signum() {
+                self.avg_price = fill.price;
+            }
+            self.quantity = new_qty;
+        }
+        self.last_price = Some(fill.price);
+    }
+
+    /// 估值价格：多头按买价、空头按卖价（平仓时实际能成交的价格），没有报价时按最近价格。
+    pub fn mark_price(&self) -> Option<f64> {
+        let quoted = if self.quantity > 0.0 {
+            self.bid
+        } else if self.quantity < 0.0 {
+            self.ask
+        } else {
+            None
+        };
+        quoted.or(self.last_price)
+    }
+
+    /// 按估值价格（见 `mark_price`）计算的未实现盈亏。
+    pub fn unrealized_pnl(&self) -> f64 {
+        match self.mark_price() {
+            Some(price) => (price - self.avg_price) * self.quantity,
+            None => 0.0,
+        }
+    }
+
+    /// 净盈亏 = 已实现 + 未实现 - 手续费。
+    pub fn net_pnl(&self) -> f64 {
+        self.realized_pnl + self.unrealized_pnl() - self.commission
+    }
+}
+
+/// ## `Portfolio`
+///
+/// 所有 symbol 的持仓。成交通过 `apply_fill`（即 `EventState::apply`）计入，行情与报价只更新估值价格。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Portfolio {
+    positions: HashMap<String, Position>,
+    /// 每个 symbol 最近一次报价的（买价，卖价），新开的持仓从这里取得报价。
+    #[serde(default)]
+    quotes: HashMap<String, (f64, f64)>,
+}
+
+impl Portfolio {
+    /// 将一笔成交计入对应 symbol 的持仓，返回更新后的持仓。
+    pub fn apply_fill(&mut self, fill: &FillEvent) -> &Position {
+        let quote = self.quotes.get(&fill.symbol).copied();
+        let position = self.positions.entry(fill.symbol.clone()).or_insert_with(|| Position {
+            bid: quote.map(|(bid, _)| bid),
+            ask: quote.map(|(_, ask)| ask),
+            ..Position::new(&fill.symbol)
+        });
+        position.apply_fill(fill);
+        position
+    }
+
+    /// 以收盘价对已有持仓估值。
+    pub fn mark(&mut self, bar: &Bar) {
+        if let Some(position) = self.positions.get_mut(&bar.symbol) {
+            position.last_price = Some(bar.close);
+        }
+    }
+
+    /// 记录 symbol 的最新报价，此后该 symbol 的多头按买价、空头按卖价估值（包括之后才开的持仓）。
+    pub fn mark_quote(&mut self, quote: &Quote) {
+        self.quotes.insert(quote.symbol.clone(), (quote.bid, quote.ask));
+        if let Some(position) = self.positions.get_mut(&quote.symbol) {
+            position.bid = Some(quote.bid);
+            position.ask = Some(quote.ask);
+        }
+    }
+
+    /// 某个 symbol 的持仓。
+    pub fn position(&self, symbol: &str) -> Option<&Position> {
+        self.positions.get(symbol)
+    }
+
+    /// 所有 symbol 的持仓，按 symbol 排序。
+    pub fn positions(&self) -> Vec<Position> {
+        let mut positions: Vec<_> = self.positions.values().cloned().collect();
+        positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        positions
+    }
+
+    /// 所有持仓的净盈亏之和。
+    pub fn net_pnl(&self) -> f64 {
+        self.positions.values().map(Position::net_pnl).sum()
+    }
+
+    /// 所有持仓累计支付的手续费。
+    pub fn total_commission(&self) -> f64 {
+        self.positions.values().map(|p| p.commission).sum()
+    }
+
+    /// 所有持仓的已实现盈亏之和（不含手续费）。
+    pub fn realized_pnl(&self) -> f64 {
+        self.positions.values().map(|p| p.realized_pnl).sum()
+    }
+}
+
+impl EventState<FillEvent> for Portfolio {
+    fn apply(&mut self, fill: &FillEvent) {
+        self.apply_fill(fill);
+    }
+}
+
+/// ## `PortfolioTracker`
+///
+/// 一个 Actor，维护所有 symbol 的持仓。
+/// - 消费 `FillEvent` 消息来更新持仓与盈亏（手续费计入净盈亏）。
+/// - 消费 `Bar` 消息，以收盘价对持仓估值；消费 `Quote` 消息，收到报价后多头按买价、空头按卖价估值，
+///   比按最近成交价估值更接近实际可以平仓的价值。
+/// - 每当一个订单成交完成（`is_final`）导致持仓变化时，生产 `PositionUpdate` 消息，
+///   同一订单的部分成交被汇总为一次更新。
+/// - 设置 `with_initial_capital` 后，每次估值与每次成交后生产 `PortfolioSnapshot` 消息，
+///   总权益为初始资金加净盈亏。
+/// - 启用 `with_fill_replay` 后，启动时从总线的消息存储重放错过的成交来重建持仓；
+///   重放的成交只更新持仓，不发布 `PositionUpdate`。
+pub struct PortfolioTracker {
+    bus: MessageBus,
+    portfolio: Mutex<Portfolio>,
+    clock: Arc<dyn Clock>,
+    /// 启动时重放事件时间不早于该值的成交。
+    replay_from: Option<u64>,
+    /// 设置后发布 `PortfolioSnapshot`。
+    initial_capital: Option<f64>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl PortfolioTracker {
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            portfolio: Mutex::new(Portfolio::default()),
+            clock: Arc::new(LiveClock),
+            replay_from: None,
+            initial_capital: None,
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 启动时重放事件时间不早于 `from_ts` 的成交，用于中途重启后追赶持仓。
+    /// 总线需要通过 `with_message_store::<FillEvent>` 保留成交。
+    pub fn with_fill_replay(mut self, from_ts: u64) -> Self {
+        self.replay_from = Some(from_ts);
+        self
+    }
+
+    /// 设置初始资金，并在每次估值与成交后发布 `PortfolioSnapshot`。
+    pub fn with_initial_capital(mut self, capital: f64) -> Self {
+        self.initial_capital = Some(capital);
+        self
+    }
+
+    /// 设置 `PositionUpdate` 与 `PortfolioSnapshot` 时间戳的时间来源。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 查询某个 symbol 的持仓快照。
+    pub fn position(&self, symbol: &str) -> Option<Position> {
+        self.portfolio.lock().unwrap().position(symbol).cloned()
+    }
+
+    /// 所有 symbol 的持仓快照，按 symbol 排序。
+    pub fn positions(&self) -> Vec<Position> {
+        self.portfolio.lock().unwrap().positions()
+    }
+
+    /// 整个组合的快照。
+    pub fn portfolio(&self) -> Portfolio {
+        self.portfolio.lock().unwrap().clone()
+    }
+
+    /// 所有持仓的净盈亏之和。
+    pub fn net_pnl(&self) -> f64 {
+        self.portfolio.lock().unwrap().net_pnl()
+    }
+
+    /// 所有持仓累计支付的手续费。
+    pub fn total_commission(&self) -> f64 {
+        self.portfolio.lock().unwrap().total_commission()
+    }
+
+    async fn handle_fill(&self, fill: FillEvent, is_replay: bool) {
+        let _timer = self.bus.actor_metrics().start_timer("PORTFOLIO");
+        let update = {
+            let mut portfolio = self.portfolio.lock().unwrap();
+            let position = portfolio.apply_fill(&fill);
+            info!(target: "PORTFOLIO", "{} position {} @ {:.4}, net PnL {:.4}", position.symbol, position.quantity, position.avg_price, position.net_pnl());
+            fill.is_final.then(|| PositionUpdate {
+                symbol: position.symbol.clone(),
+                net_position: position.quantity,
+                avg_price: position.avg_price,
+                ts: self.clock.now_nanos(),
+            })
+        };
+
+        // 重放的成交已经发布过持仓更新
+        if is_replay {
+            return;
+        }
+        if let Some(update) = update {
+            if let Err(e) = self.bus.publish(update).await {
+                tracing::error!(target: "PORTFOLIO", "Failed to publish position update: {}", e);
+            }
+        }
+        self.publish_snapshot().await;
+    }
+
+    async fn handle_bar(&self, bar: Bar) {
+        let _timer = self.bus.actor_metrics().start_timer("PORTFOLIO");
+        self.portfolio.lock().unwrap().mark(&bar);
+        self.publish_snapshot().await;
+    }
+
+    async fn handle_quote(&self, quote: Quote) {
+        let _timer = self.bus.actor_metrics().start_timer("PORTFOLIO");
+        self.portfolio.lock().unwrap().mark_quote(&quote);
+        self.publish_snapshot().await;
+    }
+
+    /// 设置了初始资金时发布当前的 `PortfolioSnapshot`。
+    async fn publish_snapshot(&self) {
+        let Some(capital) = self.initial_capital else {
+            return;
+        };
+        let net_pnl = self.net_pnl();
+        let snapshot = PortfolioSnapshot { total_equity: capital + net_pnl, net_pnl, ts: self.clock.now_nanos() };
+        if let Err(e) = self.bus.publish(snapshot).await {
+            tracing::error!(target: "PORTFOLIO", "Failed to publish portfolio snapshot: {}", e);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for PortfolioTracker {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut fill_rx = self.bus.subscribe_handle::<FillEvent>().await;
+        let mut bar_rx = self.bus.subscribe::<Bar>().await;
+        let mut quote_rx = self.bus.subscribe::<Quote>().await;
+        if let Some(from_ts) = self.replay_from {
+            match self.bus.replay_from_store(&fill_rx, from_ts) {
+                Ok(n) => info!(target: "PORTFOLIO", "Replaying {} fills since {}", n, from_ts),
+                Err(e) => tracing::warn!(target: "PORTFOLIO", "Cannot replay fills: {}", e),
+            }
+        }
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                tokio::select! {
+                    result = fill_rx.recv() => match result {
+                        Ok(envelope) => {
+                            let trace = envelope.trace.map(|trace| trace.child::<FillEvent>("PORTFOLIO"));
+                            trace::in_context(trace, self.handle_fill(envelope.message, envelope.is_replay)).await
+                        },
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "PORTFOLIO", "{}", BusError::lagged::<FillEvent>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = bar_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("PORTFOLIO", |bar| self.handle_bar(bar)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "PORTFOLIO", "{}", BusError::lagged::<Bar>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = quote_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("PORTFOLIO", |quote| self.handle_quote(quote)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "PORTFOLIO", "{}", BusError::lagged::<Quote>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
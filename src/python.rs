@@ -0,0 +1,484 @@
+// src/python.rs
+
+//! # Python 绑定模块 (python)
+//!
+//! 让 Python 编写的策略接入总线（需启用 `python` feature，扩展模块由 `python/` 下的 cdylib 导出为 `message_bus_py`）：
+//!
+//! - `MessageBus`：持有自己的 tokio 运行时的总线句柄，可以启动模拟撮合、登记策略、发布行情与订单；
+//! - `Bar` / `OrderRequest` / `FillEvent`：对应消息的 Python 类；
+//! - `PythonStrategy`：把带有 `on_bar(bar)` / `on_fill(fill)` 方法的 Python 对象包装为 Actor。
+//!
+//! Python 回调只在每个策略专用的线程中持有 GIL 执行，异步任务通过有界队列把消息交给该线程：
+//! 队列满时异步任务等待（背压），运行时的工作线程永远不会阻塞在 GIL 上。
+//! 从 Python 发布消息时释放 GIL，`shutdown` 在释放 GIL 后等待回调线程退出，回调线程在退出前释放对策略对象的引用。
+
+// pyo3 0.22 的 `#[pymethods]` 展开对返回 `PyResult` 的方法会触发该 lint
+#![allow(clippy::useless_conversion)]
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::execution::SimulatedExecutionEngine;
+use crate::message::{Bar, FillEvent, OrderRequest, OrderSide, OrderType, DEFAULT_BAR_TIMEFRAME};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// 每个策略回调队列的默认长度。
+const DEFAULT_QUEUE_SIZE: usize = 1024;
+
+/// `shutdown` 等待运行时中剩余任务结束的时间。
+const RUNTIME_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn bus_error(e: BusError) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+fn side_name(side: &OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    }
+}
+
+/// ## `PyBar`
+///
+/// Python 中的 `Bar`。
+#[pyclass(name = "Bar", module = "message_bus_py", frozen)]
+#[derive(Clone)]
+pub struct PyBar {
+    inner: Bar,
+}
+
+#[pymethods]
+impl PyBar {
+    #[new]
+    #[pyo3(signature = (symbol, open, high, low, close, volume = 0.0, ts_event = 0))]
+    fn new(symbol: String, open: f64, high: f64, low: f64, close: f64, volume: f64, ts_event: u64) -> Self {
+        let inner = Bar { id: Uuid::new_v4(), ts_event, symbol, open, high, low, close, volume, timeframe: DEFAULT_BAR_TIMEFRAME };
+        Self { inner }
+    }
+
+    #[getter]
+    fn symbol(&self) -> &str {
+        &self.inner.symbol
+    }
+
+    #[getter]
+    fn open(&self) -> f64 {
+        self.inner.open
+    }
+
+    #[getter]
+    fn high(&self) -> f64 {
+        self.inner.high
+    }
+
+    #[getter]
+    fn low(&self) -> f64 {
+        self.inner.low
+    }
+
+    #[getter]
+    fn close(&self) -> f64 {
+        self.inner.close
+    }
+
+    #[getter]
+    fn volume(&self) -> f64 {
+        self.inner.volume
+    }
+
+    #[getter]
+    fn ts_event(&self) -> u64 {
+        self.inner.ts_event
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Bar(symbol={:?}, close={}, ts_event={})", self.inner.symbol, self.inner.close, self.inner.ts_event)
+    }
+}
+
+/// ## `PyOrderRequest`
+///
+/// Python 中的 `OrderRequest`。`side` 为 `"buy"` / `"sell"`，`order_type` 为 `"market"` / `"limit"`。
+#[pyclass(name = "OrderRequest", module = "message_bus_py", frozen)]
+#[derive(Clone)]
+pub struct PyOrderRequest {
+    inner: OrderRequest,
+}
+
+#[pymethods]
+impl PyOrderRequest {
+    #[new]
+    #[pyo3(signature = (symbol, side, quantity, price, order_type = "market"))]
+    fn new(symbol: String, side: &str, quantity: f64, price: f64, order_type: &str) -> PyResult<Self> {
+        let side = match side.to_ascii_lowercase().as_str() {
+            "buy" => OrderSide::Buy,
+            "sell" => OrderSide::Sell,
+            other => return Err(PyValueError::new_err(format!("unknown side {:?}, expected \"buy\" or \"sell\"", other))),
+        };
+        let order_type = match order_type.to_ascii_lowercase().as_str() {
+            "market" => OrderType::Market,
+            "limit" => OrderType::Limit,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown order type {:?}, expected \"market\" or \"limit\"",
+                    other
+                )))
+            },
+        };
+        let inner =
+            OrderRequest { id: Uuid::new_v4(), symbol, side, order_type, price, quantity, trigger_price: None };
+        Ok(Self { inner })
+    }
+
+    #[getter]
+    fn id(&self) -> String {
+        self.inner.id.to_string()
+    }
+
+    #[getter]
+    fn symbol(&self) -> &str {
+        &self.inner.symbol
+    }
+
+    #[getter]
+    fn side(&self) -> &'static str {
+        side_name(&self.inner.side)
+    }
+
+    #[getter]
+    fn quantity(&self) -> f64 {
+        self.inner.quantity
+    }
+
+    #[getter]
+    fn price(&self) -> f64 {
+        self.inner.price
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "OrderRequest(symbol={:?}, side={:?}, quantity={}, price={})",
+            self.inner.symbol,
+            side_name(&self.inner.side),
+            self.inner.quantity,
+            self.inner.price
+        )
+    }
+}
+
+/// ## `PyFillEvent`
+///
+/// Python 中的 `FillEvent`，只读。
+#[pyclass(name = "FillEvent", module = "message_bus_py", frozen)]
+#[derive(Clone)]
+pub struct PyFillEvent {
+    inner: FillEvent,
+}
+
+#[pymethods]
+impl PyFillEvent {
+    #[getter]
+    fn order_id(&self) -> String {
+        self.inner.order_id.to_string()
+    }
+
+    #[getter]
+    fn symbol(&self) -> &str {
+        &self.inner.symbol
+    }
+
+    #[getter]
+    fn side(&self) -> &'static str {
+        side_name(&self.inner.side)
+    }
+
+    #[getter]
+    fn price(&self) -> f64 {
+        self.inner.price
+    }
+
+    #[getter]
+    fn quantity(&self) -> f64 {
+        self.inner.quantity
+    }
+
+    #[getter]
+    fn leaves_qty(&self) -> f64 {
+        self.inner.leaves_qty
+    }
+
+    #[getter]
+    fn is_final(&self) -> bool {
+        self.inner.is_final
+    }
+
+    #[getter]
+    fn commission(&self) -> f64 {
+        self.inner.commission
+    }
+
+    #[getter]
+    fn ts_event(&self) -> u64 {
+        self.inner.ts_event
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "FillEvent(symbol={:?}, side={:?}, quantity={}, price={})",
+            self.inner.symbol,
+            side_name(&self.inner.side),
+            self.inner.quantity,
+            self.inner.price
+        )
+    }
+}
+
+/// 交给回调线程的一条消息。
+enum Callback {
+    Bar(Bar),
+    Fill(FillEvent),
+}
+
+/// ## `PythonStrategy`
+///
+/// 一个 Actor，把 Python 策略对象接入总线。
+/// - 消费 `Bar` 与 `FillEvent` 消息，经有界队列交给专用线程，在该线程中持有 GIL 调用 `on_bar` / `on_fill`；
+///   对象没有对应方法时跳过该类消息。
+/// - 回调抛出的异常打印到 stderr 并记录日志，不会中断后续回调。
+/// - 转发任务结束（例如被中止）后回调线程处理完队列中剩余的消息，释放策略对象并退出，`join` 等待它退出。
+pub struct PythonStrategy {
+    bus: MessageBus,
+    strategy: Mutex<Option<Py<PyAny>>>,
+    queue_size: usize,
+    thread: Mutex<Option<std::thread::JoinHandle<()>>>,
+}
+
+impl PythonStrategy {
+    pub fn new(bus: MessageBus, strategy: Py<PyAny>) -> Self {
+        Self { bus, strategy: Mutex::new(Some(strategy)), queue_size: DEFAULT_QUEUE_SIZE, thread: Mutex::new(None) }
+    }
+
+    /// 回调队列的长度，队列满时转发任务等待 Python 处理。
+    pub fn with_queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = queue_size.max(1);
+        self
+    }
+
+    /// 等待回调线程退出。必须在不持有 GIL 时调用，且要先中止 `start` 返回的任务。
+    pub fn join(&self) {
+        if let Some(thread) = self.thread.lock().unwrap().take() {
+            if thread.join().is_err() {
+                tracing::error!(target: "PYTHON", "Python callback thread panicked");
+            }
+        }
+    }
+}
+
+/// 回调线程：逐条取出消息，在 GIL 中调用策略对象。
+fn callback_loop(strategy: Py<PyAny>, mut rx: mpsc::Receiver<Callback>) {
+    let (has_on_bar, has_on_fill) = Python::with_gil(|py| {
+        let strategy = strategy.bind(py);
+        (strategy.hasattr("on_bar").unwrap_or(false), strategy.hasattr("on_fill").unwrap_or(false))
+    });
+    while let Some(callback) = rx.blocking_recv() {
+        Python::with_gil(|py| {
+            let (method, result) = match callback {
+                Callback::Bar(bar) if has_on_bar => ("on_bar", strategy.call_method1(py, "on_bar", (PyBar { inner: bar },))),
+                Callback::Fill(fill) if has_on_fill => {
+                    ("on_fill", strategy.call_method1(py, "on_fill", (PyFillEvent { inner: fill },)))
+                },
+                _ => return,
+            };
+            if let Err(e) = result {
+                tracing::error!(target: "PYTHON", "{} raised: {}", method, e);
+                e.print(py);
+            }
+        });
+    }
+    // 在 GIL 中释放对策略对象的引用
+    Python::with_gil(|_py| drop(strategy));
+}
+
+#[async_trait::async_trait]
+impl Actor for PythonStrategy {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let Some(strategy) = self.strategy.lock().unwrap().take() else {
+            tracing::warn!(target: "PYTHON", "{} already started", actor_name);
+            return Vec::new();
+        };
+        let mut bar_rx = self.bus.subscribe::<Bar>().await;
+        let mut fill_rx = self.bus.subscribe::<FillEvent>().await;
+        let (tx, rx) = mpsc::channel(self.queue_size);
+
+        let thread = std::thread::Builder::new()
+            .name(format!("python-{}", actor_name))
+            .spawn(move || callback_loop(strategy, rx))
+            .expect("failed to spawn the Python callback thread");
+        *self.thread.lock().unwrap() = Some(thread);
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                let callback = tokio::select! {
+                    result = bar_rx.recv() => match result {
+                        Ok(bar) => Callback::Bar(bar),
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(target: "PYTHON", "{}", BusError::lagged::<Bar>(n));
+                            continue;
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = fill_rx.recv() => match result {
+                        Ok(fill) => Callback::Fill(fill),
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(target: "PYTHON", "{}", BusError::lagged::<FillEvent>(n));
+                            continue;
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                };
+                // 队列满时在这里等待，而不是阻塞工作线程
+                if tx.send(callback).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
+
+/// ## `PyMessageBus`
+///
+/// Python 中的 `MessageBus`：总线与运行它的 tokio 运行时。可以作为上下文管理器使用，退出时调用 `shutdown`。
+#[pyclass(name = "MessageBus", module = "message_bus_py", frozen)]
+pub struct PyMessageBus {
+    bus: MessageBus,
+    runtime: Mutex<Option<Runtime>>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+    strategies: Mutex<Vec<Arc<PythonStrategy>>>,
+}
+
+impl PyMessageBus {
+    /// 在总线的运行时上启动 Actor。
+    fn start_actor(&self, name: &str, actor: Arc<dyn Actor>) -> PyResult<()> {
+        let runtime = self.runtime.lock().unwrap();
+        let runtime = runtime.as_ref().ok_or_else(|| PyRuntimeError::new_err("bus is shut down"))?;
+        let handles = runtime.block_on(actor.start(name));
+        self.tasks.lock().unwrap().extend(handles);
+        Ok(())
+    }
+
+    fn ensure_open(&self) -> PyResult<()> {
+        match self.runtime.lock().unwrap().is_some() {
+            true => Ok(()),
+            false => Err(PyRuntimeError::new_err("bus is shut down")),
+        }
+    }
+
+    /// 中止所有任务，等待回调线程退出后关闭运行时。不持有 GIL 时调用。
+    fn stop(&self) {
+        let Some(runtime) = self.runtime.lock().unwrap().take() else {
+            return;
+        };
+        for handle in self.tasks.lock().unwrap().drain(..) {
+            handle.abort();
+        }
+        for strategy in self.strategies.lock().unwrap().drain(..) {
+            strategy.join();
+        }
+        runtime.shutdown_timeout(RUNTIME_SHUTDOWN_TIMEOUT);
+    }
+}
+
+#[pymethods]
+impl PyMessageBus {
+    #[new]
+    #[pyo3(signature = (capacity = 1024))]
+    fn new(capacity: usize) -> PyResult<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("message-bus")
+            .build()
+            .map_err(|e| PyRuntimeError::new_err(format!("failed to build the tokio runtime: {}", e)))?;
+        let bus = MessageBus::new(capacity).with_runtime(runtime.handle().clone());
+        Ok(Self {
+            bus,
+            runtime: Mutex::new(Some(runtime)),
+            tasks: Mutex::new(Vec::new()),
+            strategies: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// 启动模拟撮合引擎：订单按参考价成交并发布 `FillEvent`。
+    fn start_simulated_execution(&self) -> PyResult<()> {
+        self.start_actor("EXECUTION", Arc::new(SimulatedExecutionEngine::new(self.bus.clone())))
+    }
+
+    /// 登记 Python 策略对象，它的 `on_bar(bar)` / `on_fill(fill)` 在专用线程中被调用。
+    #[pyo3(signature = (strategy, name = "PYTHON", queue_size = DEFAULT_QUEUE_SIZE))]
+    fn register_strategy(&self, strategy: Py<PyAny>, name: &str, queue_size: usize) -> PyResult<()> {
+        let actor = Arc::new(PythonStrategy::new(self.bus.clone(), strategy).with_queue_size(queue_size));
+        self.start_actor(name, actor.clone())?;
+        self.strategies.lock().unwrap().push(actor);
+        Ok(())
+    }
+
+    /// 发布一根 `Bar`，返回收到它的订阅者数量。总线关闭后抛出 `RuntimeError`。
+    fn publish_bar(&self, py: Python<'_>, bar: PyRef<'_, PyBar>) -> PyResult<usize> {
+        self.ensure_open()?;
+        let bar = bar.inner.clone();
+        py.allow_threads(|| self.bus.blocking_publish(bar)).map_err(bus_error)
+    }
+
+    /// 发布一个下单请求，返回收到它的订阅者数量。可以在策略回调中调用。
+    fn publish_order(&self, py: Python<'_>, order: PyRef<'_, PyOrderRequest>) -> PyResult<usize> {
+        self.ensure_open()?;
+        let order = order.inner.clone();
+        py.allow_threads(|| self.bus.blocking_publish(order)).map_err(bus_error)
+    }
+
+    /// 停止所有 Actor 与回调线程并释放对策略对象的引用。重复调用无效果。
+    fn shutdown(&self, py: Python<'_>) {
+        py.allow_threads(|| self.stop());
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&self, py: Python<'_>, _args: &Bound<'_, pyo3::types::PyTuple>) -> bool {
+        self.shutdown(py);
+        false
+    }
+}
+
+impl Drop for PyMessageBus {
+    /// 未调用 `shutdown` 就被回收时，在持有 GIL 的情况下不能等待回调线程，只中止任务并在后台关闭运行时；
+    /// 回调线程随后自行退出并释放策略对象。
+    fn drop(&mut self) {
+        for handle in self.tasks.get_mut().unwrap().drain(..) {
+            handle.abort();
+        }
+        if let Some(runtime) = self.runtime.get_mut().unwrap().take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// 把所有类登记到扩展模块 `m`。
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyMessageBus>()?;
+    m.add_class::<PyBar>()?;
+    m.add_class::<PyOrderRequest>()?;
+    m.add_class::<PyFillEvent>()?;
+    Ok(())
+}
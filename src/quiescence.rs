@@ -0,0 +1,126 @@
+// src/quiescence.rs
+
+//! # 静止检测模块 (quiescence)
+//!
+//! 有限的工作负载（回放一段行情、跑完一个数据文件）结束后，总线上不再有新消息。
+//! `QuiescenceMonitor` 周期地读取总线的按类型发布计数（`MessageBus::type_metrics`），
+//! 数据源全部结束且连续 `quiet_period` 没有新的发布时发出停止信号，代替固定时长的运行计时器。
+//! 按周期发布的报告类消息（例如 `LatencyReport`）可以用 `ignore` 排除，否则总线永远不会静止。
+
+use crate::actor::{spawn_named, Actor, ShutdownToken};
+use crate::bus::MessageBus;
+use crate::message::Message;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::{AbortHandle, JoinHandle};
+use tokio::time::Instant;
+
+/// 默认的采样间隔。
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// ## `QuiescenceMonitor`
+///
+/// 总线静止时发出停止信号。静止指：`with_source` 登记的数据源任务全部结束，
+/// 并且除 `ignore` 排除的类型之外，连续 `quiet_period` 没有发布任何消息。
+/// 判断基于采样，实际停止的时刻比最后一条消息晚 `quiet_period` 到 `quiet_period + poll_interval`。
+///
+/// 作为 Actor 启动时，静止后调用 `shutdown_token()` 的 `shutdown`；也可以直接等待 `wait_until_quiet`。
+pub struct QuiescenceMonitor {
+    bus: MessageBus,
+    quiet_period: Duration,
+    poll_interval: Duration,
+    /// 不计入活动的消息类型名（`std::any::type_name`）。
+    ignored: HashSet<&'static str>,
+    sources: Vec<AbortHandle>,
+    shutdown: ShutdownToken,
+}
+
+impl QuiescenceMonitor {
+    pub fn new(bus: MessageBus, quiet_period: Duration) -> Self {
+        Self {
+            bus,
+            quiet_period,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            ignored: HashSet::new(),
+            sources: Vec::new(),
+            shutdown: ShutdownToken::new(),
+        }
+    }
+
+    /// 设置采样间隔，默认 `DEFAULT_POLL_INTERVAL`。
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// 登记一个数据源任务，它结束之前总线不算静止。
+    pub fn with_source(mut self, source: AbortHandle) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// 静止时发出给定的停止信号，而不是自己创建的信号。
+    pub fn with_shutdown(mut self, shutdown: ShutdownToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    /// 发布 `M` 不算作活动，用于按周期发布的报告与心跳。
+    pub fn ignore<M: Message>(mut self) -> Self {
+        self.ignored.insert(std::any::type_name::<M>());
+        self
+    }
+
+    /// 静止时发出的停止信号。
+    pub fn shutdown_token(&self) -> ShutdownToken {
+        self.shutdown.clone()
+    }
+
+    /// 未被排除的类型的发布总数。
+    fn activity(&self) -> u64 {
+        self.bus
+            .type_metrics()
+            .into_iter()
+            .filter(|(type_name, _)| !self.ignored.contains(type_name))
+            .map(|(_, metrics)| metrics.published)
+            .sum()
+    }
+
+    /// 等待总线静止，返回最后一次观察到发布到判定静止之间的时长。
+    pub async fn wait_until_quiet(&self) -> Duration {
+        let mut interval = tokio::time::interval(self.poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut last = self.activity();
+        let mut last_change = Instant::now();
+        loop {
+            interval.tick().await;
+            let current = self.activity();
+            if current != last {
+                last = current;
+                last_change = Instant::now();
+                continue;
+            }
+            let quiet_for = last_change.elapsed();
+            if quiet_for >= self.quiet_period && self.sources.iter().all(AbortHandle::is_finished) {
+                return quiet_for;
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for QuiescenceMonitor {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let handle = spawn_named(actor_name, async move {
+            tokio::select! {
+                quiet_for = self.wait_until_quiet() => {
+                    tracing::info!(target: "QUIESCENCE", "No messages published for {:?}, shutting down", quiet_for);
+                    self.shutdown.shutdown();
+                },
+                _ = self.shutdown.cancelled() => {},
+            }
+        });
+        vec![handle]
+    }
+}
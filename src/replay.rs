@@ -0,0 +1,577 @@
+// src/replay.rs
+
+//! # 日志回放模块 (replay)
+//!
+//! 读取 `EventExporter` 写出的事件日志（JSON lines，每行带 `type` 与 `ts_event`），
+//! 按类型与时间范围筛选后，以给定速度重新发布到本地 `MessageBus`，或以同样的 JSON lines 发送到 TCP 连接。
+//! 命令行工具见 `src/bin/replay.rs`。
+//!
+//! 日志可能被轮转为多个分段：`events.jsonl` 的旧分段是同目录下的 `events.<序号或日期>.jsonl`，
+//! 回放时按 序号/日期 的顺序先读旧分段，最后读当前文件。写入进程被中断时最后一个分段可能以半行结尾，
+//! 这一行会被跳过并在统计中标记，而不是让整个回放失败。
+//!
+//! 调试策略时可以通过 `Replayer::control` 取得的 `ReplayControl` 在回放过程中暂停、逐条前进与调整速度，
+//! 暂停期间检查策略与组合的状态。
+
+use crate::bus::{BusError, MessageBus};
+use crate::message::{
+    Bar, CancelOrderRequest, CancelRejected, FillEvent, FlattenAll, OpenOrdersQuery, OpenOrdersReport, OrderAccepted, OrderBookDelta,
+    OrderBookSnapshot, OrderCanceled, OrderComplete, OrderFlowMetric, OrderRejected, OrderRequest, OrderStatusChanged, OrderTriggered,
+    PortfolioSnapshot, PositionUpdate, Signal, StrategySignal, TradeTick, TradingHalted, TradingResumed, VwapUpdate,
+    WarmupComplete,
+};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+
+/// 默认的进度输出间隔。
+pub const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// ## `ReplaySpeed`
+///
+/// 回放速度。命令行写作 `realtime`、`unthrottled` 或 `Nx`（例如 `10x`、`0.5x`）。
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReplaySpeed {
+    /// 按录制时相邻事件的 `ts_event` 间隔发布。
+    Realtime,
+    /// 不等待，以最快速度发布。
+    #[default]
+    Unthrottled,
+    /// 以录制间隔的 `1 / N` 发布，即 N 倍速。
+    Multiple(f64),
+}
+
+impl ReplaySpeed {
+    /// 录制间隔 `elapsed_nanos` 在回放时对应的等待时间，`Unthrottled` 为 `None`。
+    fn scale(&self, elapsed_nanos: u64) -> Option<Duration> {
+        match self {
+            ReplaySpeed::Realtime => Some(Duration::from_nanos(elapsed_nanos)),
+            ReplaySpeed::Unthrottled => None,
+            ReplaySpeed::Multiple(factor) => Some(Duration::from_secs_f64(elapsed_nanos as f64 / 1e9 / factor)),
+        }
+    }
+}
+
+impl FromStr for ReplaySpeed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "realtime" => Ok(ReplaySpeed::Realtime),
+            "unthrottled" => Ok(ReplaySpeed::Unthrottled),
+            _ => match s.strip_suffix('x').and_then(|factor| factor.parse::<f64>().ok()) {
+                Some(factor) if factor.is_finite() && factor > 0.0 => Ok(ReplaySpeed::Multiple(factor)),
+                _ => Err(format!("invalid speed {:?}: expected realtime, unthrottled or a positive factor like 10x", s)),
+            },
+        }
+    }
+}
+
+/// ## `ReplayTarget`
+///
+/// 回放的去向。命令行写作 `local` 或 `tcp://host:port`。
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ReplayTarget {
+    /// 本地构造的 `MessageBus`。
+    #[default]
+    Local,
+    /// 连接到给定地址，逐行发送日志中的 JSON 行。
+    Tcp(String),
+}
+
+impl FromStr for ReplayTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "local" => Ok(ReplayTarget::Local),
+            _ => match s.strip_prefix("tcp://") {
+                Some(addr) if !addr.is_empty() => Ok(ReplayTarget::Tcp(addr.to_string())),
+                _ => Err(format!("invalid target {:?}: expected local or tcp://host:port", s)),
+            },
+        }
+    }
+}
+
+/// 回放时的错误。
+#[derive(Debug)]
+pub enum ReplayError {
+    /// 无法读取日志分段或写入 TCP 连接。
+    Io(PathBuf, io::Error),
+    /// 日志路径既不是文件，也没有任何轮转出的分段。
+    NotFound(PathBuf),
+    /// 发布到本地总线失败，例如类型未登记或字段不符。
+    Bus(BusError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            ReplayError::NotFound(path) => write!(f, "no journal segments found at {}", path.display()),
+            ReplayError::Bus(e) => write!(f, "failed to publish: {}", e),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReplayError::Io(_, e) => Some(e),
+            ReplayError::NotFound(_) => None,
+            ReplayError::Bus(e) => Some(e),
+        }
+    }
+}
+
+/// ## `JournalEvent`
+///
+/// 日志中的一行：类型名、事件时间与原始 JSON。
+#[derive(Clone, Debug, PartialEq)]
+pub struct JournalEvent {
+    pub type_name: String,
+    pub ts_event: u64,
+    /// 整行 JSON，包括 `type` 与 `ts_event` 字段。
+    pub json: String,
+}
+
+impl JournalEvent {
+    /// 解析一行；不是带字符串 `type` 与整数 `ts_event` 的 JSON 对象时返回 `None`。
+    fn parse(line: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(line).ok()?;
+        let type_name = value.get("type")?.as_str()?.to_string();
+        let ts_event = value.get("ts_event")?.as_u64()?;
+        Some(Self { type_name, ts_event, json: line.to_string() })
+    }
+}
+
+/// ## `ReplayFilter`
+///
+/// 选择回放哪些事件。`types` 为空时不按类型筛选；时间范围两端都包含在内。
+#[derive(Clone, Debug, Default)]
+pub struct ReplayFilter {
+    pub types: HashSet<String>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+impl ReplayFilter {
+    pub fn matches(&self, event: &JournalEvent) -> bool {
+        (self.types.is_empty() || self.types.contains(&event.type_name))
+            && self.from.is_none_or(|from| event.ts_event >= from)
+            && self.to.is_none_or(|to| event.ts_event <= to)
+    }
+}
+
+/// ## `ReplayStats`
+///
+/// 一次回放的结果。
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    /// 发布或发送的事件数。
+    pub replayed: u64,
+    /// 被筛选条件排除的事件数。
+    pub filtered: u64,
+    /// 无法解析而跳过的行数（不含末尾的半行）。
+    pub skipped: u64,
+    /// 最后一个分段是否以不完整的一行结尾。
+    pub truncated: bool,
+    /// 最后回放的事件时间。
+    pub last_ts: Option<u64>,
+}
+
+/// 日志 `path` 的所有分段，从旧到新。
+///
+/// 旧分段是同目录下形如 `stem.<label>.ext` 的文件：序号按数值排序，日期按字典序排序；当前文件排在最后。
+pub fn journal_segments(path: &Path) -> Result<Vec<PathBuf>, ReplayError> {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let prefix = format!("{}.", stem);
+
+    let mut rotated = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(&dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(label) = name.strip_prefix(&prefix).and_then(|rest| rest.strip_suffix(&ext)) else { continue };
+            if label.is_empty() || !is_rotation_label(label) {
+                continue;
+            }
+            rotated.push((label.parse::<u64>().ok(), label.to_string(), entry.path()));
+        }
+    }
+    rotated.sort();
+    let mut segments: Vec<PathBuf> = rotated.into_iter().map(|(_, _, path)| path).collect();
+    if path.is_file() {
+        segments.push(path.to_path_buf());
+    }
+    if segments.is_empty() {
+        return Err(ReplayError::NotFound(path.to_path_buf()));
+    }
+    Ok(segments)
+}
+
+/// `EventExporter` 轮转使用的标签：序号 `N`，或日期 `YYYY-MM-DD`（同一天重名时为 `YYYY-MM-DD.N`）。
+fn is_rotation_label(label: &str) -> bool {
+    label.chars().all(|c| c.is_ascii_digit() || c == '-' || c == '.') && label.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// ## `JournalReader`
+///
+/// 按顺序逐行读取日志的所有分段。无法解析的行被跳过；
+/// 最后一个分段末尾没有换行符且无法解析的一行视为写入中断留下的半行。
+pub struct JournalReader {
+    segments: Vec<PathBuf>,
+    current: Option<(PathBuf, BufReader<File>)>,
+    next_segment: usize,
+    line: String,
+    skipped: u64,
+    truncated: bool,
+}
+
+impl JournalReader {
+    /// 打开日志 `path` 的所有分段（见 `journal_segments`）。
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Ok(Self::from_segments(journal_segments(path.as_ref())?))
+    }
+
+    /// 按给定顺序读取 `segments`。
+    pub fn from_segments(segments: Vec<PathBuf>) -> Self {
+        Self { segments, current: None, next_segment: 0, line: String::new(), skipped: 0, truncated: false }
+    }
+
+    /// 读取的分段。
+    pub fn segments(&self) -> &[PathBuf] {
+        &self.segments
+    }
+
+    /// 下一个事件，所有分段读完时返回 `None`。
+    pub fn next_event(&mut self) -> Result<Option<JournalEvent>, ReplayError> {
+        loop {
+            if self.current.is_none() {
+                let Some(path) = self.segments.get(self.next_segment).cloned() else { return Ok(None) };
+                self.next_segment += 1;
+                let file = File::open(&path).map_err(|e| ReplayError::Io(path.clone(), e))?;
+                self.current = Some((path, BufReader::new(file)));
+            }
+            let (path, reader) = self.current.as_mut().expect("segment opened above");
+            self.line.clear();
+            let read = reader.read_line(&mut self.line).map_err(|e| ReplayError::Io(path.clone(), e))?;
+            if read == 0 {
+                self.current = None;
+                continue;
+            }
+            let complete = self.line.ends_with('\n');
+            let line = self.line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(event) = JournalEvent::parse(line) {
+                return Ok(Some(event));
+            }
+            if !complete && self.next_segment == self.segments.len() {
+                tracing::warn!(target: "REPLAY", "{} ends with a truncated line, ignoring it", path.display());
+                self.truncated = true;
+            } else {
+                tracing::warn!(target: "REPLAY", "Skipping unparseable line in {}: {}", path.display(), line);
+                self.skipped += 1;
+            }
+        }
+    }
+}
+
+/// ## `ReplaySink` Trait
+///
+/// 回放事件的去向。
+#[async_trait::async_trait]
+pub trait ReplaySink: Send {
+    async fn send(&mut self, event: &JournalEvent) -> Result<(), ReplayError>;
+}
+
+/// 按类型名把事件发布到本地总线，类型需已通过 `register_message` 登记（见 `register_journal_types`）。
+#[async_trait::async_trait]
+impl ReplaySink for MessageBus {
+    async fn send(&mut self, event: &JournalEvent) -> Result<(), ReplayError> {
+        self.publish_json(&event.type_name, &event.json).await.map(|_| ()).map_err(ReplayError::Bus)
+    }
+}
+
+/// 把事件以 JSON lines 写到 TCP 连接。
+pub struct TcpSink {
+    addr: String,
+    stream: TcpStream,
+}
+
+impl TcpSink {
+    pub async fn connect(addr: &str) -> Result<Self, ReplayError> {
+        let stream = TcpStream::connect(addr).await.map_err(|e| ReplayError::Io(PathBuf::from(addr), e))?;
+        Ok(Self { addr: addr.to_string(), stream })
+    }
+}
+
+#[async_trait::async_trait]
+impl ReplaySink for TcpSink {
+    async fn send(&mut self, event: &JournalEvent) -> Result<(), ReplayError> {
+        let line = format!("{}\n", event.json);
+        self.stream.write_all(line.as_bytes()).await.map_err(|e| ReplayError::Io(PathBuf::from(&self.addr), e))
+    }
+}
+
+/// 在 `bus` 上以 `EventExporter::with_type` 使用的名称（类型名的最后一段，例如 `Bar`）登记所有内置消息类型，
+/// 使日志中的事件可以按 `type` 字段发布。
+pub fn register_journal_types(bus: &MessageBus) {
+    bus.register_message::<Bar>("Bar");
+    bus.register_message::<TradeTick>("TradeTick");
+    bus.register_message::<VwapUpdate>("VwapUpdate");
+    bus.register_message::<OrderFlowMetric>("OrderFlowMetric");
+    bus.register_message::<OrderBookSnapshot>("OrderBookSnapshot");
+    bus.register_message::<OrderBookDelta>("OrderBookDelta");
+    bus.register_message::<OrderRequest>("OrderRequest");
+    bus.register_message::<OrderTriggered>("OrderTriggered");
+    bus.register_message::<OrderAccepted>("OrderAccepted");
+    bus.register_message::<OrderRejected>("OrderRejected");
+    bus.register_message::<FillEvent>("FillEvent");
+    bus.register_message::<PositionUpdate>("PositionUpdate");
+    bus.register_message::<PortfolioSnapshot>("PortfolioSnapshot");
+    bus.register_message::<CancelOrderRequest>("CancelOrderRequest");
+    bus.register_message::<OrderCanceled>("OrderCanceled");
+    bus.register_message::<CancelRejected>("CancelRejected");
+    bus.register_message::<OrderStatusChanged>("OrderStatusChanged");
+    bus.register_message::<OrderComplete>("OrderComplete");
+    bus.register_message::<OpenOrdersQuery>("OpenOrdersQuery");
+    bus.register_message::<OpenOrdersReport>("OpenOrdersReport");
+    bus.register_message::<TradingHalted>("TradingHalted");
+    bus.register_message::<FlattenAll>("FlattenAll");
+    bus.register_message::<TradingResumed>("TradingResumed");
+    bus.register_message::<WarmupComplete>("WarmupComplete");
+    bus.register_message::<Signal>("Signal");
+    bus.register_message::<StrategySignal>("StrategySignal");
+}
+
+/// 回放循环检查控制状态的结果。
+enum Permit {
+    /// 未暂停，按给定速度发送；`resumed` 表示曾因暂停而等待。
+    Run { speed: ReplaySpeed, resumed: bool },
+    /// 暂停中消耗了一次 `step`。
+    Step,
+}
+
+/// `ReplayControl` 与回放循环共享的状态。
+#[derive(Clone, Copy, Debug)]
+struct ControlState {
+    paused: bool,
+    /// 暂停期间还可以放行的事件数。
+    steps: u64,
+    speed: ReplaySpeed,
+}
+
+/// ## `ReplayControl`
+///
+/// 回放的控制句柄，由 `Replayer::control` 取得，可以克隆后在其他任务中使用。
+/// 回放循环在每条事件发送之前检查控制状态；正在等待发送时间的事件也会立即响应暂停与速度变化。
+#[derive(Clone, Debug)]
+pub struct ReplayControl {
+    state: Arc<watch::Sender<ControlState>>,
+}
+
+impl ReplayControl {
+    fn new(speed: ReplaySpeed) -> Self {
+        Self { state: Arc::new(watch::Sender::new(ControlState { paused: false, steps: 0, speed })) }
+    }
+
+    /// 暂停：下一条事件在 `resume` 或 `step` 之前不会发送。已经发送的事件不受影响。
+    pub fn pause(&self) {
+        self.state.send_modify(|state| {
+            state.paused = true;
+            state.steps = 0;
+        });
+    }
+
+    /// 恢复回放。节奏从恢复的时刻重新计算，暂停的时长不会被追赶。
+    pub fn resume(&self) {
+        self.state.send_modify(|state| {
+            state.paused = false;
+            state.steps = 0;
+        });
+    }
+
+    /// 暂停时立即发送下一条事件（不按录制间隔等待），之后继续暂停。未暂停时不起作用。
+    pub fn step(&self) {
+        self.state.send_if_modified(|state| {
+            if state.paused {
+                state.steps += 1;
+            }
+            state.paused
+        });
+    }
+
+    /// 调整回放速度，之后的事件从当前时刻起按新速度计算节奏。
+    pub fn set_speed(&self, speed: ReplaySpeed) {
+        self.state.send_modify(|state| state.speed = speed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.borrow().paused
+    }
+
+    pub fn speed(&self) -> ReplaySpeed {
+        self.state.borrow().speed
+    }
+
+    /// 等到可以发送下一条事件：未暂停时立即返回，暂停时等待 `resume` 或消耗一次 `step`。
+    async fn permit(&self, rx: &mut watch::Receiver<ControlState>) -> Permit {
+        let mut resumed = false;
+        loop {
+            rx.borrow_and_update();
+            let mut permit = None;
+            self.state.send_if_modified(|state| {
+                if !state.paused {
+                    permit = Some(Permit::Run { speed: state.speed, resumed });
+                    false
+                } else if state.steps > 0 {
+                    state.steps -= 1;
+                    permit = Some(Permit::Step);
+                    true
+                } else {
+                    false
+                }
+            });
+            if let Some(permit) = permit {
+                return permit;
+            }
+            resumed = true;
+            // 发送端由自身持有，不会关闭
+            let _ = rx.changed().await;
+        }
+    }
+}
+
+/// ## `Replayer`
+///
+/// 从 `JournalReader` 读取事件，按 `ReplayFilter` 筛选，按 `ReplaySpeed` 控制节奏发送到 `ReplaySink`。
+/// 节奏以第一条被回放事件的 `ts_event` 为起点：第 k 条事件在起点之后 `(ts_k - ts_0) / 速度` 发送，
+/// 时间倒退的事件立即发送。每隔 `with_progress_interval` 以 INFO 级别输出已回放数量与当前回放时间。
+/// 暂停或改变速度之后，节奏以上一条被回放的事件与恢复的时刻为新的起点。
+pub struct Replayer {
+    reader: JournalReader,
+    filter: ReplayFilter,
+    control: ReplayControl,
+    progress_interval: Duration,
+}
+
+impl Replayer {
+    pub fn new(reader: JournalReader) -> Self {
+        Self {
+            reader,
+            filter: ReplayFilter::default(),
+            control: ReplayControl::new(ReplaySpeed::default()),
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+        }
+    }
+
+    /// 设置筛选条件，默认回放全部事件。
+    pub fn with_filter(mut self, filter: ReplayFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// 设置回放速度，默认 `Unthrottled`。
+    pub fn with_speed(self, speed: ReplaySpeed) -> Self {
+        self.control.set_speed(speed);
+        self
+    }
+
+    /// 设置进度输出间隔，默认 1 秒。
+    pub fn with_progress_interval(mut self, interval: Duration) -> Self {
+        self.progress_interval = interval;
+        self
+    }
+
+    /// 回放的控制句柄，在 `run` 之前取得，回放过程中用它暂停、逐条前进或调整速度。
+    pub fn control(&self) -> ReplayControl {
+        self.control.clone()
+    }
+
+    /// 回放全部事件。发送失败时立即返回错误。
+    pub async fn run(mut self, sink: &mut dyn ReplaySink) -> Result<ReplayStats, ReplayError> {
+        let mut stats = ReplayStats::default();
+        // 节奏的起点：（起点事件时间，起点时刻，计算节奏的速度）
+        let mut origin: Option<(u64, tokio::time::Instant, ReplaySpeed)> = None;
+        let mut control = self.control.state.subscribe();
+        let mut last_progress = Instant::now();
+        while let Some(event) = self.reader.next_event()? {
+            if !self.filter.matches(&event) {
+                stats.filtered += 1;
+                continue;
+            }
+            self.pace(&event, &mut origin, stats.last_ts, &mut control).await;
+            sink.send(&event).await?;
+            stats.replayed += 1;
+            stats.last_ts = Some(event.ts_event);
+            if last_progress.elapsed() >= self.progress_interval {
+                tracing::info!(target: "REPLAY", "Replayed {} events, at ts_event {}", stats.replayed, event.ts_event);
+                last_progress = Instant::now();
+            }
+        }
+        stats.skipped = self.reader.skipped;
+        stats.truncated = self.reader.truncated;
+        tracing::info!(
+            target: "REPLAY",
+            "Replay finished: {} events replayed, {} filtered out, {} unparseable lines skipped{}",
+            stats.replayed,
+            stats.filtered,
+            stats.skipped,
+            if stats.truncated { ", final segment truncated" } else { "" }
+        );
+        Ok(stats)
+    }
+
+    /// 等到 `event` 的发送时间。暂停或速度变化之后以上一条事件 `last_ts` 与当前时刻为新的起点。
+    async fn pace(
+        &self,
+        event: &JournalEvent,
+        origin: &mut Option<(u64, tokio::time::Instant, ReplaySpeed)>,
+        last_ts: Option<u64>,
+        control: &mut watch::Receiver<ControlState>,
+    ) {
+        loop {
+            let speed = match self.control.permit(control).await {
+                Permit::Run { speed, resumed } => {
+                    // 暂停期间的时长不计入节奏
+                    if resumed {
+                        *origin = None;
+                    }
+                    speed
+                },
+                // 逐条前进：立即发送，恢复后重新计算节奏
+                Permit::Step => {
+                    *origin = None;
+                    return;
+                },
+            };
+            let (first_ts, started, _) = match *origin {
+                Some(current) if current.2 == speed => current,
+                _ => *origin.insert((last_ts.unwrap_or(event.ts_event), tokio::time::Instant::now(), speed)),
+            };
+            let Some(delay) = speed.scale(event.ts_event.saturating_sub(first_ts)) else { return };
+            tokio::select! {
+                _ = tokio::time::sleep_until(started + delay) => return,
+                // 暂停或速度变化，重新检查
+                _ = control.changed() => {},
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+// src/rest.rs
+
+//! # REST 执行客户端模块 (rest)
+//!
+//! 通过 HTTP 接口把订单发送到真实交易场所的 `ExecutionClient` 骨架，需要启用 `rest` feature。
+//!
+//! 约定的场所接口（JSON）：
+//! - `POST {base_url}/orders`：提交订单，返回 `VenueOrderResponse`。
+//! - `DELETE {base_url}/orders/{client_order_id}`：撤单，未知订单返回 404。
+//! - `GET {base_url}/executions?since={ts}`：返回 `ts` 之后的执行回报列表。
+//!
+//! 每个请求都带有 `X-API-KEY`、`X-TIMESTAMP` 与 `X-SIGNATURE` 头，
+//! 签名为 `HMAC-SHA256(api_secret, timestamp + method + path + body)` 的十六进制形式。
+
+use crate::actor::spawn_named;
+use crate::client::{ExecAck, ExecError, ExecutionClient, ExecutionReport};
+use crate::clock::{Clock, LiveClock};
+use crate::message::{
+    CancelOrderRequest, FillEvent, Liquidity, OrderAccepted, OrderCanceled, OrderRejected, OrderRequest, OrderSide, OrderType,
+    RejectReason,
+};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// ## `RestConfig`
+///
+/// 交易场所的地址、凭证与成交轮询间隔。
+#[derive(Clone, Debug)]
+pub struct RestConfig {
+    pub base_url: String,
+    pub api_key: String,
+    pub api_secret: String,
+    pub poll_interval: Duration,
+}
+
+/// 提交给场所的订单。
+#[derive(Debug, Serialize)]
+struct VenueOrder<'a> {
+    client_order_id: Uuid,
+    symbol: &'a str,
+    side: &'static str,
+    #[serde(rename = "type")]
+    order_type: &'static str,
+    price: Option<f64>,
+    stop_price: Option<f64>,
+    quantity: f64,
+}
+
+/// 场所对提交订单的同步回复。
+#[derive(Debug, Deserialize)]
+struct VenueOrderResponse {
+    accepted: bool,
+    venue_order_id: Option<String>,
+    reject_reason: Option<String>,
+}
+
+/// 场所推送的一条执行回报。
+#[derive(Debug, Deserialize)]
+struct VenueExecution {
+    client_order_id: Uuid,
+    symbol: String,
+    /// `"BUY"` 或 `"SELL"`。
+    side: String,
+    /// `"ACCEPTED"`、`"FILL"`、`"REJECTED"` 或 `"CANCELED"`。
+    kind: String,
+    #[serde(default)]
+    price: f64,
+    #[serde(default)]
+    quantity: f64,
+    #[serde(default)]
+    leaves_qty: f64,
+    #[serde(default)]
+    commission: f64,
+    commission_currency: Option<String>,
+    /// `"MAKER"` 或 `"TAKER"`，缺省视为 `TAKER`。
+    liquidity: Option<String>,
+    fill_id: Option<String>,
+    ts: u64,
+    reason: Option<String>,
+}
+
+impl VenueExecution {
+    /// 转换为内部回报，无法识别的回报返回 `None`。
+    fn into_report(self) -> Option<ExecutionReport> {
+        let report = match self.kind.as_str() {
+            "ACCEPTED" => ExecutionReport::Accepted(OrderAccepted {
+                order_id: self.client_order_id,
+                symbol: self.symbol,
+                ts_event: self.ts,
+            }),
+            "FILL" => ExecutionReport::Fill(FillEvent {
+                order_id: self.client_order_id,
+                symbol: self.symbol,
+                side: match self.side.as_str() {
+                    "BUY" => OrderSide::Buy,
+                    "SELL" => OrderSide::Sell,
+                    _ => return None,
+                },
+                price: self.price,
+                quantity: self.quantity,
+                leaves_qty: self.leaves_qty,
+                is_final: self.leaves_qty <= 0.0,
+                commission: self.commission,
+                commission_currency: self.commission_currency.unwrap_or_default(),
+                liquidity: match self.liquidity.as_deref() {
+                    Some("MAKER") => Liquidity::Maker,
+                    _ => Liquidity::Taker,
+                },
+                ts_event: self.ts,
+                venue_fill_id: self.fill_id,
+                correlation_id: Some(self.client_order_id),
+            }),
+            "REJECTED" => ExecutionReport::Rejected(OrderRejected {
+                order_id: self.client_order_id,
+                symbol: self.symbol,
+                reason: RejectReason::VenueRejected,
+                detail: self.reason.unwrap_or_default(),
+            }),
+            "CANCELED" => ExecutionReport::Canceled(OrderCanceled {
+                order_id: self.client_order_id,
+                symbol: self.symbol,
+                remaining_qty: self.leaves_qty,
+            }),
+            _ => return None,
+        };
+        Some(report)
+    }
+}
+
+/// ## `RestExecutionClient`
+///
+/// 签名并发送 HTTP 请求的执行客户端，按 `poll_interval` 轮询执行回报。
+pub struct RestExecutionClient {
+    config: RestConfig,
+    http: reqwest::Client,
+    clock: Arc<dyn Clock>,
+    report_tx: mpsc::UnboundedSender<ExecutionReport>,
+    report_rx: tokio::sync::Mutex<mpsc::UnboundedReceiver<ExecutionReport>>,
+}
+
+impl RestExecutionClient {
+    pub fn new(config: RestConfig) -> Self {
+        let (report_tx, report_rx) = mpsc::unbounded_channel();
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            clock: Arc::new(LiveClock),
+            report_tx,
+            report_rx: tokio::sync::Mutex::new(report_rx),
+        }
+    }
+
+    /// 设置签名时间戳的时间来源。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 计算请求签名。
+    fn sign(&self, timestamp: u64, method: &str, path: &str, body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.config.api_secret.as_bytes()).expect("HMAC accepts any key length");
+        mac.update(format!("{}{}{}{}", timestamp, method, path, body).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// 构造一个已签名的请求。
+    fn request(&self, method: reqwest::Method, path: &str, body: Option<String>) -> reqwest::RequestBuilder {
+        let timestamp = self.clock.now_nanos();
+        let body = body.unwrap_or_default();
+        let signature = self.sign(timestamp, method.as_str(), path, &body);
+        let builder = self
+            .http
+            .request(method, format!("{}{}", self.config.base_url.trim_end_matches('/'), path))
+            .header("X-API-KEY", &self.config.api_key)
+            .header("X-TIMESTAMP", timestamp.to_string())
+            .header("X-SIGNATURE", signature);
+        if body.is_empty() {
+            builder
+        } else {
+            builder.header(reqwest::header::CONTENT_TYPE, "application/json").body(body)
+        }
+    }
+
+    /// 拉取 `since` 之后的执行回报。
+    async fn poll_executions(&self, since: u64) -> Result<Vec<VenueExecution>, reqwest::Error> {
+        let path = format!("/executions?since={}", since);
+        self.request(reqwest::Method::GET, &path, None).send().await?.error_for_status()?.json().await
+    }
+}
+
+fn transport<E: std::error::Error + Send + Sync + 'static>(e: E) -> ExecError {
+    ExecError::Transport(Box::new(e))
+}
+
+#[async_trait::async_trait]
+impl ExecutionClient for RestExecutionClient {
+    /// 启动执行回报的轮询任务。
+    async fn connect(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let handle = spawn_named(actor_name, async move {
+            let mut cursor = self.clock.now_nanos();
+            let mut interval = tokio::time::interval(self.config.poll_interval);
+            loop {
+                interval.tick().await;
+                let executions = match self.poll_executions(cursor).await {
+                    Ok(executions) => executions,
+                    Err(e) => {
+                        tracing::warn!(target: "REST", "Polling executions failed: {}", e);
+                        continue;
+                    }
+                };
+                for execution in executions {
+                    cursor = cursor.max(execution.ts);
+                    let kind = execution.kind.clone();
+                    match execution.into_report() {
+                        Some(report) => {
+                            if self.report_tx.send(report).is_err() {
+                                return;
+                            }
+                        }
+                        None => tracing::warn!(target: "REST", "Ignoring unrecognized execution kind {}", kind),
+                    }
+                }
+            }
+        });
+        vec![handle]
+    }
+
+    async fn submit(&self, order: OrderRequest) -> Result<ExecAck, ExecError> {
+        let venue_order = VenueOrder {
+            client_order_id: order.id,
+            symbol: &order.symbol,
+            side: match order.side {
+                OrderSide::Buy => "BUY",
+                OrderSide::Sell => "SELL",
+            },
+            order_type: match order.order_type {
+                OrderType::Market => "MARKET",
+                OrderType::Limit => "LIMIT",
+                OrderType::StopMarket => "STOP",
+                OrderType::StopLimit => "STOP_LIMIT",
+            },
+            price: matches!(order.order_type, OrderType::Limit | OrderType::StopLimit).then_some(order.price),
+            stop_price: order.stop_price(),
+            quantity: order.quantity,
+        };
+        let body = serde_json::to_string(&venue_order).map_err(transport)?;
+        let response: VenueOrderResponse = self
+            .request(reqwest::Method::POST, "/orders", Some(body))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(transport)?
+            .json()
+            .await
+            .map_err(transport)?;
+
+        if !response.accepted {
+            return Err(ExecError::Rejected {
+                reason: RejectReason::VenueRejected,
+                detail: response.reject_reason.unwrap_or_default(),
+            });
+        }
+        Ok(ExecAck { order_id: order.id, venue_order_id: response.venue_order_id, ts_event: self.clock.now_nanos() })
+    }
+
+    async fn cancel(&self, cancel: CancelOrderRequest) -> Result<(), ExecError> {
+        let path = format!("/orders/{}", cancel.order_id);
+        let response = self.request(reqwest::Method::DELETE, &path, None).send().await.map_err(transport)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ExecError::UnknownOrder(cancel.order_id));
+        }
+        response.error_for_status().map_err(transport)?;
+        Ok(())
+    }
+
+    async fn next_report(&self) -> Option<ExecutionReport> {
+        self.report_rx.lock().await.recv().await
+    }
+}
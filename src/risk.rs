@@ -0,0 +1,124 @@
+// src/risk.rs
+
+//! # 风控模块 (risk)
+//!
+//! `DrawdownGuard` 是按最大回撤触发的熔断开关：跟踪 `PortfolioSnapshot` 的权益峰值与当前回撤，
+//! 回撤超过阈值时先发布 `FlattenAll` 平掉所有持仓，再发布全局 `TradingHalted` 停止交易。
+//!
+//! ```text
+//! drawdown_pct = (peak_equity - total_equity) / peak_equity × 100
+//! ```
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::message::{FlattenAll, PortfolioSnapshot, TradingHalted, TradingResumed};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// 回撤熔断的状态。
+#[derive(Clone, Copy, Debug, Default)]
+struct DrawdownState {
+    /// 触发以来（或启动以来）的权益峰值。
+    peak_equity: Option<f64>,
+    /// 最近一份快照的回撤百分比。
+    drawdown_pct: f64,
+    /// 是否已经触发，触发后直到全局 `TradingResumed` 之前不再重复发布。
+    tripped: bool,
+}
+
+/// ## `DrawdownGuard`
+///
+/// 一个 Actor，按最大回撤触发平仓与暂停。
+/// - 消费 `PortfolioSnapshot` 消息，更新权益峰值与当前回撤。
+/// - 回撤超过 `max_drawdown_pct`（百分比，例如 `10.0` 表示 10%）时依次发布 `FlattenAll` 与
+///   `symbol` 为 `None` 的 `TradingHalted`，之后不再重复触发。
+/// - 消费全局 `TradingResumed` 消息，重新武装：峰值从恢复后的第一份快照重新开始计算。
+///
+/// `FlattenAll` 由 `SimulatedExecutionEngine` 转换为撤单与平仓单，需要同时运行 `PortfolioTracker::with_initial_capital`。
+pub struct DrawdownGuard {
+    bus: MessageBus,
+    max_drawdown_pct: f64,
+    state: Mutex<DrawdownState>,
+}
+
+impl DrawdownGuard {
+    pub fn new(bus: MessageBus, max_drawdown_pct: f64) -> Self {
+        Self { bus, max_drawdown_pct, state: Mutex::new(DrawdownState::default()) }
+    }
+
+    /// 权益峰值，尚未收到快照时为 `None`。
+    pub fn peak_equity(&self) -> Option<f64> {
+        self.state.lock().unwrap().peak_equity
+    }
+
+    /// 最近一份快照的回撤百分比。
+    pub fn drawdown_pct(&self) -> f64 {
+        self.state.lock().unwrap().drawdown_pct
+    }
+
+    /// 是否已经触发。
+    pub fn is_tripped(&self) -> bool {
+        self.state.lock().unwrap().tripped
+    }
+
+    /// `PortfolioSnapshot` 消息的处理逻辑
+    async fn handle_snapshot(&self, snapshot: PortfolioSnapshot) {
+        let reason = {
+            let mut state = self.state.lock().unwrap();
+            let peak = state.peak_equity.map_or(snapshot.total_equity, |peak| peak.max(snapshot.total_equity));
+            state.peak_equity = Some(peak);
+            state.drawdown_pct = if peak > 0.0 { (peak - snapshot.total_equity) / peak * 100.0 } else { 0.0 };
+            if state.tripped || state.drawdown_pct <= self.max_drawdown_pct {
+                return;
+            }
+            state.tripped = true;
+            format!(
+                "drawdown {:.2}% exceeds {:.2}% (peak equity {:.2}, equity {:.2})",
+                state.drawdown_pct, self.max_drawdown_pct, peak, snapshot.total_equity
+            )
+        };
+
+        tracing::error!(target: "RISK", "Kill-switch triggered: {}", reason);
+        if let Err(e) = self.bus.publish(FlattenAll { reason: reason.clone(), ts_event: snapshot.ts }).await {
+            tracing::error!(target: "RISK", "Failed to publish FlattenAll: {}", e);
+        }
+        if let Err(e) = self.bus.publish(TradingHalted { symbol: None, reason }).await {
+            tracing::error!(target: "RISK", "Failed to publish TradingHalted: {}", e);
+        }
+    }
+
+    /// 全局恢复交易后重新武装。
+    fn handle_resume(&self, resume: TradingResumed) {
+        if resume.symbol.is_none() {
+            *self.state.lock().unwrap() = DrawdownState::default();
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for DrawdownGuard {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut snapshot_rx = self.bus.subscribe::<PortfolioSnapshot>().await;
+        let mut resume_rx = self.bus.subscribe::<TradingResumed>().await;
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                tokio::select! {
+                    result = snapshot_rx.recv_traced() => match result {
+                        Ok(traced) => traced.handle("RISK", |snapshot| self.handle_snapshot(snapshot)).await,
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "RISK", "{}", BusError::lagged::<PortfolioSnapshot>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = resume_rx.recv() => match result {
+                        Ok(resume) => self.handle_resume(resume),
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "RISK", "{}", BusError::lagged::<TradingResumed>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
@@ -0,0 +1,385 @@
+// src/scripting.rs
+
+//! # 脚本策略模块 (scripting)
+//!
+//! `ScriptedStrategy` 用 Rhai 脚本编写策略规则，修改规则无需重新编译（需启用 `scripting` feature）。
+//!
+//! 脚本可以定义 `on_bar(bar)` 与 `on_fill(fill)`，参数是字段与消息同名的对象映射
+//! （`bar.close`、`fill.side` 等，id 为字符串，`side` 为 `"buy"` / `"sell"`）。
+//! 脚本通过宿主函数 `submit_order(side, price, qty)` 为当前消息的 symbol 下市价单，`price` 为参考价。
+//! 顶层语句在加载时执行一次，顶层变量在各次调用之间保留，可以用来保存状态：
+//!
+//! ```text
+//! let closes = [];
+//!
+//! fn on_bar(bar) {
+//!     closes.push(bar.close);
+//!     if closes.len() > 3 { closes.remove(0); }
+//!     if closes.len() == 3 && closes[0] < closes[1] && closes[1] < closes[2] {
+//!         submit_order("buy", bar.close, 1.0);
+//!     }
+//! }
+//! ```
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::clock::{Clock, LiveClock};
+use crate::message::{Bar, ErrorEvent, FillEvent, OrderRequest, OrderSide, OrderType, ScriptReloaded};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Position, Scope, AST};
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tokio::time::Interval;
+use uuid::Uuid;
+
+/// 每次调用处理函数允许执行的脚本操作数上限，防止死循环卡住 Actor。
+pub const DEFAULT_MAX_OPERATIONS: u64 = 1_000_000;
+
+/// ## `ScriptError`
+///
+/// 加载脚本失败。行号与列号来自 Rhai，从 1 开始。
+#[derive(Debug)]
+pub enum ScriptError {
+    /// 无法读取脚本文件。
+    Io { path: PathBuf, source: std::io::Error },
+    /// 脚本无法编译。
+    Compile { path: PathBuf, line: Option<usize>, column: Option<usize>, message: String },
+    /// 执行脚本顶层语句时出错。
+    Init { path: PathBuf, line: Option<usize>, column: Option<usize>, message: String },
+}
+
+impl ScriptError {
+    /// 出错的行号，没有位置信息时为 `None`。
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ScriptError::Io { .. } => None,
+            ScriptError::Compile { line, .. } | ScriptError::Init { line, .. } => *line,
+        }
+    }
+}
+
+/// 格式化为 `path:line:column`，没有位置信息时只有 `path`。
+fn location(path: &Path, line: Option<usize>, column: Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!("{}:{}:{}", path.display(), line, column),
+        (Some(line), None) => format!("{}:{}", path.display(), line),
+        _ => path.display().to_string(),
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScriptError::Io { path, source } => write!(f, "failed to read script {}: {}", path.display(), source),
+            ScriptError::Compile { path, line, column, message } => {
+                write!(f, "{}: syntax error: {}", location(path, *line, *column), message)
+            },
+            ScriptError::Init { path, line, column, message } => {
+                write!(f, "{}: script initialization failed: {}", location(path, *line, *column), message)
+            },
+        }
+    }
+}
+
+impl Error for ScriptError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ScriptError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// 脚本在一次处理函数调用中提交的订单：（方向，参考价，数量）。
+type PendingOrders = Arc<Mutex<Vec<(OrderSide, f64, f64)>>>;
+
+/// 已编译的脚本与它的顶层变量。
+struct Compiled {
+    ast: AST,
+    scope: Scope<'static>,
+    has_on_bar: bool,
+    has_on_fill: bool,
+}
+
+/// 脚本引擎、当前脚本与脚本文件的修改时间。
+struct Program {
+    engine: Engine,
+    path: PathBuf,
+    compiled: Compiled,
+    modified: Option<SystemTime>,
+    pending: PendingOrders,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn line_column(position: Position) -> (Option<usize>, Option<usize>) {
+    (position.line(), position.position())
+}
+
+/// 运行错误中出错语句本身（而不是调用处理函数处）的行号、列号与不含位置后缀的描述。
+fn describe(error: &EvalAltResult) -> (Option<usize>, Option<usize>, String) {
+    let inner = error.unwrap_inner();
+    let position = inner.position();
+    let message = inner.to_string();
+    let message = match message.strip_suffix(&format!(" ({})", position)) {
+        Some(stripped) => stripped.to_string(),
+        None => message,
+    };
+    let (line, column) = line_column(position);
+    (line, column, message)
+}
+
+fn script_engine(pending: PendingOrders) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(DEFAULT_MAX_OPERATIONS);
+    let submit = move |side: &str, price: f64, qty: f64| -> Result<(), Box<EvalAltResult>> {
+        let side = match side {
+            "buy" => OrderSide::Buy,
+            "sell" => OrderSide::Sell,
+            other => return Err(format!("submit_order: unknown side {:?}, expected \"buy\" or \"sell\"", other).into()),
+        };
+        if qty.is_nan() || qty <= 0.0 {
+            return Err(format!("submit_order: quantity must be positive, got {}", qty).into());
+        }
+        pending.lock().unwrap().push((side, price, qty));
+        Ok(())
+    };
+    let submit_int = submit.clone();
+    engine.register_fn("submit_order", submit);
+    // 数量写成整数字面量（`submit_order("buy", bar.close, 1)`）时同样可用
+    engine.register_fn("submit_order", move |side: &str, price: f64, qty: i64| submit_int(side, price, qty as f64));
+    engine
+}
+
+impl Program {
+    fn load(path: &Path) -> Result<Self, ScriptError> {
+        let pending = PendingOrders::default();
+        let engine = script_engine(pending.clone());
+        let modified = modified(path);
+        let compiled = Self::compile(&engine, path)?;
+        Ok(Self { engine, path: path.to_path_buf(), compiled, modified, pending })
+    }
+
+    /// 读取、编译脚本并在新的作用域中执行顶层语句。
+    fn compile(engine: &Engine, path: &Path) -> Result<Compiled, ScriptError> {
+        let source =
+            std::fs::read_to_string(path).map_err(|source| ScriptError::Io { path: path.to_path_buf(), source })?;
+        let ast = engine.compile(&source).map_err(|e| {
+            let (line, column) = line_column(e.1);
+            ScriptError::Compile { path: path.to_path_buf(), line, column, message: e.0.to_string() }
+        })?;
+        let mut scope = Scope::new();
+        engine.run_ast_with_scope(&mut scope, &ast).map_err(|e| {
+            let (line, column, message) = describe(&e);
+            ScriptError::Init { path: path.to_path_buf(), line, column, message }
+        })?;
+        let has = |name: &str| ast.iter_functions().any(|f| f.name == name && f.params.len() == 1);
+        let (has_on_bar, has_on_fill) = (has("on_bar"), has("on_fill"));
+        Ok(Compiled { ast, scope, has_on_bar, has_on_fill })
+    }
+
+    /// 调用处理函数 `name`，返回它提交的订单。出错时丢弃本次提交的所有订单。
+    fn call(&mut self, name: &str, arg: Map, symbol: &str) -> Result<Vec<OrderRequest>, String> {
+        self.pending.lock().unwrap().clear();
+        let Compiled { ast, scope, .. } = &mut self.compiled;
+        let options = CallFnOptions::new().eval_ast(false);
+        let result = self.engine.call_fn_with_options::<Dynamic>(options, scope, ast, name, (arg,));
+        let submitted = std::mem::take(&mut *self.pending.lock().unwrap());
+        if let Err(e) = result {
+            let (line, column, message) = describe(&e);
+            return Err(format!("{}: {}: {}", location(&self.path, line, column), name, message));
+        }
+        let orders = submitted
+            .into_iter()
+            .map(|(side, price, quantity)| OrderRequest {
+                id: Uuid::new_v4(),
+                symbol: symbol.to_string(),
+                side,
+                order_type: OrderType::Market,
+                price,
+                quantity,
+                trigger_price: None,
+            })
+            .collect();
+        Ok(orders)
+    }
+
+    /// 脚本文件的修改时间变化时重新编译。返回 `None` 表示没有变化，
+    /// `Some(Ok(()))` 表示已替换为新脚本（状态被重置），`Some(Err(_))` 表示新脚本无法加载、继续使用旧脚本。
+    fn reload_if_changed(&mut self) -> Option<Result<(), ScriptError>> {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        // 无论成功与否都记录这次修改，出错的脚本不会在每次检查时重复报告
+        self.modified = modified;
+        Some(Self::compile(&self.engine, &self.path).map(|compiled| self.compiled = compiled))
+    }
+}
+
+fn bar_map(bar: &Bar) -> Map {
+    let mut map = Map::new();
+    map.insert("id".into(), bar.id.to_string().into());
+    map.insert("symbol".into(), bar.symbol.clone().into());
+    map.insert("open".into(), bar.open.into());
+    map.insert("high".into(), bar.high.into());
+    map.insert("low".into(), bar.low.into());
+    map.insert("close".into(), bar.close.into());
+    map.insert("volume".into(), bar.volume.into());
+    map.insert("ts_event".into(), (bar.ts_event as i64).into());
+    map
+}
+
+fn fill_map(fill: &FillEvent) -> Map {
+    let side = match fill.side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    };
+    let mut map = Map::new();
+    map.insert("order_id".into(), fill.order_id.to_string().into());
+    map.insert("symbol".into(), fill.symbol.clone().into());
+    map.insert("side".into(), side.into());
+    map.insert("price".into(), fill.price.into());
+    map.insert("quantity".into(), fill.quantity.into());
+    map.insert("leaves_qty".into(), fill.leaves_qty.into());
+    map.insert("is_final".into(), fill.is_final.into());
+    map.insert("commission".into(), fill.commission.into());
+    map.insert("ts_event".into(), (fill.ts_event as i64).into());
+    map
+}
+
+/// 没有设置热重载时永远不会完成。
+async fn reload_tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        },
+        None => std::future::pending().await,
+    }
+}
+
+/// ## `ScriptedStrategy`
+///
+/// 一个 Actor，把 `Bar` 与 `FillEvent` 交给 Rhai 脚本的 `on_bar` / `on_fill` 处理，
+/// 并把脚本通过 `submit_order` 提交的订单作为 `OrderRequest` 发布。脚本没有定义的处理函数对应的消息被忽略。
+///
+/// - 脚本在 `load` 时编译并执行顶层语句，语法错误与初始化错误以带行号的 `ScriptError` 返回，Actor 不会启动。
+/// - 处理函数运行出错（包括超过操作数上限）时发布 `ErrorEvent` 并跳过这条消息，本次提交的订单也被丢弃，Actor 继续运行。
+/// - 设置 `with_hot_reload` 后定期检查脚本文件的修改时间，变化时重新加载：
+///   新脚本的顶层语句重新执行，之前保存的状态全部丢失，因此记录警告并发布 `ScriptReloaded`；
+///   新脚本无法加载时发布 `ErrorEvent` 并继续使用旧脚本。
+pub struct ScriptedStrategy {
+    bus: MessageBus,
+    program: Mutex<Option<Program>>,
+    reload_interval: Option<Duration>,
+    clock: Arc<dyn Clock>,
+}
+
+impl ScriptedStrategy {
+    /// 读取并编译 `path` 处的脚本。
+    pub fn load(bus: MessageBus, path: impl AsRef<Path>) -> Result<Self, ScriptError> {
+        let program = Program::load(path.as_ref())?;
+        Ok(Self { bus, program: Mutex::new(Some(program)), reload_interval: None, clock: Arc::new(LiveClock) })
+    }
+
+    /// 每隔 `interval` 检查一次脚本文件，修改后重新加载。
+    pub fn with_hot_reload(mut self, interval: Duration) -> Self {
+        self.reload_interval = Some(interval);
+        self
+    }
+
+    /// 设置每次调用处理函数允许执行的脚本操作数，默认 `DEFAULT_MAX_OPERATIONS`，为 0 时不限制。
+    pub fn with_max_operations(self, max_operations: u64) -> Self {
+        if let Some(program) = self.program.lock().unwrap().as_mut() {
+            program.engine.set_max_operations(max_operations);
+        }
+        self
+    }
+
+    /// 设置 `ErrorEvent` 与 `ScriptReloaded` 的 `ts_event` 的时间来源，默认为 `LiveClock`。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    async fn publish_error(&self, actor_name: &str, error: String) {
+        tracing::error!(target: "SCRIPT", "{}", error);
+        let event = ErrorEvent { source: actor_name.to_string(), error, ts_event: self.clock.now_nanos() };
+        if let Err(e) = self.bus.publish(event).await {
+            tracing::error!(target: "SCRIPT", "Failed to publish error event: {}", e);
+        }
+    }
+
+    /// 调用处理函数并发布它提交的订单，出错时发布 `ErrorEvent`。
+    async fn handle(&self, actor_name: &str, program: &mut Program, name: &str, arg: Map, symbol: &str) {
+        match program.call(name, arg, symbol) {
+            Ok(orders) => {
+                for order in orders {
+                    tracing::info!(target: "SCRIPT", "Script submitted {:?}", order);
+                    if let Err(e) = self.bus.publish(order).await {
+                        tracing::error!(target: "SCRIPT", "Failed to publish order: {}", e);
+                    }
+                }
+            },
+            Err(error) => self.publish_error(actor_name, error).await,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for ScriptedStrategy {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let Some(mut program) = self.program.lock().unwrap().take() else {
+            tracing::warn!(target: "SCRIPT", "{} already started", actor_name);
+            return Vec::new();
+        };
+        let mut bar_rx = self.bus.subscribe::<Bar>().await;
+        let mut fill_rx = self.bus.subscribe::<FillEvent>().await;
+        let mut reload = self.reload_interval.map(tokio::time::interval);
+        let name = actor_name.to_string();
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                tokio::select! {
+                    result = bar_rx.recv() => match result {
+                        Ok(bar) if program.compiled.has_on_bar => {
+                            self.handle(&name, &mut program, "on_bar", bar_map(&bar), &bar.symbol).await;
+                        },
+                        Ok(_) => {},
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "SCRIPT", "{}", BusError::lagged::<Bar>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = fill_rx.recv() => match result {
+                        Ok(fill) if program.compiled.has_on_fill => {
+                            self.handle(&name, &mut program, "on_fill", fill_map(&fill), &fill.symbol).await;
+                        },
+                        Ok(_) => {},
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "SCRIPT", "{}", BusError::lagged::<FillEvent>(n)),
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = reload_tick(&mut reload) => match program.reload_if_changed() {
+                        None => {},
+                        Some(Ok(())) => {
+                            let path = program.path.display().to_string();
+                            tracing::warn!(target: "SCRIPT", "Reloaded {}, script state was reset", path);
+                            let event = ScriptReloaded { source: name.clone(), path, ts_event: self.clock.now_nanos() };
+                            if let Err(e) = self.bus.publish(event).await {
+                                tracing::error!(target: "SCRIPT", "Failed to publish reload event: {}", e);
+                            }
+                        },
+                        Some(Err(e)) => {
+                            self.publish_error(&name, format!("reload failed, keeping the previous script: {}", e)).await;
+                        },
+                    },
+                }
+            }
+        });
+        vec![handle]
+    }
+}
@@ -0,0 +1,95 @@
+// src/sharded.rs
+
+//! # 分片总线模块 (sharded)
+//!
+//! 在极高的消息速率下，单条 `MessageBus` 的通道表读写锁会成为瓶颈。
+//! `ShardedMessageBus` 把消息类型分散到 `N` 条独立的总线上，不同类型的发布与订阅不再争用同一把锁。
+
+use crate::bus::{BusError, MessageBus, MessageBusTrait, Receiver};
+use crate::message::Message;
+use std::any::TypeId;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// ## `ShardedMessageBus`
+///
+/// `N` 条独立的 `MessageBus`，每种消息类型按其 `TypeId` 的哈希固定路由到其中一条（`hash % N`），
+/// 因此同一类型的发布者与订阅者总是落在同一个分片上，对使用者透明。
+/// `N` 默认为 4，适合 4 核机器。
+///
+/// - 同一类型的消息仍共用一把锁；只有发布大量不同类型时分片才能提高吞吐。
+/// - 命名空间、消息存储、`on_new_type` 等功能属于各个分片，需要时通过 `shard::<M>()` 取得对应的分片。
+///
+/// ```ignore
+/// let bus = ShardedMessageBus::<4>::new(1024);
+/// let mut bar_rx = bus.subscribe::<Bar>().await;
+/// bus.publish(bar).await?;
+/// ```
+#[derive(Clone)]
+pub struct ShardedMessageBus<const N: usize = 4>([MessageBus; N]);
+
+impl<const N: usize> ShardedMessageBus<N> {
+    /// 创建 `N` 条新的总线，`default_capacity` 的含义同 `MessageBus::new`。
+    pub fn new(default_capacity: usize) -> Self {
+        const { assert!(N > 0, "ShardedMessageBus needs at least one shard") };
+        Self(std::array::from_fn(|_| MessageBus::new(default_capacity)))
+    }
+
+    /// 由已配置好的总线组成分片，例如各自设置了 `with_type_capacity` 的总线。
+    pub fn from_shards(shards: [MessageBus; N]) -> Self {
+        const { assert!(N > 0, "ShardedMessageBus needs at least one shard") };
+        Self(shards)
+    }
+
+    /// 消息类型 `M` 所在分片的下标。在同一进程内是确定的。
+    pub fn shard_index<M: Message>() -> usize {
+        let mut hasher = DefaultHasher::new();
+        TypeId::of::<M>().hash(&mut hasher);
+        (hasher.finish() % N as u64) as usize
+    }
+
+    /// 消息类型 `M` 所在的分片。
+    pub fn shard<M: Message>(&self) -> &MessageBus {
+        &self.0[Self::shard_index::<M>()]
+    }
+
+    /// 所有分片。
+    pub fn shards(&self) -> &[MessageBus; N] {
+        &self.0
+    }
+
+    /// 所有分片上 `publish` 被调用的总次数。
+    pub fn publish_count(&self) -> u64 {
+        self.0.iter().map(MessageBus::publish_count).sum()
+    }
+
+    /// 把消息发布到 `M` 所在的分片。
+    pub async fn publish<M: Message>(&self, msg: M) -> Result<usize, BusError> {
+        self.shard::<M>().publish(msg).await
+    }
+
+    /// 从 `M` 所在的分片订阅。
+    pub async fn subscribe<M: Message>(&self) -> Receiver<M> {
+        self.shard::<M>().subscribe::<M>().await
+    }
+
+    /// `M` 所在分片上的订阅者数量。
+    pub async fn subscriber_count<M: Message>(&self) -> usize {
+        self.shard::<M>().subscriber_count::<M>().await
+    }
+}
+
+#[async_trait::async_trait]
+impl<const N: usize> MessageBusTrait for ShardedMessageBus<N> {
+    async fn publish<M: Message>(&self, msg: M) -> Result<usize, BusError> {
+        ShardedMessageBus::publish(self, msg).await
+    }
+
+    async fn subscribe<M: Message>(&self) -> Receiver<M> {
+        ShardedMessageBus::subscribe::<M>(self).await
+    }
+
+    async fn subscriber_count<M: Message>(&self) -> usize {
+        ShardedMessageBus::subscriber_count::<M>(self).await
+    }
+}
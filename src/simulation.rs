@@ -0,0 +1,155 @@
+// src/simulation.rs
+
+//! # 模拟驱动模块 (simulation)
+//!
+//! 提供由事件而不是真实时钟驱动的确定性回测循环。
+//! 每个事件都会被完全处理（包括它引发的所有级联消息）之后，虚拟时间才会前进，
+//! 因此一年的数据可以以 CPU 允许的最快速度运行，并且每次运行结果一致。
+
+use crate::bus::MessageBus;
+use crate::clock::{Clock, VirtualClock};
+use crate::message::{Bar, Message};
+use futures::future::BoxFuture;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+use tracing::info;
+
+/// 判定系统静止前，需要连续观察到没有新发布的让出次数。
+const QUIESCENT_YIELDS: usize = 3;
+
+/// 一个延迟执行的发布操作。
+type PublishFn = Box<dyn FnOnce(MessageBus) -> BoxFuture<'static, ()> + Send>;
+
+/// 事件队列中的一个条目，按 (时间戳, 入队序号) 排序。
+struct ScheduledEvent {
+    ts: u64,
+    seq: u64,
+    publish: PublishFn,
+}
+
+impl PartialEq for ScheduledEvent {
+    fn eq(&self, other: &Self) -> bool {
+        (self.ts, self.seq) == (other.ts, other.seq)
+    }
+}
+
+impl Eq for ScheduledEvent {}
+
+impl PartialOrd for ScheduledEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledEvent {
+    /// 反转比较使 `BinaryHeap` 成为最小堆：最早的事件最先出队。
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.ts, other.seq).cmp(&(self.ts, self.seq))
+    }
+}
+
+/// ## `SimulationDriver`
+///
+/// 持有虚拟时钟和事件队列的确定性模拟驱动器。
+///
+/// - 每次取出最早的事件，将虚拟时钟推进到该事件的时间戳并发布它。
+/// - 随后反复让出执行权，直到总线上不再有新的发布（即所有级联消息都已处理完毕），
+///   才会处理下一个事件。
+///
+/// **运行要求**：必须在单线程 (`current_thread`) 运行时中调用 `run`，
+/// 这样让出执行权时所有就绪的 Actor 任务都会被轮询到。
+/// 参与模拟的 Actor 不应依赖真实时钟的定时器，而应通过 `clock()` 读取虚拟时间并等待。
+pub struct SimulationDriver {
+    bus: MessageBus,
+    clock: Arc<VirtualClock>,
+    queue: BinaryHeap<ScheduledEvent>,
+    next_seq: u64,
+}
+
+impl SimulationDriver {
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            clock: Arc::new(VirtualClock::new(0)),
+            queue: BinaryHeap::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// 驱动器的虚拟时钟，Actor 应使用它作为时间来源。
+    pub fn clock(&self) -> Arc<VirtualClock> {
+        self.clock.clone()
+    }
+
+    /// 调度一条在虚拟时间 `ts` 发布的消息。时间戳相同的事件按调度顺序发布。
+    pub fn schedule<M: Message>(&mut self, ts: u64, msg: M) {
+        let publish: PublishFn = Box::new(move |bus: MessageBus| {
+            Box::pin(async move {
+                if let Err(e) = bus.publish(msg).await {
+                    tracing::error!(target: "SIMULATION", "Failed to publish scheduled event: {}", e);
+                }
+            })
+        });
+        self.queue.push(ScheduledEvent { ts, seq: self.next_seq, publish });
+        self.next_seq += 1;
+    }
+
+    /// 按每个 `Bar` 的 `ts_event` 调度一组行情。
+    pub fn schedule_bars(&mut self, bars: impl IntoIterator<Item = Bar>) {
+        for bar in bars {
+            self.schedule(bar.ts_event, bar);
+        }
+    }
+
+    /// 队列中尚未处理的事件数量。
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// 运行模拟直到事件队列耗尽且没有待唤醒的定时器，返回处理的事件数量。
+    ///
+    /// 通过 `clock()` 等待的 Actor（例如带延迟的执行引擎）会在虚拟时间到达其截止时间时被唤醒；
+    /// 截止时间早于或等于下一个事件的定时器会先被触发。
+    pub async fn run(&mut self) -> usize {
+        let mut processed = 0;
+        // 先让所有 Actor 完成启动阶段的工作
+        self.pump().await;
+        loop {
+            let next_event = self.queue.peek().map(|event| event.ts);
+            let next_timer = self.clock.next_deadline();
+            match (next_event, next_timer) {
+                (None, None) => break,
+                (_, Some(timer)) if next_event.is_none_or(|ts| timer <= ts) => {
+                    self.clock.advance_to(timer);
+                    self.pump().await;
+                }
+                _ => {
+                    let Some(event) = self.queue.pop() else { break };
+                    self.clock.advance_to(event.ts);
+                    (event.publish)(self.bus.clone()).await;
+                    self.pump().await;
+                    processed += 1;
+                }
+            }
+        }
+        info!(target: "SIMULATION", "Simulation finished: {} events, clock at {}", processed, self.clock.now_nanos());
+        processed
+    }
+
+    /// 反复让出执行权，直到连续多次没有观察到新的发布，即系统达到静止。
+    async fn pump(&self) {
+        let mut stable = 0;
+        let mut last = self.bus.publish_count();
+        while stable < QUIESCENT_YIELDS {
+            tokio::task::yield_now().await;
+            let current = self.bus.publish_count();
+            if current == last {
+                stable += 1;
+            } else {
+                stable = 0;
+                last = current;
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// src/sizing.rs
+
+//! # 仓位计算模块 (sizing)
+//!
+//! `PositionSizer` 按组合当前权益决定每个信号的订单数量，代替 `SignalOrderConverter` 的固定数量：
+//!
+//! ```text
+//! quantity = total_equity × max_position_pct / price × strength
+//! ```
+//!
+//! `price` 为信号的参考价（产生信号时的收盘价）。权益来自 `PortfolioTracker::with_initial_capital`
+//! 发布的 `PortfolioSnapshot`，订单数量随组合的盈亏放大或缩小。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::message::{OrderRequest, OrderType, PortfolioSnapshot, Signal};
+use crate::startup::StartupBarrierHandle;
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::info;
+use uuid::Uuid;
+
+/// ## `PositionSizer`
+///
+/// 一个 Actor，把 `Signal` 转换为按权益比例计算数量的市价 `OrderRequest`。
+/// - 消费 `PortfolioSnapshot` 消息，保存最近一份快照。
+/// - 消费所有 symbol 的 `Signal` 消息，订单方向与价格取自信号，数量按模块文档中的公式计算。
+///   尚未收到快照、参考价不为正或算出的数量不为正（强度不为正、权益耗尽）时忽略该信号。
+pub struct PositionSizer {
+    bus: MessageBus,
+    /// 强度为 1 的信号占用的权益比例，例如 0.1 表示 10%。
+    max_position_pct: f64,
+    current_portfolio: Arc<RwLock<Option<PortfolioSnapshot>>>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl PositionSizer {
+    pub fn new(bus: MessageBus, max_position_pct: f64) -> Self {
+        Self { bus, max_position_pct, current_portfolio: Arc::new(RwLock::new(None)), barrier: Mutex::new(None) }
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 最近收到的组合快照。
+    pub fn current_portfolio(&self) -> Option<PortfolioSnapshot> {
+        self.current_portfolio.read().unwrap().clone()
+    }
+
+    /// 按当前权益计算信号对应的订单数量，无法计算时返回 `None`。
+    pub fn quantity_for(&self, signal: &Signal) -> Option<f64> {
+        let equity = self.current_portfolio.read().unwrap().as_ref()?.total_equity;
+        if signal.price <= 0.0 {
+            return None;
+        }
+        let quantity = equity * self.max_position_pct / signal.price * signal.strength;
+        (quantity > 0.0).then_some(quantity)
+    }
+
+    /// `Signal` 消息的处理逻辑
+    async fn handle_signal(&self, signal: Signal) {
+        let _timer = self.bus.actor_metrics().start_timer("SIZER");
+        let Some(quantity) = self.quantity_for(&signal) else {
+            tracing::debug!(target: "SIZER", "Ignoring {} signal {} for {}: no positive size", signal.source, signal.id, signal.symbol);
+            return;
+        };
+        let order = OrderRequest {
+            id: Uuid::new_v4(),
+            symbol: signal.symbol,
+            side: signal.direction,
+            order_type: OrderType::Market,
+            price: signal.price,
+            quantity,
+            trigger_price: None,
+        };
+        info!(target: "SIZER", "Signal {} from {}: publishing {:?}", signal.id, signal.source, order);
+        if let Err(e) = self.bus.publish(order).await {
+            tracing::error!(target: "SIZER", "Failed to publish order: {}", e);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for PositionSizer {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut signal_rx = self.bus.subscribe::<Signal>().await;
+        let mut snapshot_rx = self.bus.subscribe::<PortfolioSnapshot>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+
+        // 快照在独立的任务中更新，处理信号时不会等待
+        let current_portfolio = self.current_portfolio.clone();
+        let snapshot_handler = spawn_named(actor_name, async move {
+            loop {
+                match snapshot_rx.recv().await {
+                    Ok(snapshot) => *current_portfolio.write().unwrap() = Some(snapshot),
+                    Err(RecvError::Lagged(n)) => tracing::debug!(target: "SIZER", "Skipped {} portfolio snapshots", n),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let signal_handler = spawn_named(actor_name, async move {
+            loop {
+                match signal_rx.recv_traced().await {
+                    Ok(traced) => traced.handle("SIZER", |signal| self.handle_signal(signal)).await,
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "SIZER", "{}", BusError::lagged::<Signal>(n)),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        vec![snapshot_handler, signal_handler]
+    }
+}
@@ -0,0 +1,126 @@
+// src/startup.rs
+
+//! # 启动同步模块 (startup)
+//!
+//! 保证所有 Actor 完成订阅之后，数据源才开始发布，避免早期消息因无人订阅而丢失。
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// 屏障的共享状态。
+#[derive(Debug, Default)]
+struct BarrierState {
+    /// 尚未调用 `ready` 的句柄数量。
+    pending: usize,
+    /// 已就绪的 Actor 名称。
+    ready: Vec<String>,
+}
+
+/// ## `StartupBarrier`
+///
+/// 创建一对启动屏障的句柄与等待端：
+///
+/// ```ignore
+/// let (handle, wait) = StartupBarrier::new();
+/// let strategy = SimpleTrendFollower::new(bus.clone(), symbol).with_startup_barrier(handle.clone());
+/// drop(handle); // 原始句柄不参与等待
+/// // ... 启动 strategy ...
+/// wait.await_all_ready(Duration::from_secs(10)).await?;
+/// ```
+pub struct StartupBarrier;
+
+impl StartupBarrier {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> (StartupBarrierHandle, StartupBarrierWait) {
+        let (tx, rx) = watch::channel(BarrierState { pending: 1, ready: Vec::new() });
+        let handle = StartupBarrierHandle { state: Arc::new(tx), is_ready: false };
+        (handle, StartupBarrierWait { state: rx })
+    }
+}
+
+/// ## `StartupBarrierHandle`
+///
+/// 每个句柄（包括克隆出的句柄）都必须调用一次 `ready`，等待端才会放行。
+/// 未调用 `ready` 就被丢弃的句柄不再计入，因此创建者可以在分发完克隆后直接丢弃原始句柄。
+pub struct StartupBarrierHandle {
+    state: Arc<watch::Sender<BarrierState>>,
+    is_ready: bool,
+}
+
+impl StartupBarrierHandle {
+    /// 标记该句柄的持有者（`actor_name`）已完成订阅。
+    pub fn ready(mut self, actor_name: &str) {
+        self.is_ready = true;
+        self.state.send_modify(|state| {
+            state.pending -= 1;
+            state.ready.push(actor_name.to_string());
+        });
+        tracing::debug!(target: "STARTUP", "{} ready", actor_name);
+    }
+}
+
+impl Clone for StartupBarrierHandle {
+    fn clone(&self) -> Self {
+        self.state.send_modify(|state| state.pending += 1);
+        Self { state: self.state.clone(), is_ready: false }
+    }
+}
+
+impl Drop for StartupBarrierHandle {
+    fn drop(&mut self) {
+        if !self.is_ready {
+            self.state.send_modify(|state| state.pending -= 1);
+        }
+    }
+}
+
+impl fmt::Debug for StartupBarrierHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StartupBarrierHandle").field("is_ready", &self.is_ready).finish()
+    }
+}
+
+/// 等待启动屏障失败的原因。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WaitError {
+    /// 超时时仍有 `pending` 个句柄未就绪，`ready` 为已就绪的 Actor。
+    Timeout { pending: usize, ready: Vec<String> },
+}
+
+impl fmt::Display for WaitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaitError::Timeout { pending, ready } => {
+                write!(f, "startup timed out with {} actor(s) not ready (ready: {:?})", pending, ready)
+            }
+        }
+    }
+}
+
+impl Error for WaitError {}
+
+/// ## `StartupBarrierWait`
+///
+/// 启动屏障的等待端。
+pub struct StartupBarrierWait {
+    state: watch::Receiver<BarrierState>,
+}
+
+impl StartupBarrierWait {
+    /// 等待所有句柄就绪，最多等待 `timeout`。
+    pub async fn await_all_ready(&self, timeout: Duration) -> Result<(), WaitError> {
+        let mut rx = self.state.clone();
+        let all_ready = tokio::time::timeout(timeout, rx.wait_for(|state| state.pending == 0)).await.is_ok();
+        let state = rx.borrow();
+        match all_ready {
+            true => {
+                tracing::info!(target: "STARTUP", "All actors ready: {:?}", state.ready);
+                Ok(())
+            }
+            false => Err(WaitError::Timeout { pending: state.pending, ready: state.ready.clone() }),
+        }
+    }
+}
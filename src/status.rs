@@ -0,0 +1,436 @@
+// src/status.rs
+
+//! # 状态端点模块 (status)
+//!
+//! `StatusServer` 以 JSON 通过 HTTP 提供运行中系统的状态（`status` feature，基于 `axum`）：
+//!
+//! - `GET /health`：`ok` 或 `degraded`，依据被关注 Actor 的心跳与每个 symbol 行情的新鲜程度。
+//! - `GET /actors`：每个 Actor 的名称、状态（`active` / `idle`）与处理的消息数。
+//! - `GET /bus`：每种已登记消息类型的通道数、订阅者数、积压，以及发布、丢弃与落后计数。
+//! - `GET /positions`、`GET /orders`：总线上最近的 `PositionUpdate` 与未结束订单的 `OrderStatusChanged`。
+//!
+//! 请求只读取 `StatusServer` 自己缓存的快照，不会触碰总线或其他 Actor 的锁；
+//! 快照由订阅的消息和按 `with_sample_interval` 周期对 `ActorMetrics` 与总线统计的采样更新。
+
+use crate::actor::{spawn_run, Actor, ActorContext};
+use crate::bus::{BusError, MessageBus, Receiver};
+use crate::message::{Bar, OrderStatusChanged, PositionUpdate};
+use crate::startup::StartupBarrierHandle;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::IntoFuture;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::runtime::Handle;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::info;
+use uuid::Uuid;
+
+/// 默认监听端口。
+pub const DEFAULT_STATUS_PORT: u16 = 8080;
+
+/// 对 `ActorMetrics` 与总线统计的默认采样周期。
+const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 被关注的 Actor 超过该时间未处理消息即视为停止心跳。
+const DEFAULT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 一个 symbol 超过该时间没有新的 `Bar` 即视为行情陈旧。
+const DEFAULT_STALE_DATA_AFTER: Duration = Duration::from_secs(30);
+
+/// ## `HealthState`
+///
+/// `/health` 的总体结论。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthState {
+    Ok,
+    Degraded,
+}
+
+/// ## `HealthReport`
+///
+/// `/health` 的响应体。`degraded` 时 HTTP 状态码为 503，`issues` 逐条说明原因。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: HealthState,
+    pub issues: Vec<String>,
+}
+
+/// ## `ActorState`
+///
+/// Actor 在心跳超时之内是否处理过消息。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActorState {
+    Active,
+    Idle,
+}
+
+/// ## `ActorStatus`
+///
+/// `/actors` 中的一项。从未处理过消息的 Actor，`idle_ms` 从 `StatusServer` 开始运行时算起。
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActorStatus {
+    pub name: String,
+    pub state: ActorState,
+    pub processed: u64,
+    /// 距上一次观察到处理消息的毫秒数。
+    pub idle_ms: u64,
+}
+
+/// ## `BusTypeStatus`
+///
+/// `/bus` 中的一项：一种消息类型的通道汇总（`ChannelStats`）与发布计数（`TypeMetrics`）。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BusTypeStatus {
+    pub type_name: String,
+    pub channels: usize,
+    pub subscribers: usize,
+    pub buffered: usize,
+    pub published: u64,
+    pub dropped: u64,
+    pub lagged: u64,
+}
+
+/// 一个 Actor 的采样结果：上次采样时的处理数，以及观察到处理数变化的时刻。
+struct Heartbeat {
+    processed: u64,
+    last_active: Instant,
+}
+
+/// `StatusServer` 缓存的快照，HTTP 请求只读取这里的内容。
+struct StatusCache {
+    started: Instant,
+    heartbeats: BTreeMap<&'static str, Heartbeat>,
+    bus: Vec<BusTypeStatus>,
+    /// 每个 symbol 最近一次收到 `Bar` 的时刻。
+    last_bar: BTreeMap<String, Instant>,
+    positions: BTreeMap<String, PositionUpdate>,
+    open_orders: HashMap<Uuid, OrderStatusChanged>,
+}
+
+impl StatusCache {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            heartbeats: BTreeMap::new(),
+            bus: Vec::new(),
+            last_bar: BTreeMap::new(),
+            positions: BTreeMap::new(),
+            open_orders: HashMap::new(),
+        }
+    }
+}
+
+/// HTTP 处理函数共享的只读视图。
+#[derive(Clone)]
+struct StatusView {
+    cache: Arc<Mutex<StatusCache>>,
+    watched: Arc<HashSet<&'static str>>,
+    heartbeat_timeout: Duration,
+    stale_data_after: Duration,
+}
+
+impl StatusView {
+    fn actors(&self) -> Vec<ActorStatus> {
+        let cache = self.cache.lock().unwrap();
+        let mut names: Vec<&'static str> = cache.heartbeats.keys().copied().collect();
+        names.extend(self.watched.iter().filter(|name| !cache.heartbeats.contains_key(*name)));
+        names.sort_unstable();
+        names
+            .into_iter()
+            .map(|name| {
+                let (processed, last_active) = match cache.heartbeats.get(name) {
+                    Some(heartbeat) => (heartbeat.processed, heartbeat.last_active),
+                    None => (0, cache.started),
+                };
+                let idle = last_active.elapsed();
+                let state = if idle <= self.heartbeat_timeout { ActorState::Active } else { ActorState::Idle };
+                ActorStatus { name: name.to_string(), state, processed, idle_ms: millis(idle) }
+            })
+            .collect()
+    }
+
+    fn health(&self) -> HealthReport {
+        let mut issues: Vec<String> = self
+            .actors()
+            .into_iter()
+            .filter(|actor| actor.state == ActorState::Idle && self.watched.contains(actor.name.as_str()))
+            .map(|actor| format!("actor {} has not processed a message for {}ms", actor.name, actor.idle_ms))
+            .collect();
+        let cache = self.cache.lock().unwrap();
+        if cache.last_bar.is_empty() && cache.started.elapsed() > self.stale_data_after {
+            issues.push(format!("no market data received for {}ms", millis(cache.started.elapsed())));
+        }
+        for (symbol, received) in &cache.last_bar {
+            if received.elapsed() > self.stale_data_after {
+                issues.push(format!("market data for {} is {}ms old", symbol, millis(received.elapsed())));
+            }
+        }
+        let status = if issues.is_empty() { HealthState::Ok } else { HealthState::Degraded };
+        HealthReport { status, issues }
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// ## `StatusServer`
+///
+/// 一个 Actor，在 `with_addr` 指定的地址（默认 `127.0.0.1:8080`）上提供 JSON 状态端点。
+///
+/// - 心跳：每次采样时 Actor 在 `ActorMetrics` 中的处理数有变化即视为一次心跳。
+///   `watch_actor` 登记的 Actor 超过 `with_heartbeat_timeout` 没有心跳时 `/health` 为 `degraded`；
+///   未登记的 Actor 只在 `/actors` 中显示，空闲不影响健康状态。
+/// - 行情：任一 symbol 超过 `with_stale_data_after` 没有新的 `Bar`，或开始运行后同样长的时间内从未收到 `Bar`，
+///   `/health` 为 `degraded`。
+/// - 订单：收到非终结状态的 `OrderStatusChanged` 时记录，终结时移除，`/orders` 按 `ts_event` 排序。
+///
+/// 服务在 `run` 中运行，收到停止信号后不再接受新连接，等待进行中的请求结束后返回。
+pub struct StatusServer {
+    bus: MessageBus,
+    addr: SocketAddr,
+    sample_interval: Duration,
+    heartbeat_timeout: Duration,
+    stale_data_after: Duration,
+    watched: HashSet<&'static str>,
+    local_addr: Mutex<Option<SocketAddr>>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl StatusServer {
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            addr: SocketAddr::from((Ipv4Addr::LOCALHOST, DEFAULT_STATUS_PORT)),
+            sample_interval: DEFAULT_SAMPLE_INTERVAL,
+            heartbeat_timeout: DEFAULT_HEARTBEAT_TIMEOUT,
+            stale_data_after: DEFAULT_STALE_DATA_AFTER,
+            watched: HashSet::new(),
+            local_addr: Mutex::new(None),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 设置监听地址。端口为 0 时由系统分配，通过 `local_addr` 查询。
+    pub fn with_addr(mut self, addr: SocketAddr) -> Self {
+        self.addr = addr;
+        self
+    }
+
+    /// 设置对 `ActorMetrics` 与总线统计的采样周期，默认 1 秒。心跳的精度不会高于这个周期。
+    pub fn with_sample_interval(mut self, interval: Duration) -> Self {
+        self.sample_interval = interval;
+        self
+    }
+
+    /// 设置被关注的 Actor 停止心跳的判定时间，默认 60 秒。
+    pub fn with_heartbeat_timeout(mut self, timeout: Duration) -> Self {
+        self.heartbeat_timeout = timeout;
+        self
+    }
+
+    /// 设置行情陈旧的判定时间，默认 30 秒。
+    pub fn with_stale_data_after(mut self, after: Duration) -> Self {
+        self.stale_data_after = after;
+        self
+    }
+
+    /// 关注名为 `name` 的 Actor（即它在 `ActorMetrics` 中记录时使用的名称）：停止心跳时 `/health` 为 `degraded`。
+    pub fn watch_actor(mut self, name: &'static str) -> Self {
+        self.watched.insert(name);
+        self
+    }
+
+    /// 设置启动屏障，开始监听后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 实际监听的地址，开始监听之前为 `None`。
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.lock().unwrap()
+    }
+
+    fn ready(&self, ctx: &ActorContext) {
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(ctx.name());
+        }
+        ctx.ready();
+    }
+
+    /// 采样一次 Actor 处理数与总线统计，写入缓存。
+    async fn sample(&self, cache: &Mutex<StatusCache>) {
+        let actors = self.bus.actor_metrics().snapshot();
+        let channels = self.bus.connected_types().await;
+        let metrics = self.bus.type_metrics();
+
+        let mut bus: BTreeMap<&'static str, BusTypeStatus> = BTreeMap::new();
+        for (type_name, stats) in channels {
+            let entry = bus.entry(type_name).or_default();
+            entry.channels = stats.channels;
+            entry.subscribers = stats.subscribers;
+            entry.buffered = stats.buffered;
+        }
+        for (type_name, counters) in metrics {
+            let entry = bus.entry(type_name).or_default();
+            entry.published = counters.published;
+            entry.dropped = counters.dropped;
+            entry.lagged = counters.lagged;
+        }
+
+        let now = Instant::now();
+        let mut cache = cache.lock().unwrap();
+        for (name, stats) in actors {
+            let heartbeat = cache.heartbeats.entry(name).or_insert(Heartbeat { processed: 0, last_active: now });
+            if stats.processed != heartbeat.processed {
+                heartbeat.processed = stats.processed;
+                heartbeat.last_active = now;
+            }
+        }
+        cache.bus = bus
+            .into_iter()
+            .map(|(type_name, status)| BusTypeStatus { type_name: type_name.to_string(), ..status })
+            .collect();
+    }
+}
+
+/// 从总线消息更新缓存中的行情时间、持仓与未结束订单，直到任一订阅关闭。
+async fn track_messages(
+    mut bar_rx: Receiver<Bar>,
+    mut position_rx: Receiver<PositionUpdate>,
+    mut order_rx: Receiver<OrderStatusChanged>,
+    cache: &Mutex<StatusCache>,
+) {
+    loop {
+        tokio::select! {
+            result = bar_rx.recv() => match result {
+                Ok(bar) => {
+                    cache.lock().unwrap().last_bar.insert(bar.symbol, Instant::now());
+                },
+                Err(RecvError::Lagged(n)) => tracing::warn!(target: "STATUS", "{}", BusError::lagged::<Bar>(n)),
+                Err(RecvError::Closed) => break,
+            },
+            result = position_rx.recv() => match result {
+                Ok(update) => {
+                    cache.lock().unwrap().positions.insert(update.symbol.clone(), update);
+                },
+                Err(RecvError::Lagged(n)) => tracing::warn!(target: "STATUS", "{}", BusError::lagged::<PositionUpdate>(n)),
+                Err(RecvError::Closed) => break,
+            },
+            result = order_rx.recv() => match result {
+                Ok(change) => {
+                    let mut cache = cache.lock().unwrap();
+                    if change.status.is_terminal() {
+                        cache.open_orders.remove(&change.order_id);
+                    } else {
+                        cache.open_orders.insert(change.order_id, change);
+                    }
+                },
+                Err(RecvError::Lagged(n)) => tracing::warn!(target: "STATUS", "{}", BusError::lagged::<OrderStatusChanged>(n)),
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+}
+
+async fn health(State(view): State<StatusView>) -> (StatusCode, Json<HealthReport>) {
+    let report = view.health();
+    let code = match report.status {
+        HealthState::Ok => StatusCode::OK,
+        HealthState::Degraded => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (code, Json(report))
+}
+
+async fn actors(State(view): State<StatusView>) -> Json<Vec<ActorStatus>> {
+    Json(view.actors())
+}
+
+async fn bus(State(view): State<StatusView>) -> Json<Vec<BusTypeStatus>> {
+    Json(view.cache.lock().unwrap().bus.clone())
+}
+
+async fn positions(State(view): State<StatusView>) -> Json<Vec<PositionUpdate>> {
+    Json(view.cache.lock().unwrap().positions.values().cloned().collect())
+}
+
+async fn orders(State(view): State<StatusView>) -> Json<Vec<OrderStatusChanged>> {
+    let mut orders: Vec<_> = view.cache.lock().unwrap().open_orders.values().cloned().collect();
+    orders.sort_by_key(|order| (order.ts_event, order.order_id));
+    Json(orders)
+}
+
+#[async_trait::async_trait]
+impl Actor for StatusServer {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let ctx = ActorContext::new(self.bus.clone(), actor_name);
+        vec![spawn_run(self, ctx, &Handle::current()).await]
+    }
+
+    /// HTTP 服务、消息跟踪与采样都在 `run` 中运行，收到停止信号后一起结束。
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        let bar_rx = self.bus.subscribe::<Bar>().await;
+        let position_rx = self.bus.subscribe::<PositionUpdate>().await;
+        let order_rx = self.bus.subscribe::<OrderStatusChanged>().await;
+
+        let listener = match TcpListener::bind(self.addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!(target: "STATUS", "Cannot listen on {}: {}", self.addr, e);
+                self.ready(&ctx);
+                return;
+            },
+        };
+        *self.local_addr.lock().unwrap() = listener.local_addr().ok();
+        info!(target: "STATUS", "Serving status endpoints on {:?}", self.local_addr());
+
+        let cache = Arc::new(Mutex::new(StatusCache::new()));
+        let view = StatusView {
+            cache: cache.clone(),
+            watched: Arc::new(self.watched.clone()),
+            heartbeat_timeout: self.heartbeat_timeout,
+            stale_data_after: self.stale_data_after,
+        };
+        let app = Router::new()
+            .route("/health", get(health))
+            .route("/actors", get(actors))
+            .route("/bus", get(bus))
+            .route("/positions", get(positions))
+            .route("/orders", get(orders))
+            .with_state(view);
+        self.ready(&ctx);
+
+        let shutdown = ctx.shutdown_token().clone();
+        let server = axum::serve(listener, app).with_graceful_shutdown(async move { shutdown.cancelled().await });
+        let sampling = async {
+            let mut ticker = tokio::time::interval(self.sample_interval);
+            loop {
+                ticker.tick().await;
+                self.sample(&cache).await;
+            }
+        };
+        let tracking = async {
+            tokio::select! {
+                _ = track_messages(bar_rx, position_rx, order_rx, &cache) => {},
+                _ = sampling => {},
+                _ = ctx.shutdown_requested() => {},
+            }
+        };
+        let (result, ()) = tokio::join!(server.into_future(), tracking);
+        match result {
+            Ok(()) => info!(target: "STATUS", "Status endpoints stopped"),
+            Err(e) => tracing::error!(target: "STATUS", "Status endpoints failed: {}", e),
+        }
+    }
+}
@@ -0,0 +1,219 @@
+// src/store.rs
+
+//! # 消息存储模块 (store)
+//!
+//! 进程崩溃重启时，broadcast 缓冲区中的消息会全部丢失。
+//! `MessageStore` 为登记过的消息类型保留最近 N 条消息，
+//! `MessageBus::capture_state` 把它们连同通道配置导出为可序列化的 `BusState`，
+//! 重启后由 `MessageBus::restore_state` 重建通道并按原顺序重放。
+
+use crate::bus::MessageBus;
+use crate::message::Message;
+use crate::trace::TraceContext;
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// ## `Envelope`
+///
+/// 带有发布元数据的消息。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Envelope<M> {
+    /// 在同一个 `MessageStore` 中全局递增的序号，决定重放顺序。
+    pub seq: u64,
+    /// 发布者所在的命名空间，根命名空间为空字符串。
+    pub namespace: String,
+    /// 是否由 `restore_state` 或 `replay_from_store` 重放产生，而不是实时发布。
+    /// 消费者应对重放的消息更新状态，但跳过下单等副作用。
+    pub is_replay: bool,
+    pub message: M,
+    /// 实时消息发布时的追踪上下文，存储与重放的消息没有上下文（见 `trace` 模块）。
+    #[serde(skip)]
+    pub trace: Option<TraceContext>,
+}
+
+/// 一个通道的配置。
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelState {
+    /// `std::any::type_name` 给出的消息类型名。
+    pub type_name: String,
+    pub namespace: String,
+    pub capacity: usize,
+}
+
+/// 一条序列化后的缓存消息。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredMessage {
+    pub type_name: String,
+    pub envelope: Envelope<serde_json::Value>,
+}
+
+/// ## `BusState`
+///
+/// 总线的快照：所有通道的配置，以及已登记类型的最近消息（按 `seq` 升序）。
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BusState {
+    pub channels: Vec<ChannelState>,
+    pub messages: Vec<StoredMessage>,
+}
+
+/// 把序列化的消息反序列化后发布到给定的总线视图。
+pub(crate) type ReplayFn = fn(MessageBus, serde_json::Value) -> BoxFuture<'static, Result<usize, crate::bus::BusError>>;
+
+/// 一种消息类型的缓存。
+struct StoreEntry {
+    type_name: &'static str,
+    retain: usize,
+    records: VecDeque<Envelope<Box<dyn Any + Send + Sync>>>,
+    serialize: fn(&(dyn Any + Send + Sync)) -> Result<serde_json::Value, serde_json::Error>,
+    replay: ReplayFn,
+}
+
+/// ## `MessageStore`
+///
+/// 按消息类型保留最近 `retain` 条消息。只有通过 `MessageBus::with_message_store` 登记的类型会被保留。
+#[derive(Default)]
+pub struct MessageStore {
+    next_seq: u64,
+    entries: HashMap<TypeId, StoreEntry>,
+}
+
+impl MessageStore {
+    /// 登记消息类型 `M`，保留最近 `retain` 条。
+    pub(crate) fn register<M: Message + Serialize + DeserializeOwned>(&mut self, retain: usize) {
+        self.entries.insert(
+            TypeId::of::<M>(),
+            StoreEntry {
+                type_name: std::any::type_name::<M>(),
+                retain,
+                records: VecDeque::new(),
+                serialize: serialize::<M>,
+                replay: replay::<M>,
+            },
+        );
+    }
+
+    /// 记录一条已发布的消息。未登记的类型直接忽略。
+    pub(crate) fn record<M: Message>(&mut self, msg: &M, namespace: &str, is_replay: bool) {
+        let Some(entry) = self.entries.get_mut(&TypeId::of::<M>()) else {
+            return;
+        };
+        if entry.retain == 0 {
+            return;
+        }
+        if entry.records.len() == entry.retain {
+            entry.records.pop_front();
+        }
+        entry.records.push_back(Envelope {
+            seq: self.next_seq,
+            namespace: namespace.to_string(),
+            is_replay,
+            message: Box::new(msg.clone()),
+            trace: None,
+        });
+        self.next_seq += 1;
+    }
+
+    /// 登记相同类型与保留数量、但不含任何消息的存储，供 `MessageBus::fork` 使用。
+    pub(crate) fn empty_copy(&self) -> MessageStore {
+        let entries = self
+            .entries
+            .iter()
+            .map(|(type_id, entry)| {
+                let entry = StoreEntry {
+                    type_name: entry.type_name,
+                    retain: entry.retain,
+                    records: VecDeque::new(),
+                    serialize: entry.serialize,
+                    replay: entry.replay,
+                };
+                (*type_id, entry)
+            })
+            .collect();
+        MessageStore { next_seq: 0, entries }
+    }
+
+    /// 类型 `M` 是否已登记。
+    pub fn is_registered<M: Message>(&self) -> bool {
+        self.entries.contains_key(&TypeId::of::<M>())
+    }
+
+    /// 下一条记录的消息将获得的序号。
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// 类型 `M` 的缓存消息，按发布顺序排列。
+    pub fn recent<M: Message>(&self) -> Vec<Envelope<M>> {
+        let Some(entry) = self.entries.get(&TypeId::of::<M>()) else {
+            return Vec::new();
+        };
+        entry
+            .records
+            .iter()
+            .filter_map(|record| {
+                let message = record.message.downcast_ref::<M>()?.clone();
+                Some(Envelope {
+                    seq: record.seq,
+                    namespace: record.namespace.clone(),
+                    is_replay: record.is_replay,
+                    message,
+                    trace: None,
+                })
+            })
+            .collect()
+    }
+
+    /// 序列化所有缓存消息，按 `seq` 升序排列。无法序列化的消息会被跳过并记录警告。
+    pub(crate) fn snapshot(&self) -> Vec<StoredMessage> {
+        let mut messages: Vec<StoredMessage> = self
+            .entries
+            .values()
+            .flat_map(|entry| entry.records.iter().map(move |record| (entry, record)))
+            .filter_map(|(entry, record)| match (entry.serialize)(record.message.as_ref()) {
+                Ok(payload) => Some(StoredMessage {
+                    type_name: entry.type_name.to_string(),
+                    envelope: Envelope {
+                        seq: record.seq,
+                        namespace: record.namespace.clone(),
+                        is_replay: record.is_replay,
+                        message: payload,
+                        trace: None,
+                    },
+                }),
+                Err(e) => {
+                    tracing::warn!(target: "BUS", "Failed to serialize stored {}: {}", entry.type_name, e);
+                    None
+                }
+            })
+            .collect();
+        messages.sort_by_key(|stored| stored.envelope.seq);
+        messages
+    }
+
+    /// 按类型名查找重放函数。
+    pub(crate) fn replay_fn(&self, type_name: &str) -> Option<ReplayFn> {
+        self.entries.values().find(|entry| entry.type_name == type_name).map(|entry| entry.replay)
+    }
+}
+
+fn serialize<M: Message + Serialize>(msg: &(dyn Any + Send + Sync)) -> Result<serde_json::Value, serde_json::Error> {
+    let msg = msg.downcast_ref::<M>().expect("FATAL: MessageStore internal type corruption. This is a bug.");
+    serde_json::to_value(msg)
+}
+
+fn replay<M: Message + DeserializeOwned>(
+    bus: MessageBus,
+    payload: serde_json::Value,
+) -> BoxFuture<'static, Result<usize, crate::bus::BusError>> {
+    Box::pin(async move {
+        let msg: M = serde_json::from_value(payload).map_err(crate::bus::BusError::deserialize::<M>)?;
+        bus.publish_replay(msg).await
+    })
+}
+
+/// 多个总线视图共享的存储句柄。
+pub(crate) type SharedStore = Arc<std::sync::Mutex<MessageStore>>;
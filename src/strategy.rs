@@ -3,96 +3,492 @@
 //! # 策略模块 (strategy)
 //!
 //! 实现交易策略逻辑，是消息的消费者和生产者。
+//! 信号生成与下单分为两个 Actor：信号源（例如 `TrendSignalGenerator`）消费行情并发布 `Signal`，
+//! `SignalOrderConverter` 把 `Signal` 转换为 `OrderRequest` 并跟踪持仓与拒绝。
+//! 动量、均值回归等不同的信号源都可以接入同一个 `SignalOrderConverter`。
 
-use crate::actor::Actor;
-use crate::bus::MessageBus;
-use crate::message::{Bar, FillEvent, OrderRequest, OrderSide};
-use std::sync::Arc;
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::indicators::BarBuffer;
+use crate::message::{
+    Bar, FillEvent, OrderFlowMetric, OrderRejected, OrderRequest, OrderSide, OrderType, Signal, VwapUpdate, WarmupComplete,
+    DEFAULT_BAR_TIMEFRAME,
+};
+use crate::startup::StartupBarrierHandle;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::broadcast::error::RecvError;
 use tokio::task::JoinHandle;
 use tracing::info;
 use uuid::Uuid;
 
-/// ## `SimpleTrendFollower`
+/// `TrendSignalGenerator` 不使用 VWAP 时的买入阈值。
+const TREND_THRESHOLD: f64 = 102.0;
+
+/// ## `TrendSignalGenerator`
 ///
-/// 一个简单的趋势跟踪策略 Actor。
-/// - 消费 `Bar` 消息来做决策。
-/// - 生产 `OrderRequest` 消息来执行交易。
-/// - 消费 `FillEvent` 消息来更新内部状态。
-pub struct SimpleTrendFollower {
+/// 趋势信号源 Actor。
+/// - 消费 `Bar` 消息，只使用本 symbol、周期为 `with_timeframe`（默认 1 分钟）的 `Bar`。
+/// - 收盘价高于阈值时发布强度为 1 的买入 `Signal`，参考价为收盘价，信号源为 `"trend"`。
+/// - 消费 `WarmupComplete` 消息，预热完成之前不会产生信号。
+/// - 启用 `with_vwap_signal` 后消费 `VwapUpdate` 消息，改为在收盘价高于最新 VWAP 时买入。
+/// - 启用 `with_sma_signal` 后改为在收盘价高于最近若干根 `Bar` 的简单移动平均时买入；同时启用 VWAP 时以 VWAP 为准。
+/// - 启用 `with_order_flow_signal` 后消费 `OrderFlowMetric` 消息，只在最新的订单流不平衡确认买方主导时买入。
+pub struct TrendSignalGenerator {
     bus: MessageBus,
     symbol: String,
+    /// 用于决策的 `Bar` 周期。
+    timeframe: Duration,
+    is_warmed_up: AtomicBool,
+    /// 是否以 VWAP 作为买入基准。
+    use_vwap: bool,
+    /// 最近一次收到的 VWAP。
+    last_vwap: Mutex<Option<f64>>,
+    /// 买入所需的最小订单流不平衡，`None` 表示不使用订单流。
+    min_imbalance: Option<f64>,
+    /// 最近一次收到的订单流不平衡。
+    last_imbalance: Mutex<Option<f64>>,
+    /// 以简单移动平均为买入基准时的周期，`None` 表示不使用。
+    sma_period: Option<usize>,
+    /// 最近的 `Bar`（含预热期间收到的），供移动平均使用。
+    bars: Mutex<BarBuffer>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
 }
 
-impl SimpleTrendFollower {
+impl TrendSignalGenerator {
     pub fn new(bus: MessageBus, symbol: String) -> Self {
-        Self { bus, symbol }
+        Self {
+            bus,
+            symbol,
+            timeframe: DEFAULT_BAR_TIMEFRAME,
+            is_warmed_up: AtomicBool::new(false),
+            use_vwap: false,
+            last_vwap: Mutex::new(None),
+            min_imbalance: None,
+            last_imbalance: Mutex::new(None),
+            sma_period: None,
+            bars: Mutex::new(BarBuffer::new(1)),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 只使用周期为 `timeframe` 的 `Bar`，例如 `TickAggregator` 同时发布多个周期时选择其中之一。
+    pub fn with_timeframe(mut self, timeframe: Duration) -> Self {
+        self.timeframe = timeframe;
+        self
+    }
+
+    /// 以 VWAP 代替固定阈值生成信号：收盘价高于最新 `VwapUpdate` 时买入，
+    /// 尚未收到 VWAP 时不产生信号。需要同时运行 `VwapActor`。
+    pub fn with_vwap_signal(mut self) -> Self {
+        self.use_vwap = true;
+        self
+    }
+
+    /// 以订单流确认信号：价格条件满足时，还要求最新 `OrderFlowMetric` 的不平衡不低于 `min_imbalance`，
+    /// 尚未收到订单流时不产生信号。主动买盘主导的突破比单纯的收盘价突破更可信。需要同时运行 `OrderFlowActor`。
+    pub fn with_order_flow_signal(mut self, min_imbalance: f64) -> Self {
+        self.min_imbalance = Some(min_imbalance);
+        self
+    }
+
+    /// 以最近 `period` 根 `Bar`（含当前这根）收盘价的简单移动平均代替固定阈值生成信号：
+    /// 收盘价高于移动平均时买入，不足 `period` 根时不产生信号。预热期间收到的 `Bar` 同样计入。
+    pub fn with_sma_signal(mut self, period: usize) -> Self {
+        self.sma_period = Some(period.max(1));
+        self.bars = Mutex::new(BarBuffer::new(period));
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 是否已收到 `WarmupComplete`。
+    pub fn is_warmed_up(&self) -> bool {
+        self.is_warmed_up.load(Ordering::Acquire)
     }
 
     /// `Bar` 消息的处理逻辑
     async fn handle_bar(&self, bar: Bar) {
+        let _timer = self.bus.actor_metrics().start_timer("STRATEGY");
         info!(target: "STRATEGY", "Received Bar with close price {}", bar.close);
-        if bar.close > 102.0 {
-            let order = OrderRequest {
+        let sma = self.sma_period.and_then(|period| {
+            let mut bars = self.bars.lock().unwrap();
+            bars.push(bar.clone());
+            bars.sma(period)
+        });
+        if !self.is_warmed_up.load(Ordering::Acquire) {
+            return;
+        }
+        let threshold = if self.use_vwap {
+            match *self.last_vwap.lock().unwrap() {
+                Some(vwap) => vwap,
+                None => return,
+            }
+        } else if self.sma_period.is_some() {
+            match sma {
+                Some(sma) => sma,
+                None => return,
+            }
+        } else {
+            TREND_THRESHOLD
+        };
+        if let Some(min_imbalance) = self.min_imbalance {
+            match *self.last_imbalance.lock().unwrap() {
+                Some(imbalance) if imbalance >= min_imbalance => {},
+                _ => return,
+            }
+        }
+        if bar.close > threshold {
+            let signal = Signal {
                 id: Uuid::new_v4(),
                 symbol: self.symbol.clone(),
-                side: OrderSide::Buy,
+                direction: OrderSide::Buy,
+                strength: 1.0,
                 price: bar.close,
-                quantity: 1.0,
+                ts_event: bar.ts_event,
+                source: "trend".to_string(),
             };
-            info!(target: "STRATEGY", "Condition met! Publishing {:?}", order);
-            if let Err(e) = self.bus.publish(order).await {
-                tracing::error!(target: "STRATEGY", "Failed to publish order: {}", e);
+            info!(target: "STRATEGY", "Condition met! Publishing {:?}", signal);
+            if let Err(e) = self.bus.publish(signal).await {
+                tracing::error!(target: "STRATEGY", "Failed to publish signal: {}", e);
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for TrendSignalGenerator {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        // 订阅 Bar 消息
+        let mut bar_rx = self.bus.subscribe::<Bar>().await;
+        // 订阅 WarmupComplete 消息
+        let mut warmup_rx = self.bus.subscribe::<WarmupComplete>().await;
+
+        let self_clone_for_bar = self.clone();
+        let bar_handler = spawn_named(actor_name, async move {
+            loop {
+                // 总线为 Bar 设置了有效期时，落后期间过期的 Bar 被丢弃，不会按过时的价格产生信号
+                match bar_rx.recv_fresh().await {
+                    Ok(traced) => {
+                        // 过滤掉不关心的 symbol 与周期
+                        let bar = &traced.message;
+                        if bar.symbol == self_clone_for_bar.symbol && bar.timeframe == self_clone_for_bar.timeframe {
+                            traced.handle("STRATEGY", |bar| self_clone_for_bar.handle_bar(bar)).await
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "STRATEGY", "{}", BusError::lagged::<Bar>(n)),
+                    Err(RecvError::Closed) => break,
+                }
             }
+        });
+
+        let self_clone_for_warmup = self.clone();
+        let warmup_handler = spawn_named(actor_name, async move {
+            loop {
+                match warmup_rx.recv().await {
+                    Ok(complete) => {
+                        if complete.symbol == self_clone_for_warmup.symbol {
+                            info!(target: "STRATEGY", "Warm-up complete after {} bars", complete.bars_seen);
+                            self_clone_for_warmup.is_warmed_up.store(true, Ordering::Release);
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "STRATEGY", "{}", BusError::lagged::<WarmupComplete>(n)),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let mut handles = vec![bar_handler, warmup_handler];
+
+        if self.use_vwap {
+            // 订阅 VwapUpdate 消息
+            let mut vwap_rx = self.bus.subscribe::<VwapUpdate>().await;
+            let self_clone_for_vwap = self.clone();
+            handles.push(spawn_named(actor_name, async move {
+                loop {
+                    match vwap_rx.recv().await {
+                        Ok(update) => {
+                            if update.symbol == self_clone_for_vwap.symbol {
+                                *self_clone_for_vwap.last_vwap.lock().unwrap() = Some(update.vwap);
+                            }
+                        },
+                        Err(RecvError::Lagged(n)) => tracing::warn!(target: "STRATEGY", "{}", BusError::lagged::<VwapUpdate>(n)),
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            }));
+        }
+
+        if self.min_imbalance.is_some() {
+            // 订阅 OrderFlowMetric 消息
+            let mut flow_rx = self.bus.subscribe::<OrderFlowMetric>().await;
+            let self_clone_for_flow = self.clone();
+            handles.push(spawn_named(actor_name, async move {
+                loop {
+                    match flow_rx.recv().await {
+                        Ok(metric) => {
+                            if metric.symbol == self_clone_for_flow.symbol {
+                                *self_clone_for_flow.last_imbalance.lock().unwrap() = Some(metric.imbalance);
+                            }
+                        },
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(target: "STRATEGY", "{}", BusError::lagged::<OrderFlowMetric>(n))
+                        },
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            }));
+        }
+
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+        handles
+    }
+}
+
+/// ## `SignalOrderConverter`
+///
+/// 把信号转换为订单的 Actor，不关心信号来自哪个信号源。
+/// - 消费本 symbol 的 `Signal` 消息，发布同方向的市价 `OrderRequest`，
+///   价格为信号的参考价，数量为 `base_quantity × strength`；强度不为正的信号被忽略。
+/// - 消费 `FillEvent` 消息来更新持仓，部分成交会被逐笔累加。
+/// - 消费 `OrderRejected` 消息，区分“订单被拒绝”与“尚未收到回报”。
+pub struct SignalOrderConverter {
+    bus: MessageBus,
+    symbol: String,
+    /// 强度为 1 的信号对应的订单数量。
+    base_quantity: f64,
+    /// 当前净持仓，多头为正、空头为负。
+    position: Mutex<f64>,
+    /// 被执行端拒绝的订单数量。
+    rejected_orders: AtomicUsize,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl SignalOrderConverter {
+    pub fn new(bus: MessageBus, symbol: String) -> Self {
+        Self {
+            bus,
+            symbol,
+            base_quantity: 1.0,
+            position: Mutex::new(0.0),
+            rejected_orders: AtomicUsize::new(0),
+            barrier: Mutex::new(None),
+        }
+    }
+
+    /// 强度为 1 的信号对应的订单数量（默认 1）。
+    pub fn with_base_quantity(mut self, base_quantity: f64) -> Self {
+        self.base_quantity = base_quantity;
+        self
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    /// 当前净持仓。
+    pub fn position(&self) -> f64 {
+        *self.position.lock().unwrap()
+    }
+
+    /// 被执行端拒绝的订单数量。
+    pub fn rejected_orders(&self) -> usize {
+        self.rejected_orders.load(Ordering::Relaxed)
+    }
+
+    /// `Signal` 消息的处理逻辑
+    async fn handle_signal(&self, signal: Signal) {
+        let _timer = self.bus.actor_metrics().start_timer("STRATEGY");
+        let quantity = self.base_quantity * signal.strength;
+        if quantity <= 0.0 {
+            tracing::debug!(target: "STRATEGY", "Ignoring {} signal {} with strength {}", signal.source, signal.id, signal.strength);
+            return;
+        }
+        let order = OrderRequest {
+            id: Uuid::new_v4(),
+            symbol: self.symbol.clone(),
+            side: signal.direction,
+            order_type: OrderType::Market,
+            price: signal.price,
+            quantity,
+            trigger_price: None,
+        };
+        info!(target: "STRATEGY", "Signal {} from {}: publishing {:?}", signal.id, signal.source, order);
+        if let Err(e) = self.bus.publish(order).await {
+            tracing::error!(target: "STRATEGY", "Failed to publish order: {}", e);
         }
     }
-    
+
     /// `FillEvent` 消息的处理逻辑
     async fn handle_fill(&self, fill: FillEvent) {
-        info!(target: "STRATEGY", "Received Fill: {:?}. Updating portfolio.", fill);
-        // 在真实系统中，这里会更新持仓状态
+        let _timer = self.bus.actor_metrics().start_timer("STRATEGY");
+        let signed_qty = match fill.side {
+            OrderSide::Buy => fill.quantity,
+            OrderSide::Sell => -fill.quantity,
+        };
+        let position = {
+            let mut position = self.position.lock().unwrap();
+            *position += signed_qty;
+            *position
+        };
+        info!(target: "STRATEGY", "Received Fill: {:?}. Position is now {}", fill, position);
+    }
+
+    /// `OrderRejected` 消息的处理逻辑
+    async fn handle_rejected(&self, rejected: OrderRejected) {
+        self.rejected_orders.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!(target: "STRATEGY", "Order {} rejected ({}): {}", rejected.order_id, rejected.reason, rejected.detail);
     }
 }
 
 #[async_trait::async_trait]
-impl Actor for SimpleTrendFollower {
-    async fn start(self: Arc<Self>) -> Vec<JoinHandle<()>> {
-        // 订阅 Bar 消息
-        let mut bar_rx = self.bus.subscribe::<Bar>().await;
+impl Actor for SignalOrderConverter {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        // 订阅 Signal 消息
+        let mut signal_rx = self.bus.subscribe::<Signal>().await;
         // 订阅 FillEvent 消息
         let mut fill_rx = self.bus.subscribe::<FillEvent>().await;
-        
-        let self_clone_for_bar = self.clone();
-        let bar_handler = tokio::spawn(async move {
+        // 订阅 OrderRejected 消息
+        let mut rejected_rx = self.bus.subscribe::<OrderRejected>().await;
+
+        let self_clone_for_signal = self.clone();
+        let signal_handler = spawn_named(actor_name, async move {
             loop {
-                match bar_rx.recv().await {
-                    Ok(bar) => {
-                        // 过滤掉不关心的 symbol
-                        if bar.symbol == self_clone_for_bar.symbol {
-                           self_clone_for_bar.handle_bar(bar).await
+                match signal_rx.recv_traced().await {
+                    Ok(traced) => {
+                        if traced.message.symbol == self_clone_for_signal.symbol {
+                            traced.handle("STRATEGY", |signal| self_clone_for_signal.handle_signal(signal)).await
                         }
                     },
-                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "STRATEGY", "Lagged by {} bars", n),
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "STRATEGY", "{}", BusError::lagged::<Signal>(n)),
                     Err(RecvError::Closed) => break,
                 }
             }
         });
-        
+
         let self_clone_for_fill = self.clone();
-        let fill_handler = tokio::spawn(async move {
+        let fill_handler = spawn_named(actor_name, async move {
+            loop {
+                match fill_rx.recv_traced().await {
+                    Ok(traced) => {
+                        if traced.message.symbol == self_clone_for_fill.symbol {
+                            traced.handle("STRATEGY", |fill| self_clone_for_fill.handle_fill(fill)).await
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "STRATEGY", "{}", BusError::lagged::<FillEvent>(n)),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let self_clone_for_rejected = self.clone();
+        let rejected_handler = spawn_named(actor_name, async move {
             loop {
-                match fill_rx.recv().await {
-                     Ok(fill) => {
-                        if fill.symbol == self_clone_for_fill.symbol {
-                            self_clone_for_fill.handle_fill(fill).await
+                match rejected_rx.recv_traced().await {
+                    Ok(traced) => {
+                        if traced.message.symbol == self_clone_for_rejected.symbol {
+                            traced.handle("STRATEGY", |rejected| self_clone_for_rejected.handle_rejected(rejected)).await
                         }
                     },
-                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "STRATEGY", "Lagged by {} fills", n),
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "STRATEGY", "{}", BusError::lagged::<OrderRejected>(n)),
                     Err(RecvError::Closed) => break,
                 }
             }
         });
-        
-        vec![bar_handler, fill_handler]
+
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+        vec![signal_handler, fill_handler, rejected_handler]
+    }
+}
+
+/// ## `SimpleTrendFollower`
+///
+/// 一个简单的趋势跟踪策略：`TrendSignalGenerator` 与 `SignalOrderConverter` 的组合，
+/// 消费 `Bar`、`WarmupComplete`、`FillEvent`、`OrderRejected`（以及启用 VWAP 时的 `VwapUpdate`），
+/// 经由 `Signal` 生产 `OrderRequest`。需要其他信号源或多个信号源时，直接组合这两个 Actor。
+pub struct SimpleTrendFollower {
+    generator: Arc<TrendSignalGenerator>,
+    converter: Arc<SignalOrderConverter>,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl SimpleTrendFollower {
+    pub fn new(bus: MessageBus, symbol: String) -> Self {
+        Self {
+            generator: Arc::new(TrendSignalGenerator::new(bus.clone(), symbol.clone())),
+            converter: Arc::new(SignalOrderConverter::new(bus, symbol)),
+            barrier: Mutex::new(None),
+        }
     }
-}
\ No newline at end of file
+
+    /// 只使用周期为 `timeframe` 的 `Bar`，见 `TrendSignalGenerator::with_timeframe`。
+    pub fn with_timeframe(self, timeframe: Duration) -> Self {
+        self.map_generator(|generator| generator.with_timeframe(timeframe))
+    }
+
+    /// 以 VWAP 代替固定阈值生成信号，见 `TrendSignalGenerator::with_vwap_signal`。
+    pub fn with_vwap_signal(self) -> Self {
+        self.map_generator(TrendSignalGenerator::with_vwap_signal)
+    }
+
+    /// 以简单移动平均代替固定阈值生成信号，见 `TrendSignalGenerator::with_sma_signal`。
+    pub fn with_sma_signal(self, period: usize) -> Self {
+        self.map_generator(|generator| generator.with_sma_signal(period))
+    }
+
+    /// 以订单流不平衡确认信号，见 `TrendSignalGenerator::with_order_flow_signal`。
+    pub fn with_order_flow_signal(self, min_imbalance: f64) -> Self {
+        self.map_generator(|generator| generator.with_order_flow_signal(min_imbalance))
+    }
+
+    /// 设置启动屏障，两个组成部分都完成订阅后调用 `ready`。
+    pub fn with_startup_barrier(mut self, barrier: StartupBarrierHandle) -> Self {
+        self.barrier = Mutex::new(Some(barrier));
+        self
+    }
+
+    fn map_generator(mut self, f: impl FnOnce(TrendSignalGenerator) -> TrendSignalGenerator) -> Self {
+        let generator = Arc::into_inner(self.generator).expect("SimpleTrendFollower is configured before start");
+        self.generator = Arc::new(f(generator));
+        self
+    }
+
+    /// 是否已收到 `WarmupComplete`。
+    pub fn is_warmed_up(&self) -> bool {
+        self.generator.is_warmed_up()
+    }
+
+    /// 当前净持仓。
+    pub fn position(&self) -> f64 {
+        self.converter.position()
+    }
+
+    /// 被执行端拒绝的订单数量。
+    pub fn rejected_orders(&self) -> usize {
+        self.converter.rejected_orders()
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for SimpleTrendFollower {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        // 先启动下单部分，信号发布时已有订阅者
+        let mut handles = self.converter.clone().start(actor_name).await;
+        handles.extend(self.generator.clone().start(actor_name).await);
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(actor_name);
+        }
+        handles
+    }
+}
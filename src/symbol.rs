@@ -0,0 +1,72 @@
+// src/symbol.rs
+
+//! # Symbol 模块 (symbol)
+//!
+//! 不同数据源对同一标的使用不同的写法（`"BTC-USD"`、`"BTCUSD"`、`"BTC/USD"`）。
+//! `SymbolRegistry` 在数据接入边界把它们统一为规范形式，使系统内部的消息只使用规范 symbol。
+
+use std::collections::HashMap;
+
+/// 规范 symbol 中基础资产与计价资产之间的分隔符。
+const CANONICAL_SEPARATOR: char = '-';
+
+/// 接入时视为分隔符的字符，都会被替换为 `CANONICAL_SEPARATOR`。
+const SEPARATORS: [char; 4] = ['-', '/', '_', ':'];
+
+/// ## `SymbolRegistry`
+///
+/// 将任意写法的 symbol 解析为规范形式：
+/// 1. 去除首尾空白并转为大写，分隔符统一为 `-`（`"btc/usd"` → `"BTC-USD"`）。
+/// 2. 查找别名表。`register` 的规范 symbol 会自动登记去掉分隔符的写法（`"BTCUSD"`）。
+///
+/// 未登记的 symbol 只做第 1 步的规范化。
+#[derive(Clone, Debug, Default)]
+pub struct SymbolRegistry {
+    aliases: HashMap<String, String>,
+}
+
+impl SymbolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 大小写与分隔符的规范化，不查找别名。
+    pub fn normalize(raw: &str) -> String {
+        raw.trim()
+            .chars()
+            .map(|c| if SEPARATORS.contains(&c) { CANONICAL_SEPARATOR } else { c.to_ascii_uppercase() })
+            .collect()
+    }
+
+    /// 登记一个规范 symbol，同时登记其去掉分隔符的写法。
+    pub fn register(&mut self, canonical: &str) -> &mut Self {
+        let canonical = Self::normalize(canonical);
+        let compact: String = canonical.chars().filter(|c| *c != CANONICAL_SEPARATOR).collect();
+        self.aliases.insert(compact, canonical.clone());
+        self.aliases.insert(canonical.clone(), canonical);
+        self
+    }
+
+    /// 登记一个别名，例如交易所特有的 `"XBTUSD"` → `"BTC-USD"`。
+    pub fn alias(&mut self, alias: &str, canonical: &str) -> &mut Self {
+        self.register(canonical);
+        self.aliases.insert(Self::normalize(alias), Self::normalize(canonical));
+        self
+    }
+
+    /// 解析为规范 symbol。
+    pub fn resolve(&self, raw: &str) -> String {
+        let normalized = Self::normalize(raw);
+        self.aliases.get(&normalized).cloned().unwrap_or(normalized)
+    }
+
+    /// 规范 symbol 的计价资产，例如 `"BTC-USD"` → `"USD"`。没有分隔符时返回空字符串。
+    pub fn quote_currency(symbol: &str) -> &str {
+        symbol.rsplit_once(CANONICAL_SEPARATOR).map_or("", |(_, quote)| quote)
+    }
+
+    /// 是否为已登记的 symbol 或别名。
+    pub fn is_known(&self, raw: &str) -> bool {
+        self.aliases.contains_key(&Self::normalize(raw))
+    }
+}
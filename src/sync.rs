@@ -0,0 +1,54 @@
+// src/sync.rs
+
+//! # 通道表的锁 (sync)
+//!
+//! `MessageBus` 的通道表通过这里的 `RwLock` 加锁。默认就是 `tokio::sync::RwLock`；
+//! 启用 `loom` feature 时换成包装 `loom::sync::RwLock` 的同名类型，接口保持一致，
+//! 使 `tests/concurrency.rs` 能在 `loom::model` 中驱动真实的 `subscribe` / `try_subscribe`，
+//! 穷举线程交错来检查双重检查锁定与订阅者上限。
+//!
+//! loom 的锁只能在 `loom::model` 内使用，因此启用该 feature 时只应运行模型检查：
+//!
+//! ```bash
+//! cargo test --features loom --test concurrency --release
+//! ```
+
+#[cfg(not(feature = "loom"))]
+pub(crate) use tokio::sync::RwLock;
+
+#[cfg(feature = "loom")]
+pub(crate) use self::loom_lock::RwLock;
+
+#[cfg(feature = "loom")]
+mod loom_lock {
+    use loom::sync::{RwLockReadGuard, RwLockWriteGuard};
+
+    /// ## `RwLock`
+    ///
+    /// 以 `tokio::sync::RwLock` 的接口包装 loom 的读写锁：`read` / `write` 在 loom 线程上阻塞等待，
+    /// 由 `loom::future::block_on` 驱动的 future 因此与 tokio 版本走相同的加锁顺序。
+    #[derive(Debug)]
+    pub(crate) struct RwLock<T>(loom::sync::RwLock<T>);
+
+    /// `try_read` 在写锁被占用时返回的错误，对应 `tokio::sync::TryLockError`。
+    #[derive(Debug)]
+    pub(crate) struct TryLockError;
+
+    impl<T> RwLock<T> {
+        pub(crate) fn new(value: T) -> Self {
+            Self(loom::sync::RwLock::new(value))
+        }
+
+        pub(crate) async fn read(&self) -> RwLockReadGuard<'_, T> {
+            self.0.read().unwrap()
+        }
+
+        pub(crate) async fn write(&self) -> RwLockWriteGuard<'_, T> {
+            self.0.write().unwrap()
+        }
+
+        pub(crate) fn try_read(&self) -> Result<RwLockReadGuard<'_, T>, TryLockError> {
+            self.0.try_read().map_err(|_| TryLockError)
+        }
+    }
+}
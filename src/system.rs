@@ -0,0 +1,479 @@
+// src/system.rs
+
+//! # Actor 系统模块 (system)
+//!
+//! 以声明的方式组装 Actor：每个 Actor 登记时注明它发布和订阅的消息类型，
+//! `ActorSystemBuilder::build` 检查每个被订阅的类型都有发布者，在启动之前发现接线错误。
+//! 启动后可以通过 `ActorSystemHandle::restart` 单独重启某个 Actor，并检查它在旧上下文中的订阅是否都已释放。
+//! `ActorSystemHandle::shutdown` 按与启动相同的依赖顺序逐个停止 Actor：发布者先停止并排空，
+//! 下游的订阅者仍在运行，能处理完发布者最后产生的消息。
+
+use crate::actor::{spawn_run, Actor, ActorContext, Affinity, ReceiverLeases, ShutdownToken};
+use crate::bus::MessageBus;
+use crate::message::Message;
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::{Handle, Runtime};
+use tokio::task::JoinHandle;
+
+/// 专用运行时的默认工作线程数。
+const DEFAULT_DEDICATED_THREADS: usize = 1;
+
+/// `ActorSystemHandle::shutdown` 向每个 Actor 发出停止信号后等待它自行退出的时间，超时的任务被中止。
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(1);
+
+/// `ActorSystemHandle::restart` 中止旧任务后等待其订阅被丢弃的时间。
+const RESTART_GRACE: Duration = Duration::from_millis(200);
+
+/// 专用运行时线程的名称。
+pub const DEDICATED_THREAD_NAME: &str = "actor-dedicated";
+
+/// 专用线程启动时调用的钩子。
+type ThreadStartHook = Arc<dyn Fn() + Send + Sync>;
+
+/// ## `WiringError`
+///
+/// `ActorSystemBuilder::build` 发现的接线错误。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WiringError {
+    /// 同名的 Actor 被登记了两次。
+    DuplicateActor(String),
+    /// `actor` 订阅的消息类型没有任何登记的发布者。
+    NoPublisher { actor: String, message_type: String },
+}
+
+impl fmt::Display for WiringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WiringError::DuplicateActor(name) => write!(f, "actor {} registered twice", name),
+            WiringError::NoPublisher { actor, message_type } => {
+                write!(f, "actor {} subscribes to {} but no actor publishes it", actor, message_type)
+            }
+        }
+    }
+}
+
+impl Error for WiringError {}
+
+/// ## `RestartError`
+///
+/// `ActorSystemHandle::restart` 的错误。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestartError {
+    /// 系统中没有这个名称的 Actor。
+    UnknownActor(String),
+}
+
+impl fmt::Display for RestartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RestartError::UnknownActor(name) => write!(f, "no actor named {}", name),
+        }
+    }
+}
+
+impl Error for RestartError {}
+
+/// 一个登记的 Actor 及其声明的消息类型。
+struct ActorEntry {
+    name: String,
+    actor: Arc<dyn Actor>,
+    publishes: Vec<TypeId>,
+    subscribes: Vec<TypeId>,
+}
+
+/// ## `ActorSystemBuilder`
+///
+/// ```ignore
+/// let system = ActorSystemBuilder::new(bus.clone())
+///     .message::<Bar>()
+///     .actor("DATA", data_engine, &[TypeId::of::<Bar>()], &[])
+///     .actor("STRATEGY", strategy, &[TypeId::of::<OrderRequest>()], &[TypeId::of::<Bar>()])
+///     .build()?;
+/// let handle = system.start().await;
+/// ```
+///
+/// `TypeId` 本身没有可读的名字，通过 `message::<M>()` 登记后，错误信息与拓扑图中会显示类型名。
+///
+/// `Actor::affinity` 为 `Affinity::Dedicated` 的 Actor 在一个独立的多线程运行时上启动，
+/// 其余 Actor 运行在调用 `start` 的运行时上。
+pub struct ActorSystemBuilder {
+    bus: MessageBus,
+    actors: Vec<ActorEntry>,
+    type_names: HashMap<TypeId, &'static str>,
+    dedicated_threads: usize,
+    on_dedicated_thread_start: Option<ThreadStartHook>,
+}
+
+impl ActorSystemBuilder {
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            actors: Vec::new(),
+            type_names: HashMap::new(),
+            dedicated_threads: DEFAULT_DEDICATED_THREADS,
+            on_dedicated_thread_start: None,
+        }
+    }
+
+    /// 设置专用运行时的工作线程数，默认 1。
+    pub fn with_dedicated_threads(mut self, threads: usize) -> Self {
+        self.dedicated_threads = threads.max(1);
+        self
+    }
+
+    /// 在每个专用线程启动时调用 `hook`，例如绑定 CPU 核心或提高线程优先级。
+    pub fn on_dedicated_thread_start<F>(mut self, hook: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.on_dedicated_thread_start = Some(Arc::new(hook));
+        self
+    }
+
+    /// 登记消息类型 `M` 的可读名称（不含模块路径）。
+    pub fn message<M: Message>(mut self) -> Self {
+        let full = std::any::type_name::<M>();
+        let short = full.rsplit("::").next().unwrap_or(full);
+        self.type_names.insert(TypeId::of::<M>(), short);
+        self
+    }
+
+    /// 登记一个 Actor 以及它发布、订阅的消息类型。
+    pub fn actor(mut self, name: &str, actor: Arc<dyn Actor>, publishes: &[TypeId], subscribes: &[TypeId]) -> Self {
+        self.actors.push(ActorEntry {
+            name: name.to_string(),
+            actor,
+            publishes: publishes.to_vec(),
+            subscribes: subscribes.to_vec(),
+        });
+        self
+    }
+
+    /// 校验接线并确定启动顺序。
+    pub fn build(self) -> Result<ActorSystem, WiringError> {
+        let mut names = HashSet::new();
+        for entry in &self.actors {
+            if !names.insert(entry.name.as_str()) {
+                return Err(WiringError::DuplicateActor(entry.name.clone()));
+            }
+        }
+
+        let published: HashSet<TypeId> = self.actors.iter().flat_map(|entry| entry.publishes.iter().copied()).collect();
+        for entry in &self.actors {
+            if let Some(missing) = entry.subscribes.iter().find(|type_id| !published.contains(type_id)) {
+                return Err(WiringError::NoPublisher {
+                    actor: entry.name.clone(),
+                    message_type: type_label(&self.type_names, missing),
+                });
+            }
+        }
+
+        let start_order = start_order(&self.actors);
+        Ok(ActorSystem {
+            bus: self.bus,
+            actors: self.actors,
+            type_names: self.type_names,
+            start_order,
+            dedicated_threads: self.dedicated_threads,
+            on_dedicated_thread_start: self.on_dedicated_thread_start,
+        })
+    }
+}
+
+/// 按依赖顺序（发布者先于订阅者）排列 Actor 的下标。
+/// 存在环（例如策略与执行引擎互相订阅）时，环内的 Actor 按登记顺序启动。
+fn start_order(actors: &[ActorEntry]) -> Vec<usize> {
+    // depends_on[i]：i 订阅、且由其他 Actor 发布的消息的发布者
+    let depends_on: Vec<HashSet<usize>> = actors
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            actors
+                .iter()
+                .enumerate()
+                .filter(|(j, publisher)| *j != i && publisher.publishes.iter().any(|t| entry.subscribes.contains(t)))
+                .map(|(j, _)| j)
+                .collect()
+        })
+        .collect();
+
+    let mut started = vec![false; actors.len()];
+    let mut order = Vec::with_capacity(actors.len());
+    while order.len() < actors.len() {
+        let next = (0..actors.len())
+            .filter(|i| !started[*i])
+            .find(|i| depends_on[*i].iter().all(|j| started[*j]))
+            // 只剩环：按登记顺序打破
+            .or_else(|| (0..actors.len()).find(|i| !started[*i]))
+            .expect("an actor remains to be started");
+        started[next] = true;
+        order.push(next);
+    }
+    order
+}
+
+fn type_label(type_names: &HashMap<TypeId, &'static str>, type_id: &TypeId) -> String {
+    type_names.get(type_id).map(|name| name.to_string()).unwrap_or_else(|| format!("{:?}", type_id))
+}
+
+/// ## `ActorSystem`
+///
+/// 通过校验的一组 Actor。
+pub struct ActorSystem {
+    bus: MessageBus,
+    actors: Vec<ActorEntry>,
+    type_names: HashMap<TypeId, &'static str>,
+    start_order: Vec<usize>,
+    dedicated_threads: usize,
+    on_dedicated_thread_start: Option<ThreadStartHook>,
+}
+
+impl ActorSystem {
+    /// 系统使用的总线。
+    pub fn bus(&self) -> &MessageBus {
+        &self.bus
+    }
+
+    /// 启动顺序中的 Actor 名称。
+    pub fn start_order(&self) -> Vec<&str> {
+        self.start_order.iter().map(|i| self.actors[*i].name.as_str()).collect()
+    }
+
+    /// `ActorSystemHandle::shutdown` 停止 Actor 的顺序，与启动顺序相同：发布者先于订阅者停止。
+    pub fn shutdown_order(&self) -> Vec<&str> {
+        self.start_order()
+    }
+
+    /// 按依赖顺序启动所有 Actor。
+    ///
+    /// 每个 Actor 的 `Actor::run` 作为一个任务派生，上下文中带有系统总线、登记的名称，
+    /// 以及该 Actor 自己的停止信号（系统停止信号的 `child`）；等它就绪（`ActorContext::ready`）后才启动下一个。
+    /// 发布者先于订阅者启动。一启动就开始发布的数据源应配合 `StartupBarrier`，
+    /// 等所有订阅者就绪后再发布，否则最早的消息可能无人接收。
+    ///
+    /// 存在 `Affinity::Dedicated` 的 Actor 时会创建专用运行时，这些 Actor 的 `run`（以及兼容层中的 `start`）在其上执行，
+    /// 因此它们 `tokio::spawn` 的任务也运行在专用线程上。
+    /// 专用运行时无法创建时记录错误，并退回到共享运行时。
+    pub async fn start(&self) -> ActorSystemHandle {
+        let runtime = if self.actors.iter().any(|entry| entry.actor.affinity() == Affinity::Dedicated) {
+            self.build_dedicated_runtime()
+        } else {
+            None
+        };
+
+        let shutdown = ShutdownToken::new();
+        let mut handles = Vec::new();
+        let mut running = Vec::new();
+        for i in &self.start_order {
+            let entry = &self.actors[*i];
+            let actor = entry.actor.clone();
+            let actor_shutdown = shutdown.child();
+            let ctx = ActorContext::new(self.bus.clone(), &entry.name).with_shutdown(actor_shutdown.clone());
+            let receivers = ctx.receiver_leases();
+            let spawner = match (&runtime, actor.affinity()) {
+                (Some(runtime), Affinity::Dedicated) => {
+                    tracing::info!(target: "SYSTEM", "Starting {} on the dedicated runtime", entry.name);
+                    runtime.handle().clone()
+                },
+                _ => {
+                    tracing::info!(target: "SYSTEM", "Starting {}", entry.name);
+                    Handle::current()
+                },
+            };
+            handles.push(spawn_run(actor.clone(), ctx, &spawner).await);
+            running.push(RunningActor { name: entry.name.clone(), actor, spawner, receivers, shutdown: actor_shutdown });
+        }
+        self.warn_unpublished_subscriptions().await;
+        ActorSystemHandle {
+            bus: self.bus.clone(),
+            handles,
+            running,
+            shutdown,
+            runtime: runtime.map(|runtime| DedicatedRuntime(Some(runtime))),
+        }
+    }
+
+    /// 总线上有订阅者、但没有任何登记的 Actor 声明发布的消息类型。
+    /// 常见原因是 Actor 订阅了未在 `actor(..)` 中声明的类型，或者消息由系统之外的代码发布。
+    /// 只有订阅已经发生（即 `start` 之后）才能检测到。
+    pub async fn unpublished_subscriptions(&self) -> Vec<&'static str> {
+        let published: HashSet<TypeId> = self.actors.iter().flat_map(|entry| entry.publishes.iter().copied()).collect();
+        self.bus
+            .channel_stats()
+            .await
+            .into_iter()
+            .filter(|(type_id, _, stats)| stats.subscribers > 0 && !published.contains(type_id))
+            .map(|(_, type_name, _)| type_name)
+            .collect()
+    }
+
+    async fn warn_unpublished_subscriptions(&self) {
+        for type_name in self.unpublished_subscriptions().await {
+            tracing::warn!(target: "SYSTEM", "{} has subscribers but no registered actor publishes it", type_name);
+        }
+    }
+
+    fn build_dedicated_runtime(&self) -> Option<Runtime> {
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        builder.worker_threads(self.dedicated_threads).thread_name(DEDICATED_THREAD_NAME).enable_all();
+        if let Some(hook) = self.on_dedicated_thread_start.clone() {
+            builder.on_thread_start(move || hook());
+        }
+        match builder.build() {
+            Ok(runtime) => Some(runtime),
+            Err(e) => {
+                tracing::error!(target: "SYSTEM", "Failed to build dedicated runtime, using the shared one: {}", e);
+                None
+            },
+        }
+    }
+
+    /// ## `topology`
+    ///
+    /// DOT 格式的接线图：节点为 Actor，边从发布者指向订阅者，标签为消息类型。
+    /// 可以用 `dot -Tsvg` 渲染。
+    pub fn topology(&self) -> String {
+        let mut dot = String::from("digraph actors {\n");
+        for entry in &self.actors {
+            let _ = writeln!(dot, "    \"{}\";", entry.name);
+        }
+        for publisher in &self.actors {
+            for subscriber in &self.actors {
+                for type_id in publisher.publishes.iter().filter(|t| subscriber.subscribes.contains(t)) {
+                    let _ = writeln!(
+                        dot,
+                        "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                        publisher.name,
+                        subscriber.name,
+                        type_label(&self.type_names, type_id)
+                    );
+                }
+            }
+        }
+        dot.push('}');
+        dot
+    }
+}
+
+/// ## `ActorSystemHandle`
+///
+/// 已启动的系统：每个 Actor 的 `run` 任务句柄、共用的停止信号，以及专用运行时（如果有）。
+pub struct ActorSystemHandle {
+    bus: MessageBus,
+    handles: Vec<JoinHandle<()>>,
+    /// 与 `handles` 一一对应，重启时用来重新派生 `run`。
+    running: Vec<RunningActor>,
+    shutdown: ShutdownToken,
+    runtime: Option<DedicatedRuntime>,
+}
+
+/// 一个已启动的 Actor：登记的名称、实例、所在运行时、当前上下文通过 `ActorContext::subscribe` 发放的订阅，
+/// 以及它自己的停止信号。
+struct RunningActor {
+    name: String,
+    actor: Arc<dyn Actor>,
+    spawner: Handle,
+    receivers: ReceiverLeases,
+    shutdown: ShutdownToken,
+}
+
+/// 持有专用运行时。在异步上下文中直接丢弃 `Runtime` 会 panic，因此改为后台关闭。
+struct DedicatedRuntime(Option<Runtime>);
+
+impl Drop for DedicatedRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl ActorSystemHandle {
+    /// 每个 Actor 的 `run` 任务句柄，按启动顺序排列。
+    pub fn handles(&self) -> &[JoinHandle<()>] {
+        &self.handles
+    }
+
+    /// 整个系统共用的停止信号。直接调用它的 `shutdown` 会同时停止所有 Actor，不保证顺序；
+    /// 需要按依赖顺序停止时使用 `ActorSystemHandle::shutdown`。
+    pub fn shutdown_token(&self) -> &ShutdownToken {
+        &self.shutdown
+    }
+
+    /// ## `restart`
+    ///
+    /// 中止名为 `name` 的 Actor 的 `run` 任务，以新的 `ActorContext`（沿用该 Actor 的停止信号）重新派生，
+    /// 等它再次就绪后返回。
+    ///
+    /// 旧上下文通过 `ActorContext::subscribe` 发放的 `Receiver` 应随旧任务一起被丢弃；
+    /// 等待 `RESTART_GRACE` 后仍存活的（例如被保存在 Actor 结构体中）记录警告，其消息类型名作为返回值，
+    /// 这些订阅会继续占用通道并增加每次发布的克隆开销。
+    pub async fn restart(&mut self, name: &str) -> Result<Vec<&'static str>, RestartError> {
+        let i = self
+            .running
+            .iter()
+            .position(|running| running.name == name)
+            .ok_or_else(|| RestartError::UnknownActor(name.to_string()))?;
+
+        self.handles[i].abort();
+        let _ = (&mut self.handles[i]).await;
+        let leaked = wait_for_receivers(&self.running[i].receivers, RESTART_GRACE).await;
+        if !leaked.is_empty() {
+            tracing::warn!(target: "SYSTEM", "{} still holds receivers after restart: {:?}", name, leaked);
+        }
+
+        let running = &mut self.running[i];
+        tracing::info!(target: "SYSTEM", "Restarting {}", running.name);
+        let ctx = ActorContext::new(self.bus.clone(), &running.name).with_shutdown(running.shutdown.clone());
+        running.receivers = ctx.receiver_leases();
+        self.handles[i] = spawn_run(running.actor.clone(), ctx, &running.spawner).await;
+        Ok(leaked)
+    }
+
+    /// ## `shutdown`
+    ///
+    /// 按启动顺序（发布者先于订阅者，见 `ActorSystem::shutdown_order`）逐个停止 Actor：
+    /// 向一个 Actor 发出它自己的停止信号，等它自行退出（最多 `SHUTDOWN_GRACE`，超时则中止），再停止下一个。
+    /// 发布者退出前发布的消息仍有订阅者接收，订阅者应在收到停止信号后处理完已缓冲的消息再退出。
+    /// 互相订阅的环内按登记顺序停止。
+    ///
+    /// 全部停止后，仍未丢弃 `ActorContext::subscribe` 订阅的 Actor 记录警告，最后关闭专用运行时。
+    pub async fn shutdown(mut self) {
+        for (handle, running) in self.handles.iter_mut().zip(&self.running) {
+            tracing::info!(target: "SYSTEM", "Stopping {}", running.name);
+            running.shutdown.shutdown();
+            if tokio::time::timeout(SHUTDOWN_GRACE, &mut *handle).await.is_err() {
+                tracing::warn!(target: "SYSTEM", "{} did not stop within {:?}, aborting it", running.name, SHUTDOWN_GRACE);
+                handle.abort();
+                let _ = handle.await;
+            }
+        }
+        self.shutdown.shutdown();
+        for running in &self.running {
+            let leaked = running.receivers.live();
+            if !leaked.is_empty() {
+                tracing::warn!(target: "SYSTEM", "{} still holds receivers after shutdown: {:?}", running.name, leaked);
+            }
+        }
+        drop(self.runtime);
+    }
+}
+
+/// 等待 `receivers` 中的订阅全部被丢弃，最多 `grace`，返回届时仍存活的消息类型名。
+/// 中止的任务在其所在运行时上被丢弃，订阅因此可能稍晚才释放。
+async fn wait_for_receivers(receivers: &ReceiverLeases, grace: Duration) -> Vec<&'static str> {
+    let deadline = tokio::time::Instant::now() + grace;
+    loop {
+        let live = receivers.live();
+        if live.is_empty() || tokio::time::Instant::now() >= deadline {
+            return live;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
@@ -0,0 +1,295 @@
+// src/testkit.rs
+
+//! # 测试工具模块 (testkit)
+//!
+//! 为 Actor 的测试提供确定的观察手段，代替“真实总线 + 固定 sleep”的写法：
+//! - `TestBus` 包装 `MessageBus`，记录每条发布的消息及其类型、命名空间与时间，供事后断言。
+//! - `ActorTestHarness` 启动单个 Actor，向它注入消息，并以超时等待它发布的消息；
+//!   超时失败时列出实际看到的所有消息。
+//! - `BarBuilder` 以默认值构造测试用的 `Bar`，只设置测试关心的字段。
+
+use crate::actor::Actor;
+use crate::bus::MessageBus;
+pub use crate::bus::PublishedMessage;
+use crate::clock::{Clock, LiveClock};
+use crate::message::{Bar, Message, DEFAULT_BAR_TIMEFRAME};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// 已发布消息的记录，以及有新消息时的通知。
+#[derive(Default)]
+struct PublishLog {
+    messages: Mutex<Vec<PublishedMessage>>,
+    notify: Notify,
+}
+
+/// ## `TestBus`
+///
+/// 记录所有发布的消息的 `MessageBus`。通过 `bus()` 得到的总线（包括它的命名空间视图）
+/// 发布的消息都会被记录，测试中直接把它交给被测的 Actor。
+///
+/// ```ignore
+/// let test_bus = TestBus::new(64);
+/// let strategy = Arc::new(SimpleTrendFollower::new(test_bus.bus(), "BTC-USD".to_string()));
+/// // ...
+/// assert_eq!(test_bus.published::<OrderRequest>().len(), 1);
+/// ```
+#[derive(Clone)]
+pub struct TestBus {
+    bus: MessageBus,
+    log: Arc<PublishLog>,
+}
+
+impl TestBus {
+    /// 创建一条新的总线，`capacity` 的含义同 `MessageBus::new`。记录时间取自 `LiveClock`。
+    pub fn new(capacity: usize) -> Self {
+        Self::with_clock(capacity, Arc::new(LiveClock))
+    }
+
+    /// 以 `clock` 作为记录时间的来源，例如模拟中的 `VirtualClock`。
+    pub fn with_clock(capacity: usize, clock: Arc<dyn Clock>) -> Self {
+        let log = Arc::new(PublishLog::default());
+        let recorder = log.clone();
+        let bus = MessageBus::new(capacity).with_publish_tap(Arc::new(move |mut published: PublishedMessage| {
+            published.ts_nanos = clock.now_nanos();
+            recorder.messages.lock().unwrap().push(published);
+            recorder.notify.notify_waiters();
+        }));
+        Self { bus, log }
+    }
+
+    /// 被记录的总线，交给被测的 Actor 使用。
+    pub fn bus(&self) -> MessageBus {
+        self.bus.clone()
+    }
+
+    /// 按发布顺序返回所有类型为 `M` 的消息。
+    pub fn published<M: Message>(&self) -> Vec<M> {
+        self.log.messages.lock().unwrap().iter().filter_map(PublishedMessage::downcast::<M>).collect()
+    }
+
+    /// 按发布顺序返回所有已发布的消息。
+    pub fn all_published(&self) -> Vec<PublishedMessage> {
+        self.log.messages.lock().unwrap().clone()
+    }
+
+    /// 每行一条的已发布消息列表，用于断言失败时的信息。
+    pub fn describe(&self) -> String {
+        let messages = self.log.messages.lock().unwrap();
+        if messages.is_empty() {
+            return "  (nothing published)".to_string();
+        }
+        let mut out = String::new();
+        for (i, published) in messages.iter().enumerate() {
+            let _ = writeln!(out, "  #{} [{}] {}", i, published.type_name, published.debug());
+        }
+        out
+    }
+
+    /// 等待第 `skip` 条之后的下一条类型为 `M` 的消息（`skip` 从 0 起计），超时返回 `None`。
+    async fn nth<M: Message>(&self, skip: usize, timeout: Duration) -> Option<M> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // 先注册通知再检查，避免错过检查与等待之间发布的消息
+            let notified = self.log.notify.notified();
+            let found = {
+                let messages = self.log.messages.lock().unwrap();
+                messages.iter().filter(|published| published.type_id == TypeId::of::<M>()).nth(skip).cloned()
+            };
+            if let Some(published) = found {
+                return published.downcast::<M>();
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return None;
+            }
+        }
+    }
+}
+
+/// ## `ActorTestHarness`
+///
+/// 在 `TestBus` 上启动单个 Actor 的测试夹具。
+///
+/// - `send` 把消息发布到总线上，相当于上游 Actor 的输出。
+/// - `expect_message::<M>` 按顺序取出下一条尚未取出的 `M`，超时则 panic 并列出已发布的所有消息。
+///   通过 `send` 注入的消息同样会被记录，期待的通常是 Actor 自己发布的类型。
+/// - 夹具被丢弃时中止 Actor 的所有任务。
+///
+/// ```ignore
+/// let mut harness = ActorTestHarness::start(TestBus::new(64), engine).await;
+/// harness.send(order).await;
+/// let fill = harness.expect_message::<FillEvent>(Duration::from_secs(1)).await;
+/// ```
+pub struct ActorTestHarness {
+    bus: TestBus,
+    handles: Vec<JoinHandle<()>>,
+    /// 每种消息类型已被 `expect_message` 取出的数量。
+    consumed: HashMap<TypeId, usize>,
+}
+
+impl ActorTestHarness {
+    /// 启动 `actor`。返回时 Actor 已完成订阅，可以立即注入消息。
+    pub async fn start(bus: TestBus, actor: Arc<dyn Actor>) -> Self {
+        let handles = actor.start("TEST").await;
+        Self { bus, handles, consumed: HashMap::new() }
+    }
+
+    /// 夹具使用的 `TestBus`。
+    pub fn bus(&self) -> &TestBus {
+        &self.bus
+    }
+
+    /// 把 `msg` 发布到总线上。
+    pub async fn send<M: Message>(&self, msg: M) {
+        if let Err(e) = self.bus.bus.publish(msg).await {
+            panic!("failed to publish {}: {}", std::any::type_name::<M>(), e);
+        }
+    }
+
+    /// 等待下一条尚未取出的 `M`。超时时 panic，信息中列出已发布的所有消息。
+    pub async fn expect_message<M: Message>(&mut self, timeout: Duration) -> M {
+        let skip = self.consumed.get(&TypeId::of::<M>()).copied().unwrap_or(0);
+        match self.bus.nth::<M>(skip, timeout).await {
+            Some(msg) => {
+                *self.consumed.entry(TypeId::of::<M>()).or_default() += 1;
+                msg
+            },
+            None => panic!(
+                "expected a {} within {:?}, but none arrived; published so far:\n{}",
+                std::any::type_name::<M>(),
+                timeout,
+                self.bus.describe()
+            ),
+        }
+    }
+
+    /// 断言在 `within` 内没有新的 `M`（尚未被 `expect_message` 取出的）被发布。
+    pub async fn expect_no_message<M: Message>(&mut self, within: Duration) {
+        let skip = self.consumed.get(&TypeId::of::<M>()).copied().unwrap_or(0);
+        if let Some(msg) = self.bus.nth::<M>(skip, within).await {
+            panic!(
+                "expected no {} within {:?}, but got {:?}; published so far:\n{}",
+                std::any::type_name::<M>(),
+                within,
+                msg,
+                self.bus.describe()
+            );
+        }
+    }
+
+    /// 轮询 `condition` 直到它成立，用于等待 Actor 的内部状态（例如持仓）更新。
+    /// 超时时 panic，信息中列出已发布的所有消息。
+    pub async fn wait_until(&self, timeout: Duration, mut condition: impl FnMut() -> bool) {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while !condition() {
+            if tokio::time::Instant::now() >= deadline {
+                panic!("condition not met within {:?}; published so far:\n{}", timeout, self.bus.describe());
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+}
+
+impl Drop for ActorTestHarness {
+    fn drop(&mut self) {
+        for handle in &self.handles {
+            handle.abort();
+        }
+    }
+}
+
+/// ## `BarBuilder`
+///
+/// 构造测试用的 `Bar`。默认为 `BTC-USD`、`ts_event` 为 0、开高低收均为 100、成交量 1、
+/// 周期 `DEFAULT_BAR_TIMEFRAME`；只需设置测试关心的字段。每次 `build` 生成新的 `id`，
+/// 同一个构造器可以连续产出多根 `Bar`。
+///
+/// ```ignore
+/// let bar = BarBuilder::new().ts_event(3).price(101.0).build();
+/// let wide = BarBuilder::new().symbol("ETH-USD").ohlc(100.0, 106.0, 98.0, 104.0).volume(0.0).build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct BarBuilder {
+    symbol: String,
+    ts_event: u64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    timeframe: Duration,
+}
+
+impl Default for BarBuilder {
+    fn default() -> Self {
+        Self {
+            symbol: "BTC-USD".to_string(),
+            ts_event: 0,
+            open: 100.0,
+            high: 100.0,
+            low: 100.0,
+            close: 100.0,
+            volume: 1.0,
+            timeframe: DEFAULT_BAR_TIMEFRAME,
+        }
+    }
+}
+
+impl BarBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn symbol(mut self, symbol: &str) -> Self {
+        self.symbol = symbol.to_string();
+        self
+    }
+
+    pub fn ts_event(mut self, ts_event: u64) -> Self {
+        self.ts_event = ts_event;
+        self
+    }
+
+    /// 开高低收都设为 `price` 的平盘 `Bar`。
+    pub fn price(self, price: f64) -> Self {
+        self.ohlc(price, price, price, price)
+    }
+
+    pub fn ohlc(mut self, open: f64, high: f64, low: f64, close: f64) -> Self {
+        self.open = open;
+        self.high = high;
+        self.low = low;
+        self.close = close;
+        self
+    }
+
+    pub fn volume(mut self, volume: f64) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn timeframe(mut self, timeframe: Duration) -> Self {
+        self.timeframe = timeframe;
+        self
+    }
+
+    pub fn build(&self) -> Bar {
+        Bar {
+            id: Uuid::new_v4(),
+            ts_event: self.ts_event,
+            symbol: self.symbol.clone(),
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume,
+            timeframe: self.timeframe,
+        }
+    }
+}
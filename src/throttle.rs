@@ -0,0 +1,196 @@
+// src/throttle.rs
+
+//! # 下单限流模块 (throttle)
+//!
+//! 限制执行引擎接受订单的速率与每个 symbol 的未结束订单数量，
+//! 防止失控的策略循环在短时间内向交易场所发送大量订单。
+//! 所有时间都由调用方以纳秒传入（来自 `Clock`），因此回测中的行为是确定的。
+
+use crate::message::OrderRequest;
+use std::collections::{HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+/// 速率限制的统计窗口：1 秒。
+const RATE_WINDOW_NANOS: u64 = 1_000_000_000;
+
+/// ## `ThrottleMode`
+///
+/// 超出限制的订单如何处理。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ThrottleMode {
+    /// 立即以 `RejectReason::Throttled` 拒绝。
+    #[default]
+    Reject,
+    /// 每个 symbol 最多排队 `max_depth` 个订单，容量释放后按到达顺序放行；队列已满时拒绝。
+    Queue { max_depth: usize },
+}
+
+/// ## `ThrottleConfig`
+///
+/// 限流配置，值为 `None` 的限制不生效。
+#[derive(Clone, Debug, Default)]
+pub struct ThrottleConfig {
+    /// 所有 symbol 合计每秒最多接受的订单数。
+    pub max_orders_per_second: Option<usize>,
+    /// 每个 symbol 每秒最多接受的订单数。
+    pub max_orders_per_second_per_symbol: Option<usize>,
+    /// 每个 symbol 最多同时存在的未结束订单数。
+    pub max_open_orders_per_symbol: Option<usize>,
+    pub mode: ThrottleMode,
+}
+
+/// 限流的判定结果。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Admission {
+    /// 可以立即接受。
+    Admit,
+    /// 已进入等待队列。
+    Queued,
+    /// 被拒绝，附带原因说明。
+    Rejected(String),
+}
+
+/// 限流计数。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ThrottleStats {
+    /// 因限流被拒绝的订单数。
+    pub rejected: u64,
+    /// 曾进入等待队列的订单数。
+    pub queued: u64,
+}
+
+/// ## `OrderThrottle`
+///
+/// 限流状态：最近 1 秒内接受的订单时间（全局与每个 symbol）、每个 symbol 的未结束订单，以及等待队列。
+#[derive(Debug, Default)]
+pub struct OrderThrottle {
+    config: ThrottleConfig,
+    accepted: VecDeque<u64>,
+    accepted_by_symbol: HashMap<String, VecDeque<u64>>,
+    open: HashMap<String, HashSet<Uuid>>,
+    /// 每个 symbol 的等待队列，元素为 (排队序号, 订单)。
+    queues: HashMap<String, VecDeque<(u64, OrderRequest)>>,
+    queue_seq: u64,
+    stats: ThrottleStats,
+}
+
+impl OrderThrottle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    pub fn stats(&self) -> ThrottleStats {
+        self.stats
+    }
+
+    /// 等待队列中的订单总数。
+    pub fn queued_len(&self) -> usize {
+        self.queues.values().map(VecDeque::len).sum()
+    }
+
+    /// 移除 1 秒窗口之外的接受记录。
+    fn expire(&mut self, now: u64) {
+        let expired = |window: &mut VecDeque<u64>| {
+            while window.front().is_some_and(|ts| ts + RATE_WINDOW_NANOS <= now) {
+                window.pop_front();
+            }
+        };
+        expired(&mut self.accepted);
+        for window in self.accepted_by_symbol.values_mut() {
+            expired(window);
+        }
+    }
+
+    /// 在 `now` 时是否还能为 `symbol` 接受一个订单。
+    fn has_capacity(&self, symbol: &str) -> bool {
+        let within = |limit: Option<usize>, used: usize| limit.is_none_or(|limit| used < limit);
+        within(self.config.max_orders_per_second, self.accepted.len())
+            && within(
+                self.config.max_orders_per_second_per_symbol,
+                self.accepted_by_symbol.get(symbol).map_or(0, VecDeque::len),
+            )
+            && within(self.config.max_open_orders_per_symbol, self.open.get(symbol).map_or(0, HashSet::len))
+    }
+
+    fn record(&mut self, order: &OrderRequest, now: u64) {
+        self.accepted.push_back(now);
+        self.accepted_by_symbol.entry(order.symbol.clone()).or_default().push_back(now);
+        self.open.entry(order.symbol.clone()).or_default().insert(order.id);
+    }
+
+    /// 判定一个新订单。同一 symbol 已有排队订单时，新订单排在它们之后。
+    pub fn admit(&mut self, order: &OrderRequest, now: u64) -> Admission {
+        self.expire(now);
+        let queue_empty = self.queues.get(&order.symbol).is_none_or(VecDeque::is_empty);
+        if queue_empty && self.has_capacity(&order.symbol) {
+            self.record(order, now);
+            return Admission::Admit;
+        }
+        match self.config.mode {
+            ThrottleMode::Queue { max_depth } if self.queues.get(&order.symbol).map_or(0, VecDeque::len) < max_depth => {
+                self.queue_seq += 1;
+                self.queues.entry(order.symbol.clone()).or_default().push_back((self.queue_seq, order.clone()));
+                self.stats.queued += 1;
+                Admission::Queued
+            },
+            _ => {
+                self.stats.rejected += 1;
+                Admission::Rejected(format!("order rate or open-order limit reached for {}", order.symbol))
+            },
+        }
+    }
+
+    /// 一个已接受的订单结束（成交、撤销），释放它占用的未结束订单名额。
+    pub fn on_closed(&mut self, order: &OrderRequest) {
+        if let Some(open) = self.open.get_mut(&order.symbol) {
+            open.remove(&order.id);
+        }
+    }
+
+    /// 从等待队列中移除一个订单（例如被撤单）。
+    pub fn remove_queued(&mut self, order_id: Uuid) -> Option<OrderRequest> {
+        for queue in self.queues.values_mut() {
+            if let Some(idx) = queue.iter().position(|(_, order)| order.id == order_id) {
+                return queue.remove(idx).map(|(_, order)| order);
+            }
+        }
+        None
+    }
+
+    /// 按排队顺序放行所有在 `now` 时有容量的排队订单。
+    pub fn release(&mut self, now: u64) -> Vec<OrderRequest> {
+        self.expire(now);
+        let mut released = Vec::new();
+        loop {
+            // 找到队首有容量的 symbol 中排队最早的订单
+            let next = self
+                .queues
+                .iter()
+                .filter_map(|(symbol, queue)| queue.front().map(|(seq, _)| (*seq, symbol)))
+                .filter(|(_, symbol)| self.has_capacity(symbol))
+                .min()
+                .map(|(_, symbol)| symbol.clone());
+            let Some(symbol) = next else { break };
+            let Some((_, order)) = self.queues.get_mut(&symbol).and_then(VecDeque::pop_front) else { break };
+            self.record(&order, now);
+            released.push(order);
+        }
+        self.queues.retain(|_, queue| !queue.is_empty());
+        released
+    }
+
+    /// 速率窗口下一次腾出容量的时间。只有存在排队订单时才需要唤醒；
+    /// 因未结束订单数受限而排队的订单在订单结束时由 `release` 放行。
+    pub fn next_release_at(&self) -> Option<u64> {
+        if self.queues.is_empty() {
+            return None;
+        }
+        let global = self.accepted.front().filter(|_| self.config.max_orders_per_second.is_some());
+        let per_symbol = self
+            .queues
+            .keys()
+            .filter(|_| self.config.max_orders_per_second_per_symbol.is_some())
+            .filter_map(|symbol| self.accepted_by_symbol.get(symbol).and_then(VecDeque::front));
+        global.into_iter().chain(per_symbol).min().map(|ts| ts + RATE_WINDOW_NANOS)
+    }
+}
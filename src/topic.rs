@@ -0,0 +1,76 @@
+// src/topic.rs
+
+//! # 动态主题模块 (topic)
+//!
+//! 脚本与配置驱动的接线无法使用编译期泛型的 `subscribe::<M>()`。
+//! `MessageBus::register_message` 把一个字符串名称绑定到消息类型 `M` 及其序列化函数，
+//! 之后即可通过 `publish_json` / `subscribe_json` 按名称、以 JSON 收发该类型的消息，
+//! 与强类型的发布者和订阅者互通。
+
+use crate::bus::{BusError, MessageBus};
+use crate::message::Message;
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast::error::RecvError;
+
+/// 把 JSON 反序列化为具体类型后发布到给定的总线视图。
+pub(crate) type PublishJsonFn = fn(MessageBus, String) -> BoxFuture<'static, Result<usize, BusError>>;
+
+/// 订阅具体类型，并把收到的消息序列化为 JSON 流。
+pub(crate) type SubscribeJsonFn = fn(MessageBus) -> BoxFuture<'static, Result<BoxStream<'static, String>, BusError>>;
+
+/// 一个按名称登记的消息类型。
+#[derive(Clone, Copy)]
+pub(crate) struct DynamicTopic {
+    pub(crate) type_id: TypeId,
+    pub(crate) type_name: &'static str,
+    pub(crate) publish: PublishJsonFn,
+    pub(crate) subscribe: SubscribeJsonFn,
+}
+
+impl DynamicTopic {
+    pub(crate) fn of<M: Message + Serialize + DeserializeOwned>() -> Self {
+        Self {
+            type_id: TypeId::of::<M>(),
+            type_name: std::any::type_name::<M>(),
+            publish: publish_json::<M>,
+            subscribe: subscribe_json::<M>,
+        }
+    }
+}
+
+/// 多个总线视图共享的名称表。
+pub(crate) type TopicRegistry = Arc<RwLock<HashMap<String, DynamicTopic>>>;
+
+fn publish_json<M: Message + DeserializeOwned>(bus: MessageBus, json: String) -> BoxFuture<'static, Result<usize, BusError>> {
+    Box::pin(async move {
+        let msg: M = serde_json::from_str(&json).map_err(BusError::deserialize::<M>)?;
+        bus.publish(msg).await
+    })
+}
+
+fn subscribe_json<M: Message + Serialize>(bus: MessageBus) -> BoxFuture<'static, Result<BoxStream<'static, String>, BusError>> {
+    Box::pin(async move {
+        let rx = bus.try_subscribe::<M>().await?;
+        Ok(stream::unfold(rx, |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(msg) => match serde_json::to_string(&msg) {
+                        Ok(json) => return Some((json, rx)),
+                        Err(e) => tracing::warn!(target: "BUS", "Failed to serialize {}: {}", std::any::type_name::<M>(), e),
+                    },
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(target: "BUS", "JSON subscriber: {}", BusError::lagged::<M>(n))
+                    },
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+        .boxed())
+    })
+}
@@ -0,0 +1,194 @@
+// src/trace.rs
+
+//! # 消息追踪模块 (trace)
+//!
+//! 把一条消息在 Actor 之间引起的因果链连接成 `tracing` 的 span 树，例如 `Bar` → 订单 → 成交：
+//!
+//! - `MessageBus::publish` 为每条消息打开一个 `publish` span（字段 `message`、`namespace`、`correlation_id`），
+//!   它的父 span 是发布时的当前 span，并随消息一起经通道送达（`Traced`）。
+//! - 接收方用 `Receiver::recv_traced` 取得消息及其 `TraceContext`，
+//!   `Traced::handle` 在以 `publish` span 为父的 `handle` span（多一个 `actor` 字段）中运行处理函数；
+//!   处理函数中的日志与再次发布的消息都落在这个 span 之下。
+//! - 关联 ID 在因果链上保持不变：在某个处理函数内发布的消息沿用它所处理消息的 ID，
+//!   不在任何处理函数内发布的消息（例如数据源发布的 `Bar`）得到一个新的 ID。
+//!
+//! span 的 target 都是 `BUS`，级别为 INFO，可以用 `RUST_LOG` 过滤；日志输出的 span 前缀即为因果链。
+
+use crate::message::Message;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{Instrument, Span};
+
+/// 下一个新因果链的关联 ID。
+static NEXT_CORRELATION_ID: AtomicU64 = AtomicU64::new(1);
+
+tokio::task_local! {
+    /// 当前正在处理的消息的追踪上下文，由 `TraceContext::scope` 设置。
+    static CURRENT: TraceContext;
+}
+
+/// 消息类型名去掉模块路径后的部分，例如 `Bar`。
+fn short_type_name<M>() -> &'static str {
+    let name = std::any::type_name::<M>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// ## `TraceContext`
+///
+/// 一条消息的追踪上下文：所属因果链的关联 ID，以及发布（或处理）它的 span。
+#[derive(Clone, Debug)]
+pub struct TraceContext {
+    correlation_id: u64,
+    span: Span,
+}
+
+impl TraceContext {
+    /// 为即将发布的 `M` 创建上下文。在处理函数中发布时沿用其关联 ID，否则开始一条新的因果链。
+    pub(crate) fn publish<M: Message>(namespace: &str) -> Self {
+        let correlation_id =
+            current().map_or_else(|| NEXT_CORRELATION_ID.fetch_add(1, Ordering::Relaxed), |ctx| ctx.correlation_id);
+        let span = tracing::info_span!(
+            target: "BUS",
+            "publish",
+            message = short_type_name::<M>(),
+            namespace,
+            correlation_id
+        );
+        Self { correlation_id, span }
+    }
+
+    /// 所属因果链的关联 ID。
+    pub fn correlation_id(&self) -> u64 {
+        self.correlation_id
+    }
+
+    /// 对应的 span。
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// `actor` 处理这条 `M` 的上下文：关联 ID 不变，span 是 `publish` span 的子 span `handle`。
+    pub fn child<M: Message>(&self, actor: &str) -> Self {
+        let span = tracing::info_span!(
+            target: "BUS",
+            parent: &self.span,
+            "handle",
+            actor,
+            message = short_type_name::<M>(),
+            correlation_id = self.correlation_id
+        );
+        Self { correlation_id: self.correlation_id, span }
+    }
+
+    /// 在该上下文中运行 `fut`：进入它的 span，其中发布的消息沿用它的关联 ID。
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        let span = self.span.clone();
+        CURRENT.scope(self, fut.instrument(span)).await
+    }
+
+    /// `scope` 的同步版本。
+    pub fn sync_scope<R>(self, f: impl FnOnce() -> R) -> R {
+        let span = self.span.clone();
+        CURRENT.sync_scope(self, || span.in_scope(f))
+    }
+}
+
+/// 当前正在处理的消息的追踪上下文，不在任何处理函数中时为 `None`。
+pub fn current() -> Option<TraceContext> {
+    CURRENT.try_with(TraceContext::clone).ok()
+}
+
+/// 有上下文时在其中运行 `fut`，否则直接运行。
+pub async fn in_context<F: Future>(ctx: Option<TraceContext>, fut: F) -> F::Output {
+    match ctx {
+        Some(ctx) => ctx.scope(fut).await,
+        None => fut.await,
+    }
+}
+
+/// ## `Traced`
+///
+/// 通道中实际传递的内容：消息及其发布时的追踪上下文，由 `Receiver::recv_traced` 返回。
+#[derive(Clone, Debug)]
+pub struct Traced<M> {
+    pub message: M,
+    pub trace: TraceContext,
+    /// 总线收到这条消息（调用 `publish`）时的时间（纳秒），取自总线的时钟（见 `MessageBus::with_clock`）。
+    pub ts_recv: u64,
+    /// 有效期，`None` 表示不会过期（见 `MessageBus::publish_with_ttl` 与 `with_type_ttl`）。
+    pub ttl: Option<Duration>,
+    /// 消息在所在通道中的序号，由通道发送时分配；`MessageBus::purge` 据此让订阅者跳过清除之前的消息。
+    pub(crate) seq: u64,
+}
+
+impl<M> Traced<M> {
+    /// 在 `now_nanos` 时是否已过期，即 `ts_recv + ttl < now_nanos`。
+    pub fn is_expired(&self, now_nanos: u64) -> bool {
+        self.ttl.is_some_and(|ttl| {
+            let ttl_nanos = u64::try_from(ttl.as_nanos()).unwrap_or(u64::MAX);
+            self.ts_recv.saturating_add(ttl_nanos) < now_nanos
+        })
+    }
+}
+
+impl<M: Message> Traced<M> {
+    /// 在 `actor` 处理这条消息的 `handle` span 中运行 `handler(message)`。
+    ///
+    /// ```ignore
+    /// Ok(traced) => traced.handle("STRATEGY", |bar| self.handle_bar(bar)).await,
+    /// ```
+    pub async fn handle<F, Fut>(self, actor: &str, handler: F) -> Fut::Output
+    where
+        F: FnOnce(M) -> Fut,
+        Fut: Future,
+    {
+        self.trace.child::<M>(actor).scope(handler(self.message)).await
+    }
+
+    /// `handle` 的同步版本，用于不需要等待的处理函数。
+    pub fn handle_sync<R>(self, actor: &str, handler: impl FnOnce(M) -> R) -> R {
+        let message = self.message;
+        self.trace.child::<M>(actor).sync_scope(|| handler(message))
+    }
+}
+
+/// ## `OtlpGuard`
+///
+/// `otlp_layer` 返回的导出器句柄。drop 时刷新尚未导出的 span 并关闭导出器。
+#[cfg(feature = "otlp")]
+pub struct OtlpGuard {
+    provider: opentelemetry_sdk::trace::TracerProvider,
+}
+
+#[cfg(feature = "otlp")]
+impl Drop for OtlpGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("failed to shut down the OTLP exporter: {e}");
+        }
+    }
+}
+
+/// 把 span 通过 gRPC 导出到 OTLP collector 的 `tracing` 层。
+///
+/// collector 地址读取 `OTEL_EXPORTER_OTLP_ENDPOINT`，默认 `http://localhost:4317`。
+/// 必须在 tokio 运行时中调用，导出在运行时的后台任务中批量进行。
+#[cfg(feature = "otlp")]
+pub fn otlp_layer<S>(
+    service_name: &'static str,
+) -> Result<(impl tracing_subscriber::Layer<S>, OtlpGuard), opentelemetry::trace::TraceError>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::KeyValue;
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder().with_tonic().build()?;
+    let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+        .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+        .with_resource(opentelemetry_sdk::Resource::new([KeyValue::new("service.name", service_name)]))
+        .build();
+    let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer(service_name));
+    Ok((layer, OtlpGuard { provider }))
+}
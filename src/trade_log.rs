@@ -0,0 +1,192 @@
+// src/trade_log.rs
+
+//! # 交易记录模块 (trade_log)
+//!
+//! `TradeLogActor` 记录每一笔 `FillEvent`，并标注成交时的组合权益与累计已实现盈亏，供盘后分析。
+//! 累计已实现盈亏由记录器自己按成交折叠（与 `PortfolioTracker` 的计算相同，不含手续费）；
+//! 权益取成交前最近一份 `PortfolioSnapshot`，需要组合跟踪器设置 `with_initial_capital`。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::clock::{Clock, LiveClock};
+use crate::message::{FillEvent, Liquidity, OrderSide, PortfolioSnapshot, TradeLogExported};
+use crate::portfolio::Portfolio;
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+/// ## `TradeRecord`
+///
+/// 一笔成交及记录时的组合状态。
+#[derive(Clone, Debug, PartialEq)]
+pub struct TradeRecord {
+    pub fill: FillEvent,
+    /// 成交前最近一份 `PortfolioSnapshot` 的总权益，尚未收到快照时为 0。
+    pub portfolio_equity_at_fill: f64,
+    /// 计入这笔成交后所有持仓的累计已实现盈亏（不含手续费）。
+    pub running_realized_pnl: f64,
+    /// 记录的时间（纳秒）。
+    pub ts_recorded: u64,
+}
+
+/// `export_csv` 写入的一行：成交字段展开为列，后接三列标注。
+#[derive(Serialize)]
+struct TradeRow<'a> {
+    order_id: Uuid,
+    symbol: &'a str,
+    side: &'a OrderSide,
+    price: f64,
+    quantity: f64,
+    leaves_qty: f64,
+    is_final: bool,
+    commission: f64,
+    commission_currency: &'a str,
+    liquidity: Liquidity,
+    ts_event: u64,
+    venue_fill_id: Option<&'a str>,
+    correlation_id: Option<Uuid>,
+    portfolio_equity_at_fill: f64,
+    running_realized_pnl: f64,
+    ts_recorded: u64,
+}
+
+impl<'a> From<&'a TradeRecord> for TradeRow<'a> {
+    fn from(record: &'a TradeRecord) -> Self {
+        let fill = &record.fill;
+        Self {
+            order_id: fill.order_id,
+            symbol: &fill.symbol,
+            side: &fill.side,
+            price: fill.price,
+            quantity: fill.quantity,
+            leaves_qty: fill.leaves_qty,
+            is_final: fill.is_final,
+            commission: fill.commission,
+            commission_currency: &fill.commission_currency,
+            liquidity: fill.liquidity,
+            ts_event: fill.ts_event,
+            venue_fill_id: fill.venue_fill_id.as_deref(),
+            correlation_id: fill.correlation_id,
+            portfolio_equity_at_fill: record.portfolio_equity_at_fill,
+            running_realized_pnl: record.running_realized_pnl,
+            ts_recorded: record.ts_recorded,
+        }
+    }
+}
+
+/// ## `TradeLogActor`
+///
+/// 一个 Actor，把成交记录为 `TradeRecord`。
+/// - 消费 `PortfolioSnapshot` 消息，保存最近的总权益。
+/// - 消费 `FillEvent` 消息，计入累计已实现盈亏后追加一条记录。
+/// - `export_csv` 把全部记录写成 CSV，随后生产 `TradeLogExported` 消息。
+///
+/// `trade_count` 与 `total_realized_pnl` 同步读取，回测结束后可以直接用于结果汇总。
+pub struct TradeLogActor {
+    bus: MessageBus,
+    log: Arc<RwLock<Vec<TradeRecord>>>,
+    portfolio: Mutex<Portfolio>,
+    equity: Mutex<f64>,
+    clock: Arc<dyn Clock>,
+}
+
+impl TradeLogActor {
+    pub fn new(bus: MessageBus) -> Self {
+        Self {
+            bus,
+            log: Arc::new(RwLock::new(Vec::new())),
+            portfolio: Mutex::new(Portfolio::default()),
+            equity: Mutex::new(0.0),
+            clock: Arc::new(LiveClock),
+        }
+    }
+
+    /// 设置 `ts_recorded` 的时间来源。模拟模式下应传入 `SimulationDriver::clock()`。
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 已记录的成交笔数。
+    pub fn trade_count(&self) -> usize {
+        self.log.read().unwrap().len()
+    }
+
+    /// 最后一条记录的累计已实现盈亏，没有记录时为 0。
+    pub fn total_realized_pnl(&self) -> f64 {
+        self.log.read().unwrap().last().map_or(0.0, |record| record.running_realized_pnl)
+    }
+
+    /// 全部记录的副本，按收到成交的顺序排列。
+    pub fn records(&self) -> Vec<TradeRecord> {
+        self.log.read().unwrap().clone()
+    }
+
+    /// 把全部记录写成 CSV（覆盖已有文件），成功后发布 `TradeLogExported`。
+    pub async fn export_csv(&self, path: &Path) -> Result<(), io::Error> {
+        let trade_count = {
+            let log = self.log.read().unwrap();
+            let mut writer = csv::Writer::from_path(path).map_err(io::Error::other)?;
+            for record in log.iter() {
+                writer.serialize(TradeRow::from(record)).map_err(io::Error::other)?;
+            }
+            writer.flush()?;
+            log.len()
+        };
+        tracing::info!(target: "TRADE_LOG", "Exported {} trades to {}", trade_count, path.display());
+        let exported = TradeLogExported { path: path.display().to_string(), trade_count };
+        if let Err(e) = self.bus.publish(exported).await {
+            tracing::error!(target: "TRADE_LOG", "Failed to publish export event: {}", e);
+        }
+        Ok(())
+    }
+
+    fn record_fill(&self, fill: FillEvent) {
+        let running_realized_pnl = {
+            let mut portfolio = self.portfolio.lock().unwrap();
+            portfolio.apply_fill(&fill);
+            portfolio.realized_pnl()
+        };
+        let record = TradeRecord {
+            fill,
+            portfolio_equity_at_fill: *self.equity.lock().unwrap(),
+            running_realized_pnl,
+            ts_recorded: self.clock.now_nanos(),
+        };
+        self.log.write().unwrap().push(record);
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for TradeLogActor {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut fill_rx = self.bus.subscribe::<FillEvent>().await;
+        let mut snapshot_rx = self.bus.subscribe::<PortfolioSnapshot>().await;
+
+        // 两种消息在同一个任务里处理，成交时看到的是在它之前处理的最后一份快照
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                tokio::select! {
+                    result = fill_rx.recv() => match result {
+                        Ok(fill) => self.record_fill(fill),
+                        Err(RecvError::Lagged(n)) => {
+                            tracing::warn!(target: "TRADE_LOG", "{}, they are missing from the trade log", BusError::lagged::<FillEvent>(n))
+                        },
+                        Err(RecvError::Closed) => break,
+                    },
+                    result = snapshot_rx.recv() => match result {
+                        Ok(snapshot) => *self.equity.lock().unwrap() = snapshot.total_equity,
+                        Err(RecvError::Lagged(n)) => tracing::debug!(target: "TRADE_LOG", "Skipped {} portfolio snapshots", n),
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
@@ -0,0 +1,272 @@
+// src/validation.rs
+
+//! # 订单校验模块 (validation)
+//!
+//! 执行引擎在接受订单之前进行的校验。校验失败的订单会以 `OrderRejected` 回报，
+//! 而不是被静默成交或丢弃。
+
+use crate::message::{OrderRequest, OrderSide, OrderType, RejectReason, TradingHalted, TradingResumed};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// 订单 ID 默认的记忆时间。
+const DEFAULT_ORDER_ID_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 默认最多记住的订单 ID 数量。
+const DEFAULT_MAX_ORDER_IDS: usize = 100_000;
+
+/// ## `ValidationConfig`
+///
+/// 校验配置。`disabled` 中的原因不会被检查，便于宽松的测试场景。
+#[derive(Clone, Debug)]
+pub struct ValidationConfig {
+    /// 允许交易的 symbol 列表；为 `None` 时不检查 `UnknownSymbol`。
+    pub known_symbols: Option<HashSet<String>>,
+    /// 被禁用的拒绝原因。
+    pub disabled: HashSet<RejectReason>,
+    /// 已接受的订单 ID 在重复检测中保留的时间，默认 24 小时。
+    pub order_id_retention: Duration,
+    /// 最多同时记住的订单 ID 数量，默认 100,000。
+    pub max_order_ids: usize,
+    /// 为 `true` 时，与原订单完全相同的重复提交不会被拒绝，而是再次回报原订单的当前状态。
+    pub idempotent_resubmission: bool,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            known_symbols: None,
+            disabled: HashSet::new(),
+            order_id_retention: DEFAULT_ORDER_ID_RETENTION,
+            max_order_ids: DEFAULT_MAX_ORDER_IDS,
+            idempotent_resubmission: false,
+        }
+    }
+}
+
+impl ValidationConfig {
+    /// 禁用某个拒绝原因的检查。
+    pub fn disable(mut self, reason: RejectReason) -> Self {
+        self.disabled.insert(reason);
+        self
+    }
+
+    /// 设置允许交易的 symbol 列表。
+    pub fn with_known_symbols<I, S>(mut self, symbols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.known_symbols = Some(symbols.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// 设置订单 ID 的保留时间与数量上限。
+    pub fn with_order_id_window(mut self, retention: Duration, max_ids: usize) -> Self {
+        self.order_id_retention = retention;
+        self.max_order_ids = max_ids.max(1);
+        self
+    }
+
+    /// 启用幂等提交：完全相同的重复订单返回原订单的结果，而不是 `DuplicateOrderId`。
+    pub fn with_idempotent_resubmission(mut self) -> Self {
+        self.idempotent_resubmission = true;
+        self
+    }
+}
+
+/// 订单 ID 重复检测的结果。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderIdCheck {
+    /// 保留时间内未出现过的 ID。
+    New,
+    /// 与原订单完全相同的重复提交。
+    Resubmission,
+    /// ID 相同但内容不同的订单。
+    Conflict,
+    /// 记忆已满且所有 ID 都仍在保留时间内，无法确认新 ID 不是重复。
+    WindowFull,
+}
+
+/// ## `OrderIdWindow`
+///
+/// 在 `retention` 时间内记住已接受的订单 ID 及其原始请求，最多 `max_ids` 个。
+///
+/// 只有超过保留时间的 ID 会被淘汰，保留时间内的 ID 永远不会因为容量而被提前遗忘，
+/// 否则一个仍在窗口内的旧 ID 可能被错误地再次接受。记忆已满时新订单返回 `WindowFull`。
+#[derive(Debug)]
+pub struct OrderIdWindow {
+    retention: u64,
+    max_ids: usize,
+    orders: HashMap<Uuid, OrderRequest>,
+    /// (接受时间, 订单 ID)，按时间先后排列。
+    order: VecDeque<(u64, Uuid)>,
+}
+
+impl OrderIdWindow {
+    pub fn new(retention: Duration, max_ids: usize) -> Self {
+        Self {
+            retention: retention.as_nanos() as u64,
+            max_ids: max_ids.max(1),
+            orders: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// 淘汰在 `now` 时已超过保留时间的 ID。
+    fn evict_expired(&mut self, now: u64) {
+        while let Some(&(ts, id)) = self.order.front() {
+            if ts.saturating_add(self.retention) > now {
+                break;
+            }
+            self.order.pop_front();
+            self.orders.remove(&id);
+        }
+    }
+
+    /// 检查一个订单的 ID，不记录。
+    pub fn check(&mut self, order: &OrderRequest, now: u64) -> OrderIdCheck {
+        self.evict_expired(now);
+        match self.orders.get(&order.id) {
+            Some(original) if same_request(original, order) => OrderIdCheck::Resubmission,
+            Some(_) => OrderIdCheck::Conflict,
+            None if self.orders.len() >= self.max_ids => OrderIdCheck::WindowFull,
+            None => OrderIdCheck::New,
+        }
+    }
+
+    /// 记录一个已接受的订单。
+    pub fn insert(&mut self, order: &OrderRequest, now: u64) {
+        if self.orders.insert(order.id, order.clone()).is_none() {
+            self.order.push_back((now, order.id));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
+
+/// 两个请求的内容是否完全相同。
+fn same_request(a: &OrderRequest, b: &OrderRequest) -> bool {
+    a.symbol == b.symbol
+        && a.side == b.side
+        && a.order_type == b.order_type
+        && a.price == b.price
+        && a.quantity == b.quantity
+        && a.trigger_price == b.trigger_price
+}
+
+/// ## `OrderValidator`
+///
+/// 持有校验所需的状态：保留时间内已接受的订单 ID 和当前的暂停状态。
+#[derive(Debug)]
+pub struct OrderValidator {
+    config: ValidationConfig,
+    seen_ids: OrderIdWindow,
+    halted_all: bool,
+    halted_symbols: HashSet<String>,
+}
+
+impl OrderValidator {
+    pub fn new(config: ValidationConfig) -> Self {
+        let seen_ids = OrderIdWindow::new(config.order_id_retention, config.max_order_ids);
+        Self { config, seen_ids, halted_all: false, halted_symbols: HashSet::new() }
+    }
+
+    /// 是否启用了幂等提交。
+    pub fn idempotent_resubmission(&self) -> bool {
+        self.config.idempotent_resubmission
+    }
+
+    fn enabled(&self, reason: RejectReason) -> bool {
+        !self.config.disabled.contains(&reason)
+    }
+
+    /// 检查订单 ID 是否重复。`DuplicateOrderId` 被禁用时总是返回 `New`。
+    pub fn check_order_id(&mut self, order: &OrderRequest, now: u64) -> OrderIdCheck {
+        if !self.enabled(RejectReason::DuplicateOrderId) {
+            return OrderIdCheck::New;
+        }
+        self.seen_ids.check(order, now)
+    }
+
+    /// 校验一个订单，`now` 为纳秒时间戳。通过校验的订单 ID 会被记录，用于之后的重复检测。
+    /// 重复的 ID 先由 `check_order_id` 区分是幂等重放还是冲突，这里统一拒绝。
+    /// `cash` 为当前可用资金，为 `None` 时不检查 `InsufficientFunds`。
+    pub fn validate(&mut self, order: &OrderRequest, now: u64, cash: Option<f64>) -> Result<(), (RejectReason, String)> {
+        match self.check_order_id(order, now) {
+            OrderIdCheck::New => {},
+            OrderIdCheck::Resubmission | OrderIdCheck::Conflict => {
+                return Err((RejectReason::DuplicateOrderId, format!("order id {} already used", order.id)));
+            },
+            OrderIdCheck::WindowFull => {
+                return Err((
+                    RejectReason::OrderIdWindowFull,
+                    format!("{} order ids still within the duplicate window", self.seen_ids.len()),
+                ));
+            },
+        }
+        if self.enabled(RejectReason::TradingHalted) && self.is_halted(&order.symbol) {
+            return Err((RejectReason::TradingHalted, format!("trading halted for {}", order.symbol)));
+        }
+        if self.enabled(RejectReason::InvalidQuantity) && !(order.quantity.is_finite() && order.quantity > 0.0) {
+            return Err((RejectReason::InvalidQuantity, format!("quantity {} must be positive", order.quantity)));
+        }
+        if self.enabled(RejectReason::InvalidPrice) && !(order.price.is_finite() && order.price > 0.0) {
+            return Err((RejectReason::InvalidPrice, format!("price {} must be positive", order.price)));
+        }
+        if self.enabled(RejectReason::InvalidPrice) && matches!(order.order_type, OrderType::StopMarket | OrderType::StopLimit) {
+            match order.stop_price() {
+                Some(trigger) if trigger.is_finite() && trigger > 0.0 => {},
+                trigger => return Err((RejectReason::InvalidPrice, format!("trigger price {:?} must be positive", trigger))),
+            }
+        }
+        if self.enabled(RejectReason::UnknownSymbol) {
+            if let Some(known) = &self.config.known_symbols {
+                if !known.contains(&order.symbol) {
+                    return Err((RejectReason::UnknownSymbol, format!("unknown symbol {}", order.symbol)));
+                }
+            }
+        }
+        // 卖出不占用资金（允许做空），买入按参考价估算所需资金
+        if let (true, Some(cash), OrderSide::Buy) = (self.enabled(RejectReason::InsufficientFunds), cash, &order.side) {
+            let notional = order.price * order.quantity;
+            if notional > cash {
+                return Err((RejectReason::InsufficientFunds, format!("order notional {} exceeds available cash {}", notional, cash)));
+            }
+        }
+        if self.enabled(RejectReason::DuplicateOrderId) {
+            self.seen_ids.insert(order, now);
+        }
+        Ok(())
+    }
+
+    /// 某个 symbol 当前是否处于暂停状态。
+    pub fn is_halted(&self, symbol: &str) -> bool {
+        self.halted_all || self.halted_symbols.contains(symbol)
+    }
+
+    pub fn on_halt(&mut self, halt: &TradingHalted) {
+        match &halt.symbol {
+            Some(symbol) => {
+                self.halted_symbols.insert(symbol.clone());
+            }
+            None => self.halted_all = true,
+        }
+    }
+
+    pub fn on_resume(&mut self, resume: &TradingResumed) {
+        match &resume.symbol {
+            Some(symbol) => {
+                self.halted_symbols.remove(symbol);
+            }
+            None => self.halted_all = false,
+        }
+    }
+}
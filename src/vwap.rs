@@ -0,0 +1,109 @@
+// src/vwap.rs
+
+//! # VWAP 模块 (vwap)
+//!
+//! 根据逐笔成交计算滚动时间窗口内的成交量加权平均价，作为执行策略的基准。
+
+use crate::actor::{spawn_named, Actor};
+use crate::bus::{BusError, MessageBus};
+use crate::message::{TradeTick, VwapUpdate};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// ## `VwapState`
+///
+/// 单个 symbol 窗口内的成交记录 `(ts, price, volume)`，按时间先后排列。
+#[derive(Clone, Debug, Default)]
+pub struct VwapState {
+    pub history: VecDeque<(u64, f64, f64)>,
+}
+
+impl VwapState {
+    /// 加入一笔成交，并移除已超出窗口（`entry_ts + window <= ts`）的记录。
+    /// 新加入的成交本身总会保留。
+    pub fn push(&mut self, ts: u64, price: f64, volume: f64, window: Duration) {
+        self.history.push_back((ts, price, volume));
+        let window = window.as_nanos() as u64;
+        while self.history.len() > 1 && self.history.front().is_some_and(|(entry_ts, ..)| entry_ts + window <= ts) {
+            self.history.pop_front();
+        }
+    }
+
+    /// 窗口内的总成交量。
+    pub fn volume(&self) -> f64 {
+        self.history.iter().map(|(_, _, volume)| volume).sum()
+    }
+
+    /// `sum(price * volume) / sum(volume)`，窗口内没有成交量时返回 `None`。
+    pub fn vwap(&self) -> Option<f64> {
+        let volume = self.volume();
+        if volume <= 0.0 {
+            return None;
+        }
+        let notional: f64 = self.history.iter().map(|(_, price, volume)| price * volume).sum();
+        Some(notional / volume)
+    }
+}
+
+/// ## `VwapActor`
+///
+/// 一个 Actor，消费 `TradeTick` 消息，按 symbol 维护 `window` 时间窗口内的 VWAP，
+/// 每笔成交后生产一条 `VwapUpdate` 消息。窗口以成交的 `ts_event` 为准，因此在模拟中同样确定。
+pub struct VwapActor {
+    bus: MessageBus,
+    window: Duration,
+    state: Mutex<HashMap<String, VwapState>>,
+}
+
+impl VwapActor {
+    pub fn new(bus: MessageBus, window: Duration) -> Self {
+        Self { bus, window, state: Mutex::new(HashMap::new()) }
+    }
+
+    /// 某个 symbol 当前的 VWAP。
+    pub fn vwap(&self, symbol: &str) -> Option<f64> {
+        self.state.lock().unwrap().get(symbol).and_then(VwapState::vwap)
+    }
+
+    async fn handle_tick(&self, tick: TradeTick) {
+        let update = {
+            let mut state = self.state.lock().unwrap();
+            let entry = state.entry(tick.symbol.clone()).or_default();
+            entry.push(tick.ts_event, tick.price, tick.size, self.window);
+            entry.vwap().map(|vwap| VwapUpdate {
+                symbol: tick.symbol,
+                vwap,
+                window_volume: entry.volume(),
+                ts_event: tick.ts_event,
+            })
+        };
+
+        if let Some(update) = update {
+            if let Err(e) = self.bus.publish(update).await {
+                tracing::error!(target: "VWAP", "Failed to publish VWAP update: {}", e);
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for VwapActor {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut tick_rx = self.bus.subscribe::<TradeTick>().await;
+
+        let handle = spawn_named(actor_name, async move {
+            loop {
+                match tick_rx.recv().await {
+                    Ok(tick) => self.handle_tick(tick).await,
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "VWAP", "{}", BusError::lagged::<TradeTick>(n)),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        vec![handle]
+    }
+}
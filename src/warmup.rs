@@ -0,0 +1,94 @@
+// src/warmup.rs
+
+//! # 预热模块 (warmup)
+//!
+//! 在策略积累到足够的历史行情之前阻止其产生交易信号。
+
+use crate::actor::{spawn_run, Actor, ActorContext};
+use crate::bus::{BusError, MessageBus};
+use crate::message::{Bar, WarmupComplete};
+use crate::startup::StartupBarrierHandle;
+use std::sync::{Arc, Mutex};
+use tokio::runtime::Handle;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// ## `WarmupGuard`
+///
+/// 一个 Actor，统计某个 symbol 收到的 `Bar` 数量，
+/// 达到 `required_bars` 后发布一次 `WarmupComplete`。
+/// 同一 symbol 的多个策略可以共享同一个 `WarmupGuard`，因此 `new` 直接返回 `Arc`。
+pub struct WarmupGuard {
+    bus: MessageBus,
+    symbol: String,
+    required_bars: usize,
+    barrier: Mutex<Option<StartupBarrierHandle>>,
+}
+
+impl WarmupGuard {
+    pub fn new(bus: MessageBus, symbol: &str, required_bars: usize) -> Arc<Self> {
+        Arc::new(Self {
+            bus,
+            symbol: symbol.to_string(),
+            required_bars,
+            barrier: Mutex::new(None),
+        })
+    }
+
+    /// 设置启动屏障，完成所有订阅后调用 `ready`。须在 `start` 之前调用。
+    pub fn with_startup_barrier(self: Arc<Self>, barrier: StartupBarrierHandle) -> Arc<Self> {
+        *self.barrier.lock().unwrap() = Some(barrier);
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for WarmupGuard {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let ctx = ActorContext::new(self.bus.clone(), actor_name);
+        vec![spawn_run(self, ctx, &Handle::current()).await]
+    }
+
+    /// 唯一的循环直接写在 `run` 中，收到停止信号时退出。
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        let mut bar_rx = self.bus.subscribe::<Bar>().await;
+        if let Some(barrier) = self.barrier.lock().unwrap().take() {
+            barrier.ready(ctx.name());
+        }
+        ctx.ready();
+
+        let mut bars_seen = 0;
+        let mut completed = false;
+        loop {
+            let result = tokio::select! {
+                _ = ctx.shutdown_requested() => break,
+                result = bar_rx.recv() => result,
+            };
+            match result {
+                Ok(bar) => {
+                    if bar.symbol != self.symbol {
+                        continue;
+                    }
+                    bars_seen += 1;
+                    // 达到阈值后只发布一次
+                    if !completed && bars_seen >= self.required_bars {
+                        completed = true;
+                        let complete = WarmupComplete {
+                            symbol: self.symbol.clone(),
+                            bars_seen,
+                            ts_event: bar.ts_event,
+                        };
+                        info!(target: "WARMUP", "Publishing {:?}", complete);
+                        if let Err(e) = self.bus.publish(complete).await {
+                            tracing::error!(target: "WARMUP", "Failed to publish warmup complete: {}", e);
+                        }
+                    }
+                },
+                Err(RecvError::Lagged(n)) => tracing::warn!(target: "WARMUP", "{}", BusError::lagged::<Bar>(n)),
+                Err(RecvError::Closed) => break,
+            }
+        }
+        info!(target: "WARMUP", "{} stopped after {} bars", ctx.name(), bars_seen);
+    }
+}
@@ -0,0 +1,398 @@
+// src/zmq_bridge.rs
+
+//! # ZeroMQ 网桥模块 (zmq_bridge)
+//!
+//! 不经过消息中间件，直接以 ZeroMQ PUB/SUB 与外部系统（例如 C++ 行情基础设施）互通，需要启用 `zmq` feature。
+//!
+//! 每条消息是两帧的 multipart 消息：
+//!
+//! ```text
+//! [topic]   "Bar" 或 "Bar.BTC-USD"（ZmqConfig::topic_per_symbol）
+//! [payload] 按 ZmqConfig::wire_format 编码的信封（见 codec 模块）
+//! ```
+//!
+//! - 出站：PUB socket 绑定 `publish_endpoint`，发布 `with_outbound` 选定的消息类型。
+//! - 入站：SUB socket 连接 `subscribe_endpoints`，按 `topics` 前缀订阅，解码后发布到本地总线。
+//!   帧数不对、无法解码或主题与消息类型不符的消息被丢弃并计入 `ZmqStats::malformed`，不会中断接收。
+//!
+//! **慢连接者**：SUB 端的订阅需要一段时间才能传播到 PUB 端，在此之前发布的消息会被 PUB 静默丢弃。
+//! 出站线程在绑定后先等待 `slow_joiner_delay` 再发送，期间的消息在内存中排队；
+//! 需要严格不丢消息的场景应在应用层做握手或序号检查，而不是依赖这个延迟。
+//!
+//! 同一个网桥不应出站又入站同一种类型，否则两个互相订阅的网桥会把消息来回转发。
+
+use crate::actor::{spawn_named, Actor, ActorContext};
+use crate::bus::{BusError, MessageBus};
+use crate::codec::{Codec, CodecError, WireFormat, WireMessage};
+use crate::message::Message;
+use futures::future::{join_all, BoxFuture};
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+/// 阻塞的收发线程检查停止信号的间隔。
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// ## `ZmqConfig`
+///
+/// 网桥的端点、主题与 socket 选项。
+#[derive(Clone, Debug)]
+pub struct ZmqConfig {
+    /// PUB socket 绑定的端点，例如 `tcp://*:5556` 或 `inproc://md`；为 `None` 时不出站。
+    pub publish_endpoint: Option<String>,
+    /// SUB socket 连接的端点，为空时不入站。
+    pub subscribe_endpoints: Vec<String>,
+    /// SUB socket 订阅的主题前缀，例如 `"Bar"` 或 `"Bar.BTC-USD"`；为空时订阅全部。
+    pub topics: Vec<String>,
+    /// 出站主题是否带上 symbol（`Bar.BTC-USD`），默认只用类型名（`Bar`）。
+    pub topic_per_symbol: bool,
+    pub wire_format: WireFormat,
+    /// 发送高水位（`ZMQ_SNDHWM`）：每个订阅者最多排队的消息数，超出后 PUB 丢弃新消息。
+    pub send_hwm: i32,
+    /// 接收高水位（`ZMQ_RCVHWM`）。
+    pub recv_hwm: i32,
+    /// 关闭时等待未发送消息的时间（`ZMQ_LINGER`）。
+    pub linger: Duration,
+    /// 出站线程绑定后开始发送前的等待时间，见模块文档中的慢连接者。
+    pub slow_joiner_delay: Duration,
+}
+
+impl Default for ZmqConfig {
+    fn default() -> Self {
+        Self {
+            publish_endpoint: None,
+            subscribe_endpoints: Vec::new(),
+            topics: Vec::new(),
+            topic_per_symbol: false,
+            wire_format: WireFormat::default(),
+            send_hwm: 1_000,
+            recv_hwm: 1_000,
+            linger: Duration::from_secs(1),
+            slow_joiner_delay: Duration::from_millis(200),
+        }
+    }
+}
+
+/// ## `ZmqBridgeError`
+///
+/// 创建网桥时的错误。
+#[derive(Debug)]
+pub enum ZmqBridgeError {
+    /// 创建 socket、设置选项、绑定或连接失败。
+    Socket { endpoint: String, source: zmq::Error },
+    /// 选择的线上格式不可用。
+    Codec(CodecError),
+}
+
+impl fmt::Display for ZmqBridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZmqBridgeError::Socket { endpoint, source } => write!(f, "ZeroMQ socket error on {}: {}", endpoint, source),
+            ZmqBridgeError::Codec(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ZmqBridgeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ZmqBridgeError::Socket { source, .. } => Some(source),
+            ZmqBridgeError::Codec(e) => Some(e),
+        }
+    }
+}
+
+/// ## `ZmqStats`
+///
+/// 网桥的收发计数。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZmqStats {
+    /// 交给 PUB socket 的消息数（PUB 在高水位时丢弃的消息也计入）。
+    pub sent: u64,
+    /// 解码后发布到本地总线的消息数。
+    pub received: u64,
+    /// 被丢弃的畸形入站消息数。
+    pub malformed: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    sent: AtomicU64,
+    received: AtomicU64,
+    malformed: AtomicU64,
+}
+
+/// 出站转发任务共享的编码器与发送队列。
+#[derive(Clone)]
+struct Outbound {
+    codec: Arc<dyn Codec>,
+    tx: mpsc::Sender<(String, Vec<u8>)>,
+    topic_per_symbol: bool,
+}
+
+type Forwarder = fn(MessageBus, Outbound, Arc<str>) -> BoxFuture<'static, Vec<JoinHandle<()>>>;
+
+/// ## `ZmqBridge`
+///
+/// 一个 Actor，在本地总线与 ZeroMQ PUB/SUB 之间转发消息。
+/// socket 在 `new` 中创建、设置选项并绑定/连接，错误在构造时返回；
+/// 收发在各自的阻塞线程中进行（ZeroMQ socket 不能跨线程共享），每个出站类型一个异步转发任务负责编码。
+/// 通过 `run` 运行时收到停止信号后关闭 socket；直接使用 `start` 时调用 `stop` 或丢弃网桥。
+pub struct ZmqBridge {
+    bus: MessageBus,
+    codec: Arc<dyn Codec>,
+    config: ZmqConfig,
+    publisher: Mutex<Option<zmq::Socket>>,
+    subscriber: Mutex<Option<zmq::Socket>>,
+    outbound: Vec<Forwarder>,
+    running: Arc<AtomicBool>,
+    counters: Arc<Counters>,
+}
+
+impl ZmqBridge {
+    /// 在 `context` 中创建网桥的 socket。同一进程内以 `inproc://` 互通的两端必须使用同一个 `zmq::Context`。
+    pub fn new(bus: MessageBus, context: &zmq::Context, config: ZmqConfig) -> Result<Self, ZmqBridgeError> {
+        let codec: Arc<dyn Codec> = Arc::from(config.wire_format.codec().map_err(ZmqBridgeError::Codec)?);
+
+        let publisher = match &config.publish_endpoint {
+            Some(endpoint) => {
+                let socket = open_socket(context, zmq::PUB, &config, endpoint)?;
+                socket.bind(endpoint).map_err(socket_error(endpoint))?;
+                Some(socket)
+            },
+            None => None,
+        };
+
+        let subscriber = match config.subscribe_endpoints.first() {
+            Some(first) => {
+                let socket = open_socket(context, zmq::SUB, &config, first)?;
+                socket.set_rcvtimeo(POLL_INTERVAL.as_millis() as i32).map_err(socket_error(first))?;
+                if config.topics.is_empty() {
+                    socket.set_subscribe(b"").map_err(socket_error(first))?;
+                }
+                for topic in &config.topics {
+                    socket.set_subscribe(topic.as_bytes()).map_err(socket_error(first))?;
+                }
+                for endpoint in &config.subscribe_endpoints {
+                    socket.connect(endpoint).map_err(socket_error(endpoint))?;
+                }
+                Some(socket)
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            bus,
+            codec,
+            config,
+            publisher: Mutex::new(publisher),
+            subscriber: Mutex::new(subscriber),
+            outbound: Vec::new(),
+            running: Arc::new(AtomicBool::new(true)),
+            counters: Arc::new(Counters::default()),
+        })
+    }
+
+    /// 出站发布消息类型 `M`。没有配置 `publish_endpoint` 时忽略。
+    pub fn with_outbound<M: Message + Into<WireMessage>>(mut self) -> Self {
+        self.outbound.push(forward::<M>);
+        self
+    }
+
+    /// 当前的收发计数。
+    pub fn stats(&self) -> ZmqStats {
+        ZmqStats {
+            sent: self.counters.sent.load(Ordering::Relaxed),
+            received: self.counters.received.load(Ordering::Relaxed),
+            malformed: self.counters.malformed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 通知收发线程退出，它们在 `POLL_INTERVAL` 内关闭 socket（出站 socket 按 `linger` 发完剩余消息）。
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::Release);
+    }
+}
+
+/// 网桥被丢弃时同样通知收发线程退出，否则仍打开的 socket 会让 `zmq::Context` 的析构一直阻塞。
+impl Drop for ZmqBridge {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 创建 socket 并设置高水位与 linger。
+fn open_socket(
+    context: &zmq::Context,
+    kind: zmq::SocketType,
+    config: &ZmqConfig,
+    endpoint: &str,
+) -> Result<zmq::Socket, ZmqBridgeError> {
+    let socket = context.socket(kind).map_err(socket_error(endpoint))?;
+    socket.set_sndhwm(config.send_hwm).map_err(socket_error(endpoint))?;
+    socket.set_rcvhwm(config.recv_hwm).map_err(socket_error(endpoint))?;
+    socket.set_linger(config.linger.as_millis() as i32).map_err(socket_error(endpoint))?;
+    Ok(socket)
+}
+
+fn socket_error(endpoint: &str) -> impl Fn(zmq::Error) -> ZmqBridgeError + '_ {
+    move |source| ZmqBridgeError::Socket { endpoint: endpoint.to_string(), source }
+}
+
+/// 订阅 `M`，把每条消息编码后放入出站队列；转发任务以 `actor_name` 命名。
+fn forward<M: Message + Into<WireMessage>>(
+    bus: MessageBus,
+    outbound: Outbound,
+    actor_name: Arc<str>,
+) -> BoxFuture<'static, Vec<JoinHandle<()>>> {
+    Box::pin(async move {
+        let mut rx = bus.subscribe::<M>().await;
+        let handle = spawn_named(&actor_name, async move {
+            loop {
+                match rx.recv().await {
+                    Ok(msg) => {
+                        let wire: WireMessage = msg.into();
+                        let topic = match outbound.topic_per_symbol {
+                            true => format!("{}.{}", wire.type_id(), wire.symbol()),
+                            false => wire.type_id().to_string(),
+                        };
+                        match outbound.codec.encode(&wire) {
+                            Ok(payload) => {
+                                if outbound.tx.send((topic, payload)).is_err() {
+                                    break;
+                                }
+                            },
+                            Err(e) => tracing::error!(target: "ZMQ", "Failed to encode {}: {}", wire.type_id(), e),
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => tracing::warn!(target: "ZMQ", "Outbound {}", BusError::lagged::<M>(n)),
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+        vec![handle]
+    })
+}
+
+/// 出站线程：等待慢连接者后，把队列中的消息逐条发送到 PUB socket。
+fn publish_loop(
+    socket: zmq::Socket,
+    rx: mpsc::Receiver<(String, Vec<u8>)>,
+    slow_joiner_delay: Duration,
+    running: Arc<AtomicBool>,
+    counters: Arc<Counters>,
+) {
+    std::thread::sleep(slow_joiner_delay);
+    while running.load(Ordering::Acquire) {
+        let (topic, payload) = match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(frame) => frame,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        match socket.send_multipart([topic.as_bytes(), payload.as_slice()], 0) {
+            Ok(()) => {
+                counters.sent.fetch_add(1, Ordering::Relaxed);
+            },
+            Err(e) => tracing::error!(target: "ZMQ", "Failed to send {}: {}", topic, e),
+        }
+    }
+}
+
+/// 入站线程：接收 multipart 消息，解码后发布到本地总线，畸形消息计数后丢弃。
+fn subscribe_loop(
+    socket: zmq::Socket,
+    codec: Arc<dyn Codec>,
+    bus: MessageBus,
+    runtime: Handle,
+    running: Arc<AtomicBool>,
+    counters: Arc<Counters>,
+) {
+    while running.load(Ordering::Acquire) {
+        let frames = match socket.recv_multipart(0) {
+            Ok(frames) => frames,
+            Err(zmq::Error::EAGAIN) => continue,
+            Err(e) => {
+                tracing::error!(target: "ZMQ", "Receive failed, inbound bridge stopping: {}", e);
+                break;
+            },
+        };
+        let wire = match decode_frames(codec.as_ref(), &frames) {
+            Ok(wire) => wire,
+            Err(reason) => {
+                counters.malformed.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!(target: "ZMQ", "Dropped malformed message: {}", reason);
+                continue;
+            },
+        };
+        match runtime.block_on(wire.publish(&bus)) {
+            Ok(_) => {
+                counters.received.fetch_add(1, Ordering::Relaxed);
+            },
+            Err(e) => tracing::error!(target: "ZMQ", "Failed to publish inbound message: {}", e),
+        }
+    }
+}
+
+/// 校验帧数、解码负载，并确认主题的类型名与负载一致。
+fn decode_frames(codec: &dyn Codec, frames: &[Vec<u8>]) -> Result<WireMessage, String> {
+    let [topic, payload] = frames else {
+        return Err(format!("expected 2 frames, got {}", frames.len()));
+    };
+    let topic = String::from_utf8_lossy(topic);
+    let wire = codec.decode(payload).map_err(|e| format!("topic {}: {}", topic, e))?;
+    let type_name = topic.split('.').next().unwrap_or_default();
+    if type_name != wire.type_id() {
+        return Err(format!("topic {} carries a {}", topic, wire.type_id()));
+    }
+    Ok(wire)
+}
+
+#[async_trait::async_trait]
+impl Actor for ZmqBridge {
+    async fn start(self: Arc<Self>, actor_name: &str) -> Vec<JoinHandle<()>> {
+        let mut handles = Vec::new();
+
+        let publisher = self.publisher.lock().unwrap().take();
+        if let Some(socket) = publisher {
+            let (tx, rx) = mpsc::channel();
+            let outbound = Outbound { codec: self.codec.clone(), tx, topic_per_symbol: self.config.topic_per_symbol };
+            for forward in &self.outbound {
+                handles.extend(forward(self.bus.clone(), outbound.clone(), Arc::from(actor_name)).await);
+            }
+            if self.outbound.is_empty() {
+                tracing::warn!(target: "ZMQ", "No outbound message types selected, nothing will be published");
+            }
+            let delay = self.config.slow_joiner_delay;
+            let (running, counters) = (self.running.clone(), self.counters.clone());
+            handles.push(tokio::task::spawn_blocking(move || publish_loop(socket, rx, delay, running, counters)));
+        }
+
+        let subscriber = self.subscriber.lock().unwrap().take();
+        if let Some(socket) = subscriber {
+            let (codec, bus, runtime) = (self.codec.clone(), self.bus.clone(), Handle::current());
+            let (running, counters) = (self.running.clone(), self.counters.clone());
+            handles.push(tokio::task::spawn_blocking(move || {
+                subscribe_loop(socket, codec, bus, runtime, running, counters)
+            }));
+        }
+
+        handles
+    }
+
+    /// 收到停止信号后中止转发任务，并等待收发线程关闭 socket。
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        let handles = self.clone().start(ctx.name()).await;
+        ctx.ready();
+        ctx.shutdown_requested().await;
+        self.stop();
+        for handle in &handles {
+            handle.abort();
+        }
+        join_all(handles).await;
+    }
+}
@@ -0,0 +1,89 @@
+// tests/aggregator.rs
+
+//! # 多周期聚合测试
+//!
+//! 同一份 `TradeTick` 流经 `TickAggregator` 同时聚合成多个周期的 `Bar`：
+//! 每个周期按 `ts_event` 对齐到整数倍分桶，`Bar` 带各自的 `timeframe`，以窗口结束时间为 `ts_event`；
+//! 最后一个窗口在时钟越过窗口结束时关闭，其他 symbol 的成交不计入。
+
+use message_bus::actor::Actor;
+use message_bus::aggregator::TickAggregator;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::clock::VirtualClock;
+use message_bus::message::{Bar, TradeTick};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const SECOND: u64 = 1_000_000_000;
+const SYMBOL: &str = "BTC-USD";
+
+fn trade(symbol: &str, ts_event: u64, price: f64, size: f64) -> TradeTick {
+    TradeTick { id: Uuid::new_v4(), symbol: symbol.to_string(), price, size, ts_event }
+}
+
+/// 只比较聚合结果关心的字段。
+fn fields(bar: &Bar) -> (u64, Duration, f64, f64, f64, f64, f64) {
+    (bar.ts_event, bar.timeframe, bar.open, bar.high, bar.low, bar.close, bar.volume)
+}
+
+#[tokio::test]
+async fn one_tick_stream_is_bucketed_into_every_timeframe() {
+    let bus = MessageBus::new(64);
+    let mut bars = bus.subscribe::<Bar>().await;
+    let clock = Arc::new(VirtualClock::new(0));
+    let (one, five) = (Duration::from_secs(1), Duration::from_secs(5));
+    let aggregator = Arc::new(TickAggregator::new(bus.clone(), SYMBOL, &[one, five]).with_clock(clock.clone()));
+    let handles = aggregator.start("AGGREGATOR").await;
+
+    for tick in [
+        trade(SYMBOL, SECOND / 2, 100.0, 1.0),
+        trade(SYMBOL, SECOND * 6 / 5, 102.0, 2.0),
+        trade("ETH-USD", SECOND * 3 / 2, 500.0, 9.0),
+        trade(SYMBOL, SECOND * 31 / 10, 99.0, 3.0),
+        trade(SYMBOL, SECOND * 49 / 10, 101.0, 4.0),
+        trade(SYMBOL, SECOND * 11 / 2, 103.0, 5.0),
+    ] {
+        bus.publish(tick).await.unwrap();
+    }
+
+    // 成交推动关闭的窗口：4 根 1 秒 `Bar` 与 1 根 5 秒 `Bar`
+    let mut received = Vec::new();
+    for _ in 0..5 {
+        received.push(bars.recv_timeout(TIMEOUT).await.unwrap());
+    }
+    // 时钟越过最后一个窗口的结束时间后，两个周期各关闭最后一根
+    clock.advance_to(10 * SECOND);
+    for _ in 0..2 {
+        received.push(bars.recv_timeout(TIMEOUT).await.unwrap());
+    }
+    assert!(received.iter().all(|bar| bar.symbol == SYMBOL));
+
+    let by_timeframe = |timeframe: Duration| {
+        let mut bars: Vec<_> = received.iter().filter(|bar| bar.timeframe == timeframe).map(fields).collect();
+        bars.sort_by_key(|bar| bar.0);
+        bars
+    };
+    let expected = |ts_event: u64, timeframe: Duration, ohlc: [f64; 4], volume: f64| {
+        (ts_event, timeframe, ohlc[0], ohlc[1], ohlc[2], ohlc[3], volume)
+    };
+    assert_eq!(
+        by_timeframe(one),
+        [
+            expected(SECOND, one, [100.0; 4], 1.0),
+            expected(2 * SECOND, one, [102.0; 4], 2.0),
+            expected(4 * SECOND, one, [99.0; 4], 3.0),
+            expected(5 * SECOND, one, [101.0; 4], 4.0),
+            expected(6 * SECOND, one, [103.0; 4], 5.0),
+        ]
+    );
+    assert_eq!(
+        by_timeframe(five),
+        [expected(5 * SECOND, five, [100.0, 102.0, 99.0, 101.0], 10.0), expected(10 * SECOND, five, [103.0; 4], 5.0)]
+    );
+
+    for handle in handles {
+        handle.abort();
+    }
+}
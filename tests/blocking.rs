@@ -0,0 +1,58 @@
+// tests/blocking.rs
+
+//! # 阻塞接口测试
+//!
+//! 普通 `std::thread` 通过 `blocking_publish` 发布的消息送达异步订阅者，`blocking_subscribe` 的接收者可以在同步线程中接收；
+//! 在异步上下文中调用阻塞接口返回 `BusError::BlockingInAsyncContext`，总线没有运行时句柄时返回 `BusError::NoRuntime`。
+
+use message_bus::bus::{BusError, MessageBus, ReceiverExt};
+use message_bus::message::Bar;
+use message_bus::testkit::BarBuilder;
+use std::time::Duration;
+use tokio::runtime::Handle;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_plain_thread_publishes_to_an_async_subscriber() {
+    let bus = MessageBus::new(16).with_runtime(Handle::current());
+    let mut rx = bus.subscribe::<Bar>().await;
+
+    let publisher = bus.clone();
+    let thread = std::thread::spawn(move || publisher.blocking_publish(BarBuilder::new().ts_event(1).build()));
+
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    let delivered = tokio::task::spawn_blocking(move || thread.join().unwrap()).await.unwrap();
+    assert_eq!(delivered.unwrap(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_plain_thread_receives_through_blocking_subscribe() {
+    let bus = MessageBus::new(16).with_runtime(Handle::current());
+    let subscriber = bus.clone();
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+    let thread = std::thread::spawn(move || {
+        let mut rx = subscriber.blocking_subscribe::<Bar>().unwrap();
+        ready_tx.send(()).unwrap();
+        rx.blocking_recv().unwrap()
+    });
+
+    ready_rx.await.unwrap();
+    bus.publish(BarBuilder::new().ts_event(2).build()).await.unwrap();
+    let received = tokio::task::spawn_blocking(move || thread.join().unwrap()).await.unwrap();
+    assert_eq!(received.ts_event, 2);
+}
+
+#[tokio::test]
+async fn blocking_calls_are_refused_inside_the_runtime() {
+    let bus = MessageBus::new(16).with_runtime(Handle::current());
+    assert!(matches!(bus.blocking_publish(BarBuilder::new().ts_event(1).build()), Err(BusError::BlockingInAsyncContext)));
+    assert!(matches!(bus.blocking_subscribe::<Bar>(), Err(BusError::BlockingInAsyncContext)));
+}
+
+#[test]
+fn blocking_calls_need_a_runtime_handle() {
+    let bus = MessageBus::new(16);
+    assert!(matches!(bus.blocking_publish(BarBuilder::new().ts_event(1).build()), Err(BusError::NoRuntime)));
+    assert!(matches!(bus.blocking_subscribe::<Bar>(), Err(BusError::NoRuntime)));
+}
@@ -0,0 +1,162 @@
+// tests/cancel.rs
+
+//! # 撤单竞争测试
+//!
+//! 用 `VirtualClock` 精确控制撤单到达的时间：撤单早于到期的成交时取消全部剩余数量，之后不再成交；
+//! 到期时间不晚于撤单的成交先发生，撤单只作用于剩余数量；订单已全部成交、已撤销或未知时
+//! 撤单以对应原因的 `CancelRejected` 拒绝。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, Receiver, ReceiverExt, RecvTimeout};
+use message_bus::clock::VirtualClock;
+use message_bus::execution::{FillModel, LatencyModel, SimulatedExecutionEngine};
+use message_bus::message::{
+    CancelOrderRequest, CancelRejectReason, CancelRejected, FillEvent, OrderAccepted, OrderCanceled, OrderRequest,
+    OrderSide, OrderType,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+const SECOND: u64 = 1_000_000_000;
+
+fn order() -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 3.0,
+        trigger_price: None,
+    }
+}
+
+fn cancel(order: &OrderRequest) -> CancelOrderRequest {
+    CancelOrderRequest { order_id: order.id, symbol: order.symbol.clone() }
+}
+
+/// 确认没有延迟；第一笔子成交在确认 10 秒后，之后每 10 秒成交 1 个，3 个共需 30 秒。
+struct Venue {
+    bus: MessageBus,
+    clock: Arc<VirtualClock>,
+    accepted: Receiver<OrderAccepted>,
+    fills: Receiver<FillEvent>,
+    canceled: Receiver<OrderCanceled>,
+    cancel_rejected: Receiver<CancelRejected>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Venue {
+    async fn start() -> Self {
+        let bus = MessageBus::new(64);
+        let clock = Arc::new(VirtualClock::new(0));
+        let engine = SimulatedExecutionEngine::new(bus.clone())
+            .with_clock(clock.clone())
+            .with_latency(LatencyModel { fill_latency: Duration::from_secs(10), ..Default::default() })
+            .with_fill_model(FillModel::Partial { max_child_qty: 1.0, delay_between: Duration::from_secs(10) });
+        let accepted = bus.subscribe::<OrderAccepted>().await;
+        let fills = bus.subscribe::<FillEvent>().await;
+        let canceled = bus.subscribe::<OrderCanceled>().await;
+        let cancel_rejected = bus.subscribe::<CancelRejected>().await;
+        let handles = Arc::new(engine).start("EXECUTION").await;
+        Self { bus, clock, accepted, fills, canceled, cancel_rejected, handles }
+    }
+
+    /// 提交订单并等到它被确认。
+    async fn submit(&mut self, order: &OrderRequest) {
+        self.bus.publish(order.clone()).await.unwrap();
+        assert_eq!(self.accepted.recv_timeout(TIMEOUT).await.unwrap().order_id, order.id);
+    }
+
+    async fn expect_no_fill(&mut self) {
+        assert!(matches!(self.fills.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    }
+}
+
+impl Drop for Venue {
+    fn drop(&mut self) {
+        for handle in &self.handles {
+            handle.abort();
+        }
+    }
+}
+
+#[tokio::test]
+async fn cancel_before_the_first_due_fill_cancels_everything() {
+    let mut venue = Venue::start().await;
+    let order = order();
+    venue.submit(&order).await;
+
+    // 撤单在 5 秒到达，第一笔成交要到 10 秒才到期
+    venue.clock.advance_to(5 * SECOND);
+    venue.bus.publish(cancel(&order)).await.unwrap();
+    let canceled = venue.canceled.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(canceled.order_id, order.id);
+    assert_eq!(canceled.remaining_qty, 3.0);
+
+    // 原本调度的成交不会再发生
+    venue.clock.advance_to(60 * SECOND);
+    venue.expect_no_fill().await;
+}
+
+#[tokio::test]
+async fn fills_due_before_the_cancel_happen_first() {
+    let mut venue = Venue::start().await;
+    let order = order();
+    venue.submit(&order).await;
+
+    // 10 秒与 20 秒的成交都不晚于撤单到达的 20 秒；不等成交就发出撤单
+    venue.clock.advance_to(20 * SECOND);
+    venue.bus.publish(cancel(&order)).await.unwrap();
+
+    let fills = [venue.fills.recv_timeout(TIMEOUT).await.unwrap(), venue.fills.recv_timeout(TIMEOUT).await.unwrap()];
+    assert_eq!(fills.iter().map(|fill| fill.ts_event).collect::<Vec<_>>(), vec![10 * SECOND, 20 * SECOND]);
+    assert_eq!(fills[1].leaves_qty, 1.0);
+    let canceled = venue.canceled.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(canceled.remaining_qty, 1.0);
+
+    venue.clock.advance_to(60 * SECOND);
+    venue.expect_no_fill().await;
+}
+
+#[tokio::test]
+async fn cancel_after_the_last_fill_is_rejected_as_already_filled() {
+    let mut venue = Venue::start().await;
+    let order = order();
+    venue.submit(&order).await;
+
+    venue.clock.advance_to(30 * SECOND);
+    venue.bus.publish(cancel(&order)).await.unwrap();
+
+    let mut last = venue.fills.recv_timeout(TIMEOUT).await.unwrap();
+    while !last.is_final {
+        last = venue.fills.recv_timeout(TIMEOUT).await.unwrap();
+    }
+    let rejected = venue.cancel_rejected.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(rejected.order_id, order.id);
+    assert_eq!(rejected.reason, CancelRejectReason::AlreadyFilled);
+    assert!(matches!(venue.canceled.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+}
+
+#[tokio::test]
+async fn second_cancel_and_unknown_order_are_rejected() {
+    let mut venue = Venue::start().await;
+    let canceled = order();
+    venue.submit(&canceled).await;
+
+    venue.bus.publish(cancel(&canceled)).await.unwrap();
+    venue.canceled.recv_timeout(TIMEOUT).await.unwrap();
+    venue.bus.publish(cancel(&canceled)).await.unwrap();
+    let rejected = venue.cancel_rejected.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(rejected.reason, CancelRejectReason::AlreadyCanceled);
+
+    let unknown = order();
+    venue.bus.publish(cancel(&unknown)).await.unwrap();
+    let rejected = venue.cancel_rejected.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(rejected.order_id, unknown.id);
+    assert_eq!(rejected.reason, CancelRejectReason::UnknownOrder);
+}
@@ -0,0 +1,40 @@
+// tests/capacity.rs
+
+//! # 通道容量测试
+//!
+//! `MessageBus::new(0)` 与容量为 0 的按类型覆盖被提升到 `MIN_CHANNEL_CAPACITY`，
+//! 之后订阅不会 panic，发布与接收可以正常往返。
+
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::message::Message;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Ping(u32);
+impl Message for Ping {}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Pong(u32);
+impl Message for Pong {}
+
+#[tokio::test]
+async fn zero_default_capacity_is_clamped_and_round_trips() {
+    let bus = MessageBus::new(0);
+    let mut rx = bus.subscribe::<Ping>().await;
+
+    assert_eq!(bus.publish(Ping(1)).await.unwrap(), 1);
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap(), Ping(1));
+    assert_eq!(bus.publish(Ping(2)).await.unwrap(), 1);
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap(), Ping(2));
+}
+
+#[tokio::test]
+async fn zero_type_capacity_override_is_clamped() {
+    let bus = MessageBus::new(64).with_type_capacity::<Pong>(0);
+    let mut rx = bus.subscribe::<Pong>().await;
+
+    bus.publish(Pong(7)).await.unwrap();
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap(), Pong(7));
+}
@@ -0,0 +1,70 @@
+// tests/channel_hooks.rs
+
+//! # 通道创建回调测试
+//!
+//! 验证 `MessageBus::on_new_type` 在每种消息类型第一次被订阅时恰好调用一次，
+//! 无论之后在同一命名空间还是其他命名空间再订阅；`channel_exists` 只反映已有的通道，查询本身不创建通道。
+
+use message_bus::bus::MessageBus;
+use message_bus::message::{Bar, FillEvent, TradingHalted};
+use std::any::TypeId;
+use std::sync::{Arc, Mutex};
+
+/// 登记一个记录所有回调参数的回调。
+fn record_new_types(bus: &MessageBus) -> Arc<Mutex<Vec<(TypeId, &'static str)>>> {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorder = seen.clone();
+    bus.on_new_type(Arc::new(move |type_id, type_name| recorder.lock().unwrap().push((type_id, type_name))));
+    seen
+}
+
+#[tokio::test]
+async fn fires_once_per_new_type_on_first_subscribe() {
+    let bus = MessageBus::new(8);
+    let seen = record_new_types(&bus);
+
+    let _first = bus.subscribe::<Bar>().await;
+    let _second = bus.subscribe::<Bar>().await;
+    let _namespaced = bus.clone_with_prefix("paper").subscribe::<Bar>().await;
+    let _fills = bus.subscribe::<FillEvent>().await;
+
+    let seen = seen.lock().unwrap().clone();
+    assert_eq!(
+        seen,
+        vec![
+            (TypeId::of::<Bar>(), std::any::type_name::<Bar>()),
+            (TypeId::of::<FillEvent>(), std::any::type_name::<FillEvent>()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn types_created_before_registration_are_not_reported() {
+    let bus = MessageBus::new(8);
+    let _bars = bus.subscribe::<Bar>().await;
+    let seen = record_new_types(&bus);
+
+    let _more_bars = bus.subscribe::<Bar>().await;
+    // 只发布不会创建通道
+    bus.publish(TradingHalted { symbol: None, reason: "maintenance".to_string() }).await.unwrap();
+    assert!(seen.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn channel_exists_does_not_create_the_channel() {
+    let bus = MessageBus::new(8);
+    let seen = record_new_types(&bus);
+
+    assert!(!bus.channel_exists::<Bar>().await);
+    assert!(!bus.channel_exists::<Bar>().await);
+    assert!(seen.lock().unwrap().is_empty());
+
+    let bars = bus.subscribe::<Bar>().await;
+    assert!(bus.channel_exists::<Bar>().await);
+    // 通道属于订阅时的命名空间
+    assert!(!bus.clone_with_prefix("paper").channel_exists::<Bar>().await);
+    // 订阅者被丢弃后通道仍然存在
+    drop(bars);
+    assert!(bus.channel_exists::<Bar>().await);
+    assert!(!bus.channel_exists::<FillEvent>().await);
+}
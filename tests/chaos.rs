@@ -0,0 +1,103 @@
+// tests/chaos.rs
+
+//! # 故障注入测试
+//!
+//! 丢弃全部 `OrderRequest` 时模拟执行引擎不产生成交；重复全部 `OrderRequest` 时 `Deduplicator` 把它们合并为一份；
+//! 同一种子得到同一故障序列。通过 `MESSAGE_BUS_CHAOS` 启用，不需要 `chaos` feature。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::chaos::{ChaosInterceptor, ChaosRule, CHAOS_ENV};
+use message_bus::dedup::Deduplicator;
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::message::{FillEvent, OrderRequest, OrderSide, OrderType};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const QUIET: Duration = Duration::from_millis(200);
+
+fn enable_chaos() {
+    std::env::set_var(CHAOS_ENV, "1");
+}
+
+fn order() -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 1.0,
+        trigger_price: None,
+    }
+}
+
+#[tokio::test]
+async fn dropped_orders_produce_no_fills() {
+    enable_chaos();
+    let bus = MessageBus::new(64);
+    let chaos = ChaosInterceptor::new(1)
+        .with_rule::<OrderRequest>(ChaosRule { drop: 1.0, ..Default::default() })
+        .install(&bus)
+        .unwrap();
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let handles = Arc::new(SimulatedExecutionEngine::new(bus.clone())).start("EXECUTION").await;
+
+    for _ in 0..5 {
+        assert_eq!(bus.publish(order()).await.unwrap(), 0);
+    }
+    assert!(matches!(fill_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    assert_eq!(chaos.stats().dropped, 5);
+    handles.iter().for_each(|handle| handle.abort());
+}
+
+#[tokio::test]
+async fn duplicated_orders_are_collapsed_by_dedup() {
+    enable_chaos();
+    let bus = MessageBus::new(64);
+    let feed = bus.clone_with_prefix("feed");
+    let deduped = bus.clone_with_prefix("deduped");
+    // 只扰动数据源所在的视图，去重后转发的消息正常投递
+    let chaos = ChaosInterceptor::new(2)
+        .with_rule::<OrderRequest>(ChaosRule { duplicate: 1.0, ..Default::default() })
+        .in_namespace("feed")
+        .install(&bus)
+        .unwrap();
+    let mut raw_rx = feed.subscribe::<OrderRequest>().await;
+    let mut deduped_rx = deduped.subscribe::<OrderRequest>().await;
+    let handles = Arc::new(Deduplicator::<OrderRequest>::new(feed.clone(), deduped.clone(), 16)).start("DEDUP").await;
+
+    let orders = [order(), order(), order()];
+    for order in &orders {
+        feed.publish(order.clone()).await.unwrap();
+    }
+    for order in &orders {
+        assert_eq!(raw_rx.recv_timeout(QUIET).await.unwrap().id, order.id);
+        assert_eq!(raw_rx.recv_timeout(QUIET).await.unwrap().id, order.id);
+        assert_eq!(deduped_rx.recv_timeout(QUIET).await.unwrap().id, order.id);
+    }
+    assert!(matches!(deduped_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    assert_eq!(chaos.stats().duplicated, 3);
+    handles.iter().for_each(|handle| handle.abort());
+}
+
+#[tokio::test]
+async fn same_seed_injects_the_same_faults() {
+    enable_chaos();
+    let rule = ChaosRule { drop: 0.3, delay: 0.2, max_delay: Duration::from_millis(5), duplicate: 0.2 };
+    let mut outcomes = Vec::new();
+    for _ in 0..2 {
+        let bus = MessageBus::new(256);
+        let chaos = ChaosInterceptor::new(7).with_rule::<OrderRequest>(rule.clone()).install(&bus).unwrap();
+        let _rx = bus.subscribe::<OrderRequest>().await;
+        let mut delivered = Vec::new();
+        for _ in 0..50 {
+            delivered.push(bus.publish(order()).await.unwrap());
+        }
+        outcomes.push((delivered, chaos.stats()));
+    }
+    assert_eq!(outcomes[0], outcomes[1]);
+    let stats = outcomes[0].1;
+    assert!(stats.dropped > 0 && stats.delayed > 0 && stats.duplicated > 0, "{:?}", stats);
+}
@@ -0,0 +1,137 @@
+// tests/circuit_breaker.rs
+
+//! # 发布熔断测试
+//!
+//! `with_circuit_breaker` 在连续写满通道若干次后打开熔断，冷却期间发布返回 `BusError::CircuitOpen`；
+//! 冷却结束后的试探发布决定熔断关闭还是重新打开。落后的接收者以 `BusError::Lagged` 报告类型与数量。
+
+use message_bus::bus::{BusError, MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::Message;
+use std::time::{Duration, Instant};
+
+const COOLDOWN: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Quote(u32);
+impl Message for Quote {}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Other(u32);
+impl Message for Other {}
+
+/// 容量为 2、连续两次写满即熔断的总线。
+fn bus() -> MessageBus {
+    MessageBus::new(2).with_circuit_breaker::<Quote>(2, COOLDOWN)
+}
+
+#[tokio::test]
+async fn circuit_opens_after_consecutive_publishes_into_a_full_channel() {
+    let bus = bus();
+    let _slow = bus.subscribe::<Quote>().await;
+    let before = Instant::now();
+
+    // 前两条填满通道，之后两条各覆盖一条未读消息
+    for n in 0..4 {
+        assert_eq!(bus.publish(Quote(n)).await.unwrap(), 1);
+    }
+    let published = bus.publish_count();
+
+    match bus.publish(Quote(4)).await {
+        Err(BusError::CircuitOpen { type_name, since }) => {
+            assert_eq!(type_name, std::any::type_name::<Quote>());
+            assert!(since >= before && since <= Instant::now());
+        },
+        other => panic!("expected CircuitOpen, got {:?}", other),
+    }
+    assert!(matches!(bus.try_publish(Quote(5)), Err(BusError::CircuitOpen { .. })));
+    // 被拒绝的发布不计入发布计数
+    assert_eq!(bus.publish_count(), published);
+
+    // 熔断按类型记录，其他类型照常发布
+    let _other = bus.subscribe::<Other>().await;
+    assert_eq!(bus.publish(Other(0)).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn circuit_is_shared_by_namespace_views() {
+    let bus = bus();
+    let view = bus.clone_with_prefix("paper");
+    let _slow = view.subscribe::<Quote>().await;
+    for n in 0..4 {
+        view.publish(Quote(n)).await.unwrap();
+    }
+    assert!(matches!(bus.publish(Quote(4)).await, Err(BusError::CircuitOpen { .. })));
+}
+
+#[tokio::test]
+async fn circuit_closes_when_the_probe_after_cooldown_finds_room() {
+    let bus = bus();
+    let mut rx = bus.subscribe::<Quote>().await;
+    for n in 0..4 {
+        bus.publish(Quote(n)).await.unwrap();
+    }
+    assert!(matches!(bus.publish(Quote(4)).await, Err(BusError::CircuitOpen { .. })));
+
+    // 消费者赶上之后，冷却结束的试探发布成功，熔断关闭
+    rx.drain_backlog();
+    tokio::time::sleep(COOLDOWN).await;
+    assert_eq!(bus.publish(Quote(5)).await.unwrap(), 1);
+    assert_eq!(bus.publish(Quote(6)).await.unwrap(), 1);
+    assert_eq!(rx.recv_timeout(COOLDOWN).await.unwrap(), Quote(5));
+    assert_eq!(rx.recv_timeout(COOLDOWN).await.unwrap(), Quote(6));
+}
+
+#[tokio::test]
+async fn circuit_reopens_when_the_probe_still_finds_a_full_channel() {
+    let bus = bus();
+    let _slow = bus.subscribe::<Quote>().await;
+    for n in 0..4 {
+        bus.publish(Quote(n)).await.unwrap();
+    }
+    let Err(BusError::CircuitOpen { since: first, .. }) = bus.publish(Quote(4)).await else {
+        panic!("circuit should be open");
+    };
+
+    // 试探发布仍然写满通道：消息照常投递，熔断立即以新的时刻重新打开
+    tokio::time::sleep(COOLDOWN).await;
+    assert_eq!(bus.publish(Quote(5)).await.unwrap(), 1);
+    match bus.publish(Quote(6)).await {
+        Err(BusError::CircuitOpen { since, .. }) => assert!(since > first),
+        other => panic!("expected CircuitOpen, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn fork_inherits_a_closed_circuit() {
+    let bus = bus();
+    let _slow = bus.subscribe::<Quote>().await;
+    for n in 0..4 {
+        bus.publish(Quote(n)).await.unwrap();
+    }
+    assert!(matches!(bus.publish(Quote(4)).await, Err(BusError::CircuitOpen { .. })));
+
+    let fork = bus.fork();
+    let _fork_slow = fork.subscribe::<Quote>().await;
+    for n in 0..4 {
+        assert_eq!(fork.publish(Quote(n)).await.unwrap(), 1);
+    }
+    assert!(matches!(fork.publish(Quote(4)).await, Err(BusError::CircuitOpen { .. })));
+}
+
+#[tokio::test]
+async fn lagged_receiver_reports_its_type() {
+    let bus = MessageBus::new(2);
+    let mut rx = bus.subscribe::<Quote>().await;
+    for n in 0..5 {
+        bus.publish(Quote(n)).await.unwrap();
+    }
+
+    let lagged = rx.recv_timeout(COOLDOWN).await.unwrap_err();
+    assert_eq!(lagged, RecvTimeout::Lagged { type_name: std::any::type_name::<Quote>(), count: 3 });
+    let error = lagged.into_bus_error().unwrap();
+    assert!(matches!(error, BusError::Lagged { count: 3, .. }));
+    assert_eq!(error.to_string(), format!("{} receiver lagged by 3 messages", std::any::type_name::<Quote>()));
+    assert_eq!(lagged.to_string(), error.to_string());
+    assert_eq!(BusError::lagged::<Quote>(3).to_string(), error.to_string());
+    assert!(RecvTimeout::Timeout.into_bus_error().is_none());
+}
@@ -0,0 +1,135 @@
+// tests/cli.rs
+
+//! # 命令行测试
+//!
+//! `--help` 输出与 `tests/snapshots/cli_help.txt` 快照比较（设置 `UPDATE_SNAPSHOTS=1` 时重写快照）；
+//! 命令行参数覆盖配置文件后汇总为同一个 `AppConfig`，冲突的组合给出指明参数的错误。
+
+use clap::{CommandFactory, Parser};
+use message_bus::cli::Cli;
+use message_bus::config::{AppConfig, ConfigError, RunMode};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+fn parse(args: &[&str]) -> Result<AppConfig, ConfigError> {
+    Cli::try_parse_from(std::iter::once("message-bus").chain(args.iter().copied())).unwrap().into_config()
+}
+
+fn invalid(args: &[&str]) -> String {
+    match parse(args) {
+        Err(ConfigError::Invalid(reason)) => reason,
+        other => panic!("expected an invalid config for {:?}, got {:?}", args, other),
+    }
+}
+
+/// 写入临时配置文件，文件名包含进程号与测试名避免并行测试互相覆盖。
+fn config_file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("message-bus-{}-{}.toml", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn help_matches_snapshot() {
+    let help = Cli::command().render_long_help().to_string();
+    let snapshot = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/cli_help.txt");
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&snapshot, &help).unwrap();
+    }
+    let expected = std::fs::read_to_string(&snapshot).expect("missing snapshot, run with UPDATE_SNAPSHOTS=1");
+    assert_eq!(help, expected, "--help changed; rerun with UPDATE_SNAPSHOTS=1 to accept");
+}
+
+#[test]
+fn defaults_without_arguments() {
+    let config = parse(&[]).unwrap();
+    assert_eq!(config, AppConfig::default());
+    assert_eq!(config.mode, RunMode::Live);
+    assert_eq!(config.run_duration(), Duration::from_secs(5));
+}
+
+#[test]
+fn command_line_overrides_config_file() {
+    let path = config_file(
+        "overrides",
+        r#"
+mode = "sim"
+symbols = ["BTC-USD", "ETH-USD"]
+duration = "2m"
+bus_capacity = 64
+seed = 1
+log_level = "debug"
+"#,
+    );
+    let from_file = parse(&["--config", path.to_str().unwrap()]).unwrap();
+    assert_eq!(from_file.mode, RunMode::Sim);
+    assert_eq!(from_file.symbols, ["BTC-USD", "ETH-USD"]);
+    assert_eq!(from_file.duration, Some(Duration::from_secs(120)));
+    assert_eq!(from_file.bus_capacity, 64);
+
+    let config = parse(&[
+        "--config",
+        path.to_str().unwrap(),
+        "--symbol",
+        "SOL-USD",
+        "--duration",
+        "500ms",
+        "--seed",
+        "42",
+    ])
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    // 给出的参数覆盖配置文件，其余保持文件中的值
+    assert_eq!(config.symbols, ["SOL-USD"]);
+    assert_eq!(config.duration, Some(Duration::from_millis(500)));
+    assert_eq!(config.seed, Some(42));
+    assert_eq!(config.mode, RunMode::Sim);
+    assert_eq!(config.bus_capacity, 64);
+    assert_eq!(config.log_level, "debug");
+}
+
+#[test]
+fn backtest_takes_a_data_file() {
+    let config = parse(&["--mode", "backtest", "--data-file", "bars.jsonl"]).unwrap();
+    assert_eq!(config.mode, RunMode::Backtest);
+    assert_eq!(config.data_file, Some(PathBuf::from("bars.jsonl")));
+}
+
+#[test]
+fn quiet_period_is_read_in_live_mode() {
+    let config = parse(&["--quiet-period", "1.5s"]).unwrap();
+    assert_eq!(config.quiet_period, Some(Duration::from_millis(1_500)));
+    assert_eq!(parse(&[]).unwrap().quiet_period, None);
+}
+
+#[test]
+fn conflicting_combinations_name_the_argument() {
+    assert!(invalid(&["--data-file", "bars.jsonl"]).contains("--data-file"));
+    assert!(invalid(&["--mode", "sim", "--data-file", "bars.jsonl"]).contains("not sim mode"));
+    assert!(invalid(&["--mode", "backtest"]).contains("requires a data file"));
+    assert!(invalid(&["--mode", "backtest", "--data-file", "bars.jsonl", "--duration", "5s"]).contains("--duration"));
+    assert!(invalid(&["--bus-capacity", "0"]).contains("--bus-capacity"));
+    assert!(invalid(&["--duration", "0s"]).contains("--duration"));
+    assert!(invalid(&["--quiet-period", "0s"]).contains("--quiet-period"));
+    assert!(invalid(&["--mode", "sim", "--quiet-period", "2s"]).contains("only used in live mode"));
+    assert!(invalid(&["--log-level", "info,=="]).contains("--log-level"));
+}
+
+#[test]
+fn config_file_errors() {
+    let path = config_file("unknown-field", "colour = \"blue\"\n");
+    let result = parse(&["--config", path.to_str().unwrap()]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(ConfigError::Parse(..))), "{:?}", result);
+
+    let missing = std::env::temp_dir().join("message-bus-does-not-exist.toml");
+    assert!(matches!(parse(&["--config", missing.to_str().unwrap()]), Err(ConfigError::Io(..))));
+}
+
+#[test]
+fn malformed_values_are_rejected_by_clap() {
+    let args = |args: &[&str]| Cli::try_parse_from(std::iter::once("message-bus").chain(args.iter().copied()));
+    assert!(args(&["--duration", "5 parsecs"]).is_err());
+    assert!(args(&["--mode", "paper"]).is_err());
+    assert!(args(&["--seed", "-1"]).is_err());
+}
@@ -0,0 +1,77 @@
+// tests/clock.rs
+
+//! # 单调时间戳测试
+//!
+//! 用一个按脚本返回时间、中途倒退的时钟，验证 `Monotonic` 与经由它打时间戳的生产者
+//! 输出的 `ts_event` 单调不减。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::clock::{Clock, LiveClock, Monotonic};
+use message_bus::data::SimulatedDataEngine;
+use message_bus::message::Bar;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 依次返回脚本中的时间，脚本用完后停留在最后一个值。
+struct ScriptedClock {
+    script: Mutex<VecDeque<u64>>,
+    last: Mutex<u64>,
+}
+
+impl ScriptedClock {
+    fn new(script: &[u64]) -> Self {
+        Self { script: Mutex::new(script.iter().copied().collect()), last: Mutex::new(0) }
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for ScriptedClock {
+    fn now_nanos(&self) -> u64 {
+        let mut last = self.last.lock().unwrap();
+        if let Some(next) = self.script.lock().unwrap().pop_front() {
+            *last = next;
+        }
+        *last
+    }
+
+    async fn sleep_until(&self, _ts_nanos: u64) {}
+}
+
+#[test]
+fn monotonic_clamps_a_clock_that_goes_backwards() {
+    let clock = Monotonic::new(ScriptedClock::new(&[1_000, 2_000, 1_500, 500, 2_500]));
+    let observed: Vec<u64> = (0..5).map(|_| clock.now_nanos()).collect();
+    assert_eq!(observed, vec![1_000, 2_000, 2_000, 2_000, 2_500]);
+}
+
+#[test]
+fn live_clock_never_decreases() {
+    let clock = LiveClock;
+    let mut previous = clock.now_nanos();
+    for _ in 0..10_000 {
+        let now = clock.now_nanos();
+        assert!(now >= previous);
+        previous = now;
+    }
+}
+
+#[tokio::test]
+async fn data_engine_timestamps_stay_ordered_when_the_clock_goes_backwards() {
+    let bus = MessageBus::new(16);
+    let mut bar_rx = bus.subscribe::<Bar>().await;
+    let clock = Arc::new(Monotonic::new(ScriptedClock::new(&[5_000, 3_000, 4_000])));
+    let engine = Arc::new(SimulatedDataEngine::new(bus.clone(), "BTC-USD".to_string()).with_clock(clock));
+    let handles = engine.start("DATA").await;
+
+    let mut stamps = Vec::new();
+    for _ in 0..3 {
+        stamps.push(bar_rx.recv_timeout(Duration::from_secs(2)).await.unwrap().ts_event);
+    }
+    assert_eq!(stamps, vec![5_000, 5_000, 5_000]);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
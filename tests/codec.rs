@@ -0,0 +1,241 @@
+// tests/codec.rs
+
+//! # 线上编码测试
+//!
+//! 每种线上消息类型（含可选字段缺省与存在两种情况）经 JSON、MessagePack、bincode 与 protobuf 编解码后保持不变，
+//! 同一条 `FillEvent` 以 bincode 编码体积最小；连接握手按客户端声明的格式协商编解码器；
+//! protobuf 编码与 `tests/snapshots/wire_protobuf.hex` 中提交的字节比较，防止无意中改变线上格式
+//! （设置 `UPDATE_SNAPSHOTS=1` 时重写）；不认识的类型与版本被拒绝；`wire_format` 从配置中选择编码。
+//! protobuf 相关的测试需要启用 `protobuf` feature：`cargo test --features protobuf --test codec`。
+
+use message_bus::codec::{
+    accept_format, request_format, BincodeCodec, Codec, CodecError, JsonCodec, MessagePackCodec, WireFormat, WireMessage,
+    SCHEMA_VERSION,
+};
+use message_bus::message::{Bar, FillEvent, Liquidity, OrderRequest, OrderSide, OrderType};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// 固定内容的样本消息，覆盖每种类型以及可选字段的两种取值。
+fn samples() -> Vec<WireMessage> {
+    let order_id = Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+    let fill = FillEvent {
+        order_id,
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Sell,
+        price: 101.25,
+        quantity: 0.5,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.05,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Maker,
+        ts_event: 1_700_000_000_500_000_000,
+        venue_fill_id: Some("T-42".to_string()),
+        correlation_id: Some(order_id),
+    };
+    let order = OrderRequest {
+        id: order_id,
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::StopLimit,
+        price: 99.5,
+        quantity: 2.0,
+        trigger_price: Some(100.0),
+    };
+    vec![
+        WireMessage::Bar(Bar {
+            id: Uuid::from_u128(1),
+            ts_event: 1_700_000_000_000_000_000,
+            symbol: "BTC-USD".to_string(),
+            open: 100.0,
+            high: 102.5,
+            low: 99.75,
+            close: 101.0,
+            volume: 12.5,
+            timeframe: Duration::from_secs(60),
+        }),
+        WireMessage::OrderRequest(order.clone()),
+        WireMessage::OrderRequest(OrderRequest { order_type: OrderType::Market, trigger_price: None, ..order }),
+        WireMessage::FillEvent(fill.clone()),
+        WireMessage::FillEvent(FillEvent {
+            liquidity: Liquidity::Taker,
+            commission_currency: String::new(),
+            venue_fill_id: None,
+            correlation_id: None,
+            ..fill
+        }),
+    ]
+}
+
+/// 比较消息的全部字段（`Bar` 的 `PartialEq` 不比较价格）。
+fn fields(msg: &WireMessage) -> Value {
+    match msg {
+        WireMessage::Bar(bar) => json!({ "Bar": bar }),
+        WireMessage::OrderRequest(order) => json!({ "OrderRequest": order }),
+        WireMessage::FillEvent(fill) => json!({ "FillEvent": fill }),
+    }
+}
+
+fn assert_round_trips(codec: &dyn Codec) {
+    for msg in samples() {
+        let bytes = codec.encode(&msg).unwrap();
+        let decoded = codec.decode(&bytes).unwrap_or_else(|e| panic!("{:?} failed to decode {:?}: {}", codec.format(), msg, e));
+        assert_eq!(fields(&decoded), fields(&msg));
+    }
+}
+
+#[test]
+fn json_round_trips_every_type() {
+    assert_round_trips(&JsonCodec);
+    let bytes = JsonCodec.encode(&samples()[0]).unwrap();
+    let envelope: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(envelope["type_id"], "Bar");
+    assert_eq!(envelope["schema_version"], SCHEMA_VERSION);
+}
+
+#[test]
+fn binary_formats_round_trip_every_type() {
+    assert_round_trips(&MessagePackCodec);
+    assert_round_trips(&BincodeCodec);
+}
+
+#[test]
+fn fill_event_round_trips_in_every_format_and_bincode_is_smallest() {
+    let fill = samples().into_iter().find(|msg| matches!(msg, WireMessage::FillEvent(_))).unwrap();
+    let mut sizes = Vec::new();
+    for format in [WireFormat::Json, WireFormat::MessagePack, WireFormat::Bincode] {
+        let codec = format.codec().unwrap();
+        let bytes = codec.encode(&fill).unwrap();
+        assert_eq!(codec.decode(&bytes).unwrap(), fill, "{:?}", format);
+        sizes.push((format, bytes.len()));
+    }
+    let bincode = sizes[2].1;
+    assert!(sizes[..2].iter().all(|(_, len)| *len > bincode), "{:?}", sizes);
+}
+
+#[test]
+fn binary_formats_reject_unknown_types_and_truncated_payloads() {
+    let bytes = BincodeCodec.encode(&samples()[0]).unwrap();
+    assert!(matches!(BincodeCodec.decode(&bytes[..bytes.len() / 2]), Err(CodecError::Bincode(_))));
+    let bytes = MessagePackCodec.encode(&samples()[0]).unwrap();
+    assert!(matches!(MessagePackCodec.decode(&bytes[..bytes.len() / 2]), Err(CodecError::MessagePackDecode(_))));
+
+    let unknown = rmp_serde::to_vec(&("Quote", SCHEMA_VERSION)).unwrap();
+    assert!(matches!(MessagePackCodec.decode(&unknown), Err(CodecError::UnknownType(type_id)) if type_id == "Quote"));
+}
+
+#[tokio::test]
+async fn handshake_negotiates_the_client_format() {
+    let (mut client, mut server) = tokio::io::duplex(256);
+    let supported = [WireFormat::Json, WireFormat::Bincode];
+    let (client_codec, server_codec) =
+        tokio::join!(request_format(&mut client, WireFormat::Bincode), accept_format(&mut server, &supported));
+    let (client_codec, server_codec) = (client_codec.unwrap(), server_codec.unwrap());
+    assert_eq!((client_codec.format(), server_codec.format()), (WireFormat::Bincode, WireFormat::Bincode));
+    let msg = samples().remove(0);
+    let decoded = server_codec.decode(&client_codec.encode(&msg).unwrap()).unwrap();
+    assert_eq!(fields(&decoded), fields(&msg));
+
+    // 服务端不支持的格式被拒绝，双方都得到原因
+    let (mut client, mut server) = tokio::io::duplex(256);
+    let (client_result, server_result) =
+        tokio::join!(request_format(&mut client, WireFormat::MessagePack), accept_format(&mut server, &supported));
+    let client_error = client_result.err().unwrap();
+    assert!(matches!(client_error, CodecError::Handshake(_)), "{}", client_error);
+    assert!(client_error.to_string().contains("messagepack is not supported"), "{}", client_error);
+    assert!(matches!(server_result, Err(CodecError::Handshake(_))));
+}
+
+#[test]
+fn rejects_unknown_types_and_versions() {
+    let future = json!({ "type_id": "Bar", "schema_version": SCHEMA_VERSION + 1, "payload": {} });
+    let result = JsonCodec.decode(&serde_json::to_vec(&future).unwrap());
+    assert!(matches!(result, Err(CodecError::UnsupportedVersion(version)) if version == SCHEMA_VERSION + 1));
+
+    let unknown = json!({ "type_id": "Quote", "schema_version": SCHEMA_VERSION, "payload": {} });
+    let result = JsonCodec.decode(&serde_json::to_vec(&unknown).unwrap());
+    assert!(matches!(result, Err(CodecError::UnknownType(type_id)) if type_id == "Quote"));
+    assert!(matches!(JsonCodec.decode(b"not json"), Err(CodecError::Json(_))));
+}
+
+#[test]
+fn wire_format_is_selected_by_config() {
+    #[derive(Deserialize)]
+    struct BridgeConfig {
+        #[serde(default)]
+        wire_format: WireFormat,
+    }
+    let parse = |text: &str| toml::from_str::<BridgeConfig>(text).map(|config| config.wire_format);
+    assert_eq!(parse("").unwrap(), WireFormat::Json);
+    assert_eq!(parse(r#"wire_format = "json""#).unwrap(), WireFormat::Json);
+    assert_eq!(parse(r#"wire_format = "protobuf""#).unwrap(), WireFormat::Protobuf);
+    assert_eq!(parse(r#"wire_format = "messagepack""#).unwrap(), WireFormat::MessagePack);
+    assert_eq!(parse(r#"wire_format = "msgpack""#).unwrap(), WireFormat::MessagePack);
+    assert_eq!(parse(r#"wire_format = "bincode""#).unwrap(), WireFormat::Bincode);
+    assert!(parse(r#"wire_format = "xml""#).is_err());
+
+    assert_eq!(WireFormat::Json.codec().unwrap().format(), WireFormat::Json);
+    match WireFormat::Protobuf.codec() {
+        Ok(codec) => assert!(cfg!(feature = "protobuf") && codec.format() == WireFormat::Protobuf),
+        Err(e) => assert!(!cfg!(feature = "protobuf") && matches!(e, CodecError::FormatUnavailable(_))),
+    }
+}
+
+#[cfg(feature = "protobuf")]
+mod protobuf {
+    use super::*;
+    use message_bus::codec::{proto, ProtobufCodec};
+    use prost::Message as _;
+    use std::path::Path;
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn protobuf_round_trips_every_type() {
+        assert_round_trips(&ProtobufCodec);
+    }
+
+    #[test]
+    fn protobuf_encoding_matches_fixture() {
+        let encoded: String = samples()
+            .iter()
+            .map(|msg| format!("{} {}\n", msg.type_id(), to_hex(&ProtobufCodec.encode(msg).unwrap())))
+            .collect();
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/wire_protobuf.hex");
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&fixture, &encoded).unwrap();
+        }
+        let expected = std::fs::read_to_string(&fixture).expect("missing fixture, run with UPDATE_SNAPSHOTS=1");
+        assert_eq!(encoded, expected, "protobuf wire format changed; bump SCHEMA_VERSION or fix the encoding");
+
+        // 提交的字节也必须能解码回同样的消息
+        for (line, msg) in expected.lines().zip(samples()) {
+            let (type_id, hex) = line.split_once(' ').unwrap();
+            assert_eq!(type_id, msg.type_id());
+            assert_eq!(fields(&ProtobufCodec.decode(&from_hex(hex)).unwrap()), fields(&msg));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_protobuf() {
+        assert!(matches!(ProtobufCodec.decode(&[0xff, 0xff]), Err(CodecError::Protobuf(_))));
+
+        let bar = proto::Bar { id: vec![1, 2, 3], ..Default::default() };
+        let envelope =
+            proto::Envelope { type_id: "Bar".to_string(), schema_version: SCHEMA_VERSION, payload: bar.encode_to_vec() };
+        let result = ProtobufCodec.decode(&envelope.encode_to_vec());
+        assert!(matches!(result, Err(CodecError::InvalidField { field: "id", .. })), "{:?}", result);
+
+        let envelope = proto::Envelope { schema_version: 0, ..envelope };
+        assert!(matches!(ProtobufCodec.decode(&envelope.encode_to_vec()), Err(CodecError::UnsupportedVersion(0))));
+    }
+}
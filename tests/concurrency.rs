@@ -0,0 +1,220 @@
+// tests/concurrency.rs
+
+//! # MessageBus 并发测试
+//!
+//! 使用 `proptest` 随机生成跨多个任务的并发 `publish` / `subscribe` 操作序列，
+//! 验证 `subscribe` 中“双重检查锁定”的正确性：并发订阅后订阅者数量与存活的接收者一致，不同类型互不干扰。
+//!
+//! 随机调度只能抽样线程交错。启用 `loom` feature 后通道表改用 loom 的锁（见 `src/sync.rs`），
+//! 这里改为在 `loom::model` 中驱动真实的 `subscribe` / `try_subscribe`，穷举所有交错：
+//!
+//! ```bash
+//! cargo test --features loom --test concurrency --release
+//! ```
+//!
+//! loom 的锁只能在模型内使用，因此启用该 feature 时 proptest 的场景不参与编译，其他测试也不应运行。
+
+#![cfg_attr(feature = "loom", allow(dead_code, unused_imports))]
+
+use message_bus::bus::{MessageBus, Receiver};
+use message_bus::message::{Bar, FillEvent, Liquidity, Message, OrderSide};
+use message_bus::testkit::BarBuilder;
+use proptest::prelude::*;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// 单个任务可以执行的操作。
+#[derive(Clone, Debug)]
+enum Op {
+    PublishBar,
+    SubscribeBar,
+    PublishFill,
+    SubscribeFill,
+    /// 丢弃该任务持有的最早一个 `Bar` 订阅者（如果有）。
+    DropBarReceiver,
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        Just(Op::PublishBar),
+        Just(Op::SubscribeBar),
+        Just(Op::PublishFill),
+        Just(Op::SubscribeFill),
+        Just(Op::DropBarReceiver),
+    ]
+}
+
+/// 2–8 个任务，每个任务执行 1–32 个操作。
+fn scenario_strategy() -> impl Strategy<Value = Vec<Vec<Op>>> {
+    prop::collection::vec(prop::collection::vec(op_strategy(), 1..32), 2..=8)
+}
+
+fn fill(price: f64) -> FillEvent {
+    FillEvent {
+        order_id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        price,
+        quantity: 1.0,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.0,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Taker,
+        ts_event: 0,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+/// 每个任务结束时交还它仍持有的订阅者。
+type Held = (Vec<Receiver<Bar>>, Vec<Receiver<FillEvent>>);
+
+async fn run_ops(bus: MessageBus, ops: Vec<Op>) -> Held {
+    let mut bars = Vec::new();
+    let mut fills = Vec::new();
+    for op in ops {
+        match op {
+            Op::PublishBar => {
+                bus.publish(BarBuilder::new().price(1.0).build()).await.expect("publish Bar failed");
+            }
+            Op::SubscribeBar => bars.push(bus.subscribe::<Bar>().await),
+            Op::PublishFill => {
+                bus.publish(fill(1.0)).await.expect("publish FillEvent failed");
+            }
+            Op::SubscribeFill => fills.push(bus.subscribe::<FillEvent>().await),
+            Op::DropBarReceiver => {
+                if !bars.is_empty() {
+                    bars.remove(0);
+                }
+            }
+        }
+        tokio::task::yield_now().await;
+    }
+    (bars, fills)
+}
+
+/// 清空一个订阅者中所有已缓冲的消息，返回最后一条。
+fn last_buffered<M: Message>(rx: &mut Receiver<M>) -> Option<M> {
+    let mut last = None;
+    loop {
+        match rx.try_recv() {
+            Ok(msg) => last = Some(msg),
+            Err(broadcast::error::TryRecvError::Lagged(_)) => continue,
+            Err(_) => return last,
+        }
+    }
+}
+
+#[cfg(not(feature = "loom"))]
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn concurrent_publish_subscribe_is_consistent(scenario in scenario_strategy()) {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(4)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async move {
+            let bus = MessageBus::new(1024);
+
+            let handles: Vec<_> = scenario
+                .into_iter()
+                .map(|ops| tokio::spawn(run_ops(bus.clone(), ops)))
+                .collect();
+
+            // 任何一个任务 panic（例如 `expect("FATAL: ...")` 的向下转型失败）都会在这里暴露
+            let mut bar_rxs = Vec::new();
+            let mut fill_rxs = Vec::new();
+            for handle in handles {
+                let (bars, fills) = handle.await.expect("task panicked");
+                bar_rxs.extend(bars);
+                fill_rxs.extend(fills);
+            }
+
+            // 订阅者数量与仍存活的接收者一致：publish 的返回值就是送达的订阅者数
+            let probe_bar = BarBuilder::new().price(42.0).build();
+            let delivered = bus.publish(probe_bar.clone()).await.unwrap();
+            assert_eq!(delivered, bar_rxs.len());
+
+            // 不同类型之间没有 TypeId 冲突：Bar 的探针不会出现在 FillEvent 通道中
+            for rx in &mut fill_rxs {
+                if let Some(last) = last_buffered(rx) {
+                    assert_ne!(last.price, 42.0);
+                }
+            }
+            for rx in &mut bar_rxs {
+                assert_eq!(last_buffered(rx).map(|b| b.id), Some(probe_bar.id));
+            }
+
+            let probe_fill = fill(7.0);
+            let delivered = bus.publish(probe_fill.clone()).await.unwrap();
+            assert_eq!(delivered, fill_rxs.len());
+            for rx in &mut fill_rxs {
+                assert_eq!(last_buffered(rx).map(|f| f.order_id), Some(probe_fill.order_id));
+            }
+            for rx in &mut bar_rxs {
+                assert!(last_buffered(rx).is_none());
+            }
+        });
+    }
+}
+
+/// ## loom 模型检查
+///
+/// 在 `loom::model` 中以 loom 线程并发调用真实的 `MessageBus::subscribe` / `try_subscribe`，
+/// 穷举读锁快速路径与写锁慢路径（含二次检查）的所有交错。
+/// loom 只在自己的同步原语处切换线程：订阅者计数保存在 tokio 的 broadcast 通道中，不受插桩，
+/// 因此模型检查的是加锁协议（哪一段在哪把锁下完成），同一把读锁内部的竞争仍由上面的 proptest 抽样。
+#[cfg(feature = "loom")]
+mod loom_model {
+    use super::fill;
+    use loom::future::block_on;
+    use loom::thread;
+    use message_bus::bus::{BusError, MessageBus};
+    use message_bus::message::{Bar, FillEvent};
+    use message_bus::testkit::BarBuilder;
+
+    #[test]
+    fn concurrent_first_subscribe_creates_one_channel_per_type() {
+        loom::model(|| {
+            let bus = MessageBus::new(16);
+            let spawn = || {
+                let bus = bus.clone();
+                thread::spawn(move || block_on(bus.subscribe::<Bar>()))
+            };
+            let bar_a = spawn();
+            let bar_b = spawn();
+            let _fills = block_on(bus.subscribe::<FillEvent>());
+            let _bars = [bar_a.join().unwrap(), bar_b.join().unwrap()];
+
+            // 两个并发的首次订阅落在同一个通道上，另一个类型的通道互不干扰
+            assert_eq!(block_on(bus.publish(BarBuilder::new().price(1.0).build())).unwrap(), 2);
+            assert_eq!(block_on(bus.publish(fill(1.0))).unwrap(), 1);
+        });
+    }
+
+    #[test]
+    fn concurrent_subscribes_never_exceed_the_limit() {
+        loom::model(|| {
+            let bus = MessageBus::new(16).with_max_subscribers::<Bar>(2);
+            let _first = block_on(bus.try_subscribe::<Bar>()).unwrap();
+            let spawn = || {
+                let bus = bus.clone();
+                thread::spawn(move || block_on(bus.try_subscribe::<Bar>()))
+            };
+            let a = spawn();
+            let b = spawn();
+            let results = [a.join().unwrap(), b.join().unwrap()];
+
+            // 通道已存在且有一个订阅者，两个并发订阅只能有一个成功
+            let accepted = results.iter().filter(|r| r.is_ok()).count();
+            assert_eq!(accepted, 1);
+            assert!(results.iter().any(|r| matches!(r, Err(BusError::SubscriberLimit { limit: 2, .. }))));
+            assert_eq!(block_on(bus.subscriber_count::<Bar>()), 2);
+        });
+    }
+}
@@ -0,0 +1,113 @@
+// tests/consumer_group.rs
+
+//! # 消费组测试
+//!
+//! `subscribe_group` 的 `Broadcast` 组中每个成员都收到每条消息；`RoundRobin` 组中每条消息恰好交给一个成员，
+//! 按加入顺序轮流分配，整个组在通道上只计为一个订阅者；离开的成员被跳过，组按名称与消息类型区分，
+//! 最后一个成员离开时组的转发任务停止并释放订阅。
+
+use message_bus::bus::{GroupPolicy, MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::Bar;
+use message_bus::testkit::BarBuilder;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[tokio::test]
+async fn broadcast_members_each_receive_every_message() {
+    let bus = MessageBus::new(16);
+    let mut first = bus.subscribe_group::<Bar>("portfolios", GroupPolicy::Broadcast).await.unwrap();
+    let mut second = bus.subscribe_group::<Bar>("portfolios", GroupPolicy::Broadcast).await.unwrap();
+    assert_eq!(first.policy(), GroupPolicy::Broadcast);
+
+    assert_eq!(bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap(), 2);
+    bus.publish(BarBuilder::new().ts_event(2).build()).await.unwrap();
+    for rx in [&mut first, &mut second] {
+        assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+        assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 2);
+    }
+}
+
+#[tokio::test]
+async fn round_robin_members_each_receive_a_share() {
+    let bus = MessageBus::new(16);
+    let mut members = Vec::new();
+    for _ in 0..3 {
+        members.push(bus.subscribe_group::<Bar>("loggers", GroupPolicy::RoundRobin).await.unwrap());
+    }
+    // 另一个组与普通订阅者不受影响
+    let mut audit = bus.subscribe_group::<Bar>("audit", GroupPolicy::RoundRobin).await.unwrap();
+    let mut plain = bus.subscribe::<Bar>().await;
+
+    for ts_event in 0..6 {
+        // 两个组的转发任务加上普通订阅者
+        assert_eq!(bus.publish(BarBuilder::new().ts_event(ts_event).build()).await.unwrap(), 3);
+    }
+    for (i, rx) in members.iter_mut().enumerate() {
+        let received = [rx.recv_timeout(TIMEOUT).await.unwrap(), rx.recv_timeout(TIMEOUT).await.unwrap()];
+        assert_eq!(received.map(|bar| bar.ts_event), [i as u64, i as u64 + 3]);
+        assert_eq!(rx.recv_timeout(Duration::from_millis(50)).await, Err(RecvTimeout::Timeout));
+    }
+    for ts_event in 0..6 {
+        assert_eq!(audit.recv_timeout(TIMEOUT).await.unwrap().ts_event, ts_event);
+        assert_eq!(plain.recv_timeout(TIMEOUT).await.unwrap().ts_event, ts_event);
+    }
+}
+
+#[tokio::test]
+async fn departed_members_are_skipped() {
+    let bus = MessageBus::new(16);
+    let mut first = bus.subscribe_group::<Bar>("loggers", GroupPolicy::RoundRobin).await.unwrap();
+    let second = bus.subscribe_group::<Bar>("loggers", GroupPolicy::RoundRobin).await.unwrap();
+    drop(second);
+
+    for ts_event in 0..3 {
+        bus.publish(BarBuilder::new().ts_event(ts_event).build()).await.unwrap();
+    }
+    for ts_event in 0..3 {
+        assert_eq!(first.recv_timeout(TIMEOUT).await.unwrap().ts_event, ts_event);
+    }
+
+    // 之后加入的成员参与轮流分配
+    let mut third = bus.subscribe_group::<Bar>("loggers", GroupPolicy::RoundRobin).await.unwrap();
+    bus.publish(BarBuilder::new().ts_event(10).build()).await.unwrap();
+    bus.publish(BarBuilder::new().ts_event(11).build()).await.unwrap();
+    let mut received = vec![
+        first.recv_timeout(TIMEOUT).await.unwrap().ts_event,
+        third.recv_timeout(TIMEOUT).await.unwrap().ts_event,
+    ];
+    received.sort();
+    assert_eq!(received, [10, 11]);
+    assert_eq!(first.drain_backlog() + third.drain_backlog(), 0);
+}
+
+#[tokio::test]
+async fn group_is_torn_down_when_its_last_member_leaves() {
+    let bus = MessageBus::new(16);
+    let first = bus.subscribe_group::<Bar>("loggers", GroupPolicy::RoundRobin).await.unwrap();
+    let second = bus.subscribe_group::<Bar>("loggers", GroupPolicy::RoundRobin).await.unwrap();
+    assert_eq!(bus.subscriber_count::<Bar>().await, 1);
+
+    // 还有成员时组保持运行
+    drop(first);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(bus.subscriber_count::<Bar>().await, 1);
+
+    // 最后一个成员离开后，转发任务停止并释放它的订阅
+    drop(second);
+    tokio::time::timeout(TIMEOUT, async {
+        while bus.subscriber_count::<Bar>().await > 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("group relay still subscribed");
+    assert_eq!(bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap(), 0);
+
+    // 再次加入同名组会重新创建它，之前发布的消息不会补发
+    let mut rejoined = bus.subscribe_group::<Bar>("loggers", GroupPolicy::RoundRobin).await.unwrap();
+    assert_eq!(bus.subscriber_count::<Bar>().await, 1);
+    assert_eq!(bus.publish(BarBuilder::new().ts_event(2).build()).await.unwrap(), 1);
+    assert_eq!(rejoined.recv_timeout(TIMEOUT).await.unwrap().ts_event, 2);
+    assert_eq!(rejoined.recv_timeout(Duration::from_millis(50)).await, Err(RecvTimeout::Timeout));
+}
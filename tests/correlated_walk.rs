@@ -0,0 +1,114 @@
+// tests/correlated_walk.rs
+
+//! # 相关随机游走测试
+//!
+//! `CorrelatedWalk` 生成的对数收益的样本相关系数接近配置的相关矩阵，相同种子产生相同的价格路径，
+//! 无效的相关矩阵被拒绝；`SimulatedDataEngine::with_correlated_walk` 每次为每个资产各发布一根 `Bar`。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::data::{CorrelatedRandomWalk, CorrelatedWalk, CorrelatedWalkError, SimulatedDataEngine};
+use message_bus::message::Bar;
+use std::sync::Arc;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn config(correlation: f64, seed: Option<u64>) -> CorrelatedRandomWalk {
+    CorrelatedRandomWalk {
+        assets: vec!["BTC-USD".to_string(), "ETH-USD".to_string()],
+        initial_prices: vec![30_000.0, 2_000.0],
+        drift: vec![0.0, 0.0],
+        volatility: vec![0.01, 0.02],
+        correlation_matrix: vec![vec![1.0, correlation], vec![correlation, 1.0]],
+        seed,
+    }
+}
+
+fn sample_correlation(xs: &[f64], ys: &[f64]) -> f64 {
+    let n = xs.len() as f64;
+    let (mean_x, mean_y) = (xs.iter().sum::<f64>() / n, ys.iter().sum::<f64>() / n);
+    let cov: f64 = xs.iter().zip(ys).map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let var_x: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    let var_y: f64 = ys.iter().map(|y| (y - mean_y).powi(2)).sum();
+    cov / (var_x * var_y).sqrt()
+}
+
+#[test]
+fn log_returns_follow_the_configured_correlation() {
+    let mut walk = CorrelatedWalk::new(config(0.8, Some(7))).unwrap();
+    assert_eq!(walk.assets(), ["BTC-USD", "ETH-USD"]);
+    let (mut btc, mut eth) = (Vec::new(), Vec::new());
+    for _ in 0..5_000 {
+        let bars = walk.next_ohlcv();
+        let (open, _, _, close, _) = bars[0];
+        btc.push((close / open).ln());
+        let (open, _, _, close, _) = bars[1];
+        eth.push((close / open).ln());
+    }
+    let correlation = sample_correlation(&btc, &eth);
+    assert!((correlation - 0.8).abs() < 0.03, "sample correlation {}", correlation);
+}
+
+#[test]
+fn the_same_seed_reproduces_the_paths() {
+    let mut first = CorrelatedWalk::new(config(0.5, Some(42))).unwrap();
+    let mut second = CorrelatedWalk::new(config(0.5, Some(42))).unwrap();
+    for _ in 0..100 {
+        assert_eq!(first.next_ohlcv(), second.next_ohlcv());
+    }
+    let mut other = CorrelatedWalk::new(config(0.5, Some(43))).unwrap();
+    assert_ne!(first.next_ohlcv(), other.next_ohlcv());
+}
+
+#[test]
+fn invalid_configurations_are_rejected() {
+    let err = |config| CorrelatedWalk::new(config).unwrap_err();
+
+    let mut empty = config(0.5, None);
+    empty.assets.clear();
+    assert_eq!(err(empty), CorrelatedWalkError::NoAssets);
+
+    let mut short = config(0.5, None);
+    short.volatility.pop();
+    assert_eq!(err(short), CorrelatedWalkError::LengthMismatch { field: "volatility", expected: 2, actual: 1 });
+
+    let mut asymmetric = config(0.5, None);
+    asymmetric.correlation_matrix[0][1] = 0.3;
+    assert!(matches!(err(asymmetric), CorrelatedWalkError::InvalidCorrelation(_)));
+
+    assert!(matches!(err(config(1.5, None)), CorrelatedWalkError::InvalidCorrelation(_)));
+
+    let mut diagonal = config(0.5, None);
+    diagonal.correlation_matrix[1][1] = 2.0;
+    assert!(matches!(err(diagonal), CorrelatedWalkError::InvalidCorrelation(_)));
+
+    // 三个资产两两相关系数均为 -0.9，矩阵不正定
+    let negative = CorrelatedRandomWalk {
+        assets: vec!["A".to_string(), "B".to_string(), "C".to_string()],
+        initial_prices: vec![1.0; 3],
+        drift: vec![0.0; 3],
+        volatility: vec![0.01; 3],
+        correlation_matrix: vec![vec![1.0, -0.9, -0.9], vec![-0.9, 1.0, -0.9], vec![-0.9, -0.9, 1.0]],
+        seed: None,
+    };
+    assert_eq!(err(negative), CorrelatedWalkError::NotPositiveDefinite);
+}
+
+#[tokio::test]
+async fn the_engine_publishes_one_bar_per_asset_each_tick() {
+    let bus = MessageBus::new(64);
+    let mut rx = bus.subscribe::<Bar>().await;
+    let engine = SimulatedDataEngine::new(bus.clone(), "UNUSED".to_string()).with_correlated_walk(config(0.8, Some(1)));
+    let handles = Arc::new(engine).start("DATA").await;
+
+    let first = rx.recv_timeout(TIMEOUT).await.unwrap();
+    let second = rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((first.symbol.as_str(), second.symbol.as_str()), ("BTC-USD", "ETH-USD"));
+    assert_eq!(first.ts_event, second.ts_event);
+    assert_eq!((first.open, second.open), (30_000.0, 2_000.0));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,78 @@
+// tests/costs.rs
+
+//! # 交易成本测试
+//!
+//! 每种 `SlippageConfig` 构造的滑点模型都使买单的成交价不低于、卖单的成交价不高于请求价格（`None` 时相等），
+//! 偏移量符合模型的参数；每种 `FeeConfig` 构造的手续费模型按成交价、数量与流动性方向给出预期的手续费。
+
+use message_bus::costs::{FeeConfig, SlippageConfig};
+use message_bus::message::{Liquidity, OrderSide};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const PRICE: f64 = 100.0;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+}
+
+/// 以 `quantity` 分别为买单与卖单计算成交价。
+fn fill_prices(config: &SlippageConfig, quantity: f64) -> (f64, f64) {
+    let model = config.build();
+    let mut rng = StdRng::seed_from_u64(7);
+    (model.fill_price(&OrderSide::Buy, PRICE, quantity, &mut rng), model.fill_price(&OrderSide::Sell, PRICE, quantity, &mut rng))
+}
+
+#[test]
+fn no_slippage_fills_at_the_requested_price() {
+    assert_eq!(fill_prices(&SlippageConfig::None, 1.0), (PRICE, PRICE));
+}
+
+#[test]
+fn fixed_ticks_move_buys_up_and_sells_down() {
+    let (buy, sell) = fill_prices(&SlippageConfig::FixedTicks { tick_size: 0.5, ticks: 2 }, 1.0);
+    assert_close(buy, 101.0);
+    assert_close(sell, 99.0);
+}
+
+#[test]
+fn percentage_moves_buys_up_and_sells_down() {
+    let (buy, sell) = fill_prices(&SlippageConfig::Percentage { pct: 0.001 }, 1.0);
+    assert_close(buy, 100.1);
+    assert_close(sell, 99.9);
+}
+
+#[test]
+fn volume_dependent_slippage_grows_with_quantity() {
+    // 没有噪声时偏移为 数量 × 每单位基点
+    let config = SlippageConfig::VolumeDependent { impact_bps_per_unit: 10.0, noise_bps: 0.0 };
+    let (buy, sell) = fill_prices(&config, 2.0);
+    assert_close(buy, 100.2);
+    assert_close(sell, 99.8);
+
+    // 有噪声时偏移在 [冲击, 冲击 + 噪声) 之内，方向不变
+    let model = SlippageConfig::VolumeDependent { impact_bps_per_unit: 10.0, noise_bps: 5.0 }.build();
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..100 {
+        let buy = model.fill_price(&OrderSide::Buy, PRICE, 2.0, &mut rng);
+        let sell = model.fill_price(&OrderSide::Sell, PRICE, 2.0, &mut rng);
+        assert!((100.2..100.25).contains(&buy), "buy filled at {}", buy);
+        assert!(sell > 99.75 && sell <= 99.8, "sell filled at {}", sell);
+    }
+}
+
+#[test]
+fn fee_configs_charge_the_expected_commission() {
+    let cases = [
+        (FeeConfig::None, Liquidity::Taker, 0.0),
+        (FeeConfig::FixedPerTrade { per_trade: 1.25 }, Liquidity::Maker, 1.25),
+        // 名义价值 100 × 2 = 200，10 个基点
+        (FeeConfig::Bps { bps: 10.0 }, Liquidity::Taker, 0.2),
+        (FeeConfig::MakerTaker { maker_bps: -2.0, taker_bps: 5.0 }, Liquidity::Taker, 0.1),
+        // 挂单返佣为负
+        (FeeConfig::MakerTaker { maker_bps: -2.0, taker_bps: 5.0 }, Liquidity::Maker, -0.04),
+    ];
+    for (config, liquidity, expected) in cases {
+        assert_close(config.build().commission(PRICE, 2.0, liquidity), expected);
+    }
+}
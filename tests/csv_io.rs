@@ -0,0 +1,166 @@
+// tests/csv_io.rs
+
+//! # CSV 读写测试
+//!
+//! `CsvBarWriter` 写入表头与按 symbol 过滤后的 `Bar` 行，每 100 行自动刷新一次；
+//! `BarCsvReader` 跳过无法解析的行读入并发布 `Bar`；`FillCsvWriter` 追加的成交可以原样读回。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::csv_io::{BarCsvReader, CsvBarWriter, FillCsvWriter, BAR_CSV_HEADER};
+use message_bus::message::{Bar, FillEvent, Liquidity, OrderSide, DEFAULT_BAR_TIMEFRAME};
+use message_bus::testkit::BarBuilder;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn csv_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("message-bus-{}-{}.csv", std::process::id(), name))
+}
+
+fn bar(symbol: &str, ts_event: u64, close: f64) -> Bar {
+    BarBuilder::new()
+        .symbol(symbol)
+        .ts_event(ts_event)
+        .ohlc(close - 1.0, close + 1.0, close - 2.0, close)
+        .volume(3.5)
+        .build()
+}
+
+fn fill(price: f64, venue_fill_id: Option<&str>) -> FillEvent {
+    FillEvent {
+        order_id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        price,
+        quantity: 0.5,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.025,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Maker,
+        ts_event: 42,
+        venue_fill_id: venue_fill_id.map(str::to_string),
+        correlation_id: Some(Uuid::new_v4()),
+    }
+}
+
+/// 等待文件出现至少 `lines` 行。
+async fn wait_for_lines(path: &PathBuf, lines: usize) -> Vec<String> {
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            let text = std::fs::read_to_string(path).unwrap();
+            if text.lines().count() >= lines {
+                return text.lines().map(str::to_string).collect();
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn rows_for_the_filtered_symbol_are_written_after_the_header() {
+    let bus = MessageBus::new(64);
+    let path = csv_path("filtered");
+    let writer = CsvBarWriter::new(bus.clone(), &path, Some("BTC-USD".to_string())).unwrap();
+    let handles = writer.clone().start("CSV").await;
+
+    bus.publish(bar("BTC-USD", 1_000, 100.0)).await.unwrap();
+    bus.publish(bar("ETH-USD", 2_000, 50.0)).await.unwrap();
+    bus.publish(bar("BTC-USD", 3_000, 101.5)).await.unwrap();
+    let lines = tokio::time::timeout(TIMEOUT, async {
+        loop {
+            writer.flush().await.unwrap();
+            let text = std::fs::read_to_string(&path).unwrap();
+            if text.lines().count() >= 3 {
+                return text.lines().map(str::to_string).collect::<Vec<_>>();
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(lines, [BAR_CSV_HEADER, "1000,BTC-USD,99,101,98,100,3.5", "3000,BTC-USD,100.5,102.5,99.5,101.5,3.5"]);
+
+    for handle in handles {
+        handle.abort();
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn rows_are_flushed_every_hundred_bars() {
+    let bus = MessageBus::new(256);
+    let path = csv_path("batched");
+    let writer = CsvBarWriter::new(bus.clone(), &path, None).unwrap();
+    let handles = writer.start("CSV").await;
+
+    for i in 0..100 {
+        bus.publish(bar("BTC-USD", i, 100.0)).await.unwrap();
+    }
+    // 没有调用 flush，第 100 行写入后自动刷新
+    let lines = wait_for_lines(&path, 101).await;
+    assert_eq!(lines.len(), 101);
+    assert_eq!(lines[100], "99,BTC-USD,99,101,98,100,3.5");
+
+    for handle in handles {
+        handle.abort();
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn bar_reader_skips_malformed_rows_and_publishes_the_rest() {
+    let path = csv_path("bars-in");
+    std::fs::write(
+        &path,
+        "ts_event,symbol,open,high,low,close,volume\n\
+         1000,BTC-USD,99,101,98,100,3.5\n\
+         2000,BTC-USD,oops,101,98,100,3.5\n\
+         3000,ETH-USD,50,51,49,50.5,10\n",
+    )
+    .unwrap();
+    let bus = MessageBus::new(64);
+    let mut rx = bus.subscribe::<Bar>().await;
+    let reader = BarCsvReader::open(bus.clone(), &path).unwrap();
+    assert_eq!(reader.bars().len(), 2);
+
+    let handles = Arc::new(reader).start("CSV.IN").await;
+    let first = rx.recv_timeout(TIMEOUT).await.unwrap();
+    let second = rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((first.ts_event, first.symbol.as_str(), first.close, first.volume), (1000, "BTC-USD", 100.0, 3.5));
+    assert_eq!((second.ts_event, second.symbol.as_str(), second.open, second.close), (3000, "ETH-USD", 50.0, 50.5));
+    assert_eq!(first.timeframe, DEFAULT_BAR_TIMEFRAME);
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn fills_round_trip_and_later_runs_append_without_a_header() {
+    let path = csv_path("fills");
+    let fills = [fill(100.0, Some("V-1")), fill(101.25, None)];
+
+    for fill in &fills {
+        // 每次运行新建写入者，模拟多次运行写入同一个文件
+        let bus = MessageBus::new(64);
+        let writer = FillCsvWriter::new(bus.clone(), &path).unwrap();
+        let handles = writer.start("CSV.FILLS").await;
+        bus.publish(fill.clone()).await.unwrap();
+        drop(bus);
+        for handle in handles {
+            tokio::time::timeout(TIMEOUT, handle).await.unwrap().unwrap();
+        }
+    }
+
+    let mut reader = csv::Reader::from_path(&path).unwrap();
+    let read: Vec<FillEvent> = reader.deserialize().collect::<Result<_, _>>().unwrap();
+    assert_eq!(read, fills);
+    std::fs::remove_file(path).unwrap();
+}
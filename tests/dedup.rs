@@ -0,0 +1,67 @@
+// tests/dedup.rs
+
+//! # 消息去重测试
+//!
+//! 同一个 `OrderRequest` id 发布两次只在去重视图上出现一次；不同 id 都被转发；
+//! 被挤出窗口的 id 再次出现时按首次出现处理。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::dedup::{Deduplicator, SeenWindow};
+use message_bus::message::{OrderRequest, OrderSide, OrderType};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+
+fn order() -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 1.0,
+        trigger_price: None,
+    }
+}
+
+#[tokio::test]
+async fn same_order_id_twice_is_delivered_once() {
+    let bus = MessageBus::new(64);
+    let deduped = bus.clone_with_prefix("deduped");
+    let mut rx = deduped.subscribe::<OrderRequest>().await;
+    let handles = Arc::new(Deduplicator::<OrderRequest>::new(bus.clone(), deduped.clone(), 16)).start("DEDUP").await;
+
+    let first = order();
+    let second = order();
+    bus.publish(first.clone()).await.unwrap();
+    bus.publish(first.clone()).await.unwrap();
+    bus.publish(second.clone()).await.unwrap();
+
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().id, first.id);
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().id, second.id);
+    assert!(matches!(rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[test]
+fn id_evicted_from_the_window_counts_as_new() {
+    let mut seen = SeenWindow::new(2);
+    let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+    assert!(seen.insert(a));
+    assert!(!seen.insert(a));
+    assert!(seen.insert(b));
+    assert!(seen.insert(c));
+    // 容量为 2，最早的 a 已被淘汰
+    assert_eq!(seen.len(), 2);
+    assert!(!seen.contains(&a));
+    assert!(seen.insert(a));
+    assert!(!seen.insert(c));
+}
@@ -0,0 +1,113 @@
+// tests/divergence.rs
+
+//! # 回放分歧检测测试
+//!
+//! 在 `SimulationDriver` 上运行策略与模拟执行引擎并录制行情与成交，
+//! 再把录制的行情回放两次，用 `DivergenceChecker` 确认成交序列完全一致；
+//! 去掉执行引擎的随机种子后，延迟抖动使成交时间不同，检测器报告第一处分歧。
+
+use message_bus::actor::Actor;
+use message_bus::bus::MessageBus;
+use message_bus::divergence::{DivergenceChecker, DivergenceKind};
+use message_bus::execution::{LatencyModel, SimulatedExecutionEngine};
+use message_bus::message::{Bar, FillEvent, WarmupComplete};
+use message_bus::simulation::SimulationDriver;
+use message_bus::store::BusState;
+use message_bus::strategy::SimpleTrendFollower;
+use message_bus::testkit::BarBuilder;
+use std::sync::Arc;
+use std::time::Duration;
+
+const SYMBOL: &str = "BTC-USD";
+const SEED: u64 = 7;
+const SECOND: u64 = 1_000_000_000;
+/// 每次运行都会重新生成的字段：订单 ID 由策略随机生成。
+const RANDOM_FIELDS: [&str; 3] = ["order_id", "correlation_id", "venue_fill_id"];
+
+fn bars() -> Vec<Bar> {
+    [100.0, 103.0, 101.0, 104.0, 105.0, 99.0, 106.0]
+        .into_iter()
+        .enumerate()
+        .map(|(i, close)| BarBuilder::new().ts_event((i as u64 + 1) * SECOND).price(close).volume(10.0).build())
+        .collect()
+}
+
+/// 从录制中取出行情，按录制顺序回放。
+fn recorded_bars(state: &BusState) -> Vec<Bar> {
+    state
+        .messages
+        .iter()
+        .filter(|stored| stored.type_name == std::any::type_name::<Bar>())
+        .map(|stored| serde_json::from_value(stored.envelope.message.clone()).unwrap())
+        .collect()
+}
+
+/// 录制行情与成交的总线。
+fn recording_bus() -> MessageBus {
+    MessageBus::new(1024).with_message_store::<Bar>(1000).with_message_store::<FillEvent>(1000)
+}
+
+/// 在 `bus` 上运行一次模拟并返回录制。`seed` 为 `None` 时执行引擎的延迟抖动不可复现。
+async fn run(
+    bus: MessageBus,
+    bars: Vec<Bar>,
+    seed: Option<u64>,
+    checker: Option<Arc<DivergenceChecker<FillEvent>>>,
+) -> BusState {
+    let mut driver = SimulationDriver::new(bus.clone());
+    let latency = LatencyModel {
+        ack_latency: Duration::from_millis(1),
+        fill_latency: Duration::from_millis(2),
+        jitter: Duration::from_millis(5),
+    };
+    let mut engine = SimulatedExecutionEngine::new(bus.clone()).with_clock(driver.clock()).with_latency(latency);
+    if let Some(seed) = seed {
+        engine = engine.with_seed(seed);
+    }
+    Arc::new(engine).start("EXECUTION").await;
+    Arc::new(SimpleTrendFollower::new(bus.clone(), SYMBOL.to_string())).start("STRATEGY").await;
+    if let Some(checker) = checker {
+        checker.start("DIVERGENCE").await;
+    }
+
+    driver.schedule(0, WarmupComplete { symbol: SYMBOL.to_string(), bars_seen: 0, ts_event: 0 });
+    driver.schedule_bars(bars);
+    driver.run().await;
+    bus.capture_state().await
+}
+
+fn checker(bus: &MessageBus, recording: &BusState) -> Arc<DivergenceChecker<FillEvent>> {
+    Arc::new(DivergenceChecker::from_state(bus.clone(), recording).unwrap().with_ignored_fields(RANDOM_FIELDS))
+}
+
+#[tokio::test]
+async fn replaying_a_recording_twice_does_not_diverge() {
+    let recording = run(recording_bus(), bars(), Some(SEED), None).await;
+
+    for _ in 0..2 {
+        let bus = recording_bus();
+        let checker = checker(&bus, &recording);
+        let replay = run(bus, recorded_bars(&recording), Some(SEED), Some(checker.clone())).await;
+        checker.assert_no_divergence();
+        // 收盘价高于 102 的四根 Bar 各产生一笔成交
+        assert_eq!(checker.finish(), Ok(4));
+        assert_eq!(checker.recorded_len(), 4);
+        assert_eq!(recorded_bars(&replay).len(), bars().len());
+    }
+}
+
+#[tokio::test]
+async fn unseeded_latency_is_reported_as_a_divergence() {
+    let recording = run(recording_bus(), bars(), Some(SEED), None).await;
+
+    let bus = recording_bus();
+    let checker = checker(&bus, &recording);
+    run(bus, recorded_bars(&recording), None, Some(checker.clone())).await;
+    let divergence = checker.finish().unwrap_err();
+    assert_eq!(divergence.index, 0);
+    assert!(divergence.to_string().contains("ts_event"));
+    match divergence.kind {
+        DivergenceKind::Mismatch { field, .. } => assert_eq!(field, "ts_event"),
+        other => panic!("expected a mismatch, got {:?}", other),
+    }
+}
@@ -0,0 +1,51 @@
+// tests/ensemble.rs
+
+//! # 策略组合测试
+//!
+//! 验证 `SignalAggregator` 把多个策略的信号合并为一个净订单。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::ensemble::{CombinationPolicy, SignalAggregator};
+use message_bus::message::{OrderRequest, OrderSide, OrderType, StrategySignal};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn signal(strategy_id: &str, direction: OrderSide, price: f64, ts_event: u64) -> StrategySignal {
+    StrategySignal {
+        strategy_id: strategy_id.to_string(),
+        symbol: "BTC-USD".to_string(),
+        direction,
+        strength: 1.0,
+        price,
+        ts_event,
+    }
+}
+
+#[tokio::test]
+async fn majority_vote_nets_two_buys_and_one_sell_into_one_buy() {
+    let bus = MessageBus::new(16);
+    let mut order_rx = bus.subscribe::<OrderRequest>().await;
+    let aggregator = SignalAggregator::new(bus.clone(), Duration::from_millis(50), CombinationPolicy::MajorityVote)
+        .with_order_quantity(2.0);
+    let handles = Arc::new(aggregator).start("ENSEMBLE").await;
+
+    bus.publish(signal("MOMENTUM", OrderSide::Buy, 100.0, 1)).await.unwrap();
+    bus.publish(signal("MEAN_REVERSION", OrderSide::Sell, 100.5, 2)).await.unwrap();
+    bus.publish(signal("BREAKOUT", OrderSide::Buy, 101.0, 3)).await.unwrap();
+
+    let order = order_rx.recv_timeout(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(order.symbol, "BTC-USD");
+    assert_eq!(order.side, OrderSide::Buy);
+    assert_eq!(order.order_type, OrderType::Market);
+    assert_eq!(order.quantity, 2.0);
+    // 价格取胜出方向上最新的信号
+    assert_eq!(order.price, 101.0);
+
+    // 三个信号只产生一个订单
+    assert!(matches!(order_rx.recv_timeout(Duration::from_millis(200)).await, Err(RecvTimeout::Timeout)));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
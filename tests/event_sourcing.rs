@@ -0,0 +1,256 @@
+// tests/event_sourcing.rs
+
+//! # 事件溯源测试
+//!
+//! `EventSourcingActor` 崩溃重启后从最新快照出发，只重放快照之后的事件；
+//! 与快照位置同一时间戳的事件按 `EventCursor::count` 去重，不会重复计入。
+//! `JsonFileSnapshotStore` 先写临时文件再重命名，写入失败或残留的临时文件不影响已有快照。
+//! `Portfolio` 作为 `EventState<FillEvent>` 由成交折叠而来，可以从 JSON 快照与成交记录重建。
+
+use message_bus::actor::Actor;
+use message_bus::bus::MessageBus;
+use message_bus::event_sourcing::{
+    EventCursor, EventSourcingActor, EventState, InMemorySnapshotStore, JsonFileSnapshotStore, Snapshot, SnapshotStore,
+};
+use message_bus::message::{FillEvent, Liquidity, Message, OrderSide, Timestamped};
+use message_bus::portfolio::Portfolio;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Deposit {
+    id: u32,
+    ts_event: u64,
+}
+impl Message for Deposit {}
+impl Timestamped for Deposit {
+    fn ts_event(&self) -> u64 {
+        self.ts_event
+    }
+}
+
+/// 记录应用过的事件编号，重复或遗漏都会直接体现在列表中。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Ledger {
+    ids: Vec<u32>,
+}
+
+impl EventState<Deposit> for Ledger {
+    fn apply(&mut self, event: &Deposit) {
+        self.ids.push(event.id);
+    }
+}
+
+type LedgerActor = EventSourcingActor<Deposit, Ledger>;
+
+fn bus() -> MessageBus {
+    MessageBus::new(64).with_message_store::<Deposit>(100).with_message_store::<FillEvent>(100)
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("message-bus-{}-{}.json", Uuid::new_v4(), name))
+}
+
+async fn start(actor: &Arc<LedgerActor>) -> Vec<JoinHandle<()>> {
+    actor.clone().start("LEDGER").await
+}
+
+/// 模拟崩溃：中止 Actor 的任务，不保存快照。
+fn crash(handles: Vec<JoinHandle<()>>) {
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+async fn publish(bus: &MessageBus, id: u32, ts_event: u64) {
+    bus.publish(Deposit { id, ts_event }).await.unwrap();
+}
+
+/// 等待 Actor 处理到给定的事件位置。
+async fn wait_for_cursor<E: Timestamped, S: EventState<E> + Clone + Send + 'static>(
+    actor: &EventSourcingActor<E, S>,
+    cursor: EventCursor,
+) {
+    tokio::time::timeout(TIMEOUT, async {
+        while actor.cursor() != Some(cursor) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("cursor stuck at {:?}, expected {:?}", actor.cursor(), cursor));
+}
+
+#[tokio::test]
+async fn restart_replays_only_the_events_after_the_snapshot() {
+    let bus = bus();
+    let snapshots: Arc<InMemorySnapshotStore<Ledger>> = Arc::new(InMemorySnapshotStore::default());
+    let first = Arc::new(LedgerActor::new(bus.clone()).with_snapshot_store(snapshots.clone()));
+    let handles = start(&first).await;
+
+    for (id, ts) in [(1, 10), (2, 20), (3, 30)] {
+        publish(&bus, id, ts).await;
+    }
+    wait_for_cursor(&first, EventCursor { ts: 30, count: 1 }).await;
+    assert_eq!(first.take_snapshot().ids, [1, 2, 3]);
+
+    publish(&bus, 4, 40).await;
+    publish(&bus, 5, 50).await;
+    wait_for_cursor(&first, EventCursor { ts: 50, count: 1 }).await;
+    crash(handles);
+    // Actor 停止期间发布的事件同样要在重启时补上
+    publish(&bus, 6, 60).await;
+
+    let second = Arc::new(LedgerActor::new(bus.clone()).with_snapshot_store(snapshots.clone()));
+    let handles = start(&second).await;
+    // `start` 返回时状态已经追上：快照中的事件不重复，快照之后的事件各计一次
+    assert_eq!(second.current_state().ids, [1, 2, 3, 4, 5, 6]);
+    assert_eq!(second.cursor(), Some(EventCursor { ts: 60, count: 1 }));
+    assert_eq!(snapshots.load_latest().unwrap().unwrap().cursor, Some(EventCursor { ts: 30, count: 1 }));
+
+    // 之后的事件实时应用
+    publish(&bus, 7, 70).await;
+    wait_for_cursor(&second, EventCursor { ts: 70, count: 1 }).await;
+    assert_eq!(second.current_state().ids, [1, 2, 3, 4, 5, 6, 7]);
+    crash(handles);
+}
+
+#[tokio::test]
+async fn events_sharing_the_snapshot_timestamp_are_not_applied_twice() {
+    let bus = bus();
+    let snapshots: Arc<InMemorySnapshotStore<Ledger>> = Arc::new(InMemorySnapshotStore::default());
+    let first = Arc::new(LedgerActor::new(bus.clone()).with_snapshot_store(snapshots.clone()));
+    let handles = start(&first).await;
+
+    publish(&bus, 1, 5).await;
+    publish(&bus, 2, 10).await;
+    publish(&bus, 3, 10).await;
+    wait_for_cursor(&first, EventCursor { ts: 10, count: 2 }).await;
+    first.take_snapshot();
+    // 快照之后仍有与快照位置同一时间戳的事件
+    publish(&bus, 4, 10).await;
+    publish(&bus, 5, 10).await;
+    publish(&bus, 6, 20).await;
+    wait_for_cursor(&first, EventCursor { ts: 20, count: 1 }).await;
+    crash(handles);
+
+    let second = Arc::new(LedgerActor::new(bus.clone()).with_snapshot_store(snapshots));
+    let handles = start(&second).await;
+    // 从时间戳 10 重放 2、3、4、5、6，其中前两条已计入快照
+    assert_eq!(second.current_state().ids, [1, 2, 3, 4, 5, 6]);
+    assert_eq!(second.cursor(), Some(EventCursor { ts: 20, count: 1 }));
+    crash(handles);
+}
+
+#[tokio::test]
+async fn restart_without_a_snapshot_replays_everything_retained() {
+    let bus = bus();
+    let snapshots: Arc<InMemorySnapshotStore<Ledger>> = Arc::new(InMemorySnapshotStore::default());
+    publish(&bus, 1, 10).await;
+    publish(&bus, 2, 10).await;
+
+    let actor = Arc::new(LedgerActor::new(bus.clone()).with_snapshot_store(snapshots));
+    let handles = start(&actor).await;
+    assert_eq!(actor.current_state().ids, [1, 2]);
+    assert_eq!(actor.cursor(), Some(EventCursor { ts: 10, count: 2 }));
+    crash(handles);
+}
+
+#[test]
+fn json_snapshots_are_replaced_atomically() {
+    let path = temp_path("ledger");
+    let tmp = path.with_extension("tmp");
+    let store = JsonFileSnapshotStore::new(&path);
+    assert_eq!(SnapshotStore::<Ledger>::load_latest(&store).unwrap(), None);
+
+    let first = Snapshot { state: Ledger { ids: vec![1, 2] }, cursor: Some(EventCursor { ts: 10, count: 2 }) };
+    store.save(&first).unwrap();
+    // 重命名之后不留下临时文件
+    assert!(path.exists());
+    assert!(!tmp.exists());
+    assert_eq!(store.load_latest().unwrap(), Some(first.clone()));
+
+    // 上次保存中途崩溃留下的半截临时文件不影响读取
+    std::fs::write(&tmp, b"{\"state\":{\"ids\":[1,2,3").unwrap();
+    assert_eq!(store.load_latest().unwrap(), Some(first.clone()));
+
+    // 写入临时文件失败时返回错误，已有快照保持不变
+    std::fs::remove_file(&tmp).unwrap();
+    std::fs::create_dir(&tmp).unwrap();
+    let second = Snapshot { state: Ledger { ids: vec![1, 2, 3] }, cursor: Some(EventCursor { ts: 20, count: 1 }) };
+    assert!(store.save(&second).is_err());
+    assert_eq!(store.load_latest().unwrap(), Some(first));
+
+    std::fs::remove_dir(&tmp).unwrap();
+    store.save(&second).unwrap();
+    assert_eq!(store.load_latest().unwrap(), Some(second));
+    assert!(!tmp.exists());
+    std::fs::remove_file(&path).unwrap();
+}
+
+fn fill(side: OrderSide, price: f64, ts_event: u64) -> FillEvent {
+    FillEvent {
+        order_id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side,
+        price,
+        quantity: 1.0,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.5,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Taker,
+        ts_event,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+#[tokio::test]
+async fn portfolio_is_rebuilt_from_a_json_snapshot_and_later_fills() {
+    let fills = [
+        fill(OrderSide::Buy, 100.0, 1),
+        fill(OrderSide::Buy, 110.0, 2),
+        fill(OrderSide::Sell, 120.0, 3),
+        fill(OrderSide::Buy, 90.0, 4),
+    ];
+    // 直接折叠全部成交得到的持仓，与 `apply_fill` 相同
+    let mut expected = Portfolio::default();
+    for fill in &fills {
+        EventState::apply(&mut expected, fill);
+    }
+    let mut direct = Portfolio::default();
+    for fill in &fills {
+        direct.apply_fill(fill);
+    }
+    assert_eq!(expected, direct);
+    let position = expected.position("BTC-USD").unwrap();
+    assert_eq!(position.quantity, 2.0);
+    assert_eq!(position.realized_pnl, 15.0);
+
+    let bus = bus();
+    let path = temp_path("portfolio");
+    let snapshots: Arc<dyn SnapshotStore<Portfolio>> = Arc::new(JsonFileSnapshotStore::new(&path));
+    let first = Arc::new(EventSourcingActor::<FillEvent, Portfolio>::new(bus.clone()).with_snapshot_store(snapshots.clone()));
+    let handles = first.clone().start("PORTFOLIO").await;
+    for fill in &fills[..2] {
+        bus.publish(fill.clone()).await.unwrap();
+    }
+    wait_for_cursor(&first, EventCursor { ts: 2, count: 1 }).await;
+    first.take_snapshot();
+    crash(handles);
+    for fill in &fills[2..] {
+        bus.publish(fill.clone()).await.unwrap();
+    }
+
+    let second = Arc::new(EventSourcingActor::<FillEvent, Portfolio>::new(bus.clone()).with_snapshot_store(snapshots));
+    let handles = second.clone().start("PORTFOLIO").await;
+    assert_eq!(*second.current_state(), expected);
+    crash(handles);
+    std::fs::remove_file(&path).unwrap();
+}
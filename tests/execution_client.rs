@@ -0,0 +1,173 @@
+// tests/execution_client.rs
+
+//! # 执行客户端测试
+//!
+//! 用模拟交易场所的 `MockClient` 驱动 `ExecutionEngine`：总线上的订单与撤单交给客户端，
+//! 客户端的异步回报作为 `OrderAccepted` / `FillEvent` / `OrderCanceled` 发布；
+//! 同步拒绝发布为带原因的 `OrderRejected`，通信失败为 `VenueRejected`，未知订单的撤单为 `CancelRejected`。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::client::{ExecAck, ExecError, ExecutionClient, ExecutionEngine, ExecutionReport};
+use message_bus::message::{
+    CancelOrderRequest, CancelRejectReason, CancelRejected, FillEvent, Liquidity, OrderAccepted, OrderCanceled,
+    OrderRejected, OrderRequest, OrderSide, OrderType, RejectReason,
+};
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 模拟交易场所：数量超过 10 的订单同步拒绝，`OFFLINE` 上的订单通信失败；
+/// 其余订单先确认，数量为 1 的订单随即全部成交，更大的订单挂着等待撤单。
+struct MockClient {
+    open: Mutex<HashMap<Uuid, OrderRequest>>,
+    submitted: Mutex<Vec<Uuid>>,
+    report_tx: mpsc::UnboundedSender<ExecutionReport>,
+    report_rx: tokio::sync::Mutex<mpsc::UnboundedReceiver<ExecutionReport>>,
+}
+
+impl MockClient {
+    fn new() -> Self {
+        let (report_tx, report_rx) = mpsc::unbounded_channel();
+        Self {
+            open: Mutex::new(HashMap::new()),
+            submitted: Mutex::new(Vec::new()),
+            report_tx,
+            report_rx: tokio::sync::Mutex::new(report_rx),
+        }
+    }
+
+    fn report(&self, report: ExecutionReport) {
+        self.report_tx.send(report).unwrap();
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutionClient for MockClient {
+    async fn connect(self: Arc<Self>, _actor_name: &str) -> Vec<JoinHandle<()>> {
+        Vec::new()
+    }
+
+    async fn submit(&self, order: OrderRequest) -> Result<ExecAck, ExecError> {
+        self.submitted.lock().unwrap().push(order.id);
+        if order.quantity > 10.0 {
+            return Err(ExecError::Rejected { reason: RejectReason::InvalidQuantity, detail: "too large".to_string() });
+        }
+        if order.symbol == "OFFLINE" {
+            return Err(ExecError::Transport(Box::new(io::Error::new(io::ErrorKind::ConnectionRefused, "venue down"))));
+        }
+        self.report(ExecutionReport::Accepted(OrderAccepted { order_id: order.id, symbol: order.symbol.clone(), ts_event: 1 }));
+        if order.quantity == 1.0 {
+            self.report(ExecutionReport::Fill(FillEvent {
+                order_id: order.id,
+                symbol: order.symbol.clone(),
+                side: order.side.clone(),
+                price: order.price,
+                quantity: order.quantity,
+                leaves_qty: 0.0,
+                is_final: true,
+                commission: 0.0,
+                commission_currency: "USD".to_string(),
+                liquidity: Liquidity::Taker,
+                ts_event: 2,
+                venue_fill_id: Some("MOCK-1".to_string()),
+                correlation_id: Some(order.id),
+            }));
+        } else {
+            self.open.lock().unwrap().insert(order.id, order.clone());
+        }
+        Ok(ExecAck { order_id: order.id, venue_order_id: Some(format!("V-{}", order.id)), ts_event: 1 })
+    }
+
+    async fn cancel(&self, cancel: CancelOrderRequest) -> Result<(), ExecError> {
+        let order = self.open.lock().unwrap().remove(&cancel.order_id).ok_or(ExecError::UnknownOrder(cancel.order_id))?;
+        self.report(ExecutionReport::Canceled(OrderCanceled {
+            order_id: order.id,
+            symbol: order.symbol,
+            remaining_qty: order.quantity,
+        }));
+        Ok(())
+    }
+
+    async fn next_report(&self) -> Option<ExecutionReport> {
+        self.report_rx.lock().await.recv().await
+    }
+}
+
+fn order(symbol: &str, quantity: f64) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity,
+        trigger_price: None,
+    }
+}
+
+#[tokio::test]
+async fn orders_and_cancels_flow_through_the_client_and_back_to_the_bus() {
+    let bus = MessageBus::new(64);
+    let mut accepted_rx = bus.subscribe::<OrderAccepted>().await;
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let mut canceled_rx = bus.subscribe::<OrderCanceled>().await;
+    let engine = Arc::new(ExecutionEngine::new(bus.clone(), MockClient::new()));
+    let handles = engine.clone().start("EXECUTION").await;
+
+    let filled = order("BTC-USD", 1.0);
+    bus.publish(filled.clone()).await.unwrap();
+    assert_eq!(accepted_rx.recv_timeout(TIMEOUT).await.unwrap().order_id, filled.id);
+    let fill = fill_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((fill.order_id, fill.quantity, fill.is_final), (filled.id, 1.0, true));
+    assert_eq!(fill.venue_fill_id.as_deref(), Some("MOCK-1"));
+
+    let resting = order("BTC-USD", 5.0);
+    bus.publish(resting.clone()).await.unwrap();
+    assert_eq!(accepted_rx.recv_timeout(TIMEOUT).await.unwrap().order_id, resting.id);
+    bus.publish(CancelOrderRequest { order_id: resting.id, symbol: "BTC-USD".to_string() }).await.unwrap();
+    let canceled = canceled_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((canceled.order_id, canceled.remaining_qty), (resting.id, 5.0));
+
+    assert_eq!(*engine.client().submitted.lock().unwrap(), vec![filled.id, resting.id]);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn client_errors_are_published_as_rejections() {
+    let bus = MessageBus::new(64);
+    let mut rejected_rx = bus.subscribe::<OrderRejected>().await;
+    let mut cancel_rejected_rx = bus.subscribe::<CancelRejected>().await;
+    let handles = Arc::new(ExecutionEngine::new(bus.clone(), MockClient::new())).start("EXECUTION").await;
+
+    // 场所同步拒绝，保留场所给出的原因
+    let too_large = order("BTC-USD", 50.0);
+    bus.publish(too_large.clone()).await.unwrap();
+    let rejected = rejected_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((rejected.order_id, rejected.reason), (too_large.id, RejectReason::InvalidQuantity));
+
+    // 通信失败
+    let offline = order("OFFLINE", 1.0);
+    bus.publish(offline.clone()).await.unwrap();
+    let rejected = rejected_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((rejected.order_id, rejected.reason), (offline.id, RejectReason::VenueRejected));
+    assert!(rejected.detail.contains("venue down"), "{}", rejected.detail);
+
+    let unknown = Uuid::new_v4();
+    bus.publish(CancelOrderRequest { order_id: unknown, symbol: "BTC-USD".to_string() }).await.unwrap();
+    let rejected = cancel_rejected_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((rejected.order_id, rejected.reason), (unknown, CancelRejectReason::UnknownOrder));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
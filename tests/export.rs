@@ -0,0 +1,230 @@
+// tests/export.rs
+
+//! # 事件导出测试
+//!
+//! 短暂运行演示流水线（数据源、策略、模拟撮合、组合），把行情、订单、成交与持仓更新导出为 JSON lines，
+//! 逐行解析回对应的消息类型；再验证按大小与按日期的文件轮转，以及写入阻塞时丢弃最旧的行而不阻塞总线。
+
+use message_bus::actor::Actor;
+use message_bus::bus::MessageBus;
+use message_bus::clock::VirtualClock;
+use message_bus::data::SimulatedDataEngine;
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::export::{EventExporter, Rotation};
+use message_bus::message::{Bar, FillEvent, OrderRequest, PositionUpdate, WarmupComplete};
+use message_bus::portfolio::PortfolioTracker;
+use message_bus::strategy::SimpleTrendFollower;
+use message_bus::testkit::BarBuilder;
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+const SYMBOL: &str = "BTC-USD";
+const DAY: u64 = 86_400 * 1_000_000_000;
+
+/// 每个测试独立的临时目录。
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("message-bus-export-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 中止所有任务并等待结束；导出器的写入任务在接收任务结束后写完剩余的行再退出。
+async fn shutdown(handles: Vec<JoinHandle<()>>) {
+    for handle in &handles {
+        handle.abort();
+    }
+    futures::future::join_all(handles).await;
+}
+
+/// 等待写入任务写出 `n` 行。中止接收任务会丢弃尚未接收的消息，关闭前需要先等待。
+async fn wait_exported(exporter: &EventExporter, n: u64) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while exporter.exported() < n {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("exported {} of {} lines", exporter.exported(), n));
+}
+
+fn read_lines(path: &Path) -> Vec<Value> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("invalid JSON line {:?}: {}", line, e)))
+        .collect()
+}
+
+#[tokio::test]
+async fn demo_pipeline_exports_parseable_lines() {
+    let dir = temp_dir("pipeline");
+    let path = dir.join("events.jsonl");
+    let bus = MessageBus::new(1024);
+    let exporter = Arc::new(
+        EventExporter::to_file(bus.clone(), &path)
+            .unwrap()
+            .with_type::<Bar>()
+            .with_type::<OrderRequest>()
+            .with_type::<FillEvent>()
+            .with_type::<PositionUpdate>()
+            .with_flush_interval(Duration::from_millis(100)),
+    );
+
+    let mut handles = exporter.clone().start("EXPORT").await;
+    handles.extend(Arc::new(PortfolioTracker::new(bus.clone())).start("PORTFOLIO").await);
+    handles.extend(Arc::new(SimulatedExecutionEngine::new(bus.clone())).start("EXECUTION").await);
+    handles.extend(Arc::new(SimpleTrendFollower::new(bus.clone(), SYMBOL.to_string())).start("STRATEGY").await);
+    bus.publish(WarmupComplete { symbol: SYMBOL.to_string(), bars_seen: 0, ts_event: 0 }).await.unwrap();
+    // 数据源每 500ms 发布一根 Bar，收盘价从 100 起逐根加 1，超过 102 后策略下单
+    handles.extend(Arc::new(SimulatedDataEngine::new(bus.clone(), SYMBOL.to_string())).start("DATA").await);
+    tokio::time::sleep(Duration::from_millis(2_300)).await;
+    shutdown(handles).await;
+
+    let lines = read_lines(&path);
+    assert_eq!(lines.len() as u64, exporter.exported());
+    assert_eq!(exporter.dropped(), 0);
+    let (mut bars, mut orders, mut fills, mut updates) = (0, 0, 0, 0);
+    for line in lines {
+        assert!(line["ts_event"].is_u64(), "missing ts_event in {}", line);
+        match line["type"].as_str().unwrap() {
+            "Bar" => {
+                let bar: Bar = serde_json::from_value(line.clone()).unwrap();
+                assert_eq!(bar.ts_event, line["ts_event"].as_u64().unwrap());
+                bars += 1;
+            },
+            "OrderRequest" => {
+                let order: OrderRequest = serde_json::from_value(line).unwrap();
+                assert_eq!(order.symbol, SYMBOL);
+                orders += 1;
+            },
+            "FillEvent" => {
+                serde_json::from_value::<FillEvent>(line).unwrap();
+                fills += 1;
+            },
+            "PositionUpdate" => {
+                serde_json::from_value::<PositionUpdate>(line).unwrap();
+                updates += 1;
+            },
+            other => panic!("unexpected type {}", other),
+        }
+    }
+    assert!(bars >= 4, "expected at least 4 bars, got {}", bars);
+    assert!(orders >= 1 && fills >= 1 && updates >= 1, "orders {} fills {} updates {}", orders, fills, updates);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn rotates_by_size() {
+    let dir = temp_dir("size");
+    let path = dir.join("bars.jsonl");
+    let bus = MessageBus::new(1024);
+    let exporter = Arc::new(
+        EventExporter::to_file(bus.clone(), &path).unwrap().with_type::<Bar>().with_rotation(Rotation::Size(1_000)),
+    );
+    let handles = exporter.clone().start("EXPORT").await;
+    for i in 0..20 {
+        bus.publish(BarBuilder::new().ts_event(i).price(100.0).build()).await.unwrap();
+    }
+    wait_exported(&exporter, 20).await;
+    shutdown(handles).await;
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().path()).collect();
+    files.sort();
+    assert!(files.len() > 1, "expected rotated files, got {:?}", files);
+    assert!(files.contains(&dir.join("bars.1.jsonl")));
+    let mut timestamps = Vec::new();
+    for file in &files {
+        assert!(std::fs::metadata(file).unwrap().len() <= 1_000, "{} exceeds the size limit", file.display());
+        timestamps.extend(read_lines(file).iter().map(|line| line["ts_event"].as_u64().unwrap()));
+    }
+    timestamps.sort();
+    assert_eq!(timestamps, (0..20).collect::<Vec<_>>());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn rotates_daily() {
+    let dir = temp_dir("daily");
+    let path = dir.join("bars.jsonl");
+    let bus = MessageBus::new(1024);
+    // 2026-10-16 12:00 UTC
+    let clock = Arc::new(VirtualClock::new(20_742 * DAY + DAY / 2));
+    let exporter = Arc::new(
+        EventExporter::to_file(bus.clone(), &path)
+            .unwrap()
+            .with_type::<Bar>()
+            .with_rotation(Rotation::Daily)
+            .with_clock(clock.clone()),
+    );
+    let handles = exporter.clone().start("EXPORT").await;
+    bus.publish(BarBuilder::new().ts_event(1).price(100.0).build()).await.unwrap();
+    wait_exported(&exporter, 1).await;
+    clock.advance_to(20_743 * DAY + 1);
+    bus.publish(BarBuilder::new().ts_event(2).price(101.0).build()).await.unwrap();
+    wait_exported(&exporter, 2).await;
+    shutdown(handles).await;
+
+    let rotated = read_lines(&dir.join("bars.2026-10-16.jsonl"));
+    assert_eq!(rotated.iter().map(|line| line["ts_event"].as_u64().unwrap()).collect::<Vec<_>>(), [1]);
+    let current = read_lines(&path);
+    assert_eq!(current.iter().map(|line| line["ts_event"].as_u64().unwrap()).collect::<Vec<_>>(), [2]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// 第一次写入时阻塞，直到测试放行；记录写入的内容。
+struct GatedWriter {
+    entered: mpsc::Sender<()>,
+    release: Mutex<Option<mpsc::Receiver<()>>>,
+    written: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Write for GatedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(release) = self.release.lock().unwrap().take() {
+            let _ = self.entered.send(());
+            let _ = release.recv();
+        }
+        self.written.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn slow_output_drops_oldest_lines_without_blocking_the_bus() {
+    let (entered_tx, entered_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel();
+    let written = Arc::new(Mutex::new(Vec::new()));
+    let writer = GatedWriter { entered: entered_tx, release: Mutex::new(Some(release_rx)), written: written.clone() };
+    let bus = MessageBus::new(1024);
+    let exporter = Arc::new(EventExporter::to_writer(bus.clone(), writer).with_type::<Bar>().with_buffer_capacity(5));
+    let handles = exporter.clone().start("EXPORT").await;
+
+    // 第一行让写入任务阻塞在输出上
+    bus.publish(BarBuilder::new().price(100.0).build()).await.unwrap();
+    tokio::task::spawn_blocking(move || entered_rx.recv().unwrap()).await.unwrap();
+    for ts in 1..=20 {
+        bus.publish(BarBuilder::new().ts_event(ts).price(100.0).build()).await.unwrap();
+    }
+    // 等待接收任务处理完所有行：缓冲区只保留最近 5 行
+    while exporter.dropped() < 15 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    release_tx.send(()).unwrap();
+    shutdown(handles).await;
+
+    assert_eq!(exporter.dropped(), 15);
+    assert_eq!(exporter.exported(), 6);
+    let text = String::from_utf8(written.lock().unwrap().clone()).unwrap();
+    let timestamps: Vec<u64> =
+        text.lines().map(|line| serde_json::from_str::<Value>(line).unwrap()["ts_event"].as_u64().unwrap()).collect();
+    assert_eq!(timestamps, [0, 16, 17, 18, 19, 20]);
+}
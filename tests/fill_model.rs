@@ -0,0 +1,129 @@
+// tests/fill_model.rs
+
+//! # 成交模型测试
+//!
+//! 每种 `FillModel` 都能让市价单在有限笔成交内到达 `is_final`，子成交数量之和等于订单数量；
+//! `Partial` 的 `max_child_qty` 与 `Participation` 的参与率不为正时 `with_fill_model` 拒绝该模型，
+//! 概率模型抽到 0 比例时剩余数量仍然递减。
+
+use message_bus::bus::MessageBus;
+use message_bus::execution::{FillModel, SimulatedExecutionEngine};
+use message_bus::message::{FillEvent, OrderAccepted, OrderRequest, OrderSide, OrderType};
+use message_bus::testkit::{ActorTestHarness, BarBuilder, TestBus};
+use rand::distributions::Uniform;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const SYMBOL: &str = "BTC-USD";
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn market_order(quantity: f64) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: SYMBOL.to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity,
+        trigger_price: None,
+    }
+}
+
+/// 提交一笔数量为 1 的市价单，收集成交直到 `is_final`；`with_bars` 时每等待一笔成交前先发送一根 `Bar`。
+async fn fills_until_final(fill_model: FillModel, with_bars: bool) -> Vec<FillEvent> {
+    let test_bus = TestBus::new(256);
+    let engine = Arc::new(SimulatedExecutionEngine::new(test_bus.bus()).with_fill_model(fill_model).with_seed(7));
+    let mut venue = ActorTestHarness::start(test_bus, engine).await;
+
+    let order = market_order(1.0);
+    venue.send(order.clone()).await;
+    venue.expect_message::<OrderAccepted>(TIMEOUT).await;
+    let mut fills = Vec::new();
+    loop {
+        if with_bars {
+            venue.send(BarBuilder::new().volume(2.0).build()).await;
+        }
+        let fill = venue.expect_message::<FillEvent>(TIMEOUT).await;
+        assert_eq!(fill.order_id, order.id);
+        assert!(fill.quantity > 0.0, "zero-quantity fill {:?}", fill);
+        let is_final = fill.is_final;
+        fills.push(fill);
+        if is_final {
+            break;
+        }
+        assert!(fills.len() < 1_000, "order never completed");
+    }
+    let filled: f64 = fills.iter().map(|fill| fill.quantity).sum();
+    assert!((filled - 1.0).abs() < 1e-9, "filled {}", filled);
+    assert_eq!(fills.last().unwrap().leaves_qty, 0.0);
+    fills
+}
+
+#[tokio::test]
+async fn immediate_fills_in_one_child() {
+    assert_eq!(fills_until_final(FillModel::Immediate, false).await.len(), 1);
+}
+
+#[tokio::test]
+async fn partial_fills_in_max_child_qty_slices() {
+    let model = FillModel::Partial { max_child_qty: 0.3, delay_between: Duration::from_millis(1) };
+    let fills = fills_until_final(model, false).await;
+    assert_eq!(fills.len(), 4);
+    assert!((fills[3].quantity - 0.1).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn probabilistic_fills_until_final() {
+    let model = FillModel::Probabilistic {
+        fill_ratio_distribution: Uniform::new(0.2, 0.5),
+        delay_between: Duration::from_millis(1),
+    };
+    let fills = fills_until_final(model, false).await;
+    assert!((2..=5).contains(&fills.len()), "{} fills", fills.len());
+}
+
+#[tokio::test]
+async fn probabilistic_zero_ratios_still_make_progress() {
+    // 每次都抽到 0：子成交数量被抬到最小值，剩余数量严格递减，而不是无限调度空成交
+    let model = FillModel::Probabilistic {
+        fill_ratio_distribution: Uniform::new_inclusive(0.0, 0.0),
+        delay_between: Duration::from_millis(1),
+    };
+    let test_bus = TestBus::new(256);
+    let engine = Arc::new(SimulatedExecutionEngine::new(test_bus.bus()).with_fill_model(model));
+    let mut venue = ActorTestHarness::start(test_bus, engine).await;
+
+    venue.send(market_order(1.0)).await;
+    let mut leaves = 1.0;
+    for _ in 0..3 {
+        let fill = venue.expect_message::<FillEvent>(TIMEOUT).await;
+        assert!(fill.quantity > 0.0);
+        assert!(fill.leaves_qty < leaves);
+        leaves = fill.leaves_qty;
+    }
+}
+
+#[tokio::test]
+async fn participation_fills_until_final() {
+    // 每根 Bar 成交量 2，参与率 25%，每根成交 0.5
+    let fills = fills_until_final(FillModel::Participation { participation_rate: 0.25 }, true).await;
+    assert_eq!(fills.len(), 2);
+}
+
+#[test]
+fn non_positive_parameters_are_rejected() {
+    for max_child_qty in [0.0, -1.0, f64::NAN] {
+        let model = FillModel::Partial { max_child_qty, delay_between: Duration::ZERO };
+        assert!(model.validate().is_err(), "accepted max_child_qty {}", max_child_qty);
+    }
+    assert!(FillModel::Participation { participation_rate: 0.0 }.validate().is_err());
+    assert!(FillModel::Partial { max_child_qty: 0.5, delay_between: Duration::ZERO }.validate().is_ok());
+}
+
+#[test]
+#[should_panic(expected = "max_child_qty must be positive")]
+fn engine_refuses_a_zero_child_qty() {
+    let bus = MessageBus::new(16);
+    let _ = SimulatedExecutionEngine::new(bus).with_fill_model(FillModel::Partial { max_child_qty: 0.0, delay_between: Duration::ZERO });
+}
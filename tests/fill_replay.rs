@@ -0,0 +1,140 @@
+// tests/fill_replay.rs
+
+//! # 成交重放测试
+//!
+//! `replay_from_store` 把订阅之前发布的消息注入 `SubscriptionHandle` 的回环通道并标记为 `is_replay`，
+//! 订阅之后（序号不小于 `live_from_seq`）发布的消息只从实时通道收到一次。
+//! `PortfolioTracker::with_fill_replay` 以此在启动时重建持仓：重放的成交只更新持仓，
+//! 不发布 `PositionUpdate` 与 `PortfolioSnapshot`。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{BusError, MessageBus, ReceiverExt, RecvTimeout, SubscriptionHandle};
+use message_bus::message::{FillEvent, Liquidity, Message, OrderSide, PortfolioSnapshot, PositionUpdate, Quote};
+use message_bus::portfolio::PortfolioTracker;
+use message_bus::store::Envelope;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const QUIET: Duration = Duration::from_millis(100);
+
+fn fill(price: f64, ts_event: u64) -> FillEvent {
+    FillEvent {
+        order_id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        price,
+        quantity: 1.0,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.0,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Taker,
+        ts_event,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+fn bus() -> MessageBus {
+    MessageBus::new(64).with_message_store::<FillEvent>(100)
+}
+
+async fn next<M: Message>(handle: &mut SubscriptionHandle<M>) -> Envelope<M> {
+    tokio::time::timeout(TIMEOUT, handle.recv()).await.expect("no message").unwrap()
+}
+
+async fn assert_quiet<M: Message>(handle: &mut SubscriptionHandle<M>) {
+    assert!(tokio::time::timeout(QUIET, handle.recv()).await.is_err(), "unexpected message");
+}
+
+#[tokio::test]
+async fn replayed_messages_precede_live_ones_and_are_not_delivered_twice() {
+    let bus = bus();
+    let (before_a, before_b) = (fill(100.0, 1), fill(101.0, 2));
+    bus.publish(before_a.clone()).await.unwrap();
+    bus.publish(before_b.clone()).await.unwrap();
+
+    let mut handle = bus.subscribe_handle::<FillEvent>().await;
+    // 订阅之后发布的成交已在存储中，但序号不小于 `live_from_seq`，只从实时通道收到
+    let after = fill(102.0, 3);
+    bus.publish(after.clone()).await.unwrap();
+    assert_eq!(bus.replay_from_store(&handle, 0).unwrap(), 2);
+
+    for expected in [&before_a, &before_b] {
+        let envelope = next(&mut handle).await;
+        assert!(envelope.is_replay);
+        assert_eq!(envelope.message.order_id, expected.order_id);
+    }
+    let live = next(&mut handle).await;
+    assert!(!live.is_replay);
+    assert_eq!(live.message.order_id, after.order_id);
+    assert_quiet(&mut handle).await;
+
+    // 再次重放同样跳过订阅之后的成交
+    assert_eq!(bus.replay_from_store(&handle, 0).unwrap(), 2);
+    assert!(next(&mut handle).await.is_replay);
+    assert!(next(&mut handle).await.is_replay);
+    assert_quiet(&mut handle).await;
+}
+
+#[tokio::test]
+async fn replay_is_private_to_the_handle_and_filtered_by_event_time() {
+    let bus = bus();
+    for ts in 1..=3 {
+        bus.publish(fill(100.0, ts)).await.unwrap();
+    }
+    let mut other = bus.subscribe::<FillEvent>().await;
+    let mut handle = bus.subscribe_handle::<FillEvent>().await;
+
+    // 只重放事件时间不早于 2 的成交，其他订阅者收不到重放
+    assert_eq!(bus.replay_from_store(&handle, 2).unwrap(), 2);
+    assert_eq!(next(&mut handle).await.message.ts_event, 2);
+    assert_eq!(next(&mut handle).await.message.ts_event, 3);
+    assert!(matches!(other.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    // 没有登记到消息存储的类型无法重放
+    let mut quotes = bus.subscribe_handle::<Quote>().await;
+    assert!(matches!(bus.replay_from_store(&quotes, 0), Err(BusError::UnknownType { .. })));
+    assert_quiet(&mut quotes).await;
+}
+
+#[tokio::test]
+async fn tracker_rebuilds_positions_from_replayed_fills_without_publishing() {
+    let bus = bus();
+    let mut updates = bus.subscribe::<PositionUpdate>().await;
+    let mut snapshots = bus.subscribe::<PortfolioSnapshot>().await;
+    // 事件时间早于重放起点的成交不计入
+    bus.publish(fill(50.0, 1)).await.unwrap();
+    bus.publish(fill(100.0, 2)).await.unwrap();
+    bus.publish(fill(110.0, 3)).await.unwrap();
+
+    let tracker = Arc::new(PortfolioTracker::new(bus.clone()).with_fill_replay(2).with_initial_capital(10_000.0));
+    let handles = tracker.clone().start("PORTFOLIO").await;
+    tokio::time::timeout(TIMEOUT, async {
+        while tracker.position("BTC-USD").map(|p| p.quantity) != Some(2.0) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("replayed fills were not applied");
+    assert_eq!(tracker.position("BTC-USD").unwrap().avg_price, 105.0);
+
+    // 重放的成交已经发布过持仓更新与快照，不再重复发布
+    assert!(matches!(updates.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    assert!(matches!(snapshots.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    // 实时成交照常发布，且只计入一次
+    bus.publish(fill(120.0, 4)).await.unwrap();
+    let update = updates.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(update.net_position, 3.0);
+    assert_eq!(update.avg_price, 110.0);
+    snapshots.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(tracker.position("BTC-USD").unwrap().quantity, 3.0);
+    assert!(matches!(updates.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,110 @@
+// tests/fix.rs
+
+//! # FIX 桥接测试
+//!
+//! `FixBridgeActor` 把 `OrderRequest` 转换为带 FIX 4.2 取值的 `FixNewOrderSingle`，
+//! 再把该订单的部分成交与完全成交回报转换为 `FillEvent`；非成交回报、未知订单与已结束订单的回报被忽略。
+
+use message_bus::bus::MessageBus;
+use message_bus::fix::{
+    FixBridgeActor, FixExecType, FixExecutionReport, FixNewOrderSingle, FixOrdStatus, FixOrdType, FixSide,
+    FixTimeInForce,
+};
+use message_bus::message::{FillEvent, OrderRequest, OrderSide, OrderType};
+use message_bus::testkit::{ActorTestHarness, TestBus};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+
+fn order(side: OrderSide, order_type: OrderType) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "AAPL".to_string(),
+        side,
+        order_type,
+        price: 190.0,
+        quantity: 100.0,
+        trigger_price: None,
+    }
+}
+
+fn report(orderid: &str, exec_type: FixExecType, ord_status: FixOrdStatus, last_qty: f64, leaves_qty: f64) -> FixExecutionReport {
+    FixExecutionReport {
+        orderid: orderid.to_string(),
+        execid: format!("EXEC-{:?}-{}", exec_type, leaves_qty),
+        exec_type,
+        ord_status,
+        symbol: "AAPL".to_string(),
+        cum_qty: 100.0 - leaves_qty,
+        leaves_qty,
+        avg_px: 190.0,
+        last_px: 190.0,
+        last_qty,
+    }
+}
+
+#[tokio::test]
+async fn order_goes_out_as_new_order_single_and_reports_come_back_as_fills() {
+    let test_bus = TestBus::new(64);
+    let bridge = FixBridgeActor::new(test_bus.bus()).with_time_in_force(FixTimeInForce::GoodTillCancel).with_account("ACC-1");
+    let mut harness = ActorTestHarness::start(test_bus, Arc::new(bridge)).await;
+
+    let order = order(OrderSide::Sell, OrderType::Limit);
+    harness.send(order.clone()).await;
+    let new_order: FixNewOrderSingle = harness.expect_message(TIMEOUT).await;
+    assert_eq!(new_order.clordid, order.id.to_string());
+    assert_eq!((new_order.side, new_order.ordtype), (FixSide::Sell, FixOrdType::Limit));
+    assert_eq!(new_order.price, Some(190.0));
+    assert_eq!(new_order.qty, 100.0);
+    assert_eq!(new_order.time_in_force, FixTimeInForce::GoodTillCancel);
+    assert_eq!(new_order.account.as_deref(), Some("ACC-1"));
+
+    let orderid = new_order.clordid;
+    // 确认回报不产生成交
+    harness.send(report(&orderid, FixExecType::New, FixOrdStatus::New, 0.0, 100.0)).await;
+    harness.expect_no_message::<FillEvent>(QUIET).await;
+
+    harness.send(report(&orderid, FixExecType::PartialFill, FixOrdStatus::PartiallyFilled, 40.0, 60.0)).await;
+    let partial: FillEvent = harness.expect_message(TIMEOUT).await;
+    assert_eq!(partial.order_id, order.id);
+    assert_eq!(partial.side, OrderSide::Sell);
+    assert_eq!((partial.quantity, partial.leaves_qty, partial.is_final), (40.0, 60.0, false));
+    assert_eq!(partial.venue_fill_id.as_deref(), Some("EXEC-PartialFill-60"));
+
+    harness.send(report(&orderid, FixExecType::Fill, FixOrdStatus::Filled, 60.0, 0.0)).await;
+    let last: FillEvent = harness.expect_message(TIMEOUT).await;
+    assert_eq!((last.quantity, last.leaves_qty, last.is_final), (60.0, 0.0, true));
+
+    // 订单结束后的回报与未知订单的回报都被忽略
+    harness.send(report(&orderid, FixExecType::Fill, FixOrdStatus::Filled, 60.0, 0.0)).await;
+    harness.send(report(&Uuid::new_v4().to_string(), FixExecType::Fill, FixOrdStatus::Filled, 1.0, 0.0)).await;
+    harness.send(report("not-a-uuid", FixExecType::Fill, FixOrdStatus::Filled, 1.0, 0.0)).await;
+    harness.expect_no_message::<FillEvent>(QUIET).await;
+}
+
+#[test]
+fn order_types_map_to_fix_ord_type_and_prices() {
+    let bridge = FixBridgeActor::new(MessageBus::new(1));
+
+    let market = bridge.to_new_order_single(&order(OrderSide::Buy, OrderType::Market));
+    assert_eq!((market.side, market.ordtype, market.price), (FixSide::Buy, FixOrdType::Market, None));
+    assert_eq!(market.time_in_force, FixTimeInForce::Day);
+    assert_eq!(market.account, None);
+
+    let stop = OrderRequest { trigger_price: Some(180.0), ..order(OrderSide::Sell, OrderType::StopMarket) };
+    let stop = bridge.to_new_order_single(&stop);
+    assert_eq!((stop.ordtype, stop.price, stop.stop_px), (FixOrdType::Stop, None, Some(180.0)));
+}
+
+#[test]
+fn enum_values_follow_fix_tags() {
+    assert_eq!(FixSide::Sell.tag_value(), '2');
+    assert_eq!(FixOrdType::StopLimit.tag_value(), '4');
+    assert_eq!(FixExecType::PendingNew.tag_value(), 'A');
+    assert_eq!(FixOrdStatus::from_tag_value('2'), Some(FixOrdStatus::Filled));
+    assert_eq!(FixTimeInForce::from_tag_value('3'), Some(FixTimeInForce::ImmediateOrCancel));
+    assert_eq!(FixSide::from_tag_value('9'), None);
+}
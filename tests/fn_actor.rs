@@ -0,0 +1,93 @@
+// tests/fn_actor.rs
+
+//! # 闭包 Actor 测试
+//!
+//! `FnActor` 按到达顺序把每条消息交给处理函数；`FnActor2` 的两个处理函数运行在各自的任务中，
+//! 一个处理函数等待时另一个照常执行；总线关闭后处理任务退出。
+
+use message_bus::actor::{FnActor, FnActor2};
+use message_bus::bus::MessageBus;
+use message_bus::message::{Bar, Quote};
+use message_bus::testkit::BarBuilder;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Barrier};
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn quote(bid: f64) -> Quote {
+    Quote { symbol: "BTC-USD".to_string(), bid, ask: bid + 1.0, ts_event: 0 }
+}
+
+#[tokio::test]
+async fn handler_receives_messages_in_order_and_exits_when_the_bus_closes() {
+    let bus = MessageBus::new(64);
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let actor = FnActor::new::<Bar>(bus.clone(), move |bar| {
+        let tx = tx.clone();
+        Box::pin(async move {
+            let _ = tx.send(bar.close);
+        })
+    });
+    let mut handles = actor.start("MONITOR").await;
+    assert_eq!(handles.len(), 1);
+    assert_eq!(bus.subscriber_count::<Bar>().await, 1);
+
+    for close in [1.0, 2.0, 3.0] {
+        bus.publish(BarBuilder::new().price(close).build()).await.unwrap();
+    }
+    // 其他类型的消息不会交给处理函数
+    bus.publish(quote(100.0)).await.unwrap();
+    for expected in [1.0, 2.0, 3.0] {
+        assert_eq!(tokio::time::timeout(TIMEOUT, rx.recv()).await.unwrap(), Some(expected));
+    }
+
+    // 丢弃最后一个总线句柄后通道关闭，任务正常结束，处理函数随之释放
+    drop(bus);
+    tokio::time::timeout(TIMEOUT, handles.remove(0)).await.expect("handler task did not exit").unwrap();
+    assert_eq!(tokio::time::timeout(TIMEOUT, rx.recv()).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn fn_actor2_runs_both_handlers_concurrently() {
+    let bus = MessageBus::new(64);
+    // 两个处理函数都要等到对方也进入屏障才能返回；若串行执行则永远等不到
+    let barrier = Arc::new(Barrier::new(2));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let (bar_barrier, bar_tx) = (barrier.clone(), tx.clone());
+    let (quote_barrier, quote_tx) = (barrier, tx);
+    let actor = FnActor2::new::<Bar, Quote>(
+        bus.clone(),
+        move |bar| {
+            let (barrier, tx) = (bar_barrier.clone(), bar_tx.clone());
+            Box::pin(async move {
+                barrier.wait().await;
+                let _ = tx.send(format!("bar {}", bar.close));
+            })
+        },
+        move |quote| {
+            let (barrier, tx) = (quote_barrier.clone(), quote_tx.clone());
+            Box::pin(async move {
+                barrier.wait().await;
+                let _ = tx.send(format!("quote {}", quote.bid));
+            })
+        },
+    );
+    let handles = actor.start("OBSERVER").await;
+    assert_eq!(handles.len(), 2);
+
+    bus.publish(BarBuilder::new().price(7.0).build()).await.unwrap();
+    bus.publish(quote(8.0)).await.unwrap();
+    let mut received = Vec::new();
+    for _ in 0..2 {
+        received.push(tokio::time::timeout(TIMEOUT, rx.recv()).await.expect("handlers blocked each other").unwrap());
+    }
+    received.sort();
+    assert_eq!(received, ["bar 7", "quote 8"]);
+
+    // 总线关闭后两个任务都退出
+    drop(bus);
+    for handle in handles {
+        tokio::time::timeout(TIMEOUT, handle).await.expect("handler task did not exit").unwrap();
+    }
+}
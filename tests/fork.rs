@@ -0,0 +1,73 @@
+// tests/fork.rs
+
+//! # 总线分叉测试
+//!
+//! 在 `fork` 得到的分叉上发布的消息不会到达原总线的订阅者，反之亦然；
+//! 分叉继承消息存储的登记，可以用 `seed_from` 从原总线的最近消息出发，而原总线不受影响。
+
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::{Bar, OrderRequest, OrderSide, OrderType};
+use message_bus::testkit::BarBuilder;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+
+fn order() -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 1.0,
+        trigger_price: None,
+    }
+}
+
+#[tokio::test]
+async fn fork_is_isolated_from_the_original() {
+    let bus = MessageBus::new(16);
+    let mut original_rx = bus.subscribe::<OrderRequest>().await;
+    let fork = bus.fork();
+    let mut fork_rx = fork.subscribe::<OrderRequest>().await;
+
+    let what_if = order();
+    assert_eq!(fork.publish(what_if.clone()).await.unwrap(), 1);
+    assert_eq!(fork_rx.recv_timeout(TIMEOUT).await.unwrap().id, what_if.id);
+    assert!(matches!(original_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    // 原总线上的消息同样不会进入分叉
+    bus.publish(order()).await.unwrap();
+    assert!(matches!(fork_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    assert_eq!(bus.publish_count(), 1);
+    assert_eq!(fork.publish_count(), 1);
+}
+
+#[tokio::test]
+async fn fork_can_be_seeded_from_the_parent_store() {
+    let bus = MessageBus::new(16).with_message_store::<Bar>(10);
+    for ts in 1..=3 {
+        bus.publish(BarBuilder::new().ts_event(ts).build()).await.unwrap();
+    }
+    let mut original_rx = bus.subscribe::<Bar>().await;
+
+    let fork = bus.fork();
+    let mut fork_rx = fork.subscribe::<Bar>().await;
+    fork.seed_from(&bus).await.unwrap();
+    for ts in 1..=3 {
+        assert_eq!(fork_rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, ts);
+    }
+    assert!(matches!(original_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    // 分叉上的新消息只进入分叉自己的存储
+    fork.publish(BarBuilder::new().ts_event(4).build()).await.unwrap();
+    let parent: Vec<u64> =
+        bus.capture_state().await.messages.iter().map(|m| m.envelope.message["ts_event"].as_u64().unwrap()).collect();
+    assert_eq!(parent, [1, 2, 3]);
+    let forked = fork.capture_state().await.messages;
+    assert_eq!(forked.len(), 4);
+    assert!(forked[..3].iter().all(|stored| stored.envelope.is_replay));
+    assert!(!forked[3].envelope.is_replay);
+}
@@ -0,0 +1,254 @@
+// tests/grpc.rs
+
+//! # gRPC 控制接口测试
+//!
+//! 用 tonic 客户端连接 `GrpcControl`，背后是直接运行在同一总线上的模拟执行引擎，
+//! 验证认证、下单校验、撤单、持仓与未结束订单查询、暂停/恢复、成交推送以及随停止信号关闭服务。
+//! 需要启用 `grpc` feature：`cargo test --features grpc`。
+
+#![cfg(feature = "grpc")]
+// `tonic::Status` 由拦截器的签名决定
+#![allow(clippy::result_large_err)]
+
+use message_bus::actor::{spawn_run, Actor, ActorContext, ShutdownToken};
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::grpc::proto::control_client::ControlClient;
+use message_bus::grpc::proto::{
+    CancelOrderRequest, GetOpenOrdersRequest, GetPositionsRequest, OrderType, Side, StrategyControlRequest,
+    StreamFillsRequest, SubmitOrderRequest,
+};
+use message_bus::grpc::GrpcControl;
+use message_bus::message::{OrderCanceled, OrderRequest, PositionUpdate, TradingHalted};
+use message_bus::testkit::TestBus;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Channel;
+use tonic::{Code, Request, Status};
+
+const SYMBOL: &str = "BTC-USD";
+const TOKEN: &str = "secret";
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+type Client = ControlClient<InterceptedService<Channel, fn(Request<()>) -> Result<Request<()>, Status>>>;
+
+fn authorize(mut request: Request<()>) -> Result<Request<()>, Status> {
+    request.metadata_mut().insert("authorization", format!("Bearer {}", TOKEN).parse().unwrap());
+    Ok(request)
+}
+
+fn control(test_bus: &TestBus) -> Arc<GrpcControl> {
+    Arc::new(GrpcControl::new(test_bus.bus(), TOKEN).with_addr(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))))
+}
+
+async fn channel(control: &GrpcControl) -> Channel {
+    let addr = control.local_addr().expect("control API is listening");
+    Channel::from_shared(format!("http://{}", addr)).unwrap().connect().await.unwrap()
+}
+
+async fn client(control: &GrpcControl) -> Client {
+    let channel = channel(control).await;
+    ControlClient::with_interceptor(channel, authorize as fn(Request<()>) -> Result<Request<()>, Status>)
+}
+
+/// 同一总线上启动模拟执行引擎与控制接口。
+async fn start() -> (TestBus, Client) {
+    let test_bus = TestBus::new(256);
+    Arc::new(SimulatedExecutionEngine::new(test_bus.bus())).start("EXECUTION").await;
+    let control = control(&test_bus);
+    control.clone().start("GRPC").await;
+    let client = client(&control).await;
+    (test_bus, client)
+}
+
+fn order(order_type: OrderType, price: f64, quantity: f64) -> SubmitOrderRequest {
+    SubmitOrderRequest {
+        order_id: String::new(),
+        symbol: SYMBOL.to_string(),
+        side: Side::Buy.into(),
+        order_type: order_type.into(),
+        price,
+        quantity,
+        trigger_price: None,
+    }
+}
+
+#[tokio::test]
+async fn rejects_calls_without_a_valid_token() {
+    let test_bus = TestBus::new(64);
+    let control = control(&test_bus);
+    control.clone().start("GRPC").await;
+
+    let mut anonymous = ControlClient::new(channel(&control).await);
+    let status = anonymous.get_positions(GetPositionsRequest { symbol: None }).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    let mut wrong = ControlClient::with_interceptor(channel(&control).await, |mut request: Request<()>| {
+        request.metadata_mut().insert("authorization", "Bearer wrong".parse().unwrap());
+        Ok(request)
+    });
+    let status = wrong.submit_order(order(OrderType::Market, 100.0, 1.0)).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+    assert!(test_bus.published::<OrderRequest>().is_empty());
+}
+
+#[tokio::test]
+async fn submitted_order_is_streamed_back_as_a_fill() {
+    let (test_bus, mut client) = start().await;
+    let mut fills = client.stream_fills(StreamFillsRequest { symbol: None }).await.unwrap().into_inner();
+
+    let order_id = client.submit_order(order(OrderType::Market, 100.0, 2.0)).await.unwrap().into_inner().order_id;
+    let fill = tokio::time::timeout(TIMEOUT, fills.message()).await.unwrap().unwrap().expect("fill");
+    assert_eq!(fill.order_id, order_id);
+    assert_eq!(fill.symbol, SYMBOL);
+    assert_eq!(fill.side, i32::from(Side::Buy));
+    assert_eq!(fill.quantity, 2.0);
+    assert!(fill.is_final);
+
+    let published = test_bus.published::<OrderRequest>();
+    assert_eq!(published.len(), 1);
+    assert_eq!(published[0].id.to_string(), order_id);
+}
+
+#[tokio::test]
+async fn invalid_orders_are_rejected_before_reaching_the_bus() {
+    let (test_bus, mut client) = start().await;
+
+    let status = client.submit_order(order(OrderType::Market, 100.0, 0.0)).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    let status = client.submit_order(order(OrderType::Limit, -1.0, 1.0)).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    let status = client.submit_order(order(OrderType::Unspecified, 100.0, 1.0)).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    // 重复的订单 ID 与执行端一样被拒绝
+    let mut first = order(OrderType::Limit, 90.0, 1.0);
+    first.order_id = client.submit_order(first.clone()).await.unwrap().into_inner().order_id;
+    let status = client.submit_order(first).await.unwrap_err();
+    assert_eq!(status.code(), Code::AlreadyExists);
+    assert_eq!(test_bus.published::<OrderRequest>().len(), 1);
+}
+
+#[tokio::test]
+async fn open_orders_are_queried_and_canceled() {
+    let (test_bus, mut client) = start().await;
+
+    // 限价低于行情，挂单等待
+    let order_id = client.submit_order(order(OrderType::Limit, 90.0, 1.0)).await.unwrap().into_inner().order_id;
+    let mut open = Vec::new();
+    for _ in 0..20 {
+        open = client.get_open_orders(GetOpenOrdersRequest { symbol: None }).await.unwrap().into_inner().orders;
+        if !open.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].order_id, order_id);
+    assert_eq!(open[0].order_type, i32::from(OrderType::Limit));
+    assert_eq!(open[0].leaves_qty, 1.0);
+
+    client
+        .cancel_order(CancelOrderRequest { order_id: order_id.clone(), symbol: SYMBOL.to_string() })
+        .await
+        .unwrap();
+    for _ in 0..100 {
+        if !test_bus.published::<OrderCanceled>().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(test_bus.published::<OrderCanceled>()[0].order_id.to_string(), order_id);
+    let open = client.get_open_orders(GetOpenOrdersRequest { symbol: None }).await.unwrap().into_inner().orders;
+    assert!(open.is_empty());
+
+    let status = client
+        .cancel_order(CancelOrderRequest { order_id: "not-a-uuid".to_string(), symbol: SYMBOL.to_string() })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn pause_halts_trading_until_resumed() {
+    let (test_bus, mut client) = start().await;
+
+    let pause = StrategyControlRequest { symbol: Some(SYMBOL.to_string()), reason: "maintenance".to_string() };
+    client.pause_strategy(pause).await.unwrap();
+    let halts = test_bus.published::<TradingHalted>();
+    assert_eq!(halts.len(), 1);
+    assert_eq!(halts[0].symbol.as_deref(), Some(SYMBOL));
+    assert_eq!(halts[0].reason, "maintenance");
+
+    let mut status = None;
+    for _ in 0..100 {
+        match client.submit_order(order(OrderType::Market, 100.0, 1.0)).await {
+            Err(e) => {
+                status = Some(e);
+                break;
+            },
+            Ok(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+        }
+    }
+    assert_eq!(status.expect("order rejected while paused").code(), Code::FailedPrecondition);
+
+    let submitted = test_bus.published::<OrderRequest>().len();
+    client.resume_strategy(StrategyControlRequest { symbol: Some(SYMBOL.to_string()), reason: String::new() }).await.unwrap();
+    let mut accepted = false;
+    for _ in 0..100 {
+        if client.submit_order(order(OrderType::Market, 100.0, 1.0)).await.is_ok() {
+            accepted = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert!(accepted);
+    assert_eq!(test_bus.published::<OrderRequest>().len(), submitted + 1);
+}
+
+#[tokio::test]
+async fn positions_follow_position_updates() {
+    let (test_bus, mut client) = start().await;
+
+    for (symbol, net_position) in [("ETH-USD", -1.0), (SYMBOL, 2.0), (SYMBOL, 3.0)] {
+        let update = PositionUpdate { symbol: symbol.to_string(), net_position, avg_price: 100.0, ts: 0 };
+        test_bus.bus().publish(update).await.unwrap();
+    }
+    let mut positions = Vec::new();
+    for _ in 0..100 {
+        positions = client.get_positions(GetPositionsRequest { symbol: None }).await.unwrap().into_inner().positions;
+        if positions.len() == 2 && positions[0].net_position == 3.0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    // 按 symbol 排序，每个 symbol 只保留最新的一条
+    assert_eq!(positions.iter().map(|p| p.symbol.as_str()).collect::<Vec<_>>(), [SYMBOL, "ETH-USD"]);
+    assert_eq!(positions[0].net_position, 3.0);
+    assert_eq!(positions[1].net_position, -1.0);
+
+    let filtered = client.get_positions(GetPositionsRequest { symbol: Some("ETH-USD".to_string()) }).await.unwrap();
+    assert_eq!(filtered.into_inner().positions.len(), 1);
+}
+
+#[tokio::test]
+async fn shutdown_ends_streams_and_stops_the_server() {
+    let test_bus = TestBus::new(64);
+    let control = control(&test_bus);
+    let token = ShutdownToken::new();
+    let ctx = ActorContext::new(test_bus.bus(), "GRPC").with_shutdown(token.clone());
+    let handle = spawn_run(control.clone(), ctx, &Handle::current()).await;
+
+    let mut client = client(&control).await;
+    let mut fills = client.stream_fills(StreamFillsRequest { symbol: None }).await.unwrap().into_inner();
+
+    token.shutdown();
+    let end = tokio::time::timeout(TIMEOUT, fills.message()).await.expect("stream ends on shutdown");
+    assert!(end.unwrap().is_none());
+    tokio::time::timeout(TIMEOUT, handle).await.expect("run returns after shutdown").unwrap();
+
+    let addr = control.local_addr().unwrap();
+    assert!(Channel::from_shared(format!("http://{}", addr)).unwrap().connect().await.is_err());
+}
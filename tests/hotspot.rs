@@ -0,0 +1,40 @@
+// tests/hotspot.rs
+
+//! # 通道热点检测测试
+//!
+//! 订阅者不读取时 `Bar` 通道逐渐积压：`channel_fill_ratios` 反映积压比例，
+//! 超过阈值后检测任务发布 `SystemEvent::ChannelHotSpot`，未超过阈值的类型不会报告。
+
+use message_bus::bus::MessageBus;
+use message_bus::message::{Bar, FillEvent, SystemEvent};
+use message_bus::testkit::BarBuilder;
+use std::time::Duration;
+
+#[tokio::test]
+async fn reports_channels_above_the_threshold() {
+    let bus = MessageBus::new(8);
+    // 两个不读取的订阅者，Bar 通道积压 6 / 8
+    let _slow = bus.subscribe::<Bar>().await;
+    let _slower = bus.clone_with_prefix("paper").subscribe::<Bar>().await;
+    let _fills = bus.subscribe::<FillEvent>().await;
+    for ts in 0..6 {
+        bus.publish(BarBuilder::new().ts_event(ts).build()).await.unwrap();
+    }
+
+    let fills = bus.channel_fill_ratios().await;
+    let bar_fill = fills.iter().find(|fill| fill.type_name == std::any::type_name::<Bar>()).unwrap();
+    assert_eq!(bar_fill.fill_pct, 75.0);
+    assert_eq!(bar_fill.receiver_count, 2);
+
+    let mut events = bus.subscribe::<SystemEvent>().await;
+    let detector = bus.enable_hotspot_detection(50.0, Duration::from_millis(10));
+    let event = tokio::time::timeout(Duration::from_secs(1), events.recv()).await.unwrap().unwrap();
+    assert_eq!(
+        event,
+        SystemEvent::ChannelHotSpot { type_name: std::any::type_name::<Bar>(), fill_pct: 75.0, receiver_count: 2 }
+    );
+    // 空的 FillEvent 通道不会被报告，过热的 Bar 在下一次采样时再次报告
+    let again = tokio::time::timeout(Duration::from_secs(1), events.recv()).await.unwrap().unwrap();
+    assert_eq!(again, event);
+    detector.abort();
+}
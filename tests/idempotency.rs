@@ -0,0 +1,180 @@
+// tests/idempotency.rs
+
+//! # 订单幂等性测试
+//!
+//! 验证执行引擎对重复订单 ID 的处理：默认拒绝，启用幂等提交后回复原订单的结果；
+//! 以及总线的 `publish_idempotent` 按类型的去重时长丢弃重复发布。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::clock::VirtualClock;
+use message_bus::execution::{LatencyModel, SimulatedExecutionEngine};
+use message_bus::message::{
+    Bar, FillEvent, OrderRejected, OrderRequest, OrderSide, OrderStatus, OrderStatusChanged, OrderType, RejectReason,
+    DEFAULT_BAR_TIMEFRAME,
+};
+use message_bus::validation::{OrderIdCheck, OrderIdWindow, ValidationConfig};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+const QUIET: Duration = Duration::from_millis(200);
+
+fn order(quantity: f64) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity,
+        trigger_price: None,
+    }
+}
+
+fn stop(handles: Vec<JoinHandle<()>>) {
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn duplicate_before_fill_is_rejected_and_fills_once() {
+    let bus = MessageBus::new(64);
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let mut rejected_rx = bus.subscribe::<OrderRejected>().await;
+    let latency = LatencyModel { ack_latency: Duration::from_millis(100), ..Default::default() };
+    let handles = Arc::new(SimulatedExecutionEngine::new(bus.clone()).with_latency(latency)).start("EXECUTION").await;
+
+    let original = order(1.0);
+    bus.publish(original.clone()).await.unwrap();
+    bus.publish(original.clone()).await.unwrap();
+
+    let rejected = rejected_rx.recv_timeout(QUIET).await.unwrap();
+    assert_eq!(rejected.order_id, original.id);
+    assert_eq!(rejected.reason, RejectReason::DuplicateOrderId);
+
+    let fill = fill_rx.recv_timeout(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(fill.order_id, original.id);
+    assert_eq!(fill.quantity, 1.0);
+    assert!(matches!(fill_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    stop(handles);
+}
+
+#[tokio::test]
+async fn duplicate_after_fill_is_rejected_without_a_second_fill() {
+    let bus = MessageBus::new(64);
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let mut rejected_rx = bus.subscribe::<OrderRejected>().await;
+    let handles = Arc::new(SimulatedExecutionEngine::new(bus.clone())).start("EXECUTION").await;
+
+    let original = order(1.0);
+    bus.publish(original.clone()).await.unwrap();
+    let fill = fill_rx.recv_timeout(Duration::from_secs(1)).await.unwrap();
+    assert!(fill.is_final);
+
+    bus.publish(original.clone()).await.unwrap();
+    let rejected = rejected_rx.recv_timeout(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(rejected.reason, RejectReason::DuplicateOrderId);
+    assert!(matches!(fill_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    stop(handles);
+}
+
+#[tokio::test]
+async fn idempotent_resubmission_returns_the_original_outcome() {
+    let bus = MessageBus::new(64);
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let mut rejected_rx = bus.subscribe::<OrderRejected>().await;
+    let mut status_rx = bus.subscribe::<OrderStatusChanged>().await;
+    let validation = ValidationConfig::default().with_idempotent_resubmission();
+    let handles = Arc::new(SimulatedExecutionEngine::new(bus.clone()).with_validation(validation)).start("EXECUTION").await;
+
+    let original = order(1.0);
+    bus.publish(original.clone()).await.unwrap();
+    fill_rx.recv_timeout(Duration::from_secs(1)).await.unwrap();
+    loop {
+        let changed = status_rx.recv_timeout(Duration::from_secs(1)).await.unwrap();
+        if changed.status == OrderStatus::Filled {
+            break;
+        }
+    }
+
+    // 完全相同的重复提交：回复原订单的结果，不拒绝、不再成交
+    bus.publish(original.clone()).await.unwrap();
+    let replayed = status_rx.recv_timeout(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(replayed.order_id, original.id);
+    assert_eq!(replayed.status, OrderStatus::Filled);
+    assert_eq!(replayed.filled_qty, 1.0);
+    assert!(matches!(rejected_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    assert!(matches!(fill_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    // 同一 ID 但内容不同：仍然拒绝
+    let conflicting = OrderRequest { quantity: 2.0, ..original };
+    bus.publish(conflicting).await.unwrap();
+    let rejected = rejected_rx.recv_timeout(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(rejected.reason, RejectReason::DuplicateOrderId);
+    stop(handles);
+}
+
+#[test]
+fn order_id_window_forgets_ids_only_after_retention() {
+    const SECOND: u64 = 1_000_000_000;
+    let mut window = OrderIdWindow::new(Duration::from_secs(10), 2);
+    let first = order(1.0);
+    let second = order(1.0);
+    let third = order(1.0);
+
+    window.insert(&first, 0);
+    window.insert(&second, SECOND);
+    assert_eq!(window.check(&first, 5 * SECOND), OrderIdCheck::Resubmission);
+    assert_eq!(window.check(&OrderRequest { quantity: 3.0, ..first.clone() }, 5 * SECOND), OrderIdCheck::Conflict);
+    // 容量已满但两个 ID 都在保留时间内：不能淘汰旧 ID，新 ID 无法接受
+    assert_eq!(window.check(&third, 5 * SECOND), OrderIdCheck::WindowFull);
+
+    // 第一个 ID 过期后腾出空间，且可以被重新使用
+    assert_eq!(window.check(&third, 10 * SECOND), OrderIdCheck::New);
+    assert_eq!(window.check(&first, 10 * SECOND), OrderIdCheck::New);
+    assert_eq!(window.check(&second, 10 * SECOND), OrderIdCheck::Resubmission);
+}
+
+#[tokio::test]
+async fn publish_idempotent_uses_per_type_dedup_windows() {
+    const START: u64 = 1_000_000_000;
+    let clock = Arc::new(VirtualClock::new(START));
+    let bus = MessageBus::new(16).with_clock(clock.clone()).with_default_dedup_ttl(Duration::from_secs(1));
+    bus.set_dedup_ttl::<Bar>(Duration::from_millis(100));
+    let mut order_rx = bus.subscribe::<OrderRequest>().await;
+    let mut bar_rx = bus.subscribe::<Bar>().await;
+
+    let order = order(1.0);
+    let bar = Bar {
+        id: Uuid::new_v4(),
+        ts_event: 0,
+        symbol: "BTC-USD".to_string(),
+        open: 100.0,
+        high: 100.0,
+        low: 100.0,
+        close: 100.0,
+        volume: 1.0,
+        timeframe: DEFAULT_BAR_TIMEFRAME,
+    };
+    assert_eq!(bus.publish_idempotent(order.clone()).await.unwrap(), Some(1));
+    assert_eq!(bus.publish_idempotent(bar.clone()).await.unwrap(), Some(1));
+    assert_eq!(bus.publish_idempotent(order.clone()).await.unwrap(), None);
+    assert_eq!(bus.publish_idempotent(bar.clone()).await.unwrap(), None);
+
+    // Bar 的窗口已过，订单仍在默认窗口内
+    clock.advance_to(START + Duration::from_millis(500).as_nanos() as u64);
+    assert_eq!(bus.publish_idempotent(bar.clone()).await.unwrap(), Some(1));
+    assert_eq!(bus.publish_idempotent(order.clone()).await.unwrap(), None);
+
+    clock.advance_to(START + Duration::from_millis(1_001).as_nanos() as u64);
+    assert_eq!(bus.publish_idempotent(order.clone()).await.unwrap(), Some(1));
+
+    assert_eq!(order_rx.recv_timeout(QUIET).await.unwrap().id, order.id);
+    assert_eq!(order_rx.recv_timeout(QUIET).await.unwrap().id, order.id);
+    assert!(matches!(order_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    assert_eq!(bar_rx.recv_timeout(QUIET).await.unwrap().id, bar.id);
+    assert_eq!(bar_rx.recv_timeout(QUIET).await.unwrap().id, bar.id);
+}
@@ -0,0 +1,51 @@
+// tests/indicators.rs
+
+//! # 指标测试
+//!
+//! `BarBuffer` 满了之后淘汰最旧的 `Bar`，`as_slice` 始终按从旧到新的顺序连续借出，
+//! 移动平均只使用最近 `period` 根。
+
+use message_bus::indicators::{sma, BarBuffer};
+use message_bus::testkit::BarBuilder;
+
+fn closes(buffer: &BarBuffer) -> Vec<f64> {
+    buffer.as_slice().iter().map(|bar| bar.close).collect()
+}
+
+#[test]
+fn push_evicts_the_oldest_bar_once_full() {
+    let mut buffer = BarBuffer::new(3);
+    assert!(buffer.is_empty());
+    assert!(buffer.latest().is_none());
+
+    for close in [1.0, 2.0, 3.0] {
+        buffer.push(BarBuilder::new().ts_event(close as u64).price(close).build());
+    }
+    assert!(buffer.is_full());
+    assert_eq!(closes(&buffer), [1.0, 2.0, 3.0]);
+
+    // 环形存储回绕多次后，切片仍然完整且有序
+    for close in (4..=10).map(f64::from) {
+        buffer.push(BarBuilder::new().ts_event(close as u64).price(close).build());
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(closes(&buffer), [close - 2.0, close - 1.0, close]);
+    }
+    assert_eq!(buffer.latest().unwrap().close, 10.0);
+}
+
+#[test]
+fn sma_uses_only_the_most_recent_period() {
+    let mut buffer = BarBuffer::new(4);
+    for close in [10.0, 20.0, 30.0] {
+        buffer.push(BarBuilder::new().ts_event(close as u64).price(close).build());
+    }
+    assert_eq!(buffer.sma(4), None);
+    assert_eq!(buffer.sma(2), Some(25.0));
+    assert_eq!(buffer.sma(3), Some(20.0));
+    assert_eq!(buffer.sma(0), None);
+
+    buffer.push(BarBuilder::new().ts_event(40.0 as u64).price(40.0).build());
+    buffer.push(BarBuilder::new().ts_event(50.0 as u64).price(50.0).build());
+    assert_eq!(buffer.sma(4), Some(35.0));
+    assert_eq!(sma(buffer.as_slice(), 4), buffer.sma(4));
+}
@@ -0,0 +1,89 @@
+// tests/joiner.rs
+
+//! # 订单成交关联测试
+//!
+//! 用 `ActorTestHarness` 启动 `OrderFillJoiner`：两笔部分成交完成一个订单时恰好发布一条 `OrderComplete`，
+//! 平均价按成交数量加权；迟迟不结束的订单超时后带着已有的部分成交发布。
+
+use message_bus::joiner::OrderFillJoiner;
+use message_bus::message::{CompletionStatus, FillEvent, Liquidity, OrderComplete, OrderRequest, OrderSide, OrderType};
+use message_bus::testkit::{ActorTestHarness, TestBus};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+
+fn order(quantity: f64) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Limit,
+        price: 101.0,
+        quantity,
+        trigger_price: None,
+    }
+}
+
+fn fill(order: &OrderRequest, price: f64, quantity: f64, leaves_qty: f64) -> FillEvent {
+    FillEvent {
+        order_id: order.id,
+        symbol: order.symbol.clone(),
+        side: order.side.clone(),
+        price,
+        quantity,
+        leaves_qty,
+        is_final: leaves_qty == 0.0,
+        commission: 0.0,
+        commission_currency: String::new(),
+        liquidity: Liquidity::Maker,
+        ts_event: 0,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+#[tokio::test]
+async fn partial_fills_complete_an_order_once() {
+    let test_bus = TestBus::new(64);
+    let joiner = Arc::new(OrderFillJoiner::new(test_bus.bus()));
+    let mut harness = ActorTestHarness::start(test_bus, joiner.clone()).await;
+
+    let order = order(4.0);
+    harness.send(order.clone()).await;
+    harness.send(fill(&order, 100.0, 1.0, 3.0)).await;
+    harness.send(fill(&order, 102.0, 3.0, 0.0)).await;
+
+    let complete: OrderComplete = harness.expect_message(TIMEOUT).await;
+    assert_eq!(complete.order.id, order.id);
+    assert_eq!(complete.status, CompletionStatus::Filled);
+    assert_eq!(complete.fills.iter().map(|fill| fill.quantity).collect::<Vec<_>>(), [1.0, 3.0]);
+    assert_eq!(complete.filled_qty, 4.0);
+    // (100 × 1 + 102 × 3) / 4
+    assert_eq!(complete.avg_fill_price, 101.5);
+
+    // 结束之后的重复成交不会再次发布
+    harness.send(fill(&order, 102.0, 3.0, 0.0)).await;
+    harness.expect_no_message::<OrderComplete>(QUIET).await;
+    assert_eq!(harness.bus().published::<OrderComplete>().len(), 1);
+    assert_eq!(joiner.open_orders(), 0);
+}
+
+#[tokio::test]
+async fn orders_that_never_complete_time_out() {
+    let test_bus = TestBus::new(64);
+    let joiner = Arc::new(OrderFillJoiner::new(test_bus.bus()).with_order_timeout(Duration::from_millis(100)));
+    let mut harness = ActorTestHarness::start(test_bus, joiner.clone()).await;
+
+    let order = order(2.0);
+    harness.send(order.clone()).await;
+    harness.send(fill(&order, 100.0, 0.5, 1.5)).await;
+    harness.expect_no_message::<OrderComplete>(QUIET).await;
+
+    let complete: OrderComplete = harness.expect_message(TIMEOUT).await;
+    assert_eq!(complete.status, CompletionStatus::TimedOut);
+    assert_eq!((complete.filled_qty, complete.avg_fill_price), (0.5, 100.0));
+    assert_eq!(joiner.open_orders(), 0);
+}
@@ -0,0 +1,131 @@
+// tests/kafka.rs
+
+//! # Kafka 网桥测试
+//!
+//! 在 librdkafka 内置的模拟集群上运行：出站网桥以 symbol 为 key 把 `Bar` 写入主题，
+//! 另一条总线上的入站网桥消费并发布，无法解码的记录被计数跳过；
+//! 入站在发布成功（或跳过畸形记录）后提交 offset。broker 不可达时 `connect` 失败或按配置重试。
+//! 需要启用 `kafka` feature：`cargo test --features kafka --test kafka`。
+
+#![cfg(feature = "kafka")]
+
+use message_bus::actor::{spawn_run, ActorContext, ShutdownToken};
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::config::AppConfig;
+use message_bus::kafka_bridge::{KafkaBridge, KafkaBridgeError, KafkaConfig, UnreachablePolicy};
+use message_bus::message::Bar;
+use message_bus::testkit::BarBuilder;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{BaseConsumer, Consumer};
+use rdkafka::mocking::MockCluster;
+use rdkafka::producer::{BaseProducer, BaseRecord, Producer};
+use rdkafka::{Offset, TopicPartitionList};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const TOPIC: &str = "bars";
+
+fn config(brokers: String) -> KafkaConfig {
+    KafkaConfig { brokers, connect_timeout_ms: 2_000, ..KafkaConfig::default() }
+}
+
+fn outbound(brokers: String) -> KafkaConfig {
+    KafkaConfig { outbound: BTreeMap::from([("Bar".to_string(), TOPIC.to_string())]), ..config(brokers) }
+}
+
+fn inbound(brokers: String, group_id: &str) -> KafkaConfig {
+    KafkaConfig { inbound: vec![TOPIC.to_string()], group_id: group_id.to_string(), ..config(brokers) }
+}
+
+/// 以 `run` 启动网桥，返回用于关闭它的停止信号。
+async fn run(bridge: Arc<KafkaBridge>, name: &str, bus: &MessageBus) -> (ShutdownToken, JoinHandle<()>) {
+    let shutdown = ShutdownToken::new();
+    let ctx = ActorContext::new(bus.clone(), name).with_shutdown(shutdown.clone());
+    (shutdown, spawn_run(bridge, ctx, &Handle::current()).await)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn bars_round_trip_and_offsets_are_committed_after_publish() {
+    let cluster = MockCluster::new(1).unwrap();
+    cluster.create_topic(TOPIC, 1, 1).unwrap();
+    let brokers = cluster.bootstrap_servers();
+
+    let local = MessageBus::new(64);
+    let producer = Arc::new(KafkaBridge::connect(local.clone(), outbound(brokers.clone())).await.unwrap());
+    let (stop_producer, producer_task) = run(producer.clone(), "KAFKA.OUT", &local).await;
+
+    let remote = MessageBus::new(64);
+    let mut bars = remote.subscribe::<Bar>().await;
+    let consumer = Arc::new(KafkaBridge::connect(remote.clone(), inbound(brokers.clone(), "archive")).await.unwrap());
+    let (stop_consumer, consumer_task) = run(consumer.clone(), "KAFKA.IN", &remote).await;
+
+    // 无法解码的记录被跳过，不影响之后的记录
+    let raw: BaseProducer = ClientConfig::new().set("bootstrap.servers", &brokers).create().unwrap();
+    raw.send(BaseRecord::to(TOPIC).key("BTC-USD").payload("not an envelope")).unwrap();
+    raw.flush(TIMEOUT).unwrap();
+
+    local.publish(BarBuilder::new().price(100.0).build()).await.unwrap();
+    let received = bars.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(received.close, 100.0);
+
+    stop_consumer.shutdown();
+    consumer_task.await.unwrap();
+    assert_eq!(consumer.stats().malformed, 1);
+    assert_eq!(consumer.stats().received, 1);
+    drop(consumer);
+
+    // 两条记录（畸形的与发布成功的）都已提交，同组的消费者从 offset 2 继续
+    let group: BaseConsumer =
+        ClientConfig::new().set("bootstrap.servers", &brokers).set("group.id", "archive").create().unwrap();
+    let mut partitions = TopicPartitionList::new();
+    partitions.add_partition(TOPIC, 0);
+    let committed = group.committed_offsets(partitions, TIMEOUT).unwrap();
+    assert_eq!(committed.find_partition(TOPIC, 0).unwrap().offset(), Offset::Offset(2));
+
+    stop_producer.shutdown();
+    producer_task.await.unwrap();
+    let stats = producer.stats();
+    assert_eq!((stats.sent, stats.delivered, stats.dropped), (1, 1, 0));
+}
+
+#[tokio::test]
+async fn unreachable_brokers_fail_or_retry() {
+    let unreachable = KafkaConfig { connect_timeout_ms: 200, ..outbound("127.0.0.1:1".to_string()) };
+    let error = KafkaBridge::connect(MessageBus::new(8), unreachable.clone()).await.err().unwrap();
+    assert!(matches!(error, KafkaBridgeError::Unreachable { .. }), "{}", error);
+    assert!(error.to_string().contains("127.0.0.1:1"), "{}", error);
+
+    let retrying = KafkaConfig { on_unreachable: UnreachablePolicy::Retry, retry_interval_ms: 50, ..unreachable };
+    let connect = KafkaBridge::connect(MessageBus::new(8), retrying);
+    assert!(tokio::time::timeout(Duration::from_secs(1), connect).await.is_err());
+}
+
+#[test]
+fn kafka_section_is_read_from_the_app_config() {
+    let config: AppConfig = toml::from_str(
+        r#"
+        [kafka]
+        brokers = "kafka-1:9092"
+        inbound = ["signals"]
+        sasl_password = "secret"
+
+        [kafka.outbound]
+        FillEvent = "trading.fills"
+        "#,
+    )
+    .unwrap();
+    config.validate().unwrap();
+    let kafka = config.kafka.unwrap();
+    assert_eq!(kafka.outbound["FillEvent"], "trading.fills");
+    assert!(!format!("{:?}", kafka).contains("secret"));
+
+    let unknown = AppConfig {
+        kafka: Some(KafkaConfig { outbound: BTreeMap::from([("Tick".to_string(), "ticks".to_string())]), ..kafka }),
+        ..AppConfig::default()
+    };
+    assert!(unknown.validate().unwrap_err().to_string().contains("kafka.outbound"));
+}
@@ -0,0 +1,140 @@
+// tests/latency.rs
+
+//! # 延迟统计测试
+//!
+//! 向 `LatencyTracker` 记录已知分布，验证报告的分位数落在预期的桶内；
+//! 再通过 `ActorTestHarness` 驱动 `LatencyProbe`，验证每一跳的延迟与超时订单的处理。
+
+use message_bus::bus::MessageBus;
+use message_bus::latency::{LatencyProbe, LatencyReport, LatencyTracker};
+use message_bus::message::{FillEvent, Liquidity, OrderRequest, OrderSide, OrderType};
+use message_bus::testkit::{ActorTestHarness, BarBuilder, TestBus};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// 3 位有效数字的直方图，相对误差不超过 0.1%。
+fn assert_close(actual: Duration, expected: Duration) {
+    let tolerance = expected.as_nanos() / 1000 + 1;
+    let diff = actual.as_nanos().abs_diff(expected.as_nanos());
+    assert!(diff <= tolerance, "expected ~{:?}, got {:?}", expected, actual);
+}
+
+#[test]
+fn uniform_distribution_percentiles() {
+    let tracker = LatencyTracker::new(MessageBus::new(1));
+    for micros in 1..=1000 {
+        tracker.record(Duration::from_micros(micros));
+    }
+
+    assert_eq!(tracker.count(), 1000);
+    assert_close(tracker.percentile(50.0), Duration::from_micros(500));
+    assert_close(tracker.percentile(99.0), Duration::from_micros(990));
+
+    let summary = tracker.summary();
+    assert_close(summary.p90, Duration::from_micros(900));
+    assert_close(summary.max, Duration::from_micros(1000));
+}
+
+#[test]
+fn skewed_distribution_keeps_the_tail() {
+    let tracker = LatencyTracker::new(MessageBus::new(1));
+    // 99.5% 的样本为 1ms，0.5% 的样本为 250ms
+    for _ in 0..995 {
+        tracker.record(Duration::from_millis(1));
+    }
+    for _ in 0..5 {
+        tracker.record(Duration::from_millis(250));
+    }
+
+    let summary = tracker.summary();
+    assert_close(summary.p50, Duration::from_millis(1));
+    assert_close(summary.p99, Duration::from_millis(1));
+    assert_close(summary.p999, Duration::from_millis(250));
+}
+
+#[test]
+fn empty_tracker_reports_zero() {
+    let tracker = LatencyTracker::new(MessageBus::new(1));
+    assert_eq!(tracker.percentile(99.0), Duration::ZERO);
+    assert_eq!(tracker.summary().count, 0);
+}
+
+fn order() -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 1.0,
+        trigger_price: None,
+    }
+}
+
+fn fill(order: &OrderRequest) -> FillEvent {
+    FillEvent {
+        order_id: order.id,
+        symbol: order.symbol.clone(),
+        side: order.side.clone(),
+        price: order.price,
+        quantity: order.quantity,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.0,
+        commission_currency: String::new(),
+        liquidity: Liquidity::Taker,
+        ts_event: 0,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+#[tokio::test]
+async fn probe_records_each_hop_and_the_end_to_end_latency() {
+    let test_bus = TestBus::new(64);
+    let probe = Arc::new(LatencyProbe::new(test_bus.bus()).with_report_interval(Duration::from_millis(20)));
+    let mut harness = ActorTestHarness::start(test_bus, probe.clone()).await;
+
+    let order = order();
+    harness.send(BarBuilder::new().build()).await;
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    harness.send(order.clone()).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    harness.send(fill(&order)).await;
+    // 同一订单的后续成交不再计入
+    harness.send(fill(&order)).await;
+    harness.wait_until(Duration::from_secs(1), || probe.report().bar_to_fill.count == 1).await;
+
+    let report = probe.report();
+    assert_eq!(report.bar_to_order.count, 1);
+    assert_eq!(report.order_to_fill.count, 1);
+    assert!(report.bar_to_order.p50 >= Duration::from_millis(10));
+    assert!(report.order_to_fill.p50 >= Duration::from_millis(20));
+    assert!(report.bar_to_fill.p50 >= Duration::from_millis(30));
+    assert_eq!(report.pending_orders, 0);
+
+    // 定期发布报告
+    harness.expect_message::<LatencyReport>(Duration::from_secs(1)).await;
+}
+
+#[tokio::test]
+async fn unfilled_orders_expire_without_skewing_the_stats() {
+    let test_bus = TestBus::new(64);
+    let probe = Arc::new(LatencyProbe::new(test_bus.bus()).with_order_timeout(Duration::from_millis(20)));
+    let harness = ActorTestHarness::start(test_bus, probe.clone()).await;
+
+    let order = order();
+    harness.send(BarBuilder::new().build()).await;
+    harness.send(order.clone()).await;
+    harness.wait_until(Duration::from_secs(1), || probe.report().pending_orders == 1).await;
+    harness.wait_until(Duration::from_secs(1), || probe.report().expired_orders == 1).await;
+
+    // 超时之后才到的成交不计入
+    harness.send(fill(&order)).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let report = probe.report();
+    assert_eq!(report.pending_orders, 0);
+    assert_eq!(report.order_to_fill.count, 0);
+    assert_eq!(report.bar_to_fill.count, 0);
+}
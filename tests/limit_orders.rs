@@ -0,0 +1,123 @@
+// tests/limit_orders.rs
+
+//! # 限价挂单测试
+//!
+//! 限价单在价格未触及前跨越多根 `Bar` 挂单等待，触及后按限价以 `Maker` 成交；
+//! 同一价位的两张挂单按确认顺序（时间优先）成交；挂单被撤销后不再成交。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::message::{
+    Bar, CancelOrderRequest, FillEvent, Liquidity, OrderAccepted, OrderCanceled, OrderRequest, OrderSide, OrderType,
+};
+use message_bus::testkit::BarBuilder;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+
+fn limit(side: OrderSide, price: f64, quantity: f64) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side,
+        order_type: OrderType::Limit,
+        price,
+        quantity,
+        trigger_price: None,
+    }
+}
+
+fn bar(ts_event: u64, low: f64, high: f64) -> Bar {
+    let mid = (low + high) / 2.0;
+    BarBuilder::new().ts_event(ts_event).ohlc(mid, high, low, mid).volume(10.0).build()
+}
+
+#[tokio::test]
+async fn limit_order_rests_across_bars_until_price_reaches_it() {
+    let bus = MessageBus::new(64);
+    let mut accepted_rx = bus.subscribe::<OrderAccepted>().await;
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let handles = Arc::new(SimulatedExecutionEngine::new(bus.clone())).start("EXECUTION").await;
+
+    let buy = limit(OrderSide::Buy, 95.0, 2.0);
+    bus.publish(buy.clone()).await.unwrap();
+    accepted_rx.recv_timeout(TIMEOUT).await.unwrap();
+
+    // 最低价没有低于限价的 Bar 不成交，订单继续挂着
+    for (ts, low) in [(1, 99.0), (2, 96.0), (3, 95.0)] {
+        bus.publish(bar(ts, low, 101.0)).await.unwrap();
+        assert!(matches!(fill_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)), "filled at bar {}", ts);
+    }
+
+    bus.publish(bar(4, 94.0, 97.0)).await.unwrap();
+    let fill = fill_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(fill.order_id, buy.id);
+    assert_eq!(fill.price, 95.0);
+    assert_eq!(fill.quantity, 2.0);
+    assert_eq!(fill.liquidity, Liquidity::Maker);
+    assert_eq!(fill.ts_event, 4);
+    assert!(fill.is_final);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn resting_orders_at_the_same_price_fill_in_time_priority() {
+    let bus = MessageBus::new(64);
+    let mut accepted_rx = bus.subscribe::<OrderAccepted>().await;
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let handles = Arc::new(SimulatedExecutionEngine::new(bus.clone())).start("EXECUTION").await;
+
+    let first = limit(OrderSide::Sell, 105.0, 1.0);
+    let second = limit(OrderSide::Sell, 105.0, 3.0);
+    for order in [&first, &second] {
+        bus.publish(order.clone()).await.unwrap();
+        assert_eq!(accepted_rx.recv_timeout(TIMEOUT).await.unwrap().order_id, order.id);
+    }
+
+    bus.publish(bar(1, 100.0, 106.0)).await.unwrap();
+    let fills = [fill_rx.recv_timeout(TIMEOUT).await.unwrap(), fill_rx.recv_timeout(TIMEOUT).await.unwrap()];
+    assert_eq!(fills.iter().map(|fill| fill.order_id).collect::<Vec<_>>(), vec![first.id, second.id]);
+    assert!(fills.iter().all(|fill| fill.price == 105.0 && fill.is_final));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn canceled_resting_order_never_fills() {
+    let bus = MessageBus::new(64);
+    let mut accepted_rx = bus.subscribe::<OrderAccepted>().await;
+    let mut canceled_rx = bus.subscribe::<OrderCanceled>().await;
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let handles = Arc::new(SimulatedExecutionEngine::new(bus.clone())).start("EXECUTION").await;
+
+    let canceled = limit(OrderSide::Buy, 95.0, 1.0);
+    let kept = limit(OrderSide::Buy, 95.0, 1.0);
+    for order in [&canceled, &kept] {
+        bus.publish(order.clone()).await.unwrap();
+        accepted_rx.recv_timeout(TIMEOUT).await.unwrap();
+    }
+    bus.publish(bar(1, 98.0, 101.0)).await.unwrap();
+
+    bus.publish(CancelOrderRequest { order_id: canceled.id, symbol: "BTC-USD".to_string() }).await.unwrap();
+    let ack = canceled_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(ack.order_id, canceled.id);
+    assert_eq!(ack.remaining_qty, 1.0);
+
+    // 价格触及后只有未撤销的挂单成交
+    bus.publish(bar(2, 90.0, 96.0)).await.unwrap();
+    assert_eq!(fill_rx.recv_timeout(TIMEOUT).await.unwrap().order_id, kept.id);
+    assert!(matches!(fill_rx.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
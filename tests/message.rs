@@ -0,0 +1,116 @@
+// tests/message.rs
+
+//! # 消息索引测试
+//!
+//! 打乱的 `Vec<Bar>` 排序后按事件时间升序，`Bar` 可以放入 `HashSet`/`BTreeSet`；
+//! `FillEvent` 以 `Keyed::key`（所属订单）为键放入 `HashMap`。
+
+use message_bus::message::{Bar, FillEvent, Keyed, Liquidity, OrderRequest, OrderSide, OrderType, DEFAULT_BAR_TIMEFRAME};
+use message_bus::testkit::BarBuilder;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::time::Duration;
+use uuid::Uuid;
+
+fn fill(order_id: Uuid, quantity: f64, leaves_qty: f64) -> FillEvent {
+    FillEvent {
+        order_id,
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        price: 100.0,
+        quantity,
+        leaves_qty,
+        is_final: leaves_qty == 0.0,
+        commission: 0.0,
+        commission_currency: String::new(),
+        liquidity: Liquidity::Taker,
+        ts_event: 0,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+#[test]
+fn shuffled_bars_sort_by_timestamp() {
+    let bars: Vec<Bar> = (1..=50).map(|ts| BarBuilder::new().ts_event(ts * 1_000).price(ts as f64).build()).collect();
+    let mut shuffled = bars.clone();
+    shuffled.shuffle(&mut StdRng::seed_from_u64(7));
+    assert_ne!(shuffled, bars);
+
+    shuffled.sort();
+    assert_eq!(shuffled, bars);
+    assert!(shuffled.windows(2).all(|pair| pair[0].ts_event < pair[1].ts_event));
+}
+
+#[test]
+fn bars_with_equal_timestamps_sort_deterministically() {
+    let mut bars = vec![
+        BarBuilder::new().symbol("ETH-USD").ts_event(5).price(1.0).build(),
+        BarBuilder::new().ts_event(5).price(2.0).build(),
+        BarBuilder::new().ts_event(1).price(3.0).build(),
+    ];
+    bars.push(BarBuilder::new().ts_event(5).price(4.0).timeframe(Duration::from_secs(300)).build());
+    let mut reversed = bars.clone();
+    reversed.reverse();
+    bars.sort();
+    reversed.sort();
+    assert_eq!(bars, reversed);
+    let keys: Vec<(u64, &str, Duration)> =
+        bars.iter().map(|bar| (bar.ts_event, bar.symbol.as_str(), bar.timeframe)).collect();
+    assert_eq!(
+        keys,
+        [
+            (1, "BTC-USD", DEFAULT_BAR_TIMEFRAME),
+            (5, "BTC-USD", DEFAULT_BAR_TIMEFRAME),
+            (5, "BTC-USD", Duration::from_secs(300)),
+            (5, "ETH-USD", DEFAULT_BAR_TIMEFRAME),
+        ]
+    );
+}
+
+#[test]
+fn bars_are_set_members_by_identity() {
+    let first = BarBuilder::new().ts_event(1).price(100.0).build();
+    // 同一窗口的修订：键相同，但 id 不同，是另一条消息
+    let revised = Bar { id: Uuid::new_v4(), close: 101.0, ..first.clone() };
+    let set: HashSet<Bar> = [first.clone(), first.clone(), revised.clone()].into_iter().collect();
+    assert_eq!(set.len(), 2);
+    assert_eq!(first.key(), revised.key());
+
+    let later = BarBuilder::new().ts_event(3).price(1.0).build();
+    let ordered: BTreeSet<Bar> = [later, BarBuilder::new().ts_event(2).price(1.0).build()].into_iter().collect();
+    assert_eq!(ordered.iter().map(|bar| bar.ts_event).collect::<Vec<_>>(), [2, 3]);
+}
+
+#[test]
+fn fills_are_indexed_by_order_id() {
+    let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+    let fills = [fill(a, 1.0, 2.0), fill(b, 5.0, 0.0), fill(a, 2.0, 0.0)];
+
+    let mut by_order: HashMap<Uuid, Vec<FillEvent>> = HashMap::new();
+    for fill in &fills {
+        by_order.entry(fill.key()).or_default().push(fill.clone());
+    }
+    assert_eq!(by_order.len(), 2);
+    assert_eq!(by_order[&a], [fills[0].clone(), fills[2].clone()]);
+    assert_eq!(by_order[&b].iter().map(|fill| fill.quantity).sum::<f64>(), 5.0);
+}
+
+#[test]
+fn orders_are_indexed_by_id() {
+    let order = OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Sell,
+        order_type: OrderType::Limit,
+        price: 100.0,
+        quantity: 1.0,
+        trigger_price: None,
+    };
+    let orders: HashMap<Uuid, OrderRequest> = [(order.key(), order.clone())].into_iter().collect();
+    assert_eq!(orders[&order.id], order);
+    let kinds: HashSet<(OrderSide, OrderType)> = [(order.side.clone(), order.order_type)].into_iter().collect();
+    assert!(kinds.contains(&(OrderSide::Sell, OrderType::Limit)));
+}
@@ -0,0 +1,145 @@
+// tests/metrics.rs
+
+//! # 指标端点测试
+//!
+//! `MetricsExporter` 绑定系统分配的端口（`with_port(0)`），用 reqwest 抓取 `/metrics`：
+//! 响应为 Prometheus 文本格式，包含总线计数、未结束订单数与成交计数；其他路径与非 GET 请求返回 404。
+//! 需要启用 `metrics` feature：`cargo test --features metrics --test metrics`。
+
+#![cfg(feature = "metrics")]
+
+use message_bus::actor::Actor;
+use message_bus::bus::MessageBus;
+use message_bus::message::{FillEvent, Liquidity, OrderRejected, OrderRequest, OrderSide, OrderType, RejectReason};
+use message_bus::metrics::MetricsExporter;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn order(symbol: &str) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 2.0,
+        trigger_price: None,
+    }
+}
+
+fn fill(order: &OrderRequest, quantity: f64, is_final: bool) -> FillEvent {
+    FillEvent {
+        order_id: order.id,
+        symbol: order.symbol.clone(),
+        side: order.side.clone(),
+        price: order.price,
+        quantity,
+        leaves_qty: if is_final { 0.0 } else { order.quantity - quantity },
+        is_final,
+        commission: 0.0,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Taker,
+        ts_event: 1,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+/// 请求 `path`，返回状态码与响应体。
+async fn get(base: &str, path: &str) -> (u16, String) {
+    let response = reqwest::get(format!("{}{}", base, path)).await.unwrap();
+    (response.status().as_u16(), response.text().await.unwrap())
+}
+
+/// 反复抓取 `/metrics`，直到响应体包含所有 `lines`，超时则失败。
+async fn scrape_until(base: &str, lines: &[&str]) -> String {
+    let deadline = tokio::time::Instant::now() + TIMEOUT;
+    loop {
+        let (status, body) = get(base, "/metrics").await;
+        assert_eq!(status, 200);
+        if lines.iter().all(|line| body.lines().any(|l| l == *line)) {
+            return body;
+        }
+        assert!(tokio::time::Instant::now() < deadline, "metrics never contained {:?}:\n{}", lines, body);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn scrape_reports_open_orders_and_fills() {
+    let bus = MessageBus::new(64);
+    let exporter = Arc::new(MetricsExporter::new(bus.clone()).with_port(0));
+    let handles = exporter.clone().start("METRICS").await;
+    let port = exporter.local_addr().expect("metrics exporter is listening").port();
+    let base = format!("http://{}", SocketAddr::from((Ipv4Addr::LOCALHOST, port)));
+
+    let (filled, resting, rejected) = (order("BTC-USD"), order("BTC-USD"), order("ETH-USD"));
+    for order in [&filled, &resting, &rejected] {
+        bus.publish(order.clone()).await.unwrap();
+    }
+    scrape_until(
+        &base,
+        &["execution_open_orders{symbol=\"BTC-USD\"} 2", "execution_open_orders{symbol=\"ETH-USD\"} 1"],
+    )
+    .await;
+
+    // 部分成交不结束订单，完全成交与被拒绝结束订单；结束后 symbol 仍以 0 导出
+    bus.publish(fill(&filled, 0.5, false)).await.unwrap();
+    bus.publish(fill(&filled, 1.5, true)).await.unwrap();
+    bus.publish(OrderRejected {
+        order_id: rejected.id,
+        symbol: rejected.symbol.clone(),
+        reason: RejectReason::InsufficientFunds,
+        detail: "test".to_string(),
+    })
+    .await
+    .unwrap();
+    let body = scrape_until(
+        &base,
+        &[
+            "execution_open_orders{symbol=\"BTC-USD\"} 1",
+            "execution_open_orders{symbol=\"ETH-USD\"} 0",
+            "execution_fills_total{symbol=\"BTC-USD\"} 2",
+            "execution_filled_quantity_total{symbol=\"BTC-USD\"} 2",
+        ],
+    )
+    .await;
+    assert!(body.contains("# TYPE execution_open_orders gauge"));
+    assert!(body.contains("# TYPE execution_fills_total counter"));
+    assert!(body.lines().any(|line| line == "message_bus_published_total{type=\"OrderRequest\"} 3"));
+    assert!(body.lines().any(|line| line == "message_bus_published_total{type=\"FillEvent\"} 2"));
+    assert_eq!(body, exporter.render());
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn other_paths_and_methods_are_not_found() {
+    let bus = MessageBus::new(64);
+    let exporter = Arc::new(MetricsExporter::new(bus.clone()).with_port(0));
+    let handles = exporter.clone().start("METRICS").await;
+    let port = exporter.local_addr().expect("metrics exporter is listening").port();
+    let base = format!("http://{}", SocketAddr::from((Ipv4Addr::LOCALHOST, port)));
+
+    assert_eq!(get(&base, "/").await, (404, "not found\n".to_string()));
+    assert_eq!(get(&base, "/metrics/extra").await, (404, "not found\n".to_string()));
+    // 查询参数不影响路径匹配
+    assert_eq!(get(&base, "/metrics?name=x").await.0, 200);
+    let response = reqwest::Client::new().post(format!("{}/metrics", base)).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+
+    // 一次失败的请求不影响之后的抓取
+    let (status, body) = get(&base, "/metrics").await;
+    assert_eq!(status, 200);
+    assert!(body.contains("# TYPE actor_handler_duration_seconds histogram"));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
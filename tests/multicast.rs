@@ -0,0 +1,46 @@
+// tests/multicast.rs
+
+//! # 多播测试
+//!
+//! `MulticastGroup` 把每条消息分别发布到每条目标总线，按添加顺序返回各目标的送达数；
+//! 各总线相互独立，作为 `PublishTarget` 时订阅者数量为各目标之和。
+
+use message_bus::bus::{MessageBus, PublishTarget, ReceiverExt};
+use message_bus::message::Bar;
+use message_bus::multicast::MulticastGroup;
+use message_bus::testkit::BarBuilder;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[tokio::test]
+async fn every_target_bus_receives_each_message() {
+    let (paper, live) = (MessageBus::new(16), MessageBus::new(16));
+    let mut paper_rx = paper.subscribe::<Bar>().await;
+    let mut live_rxs = [live.subscribe::<Bar>().await, live.subscribe::<Bar>().await];
+    let mut group = MulticastGroup::<Bar>::new(vec![paper.clone()]);
+    group.add_target(live.clone());
+
+    let results = group.publish(BarBuilder::new().ts_event(1).build()).await;
+    assert_eq!(results.into_iter().map(Result::unwrap).collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(paper_rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    for rx in &mut live_rxs {
+        assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    }
+    assert_eq!(PublishTarget::<Bar>::subscriber_count(&group).await, 3);
+}
+
+#[tokio::test]
+async fn targets_stay_independent() {
+    let (paper, live) = (MessageBus::new(16), MessageBus::new(16));
+    let mut paper_rx = paper.subscribe::<Bar>().await;
+    let group = MulticastGroup::<Bar>::new(vec![paper.clone(), live.clone()]);
+
+    // 没有订阅者的目标送达 0，不影响其他目标
+    let results = group.publish(BarBuilder::new().ts_event(1).build()).await;
+    assert_eq!(results.into_iter().map(Result::unwrap).collect::<Vec<_>>(), [1, 0]);
+    // 直接发布到一条目标总线不会经过另一条
+    live.publish(BarBuilder::new().ts_event(2).build()).await.unwrap();
+    assert_eq!(paper_rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    assert_eq!(paper_rx.drain_backlog(), 0);
+}
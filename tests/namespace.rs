@@ -0,0 +1,62 @@
+// tests/namespace.rs
+
+//! # 命名空间测试
+//!
+//! `clone_with_prefix` 创建的两个同级视图互不可见：各自只收到自己命名空间内发布的消息，
+//! 原始总线（根命名空间）的订阅者收到两个视图的全部消息；嵌套视图的消息同样逐级上送。
+
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::Bar;
+use message_bus::testkit::BarBuilder;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const SHORT: Duration = Duration::from_millis(50);
+
+#[tokio::test]
+async fn sibling_views_are_isolated_and_the_root_sees_both() {
+    let bus = MessageBus::new(16);
+    let paper = bus.clone_with_prefix("paper");
+    let live = bus.clone_with_prefix("live");
+    let mut root_rx = bus.subscribe::<Bar>().await;
+    let mut paper_rx = paper.subscribe::<Bar>().await;
+    let mut live_rx = live.subscribe::<Bar>().await;
+
+    // 本命名空间与根命名空间各一个订阅者
+    assert_eq!(paper.publish(BarBuilder::new().ts_event(1).build()).await.unwrap(), 2);
+    assert_eq!(live.publish(BarBuilder::new().symbol("ETH-USD").ts_event(2).build()).await.unwrap(), 2);
+
+    assert_eq!(paper_rx.recv_timeout(TIMEOUT).await.unwrap().symbol, "BTC-USD");
+    assert_eq!(paper_rx.recv_timeout(SHORT).await, Err(RecvTimeout::Timeout));
+    assert_eq!(live_rx.recv_timeout(TIMEOUT).await.unwrap().symbol, "ETH-USD");
+    assert_eq!(live_rx.recv_timeout(SHORT).await, Err(RecvTimeout::Timeout));
+
+    assert_eq!(root_rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    assert_eq!(root_rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 2);
+}
+
+#[tokio::test]
+async fn root_publishes_do_not_reach_prefixed_views() {
+    let bus = MessageBus::new(16);
+    let paper = bus.clone_with_prefix("paper");
+    let mut paper_rx = paper.subscribe::<Bar>().await;
+
+    assert_eq!(bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap(), 0);
+    assert_eq!(paper_rx.recv_timeout(SHORT).await, Err(RecvTimeout::Timeout));
+}
+
+#[tokio::test]
+async fn nested_views_deliver_to_every_ancestor() {
+    let bus = MessageBus::new(16);
+    let paper = bus.clone_with_prefix("paper");
+    let btc = paper.clone_with_prefix("btc");
+    assert_eq!(btc.namespace(), "paper/btc");
+    let mut root_rx = bus.subscribe::<Bar>().await;
+    let mut paper_rx = paper.subscribe::<Bar>().await;
+    let mut btc_rx = btc.subscribe::<Bar>().await;
+
+    assert_eq!(btc.publish(BarBuilder::new().ts_event(7).build()).await.unwrap(), 3);
+    for rx in [&mut root_rx, &mut paper_rx, &mut btc_rx] {
+        assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 7);
+    }
+}
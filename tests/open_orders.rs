@@ -0,0 +1,108 @@
+// tests/open_orders.rs
+
+//! # 未结束订单查询测试
+//!
+//! 执行引擎收到 `OpenOrdersQuery` 后回复 `OpenOrdersReport`：只包含未结束的订单，按提交时间排列，
+//! 可按 symbol 过滤，并带有成交数量、剩余数量与每次状态变化的时间；每次状态变化都发布 `OrderStatusChanged`。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::clock::VirtualClock;
+use message_bus::execution::{FillModel, LatencyModel, SimulatedExecutionEngine};
+use message_bus::message::{
+    CancelOrderRequest, FillEvent, OpenOrdersQuery, OpenOrdersReport, OrderAccepted, OrderCanceled, OrderRequest,
+    OrderSide, OrderStatus, OrderStatusChanged, OrderType,
+};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const SECOND: u64 = 1_000_000_000;
+
+fn order(symbol: &str, order_type: OrderType, price: f64) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        side: OrderSide::Buy,
+        order_type,
+        price,
+        quantity: 3.0,
+        trigger_price: None,
+    }
+}
+
+#[tokio::test]
+async fn query_reports_only_live_orders_in_submission_order() {
+    let bus = MessageBus::new(64);
+    let clock = Arc::new(VirtualClock::new(0));
+    let mut accepted_rx = bus.subscribe::<OrderAccepted>().await;
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let mut canceled_rx = bus.subscribe::<OrderCanceled>().await;
+    let mut report_rx = bus.subscribe::<OpenOrdersReport>().await;
+    let mut status_rx = bus.subscribe::<OrderStatusChanged>().await;
+    let engine = SimulatedExecutionEngine::new(bus.clone())
+        .with_clock(clock.clone())
+        .with_latency(LatencyModel { fill_latency: Duration::from_secs(10), ..Default::default() })
+        .with_fill_model(FillModel::Partial { max_child_qty: 1.0, delay_between: Duration::from_secs(10) });
+    let handles = Arc::new(engine).start("EXECUTION").await;
+
+    // 在不同时间提交：BTC 限价单、ETH 限价单、BTC 市价单（分 3 笔成交）、再撤销一张 ETH 限价单
+    let btc_limit = order("BTC-USD", OrderType::Limit, 90.0);
+    let eth_limit = order("ETH-USD", OrderType::Limit, 9.0);
+    let btc_market = order("BTC-USD", OrderType::Market, 100.0);
+    let eth_canceled = order("ETH-USD", OrderType::Limit, 8.0);
+    for (ts, order) in [(1, &btc_limit), (2, &eth_limit), (3, &btc_market), (4, &eth_canceled)] {
+        clock.advance_to(ts * SECOND);
+        bus.publish(order.clone()).await.unwrap();
+        assert_eq!(accepted_rx.recv_timeout(TIMEOUT).await.unwrap().order_id, order.id);
+    }
+    bus.publish(CancelOrderRequest { order_id: eth_canceled.id, symbol: "ETH-USD".to_string() }).await.unwrap();
+    canceled_rx.recv_timeout(TIMEOUT).await.unwrap();
+
+    // 市价单在 13 秒成交第一笔
+    clock.advance_to(13 * SECOND);
+    assert_eq!(fill_rx.recv_timeout(TIMEOUT).await.unwrap().order_id, btc_market.id);
+
+    bus.publish(OpenOrdersQuery { symbol: None }).await.unwrap();
+    let report = report_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(report.symbol, None);
+    assert_eq!(report.ts_event, 13 * SECOND);
+    let ids: Vec<Uuid> = report.orders.iter().map(|tracked| tracked.order.id).collect();
+    assert_eq!(ids, vec![btc_limit.id, eth_limit.id, btc_market.id]);
+
+    let market = &report.orders[2];
+    assert_eq!(market.status, OrderStatus::PartiallyFilled);
+    assert_eq!((market.filled_qty, market.leaves_qty), (1.0, 2.0));
+    assert_eq!(
+        market.transitions,
+        vec![(OrderStatus::Submitted, 3 * SECOND), (OrderStatus::Accepted, 3 * SECOND), (OrderStatus::PartiallyFilled, 13 * SECOND)]
+    );
+    assert_eq!(report.orders[0].status, OrderStatus::Accepted);
+
+    // 按 symbol 过滤
+    bus.publish(OpenOrdersQuery { symbol: Some("ETH-USD".to_string()) }).await.unwrap();
+    let report = report_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(report.symbol.as_deref(), Some("ETH-USD"));
+    assert_eq!(report.orders.iter().map(|tracked| tracked.order.id).collect::<Vec<_>>(), vec![eth_limit.id]);
+
+    // 每次状态变化都有对应的 OrderStatusChanged
+    let mut canceled_changes = Vec::new();
+    while let Ok(changed) = status_rx.try_recv() {
+        if changed.order_id == eth_canceled.id {
+            canceled_changes.push((changed.previous, changed.status));
+        }
+    }
+    assert_eq!(
+        canceled_changes,
+        vec![
+            (None, OrderStatus::Submitted),
+            (Some(OrderStatus::Submitted), OrderStatus::Accepted),
+            (Some(OrderStatus::Accepted), OrderStatus::Canceled),
+        ]
+    );
+
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,127 @@
+// tests/orderbook.rs
+
+//! # 本地订单簿测试
+//!
+//! 快照整体替换买卖盘并给出最优价、中间价、价差与深度；增量的新增、修改、删除改变对应价位，
+//! 与当前状态不符的动作、非法数量与会导致交叉盘的增量被拒绝，且订单簿保持原状。
+
+use message_bus::message::{BookAction, BookSide, OrderBookDelta, OrderBookSnapshot, PriceLevel};
+use message_bus::orderbook::{BookError, LocalOrderBook};
+
+fn level(price: f64, size: f64) -> PriceLevel {
+    PriceLevel { price, size }
+}
+
+fn snapshot() -> OrderBookSnapshot {
+    OrderBookSnapshot {
+        symbol: "BTC-USD".to_string(),
+        bids: vec![level(99.0, 2.0), level(100.0, 1.0), level(98.0, 3.0), level(97.0, 0.0)],
+        asks: vec![level(102.0, 2.0), level(101.0, 1.5), level(103.0, 4.0)],
+        ts_event: 10,
+    }
+}
+
+fn delta(side: BookSide, action: BookAction, price: f64, size: f64, ts_event: u64) -> OrderBookDelta {
+    OrderBookDelta { symbol: "BTC-USD".to_string(), side, action, price, size, ts_event }
+}
+
+fn book() -> LocalOrderBook {
+    let mut book = LocalOrderBook::new("BTC-USD");
+    book.apply_snapshot(&snapshot());
+    book
+}
+
+#[test]
+fn snapshot_replaces_levels_and_answers_queries() {
+    let mut book = LocalOrderBook::new("BTC-USD");
+    assert_eq!(book.best_bid(), None);
+    assert_eq!(book.mid_price(), None);
+
+    book.apply_snapshot(&snapshot());
+    assert_eq!(book.best_bid(), Some(level(100.0, 1.0)));
+    assert_eq!(book.best_ask(), Some(level(101.0, 1.5)));
+    assert_eq!(book.mid_price(), Some(100.5));
+    assert_eq!(book.spread(), Some(1.0));
+    assert_eq!(book.last_update_ts, 10);
+
+    // 数量为 0 的价位被忽略，买盘从高到低、卖盘从低到高
+    let (bids, asks) = book.depth(2);
+    assert_eq!(bids, vec![level(100.0, 1.0), level(99.0, 2.0)]);
+    assert_eq!(asks, vec![level(101.0, 1.5), level(102.0, 2.0)]);
+    assert_eq!(book.depth(10).0.len(), 3);
+    assert_eq!(book.total_bid_volume(2), 3.0);
+    assert_eq!(book.total_bid_volume(10), 6.0);
+
+    // 新快照整体替换，而不是合并
+    book.apply_snapshot(&OrderBookSnapshot {
+        symbol: "BTC-USD".to_string(),
+        bids: vec![level(90.0, 1.0)],
+        asks: vec![level(95.0, 1.0)],
+        ts_event: 20,
+    });
+    assert_eq!(book.depth(10), (vec![level(90.0, 1.0)], vec![level(95.0, 1.0)]));
+}
+
+#[test]
+fn deltas_add_update_and_delete_levels() {
+    let mut book = book();
+
+    book.apply_delta(&delta(BookSide::Bid, BookAction::Add, 100.5, 0.5, 11)).unwrap();
+    assert_eq!(book.best_bid(), Some(level(100.5, 0.5)));
+    book.apply_delta(&delta(BookSide::Bid, BookAction::Update, 100.5, 4.0, 12)).unwrap();
+    assert_eq!(book.best_bid(), Some(level(100.5, 4.0)));
+    book.apply_delta(&delta(BookSide::Ask, BookAction::Delete, 101.0, 0.0, 13)).unwrap();
+    assert_eq!(book.best_ask(), Some(level(102.0, 2.0)));
+
+    assert_eq!(book.spread(), Some(1.5));
+    assert_eq!(book.last_update_ts, 13);
+}
+
+#[test]
+fn invalid_deltas_are_rejected_without_changing_the_book() {
+    let mut book = book();
+    let before = book.depth(10);
+
+    assert_eq!(
+        book.apply_delta(&delta(BookSide::Bid, BookAction::Add, 100.0, 1.0, 11)),
+        Err(BookError::InvalidDeltaAction { action: BookAction::Add, side: BookSide::Bid, price: 100.0 })
+    );
+    assert!(matches!(
+        book.apply_delta(&delta(BookSide::Ask, BookAction::Update, 150.0, 1.0, 11)),
+        Err(BookError::InvalidDeltaAction { action: BookAction::Update, .. })
+    ));
+    assert!(matches!(
+        book.apply_delta(&delta(BookSide::Ask, BookAction::Delete, 150.0, 0.0, 11)),
+        Err(BookError::InvalidDeltaAction { action: BookAction::Delete, .. })
+    ));
+    assert_eq!(
+        book.apply_delta(&delta(BookSide::Bid, BookAction::Update, 100.0, -1.0, 11)),
+        Err(BookError::InvalidSize { size: -1.0 })
+    );
+    let mut other = delta(BookSide::Bid, BookAction::Add, 95.0, 1.0, 11);
+    other.symbol = "ETH-USD".to_string();
+    assert!(matches!(book.apply_delta(&other), Err(BookError::SymbolMismatch { .. })));
+
+    assert_eq!(book.depth(10), before);
+    assert_eq!(book.last_update_ts, 10);
+}
+
+#[test]
+fn delta_that_would_cross_the_book_is_rolled_back() {
+    let mut book = book();
+    let before = book.depth(10);
+
+    // 新增高于最优卖价的买价
+    assert_eq!(
+        book.apply_delta(&delta(BookSide::Bid, BookAction::Add, 101.5, 1.0, 11)),
+        Err(BookError::CrossedBook { best_bid: 101.5, best_ask: 101.0 })
+    );
+    // 新增等于最优买价的卖价同样视为交叉
+    assert_eq!(
+        book.apply_delta(&delta(BookSide::Ask, BookAction::Add, 100.0, 1.0, 11)),
+        Err(BookError::CrossedBook { best_bid: 100.0, best_ask: 100.0 })
+    );
+
+    assert_eq!(book.depth(10), before);
+    assert_eq!(book.last_update_ts, 10);
+}
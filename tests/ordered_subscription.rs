@@ -0,0 +1,88 @@
+// tests/ordered_subscription.rs
+
+//! # 有序订阅测试
+//!
+//! `subscribe_ordered` 的订阅者按优先级从高到低收到每条消息：优先级更高的订阅者确认（再次 `recv` 或 `ack`）之前，
+//! 优先级更低的订阅者收不到它；所有有序订阅者在通道上只计为一个订阅者，离开的订阅者不会阻塞其他人。
+
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::Bar;
+use message_bus::testkit::BarBuilder;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const SHORT: Duration = Duration::from_millis(50);
+
+#[tokio::test]
+async fn lower_priorities_wait_for_the_higher_priority_ack() {
+    let bus = MessageBus::new(16);
+    // 先订阅的低优先级订阅者仍然排在后面
+    let mut execution = bus.subscribe_ordered::<Bar>(0).await.unwrap();
+    let mut risk = bus.subscribe_ordered::<Bar>(10).await.unwrap();
+    assert_eq!((risk.priority(), execution.priority()), (10, 0));
+
+    bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap();
+    assert_eq!(risk.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    assert_eq!(execution.recv_timeout(SHORT).await, Err(RecvTimeout::Timeout));
+
+    risk.ack();
+    assert_eq!(execution.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+}
+
+#[tokio::test]
+async fn processing_alternates_in_priority_order() {
+    let bus = MessageBus::new(16);
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut handles = Vec::new();
+    for (name, priority) in [("execution", -5), ("risk", 5)] {
+        let mut rx = bus.subscribe_ordered::<Bar>(priority).await.unwrap();
+        let log = log.clone();
+        handles.push(tokio::spawn(async move {
+            for _ in 0..3 {
+                let bar = rx.recv().await.unwrap();
+                // 处理需要时间，确认在下一次 recv 时才发出
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                log.lock().unwrap().push(format!("{}:{}", name, bar.ts_event));
+            }
+        }));
+    }
+
+    for ts_event in 1..=3 {
+        bus.publish(BarBuilder::new().ts_event(ts_event).build()).await.unwrap();
+    }
+    for handle in handles {
+        tokio::time::timeout(TIMEOUT, handle).await.unwrap().unwrap();
+    }
+    assert_eq!(*log.lock().unwrap(), ["risk:1", "execution:1", "risk:2", "execution:2", "risk:3", "execution:3"]);
+}
+
+#[tokio::test]
+async fn ordered_subscribers_count_once_and_plain_subscribers_are_unaffected() {
+    let bus = MessageBus::new(16);
+    let mut first = bus.subscribe_ordered::<Bar>(1).await.unwrap();
+    let mut second = bus.subscribe_ordered::<Bar>(1).await.unwrap();
+    let mut plain = bus.subscribe::<Bar>().await;
+
+    assert_eq!(bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap(), 2);
+    assert_eq!(plain.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    // 同一优先级同时收到
+    assert_eq!(first.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    assert_eq!(second.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+}
+
+#[tokio::test]
+async fn departed_subscribers_do_not_block_the_chain() {
+    let bus = MessageBus::new(16);
+    let mut risk = bus.subscribe_ordered::<Bar>(10).await.unwrap();
+    let mut execution = bus.subscribe_ordered::<Bar>(0).await.unwrap();
+
+    bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap();
+    assert_eq!(risk.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    // 持有未确认的消息离开，确认视为已发出
+    drop(risk);
+    assert_eq!(execution.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+
+    bus.publish(BarBuilder::new().ts_event(2).build()).await.unwrap();
+    assert_eq!(execution.recv_timeout(TIMEOUT).await.unwrap().ts_event, 2);
+}
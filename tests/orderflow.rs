@@ -0,0 +1,67 @@
+// tests/orderflow.rs
+
+//! # 订单流测试
+//!
+//! 验证 tick rule 的买卖分类、滚动窗口的淘汰与不平衡的计算，
+//! 以及 `OrderFlowActor` 按 symbol 发布 `OrderFlowMetric`。
+
+use message_bus::message::{OrderFlowMetric, OrderSide, TradeTick};
+use message_bus::orderflow::{FlowState, OrderFlowActor};
+use message_bus::testkit::{ActorTestHarness, TestBus};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const SECOND: u64 = 1_000_000_000;
+const WINDOW: Duration = Duration::from_secs(10);
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+fn tick(symbol: &str, price: f64, size: f64, ts_event: u64) -> TradeTick {
+    TradeTick { id: Uuid::new_v4(), symbol: symbol.to_string(), price, size, ts_event }
+}
+
+#[test]
+fn tick_rule_classifies_and_window_expires() {
+    let mut state = FlowState::default();
+    // 第一笔方向未知
+    state.push(0, 100.0, 5.0, WINDOW);
+    assert_eq!(state.imbalance(), None);
+
+    state.push(SECOND, 101.0, 3.0, WINDOW); // 上涨：买
+    state.push(2 * SECOND, 101.0, 1.0, WINDOW); // 不变：沿用买
+    state.push(3 * SECOND, 100.5, 2.0, WINDOW); // 下跌：卖
+    assert_eq!(state.volume(OrderSide::Buy), 4.0);
+    assert_eq!(state.volume(OrderSide::Sell), 2.0);
+    assert!((state.imbalance().unwrap() - 2.0 / 6.0).abs() < 1e-12);
+
+    // 11 秒时 1 秒的买单移出窗口
+    state.push(11 * SECOND, 100.0, 1.0, WINDOW);
+    assert_eq!(state.volume(OrderSide::Buy), 1.0);
+    assert_eq!(state.volume(OrderSide::Sell), 3.0);
+    assert_eq!(state.imbalance(), Some(-0.5));
+}
+
+#[tokio::test]
+async fn publishes_a_metric_per_classified_tick() {
+    let test_bus = TestBus::new(64);
+    let actor = Arc::new(OrderFlowActor::new(test_bus.bus(), WINDOW));
+    let mut harness = ActorTestHarness::start(test_bus, actor.clone()).await;
+
+    harness.send(tick("BTC-USD", 100.0, 1.0, 0)).await;
+    harness.send(tick("ETH-USD", 10.0, 1.0, 0)).await;
+    harness.send(tick("BTC-USD", 102.0, 3.0, SECOND)).await;
+    let metric: OrderFlowMetric = harness.expect_message(TIMEOUT).await;
+    assert_eq!(metric.symbol, "BTC-USD");
+    assert_eq!(metric.imbalance, 1.0);
+    assert_eq!(metric.buy_volume, 3.0);
+    assert_eq!(metric.sell_volume, 0.0);
+    assert_eq!(metric.window_end_ts, SECOND);
+
+    harness.send(tick("BTC-USD", 101.0, 1.0, 2 * SECOND)).await;
+    let metric: OrderFlowMetric = harness.expect_message(TIMEOUT).await;
+    assert_eq!(metric.imbalance, 0.5);
+    assert_eq!(actor.imbalance("BTC-USD"), Some(0.5));
+    // 每个 symbol 的第一笔成交方向未知，不发布
+    assert_eq!(actor.imbalance("ETH-USD"), None);
+    assert_eq!(harness.bus().published::<OrderFlowMetric>().len(), 2);
+}
@@ -0,0 +1,56 @@
+// tests/participation.rs
+
+//! # 按参与率成交测试
+//!
+//! 使用 `FillModel::Participation` 的模拟执行引擎，验证市价单按每根 `Bar` 成交量的固定比例
+//! 分多根 `Bar` 成交，每笔成交价为对应 `Bar` 的收盘价。
+
+use message_bus::execution::{FillModel, SimulatedExecutionEngine};
+use message_bus::message::{FillEvent, OrderAccepted, OrderRequest, OrderSide, OrderType};
+use message_bus::testkit::{ActorTestHarness, BarBuilder, TestBus};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const SYMBOL: &str = "BTC-USD";
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+
+#[tokio::test]
+async fn order_completes_over_three_bars() {
+    let test_bus = TestBus::new(64);
+    let engine = Arc::new(
+        SimulatedExecutionEngine::new(test_bus.bus()).with_fill_model(FillModel::Participation { participation_rate: 0.1 }),
+    );
+    let mut venue = ActorTestHarness::start(test_bus, engine).await;
+
+    // 每根 Bar 成交量 10，参与率 10%，每根最多成交 1；订单为 3 倍
+    let order = OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: SYMBOL.to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 3.0,
+        trigger_price: None,
+    };
+    venue.send(order.clone()).await;
+    venue.expect_message::<OrderAccepted>(TIMEOUT).await;
+    // 确认后不会立即成交，而是等待行情
+    venue.expect_no_message::<FillEvent>(QUIET).await;
+
+    for (i, close) in [101.0, 102.0, 103.0].into_iter().enumerate() {
+        venue.send(BarBuilder::new().price(close).volume(10.0).build()).await;
+        let fill = venue.expect_message::<FillEvent>(TIMEOUT).await;
+        assert_eq!(fill.order_id, order.id);
+        assert_eq!(fill.price, close);
+        assert!((fill.quantity - 1.0).abs() < 1e-9);
+        assert!((fill.leaves_qty - (2 - i) as f64).abs() < 1e-9);
+        assert_eq!(fill.is_final, i == 2);
+    }
+
+    // 全部成交后不再有新的成交
+    venue.send(BarBuilder::new().price(104.0).volume(10.0).build()).await;
+    venue.expect_no_message::<FillEvent>(QUIET).await;
+    assert_eq!(venue.bus().published::<FillEvent>().len(), 3);
+}
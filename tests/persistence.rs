@@ -0,0 +1,115 @@
+// tests/persistence.rs
+
+//! # 交易持久化测试
+//!
+//! 使用内存 SQLite 数据库验证 `TradePersistence` 的写入与查询往返，以及 schema 迁移的幂等性。
+//! 需要启用 `sqlite` feature：`cargo test --features sqlite`。
+
+#![cfg(feature = "sqlite")]
+
+use message_bus::actor::Actor;
+use message_bus::bus::MessageBus;
+use message_bus::message::{FillEvent, Liquidity, OrderRequest, OrderSide, OrderStatus, OrderStatusChanged, OrderType};
+use message_bus::persistence::TradePersistence;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn fill(order_id: Uuid, symbol: &str, ts_event: u64) -> FillEvent {
+    FillEvent {
+        order_id,
+        symbol: symbol.to_string(),
+        side: OrderSide::Sell,
+        price: 101.5,
+        quantity: 2.0,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.25,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Maker,
+        ts_event,
+        venue_fill_id: Some(format!("F-{}", ts_event)),
+        correlation_id: Some(order_id),
+    }
+}
+
+/// 轮询直到写入任务提交了 `expected` 笔成交。
+async fn wait_for_fills(persistence: &TradePersistence, symbol: &str, expected: usize) -> Vec<FillEvent> {
+    for _ in 0..100 {
+        let fills = persistence.fills_for_symbol(symbol, 0..u64::MAX).unwrap();
+        if fills.len() >= expected {
+            return fills;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("fills for {} were not persisted in time", symbol);
+}
+
+#[tokio::test]
+async fn fills_round_trip_through_sqlite() {
+    let bus = MessageBus::new(64);
+    let persistence = Arc::new(TradePersistence::in_memory(bus.clone()).unwrap());
+    let handles = persistence.clone().start("PERSISTENCE").await;
+
+    let order = OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Sell,
+        order_type: OrderType::Limit,
+        price: 101.5,
+        quantity: 2.0,
+        trigger_price: None,
+    };
+    bus.publish(order.clone()).await.unwrap();
+    bus.publish(OrderStatusChanged {
+        order_id: order.id,
+        symbol: order.symbol.clone(),
+        previous: Some(OrderStatus::Accepted),
+        status: OrderStatus::Filled,
+        filled_qty: 2.0,
+        leaves_qty: 0.0,
+        ts_event: 20,
+    })
+    .await
+    .unwrap();
+    for ts in [30, 10, 50] {
+        bus.publish(fill(order.id, "BTC-USD", ts)).await.unwrap();
+    }
+    bus.publish(fill(order.id, "ETH-USD", 40)).await.unwrap();
+
+    let fills = wait_for_fills(&persistence, "BTC-USD", 3).await;
+    assert_eq!(fills.iter().map(|f| f.ts_event).collect::<Vec<_>>(), vec![10, 30, 50]);
+    let first = &fills[0];
+    assert_eq!(first.order_id, order.id);
+    assert_eq!(first.side, OrderSide::Sell);
+    assert_eq!((first.price, first.quantity, first.leaves_qty, first.commission), (101.5, 2.0, 0.0, 0.25));
+    assert!(first.is_final);
+    assert_eq!(first.commission_currency, "USD");
+    assert_eq!(first.liquidity, Liquidity::Maker);
+    assert_eq!(first.venue_fill_id.as_deref(), Some("F-10"));
+
+    // 时间范围为左闭右开
+    let ranged = persistence.fills_for_symbol("BTC-USD", 10..50).unwrap();
+    assert_eq!(ranged.iter().map(|f| f.ts_event).collect::<Vec<_>>(), vec![10, 30]);
+    assert_eq!(wait_for_fills(&persistence, "ETH-USD", 1).await.len(), 1);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[test]
+fn schema_migration_is_idempotent() {
+    let path = std::env::temp_dir().join(format!("message-bus-{}.sqlite", Uuid::new_v4()));
+    let bus = MessageBus::new(8);
+    let first = TradePersistence::open(bus.clone(), &path).unwrap();
+    let version = first.schema_version().unwrap();
+    assert!(version > 0);
+    drop(first);
+
+    let reopened = TradePersistence::open(bus, &path).unwrap();
+    assert_eq!(reopened.schema_version().unwrap(), version);
+    assert!(reopened.fills_for_symbol("BTC-USD", 0..u64::MAX).unwrap().is_empty());
+    drop(reopened);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,63 @@
+// tests/pipeline.rs
+
+//! # 流水线测试
+//!
+//! `Pipeline` 的各级按添加顺序处理每条消息，最后一级的输出是所有转换依次作用的结果；
+//! 某一级返回 `None` 时消息在该级被丢弃，下游不再收到；各级在上游发布之前已完成订阅。
+
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::Message;
+use message_bus::pipeline::Pipeline;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug)]
+struct Raw(u64);
+impl Message for Raw {}
+
+#[derive(Clone, Debug)]
+struct Doubled(u64);
+impl Message for Doubled {}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Labeled(String);
+impl Message for Labeled {}
+
+/// 三级流水线 `Raw -> Doubled -> Labeled`，每一级把 (级名, 值) 记入 `log`；奇数在第一级被丢弃。
+fn pipeline(bus: &MessageBus, log: &Arc<Mutex<Vec<(&'static str, u64)>>>) -> Pipeline<Labeled> {
+    let (first, second) = (log.clone(), log.clone());
+    Pipeline::new(bus.clone())
+        .stage(move |Raw(n): Raw| {
+            first.lock().unwrap().push(("double", n));
+            (n % 2 == 0).then_some(Doubled(n * 2))
+        })
+        .stage(move |Doubled(n): Doubled| {
+            second.lock().unwrap().push(("label", n));
+            Some(Labeled(format!("#{}", n)))
+        })
+}
+
+#[tokio::test]
+async fn stages_run_in_order_and_compose() {
+    let bus = MessageBus::new(16);
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut out = bus.subscribe::<Labeled>().await;
+    let handles = pipeline(&bus, &log).start().await;
+
+    bus.publish(Raw(2)).await.unwrap();
+    assert_eq!(out.recv_timeout(TIMEOUT).await.unwrap(), Labeled("#4".to_string()));
+    assert_eq!(*log.lock().unwrap(), [("double", 2), ("label", 4)]);
+
+    bus.publish(Raw(5)).await.unwrap();
+    bus.publish(Raw(6)).await.unwrap();
+    assert_eq!(out.recv_timeout(TIMEOUT).await.unwrap(), Labeled("#12".to_string()));
+    // 5 在第一级被丢弃，第二级只看到 12
+    assert_eq!(*log.lock().unwrap(), [("double", 2), ("label", 4), ("double", 5), ("double", 6), ("label", 12)]);
+    assert_eq!(out.recv_timeout(Duration::from_millis(50)).await, Err(RecvTimeout::Timeout));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
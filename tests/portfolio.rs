@@ -0,0 +1,126 @@
+// tests/portfolio.rs
+
+//! # 组合估值测试
+//!
+//! `PortfolioTracker` 收到 `Quote` 后多头按买价、空头按卖价计算未实现盈亏，
+//! 没有报价的 symbol 仍按最近的 `Bar.close` 估值，开仓之前收到的报价在开仓后生效；
+//! 同一订单的部分成交只在完成时合并发布一条 `PositionUpdate`。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::{FillEvent, Liquidity, OrderSide, PortfolioSnapshot, PositionUpdate, Quote};
+use message_bus::portfolio::PortfolioTracker;
+use message_bus::testkit::BarBuilder;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn fill(symbol: &str, side: OrderSide, price: f64) -> FillEvent {
+    FillEvent {
+        order_id: Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        side,
+        price,
+        quantity: 2.0,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.0,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Taker,
+        ts_event: 1,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+fn quote(symbol: &str, bid: f64, ask: f64) -> Quote {
+    Quote { symbol: symbol.to_string(), bid, ask, ts_event: 3 }
+}
+
+#[tokio::test]
+async fn longs_are_marked_at_the_bid_and_shorts_at_the_ask() {
+    let bus = MessageBus::new(64);
+    let mut snapshots = bus.subscribe::<PortfolioSnapshot>().await;
+    let tracker = Arc::new(PortfolioTracker::new(bus.clone()).with_initial_capital(10_000.0));
+    let handles = tracker.clone().start("PORTFOLIO").await;
+
+    // 成交、Bar 与报价走不同的通道，每一步等到各自的快照再继续，保证先开仓再估值
+    let fills = [
+        fill("BTC-USD", OrderSide::Buy, 100.0),
+        fill("ETH-USD", OrderSide::Sell, 50.0),
+        fill("SOL-USD", OrderSide::Buy, 20.0),
+    ];
+    for fill in fills {
+        bus.publish(fill).await.unwrap();
+        snapshots.recv_timeout(TIMEOUT).await.unwrap();
+    }
+    let bar = BarBuilder::new().ts_event(2);
+    for bar in [
+        bar.clone().price(105.0).build(),
+        bar.clone().symbol("ETH-USD").price(45.0).build(),
+        bar.symbol("SOL-USD").price(22.0).build(),
+    ] {
+        bus.publish(bar).await.unwrap();
+        snapshots.recv_timeout(TIMEOUT).await.unwrap();
+    }
+    for quote in [quote("BTC-USD", 104.0, 106.0), quote("ETH-USD", 44.0, 46.0)] {
+        bus.publish(quote).await.unwrap();
+        snapshots.recv_timeout(TIMEOUT).await.unwrap();
+    }
+
+    // 多头按买价 104 而不是收盘价 105 估值
+    let long = tracker.position("BTC-USD").unwrap();
+    assert_eq!(long.mark_price(), Some(104.0));
+    assert_eq!(long.unrealized_pnl(), 8.0);
+    // 空头按卖价 46 估值
+    let short = tracker.position("ETH-USD").unwrap();
+    assert_eq!(short.mark_price(), Some(46.0));
+    assert_eq!(short.unrealized_pnl(), 8.0);
+    // 没有报价时按收盘价估值
+    let unquoted = tracker.position("SOL-USD").unwrap();
+    assert_eq!(unquoted.unrealized_pnl(), 4.0);
+    assert_eq!(tracker.net_pnl(), 20.0);
+
+    // 开仓之前收到的报价同样用于估值
+    bus.publish(quote("XRP-USD", 0.9, 1.1)).await.unwrap();
+    snapshots.recv_timeout(TIMEOUT).await.unwrap();
+    bus.publish(fill("XRP-USD", OrderSide::Buy, 1.0)).await.unwrap();
+    snapshots.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(tracker.position("XRP-USD").unwrap().mark_price(), Some(0.9));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn partial_buys_of_one_order_coalesce_into_a_single_position_update() {
+    let bus = MessageBus::new(64);
+    let mut updates = bus.subscribe::<PositionUpdate>().await;
+    let mut snapshots = bus.subscribe::<PortfolioSnapshot>().await;
+    let tracker = Arc::new(PortfolioTracker::new(bus.clone()).with_initial_capital(10_000.0));
+    let handles = tracker.clone().start("PORTFOLIO").await;
+
+    // 同一订单的两笔买入：1 @ 100 的部分成交，再 1 @ 102 完成
+    let order_id = Uuid::new_v4();
+    let first = FillEvent { order_id, quantity: 1.0, leaves_qty: 1.0, is_final: false, ..fill("BTC-USD", OrderSide::Buy, 100.0) };
+    let second = FillEvent { order_id, quantity: 1.0, ..fill("BTC-USD", OrderSide::Buy, 102.0) };
+
+    bus.publish(first).await.unwrap();
+    snapshots.recv_timeout(TIMEOUT).await.unwrap();
+    bus.publish(second).await.unwrap();
+    snapshots.recv_timeout(TIMEOUT).await.unwrap();
+
+    let update = updates.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(update.symbol, "BTC-USD");
+    assert_eq!(update.net_position, 2.0);
+    assert_eq!(update.avg_price, 101.0);
+    // 部分成交没有单独发布持仓更新
+    assert!(matches!(updates.recv_timeout(Duration::from_millis(100)).await, Err(RecvTimeout::Timeout)));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
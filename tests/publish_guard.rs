@@ -0,0 +1,65 @@
+// tests/publish_guard.rs
+
+//! # 发布守卫测试
+//!
+//! `PublishGuard` 在离开作用域时（包括提前返回与任务被中止）通过同步的 `try_publish` 发布消息，
+//! `cancel` 之后不再发布。
+
+use message_bus::bus::{MessageBus, PublishGuard, ReceiverExt, RecvTimeout};
+use message_bus::message::ActorStopped;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn stopped(actor_name: &str) -> ActorStopped {
+    ActorStopped { actor_name: actor_name.to_string() }
+}
+
+fn parse(bus: &MessageBus, input: &str) -> Result<u64, std::num::ParseIntError> {
+    let _guard = PublishGuard::new(bus.clone(), stopped("PARSER"));
+    input.parse()
+}
+
+#[tokio::test]
+async fn message_is_published_when_the_scope_returns_early() {
+    let bus = MessageBus::new(16);
+    let mut rx = bus.subscribe::<ActorStopped>().await;
+
+    assert!(parse(&bus, "not a number").is_err());
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().actor_name, "PARSER");
+}
+
+#[tokio::test]
+async fn message_is_published_when_the_task_is_aborted() {
+    let bus = MessageBus::new(16);
+    let mut rx = bus.subscribe::<ActorStopped>().await;
+
+    let task_bus = bus.clone();
+    let task = tokio::spawn(async move {
+        let _guard = PublishGuard::new(task_bus, stopped("STRATEGY"));
+        std::future::pending::<()>().await;
+    });
+    tokio::task::yield_now().await;
+    task.abort();
+    assert!(task.await.unwrap_err().is_cancelled());
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().actor_name, "STRATEGY");
+}
+
+#[tokio::test]
+async fn cancelled_guard_does_not_publish() {
+    let bus = MessageBus::new(16);
+    let mut rx = bus.subscribe::<ActorStopped>().await;
+
+    let guard = PublishGuard::new(bus.clone(), stopped("RISK"));
+    assert_eq!(guard.cancel().actor_name, "RISK");
+    assert!(matches!(rx.recv_timeout(Duration::from_millis(50)).await, Err(RecvTimeout::Timeout)));
+}
+
+#[tokio::test]
+async fn try_publish_delivers_synchronously() {
+    let bus = MessageBus::new(16);
+    let mut rx = bus.subscribe::<ActorStopped>().await;
+
+    assert_eq!(bus.try_publish(stopped("EXECUTION")).unwrap(), 1);
+    assert_eq!(rx.try_recv().unwrap().actor_name, "EXECUTION");
+}
@@ -0,0 +1,53 @@
+// tests/publish_if.rs
+
+//! # 条件发布测试
+//!
+//! `publish_if` 在条件为假时跳过发布，订阅者收不到消息，发布计数也不变；条件为真时与 `publish` 相同。
+//! 多个发布方共享同一个暂停标志即可统一把关。
+
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::Bar;
+use message_bus::testkit::BarBuilder;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[tokio::test]
+async fn false_condition_skips_the_publish() {
+    let bus = MessageBus::new(16);
+    let mut rx = bus.subscribe::<Bar>().await;
+
+    assert_eq!(bus.publish_if(BarBuilder::new().ts_event(1).build(), || false).await.unwrap(), None);
+    assert_eq!(rx.recv_timeout(Duration::from_millis(50)).await, Err(RecvTimeout::Timeout));
+    assert_eq!(bus.publish_count(), 0);
+}
+
+#[tokio::test]
+async fn true_condition_publishes_to_subscribers() {
+    let bus = MessageBus::new(16);
+    let mut rx = bus.subscribe::<Bar>().await;
+
+    assert_eq!(bus.publish_if(BarBuilder::new().ts_event(1).build(), || true).await.unwrap(), Some(1));
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    assert_eq!(bus.publish_count(), 1);
+}
+
+#[tokio::test]
+async fn a_shared_halt_flag_gates_every_publisher() {
+    let bus = MessageBus::new(16);
+    let mut rx = bus.subscribe::<Bar>().await;
+    let halted = Arc::new(AtomicBool::new(false));
+    let running = || !halted.load(Ordering::Acquire);
+
+    bus.publish_if(BarBuilder::new().ts_event(1).build(), running).await.unwrap();
+    halted.store(true, Ordering::Release);
+    bus.clone_with_prefix("strategy").publish_if(BarBuilder::new().ts_event(2).build(), running).await.unwrap();
+    halted.store(false, Ordering::Release);
+    bus.publish_if(BarBuilder::new().ts_event(3).build(), running).await.unwrap();
+
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 1);
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap().ts_event, 3);
+    assert_eq!(rx.recv_timeout(Duration::from_millis(50)).await, Err(RecvTimeout::Timeout));
+}
@@ -0,0 +1,79 @@
+// tests/purge.rs
+
+//! # 通道清除测试
+//!
+//! `MessageBus::purge` 让现有订阅者跳过已缓冲的消息，通道保持打开；
+//! `SimulatedDataEngine` 重启时以此丢弃重启前的陈旧 `Bar`。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{BusError, MessageBus, Receiver, ReceiverExt, RecvTimeout};
+use message_bus::data::SimulatedDataEngine;
+use message_bus::message::Bar;
+use message_bus::testkit::BarBuilder;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::TryRecvError;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[tokio::test]
+async fn purge_discards_buffered_messages_and_keeps_the_channel_open() {
+    let bus = MessageBus::new(16);
+    let mut first = bus.subscribe::<Bar>().await;
+    let mut second = bus.subscribe::<Bar>().await;
+    for ts in 0..5 {
+        bus.publish(BarBuilder::new().ts_event(ts).build()).await.unwrap();
+    }
+    // 第二个订阅者已经读过两条，通道中仍有 5 条未被所有订阅者读取
+    second.recv().await.unwrap();
+    second.recv().await.unwrap();
+
+    assert_eq!(bus.purge::<Bar>().await.unwrap(), 5);
+    assert!(matches!(first.try_recv(), Err(TryRecvError::Empty)));
+    assert!(matches!(second.recv_timeout(Duration::from_millis(50)).await, Err(RecvTimeout::Timeout)));
+
+    assert_eq!(bus.publish(BarBuilder::new().ts_event(5).build()).await.unwrap(), 2);
+    assert_eq!(first.recv().await.unwrap().ts_event, 5);
+    assert_eq!(second.recv().await.unwrap().ts_event, 5);
+    assert_eq!(bus.purge::<Bar>().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn purge_without_a_channel_is_an_error() {
+    let bus = MessageBus::new(16);
+    assert!(matches!(bus.purge::<Bar>().await, Err(BusError::ChannelNotFound { .. })));
+    // 其他命名空间的通道不算
+    let _rx = bus.clone_with_prefix("venue").subscribe::<Bar>().await;
+    assert!(matches!(bus.purge::<Bar>().await, Err(BusError::ChannelNotFound { .. })));
+}
+
+#[tokio::test]
+async fn restarted_data_engine_purges_stale_bars() {
+    let bus = MessageBus::new(16);
+    let mut rx = bus.subscribe::<Bar>().await;
+    let engine = Arc::new(SimulatedDataEngine::new(bus.clone(), "BTC-USD".to_string()));
+
+    // 第一次启动发布一根 Bar 后停止，它留在缓冲中没有被读取
+    for handle in engine.clone().start("DATA").await {
+        wait_for_buffered(&rx, 1).await;
+        handle.abort();
+    }
+    for handle in engine.clone().start("DATA").await {
+        wait_for_buffered(&rx, 2).await;
+        handle.abort();
+    }
+
+    // 重启前的 Bar 被跳过，只收到重启后发布的一根
+    rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+}
+
+async fn wait_for_buffered(rx: &Receiver<Bar>, buffered: usize) {
+    tokio::time::timeout(TIMEOUT, async {
+        while rx.len() < buffered {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
@@ -0,0 +1,88 @@
+// tests/quiescence.rs
+
+//! # 静止检测测试
+//!
+//! 有限的一段 `Bar` 发布完毕后，`QuiescenceMonitor` 在静止期之后不久发出停止信号；
+//! 数据源任务结束之前、或被排除类型的消息仍在发布时都不算静止。
+
+use message_bus::actor::Actor;
+use message_bus::bus::MessageBus;
+use message_bus::message::{Bar, SystemEvent};
+use message_bus::quiescence::QuiescenceMonitor;
+use message_bus::testkit::BarBuilder;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const QUIET_PERIOD: Duration = Duration::from_millis(200);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[tokio::test]
+async fn shutdown_follows_the_last_bar_of_a_finite_series() {
+    let bus = MessageBus::new(64);
+    let _rx = bus.subscribe::<Bar>().await;
+
+    // 数据源以固定间隔发布 20 根 Bar，最后一根之后总线静止
+    let source = tokio::spawn({
+        let bus = bus.clone();
+        async move {
+            for ts_event in 0..20 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                bus.publish(BarBuilder::new().ts_event(ts_event).build()).await.unwrap();
+            }
+            Instant::now()
+        }
+    });
+    let monitor = Arc::new(
+        QuiescenceMonitor::new(bus.clone(), QUIET_PERIOD)
+            .with_poll_interval(POLL_INTERVAL)
+            .with_source(source.abort_handle()),
+    );
+    let shutdown = monitor.shutdown_token();
+    let handles = monitor.start("QUIESCENCE").await;
+
+    tokio::time::timeout(Duration::from_secs(5), shutdown.cancelled()).await.unwrap();
+    let stopped = Instant::now();
+    let last_bar = source.await.unwrap();
+    assert_eq!(bus.type_metrics()[0].1.published, 20);
+    let after_last = stopped - last_bar;
+    assert!(after_last + POLL_INTERVAL >= QUIET_PERIOD, "stopped {:?} after the last bar", after_last);
+    assert!(after_last < QUIET_PERIOD + Duration::from_millis(300), "stopped {:?} after the last bar", after_last);
+    for handle in handles {
+        handle.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn unfinished_sources_and_ignored_types_are_handled() {
+    let bus = MessageBus::new(64);
+    let _events = bus.subscribe::<SystemEvent>().await;
+    let _bars = bus.subscribe::<Bar>().await;
+
+    // 数据源尚未结束，即使没有任何发布也不算静止
+    let source = tokio::spawn(tokio::time::sleep(Duration::from_secs(60)));
+    let waiting = QuiescenceMonitor::new(bus.clone(), QUIET_PERIOD)
+        .with_poll_interval(POLL_INTERVAL)
+        .with_source(source.abort_handle());
+    assert!(tokio::time::timeout(QUIET_PERIOD * 2, waiting.wait_until_quiet()).await.is_err());
+    source.abort();
+    let quiet_for = tokio::time::timeout(QUIET_PERIOD * 2, waiting.wait_until_quiet()).await.unwrap();
+    assert!(quiet_for >= QUIET_PERIOD);
+
+    // 被排除的类型持续发布时仍然算静止
+    let heartbeat = tokio::spawn({
+        let bus = bus.clone();
+        async move {
+            loop {
+                let event = SystemEvent::ChannelHotSpot { type_name: "Bar", fill_pct: 90.0, receiver_count: 1 };
+                bus.publish(event).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        }
+    });
+    bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap();
+    let ignoring = QuiescenceMonitor::new(bus.clone(), QUIET_PERIOD).with_poll_interval(POLL_INTERVAL);
+    assert!(tokio::time::timeout(QUIET_PERIOD * 2, ignoring.wait_until_quiet()).await.is_err());
+    let ignoring = ignoring.ignore::<SystemEvent>();
+    tokio::time::timeout(QUIET_PERIOD * 2, ignoring.wait_until_quiet()).await.unwrap();
+    heartbeat.abort();
+}
@@ -0,0 +1,94 @@
+// tests/random_walk.rs
+
+//! # 随机游走数据引擎测试
+//!
+//! `SimulatedDataEngine::with_random_walk` 在相同种子下两次运行发布相同的 `Bar`；
+//! 价格模型由引擎与发布任务共享，重启后从停下的位置继续，而不是回到初始价格或默认序列。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::clock::VirtualClock;
+use message_bus::data::{RandomWalk, RandomWalkConfig, SimulatedDataEngine};
+use message_bus::message::Bar;
+use std::sync::Arc;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn config(seed: u64) -> RandomWalkConfig {
+    RandomWalkConfig { initial_price: 30_000.0, drift_per_bar: 0.0, volatility_per_bar: 0.01, seed: Some(seed) }
+}
+
+fn engine(bus: &MessageBus, seed: u64) -> Arc<SimulatedDataEngine> {
+    let engine = SimulatedDataEngine::new(bus.clone(), "BTC-USD".to_string())
+        .with_clock(Arc::new(VirtualClock::new(1_000)))
+        .with_random_walk(config(seed));
+    Arc::new(engine)
+}
+
+/// `Bar` 的相等只比较标识字段，这里比较除随机 `id` 之外的全部内容。
+fn contents(bar: &Bar) -> (u64, String, [f64; 5], Duration) {
+    (bar.ts_event, bar.symbol.clone(), [bar.open, bar.high, bar.low, bar.close, bar.volume], bar.timeframe)
+}
+
+fn ohlcv(bar: &Bar) -> (f64, f64, f64, f64, f64) {
+    (bar.open, bar.high, bar.low, bar.close, bar.volume)
+}
+
+/// 启动引擎，收到一根 `Bar` 后停止发布任务。
+async fn run_once(engine: &Arc<SimulatedDataEngine>, bus: &MessageBus) -> Bar {
+    let mut rx = bus.subscribe::<Bar>().await;
+    let handles = engine.clone().start("DATA").await;
+    let bar = rx.recv_timeout(TIMEOUT).await.unwrap();
+    for handle in handles {
+        handle.abort();
+    }
+    bar
+}
+
+#[tokio::test]
+async fn the_same_seed_publishes_identical_bars() {
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let bus = MessageBus::new(16);
+        let mut rx = bus.subscribe::<Bar>().await;
+        let handles = engine(&bus, 42).start("DATA").await;
+        let mut bars = Vec::new();
+        for _ in 0..3 {
+            bars.push(contents(&rx.recv_timeout(TIMEOUT).await.unwrap()));
+        }
+        for handle in handles {
+            handle.abort();
+        }
+        runs.push(bars);
+    }
+    assert_eq!(runs[0], runs[1]);
+    assert_eq!(runs[0][0].2[0], 30_000.0);
+
+    let bus = MessageBus::new(16);
+    let other = run_once(&engine(&bus, 43), &bus).await;
+    assert_ne!(contents(&other).2, runs[0][0].2);
+}
+
+#[tokio::test]
+async fn restart_continues_the_seeded_walk() {
+    let mut expected = RandomWalk::new(config(7));
+    let bus = MessageBus::new(16);
+    let engine = engine(&bus, 7);
+
+    let first = run_once(&engine, &bus).await;
+    assert_eq!(ohlcv(&first), expected.next_ohlcv());
+    for _ in 0..2 {
+        let bar = run_once(&engine, &bus).await;
+        assert_eq!(ohlcv(&bar), expected.next_ohlcv());
+    }
+}
+
+#[tokio::test]
+async fn restart_continues_the_default_series() {
+    let bus = MessageBus::new(16);
+    let engine = Arc::new(SimulatedDataEngine::new(bus.clone(), "BTC-USD".to_string()));
+    for price in [100.0, 101.0, 102.0] {
+        assert_eq!(run_once(&engine, &bus).await.close, price);
+    }
+}
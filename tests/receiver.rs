@@ -0,0 +1,113 @@
+// tests/receiver.rs
+
+//! # 订阅者扩展方法测试
+//!
+//! 验证 `ReceiverExt` 提供的辅助方法（包括普通、分组与有序订阅端的 `recv_timeout` 超时），
+//! 以及 `subscribe_sampled` 的抽样订阅。
+
+use message_bus::bus::{BusError, GroupPolicy, MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::Bar;
+use message_bus::testkit::BarBuilder;
+use futures::StreamExt;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn recv_timeout_returns_timeout_when_nothing_arrives() {
+    let bus = MessageBus::new(4);
+    let mut rx = bus.subscribe::<Bar>().await;
+
+    let wait = Duration::from_millis(50);
+    let started = Instant::now();
+    assert_eq!(rx.recv_timeout(wait).await.unwrap_err(), RecvTimeout::Timeout);
+    assert!(started.elapsed() >= wait);
+
+    // 超时之后接收者仍然可用
+    bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap();
+    assert_eq!(rx.recv_timeout(wait).await.unwrap().ts_event, 1);
+
+    // 落后与关闭分别返回 Lagged 与 Closed，而不是 Timeout
+    for ts in 0..6 {
+        bus.publish(BarBuilder::new().ts_event(ts).build()).await.unwrap();
+    }
+    let lagged = rx.recv_timeout(wait).await.unwrap_err();
+    assert_eq!(lagged, RecvTimeout::Lagged { type_name: std::any::type_name::<Bar>(), count: 2 });
+    assert!(matches!(lagged.into_bus_error(), Some(BusError::Lagged { count: 2, .. })));
+    drop(bus);
+    rx.drain_backlog();
+    assert_eq!(rx.recv_timeout(wait).await.unwrap_err(), RecvTimeout::Closed);
+}
+
+#[tokio::test]
+async fn group_and_ordered_receivers_time_out_the_same_way() {
+    let bus = MessageBus::new(4);
+    let mut group = bus.subscribe_group::<Bar>("workers", GroupPolicy::RoundRobin).await.unwrap();
+    let mut ordered = bus.subscribe_ordered::<Bar>(0).await.unwrap();
+    let wait = Duration::from_millis(50);
+
+    assert_eq!(group.recv_timeout(wait).await.unwrap_err(), RecvTimeout::Timeout);
+    assert_eq!(ordered.recv_timeout(wait).await.unwrap_err(), RecvTimeout::Timeout);
+
+    bus.publish(BarBuilder::new().ts_event(7).build()).await.unwrap();
+    assert_eq!(group.recv_timeout(wait).await.unwrap().ts_event, 7);
+    assert_eq!(ordered.recv_timeout(wait).await.unwrap().ts_event, 7);
+}
+
+#[tokio::test]
+async fn drain_backlog_discards_all_buffered_messages() {
+    let bus = MessageBus::new(16);
+    let mut rx = bus.subscribe::<Bar>().await;
+    for ts in 0..10 {
+        bus.publish(BarBuilder::new().ts_event(ts).build()).await.unwrap();
+    }
+
+    assert_eq!(rx.drain_backlog(), 10);
+    assert_eq!(rx.drain_backlog(), 0);
+    // 没有残留的过期消息，下一次 recv 会一直等待
+    assert!(matches!(rx.recv_timeout(Duration::from_millis(50)).await, Err(RecvTimeout::Timeout)));
+
+    // 排空之后发布的消息照常收到
+    bus.publish(BarBuilder::new().ts_event(10).build()).await.unwrap();
+    assert_eq!(rx.recv().await.unwrap().ts_event, 10);
+}
+
+#[tokio::test]
+async fn drain_backlog_counts_lagged_messages() {
+    let bus = MessageBus::new(4);
+    let mut rx = bus.subscribe::<Bar>().await;
+    for ts in 0..10 {
+        bus.publish(BarBuilder::new().ts_event(ts).build()).await.unwrap();
+    }
+
+    // 6 条因滞后被跳过，4 条仍在缓冲区中
+    assert_eq!(rx.drain_backlog(), 10);
+    assert!(matches!(rx.recv_timeout(Duration::from_millis(50)).await, Err(RecvTimeout::Timeout)));
+}
+
+#[tokio::test]
+async fn sampled_subscriber_receives_every_tenth_bar() {
+    let bus = MessageBus::new(128);
+    let mut rx = bus.subscribe_sampled::<Bar>(10).await;
+    for ts in 0..100 {
+        bus.publish(BarBuilder::new().ts_event(ts).build()).await.unwrap();
+    }
+
+    let mut received = Vec::new();
+    while let Ok(Ok(bar)) = tokio::time::timeout(Duration::from_millis(50), rx.recv()).await {
+        received.push(bar.ts_event);
+    }
+    assert_eq!(received, [9, 19, 29, 39, 49, 59, 69, 79, 89, 99]);
+    assert_eq!(rx.discarded_count(), 90);
+}
+
+#[tokio::test]
+async fn sampled_stream_ends_when_the_bus_is_dropped() {
+    let bus = MessageBus::new(16);
+    let stream = bus.subscribe_sampled::<Bar>(3).await.into_stream();
+    for ts in 0..7 {
+        bus.publish(BarBuilder::new().ts_event(ts).build()).await.unwrap();
+    }
+    drop(bus);
+
+    let sampled: Vec<u64> = stream.map(|bar| bar.ts_event).collect().await;
+    assert_eq!(sampled, [2, 5]);
+}
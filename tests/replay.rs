@@ -0,0 +1,220 @@
+// tests/replay.rs
+
+//! # 日志回放测试
+//!
+//! 用 `EventExporter` 写出按大小轮转的日志，在最后一个分段末尾追加写入中断留下的半行，
+//! 验证回放按分段顺序发布选中的类型与时间范围、跳过半行并在统计中标记；
+//! 再验证 TCP 目标逐行发送原始 JSON，倍速回放按录制间隔缩放等待时间，
+//! 以及 `ReplayControl` 暂停后不再发布、逐条前进恰好发布一条、调整速度对正在等待的事件立即生效。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::export::{EventExporter, Rotation};
+use message_bus::message::{Bar, WarmupComplete};
+use message_bus::replay::{
+    journal_segments, register_journal_types, JournalReader, ReplayFilter, ReplaySpeed, ReplayTarget, Replayer, TcpSink,
+};
+use message_bus::testkit::BarBuilder;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::TcpListener;
+
+const SYMBOL: &str = "BTC-USD";
+const SECOND: u64 = 1_000_000_000;
+
+/// 每个测试独立的临时目录。
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("message-bus-replay-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 直接写出 `Bar` 的日志行，`ts_event` 取自 `timestamps`。
+fn write_bar_journal(path: &Path, timestamps: impl IntoIterator<Item = u64>) {
+    let lines: Vec<String> = timestamps
+        .into_iter()
+        .map(|ts| {
+            let mut value = serde_json::to_value(BarBuilder::new().ts_event(ts).build()).unwrap();
+            value["type"] = "Bar".into();
+            value.to_string()
+        })
+        .collect();
+    std::fs::write(path, lines.join("\n") + "\n").unwrap();
+}
+
+/// 导出 `count` 根 `Bar`（`ts_event` 为 0..count）与一条 `WarmupComplete`，按 `rotate_at` 字节轮转。
+async fn record_journal(path: &Path, count: u64, rotate_at: u64) {
+    let bus = MessageBus::new(1024);
+    let exporter = Arc::new(
+        EventExporter::to_file(bus.clone(), path)
+            .unwrap()
+            .with_type::<Bar>()
+            .with_type::<WarmupComplete>()
+            .with_rotation(Rotation::Size(rotate_at)),
+    );
+    let handles = exporter.clone().start("EXPORT").await;
+    for ts in 0..count {
+        bus.publish(BarBuilder::new().ts_event(ts).build()).await.unwrap();
+    }
+    bus.publish(WarmupComplete { symbol: SYMBOL.to_string(), bars_seen: count as usize, ts_event: count }).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while exporter.exported() < count + 1 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("journal written");
+    for handle in &handles {
+        handle.abort();
+    }
+    futures::future::join_all(handles).await;
+}
+
+#[tokio::test]
+async fn replays_selected_events_across_segments_and_tolerates_a_truncated_tail() {
+    let dir = temp_dir("segments");
+    let path = dir.join("events.jsonl");
+    record_journal(&path, 20, 1_000).await;
+    // 写入进程在一行的中间被中断
+    let mut current = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+    write!(current, "{{\"type\":\"Bar\",\"ts_event\":99,\"sym").unwrap();
+    drop(current);
+
+    let segments = journal_segments(&path).unwrap();
+    assert!(segments.len() > 2, "expected rotated segments, got {:?}", segments);
+    assert_eq!(segments.first(), Some(&dir.join("events.1.jsonl")));
+    assert_eq!(segments.last(), Some(&path));
+
+    let mut bus = MessageBus::new(1024);
+    register_journal_types(&bus);
+    let mut bars = bus.subscribe::<Bar>().await;
+    let mut warmups = bus.subscribe::<WarmupComplete>().await;
+    let filter = ReplayFilter { types: ["Bar".to_string()].into(), from: Some(5), to: Some(14) };
+    let stats = Replayer::new(JournalReader::open(&path).unwrap()).with_filter(filter).run(&mut bus).await.unwrap();
+
+    assert_eq!(stats.replayed, 10);
+    assert_eq!(stats.filtered, 11);
+    assert_eq!(stats.skipped, 0);
+    assert!(stats.truncated);
+    assert_eq!(stats.last_ts, Some(14));
+    let replayed: Vec<u64> = std::iter::from_fn(|| bars.try_recv().ok()).map(|bar| bar.ts_event).collect();
+    assert_eq!(replayed, (5..15).collect::<Vec<_>>());
+    assert!(warmups.try_recv().is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn tcp_target_receives_the_journal_lines() {
+    let dir = temp_dir("tcp");
+    let path = dir.join("events.jsonl");
+    record_journal(&path, 3, u64::MAX).await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let target: ReplayTarget = format!("tcp://{}", listener.local_addr().unwrap()).parse().unwrap();
+    let ReplayTarget::Tcp(addr) = target else { panic!("expected a tcp target") };
+    let receiver = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let mut received = Vec::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            received.push(line);
+        }
+        received
+    });
+
+    let mut sink = TcpSink::connect(&addr).await.unwrap();
+    let stats = Replayer::new(JournalReader::open(&path).unwrap()).run(&mut sink).await.unwrap();
+    drop(sink);
+
+    assert_eq!(stats.replayed, 4);
+    let received = receiver.await.unwrap();
+    assert_eq!(received, std::fs::read_to_string(&path).unwrap().lines().collect::<Vec<_>>());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn speed_scales_recorded_gaps() {
+    assert_eq!("realtime".parse(), Ok(ReplaySpeed::Realtime));
+    assert_eq!("unthrottled".parse(), Ok(ReplaySpeed::Unthrottled));
+    assert!("0x".parse::<ReplaySpeed>().is_err());
+    assert!("fast".parse::<ReplaySpeed>().is_err());
+
+    let dir = temp_dir("speed");
+    let path = dir.join("events.jsonl");
+    let lines: Vec<String> = (0..3)
+        .map(|i| serde_json::json!({ "type": "TradingResumed", "ts_event": 10 * SECOND + i * 2 * SECOND }).to_string())
+        .collect();
+    std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+    let mut bus = MessageBus::new(16);
+    register_journal_types(&bus);
+    let started = std::time::Instant::now();
+    let speed = "40x".parse().unwrap();
+    let stats = Replayer::new(JournalReader::open(&path).unwrap()).with_speed(speed).run(&mut bus).await.unwrap();
+
+    assert_eq!(stats.replayed, 3);
+    // 录制跨度 4 秒，40 倍速回放约 100 毫秒
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(100) && elapsed < Duration::from_secs(1), "took {:?}", elapsed);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn pause_holds_the_replay_and_step_releases_exactly_one_event() {
+    let dir = temp_dir("pause");
+    let path = dir.join("events.jsonl");
+    write_bar_journal(&path, (0..5).map(|i| i * SECOND));
+
+    let mut bus = MessageBus::new(16);
+    register_journal_types(&bus);
+    let mut rx = bus.subscribe::<Bar>().await;
+    // 录制间隔 1 秒，20 倍速回放每 50 毫秒一根
+    let replayer = Replayer::new(JournalReader::open(&path).unwrap()).with_speed(ReplaySpeed::Multiple(20.0));
+    let control = replayer.control();
+    let replay = tokio::spawn(async move { replayer.run(&mut bus).await });
+
+    let timeout = Duration::from_secs(2);
+    assert_eq!(rx.recv_timeout(timeout).await.unwrap().ts_event, 0);
+    assert_eq!(rx.recv_timeout(timeout).await.unwrap().ts_event, SECOND);
+    control.pause();
+    assert!(control.is_paused());
+    assert_eq!(rx.recv_timeout(Duration::from_millis(200)).await, Err(RecvTimeout::Timeout));
+
+    control.step();
+    assert_eq!(rx.recv_timeout(timeout).await.unwrap().ts_event, 2 * SECOND);
+    assert_eq!(rx.recv_timeout(Duration::from_millis(200)).await, Err(RecvTimeout::Timeout));
+
+    control.resume();
+    assert_eq!(rx.recv_timeout(timeout).await.unwrap().ts_event, 3 * SECOND);
+    assert_eq!(rx.recv_timeout(timeout).await.unwrap().ts_event, 4 * SECOND);
+    let stats = replay.await.unwrap().unwrap();
+    assert_eq!(stats.replayed, 5);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn set_speed_applies_to_the_event_being_waited_for() {
+    let dir = temp_dir("set-speed");
+    let path = dir.join("events.jsonl");
+    // 录制间隔 1 小时，按实时回放不可能在测试中完成
+    write_bar_journal(&path, (0..3).map(|i| i * 3_600 * SECOND));
+
+    let mut bus = MessageBus::new(16);
+    register_journal_types(&bus);
+    let mut rx = bus.subscribe::<Bar>().await;
+    let replayer = Replayer::new(JournalReader::open(&path).unwrap()).with_speed(ReplaySpeed::Realtime);
+    let control = replayer.control();
+    let replay = tokio::spawn(async move { replayer.run(&mut bus).await });
+
+    assert_eq!(rx.recv_timeout(Duration::from_secs(2)).await.unwrap().ts_event, 0);
+    assert_eq!(rx.recv_timeout(Duration::from_millis(100)).await, Err(RecvTimeout::Timeout));
+    control.set_speed(ReplaySpeed::Unthrottled);
+    assert_eq!(control.speed(), ReplaySpeed::Unthrottled);
+    let stats = tokio::time::timeout(Duration::from_secs(2), replay).await.unwrap().unwrap().unwrap();
+    assert_eq!(stats.replayed, 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,51 @@
+// tests/request_reply.rs
+
+//! # 请求/回复测试
+//!
+//! 通过 `publish_and_await_reply` 向模拟执行引擎下单，验证返回的是与该订单关联的成交，
+//! 以及没有回复时按超时返回错误。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, RequestError};
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::message::{FillEvent, OrderRequest, OrderSide, OrderType};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn market_order() -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 1.0,
+        trigger_price: None,
+    }
+}
+
+#[tokio::test]
+async fn returns_the_fill_correlated_with_the_order() {
+    let bus = MessageBus::new(64);
+    let handles = Arc::new(SimulatedExecutionEngine::new(bus.clone())).start("EXECUTION").await;
+
+    // 先下一笔无关的订单，它的成交不应被当作回复
+    bus.publish(market_order()).await.unwrap();
+    let order = market_order();
+    let fill: FillEvent = bus.publish_and_await_reply(order.clone(), Duration::from_secs(2)).await.unwrap();
+    assert_eq!(fill.order_id, order.id);
+    assert_eq!(fill.correlation_id, Some(order.id));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn times_out_without_a_reply() {
+    let bus = MessageBus::new(64);
+    let timeout = Duration::from_millis(50);
+    let result = bus.publish_and_await_reply::<OrderRequest, FillEvent>(market_order(), timeout).await;
+    assert!(matches!(result, Err(RequestError::Timeout(t)) if t == timeout));
+}
@@ -0,0 +1,107 @@
+// tests/restart.rs
+
+//! # Actor 重启测试
+//!
+//! `ActorSystemHandle::restart` 多次重启同一个 Actor 后，总线上的订阅者数量保持不变：
+//! 通过 `ActorContext::subscribe` 取得的 `Receiver` 随旧任务一起被丢弃；
+//! 把订阅保存在 Actor 结构体中的 Actor 会被报告为泄漏。
+
+use message_bus::actor::{Actor, ActorContext};
+use message_bus::bus::{MessageBus, Receiver};
+use message_bus::message::Bar;
+use message_bus::system::{ActorSystemBuilder, RestartError};
+use message_bus::testkit::BarBuilder;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+const RESTARTS: usize = 5;
+
+/// 在 `run` 中订阅 `Bar` 并计数；`leak` 为真时把每次的订阅另存一份在结构体中。
+struct BarCounter {
+    received: AtomicUsize,
+    leak: bool,
+    stashed: Mutex<Vec<Receiver<Bar>>>,
+}
+
+impl BarCounter {
+    fn new(leak: bool) -> Arc<Self> {
+        Arc::new(Self { received: AtomicUsize::new(0), leak, stashed: Mutex::new(Vec::new()) })
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for BarCounter {
+    async fn start(self: Arc<Self>, _actor_name: &str) -> Vec<JoinHandle<()>> {
+        unreachable!("started through run")
+    }
+
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        let mut bars = ctx.subscribe::<Bar>().await;
+        if self.leak {
+            let stashed = ctx.subscribe::<Bar>().await;
+            self.stashed.lock().unwrap().push(stashed);
+        }
+        ctx.ready();
+        loop {
+            tokio::select! {
+                _ = ctx.shutdown_requested() => break,
+                received = bars.recv() => match received {
+                    Ok(_) => {
+                        self.received.fetch_add(1, Ordering::SeqCst);
+                    },
+                    Err(_) => break,
+                },
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn restarts_do_not_grow_the_subscriber_count() {
+    let bus = MessageBus::new(64);
+    let counter = BarCounter::new(false);
+    let system = ActorSystemBuilder::new(bus.clone()).actor("COUNTER", counter.clone(), &[], &[]).build().unwrap();
+    let mut handle = system.start().await;
+    assert_eq!(bus.subscriber_count::<Bar>().await, 1);
+
+    for i in 0..RESTARTS {
+        assert_eq!(handle.restart("COUNTER").await.unwrap(), Vec::<&str>::new());
+        assert_eq!(bus.subscriber_count::<Bar>().await, 1);
+
+        // 重启后的 Actor 仍然收到消息
+        bus.publish(BarBuilder::new().price(100.0).build()).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while counter.received.load(Ordering::SeqCst) <= i {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    handle.shutdown().await;
+    assert_eq!(bus.subscriber_count::<Bar>().await, 0);
+}
+
+#[tokio::test]
+async fn receivers_kept_across_restarts_are_reported() {
+    let bus = MessageBus::new(64);
+    let leaky = BarCounter::new(true);
+    let system = ActorSystemBuilder::new(bus.clone()).actor("LEAKY", leaky.clone(), &[], &[]).build().unwrap();
+    let mut handle = system.start().await;
+    assert_eq!(bus.subscriber_count::<Bar>().await, 2);
+
+    let leaked = handle.restart("LEAKY").await.unwrap();
+    assert_eq!(leaked, [std::any::type_name::<Bar>()]);
+    assert_eq!(bus.subscriber_count::<Bar>().await, 3);
+
+    // 丢弃保存的订阅后不再报告
+    leaky.stashed.lock().unwrap().clear();
+    let leaked = handle.restart("LEAKY").await.unwrap();
+    assert_eq!(leaked, Vec::<&str>::new());
+
+    assert_eq!(handle.restart("MISSING").await.unwrap_err(), RestartError::UnknownActor("MISSING".to_string()));
+    handle.shutdown().await;
+}
@@ -0,0 +1,136 @@
+// tests/risk.rs
+
+//! # 回撤熔断测试
+//!
+//! 用 `PortfolioSnapshot` 把权益推过回撤阈值，验证 `DrawdownGuard` 依次发布 `FlattenAll` 与 `TradingHalted`
+//! 且只触发一次；再验证执行引擎收到紧挨着发布的两条消息后先撤单、平仓，之后的新订单被暂停拒绝。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::message::{
+    Bar, FillEvent, FlattenAll, OrderCanceled, OrderRejected, OrderRequest, OrderSide, OrderType, PortfolioSnapshot,
+    RejectReason, TradingHalted, TradingResumed, DEFAULT_BAR_TIMEFRAME,
+};
+use message_bus::risk::DrawdownGuard;
+use message_bus::testkit::{ActorTestHarness, TestBus};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+
+fn snapshot(total_equity: f64, ts: u64) -> PortfolioSnapshot {
+    PortfolioSnapshot { total_equity, net_pnl: total_equity - 100_000.0, ts }
+}
+
+fn order(side: OrderSide, order_type: OrderType, price: f64, quantity: f64) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side,
+        order_type,
+        price,
+        quantity,
+        trigger_price: None,
+    }
+}
+
+#[tokio::test]
+async fn drawdown_past_threshold_flattens_then_halts_once() {
+    let test_bus = TestBus::new(64);
+    let guard = Arc::new(DrawdownGuard::new(test_bus.bus(), 10.0));
+    let mut harness = ActorTestHarness::start(test_bus, guard.clone()).await;
+
+    harness.send(snapshot(100_000.0, 1)).await;
+    harness.send(snapshot(110_000.0, 2)).await;
+    harness.send(snapshot(100_000.0, 3)).await;
+    harness.wait_until(TIMEOUT, || guard.drawdown_pct() > 9.0).await;
+    assert_eq!(guard.peak_equity(), Some(110_000.0));
+    harness.expect_no_message::<FlattenAll>(QUIET).await;
+
+    // 回撤 (110000 - 98000) / 110000 ≈ 10.9%
+    harness.send(snapshot(98_000.0, 4)).await;
+    let flatten: FlattenAll = harness.expect_message(TIMEOUT).await;
+    assert_eq!(flatten.ts_event, 4);
+    assert!(flatten.reason.contains("10.91%"), "{}", flatten.reason);
+    let halted: TradingHalted = harness.expect_message(TIMEOUT).await;
+    assert_eq!(halted.symbol, None);
+    assert!(guard.is_tripped());
+
+    let order: Vec<_> = harness
+        .bus()
+        .all_published()
+        .into_iter()
+        .map(|published| published.type_name)
+        .filter(|name| *name == "FlattenAll" || *name == "TradingHalted")
+        .collect();
+    assert_eq!(order, vec!["FlattenAll", "TradingHalted"]);
+
+    // 继续下跌不会重复触发
+    harness.send(snapshot(90_000.0, 5)).await;
+    harness.expect_no_message::<FlattenAll>(QUIET).await;
+
+    // 全局恢复后重新武装，峰值从新的快照开始
+    harness.send(TradingResumed { symbol: None }).await;
+    harness.wait_until(TIMEOUT, || !guard.is_tripped()).await;
+    harness.send(snapshot(90_000.0, 6)).await;
+    harness.wait_until(TIMEOUT, || guard.peak_equity() == Some(90_000.0)).await;
+    harness.send(snapshot(80_000.0, 7)).await;
+    let _: FlattenAll = harness.expect_message(TIMEOUT).await;
+    assert_eq!(harness.bus().published::<FlattenAll>().len(), 2);
+}
+
+#[tokio::test]
+async fn engine_flattens_before_the_halt_applies() {
+    let bus = MessageBus::new(64);
+    let engine = Arc::new(SimulatedExecutionEngine::new(bus.clone()));
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let mut canceled_rx = bus.subscribe::<OrderCanceled>().await;
+    let mut rejected_rx = bus.subscribe::<OrderRejected>().await;
+    let handles = engine.clone().start("EXECUTION").await;
+
+    // 建立 3 个多头，并挂一张不会成交的限价买单
+    bus.publish(order(OrderSide::Buy, OrderType::Market, 100.0, 3.0)).await.unwrap();
+    fill_rx.recv_timeout(TIMEOUT).await.unwrap();
+    let resting = order(OrderSide::Buy, OrderType::Limit, 50.0, 1.0);
+    bus.publish(resting.clone()).await.unwrap();
+    bus.publish(Bar {
+        id: Uuid::new_v4(),
+        ts_event: 0,
+        symbol: "BTC-USD".to_string(),
+        open: 95.0,
+        high: 96.0,
+        low: 94.0,
+        close: 95.0,
+        volume: 1.0,
+        timeframe: DEFAULT_BAR_TIMEFRAME,
+    })
+    .await
+    .unwrap();
+    tokio::time::sleep(QUIET).await;
+    assert_eq!(engine.net_position("BTC-USD"), 3.0);
+
+    // 与 DrawdownGuard 一样紧挨着发布
+    bus.publish(FlattenAll { reason: "test".to_string(), ts_event: 0 }).await.unwrap();
+    bus.publish(TradingHalted { symbol: None, reason: "test".to_string() }).await.unwrap();
+
+    let canceled = canceled_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(canceled.order_id, resting.id);
+    let close = fill_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(close.side, OrderSide::Sell);
+    assert_eq!(close.quantity, 3.0);
+    // 平仓单以最近的行情价（Bar 收盘价）成交
+    assert_eq!(close.price, 95.0);
+    assert_eq!(engine.net_position("BTC-USD"), 0.0);
+
+    // 暂停之后的新订单被拒绝
+    bus.publish(order(OrderSide::Buy, OrderType::Market, 95.0, 1.0)).await.unwrap();
+    let rejected = rejected_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(rejected.reason, RejectReason::TradingHalted);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,154 @@
+// tests/scripting.rs
+
+//! # 脚本策略测试
+//!
+//! `ScriptedStrategy` 的 Rhai 脚本在各次调用之间保留顶层变量并通过 `submit_order` 下单；
+//! 语法错误在加载时带行号报告，处理函数的运行错误发布 `ErrorEvent` 后跳过该消息，
+//! 修改脚本文件后重新加载并发布 `ScriptReloaded`，状态随之重置。
+//! 需要启用 `scripting` feature：`cargo test --features scripting --test scripting`。
+
+#![cfg(feature = "scripting")]
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::{ErrorEvent, OrderRequest, OrderSide, ScriptReloaded};
+use message_bus::scripting::{ScriptError, ScriptedStrategy};
+use message_bus::testkit::BarBuilder;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const QUIET: Duration = Duration::from_millis(100);
+
+/// 连续三根收盘价上涨时买入。
+const RISING: &str = r#"
+let closes = [];
+
+fn on_bar(bar) {
+    closes.push(bar.close);
+    if closes.len() > 3 { closes.remove(0); }
+    if closes.len() == 3 && closes[0] < closes[1] && closes[1] < closes[2] {
+        submit_order("buy", bar.close, 2);
+    }
+}
+"#;
+
+fn script(name: &str, source: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("message-bus-{}-{}.rhai", std::process::id(), name));
+    std::fs::write(&path, source).unwrap();
+    path
+}
+
+#[tokio::test]
+async fn script_state_persists_across_bars_and_orders_are_published() {
+    let bus = MessageBus::new(64);
+    let path = script("rising", RISING);
+    let strategy = Arc::new(ScriptedStrategy::load(bus.clone(), &path).unwrap());
+    let mut orders = bus.subscribe::<OrderRequest>().await;
+    let handles = strategy.start("SCRIPT").await;
+
+    for close in [100.0, 101.0] {
+        bus.publish(BarBuilder::new().price(close).build()).await.unwrap();
+    }
+    assert!(matches!(orders.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    bus.publish(BarBuilder::new().price(102.0).build()).await.unwrap();
+
+    let order = orders.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(order.symbol, "BTC-USD");
+    assert_eq!(order.side, OrderSide::Buy);
+    assert_eq!(order.price, 102.0);
+    assert_eq!(order.quantity, 2.0);
+
+    for handle in handles {
+        handle.abort();
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn compile_errors_are_reported_with_line_numbers() {
+    let path = script("syntax", "let x = 1;\n\nfn on_bar(bar) {\n    let = 2;\n}\n");
+    let error = ScriptedStrategy::load(MessageBus::new(8), &path).err().unwrap();
+    assert!(matches!(error, ScriptError::Compile { .. }), "{}", error);
+    assert_eq!(error.line(), Some(4));
+    assert!(error.to_string().contains(&format!("{}:4:", path.display())), "{}", error);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn runtime_errors_publish_an_error_event_and_skip_the_message() {
+    let bus = MessageBus::new(64);
+    let path = script(
+        "runtime",
+        r#"
+fn on_bar(bar) {
+    submit_order("buy", bar.close, 1.0);
+    if bar.close < 0.0 {
+        throw "negative price";
+    }
+}
+"#,
+    );
+    let strategy = Arc::new(ScriptedStrategy::load(bus.clone(), &path).unwrap());
+    let mut orders = bus.subscribe::<OrderRequest>().await;
+    let mut errors = bus.subscribe::<ErrorEvent>().await;
+    let handles = strategy.start("SCRIPT").await;
+
+    bus.publish(BarBuilder::new().price(-1.0).build()).await.unwrap();
+    let error = errors.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(error.source, "SCRIPT");
+    assert!(error.error.contains("negative price"), "{}", error.error);
+    assert!(error.error.contains(":5:"), "{}", error.error);
+
+    // 出错的调用提交的订单被丢弃，Actor 继续处理后续消息
+    bus.publish(BarBuilder::new().price(100.0).build()).await.unwrap();
+    assert_eq!(orders.recv_timeout(TIMEOUT).await.unwrap().price, 100.0);
+    assert!(matches!(orders.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    for handle in handles {
+        handle.abort();
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn modified_scripts_are_reloaded_with_fresh_state() {
+    let bus = MessageBus::new(64);
+    let path = script("reload", RISING);
+    let strategy = Arc::new(
+        ScriptedStrategy::load(bus.clone(), &path).unwrap().with_hot_reload(Duration::from_millis(10)),
+    );
+    let mut orders = bus.subscribe::<OrderRequest>().await;
+    let mut reloads = bus.subscribe::<ScriptReloaded>().await;
+    let mut errors = bus.subscribe::<ErrorEvent>().await;
+    let handles = strategy.start("SCRIPT").await;
+
+    for close in [100.0, 101.0] {
+        bus.publish(BarBuilder::new().price(close).build()).await.unwrap();
+    }
+
+    // 无法编译的新版本被报告，旧脚本继续运行
+    std::fs::write(&path, "fn on_bar(bar) {").unwrap();
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(1)).unwrap();
+    assert!(errors.recv_timeout(TIMEOUT).await.unwrap().error.contains("reload failed"));
+
+    // 改为卖出；状态被重置，需要重新积累三根上涨的 Bar
+    std::fs::write(&path, RISING.replace("\"buy\"", "\"sell\"")).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(2)).unwrap();
+    let reloaded = reloads.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(reloaded.path, path.display().to_string());
+
+    bus.publish(BarBuilder::new().price(102.0).build()).await.unwrap();
+    assert!(matches!(orders.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    for close in [103.0, 104.0] {
+        bus.publish(BarBuilder::new().price(close).build()).await.unwrap();
+    }
+    assert_eq!(orders.recv_timeout(TIMEOUT).await.unwrap().side, OrderSide::Sell);
+
+    for handle in handles {
+        handle.abort();
+    }
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,106 @@
+// tests/sharded.rs
+
+//! # 分片总线测试
+//!
+//! `ShardedMessageBus` 把每种消息类型固定路由到一个分片：发布与订阅经同一分片往返，
+//! `shard_index` 稳定且小于分片数，不同分片上的类型互不可见；
+//! 以 `MessageBusTrait` 为泛型参数的代码可以用它替换 `MessageBus`。
+
+use message_bus::bus::{MessageBus, MessageBusTrait, ReceiverExt, RecvTimeout};
+use message_bus::message::Message;
+use message_bus::sharded::ShardedMessageBus;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_millis(200);
+
+/// 以常量参数区分的一组消息类型，`TypeId` 各不相同，用于找出落在不同分片上的类型。
+#[derive(Clone, Debug, PartialEq)]
+struct Probe<const K: usize>(u32);
+impl<const K: usize> Message for Probe<K> {}
+
+/// 前 8 种 `Probe` 所在分片的下标。
+fn probe_indices<const N: usize>() -> [usize; 8] {
+    [
+        ShardedMessageBus::<N>::shard_index::<Probe<0>>(),
+        ShardedMessageBus::<N>::shard_index::<Probe<1>>(),
+        ShardedMessageBus::<N>::shard_index::<Probe<2>>(),
+        ShardedMessageBus::<N>::shard_index::<Probe<3>>(),
+        ShardedMessageBus::<N>::shard_index::<Probe<4>>(),
+        ShardedMessageBus::<N>::shard_index::<Probe<5>>(),
+        ShardedMessageBus::<N>::shard_index::<Probe<6>>(),
+        ShardedMessageBus::<N>::shard_index::<Probe<7>>(),
+    ]
+}
+
+#[tokio::test]
+async fn publish_and_subscribe_round_trip_through_one_shard() {
+    let bus = ShardedMessageBus::<4>::new(16);
+    let mut rx = bus.subscribe::<Probe<0>>().await;
+    assert_eq!(bus.subscriber_count::<Probe<0>>().await, 1);
+
+    assert_eq!(bus.publish(Probe::<0>(7)).await.unwrap(), 1);
+    assert_eq!(rx.recv_timeout(TIMEOUT).await.unwrap(), Probe(7));
+
+    // 消息只经过该类型所在的分片
+    let index = ShardedMessageBus::<4>::shard_index::<Probe<0>>();
+    for (i, shard) in bus.shards().iter().enumerate() {
+        assert_eq!(shard.publish_count(), u64::from(i == index));
+    }
+    assert_eq!(bus.publish_count(), 1);
+    assert_eq!(bus.shard::<Probe<0>>().subscriber_count::<Probe<0>>().await, 1);
+}
+
+#[test]
+fn shard_index_is_stable_and_in_range() {
+    fn check<const N: usize>() {
+        let first = probe_indices::<N>();
+        assert!(first.iter().all(|&index| index < N), "{:?} out of range for {} shards", first, N);
+        assert_eq!(probe_indices::<N>(), first);
+    }
+    check::<1>();
+    check::<3>();
+    check::<4>();
+    check::<8>();
+    assert_eq!(probe_indices::<1>(), [0; 8]);
+}
+
+#[tokio::test]
+async fn types_on_different_shards_do_not_see_each_other() {
+    // 找出与 `Probe<0>` 不在同一分片上的类型
+    let indices = probe_indices::<4>();
+    let other = (1..indices.len()).find(|&k| indices[k] != indices[0]).expect("all probes hashed to one shard");
+    let bus = ShardedMessageBus::<4>::new(16);
+    let shard_a = &bus.shards()[indices[0]];
+    let shard_b = &bus.shards()[indices[other]];
+
+    let mut rx_a = bus.subscribe::<Probe<0>>().await;
+    assert_eq!(bus.publish(Probe::<0>(1)).await.unwrap(), 1);
+    assert_eq!(rx_a.recv_timeout(TIMEOUT).await.unwrap(), Probe(1));
+
+    // `Probe<0>` 的通道只存在于它自己的分片上，另一个分片既没有发布也没有订阅者
+    assert_eq!(shard_b.publish_count(), 0);
+    assert_eq!(shard_b.subscriber_count::<Probe<0>>().await, 0);
+    assert_eq!(shard_a.subscriber_count::<Probe<0>>().await, 1);
+
+    // 直接发布到另一个分片的同类型消息不会到达经分片总线订阅的接收者
+    let mut direct = shard_b.subscribe::<Probe<0>>().await;
+    shard_b.publish(Probe::<0>(2)).await.unwrap();
+    assert_eq!(direct.recv_timeout(TIMEOUT).await.unwrap(), Probe(2));
+    assert!(matches!(rx_a.recv_timeout(TIMEOUT).await, Err(RecvTimeout::Timeout)));
+    assert_eq!(shard_a.publish_count(), 1);
+}
+
+/// 只依赖 `MessageBusTrait` 的代码：订阅、发布并返回收到的消息与订阅者数量。
+async fn echo<B: MessageBusTrait>(bus: B, value: u32) -> (Probe<3>, usize) {
+    let mut rx = bus.subscribe::<Probe<3>>().await;
+    let delivered = bus.publish(Probe::<3>(value)).await.unwrap();
+    assert_eq!(delivered, 1);
+    (rx.recv_timeout(TIMEOUT).await.unwrap(), bus.subscriber_count::<Probe<3>>().await)
+}
+
+#[tokio::test]
+async fn sharded_bus_replaces_message_bus_behind_the_trait() {
+    assert_eq!(echo(MessageBus::new(16), 5).await, (Probe(5), 1));
+    assert_eq!(echo(ShardedMessageBus::<4>::new(16), 5).await, (Probe(5), 1));
+    assert_eq!(echo(ShardedMessageBus::<1>::new(16), 5).await, (Probe(5), 1));
+}
@@ -0,0 +1,36 @@
+// tests/shared.rs
+
+//! # 共享发布测试
+//!
+//! 验证 `publish_shared` 发布的消息在所有 `subscribe_shared` 订阅者之间共享同一份分配，
+//! 且共享通道与按值通道互不相通。
+
+use message_bus::bus::MessageBus;
+use message_bus::message::TradingHalted;
+use std::sync::Arc;
+
+fn halted() -> TradingHalted {
+    TradingHalted { symbol: None, reason: "maintenance".to_string() }
+}
+
+#[tokio::test]
+async fn shared_subscribers_receive_the_same_allocation() {
+    let bus = MessageBus::new(8);
+    let mut first = bus.subscribe_shared::<TradingHalted>().await;
+    let mut second = bus.subscribe_shared::<TradingHalted>().await;
+    let mut by_value = bus.subscribe::<TradingHalted>().await;
+
+    let msg = Arc::new(halted());
+    assert_eq!(bus.publish_shared(msg.clone()).await.unwrap(), 2);
+
+    let a = first.recv().await.unwrap();
+    let b = second.recv().await.unwrap();
+    assert!(Arc::ptr_eq(&a, &msg));
+    assert!(Arc::ptr_eq(&b, &msg));
+    assert!(by_value.try_recv().is_err());
+
+    // 按值发布也不会送达共享订阅者
+    bus.publish(halted()).await.unwrap();
+    assert_eq!(by_value.try_recv().unwrap().reason, "maintenance");
+    assert!(first.try_recv().is_err());
+}
@@ -0,0 +1,190 @@
+// tests/shutdown_order.rs
+
+//! # 停止顺序测试
+//!
+//! `ActorSystemHandle::shutdown` 按依赖顺序逐个停止 Actor：数据源 → 策略 → 执行 → 记录者。
+//! 数据源在执行引擎收到停止信号之前已经停止发布，下游排空缓冲后退出，没有成交丢失。
+
+use message_bus::actor::{Actor, ActorContext};
+use message_bus::bus::MessageBus;
+use message_bus::message::{Bar, FillEvent, OrderRequest, OrderSide, OrderType};
+use message_bus::system::ActorSystemBuilder;
+use message_bus::testkit::BarBuilder;
+use std::any::TypeId;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+fn order(bar: &Bar) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: bar.symbol.clone(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: bar.close,
+        quantity: 1.0,
+        trigger_price: None,
+    }
+}
+
+fn fill(order: &OrderRequest) -> FillEvent {
+    FillEvent {
+        order_id: order.id,
+        symbol: order.symbol.clone(),
+        side: order.side.clone(),
+        price: order.price,
+        quantity: order.quantity,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.0,
+        commission_currency: String::new(),
+        liquidity: Default::default(),
+        ts_event: 0,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+/// 每毫秒发布一根 `Bar`，直到收到停止信号。
+#[derive(Default)]
+struct DataSource {
+    published: AtomicU64,
+    stopped: AtomicBool,
+}
+
+#[async_trait::async_trait]
+impl Actor for DataSource {
+    async fn start(self: Arc<Self>, _actor_name: &str) -> Vec<JoinHandle<()>> {
+        unreachable!("started through run")
+    }
+
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        ctx.ready();
+        let mut ts = 0;
+        loop {
+            tokio::select! {
+                _ = ctx.shutdown_requested() => break,
+                _ = tokio::time::sleep(Duration::from_millis(1)) => {
+                    ts += 1;
+                    ctx.bus().publish(BarBuilder::new().ts_event(ts).build()).await.unwrap();
+                    self.published.fetch_add(1, Ordering::SeqCst);
+                },
+            }
+        }
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+/// 每根 `Bar` 下一张市价单；停止时处理完已缓冲的 `Bar`。
+struct Strategy;
+
+#[async_trait::async_trait]
+impl Actor for Strategy {
+    async fn start(self: Arc<Self>, _actor_name: &str) -> Vec<JoinHandle<()>> {
+        unreachable!("started through run")
+    }
+
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        let mut bars = ctx.subscribe::<Bar>().await;
+        ctx.ready();
+        loop {
+            tokio::select! {
+                _ = ctx.shutdown_requested() => break,
+                Ok(bar) = bars.recv() => {
+                    ctx.bus().publish(order(&bar)).await.unwrap();
+                },
+            }
+        }
+        while let Ok(bar) = bars.try_recv() {
+            ctx.bus().publish(order(&bar)).await.unwrap();
+        }
+    }
+}
+
+/// 每张订单全部成交；记录收到停止信号时数据源是否已经停止。
+struct Execution {
+    data: Arc<DataSource>,
+    data_stopped_when_told: Mutex<Option<bool>>,
+}
+
+#[async_trait::async_trait]
+impl Actor for Execution {
+    async fn start(self: Arc<Self>, _actor_name: &str) -> Vec<JoinHandle<()>> {
+        unreachable!("started through run")
+    }
+
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        let mut orders = ctx.subscribe::<OrderRequest>().await;
+        ctx.ready();
+        loop {
+            tokio::select! {
+                _ = ctx.shutdown_requested() => break,
+                Ok(order) = orders.recv() => {
+                    ctx.bus().publish(fill(&order)).await.unwrap();
+                },
+            }
+        }
+        *self.data_stopped_when_told.lock().unwrap() = Some(self.data.stopped.load(Ordering::SeqCst));
+        while let Ok(order) = orders.try_recv() {
+            ctx.bus().publish(fill(&order)).await.unwrap();
+        }
+    }
+}
+
+/// 统计收到的成交。
+#[derive(Default)]
+struct FillRecorder {
+    fills: AtomicU64,
+}
+
+#[async_trait::async_trait]
+impl Actor for FillRecorder {
+    async fn start(self: Arc<Self>, _actor_name: &str) -> Vec<JoinHandle<()>> {
+        unreachable!("started through run")
+    }
+
+    async fn run(self: Arc<Self>, ctx: ActorContext) {
+        let mut fills = ctx.subscribe::<FillEvent>().await;
+        ctx.ready();
+        loop {
+            tokio::select! {
+                _ = ctx.shutdown_requested() => break,
+                Ok(_) = fills.recv() => {
+                    self.fills.fetch_add(1, Ordering::SeqCst);
+                },
+            }
+        }
+        while fills.try_recv().is_ok() {
+            self.fills.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[tokio::test]
+async fn producers_stop_before_consumers_and_no_fill_is_lost() {
+    let bus = MessageBus::new(1024);
+    let data = Arc::new(DataSource::default());
+    let execution = Arc::new(Execution { data: data.clone(), data_stopped_when_told: Mutex::new(None) });
+    let recorder = Arc::new(FillRecorder::default());
+    let (bar_t, order_t, fill_t) = (TypeId::of::<Bar>(), TypeId::of::<OrderRequest>(), TypeId::of::<FillEvent>());
+    // 故意逆序登记，停止顺序由订阅关系决定
+    let system = ActorSystemBuilder::new(bus)
+        .actor("RECORDER", recorder.clone(), &[], &[fill_t])
+        .actor("EXECUTION", execution.clone(), &[fill_t], &[order_t])
+        .actor("STRATEGY", Arc::new(Strategy), &[order_t], &[bar_t])
+        .actor("DATA", data.clone(), &[bar_t], &[])
+        .build()
+        .unwrap();
+    assert_eq!(system.shutdown_order(), ["DATA", "STRATEGY", "EXECUTION", "RECORDER"]);
+
+    let handle = system.start().await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    handle.shutdown().await;
+
+    let published = data.published.load(Ordering::SeqCst);
+    assert!(published > 0);
+    assert_eq!(*execution.data_stopped_when_told.lock().unwrap(), Some(true));
+    assert_eq!(recorder.fills.load(Ordering::SeqCst), published);
+}
@@ -0,0 +1,75 @@
+// tests/signal_converter.rs
+
+//! # 信号转订单测试
+//!
+//! `SignalOrderConverter` 不关心信号来源：任何 `source` 的 `Signal` 都转换为同方向的市价 `OrderRequest`，
+//! 数量为 `base_quantity × strength`；强度不为正的信号与其他 symbol 的信号被忽略。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::{OrderRequest, OrderSide, OrderType, Signal};
+use message_bus::strategy::SignalOrderConverter;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const QUIET: Duration = Duration::from_millis(100);
+
+fn signal(source: &str, symbol: &str, direction: OrderSide, strength: f64) -> Signal {
+    Signal {
+        id: Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        direction,
+        strength,
+        price: 101.5,
+        ts_event: 1,
+        source: source.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn signals_from_any_source_become_orders_scaled_by_strength() {
+    let bus = MessageBus::new(64);
+    let mut orders = bus.subscribe::<OrderRequest>().await;
+    let converter = Arc::new(SignalOrderConverter::new(bus.clone(), "BTC-USD".to_string()).with_base_quantity(4.0));
+    let handles = converter.clone().start("CONVERTER").await;
+
+    for (source, direction, strength, quantity) in
+        [("orderflow", OrderSide::Buy, 0.5, 2.0), ("ml_model", OrderSide::Sell, 0.25, 1.0), ("manual", OrderSide::Buy, 1.5, 6.0)]
+    {
+        bus.publish(signal(source, "BTC-USD", direction.clone(), strength)).await.unwrap();
+        let order = orders.recv_timeout(TIMEOUT).await.unwrap();
+        assert_eq!(order.symbol, "BTC-USD");
+        assert_eq!(order.side, direction, "{} signal", source);
+        assert_eq!(order.order_type, OrderType::Market);
+        assert_eq!(order.price, 101.5);
+        assert_eq!(order.quantity, quantity, "{} signal", source);
+    }
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn non_positive_strength_and_other_symbols_are_dropped() {
+    let bus = MessageBus::new(64);
+    let mut orders = bus.subscribe::<OrderRequest>().await;
+    let converter = Arc::new(SignalOrderConverter::new(bus.clone(), "BTC-USD".to_string()).with_base_quantity(4.0));
+    let handles = converter.clone().start("CONVERTER").await;
+
+    bus.publish(signal("orderflow", "BTC-USD", OrderSide::Buy, 0.0)).await.unwrap();
+    bus.publish(signal("orderflow", "BTC-USD", OrderSide::Sell, -0.5)).await.unwrap();
+    bus.publish(signal("orderflow", "ETH-USD", OrderSide::Buy, 1.0)).await.unwrap();
+    assert!(matches!(orders.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    // 之后的有效信号照常转换
+    bus.publish(signal("orderflow", "BTC-USD", OrderSide::Buy, 1.0)).await.unwrap();
+    assert_eq!(orders.recv_timeout(TIMEOUT).await.unwrap().quantity, 4.0);
+    assert!(matches!(orders.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,113 @@
+// tests/simulation.rs
+
+//! # 模拟驱动测试
+//!
+//! 同样的行情在 `SimulationDriver` 上运行两次，带种子的延迟抖动下策略与执行引擎产生完全相同的成交序列；
+//! 每个事件引发的级联消息（包括中间让出执行权几次的处理）都在虚拟时钟前进到下一个事件之前处理完毕。
+
+use message_bus::actor::Actor;
+use message_bus::bus::MessageBus;
+use message_bus::clock::Clock;
+use message_bus::execution::{LatencyModel, SimulatedExecutionEngine};
+use message_bus::message::{Bar, FillEvent, Message, OrderSide, WarmupComplete};
+use message_bus::simulation::SimulationDriver;
+use message_bus::strategy::SimpleTrendFollower;
+use message_bus::testkit::BarBuilder;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const SYMBOL: &str = "BTC-USD";
+const SECOND: u64 = 1_000_000_000;
+/// 级联的深度：每根 `Bar` 之后依次发布的 `Hop` 数量。
+const DEPTH: u32 = 10;
+
+fn bars() -> Vec<Bar> {
+    [100.0, 103.0, 101.0, 104.0, 105.0, 99.0, 106.0]
+        .into_iter()
+        .enumerate()
+        .map(|(i, close)| BarBuilder::new().ts_event((i as u64 + 1) * SECOND).price(close).volume(10.0).build())
+        .collect()
+}
+
+/// 成交中与随机生成的订单 ID 无关的部分。
+type FillKey = (OrderSide, u64, u64, u64);
+
+/// 运行一次模拟，返回按发布顺序的成交。
+async fn run_once() -> Vec<FillKey> {
+    let bus = MessageBus::new(1024).with_message_store::<FillEvent>(1000);
+    let mut driver = SimulationDriver::new(bus.clone());
+    let latency = LatencyModel {
+        ack_latency: Duration::from_millis(1),
+        fill_latency: Duration::from_millis(2),
+        jitter: Duration::from_millis(5),
+    };
+    let engine = SimulatedExecutionEngine::new(bus.clone()).with_clock(driver.clock()).with_latency(latency).with_seed(7);
+    Arc::new(engine).start("EXECUTION").await;
+    Arc::new(SimpleTrendFollower::new(bus.clone(), SYMBOL.to_string())).start("STRATEGY").await;
+
+    driver.schedule(0, WarmupComplete { symbol: SYMBOL.to_string(), bars_seen: 0, ts_event: 0 });
+    driver.schedule_bars(bars());
+    assert_eq!(driver.run().await, bars().len() + 1);
+    bus.recent::<FillEvent>()
+        .into_iter()
+        .map(|envelope| {
+            let fill = envelope.message;
+            (fill.side, fill.price.to_bits(), fill.quantity.to_bits(), fill.ts_event)
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn two_runs_produce_identical_fills() {
+    let first = run_once().await;
+    let second = run_once().await;
+    assert!(!first.is_empty());
+    assert_eq!(first, second);
+}
+
+/// 级联中的一跳。
+#[derive(Clone, Debug)]
+struct Hop {
+    bar_ts: u64,
+    depth: u32,
+}
+impl Message for Hop {}
+
+#[tokio::test]
+async fn cascades_finish_before_the_clock_advances() {
+    let bus = MessageBus::new(1024);
+    let mut driver = SimulationDriver::new(bus.clone());
+    let clock = driver.clock();
+    // 每一跳：(所属 Bar 的时间, 深度, 处理时的虚拟时间)
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let mut bar_rx = bus.subscribe::<Bar>().await;
+    let source = bus.clone();
+    tokio::spawn(async move {
+        while let Ok(bar) = bar_rx.recv().await {
+            source.publish(Hop { bar_ts: bar.ts_event, depth: 0 }).await.unwrap();
+        }
+    });
+    let mut hop_rx = bus.subscribe::<Hop>().await;
+    let relay = bus.clone();
+    let relay_log = log.clone();
+    tokio::spawn(async move {
+        while let Ok(hop) = hop_rx.recv().await {
+            relay_log.lock().unwrap().push((hop.bar_ts, hop.depth, clock.now_nanos()));
+            // 处理中让出两次执行权，仍在静止判定的容忍范围内
+            tokio::task::yield_now().await;
+            tokio::task::yield_now().await;
+            if hop.depth < DEPTH {
+                relay.publish(Hop { depth: hop.depth + 1, ..hop }).await.unwrap();
+            }
+        }
+    });
+
+    driver.schedule_bars(bars());
+    driver.run().await;
+
+    let log = log.lock().unwrap();
+    let expected: Vec<(u64, u32, u64)> =
+        bars().iter().flat_map(|bar| (0..=DEPTH).map(move |depth| (bar.ts_event, depth, bar.ts_event))).collect();
+    assert_eq!(*log, expected);
+}
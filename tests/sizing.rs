@@ -0,0 +1,139 @@
+// tests/sizing.rs
+
+//! # 仓位计算测试
+//!
+//! `PositionSizer` 按 `权益 × max_position_pct / 参考价 × 强度` 计算订单数量；
+//! 尚未收到 `PortfolioSnapshot`、参考价不为正或强度不为正时忽略信号，
+//! 新的快照改变之后订单的数量。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, Receiver, ReceiverExt, RecvTimeout};
+use message_bus::message::{OrderRequest, OrderSide, OrderType, PortfolioSnapshot, Signal};
+use message_bus::sizing::PositionSizer;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const QUIET: Duration = Duration::from_millis(100);
+
+fn signal(strength: f64, price: f64) -> Signal {
+    Signal {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        direction: OrderSide::Buy,
+        strength,
+        price,
+        ts_event: 1,
+        source: "trend".to_string(),
+    }
+}
+
+fn snapshot(total_equity: f64) -> PortfolioSnapshot {
+    PortfolioSnapshot { total_equity, net_pnl: total_equity - 10_000.0, ts: 1 }
+}
+
+/// 启动一个占用 10% 权益的 `PositionSizer`，返回它与订单的订阅者。
+async fn sizer(bus: &MessageBus) -> (Arc<PositionSizer>, Vec<JoinHandle<()>>, Receiver<OrderRequest>) {
+    let orders = bus.subscribe::<OrderRequest>().await;
+    let sizer = Arc::new(PositionSizer::new(bus.clone(), 0.1));
+    let handles = sizer.clone().start("SIZER").await;
+    (sizer, handles, orders)
+}
+
+/// 发布快照并等待 `PositionSizer` 保存它。
+async fn publish_snapshot(bus: &MessageBus, sizer: &PositionSizer, total_equity: f64) {
+    bus.publish(snapshot(total_equity)).await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while sizer.current_portfolio().map(|s| s.total_equity) != Some(total_equity) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("snapshot was not stored");
+}
+
+#[tokio::test]
+async fn quantity_scales_with_equity_price_and_strength() {
+    let bus = MessageBus::new(64);
+    let (sizer, handles, mut orders) = sizer(&bus).await;
+    publish_snapshot(&bus, &sizer, 10_000.0).await;
+
+    // 10_000 × 0.1 / 50 × 0.5 = 10
+    assert_eq!(sizer.quantity_for(&signal(0.5, 50.0)), Some(10.0));
+    assert_eq!(sizer.quantity_for(&signal(1.0, 200.0)), Some(5.0));
+
+    let signal = signal(0.5, 50.0);
+    bus.publish(signal.clone()).await.unwrap();
+    let order = orders.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(order.quantity, 10.0);
+    assert_eq!(order.price, 50.0);
+    assert_eq!(order.side, OrderSide::Buy);
+    assert_eq!(order.symbol, signal.symbol);
+    assert_eq!(order.order_type, OrderType::Market);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn signals_are_ignored_before_the_first_snapshot() {
+    let bus = MessageBus::new(64);
+    let (sizer, handles, mut orders) = sizer(&bus).await;
+
+    assert!(sizer.current_portfolio().is_none());
+    assert_eq!(sizer.quantity_for(&signal(1.0, 50.0)), None);
+    bus.publish(signal(1.0, 50.0)).await.unwrap();
+    assert!(matches!(orders.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn non_positive_price_or_strength_is_ignored() {
+    let bus = MessageBus::new(64);
+    let (sizer, handles, mut orders) = sizer(&bus).await;
+    publish_snapshot(&bus, &sizer, 10_000.0).await;
+
+    for (strength, price) in [(1.0, 0.0), (1.0, -50.0), (0.0, 50.0), (-0.5, 50.0)] {
+        assert_eq!(sizer.quantity_for(&signal(strength, price)), None, "strength {} price {}", strength, price);
+        bus.publish(signal(strength, price)).await.unwrap();
+    }
+    assert!(matches!(orders.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    // 权益耗尽时算出的数量不为正，同样忽略
+    publish_snapshot(&bus, &sizer, 0.0).await;
+    assert_eq!(sizer.quantity_for(&signal(1.0, 50.0)), None);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn a_new_snapshot_resizes_the_next_order() {
+    let bus = MessageBus::new(64);
+    let (sizer, handles, mut orders) = sizer(&bus).await;
+
+    publish_snapshot(&bus, &sizer, 10_000.0).await;
+    bus.publish(signal(1.0, 100.0)).await.unwrap();
+    assert_eq!(orders.recv_timeout(TIMEOUT).await.unwrap().quantity, 10.0);
+
+    // 组合盈利后权益增加，下一笔订单随之放大
+    publish_snapshot(&bus, &sizer, 15_000.0).await;
+    bus.publish(signal(1.0, 100.0)).await.unwrap();
+    assert_eq!(orders.recv_timeout(TIMEOUT).await.unwrap().quantity, 15.0);
+
+    // 亏损后缩小
+    publish_snapshot(&bus, &sizer, 5_000.0).await;
+    bus.publish(signal(1.0, 100.0)).await.unwrap();
+    assert_eq!(orders.recv_timeout(TIMEOUT).await.unwrap().quantity, 5.0);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,82 @@
+// tests/startup.rs
+
+//! # 启动屏障测试
+//!
+//! 每个克隆出的句柄都调用 `ready` 之后 `await_all_ready` 才放行；超时时报告仍未就绪的数量与已就绪的 Actor；
+//! 未就绪就被丢弃的句柄不再阻塞。放行之后发布的第一条消息已有订阅者接收。
+
+use message_bus::actor::Actor;
+use message_bus::bus::MessageBus;
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::message::{Bar, DEFAULT_BAR_TIMEFRAME};
+use message_bus::startup::{StartupBarrier, WaitError};
+use message_bus::strategy::SimpleTrendFollower;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const SHORT: Duration = Duration::from_millis(50);
+
+#[tokio::test]
+async fn releases_only_after_every_handle_is_ready() {
+    let (handle, wait) = StartupBarrier::new();
+    let data = handle.clone();
+    let strategy = handle.clone();
+    drop(handle);
+
+    data.ready("DATA");
+    assert_eq!(
+        wait.await_all_ready(SHORT).await,
+        Err(WaitError::Timeout { pending: 1, ready: vec!["DATA".to_string()] })
+    );
+
+    // 另一个任务稍后就绪，等待中的 await_all_ready 随之放行
+    tokio::spawn(async move {
+        tokio::time::sleep(SHORT).await;
+        strategy.ready("STRATEGY");
+    });
+    wait.await_all_ready(TIMEOUT).await.unwrap();
+}
+
+#[tokio::test]
+async fn dropped_handle_does_not_block_the_barrier() {
+    let (handle, wait) = StartupBarrier::new();
+    let unused = handle.clone();
+    handle.ready("EXECUTION");
+    assert!(wait.await_all_ready(SHORT).await.is_err());
+
+    drop(unused);
+    wait.await_all_ready(TIMEOUT).await.unwrap();
+}
+
+#[tokio::test]
+async fn first_bar_after_release_reaches_every_subscriber() {
+    let bus = MessageBus::new(64);
+    let (handle, wait) = StartupBarrier::new();
+    let strategy = SimpleTrendFollower::new(bus.clone(), "BTC-USD".to_string()).with_startup_barrier(handle.clone());
+    let engine = SimulatedExecutionEngine::new(bus.clone()).with_startup_barrier(handle.clone());
+    drop(handle);
+
+    let mut handles = Arc::new(strategy).start("STRATEGY").await;
+    handles.extend(Arc::new(engine).start("EXECUTION").await);
+    wait.await_all_ready(TIMEOUT).await.unwrap();
+
+    let bar = Bar {
+        id: Uuid::new_v4(),
+        ts_event: 1,
+        symbol: "BTC-USD".to_string(),
+        open: 100.0,
+        high: 100.0,
+        low: 100.0,
+        close: 100.0,
+        volume: 1.0,
+        timeframe: DEFAULT_BAR_TIMEFRAME,
+    };
+    // 策略与执行引擎都已订阅 Bar
+    assert_eq!(bus.publish(bar).await.unwrap(), 2);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,172 @@
+// tests/status.rs
+
+//! # 状态端点测试
+//!
+//! 用 reqwest 访问 `StatusServer`：被关注 Actor 的心跳与行情新鲜程度决定 `/health`，
+//! `/actors`、`/bus`、`/positions` 与 `/orders` 反映总线上的消息，停止信号之后服务不再接受连接。
+//! 需要启用 `status` feature：`cargo test --features status --test status`。
+
+#![cfg(feature = "status")]
+
+use message_bus::actor::{spawn_run, ActorContext, ShutdownToken};
+use message_bus::bus::MessageBus;
+use message_bus::message::{OrderStatus, OrderStatusChanged, PositionUpdate};
+use message_bus::status::{ActorState, ActorStatus, BusTypeStatus, HealthReport, HealthState, StatusServer};
+use message_bus::testkit::BarBuilder;
+use serde::de::DeserializeOwned;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(10);
+
+struct Running {
+    base: String,
+    shutdown: ShutdownToken,
+    handle: JoinHandle<()>,
+}
+
+async fn start(server: StatusServer, bus: &MessageBus) -> Running {
+    let server = Arc::new(
+        server.with_addr(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).with_sample_interval(SAMPLE_INTERVAL),
+    );
+    let ctx = ActorContext::new(bus.clone(), "STATUS");
+    let shutdown = ctx.shutdown_token().clone();
+    let handle = spawn_run(server.clone(), ctx, &Handle::current()).await;
+    let addr = server.local_addr().expect("status server is listening");
+    Running { base: format!("http://{}", addr), shutdown, handle }
+}
+
+async fn get<T: DeserializeOwned>(running: &Running, path: &str) -> (u16, T) {
+    let response = reqwest::get(format!("{}{}", running.base, path)).await.unwrap();
+    let status = response.status().as_u16();
+    (status, response.json().await.unwrap())
+}
+
+/// 反复请求 `path`，直到响应满足 `done`，超时则失败。
+async fn poll<T: DeserializeOwned>(running: &Running, path: &str, done: impl Fn(u16, &T) -> bool) -> T {
+    let deadline = tokio::time::Instant::now() + TIMEOUT;
+    loop {
+        let (status, body) = get::<T>(running, path).await;
+        if done(status, &body) {
+            return body;
+        }
+        assert!(tokio::time::Instant::now() < deadline, "{} did not reach the expected state", path);
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+    }
+}
+
+fn order_status(order_id: Uuid, status: OrderStatus, ts_event: u64) -> OrderStatusChanged {
+    OrderStatusChanged {
+        order_id,
+        symbol: "BTC-USD".to_string(),
+        previous: None,
+        status,
+        filled_qty: 0.0,
+        leaves_qty: 1.0,
+        ts_event,
+    }
+}
+
+#[tokio::test]
+async fn health_follows_heartbeats_and_data_staleness() {
+    let bus = MessageBus::new(64);
+    let server = StatusServer::new(bus.clone())
+        .watch_actor("STRATEGY")
+        .with_heartbeat_timeout(Duration::from_millis(150))
+        .with_stale_data_after(Duration::from_millis(150));
+    let running = start(server, &bus).await;
+
+    bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap();
+    bus.actor_metrics().record("STRATEGY", Duration::from_micros(5));
+    let healthy = poll::<HealthReport>(&running, "/health", |code, _| code == 200).await;
+    assert_eq!(healthy, HealthReport { status: HealthState::Ok, issues: vec![] });
+
+    // 两者都不再更新后变为 degraded，原因逐条列出
+    let degraded = poll::<HealthReport>(&running, "/health", |_, report| report.issues.len() == 2).await;
+    assert_eq!(degraded.status, HealthState::Degraded);
+    assert!(degraded.issues.iter().any(|issue| issue.starts_with("actor STRATEGY")), "{:?}", degraded.issues);
+    assert!(degraded.issues.iter().any(|issue| issue.contains("BTC-USD")), "{:?}", degraded.issues);
+    let (code, _) = get::<HealthReport>(&running, "/health").await;
+    assert_eq!(code, 503);
+
+    // 恢复心跳与行情后回到 ok
+    bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap();
+    bus.actor_metrics().record("STRATEGY", Duration::from_micros(5));
+    poll::<HealthReport>(&running, "/health", |code, report| code == 200 && report.status == HealthState::Ok).await;
+}
+
+#[tokio::test]
+async fn actors_and_bus_reflect_sampled_metrics() {
+    let bus = MessageBus::new(64);
+    let running = start(StatusServer::new(bus.clone()).watch_actor("RISK"), &bus).await;
+
+    bus.actor_metrics().record("STRATEGY", Duration::from_micros(5));
+    bus.actor_metrics().record("STRATEGY", Duration::from_micros(5));
+    let actors = poll::<Vec<ActorStatus>>(&running, "/actors", |_, actors| {
+        actors.iter().any(|actor| actor.name == "STRATEGY" && actor.processed == 2)
+    })
+    .await;
+    // 被关注但从未记录过的 Actor 同样列出
+    let names: Vec<&str> = actors.iter().map(|actor| actor.name.as_str()).collect();
+    assert_eq!(names, ["RISK", "STRATEGY"]);
+    assert!(actors.iter().all(|actor| actor.state == ActorState::Active));
+
+    let _rx = bus.subscribe::<PositionUpdate>().await;
+    bus.publish(BarBuilder::new().symbol("ETH-USD").ts_event(1).build()).await.unwrap();
+    let types = poll::<Vec<BusTypeStatus>>(&running, "/bus", |_, types| {
+        types.iter().any(|status| status.type_name.ends_with("::Bar") && status.published == 1)
+    })
+    .await;
+    let bars = types.iter().find(|status| status.type_name.ends_with("::Bar")).unwrap();
+    assert_eq!((bars.channels, bars.subscribers, bars.dropped), (1, 1, 0));
+    // 状态服务自己的订阅加上测试中的一个
+    let positions = types.iter().find(|status| status.type_name.ends_with("::PositionUpdate")).unwrap();
+    assert_eq!(positions.subscribers, 2);
+}
+
+#[tokio::test]
+async fn positions_and_open_orders_are_cached_from_the_bus() {
+    let bus = MessageBus::new(64);
+    let running = start(StatusServer::new(bus.clone()), &bus).await;
+
+    for (symbol, net_position) in [("ETH-USD", -2.0), ("BTC-USD", 1.0), ("BTC-USD", 3.0)] {
+        bus.publish(PositionUpdate { symbol: symbol.to_string(), net_position, avg_price: 100.0, ts: 1 })
+            .await
+            .unwrap();
+    }
+    let positions = poll::<Vec<PositionUpdate>>(&running, "/positions", |_, positions| {
+        positions.iter().any(|position| position.net_position == 3.0)
+    })
+    .await;
+    let summary: Vec<(&str, f64)> =
+        positions.iter().map(|position| (position.symbol.as_str(), position.net_position)).collect();
+    assert_eq!(summary, [("BTC-USD", 3.0), ("ETH-USD", -2.0)]);
+
+    let (working, filled) = (Uuid::new_v4(), Uuid::new_v4());
+    bus.publish(order_status(filled, OrderStatus::Accepted, 1)).await.unwrap();
+    bus.publish(order_status(working, OrderStatus::Accepted, 2)).await.unwrap();
+    bus.publish(order_status(working, OrderStatus::PartiallyFilled, 3)).await.unwrap();
+    bus.publish(order_status(filled, OrderStatus::Filled, 4)).await.unwrap();
+    let orders = poll::<Vec<OrderStatusChanged>>(&running, "/orders", |_, orders| {
+        orders.len() == 1 && orders[0].status == OrderStatus::PartiallyFilled
+    })
+    .await;
+    assert_eq!(orders[0].order_id, working);
+}
+
+#[tokio::test]
+async fn stops_serving_on_shutdown() {
+    let bus = MessageBus::new(64);
+    let running = start(StatusServer::new(bus.clone()), &bus).await;
+    let (code, _) = get::<Vec<ActorStatus>>(&running, "/actors").await;
+    assert_eq!(code, 200);
+
+    running.shutdown.shutdown();
+    tokio::time::timeout(TIMEOUT, running.handle).await.expect("server stops").unwrap();
+    assert!(reqwest::get(format!("{}/actors", running.base)).await.is_err());
+}
@@ -0,0 +1,213 @@
+// tests/stops.rs
+
+//! # 止损单触发测试
+//!
+//! 买入止损与卖出止损以 100 为中心对称地构造：买入止损价 105 对应卖出止损价 95，
+//! 行情上的价格 `p` 对应 `200 - p`。每个场景对两个方向分别验证触发、跳空成交价与转换后的订单类型。
+//! 模拟执行引擎通过 `ActorTestHarness` 启动，行情由测试直接注入。
+
+use message_bus::execution::{SimulatedExecutionEngine, StopTrigger};
+use message_bus::message::{
+    Bar, FillEvent, Liquidity, OrderAccepted, OrderBookSnapshot, OrderRequest, OrderSide, OrderTriggered, OrderType,
+    PriceLevel, TradeTick,
+};
+use message_bus::testkit::{ActorTestHarness, BarBuilder, TestBus};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const SYMBOL: &str = "BTC-USD";
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+
+/// 把以买入方向描述的价格映射到 `side` 方向。
+fn mirror(side: &OrderSide, price: f64) -> f64 {
+    match side {
+        OrderSide::Buy => price,
+        OrderSide::Sell => 200.0 - price,
+    }
+}
+
+/// 以买入方向描述的 `Bar`：`toward` 是朝触发方向的极值（买入为最高价，卖出为最低价）。
+fn bar(side: &OrderSide, open: f64, toward: f64) -> Bar {
+    let (open, toward) = (mirror(side, open), mirror(side, toward));
+    BarBuilder::new().ohlc(open, open.max(toward), open.min(toward), open).volume(0.0).build()
+}
+
+fn trade(side: &OrderSide, price: f64) -> TradeTick {
+    TradeTick { id: Uuid::new_v4(), symbol: SYMBOL.to_string(), price: mirror(side, price), size: 1.0, ts_event: 0 }
+}
+
+/// 对手价在 `price` 的订单簿快照：买入止损看卖一，卖出止损看买一。
+fn quote(side: &OrderSide, price: f64) -> OrderBookSnapshot {
+    let level = |price: f64| vec![PriceLevel { price, size: 1.0 }];
+    let (bids, asks) = match side {
+        OrderSide::Buy => (level(price - 1.0), level(price)),
+        OrderSide::Sell => (level(200.0 - price), level(201.0 - price)),
+    };
+    OrderBookSnapshot { symbol: SYMBOL.to_string(), bids, asks, ts_event: 0 }
+}
+
+fn stop_market(side: &OrderSide, stop: f64) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: SYMBOL.to_string(),
+        side: side.clone(),
+        order_type: OrderType::StopMarket,
+        price: mirror(side, stop),
+        quantity: 1.0,
+        trigger_price: Some(mirror(side, stop)),
+    }
+}
+
+fn stop_limit(side: &OrderSide, stop: f64, limit: f64) -> OrderRequest {
+    OrderRequest {
+        order_type: OrderType::StopLimit,
+        price: mirror(side, limit),
+        ..stop_market(side, stop)
+    }
+}
+
+async fn start_venue(trigger: StopTrigger) -> ActorTestHarness {
+    let test_bus = TestBus::new(64);
+    let engine = Arc::new(SimulatedExecutionEngine::new(test_bus.bus()).with_stop_trigger(trigger));
+    ActorTestHarness::start(test_bus, engine).await
+}
+
+/// 下单并等待确认，确认后订单才开始挂单等待行情。
+async fn submit(venue: &mut ActorTestHarness, order: &OrderRequest) {
+    venue.send(order.clone()).await;
+    let accepted = venue.expect_message::<OrderAccepted>(TIMEOUT).await;
+    assert_eq!(accepted.order_id, order.id);
+}
+
+/// 发布一条行情后，确认没有触发也没有成交。
+async fn assert_quiet(venue: &mut ActorTestHarness) {
+    venue.expect_no_message::<OrderTriggered>(QUIET).await;
+    venue.expect_no_message::<FillEvent>(Duration::ZERO).await;
+}
+
+const SIDES: [OrderSide; 2] = [OrderSide::Buy, OrderSide::Sell];
+
+#[tokio::test]
+async fn stop_market_triggers_when_the_bar_touches_the_stop() {
+    for side in SIDES {
+        let mut venue = start_venue(StopTrigger::HighLow).await;
+        let order = stop_market(&side, 105.0);
+        submit(&mut venue, &order).await;
+
+        // 未触及触发价
+        venue.send(bar(&side, 100.0, 104.0)).await;
+        assert_quiet(&mut venue).await;
+
+        venue.send(bar(&side, 100.0, 106.0)).await;
+        let triggered = venue.expect_message::<OrderTriggered>(TIMEOUT).await;
+        assert_eq!(triggered.order_id, order.id);
+        assert_eq!(triggered.order_type, OrderType::Market);
+        assert_eq!(triggered.reference_price, mirror(&side, 105.0));
+        let fill = venue.expect_message::<FillEvent>(TIMEOUT).await;
+        assert_eq!(fill.order_id, order.id);
+        assert_eq!(fill.price, mirror(&side, 105.0), "{:?} stop", side);
+        assert_eq!(fill.liquidity, Liquidity::Taker);
+        assert!(fill.is_final);
+    }
+}
+
+#[tokio::test]
+async fn stop_market_gapping_through_the_stop_fills_at_the_open() {
+    for side in SIDES {
+        let mut venue = start_venue(StopTrigger::HighLow).await;
+        let order = stop_market(&side, 105.0);
+        submit(&mut venue, &order).await;
+
+        venue.send(bar(&side, 110.0, 112.0)).await;
+        let triggered = venue.expect_message::<OrderTriggered>(TIMEOUT).await;
+        assert_eq!(triggered.trigger_price, mirror(&side, 105.0));
+        assert_eq!(triggered.reference_price, mirror(&side, 110.0));
+        assert_eq!(venue.expect_message::<FillEvent>(TIMEOUT).await.price, mirror(&side, 110.0), "{:?} stop", side);
+    }
+}
+
+#[tokio::test]
+async fn stop_market_on_last_trade_fills_at_the_trade_price() {
+    for side in SIDES {
+        let mut venue = start_venue(StopTrigger::LastTrade).await;
+        let order = stop_market(&side, 105.0);
+        submit(&mut venue, &order).await;
+
+        // 按成交触发时忽略 Bar
+        venue.send(bar(&side, 100.0, 120.0)).await;
+        venue.send(trade(&side, 104.0)).await;
+        assert_quiet(&mut venue).await;
+
+        venue.send(trade(&side, 107.0)).await;
+        assert_eq!(venue.expect_message::<OrderTriggered>(TIMEOUT).await.reference_price, mirror(&side, 107.0));
+        assert_eq!(venue.expect_message::<FillEvent>(TIMEOUT).await.price, mirror(&side, 107.0), "{:?} stop", side);
+    }
+}
+
+#[tokio::test]
+async fn stop_on_bid_ask_uses_the_opposite_side_and_falls_back_to_bars() {
+    for side in SIDES {
+        // 收到报价前按 Bar 的最高/最低价触发
+        let mut venue = start_venue(StopTrigger::BidAsk).await;
+        let order = stop_market(&side, 105.0);
+        submit(&mut venue, &order).await;
+        venue.send(bar(&side, 100.0, 106.0)).await;
+        assert_eq!(venue.expect_message::<FillEvent>(TIMEOUT).await.price, mirror(&side, 105.0), "{:?} stop", side);
+
+        // 收到报价后只看对手价
+        let mut venue = start_venue(StopTrigger::BidAsk).await;
+        let order = stop_market(&side, 105.0);
+        submit(&mut venue, &order).await;
+        venue.send(quote(&side, 104.0)).await;
+        assert_quiet(&mut venue).await;
+        venue.send(bar(&side, 100.0, 120.0)).await;
+        assert_quiet(&mut venue).await;
+
+        venue.send(quote(&side, 105.5)).await;
+        assert_eq!(venue.expect_message::<OrderTriggered>(TIMEOUT).await.reference_price, mirror(&side, 105.5));
+        assert_eq!(venue.expect_message::<FillEvent>(TIMEOUT).await.price, mirror(&side, 105.5), "{:?} stop", side);
+    }
+}
+
+#[tokio::test]
+async fn marketable_stop_limit_fills_immediately_as_taker() {
+    for side in SIDES {
+        let mut venue = start_venue(StopTrigger::LastTrade).await;
+        let order = stop_limit(&side, 105.0, 106.0);
+        submit(&mut venue, &order).await;
+
+        venue.send(trade(&side, 105.5)).await;
+        let triggered = venue.expect_message::<OrderTriggered>(TIMEOUT).await;
+        assert_eq!(triggered.order_type, OrderType::Limit);
+        let fill = venue.expect_message::<FillEvent>(TIMEOUT).await;
+        assert_eq!(fill.price, mirror(&side, 105.5), "{:?} stop", side);
+        assert_eq!(fill.liquidity, Liquidity::Taker);
+    }
+}
+
+#[tokio::test]
+async fn stop_limit_gapping_past_the_limit_rests_until_the_limit_trades() {
+    for side in SIDES {
+        let mut venue = start_venue(StopTrigger::HighLow).await;
+        let order = stop_limit(&side, 105.0, 106.0);
+        submit(&mut venue, &order).await;
+
+        // 跳空越过限价：触发但不成交，转为限价单挂单
+        venue.send(bar(&side, 110.0, 112.0)).await;
+        let triggered = venue.expect_message::<OrderTriggered>(TIMEOUT).await;
+        assert_eq!(triggered.order_type, OrderType::Limit);
+        assert_eq!(triggered.reference_price, mirror(&side, 110.0));
+        assert_quiet(&mut venue).await;
+
+        // 回落到限价以内后按限价被动成交
+        let mut retrace = bar(&side, 108.0, 108.0);
+        retrace.high = retrace.high.max(mirror(&side, 105.0));
+        retrace.low = retrace.low.min(mirror(&side, 105.0));
+        venue.send(retrace).await;
+        let fill = venue.expect_message::<FillEvent>(TIMEOUT).await;
+        assert_eq!(fill.price, mirror(&side, 106.0), "{:?} stop", side);
+        assert_eq!(fill.liquidity, Liquidity::Maker);
+    }
+}
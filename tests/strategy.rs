@@ -0,0 +1,166 @@
+// tests/strategy.rs
+
+//! # 趋势策略测试
+//!
+//! 用 `ActorTestHarness` 启动 `SimpleTrendFollower`，注入行情、预热、成交与拒绝消息，
+//! 验证它只在预热后对本 symbol 的信号下单，并正确累计持仓与拒绝数；启用订单流确认后只在买方主导时下单，
+//! 启用移动平均后只在收盘价高于最近几根的均值时下单。
+
+use message_bus::message::{
+    FillEvent, Liquidity, OrderFlowMetric, OrderRejected, OrderRequest, OrderSide, OrderType, RejectReason, WarmupComplete,
+};
+use message_bus::strategy::SimpleTrendFollower;
+use message_bus::testkit::{ActorTestHarness, BarBuilder, TestBus};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const SYMBOL: &str = "BTC-USD";
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+
+fn fill(side: OrderSide, quantity: f64) -> FillEvent {
+    FillEvent {
+        order_id: Uuid::new_v4(),
+        symbol: SYMBOL.to_string(),
+        side,
+        price: 100.0,
+        quantity,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.0,
+        commission_currency: String::new(),
+        liquidity: Liquidity::Taker,
+        ts_event: 0,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+/// 启动策略并等待它处理完预热消息。
+async fn warmed_up() -> (ActorTestHarness, Arc<SimpleTrendFollower>) {
+    let test_bus = TestBus::new(64);
+    let strategy = Arc::new(SimpleTrendFollower::new(test_bus.bus(), SYMBOL.to_string()));
+    let harness = ActorTestHarness::start(test_bus, strategy.clone()).await;
+    harness.send(WarmupComplete { symbol: SYMBOL.to_string(), bars_seen: 3, ts_event: 0 }).await;
+    harness.wait_until(TIMEOUT, || strategy.is_warmed_up()).await;
+    (harness, strategy)
+}
+
+#[tokio::test]
+async fn no_orders_before_warmup() {
+    let test_bus = TestBus::new(64);
+    let strategy = Arc::new(SimpleTrendFollower::new(test_bus.bus(), SYMBOL.to_string()));
+    let mut harness = ActorTestHarness::start(test_bus, strategy).await;
+
+    harness.send(BarBuilder::new().price(110.0).build()).await;
+    harness.expect_no_message::<OrderRequest>(QUIET).await;
+}
+
+#[tokio::test]
+async fn buys_when_close_breaks_the_threshold() {
+    let (mut harness, _strategy) = warmed_up().await;
+
+    harness.send(BarBuilder::new().price(101.0).build()).await;
+    harness.send(BarBuilder::new().price(110.0).build()).await;
+    let order = harness.expect_message::<OrderRequest>(TIMEOUT).await;
+    assert_eq!(order.symbol, SYMBOL);
+    assert_eq!(order.side, OrderSide::Buy);
+    assert_eq!(order.order_type, OrderType::Market);
+    assert_eq!(order.price, 110.0);
+    // 101 低于阈值，不应产生第二笔订单
+    harness.expect_no_message::<OrderRequest>(QUIET).await;
+    assert_eq!(harness.bus().published::<OrderRequest>().len(), 1);
+}
+
+#[tokio::test]
+async fn ignores_bars_for_other_symbols() {
+    let (mut harness, _strategy) = warmed_up().await;
+
+    harness.send(BarBuilder::new().symbol("ETH-USD").price(110.0).build()).await;
+    harness.expect_no_message::<OrderRequest>(QUIET).await;
+}
+
+#[tokio::test]
+async fn tracks_position_from_fills_and_counts_rejections() {
+    let (harness, strategy) = warmed_up().await;
+
+    harness.send(fill(OrderSide::Buy, 3.0)).await;
+    harness.send(fill(OrderSide::Sell, 1.0)).await;
+    harness.wait_until(TIMEOUT, || strategy.position() == 2.0).await;
+
+    let rejected = OrderRejected {
+        order_id: Uuid::new_v4(),
+        symbol: SYMBOL.to_string(),
+        reason: RejectReason::InvalidQuantity,
+        detail: "quantity must be positive".to_string(),
+    };
+    harness.send(rejected).await;
+    harness.wait_until(TIMEOUT, || strategy.rejected_orders() == 1).await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "published so far")]
+async fn expect_message_failure_lists_what_was_published() {
+    let test_bus = TestBus::new(64);
+    let strategy = Arc::new(SimpleTrendFollower::new(test_bus.bus(), SYMBOL.to_string()));
+    let mut harness = ActorTestHarness::start(test_bus, strategy).await;
+
+    harness.send(BarBuilder::new().price(110.0).build()).await;
+    harness.expect_message::<OrderRequest>(QUIET).await;
+}
+
+#[tokio::test]
+async fn order_flow_signal_requires_buy_side_imbalance() {
+    let test_bus = TestBus::new(64);
+    let strategy =
+        Arc::new(SimpleTrendFollower::new(test_bus.bus(), SYMBOL.to_string()).with_order_flow_signal(0.2));
+    let mut harness = ActorTestHarness::start(test_bus, strategy.clone()).await;
+    harness.send(WarmupComplete { symbol: SYMBOL.to_string(), bars_seen: 3, ts_event: 0 }).await;
+    harness.wait_until(TIMEOUT, || strategy.is_warmed_up()).await;
+
+    let flow = |imbalance: f64| OrderFlowMetric {
+        symbol: SYMBOL.to_string(),
+        imbalance,
+        buy_volume: 1.0 + imbalance,
+        sell_volume: 1.0 - imbalance,
+        window_end_ts: 0,
+    };
+    // 尚未收到订单流
+    harness.send(BarBuilder::new().price(110.0).build()).await;
+    harness.expect_no_message::<OrderRequest>(QUIET).await;
+
+    // 卖方主导，价格突破不可信
+    harness.send(flow(-0.4)).await;
+    harness.send(BarBuilder::new().price(110.0).build()).await;
+    harness.expect_no_message::<OrderRequest>(QUIET).await;
+
+    harness.send(flow(0.4)).await;
+    // 等待订单流被处理后再发送 Bar
+    tokio::time::sleep(QUIET).await;
+    harness.send(BarBuilder::new().price(110.0).build()).await;
+    let order = harness.expect_message::<OrderRequest>(TIMEOUT).await;
+    assert_eq!(order.side, OrderSide::Buy);
+}
+
+#[tokio::test]
+async fn sma_signal_buys_above_the_moving_average_including_warmup_bars() {
+    let test_bus = TestBus::new(64);
+    let strategy = Arc::new(SimpleTrendFollower::new(test_bus.bus(), SYMBOL.to_string()).with_sma_signal(3));
+    let mut harness = ActorTestHarness::start(test_bus, strategy.clone()).await;
+
+    // 预热之前的 Bar 计入均值但不产生订单
+    harness.send(BarBuilder::new().price(50.0).build()).await;
+    harness.send(BarBuilder::new().price(60.0).build()).await;
+    harness.expect_no_message::<OrderRequest>(QUIET).await;
+    harness.send(WarmupComplete { symbol: SYMBOL.to_string(), bars_seen: 2, ts_event: 0 }).await;
+    harness.wait_until(TIMEOUT, || strategy.is_warmed_up()).await;
+
+    // 均值 (50 + 60 + 40) / 3 = 50，收盘价低于均值
+    harness.send(BarBuilder::new().price(40.0).build()).await;
+    harness.expect_no_message::<OrderRequest>(QUIET).await;
+    // 均值 (60 + 40 + 70) / 3 ≈ 56.7，低于固定阈值但高于均值
+    harness.send(BarBuilder::new().price(70.0).build()).await;
+    let order = harness.expect_message::<OrderRequest>(TIMEOUT).await;
+    assert_eq!(order.price, 70.0);
+}
@@ -0,0 +1,291 @@
+// tests/stress.rs
+
+//! # MessageBus 压力与模型测试
+//!
+//! - 多线程压力：数十个任务同时订阅、同时发布多种消息类型，每个订阅者按发布者检查序号连续且类型正确。
+//! - 并发首次订阅：同一类型的并发首次订阅只创建一个通道（`on_new_type` 只触发一次）；
+//!   设置了订阅者上限时，并发订阅成功的数量不超过上限。
+//! - 模型检查：`proptest` 生成多个客户端的订阅 / 发布 / 丢弃订阅者交错序列，
+//!   与单线程的简单模型逐步比较送达数、订阅者数与每个订阅者收到的序列。
+
+use message_bus::bus::{MessageBus, Receiver};
+use message_bus::message::Message;
+use proptest::prelude::*;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Barrier;
+
+/// 带类型标签的序号消息，`TAG` 不同即为不同的消息类型。
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Seq<const TAG: u8> {
+    tag: u8,
+    publisher: usize,
+    seq: u64,
+}
+
+impl<const TAG: u8> Message for Seq<TAG> {}
+
+impl<const TAG: u8> Seq<TAG> {
+    fn new(publisher: usize, seq: u64) -> Self {
+        Self { tag: TAG, publisher, seq }
+    }
+}
+
+const PUBLISHERS: usize = 8;
+const SUBSCRIBERS_PER_TYPE: usize = 8;
+const MESSAGES_PER_PUBLISHER: u64 = 500;
+
+/// 接收 `PUBLISHERS * MESSAGES_PER_PUBLISHER` 条消息，检查标签正确且每个发布者的序号从 0 开始连续。
+async fn consume<const TAG: u8>(mut rx: Receiver<Seq<TAG>>) {
+    let mut next = [0u64; PUBLISHERS];
+    for _ in 0..PUBLISHERS as u64 * MESSAGES_PER_PUBLISHER {
+        let msg = rx.recv().await.expect("subscriber lagged or channel closed");
+        assert_eq!(msg.tag, TAG, "message of the wrong type on channel {}", TAG);
+        assert_eq!(msg.seq, next[msg.publisher], "gap in publisher {} on channel {}", msg.publisher, TAG);
+        next[msg.publisher] += 1;
+    }
+    assert!(rx.try_recv().is_err(), "unexpected extra message on channel {}", TAG);
+}
+
+/// 同时订阅 `SUBSCRIBERS_PER_TYPE` 次，所有订阅完成后才放行发布者。
+fn spawn_consumers<const TAG: u8>(bus: &MessageBus, ready: &Arc<Barrier>) -> Vec<tokio::task::JoinHandle<()>> {
+    (0..SUBSCRIBERS_PER_TYPE)
+        .map(|_| {
+            let bus = bus.clone();
+            let ready = ready.clone();
+            tokio::spawn(async move {
+                let rx = bus.subscribe::<Seq<TAG>>().await;
+                ready.wait().await;
+                consume(rx).await;
+            })
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_publishers_deliver_gap_free_sequences() {
+    // 容量足以容纳每种类型的全部消息，任何落后都是错误
+    let bus = MessageBus::new(PUBLISHERS * MESSAGES_PER_PUBLISHER as usize);
+    let ready = Arc::new(Barrier::new(3 * SUBSCRIBERS_PER_TYPE + PUBLISHERS));
+
+    let mut consumers = spawn_consumers::<0>(&bus, &ready);
+    consumers.extend(spawn_consumers::<1>(&bus, &ready));
+    consumers.extend(spawn_consumers::<2>(&bus, &ready));
+
+    let publishers: Vec<_> = (0..PUBLISHERS)
+        .map(|publisher| {
+            let bus = bus.clone();
+            let ready = ready.clone();
+            tokio::spawn(async move {
+                ready.wait().await;
+                for seq in 0..MESSAGES_PER_PUBLISHER {
+                    // 每个发布者交替发布三种类型
+                    assert_eq!(bus.publish(Seq::<0>::new(publisher, seq)).await.unwrap(), SUBSCRIBERS_PER_TYPE);
+                    assert_eq!(bus.publish(Seq::<1>::new(publisher, seq)).await.unwrap(), SUBSCRIBERS_PER_TYPE);
+                    assert_eq!(bus.publish(Seq::<2>::new(publisher, seq)).await.unwrap(), SUBSCRIBERS_PER_TYPE);
+                    if seq % 64 == 0 {
+                        tokio::task::yield_now().await;
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for handle in publishers.into_iter().chain(consumers) {
+        handle.await.expect("task panicked");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_first_subscribes_create_one_channel() {
+    const TASKS: usize = 32;
+
+    for _ in 0..50 {
+        let bus = MessageBus::new(8);
+        let created = Arc::new(Mutex::new(Vec::new()));
+        let recorder = created.clone();
+        bus.on_new_type(Arc::new(move |type_id, _| recorder.lock().unwrap().push(type_id)));
+
+        let ready = Arc::new(Barrier::new(TASKS));
+        let handles: Vec<_> = (0..TASKS)
+            .map(|_| {
+                let bus = bus.clone();
+                let ready = ready.clone();
+                tokio::spawn(async move {
+                    ready.wait().await;
+                    bus.subscribe::<Seq<0>>().await
+                })
+            })
+            .collect();
+        let mut receivers = Vec::new();
+        for handle in handles {
+            receivers.push(handle.await.unwrap());
+        }
+
+        assert_eq!(*created.lock().unwrap(), vec![TypeId::of::<Seq<0>>()]);
+        let stats: HashMap<_, _> = bus.connected_types().await.into_iter().collect();
+        let channel = &stats[std::any::type_name::<Seq<0>>()];
+        assert_eq!(channel.channels, 1);
+        assert_eq!(channel.subscribers, TASKS);
+
+        // 所有订阅者都在同一个通道上
+        assert_eq!(bus.publish(Seq::<0>::new(0, 0)).await.unwrap(), TASKS);
+        for rx in &mut receivers {
+            assert_eq!(rx.try_recv().unwrap(), Seq::new(0, 0));
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_subscribes_respect_the_subscriber_limit() {
+    const TASKS: usize = 32;
+    const LIMIT: usize = 4;
+
+    for _ in 0..50 {
+        let bus = MessageBus::new(8).with_max_subscribers::<Seq<0>>(LIMIT);
+        // 通道已经存在，之后的订阅都可能走快速路径
+        let first = bus.subscribe::<Seq<0>>().await;
+
+        let ready = Arc::new(Barrier::new(TASKS));
+        let handles: Vec<_> = (0..TASKS)
+            .map(|_| {
+                let bus = bus.clone();
+                let ready = ready.clone();
+                tokio::spawn(async move {
+                    ready.wait().await;
+                    bus.try_subscribe::<Seq<0>>().await.ok()
+                })
+            })
+            .collect();
+        let mut accepted = vec![first];
+        for handle in handles {
+            accepted.extend(handle.await.unwrap());
+        }
+
+        assert_eq!(accepted.len(), LIMIT);
+        assert_eq!(bus.subscriber_count::<Seq<0>>().await, LIMIT);
+    }
+}
+
+// --- 模型检查 ---
+
+/// 模型中使用的命名空间：根、`a` 与 `a/b`。
+const NAMESPACES: [&str; 3] = ["", "a", "a/b"];
+
+/// 一个客户端的操作。`ty` 选择两种消息类型之一，`ns` 为 `NAMESPACES` 的下标。
+#[derive(Clone, Debug)]
+enum Op {
+    Subscribe { ty: u8, ns: usize },
+    Publish { ty: u8, ns: usize },
+    /// 丢弃该客户端持有的第 `index % n` 个订阅者。
+    Drop { index: usize },
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    let ty = 0u8..2;
+    let ns = 0..NAMESPACES.len();
+    prop_oneof![
+        1 => (ty.clone(), ns.clone()).prop_map(|(ty, ns)| Op::Subscribe { ty, ns }),
+        2 => (ty, ns).prop_map(|(ty, ns)| Op::Publish { ty, ns }),
+        1 => any::<usize>().prop_map(|index| Op::Drop { index }),
+    ]
+}
+
+/// 1–4 个客户端各自的操作序列，以及它们的交错顺序（每一步执行哪个客户端的下一个操作）。
+fn interleaving_strategy() -> impl Strategy<Value = (Vec<Vec<Op>>, Vec<usize>)> {
+    prop::collection::vec(prop::collection::vec(op_strategy(), 0..24), 1..=4).prop_flat_map(|clients| {
+        let steps: Vec<usize> = clients.iter().enumerate().flat_map(|(client, ops)| vec![client; ops.len()]).collect();
+        (Just(clients), Just(steps).prop_shuffle())
+    })
+}
+
+/// 总线上的一个订阅者：实际的 `Receiver` 与模型预期它收到的序号。
+enum Subscription {
+    Zero(Receiver<Seq<0>>, Vec<u64>),
+    One(Receiver<Seq<1>>, Vec<u64>),
+}
+
+struct ModelSubscriber {
+    ty: u8,
+    ns: usize,
+    sub: Subscription,
+}
+
+/// 命名空间 `publisher` 中发布的消息是否会送达 `subscriber` 中的订阅者（自身或上级命名空间）。
+fn reaches(publisher: usize, subscriber: usize) -> bool {
+    let publisher = NAMESPACES[publisher];
+    let subscriber = NAMESPACES[subscriber];
+    subscriber.is_empty() || publisher == subscriber || publisher.starts_with(&format!("{}/", subscriber))
+}
+
+fn drain<const TAG: u8>(rx: &mut Receiver<Seq<TAG>>) -> Vec<u64> {
+    std::iter::from_fn(|| rx.try_recv().ok()).map(|msg| msg.seq).collect()
+}
+
+async fn check_against_model(clients: Vec<Vec<Op>>, steps: Vec<usize>) {
+    let root = MessageBus::new(1024);
+    let views: Vec<MessageBus> =
+        NAMESPACES.iter().map(|ns| if ns.is_empty() { root.clone() } else { root.clone_with_prefix(ns) }).collect();
+    let mut seq = 0u64;
+
+    let mut cursors = vec![0; clients.len()];
+    let mut held: Vec<Vec<ModelSubscriber>> = clients.iter().map(|_| Vec::new()).collect();
+    for client in steps {
+        let op = clients[client][cursors[client]].clone();
+        cursors[client] += 1;
+        match op {
+            Op::Subscribe { ty, ns } => {
+                let sub = match ty {
+                    0 => Subscription::Zero(views[ns].subscribe().await, Vec::new()),
+                    _ => Subscription::One(views[ns].subscribe().await, Vec::new()),
+                };
+                held[client].push(ModelSubscriber { ty, ns, sub });
+            },
+            Op::Publish { ty, ns } => {
+                let n = seq;
+                seq += 1;
+                let mut expected = 0;
+                for subscriber in held.iter_mut().flatten().filter(|s| s.ty == ty && reaches(ns, s.ns)) {
+                    match &mut subscriber.sub {
+                        Subscription::Zero(_, seqs) | Subscription::One(_, seqs) => seqs.push(n),
+                    }
+                    expected += 1;
+                }
+                let delivered = match ty {
+                    0 => views[ns].publish(Seq::<0>::new(client, n)).await.unwrap(),
+                    _ => views[ns].publish(Seq::<1>::new(client, n)).await.unwrap(),
+                };
+                assert_eq!(delivered, expected, "delivered count for type {} in {:?}", ty, NAMESPACES[ns]);
+                let counted = match ty {
+                    0 => views[ns].subscriber_count::<Seq<0>>().await,
+                    _ => views[ns].subscriber_count::<Seq<1>>().await,
+                };
+                assert_eq!(counted, expected);
+            },
+            Op::Drop { index } => {
+                if !held[client].is_empty() {
+                    let index = index % held[client].len();
+                    held[client].remove(index);
+                }
+            },
+        }
+    }
+
+    for subscriber in held.iter_mut().flatten() {
+        match &mut subscriber.sub {
+            Subscription::Zero(rx, expected) => assert_eq!(drain(rx), *expected),
+            Subscription::One(rx, expected) => assert_eq!(drain(rx), *expected),
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn interleaved_operations_match_the_model((clients, steps) in interleaving_strategy()) {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(check_against_model(clients, steps));
+    }
+}
@@ -0,0 +1,84 @@
+// tests/subscriber_limit.rs
+
+//! # 订阅者上限测试
+//!
+//! `with_max_subscribers` 限制一种消息类型同时存在的订阅者数量：达到上限后 `try_subscribe` 返回错误，
+//! 上限按所有命名空间合计，被丢弃的订阅者让出名额，其他类型不受影响。
+//! 总线内部需要订阅的接口（组、有序订阅、类型别名、JSON 订阅、请求/回复）同样返回错误，而不是 panic。
+
+use message_bus::bus::{BusError, GroupPolicy, MessageBus, RequestError};
+use message_bus::message::{Bar, FillEvent, Message, OrderRequest, OrderSide, OrderType};
+use std::time::Duration;
+use uuid::Uuid;
+
+fn order() -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 1.0,
+        trigger_price: None,
+    }
+}
+
+#[tokio::test]
+async fn third_subscriber_is_rejected() {
+    let bus = MessageBus::new(8).with_max_subscribers::<Bar>(2);
+
+    let first = bus.try_subscribe::<Bar>().await.unwrap();
+    let _second = bus.clone_with_prefix("paper").try_subscribe::<Bar>().await.unwrap();
+    let err = bus.try_subscribe::<Bar>().await.unwrap_err();
+    assert!(matches!(err, BusError::SubscriberLimit { limit: 2, .. }), "{:?}", err);
+    assert_eq!(bus.subscriber_count::<Bar>().await, 1);
+
+    // 其他类型不受限制
+    let _fills = bus.try_subscribe::<FillEvent>().await.unwrap();
+    let _more_fills = bus.try_subscribe::<FillEvent>().await.unwrap();
+    let _even_more_fills = bus.try_subscribe::<FillEvent>().await.unwrap();
+
+    // 丢弃订阅者后名额空出
+    drop(first);
+    let _third = bus.try_subscribe::<Bar>().await.unwrap();
+}
+
+#[derive(Clone, Debug)]
+struct LegacyBar {
+    _close: f64,
+}
+impl Message for LegacyBar {}
+
+impl From<Bar> for LegacyBar {
+    fn from(bar: Bar) -> Self {
+        LegacyBar { _close: bar.close }
+    }
+}
+
+fn is_limit(result: Result<(), BusError>) -> bool {
+    matches!(result, Err(BusError::SubscriberLimit { limit: 2, .. }))
+}
+
+#[tokio::test]
+async fn third_subscribe_through_any_interface_returns_the_limit_error() {
+    let bus = MessageBus::new(8).with_max_subscribers::<Bar>(2).with_max_subscribers::<FillEvent>(2);
+    bus.register_message::<Bar>("bar");
+    let _first = bus.try_subscribe::<Bar>().await.unwrap();
+    let _second = bus.try_subscribe::<Bar>().await.unwrap();
+
+    // 内部需要订阅的接口不 panic，而是把上限错误返回给调用者
+    assert!(is_limit(bus.subscribe_group::<Bar>("loggers", GroupPolicy::Broadcast).await.map(drop)));
+    assert!(is_limit(bus.subscribe_group::<Bar>("loggers", GroupPolicy::RoundRobin).await.map(drop)));
+    assert!(is_limit(bus.subscribe_ordered::<Bar>(0).await.map(drop)));
+    assert!(is_limit(bus.type_alias::<LegacyBar, Bar>().await.map(drop)));
+    assert!(is_limit(bus.subscribe_json("bar").await.map(drop)));
+    assert_eq!(bus.subscriber_count::<Bar>().await, 2);
+
+    let _fills = bus.try_subscribe::<FillEvent>().await.unwrap();
+    let _more_fills = bus.try_subscribe::<FillEvent>().await.unwrap();
+    let request = bus.publish_and_await_reply::<OrderRequest, FillEvent>(order(), Duration::from_millis(10)).await;
+    match request {
+        Err(RequestError::Subscribe(BusError::SubscriberLimit { limit: 2, .. })) => {},
+        other => panic!("expected a subscribe error, got {:?}", other),
+    }
+}
@@ -0,0 +1,69 @@
+// tests/symbol.rs
+
+//! # Symbol 规范化测试
+//!
+//! `SymbolRegistry` 把不同写法统一为规范 symbol，登记的别名解析为对应的规范 symbol，
+//! 未登记的 symbol 只做大小写与分隔符的规范化；数据引擎以 `"BTCUSD"` 接入时发布的 `Bar`
+//! 使用规范的 `"BTC-USD"`，订阅 `"BTC-USD"` 的策略因此能够匹配并下单。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::data::SimulatedDataEngine;
+use message_bus::message::{Bar, OrderRequest, WarmupComplete};
+use message_bus::strategy::SimpleTrendFollower;
+use message_bus::symbol::SymbolRegistry;
+use std::sync::Arc;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn registry() -> SymbolRegistry {
+    let mut registry = SymbolRegistry::new();
+    registry.register("BTC-USD").alias("XBTUSD", "BTC-USD");
+    registry
+}
+
+#[test]
+fn registered_spellings_resolve_to_the_canonical_symbol() {
+    let registry = registry();
+    for raw in ["BTCUSD", "btcusd", "BTC/USD", "btc_usd", " BTC:USD ", "XBTUSD", "xbtusd"] {
+        assert_eq!(registry.resolve(raw), "BTC-USD", "{:?}", raw);
+    }
+    assert!(registry.is_known("BTCUSD"));
+    assert_eq!(SymbolRegistry::quote_currency("BTC-USD"), "USD");
+}
+
+#[test]
+fn unknown_symbol_is_only_normalized() {
+    let registry = registry();
+    assert!(!registry.is_known("ETHUSD"));
+    // 没有登记，无法知道 ETHUSD 的分隔位置，原样保留
+    assert_eq!(registry.resolve("ETHUSD"), "ETHUSD");
+    assert_eq!(registry.resolve("eth/usd"), "ETH-USD");
+    assert_eq!(SymbolRegistry::quote_currency("ETHUSD"), "");
+}
+
+#[tokio::test]
+async fn data_engine_publishes_canonical_symbols_that_the_strategy_matches() {
+    let bus = MessageBus::new(64);
+    let mut bar_rx = bus.subscribe::<Bar>().await;
+    let mut order_rx = bus.subscribe::<OrderRequest>().await;
+    // 收盘价高于最近两根的均值时买入，默认数据源的价格逐根上涨，第二根 Bar 即可触发
+    let strategy = Arc::new(SimpleTrendFollower::new(bus.clone(), "BTC-USD".to_string()).with_sma_signal(2));
+    let mut handles = strategy.clone().start("STRATEGY").await;
+    bus.publish(WarmupComplete { symbol: "BTC-USD".to_string(), bars_seen: 0, ts_event: 0 }).await.unwrap();
+    while !strategy.is_warmed_up() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let data = Arc::new(SimulatedDataEngine::new(bus.clone(), "BTCUSD".to_string()).with_symbol_registry(&registry()));
+    handles.extend(data.start("DATA").await);
+
+    assert_eq!(bar_rx.recv_timeout(TIMEOUT).await.unwrap().symbol, "BTC-USD");
+    let order = order_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(order.symbol, "BTC-USD");
+
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,86 @@
+// tests/system.rs
+
+//! # Actor 运行时亲和性测试
+//!
+//! 验证 `Affinity::Dedicated` 的 Actor 由 `ActorSystem` 启动在专用运行时的线程上，
+//! 而共享的 Actor 留在默认的运行时上。
+
+use message_bus::actor::{Actor, Affinity};
+use message_bus::bus::MessageBus;
+use message_bus::system::{ActorSystemBuilder, DEDICATED_THREAD_NAME};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// 任务所在线程的 ID 与名称。
+type ThreadInfo = (ThreadId, Option<String>);
+
+/// 在 `start` 中派生一个任务，记录该任务所在线程的 ID 与名称。
+struct ThreadProbe {
+    affinity: Affinity,
+    observed: Mutex<Option<oneshot::Sender<ThreadInfo>>>,
+}
+
+impl ThreadProbe {
+    fn new(affinity: Affinity) -> (Arc<Self>, oneshot::Receiver<ThreadInfo>) {
+        let (tx, rx) = oneshot::channel();
+        (Arc::new(Self { affinity, observed: Mutex::new(Some(tx)) }), rx)
+    }
+}
+
+#[async_trait::async_trait]
+impl Actor for ThreadProbe {
+    async fn start(self: Arc<Self>, _actor_name: &str) -> Vec<JoinHandle<()>> {
+        let handle = tokio::spawn(async move {
+            let thread = std::thread::current();
+            if let Some(tx) = self.observed.lock().unwrap().take() {
+                let _ = tx.send((thread.id(), thread.name().map(str::to_string)));
+            }
+        });
+        vec![handle]
+    }
+
+    fn affinity(&self) -> Affinity {
+        self.affinity
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn dedicated_actor_runs_on_a_separate_thread() {
+    let (shared, shared_rx) = ThreadProbe::new(Affinity::Shared);
+    let (dedicated, dedicated_rx) = ThreadProbe::new(Affinity::Dedicated);
+    let started_hooks = Arc::new(AtomicUsize::new(0));
+    let hooks = started_hooks.clone();
+    let system = ActorSystemBuilder::new(MessageBus::new(8))
+        .actor("SHARED", shared, &[], &[])
+        .actor("DEDICATED", dedicated, &[], &[])
+        .on_dedicated_thread_start(move || {
+            hooks.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+    let handle = system.start().await;
+
+    let (shared_thread, shared_name) = shared_rx.await.unwrap();
+    let (dedicated_thread, dedicated_name) = dedicated_rx.await.unwrap();
+    assert_ne!(shared_thread, dedicated_thread);
+    assert_eq!(dedicated_name.as_deref(), Some(DEDICATED_THREAD_NAME));
+    assert_ne!(shared_name.as_deref(), Some(DEDICATED_THREAD_NAME));
+    assert!(started_hooks.load(Ordering::SeqCst) >= 1);
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn shared_only_system_stays_on_the_caller_runtime() {
+    let (shared, shared_rx) = ThreadProbe::new(Affinity::Shared);
+    let system = ActorSystemBuilder::new(MessageBus::new(8)).actor("SHARED", shared, &[], &[]).build().unwrap();
+    let handle = system.start().await;
+
+    // current-thread 运行时：任务运行在测试线程上
+    let (thread, _) = shared_rx.await.unwrap();
+    assert_eq!(thread, std::thread::current().id());
+    handle.shutdown().await;
+}
@@ -0,0 +1,145 @@
+// tests/throttle.rs
+
+//! # 下单限流测试
+//!
+//! 用显式的纳秒时间驱动 `OrderThrottle`，验证速率限制、未结束订单数限制与排队放行；
+//! 再通过执行引擎验证被限流的订单以 `Throttled` 拒绝并计入统计。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::message::{FillEvent, OrderRejected, OrderRequest, OrderSide, OrderType, RejectReason};
+use message_bus::throttle::{Admission, OrderThrottle, ThrottleConfig, ThrottleMode, ThrottleStats};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const SECOND: u64 = 1_000_000_000;
+
+fn order(symbol: &str) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 1.0,
+        trigger_price: None,
+    }
+}
+
+#[test]
+fn rate_limit_rejects_excess_and_recovers_after_window() {
+    let mut throttle = OrderThrottle::new(ThrottleConfig {
+        max_orders_per_second_per_symbol: Some(2),
+        ..Default::default()
+    });
+
+    assert_eq!(throttle.admit(&order("BTC-USD"), 0), Admission::Admit);
+    assert_eq!(throttle.admit(&order("BTC-USD"), 10), Admission::Admit);
+    assert!(matches!(throttle.admit(&order("BTC-USD"), 20), Admission::Rejected(_)));
+    // 其他 symbol 的计数相互独立
+    assert_eq!(throttle.admit(&order("ETH-USD"), 20), Admission::Admit);
+    // 第一笔订单滑出 1 秒窗口后恢复一个名额
+    assert_eq!(throttle.admit(&order("BTC-USD"), SECOND), Admission::Admit);
+    assert_eq!(throttle.stats(), ThrottleStats { rejected: 1, queued: 0 });
+}
+
+#[test]
+fn global_rate_limit_spans_symbols() {
+    let mut throttle = OrderThrottle::new(ThrottleConfig { max_orders_per_second: Some(2), ..Default::default() });
+
+    assert_eq!(throttle.admit(&order("BTC-USD"), 0), Admission::Admit);
+    assert_eq!(throttle.admit(&order("ETH-USD"), 0), Admission::Admit);
+    assert!(matches!(throttle.admit(&order("SOL-USD"), 0), Admission::Rejected(_)));
+}
+
+#[test]
+fn queued_orders_are_released_in_arrival_order_when_rate_frees() {
+    let mut throttle = OrderThrottle::new(ThrottleConfig {
+        max_orders_per_second: Some(1),
+        mode: ThrottleMode::Queue { max_depth: 2 },
+        ..Default::default()
+    });
+
+    let first = order("BTC-USD");
+    let second = order("BTC-USD");
+    let third = order("BTC-USD");
+    assert_eq!(throttle.admit(&first, 0), Admission::Admit);
+    assert_eq!(throttle.admit(&second, 1), Admission::Queued);
+    assert_eq!(throttle.admit(&third, 2), Admission::Queued);
+    // 队列已满时拒绝
+    assert!(matches!(throttle.admit(&order("BTC-USD"), 3), Admission::Rejected(_)));
+    assert_eq!(throttle.queued_len(), 2);
+
+    assert_eq!(throttle.next_release_at(), Some(SECOND));
+    assert!(throttle.release(SECOND - 1).is_empty());
+    let released = throttle.release(SECOND);
+    assert_eq!(released.iter().map(|o| o.id).collect::<Vec<_>>(), vec![second.id]);
+    let released = throttle.release(2 * SECOND);
+    assert_eq!(released.iter().map(|o| o.id).collect::<Vec<_>>(), vec![third.id]);
+    assert_eq!(throttle.next_release_at(), None);
+    assert_eq!(throttle.stats(), ThrottleStats { rejected: 1, queued: 2 });
+}
+
+#[test]
+fn open_order_limit_releases_queue_when_an_order_closes() {
+    let mut throttle = OrderThrottle::new(ThrottleConfig {
+        max_open_orders_per_symbol: Some(1),
+        mode: ThrottleMode::Queue { max_depth: 4 },
+        ..Default::default()
+    });
+
+    let first = order("BTC-USD");
+    let second = order("BTC-USD");
+    assert_eq!(throttle.admit(&first, 0), Admission::Admit);
+    assert_eq!(throttle.admit(&second, 0), Admission::Queued);
+    assert!(throttle.release(SECOND).is_empty());
+
+    throttle.on_closed(&first);
+    let released = throttle.release(SECOND);
+    assert_eq!(released.iter().map(|o| o.id).collect::<Vec<_>>(), vec![second.id]);
+}
+
+#[test]
+fn cancelled_queued_order_is_never_released() {
+    let mut throttle = OrderThrottle::new(ThrottleConfig {
+        max_orders_per_second: Some(1),
+        mode: ThrottleMode::Queue { max_depth: 4 },
+        ..Default::default()
+    });
+
+    let queued = order("BTC-USD");
+    assert_eq!(throttle.admit(&order("BTC-USD"), 0), Admission::Admit);
+    assert_eq!(throttle.admit(&queued, 0), Admission::Queued);
+    assert_eq!(throttle.remove_queued(queued.id).map(|o| o.id), Some(queued.id));
+    assert!(throttle.release(SECOND).is_empty());
+}
+
+#[tokio::test]
+async fn engine_rejects_throttled_orders_and_counts_them() {
+    let bus = MessageBus::new(64);
+    let mut fill_rx = bus.subscribe::<FillEvent>().await;
+    let mut rejected_rx = bus.subscribe::<OrderRejected>().await;
+    let engine = Arc::new(
+        SimulatedExecutionEngine::new(bus.clone())
+            .with_throttle(ThrottleConfig { max_orders_per_second_per_symbol: Some(1), ..Default::default() }),
+    );
+    let handles = engine.clone().start("EXECUTION").await;
+
+    let accepted = order("BTC-USD");
+    let throttled = order("BTC-USD");
+    bus.publish(accepted.clone()).await.unwrap();
+    bus.publish(throttled.clone()).await.unwrap();
+
+    let rejected = rejected_rx.recv_timeout(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(rejected.order_id, throttled.id);
+    assert_eq!(rejected.reason, RejectReason::Throttled);
+    let fill = fill_rx.recv_timeout(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(fill.order_id, accepted.id);
+    assert_eq!(engine.throttle_stats(), ThrottleStats { rejected: 1, queued: 0 });
+
+    for handle in handles {
+        handle.abort();
+    }
+}
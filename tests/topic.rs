@@ -0,0 +1,71 @@
+// tests/topic.rs
+
+//! # 动态主题测试
+//!
+//! 验证按名称登记的消息类型可以通过 JSON 发布与订阅，并与强类型的订阅者、发布者互通。
+
+use futures::StreamExt;
+use message_bus::bus::{BusError, MessageBus, ReceiverExt};
+use message_bus::message::{Bar, DEFAULT_BAR_TIMEFRAME};
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+#[tokio::test]
+async fn json_published_bar_reaches_typed_subscriber() {
+    let bus = MessageBus::new(16);
+    bus.register_message::<Bar>("bar");
+    let mut bar_rx = bus.subscribe::<Bar>().await;
+
+    let id = Uuid::new_v4();
+    let json = format!(
+        r#"{{"id":"{}","ts_event":42,"symbol":"BTC-USD","open":100.0,"high":101.5,"low":99.5,"close":101.0,"volume":3.0}}"#,
+        id
+    );
+    assert_eq!(bus.publish_json("bar", &json).await.unwrap(), 1);
+
+    let bar = bar_rx.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(bar.id, id);
+    assert_eq!(bar.ts_event, 42);
+    assert_eq!(bar.symbol, "BTC-USD");
+    assert_eq!((bar.open, bar.high, bar.low, bar.close, bar.volume), (100.0, 101.5, 99.5, 101.0, 3.0));
+}
+
+#[tokio::test]
+async fn json_subscriber_receives_typed_publishes() {
+    let bus = MessageBus::new(16);
+    bus.register_message::<Bar>("bar");
+    let mut stream = bus.subscribe_json("bar").await.unwrap();
+
+    let bar = Bar {
+        id: Uuid::new_v4(),
+        ts_event: 7,
+        symbol: "ETH-USD".to_string(),
+        open: 10.0,
+        high: 11.0,
+        low: 9.0,
+        close: 10.5,
+        volume: 2.0,
+        timeframe: DEFAULT_BAR_TIMEFRAME,
+    };
+    bus.publish(bar.clone()).await.unwrap();
+
+    let json = tokio::time::timeout(TIMEOUT, stream.next()).await.unwrap().unwrap();
+    let parsed: Bar = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.id, bar.id);
+    assert_eq!(parsed.close, bar.close);
+}
+
+#[tokio::test]
+async fn unknown_name_and_malformed_json_are_errors() {
+    let bus = MessageBus::new(16);
+    bus.register_message::<Bar>("bar");
+
+    assert!(matches!(bus.publish_json("order", "{}").await, Err(BusError::UnknownTopic { name }) if name == "order"));
+    assert!(matches!(bus.subscribe_json("order").await, Err(BusError::UnknownTopic { .. })));
+    let malformed = bus.publish_json("bar", r#"{"symbol":"BTC-USD"}"#).await;
+    assert!(matches!(&malformed, Err(BusError::Deserialize { type_name, .. }) if type_name.ends_with("Bar")));
+    assert!(std::error::Error::source(&malformed.unwrap_err()).is_some());
+    assert_eq!(bus.registered_topics(), vec!["bar".to_string()]);
+}
@@ -0,0 +1,171 @@
+// tests/trace.rs
+
+//! # 消息追踪测试
+//!
+//! 用记录 span 创建的 `tracing` 层捕获一次 `Bar` → 订单 → 成交的因果链：
+//! 成交的 `publish` span 沿父 span 依次回溯到执行引擎、策略对订单与信号的处理，最终到 `Bar` 的发布，
+//! 链上所有 span 带有同一个关联 ID，策略的日志落在它处理 `Bar` 的 `handle` span 中。
+//! 另外验证 `spawn_named` 派生的 Actor 任务运行在带有 Actor 名称的 `actor` span 中。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::data::SimulatedDataEngine;
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::message::{Bar, FillEvent, WarmupComplete};
+use message_bus::strategy::SimpleTrendFollower;
+use message_bus::testkit::{ActorTestHarness, BarBuilder, TestBus};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+const SYMBOL: &str = "BTC-USD";
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 捕获到的一个 span。
+#[derive(Clone, Debug)]
+struct SpanRecord {
+    name: &'static str,
+    fields: HashMap<&'static str, String>,
+    parent: Option<Id>,
+}
+
+/// 捕获到的一条日志及其所在的 span。
+#[derive(Clone, Debug)]
+struct EventRecord {
+    message: String,
+    span: Option<Id>,
+}
+
+#[derive(Clone, Default)]
+struct Captured {
+    spans: Arc<Mutex<HashMap<Id, SpanRecord>>>,
+    events: Arc<Mutex<Vec<EventRecord>>>,
+}
+
+struct FieldVisitor<'a>(&'a mut HashMap<&'static str, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.insert(field.name(), format!("{:?}", value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.to_string());
+    }
+}
+
+impl<S: Subscriber + for<'span> LookupSpan<'span>> Layer<S> for Captured {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = HashMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        let parent = ctx.span(id).and_then(|span| span.parent()).map(|parent| parent.id());
+        self.spans.lock().unwrap().insert(id.clone(), SpanRecord { name: attrs.metadata().name(), fields, parent });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = HashMap::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        let message = fields.remove("message").unwrap_or_default();
+        self.events.lock().unwrap().push(EventRecord { message, span: ctx.current_span().id().cloned() });
+    }
+}
+
+impl Captured {
+    /// 从 `id` 开始沿父 span 回溯到根的 span 链。
+    fn ancestry(&self, id: &Id) -> Vec<SpanRecord> {
+        let spans = self.spans.lock().unwrap();
+        let mut chain = Vec::new();
+        let mut next = Some(id.clone());
+        while let Some(id) = next {
+            let record = spans[&id].clone();
+            next = record.parent.clone();
+            chain.push(record);
+        }
+        chain
+    }
+
+    /// 第一个发布 `message` 的 `publish` span。
+    fn publish_span(&self, message: &str) -> Id {
+        let spans = self.spans.lock().unwrap();
+        let (id, _) = spans
+            .iter()
+            .find(|(_, record)| record.name == "publish" && record.fields["message"] == message)
+            .unwrap_or_else(|| panic!("no publish span for {}", message));
+        id.clone()
+    }
+}
+
+#[tokio::test]
+async fn bar_order_fill_form_one_span_chain() {
+    // 单线程运行时中，Actor 的任务与测试运行在同一线程上，都使用这个订阅者
+    let captured = Captured::default();
+    let _default = tracing::subscriber::set_default(tracing_subscriber::registry().with(captured.clone()));
+
+    let test_bus = TestBus::new(64);
+    let engine = Arc::new(SimulatedExecutionEngine::new(test_bus.bus()));
+    let _engine_handles = engine.start("EXECUTION").await;
+    let strategy = Arc::new(SimpleTrendFollower::new(test_bus.bus(), SYMBOL.to_string()));
+    let mut harness = ActorTestHarness::start(test_bus, strategy.clone()).await;
+    harness.send(WarmupComplete { symbol: SYMBOL.to_string(), bars_seen: 3, ts_event: 0 }).await;
+    harness.wait_until(TIMEOUT, || strategy.is_warmed_up()).await;
+
+    harness.send(BarBuilder::new().price(110.0).build()).await;
+    harness.expect_message::<FillEvent>(TIMEOUT).await;
+
+    let chain = captured.ancestry(&captured.publish_span("FillEvent"));
+    let steps: Vec<_> = chain
+        .iter()
+        .map(|span| (span.name, span.fields.get("actor").map(String::as_str), span.fields["message"].as_str()))
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            ("publish", None, "FillEvent"),
+            ("handle", Some("EXECUTION"), "OrderRequest"),
+            ("publish", None, "OrderRequest"),
+            ("handle", Some("STRATEGY"), "Signal"),
+            ("publish", None, "Signal"),
+            ("handle", Some("STRATEGY"), "Bar"),
+            ("publish", None, "Bar"),
+        ]
+    );
+    let correlation_id = &chain[0].fields["correlation_id"];
+    assert!(chain.iter().all(|span| &span.fields["correlation_id"] == correlation_id));
+
+    // 策略收到 Bar 时的日志落在它处理该 Bar 的 span 中
+    let events = captured.events.lock().unwrap();
+    let log = events.iter().find(|event| event.message.contains("Received Bar")).expect("strategy logged the bar");
+    let log_span = captured.spans.lock().unwrap()[log.span.as_ref().expect("logged inside a span")].clone();
+    assert_eq!(log_span.name, "handle");
+    assert_eq!(log_span.fields["actor"], "STRATEGY");
+    assert_eq!(log_span.fields["message"], "Bar");
+    assert_eq!(log_span.fields["correlation_id"], *correlation_id);
+}
+
+#[tokio::test]
+async fn actor_tasks_run_in_a_named_actor_span() {
+    let captured = Captured::default();
+    let _default = tracing::subscriber::set_default(tracing_subscriber::registry().with(captured.clone()));
+
+    let bus = MessageBus::new(16);
+    let mut bar_rx = bus.subscribe::<Bar>().await;
+    let handles = Arc::new(SimulatedDataEngine::new(bus.clone(), SYMBOL.to_string())).start("DATA.BTC-USD").await;
+    bar_rx.recv_timeout(TIMEOUT).await.unwrap();
+
+    // 数据源在自己的任务中发布，Bar 的 publish span 挂在该任务的 actor span 下
+    let chain = captured.ancestry(&captured.publish_span("Bar"));
+    let names: Vec<_> = chain.iter().map(|span| span.name).collect();
+    assert_eq!(names, ["publish", "actor"]);
+    assert_eq!(chain[1].fields["name"], "DATA.BTC-USD");
+
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,99 @@
+// tests/trade_log.rs
+
+//! # 交易记录测试
+//!
+//! `TradeLogActor` 为每笔成交标注成交前的组合权益与累计已实现盈亏，
+//! `export_csv` 写出全部记录并发布 `TradeLogExported`。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::message::{FillEvent, Liquidity, OrderSide, PortfolioSnapshot, TradeLogExported};
+use message_bus::trade_log::TradeLogActor;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn fill(side: OrderSide, price: f64, ts_event: u64) -> FillEvent {
+    FillEvent {
+        order_id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side,
+        price,
+        quantity: 2.0,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.1,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Taker,
+        ts_event,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+async fn wait_for_trades(log: &TradeLogActor, count: usize) {
+    tokio::time::timeout(TIMEOUT, async {
+        while log.trade_count() < count {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn fills_are_annotated_with_equity_and_running_realized_pnl() {
+    let bus = MessageBus::new(64);
+    let log = Arc::new(TradeLogActor::new(bus.clone()));
+    let handles = log.clone().start("TRADE_LOG").await;
+
+    bus.publish(fill(OrderSide::Buy, 100.0, 1)).await.unwrap();
+    wait_for_trades(&log, 1).await;
+    bus.publish(PortfolioSnapshot { total_equity: 10_000.0, net_pnl: 0.0, ts: 2 }).await.unwrap();
+    // 等待快照被处理：之后的成交看到新的权益
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    bus.publish(fill(OrderSide::Sell, 110.0, 3)).await.unwrap();
+    wait_for_trades(&log, 2).await;
+
+    let records = log.records();
+    assert_eq!((records[0].portfolio_equity_at_fill, records[0].running_realized_pnl), (0.0, 0.0));
+    assert_eq!((records[1].portfolio_equity_at_fill, records[1].running_realized_pnl), (10_000.0, 20.0));
+    assert_eq!(records[1].fill.price, 110.0);
+    assert_eq!(log.total_realized_pnl(), 20.0);
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn export_writes_every_record_and_announces_it() {
+    let bus = MessageBus::new(64);
+    let mut exported = bus.subscribe::<TradeLogExported>().await;
+    let log = Arc::new(TradeLogActor::new(bus.clone()));
+    let handles = log.clone().start("TRADE_LOG").await;
+    bus.publish(fill(OrderSide::Buy, 100.0, 1)).await.unwrap();
+    bus.publish(fill(OrderSide::Sell, 95.0, 2)).await.unwrap();
+    wait_for_trades(&log, 2).await;
+
+    let path = std::env::temp_dir().join(format!("message-bus-{}-trades.csv", std::process::id()));
+    log.export_csv(&path).await.unwrap();
+    let event = exported.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((event.path, event.trade_count), (path.display().to_string(), 2));
+
+    let mut reader = csv::Reader::from_path(&path).unwrap();
+    let headers = reader.headers().unwrap().clone();
+    assert_eq!(&headers[0], "order_id");
+    assert_eq!(&headers[headers.len() - 3], "portfolio_equity_at_fill");
+    let rows: Vec<csv::StringRecord> = reader.records().collect::<Result<_, _>>().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(&rows[1][2], "Sell");
+    assert_eq!(&rows[1][headers.len() - 2], "-10.0");
+
+    for handle in handles {
+        handle.abort();
+    }
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,91 @@
+// tests/trading_event.rs
+
+//! # 复合消息测试
+//!
+//! 订单与成交以 `TradingEvent` 发布到同一个通道，消费者按发布顺序收到并按变体匹配；
+//! 以联合类型发布的消息不会出现在各自类型的通道中。
+
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::{
+    CancelOrderRequest, FillEvent, Liquidity, OrderRequest, OrderSide, OrderType, TradingEvent,
+};
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(100);
+
+fn order() -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        order_type: OrderType::Market,
+        price: 100.0,
+        quantity: 2.0,
+        trigger_price: None,
+    }
+}
+
+fn fill(order: &OrderRequest, quantity: f64, leaves_qty: f64) -> FillEvent {
+    FillEvent {
+        order_id: order.id,
+        symbol: order.symbol.clone(),
+        side: order.side.clone(),
+        price: order.price,
+        quantity,
+        leaves_qty,
+        is_final: leaves_qty == 0.0,
+        commission: 0.0,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Taker,
+        ts_event: 0,
+        venue_fill_id: None,
+        correlation_id: Some(order.id),
+    }
+}
+
+#[tokio::test]
+async fn order_and_its_fills_arrive_in_publish_order() {
+    let bus = MessageBus::new(64);
+    let mut events = bus.subscribe::<TradingEvent>().await;
+    let mut orders = bus.subscribe::<OrderRequest>().await;
+
+    let first = order();
+    let second = order();
+    bus.publish_as::<TradingEvent, _>(first.clone()).await.unwrap();
+    bus.publish_as::<TradingEvent, _>(fill(&first, 1.0, 1.0)).await.unwrap();
+    bus.publish_as::<TradingEvent, _>(second.clone()).await.unwrap();
+    bus.publish_as::<TradingEvent, _>(fill(&first, 1.0, 0.0)).await.unwrap();
+    bus.publish_as::<TradingEvent, _>(CancelOrderRequest { order_id: second.id, symbol: second.symbol.clone() })
+        .await
+        .unwrap();
+
+    let mut received = Vec::new();
+    for _ in 0..5 {
+        let event = events.recv_timeout(TIMEOUT).await.unwrap();
+        received.push(match &event {
+            TradingEvent::Order(_) => "order",
+            TradingEvent::Fill(fill) if fill.is_final => "final fill",
+            TradingEvent::Fill(_) => "fill",
+            TradingEvent::Cancel(_) => "cancel",
+        });
+        assert_eq!(event.symbol(), "BTC-USD");
+    }
+    assert_eq!(received, ["order", "fill", "order", "final fill", "cancel"]);
+
+    // 以联合类型发布的订单不进入 OrderRequest 的通道
+    assert!(matches!(orders.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+}
+
+#[tokio::test]
+async fn order_ids_follow_the_order_across_variants() {
+    let bus = MessageBus::new(64);
+    let mut events = bus.subscribe::<TradingEvent>().await;
+    let order = order();
+    bus.publish_as::<TradingEvent, _>(order.clone()).await.unwrap();
+    bus.publish(TradingEvent::from(fill(&order, 2.0, 0.0))).await.unwrap();
+
+    assert_eq!(events.recv_timeout(TIMEOUT).await.unwrap().order_id(), order.id);
+    assert_eq!(events.recv_timeout(TIMEOUT).await.unwrap().order_id(), order.id);
+}
@@ -0,0 +1,64 @@
+// tests/ttl.rs
+
+//! # 消息有效期测试
+//!
+//! 总线使用虚拟时钟为消息打上 `ts_recv`：推进时钟越过有效期后，`recv_fresh` 丢弃过期消息并计数，
+//! 而不是把它交给处理函数；没有有效期的消息与普通的 `recv` 不受影响。
+
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::clock::VirtualClock;
+use message_bus::message::{Bar, TradingHalted};
+use message_bus::testkit::BarBuilder;
+use std::sync::Arc;
+use std::time::Duration;
+
+const START: u64 = 1_000_000_000;
+const TTL: Duration = Duration::from_millis(10);
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+#[tokio::test]
+async fn recv_fresh_drops_messages_older_than_their_ttl() {
+    let clock = Arc::new(VirtualClock::new(START));
+    let bus = MessageBus::new(16).with_clock(clock.clone()).with_type_ttl::<Bar>(TTL);
+    let mut fresh_rx = bus.subscribe::<Bar>().await;
+    let mut raw_rx = bus.subscribe::<Bar>().await;
+
+    bus.publish(BarBuilder::new().price(100.0).build()).await.unwrap();
+    // 恰好到期时仍然有效
+    clock.advance_to(START + TTL.as_nanos() as u64);
+    bus.publish(BarBuilder::new().price(101.0).build()).await.unwrap();
+    // 越过第一条的有效期
+    clock.advance_to(START + TTL.as_nanos() as u64 + 1);
+    bus.publish(BarBuilder::new().price(102.0).build()).await.unwrap();
+
+    let first = fresh_rx.recv_fresh().await.unwrap();
+    assert_eq!(first.message.close, 101.0);
+    assert_eq!(first.ts_recv, START + TTL.as_nanos() as u64);
+    assert_eq!(first.ttl, Some(TTL));
+    assert_eq!(fresh_rx.recv_fresh().await.unwrap().message.close, 102.0);
+    assert_eq!(fresh_rx.expired_count(), 1);
+
+    // 普通接收不检查有效期
+    assert_eq!(raw_rx.recv_timeout(TIMEOUT).await.unwrap().close, 100.0);
+}
+
+#[tokio::test]
+async fn per_message_ttl_overrides_the_type_default() {
+    let clock = Arc::new(VirtualClock::new(START));
+    let bus = MessageBus::new(16).with_clock(clock.clone()).with_type_ttl::<Bar>(TTL);
+    let mut bar_rx = bus.subscribe::<Bar>().await;
+    let mut halt_rx = bus.subscribe::<TradingHalted>().await;
+
+    bus.publish_with_ttl(BarBuilder::new().price(100.0).build(), Duration::from_secs(1)).await.unwrap();
+    bus.publish(BarBuilder::new().price(101.0).build()).await.unwrap();
+    // 没有设置有效期的类型永不过期
+    bus.publish(TradingHalted { symbol: None, reason: "test".to_string() }).await.unwrap();
+    clock.advance_to(START + Duration::from_millis(500).as_nanos() as u64);
+
+    assert_eq!(bar_rx.recv_fresh().await.unwrap().message.close, 100.0);
+    // 按类型默认有效期发布的那条已过期
+    assert!(tokio::time::timeout(Duration::from_millis(50), bar_rx.recv_fresh()).await.is_err());
+    assert_eq!(bar_rx.expired_count(), 1);
+    assert_eq!(halt_rx.recv_fresh().await.unwrap().message.reason, "test");
+    assert_eq!(halt_rx.expired_count(), 0);
+}
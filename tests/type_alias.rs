@@ -0,0 +1,125 @@
+// tests/type_alias.rs
+
+//! # 类型别名测试
+//!
+//! `type_alias::<QuoteTick, TradeTick>()` 之后发布的 `TradeTick` 同时送达两种类型的订阅者，
+//! 直接发布的 `QuoteTick` 不会反向转发；重复登记不重复投递，别名可以串联，构成环的登记返回 `CircularAlias`。
+//! 丢弃最后一个 `TypeAlias` 句柄后转发停止，转发任务不再计为 `TradeTick` 的订阅者。
+
+use message_bus::bus::{BusError, MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::{Message, TradeTick};
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 旧版 Actor 订阅的行情类型，字段与 `TradeTick` 相同。
+#[derive(Clone, Debug)]
+struct QuoteTick {
+    symbol: String,
+    price: f64,
+    ts_event: u64,
+}
+impl Message for QuoteTick {}
+
+impl From<TradeTick> for QuoteTick {
+    fn from(tick: TradeTick) -> Self {
+        Self { symbol: tick.symbol, price: tick.price, ts_event: tick.ts_event }
+    }
+}
+
+/// 由 `QuoteTick` 再转换出的类型，用于串联与环的检查。
+#[derive(Clone, Debug)]
+struct LegacyPrice(f64);
+impl Message for LegacyPrice {}
+
+impl From<QuoteTick> for LegacyPrice {
+    fn from(quote: QuoteTick) -> Self {
+        Self(quote.price)
+    }
+}
+
+impl From<LegacyPrice> for TradeTick {
+    fn from(price: LegacyPrice) -> Self {
+        tick(price.0, 0)
+    }
+}
+
+impl From<QuoteTick> for TradeTick {
+    fn from(quote: QuoteTick) -> Self {
+        tick(quote.price, quote.ts_event)
+    }
+}
+
+fn tick(price: f64, ts_event: u64) -> TradeTick {
+    TradeTick { id: Uuid::new_v4(), symbol: "BTC-USD".to_string(), price, size: 0.5, ts_event }
+}
+
+#[tokio::test]
+async fn canonical_publishes_reach_alias_subscribers() {
+    let bus = MessageBus::new(16);
+    let mut trades = bus.subscribe::<TradeTick>().await;
+    let mut quotes = bus.subscribe::<QuoteTick>().await;
+    let _alias = bus.type_alias::<QuoteTick, TradeTick>().await.unwrap();
+    // 重复登记不会让消息投递两次
+    let _again = bus.type_alias::<QuoteTick, TradeTick>().await.unwrap();
+
+    bus.publish(tick(100.5, 42)).await.unwrap();
+    assert_eq!(trades.recv_timeout(TIMEOUT).await.unwrap().price, 100.5);
+    let quote = quotes.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((quote.symbol.as_str(), quote.price, quote.ts_event), ("BTC-USD", 100.5, 42));
+    assert_eq!(quotes.recv_timeout(Duration::from_millis(50)).await.unwrap_err(), RecvTimeout::Timeout);
+
+    // 直接发布的别名类型不会转发回原类型
+    bus.publish(QuoteTick { symbol: "ETH-USD".to_string(), price: 10.0, ts_event: 1 }).await.unwrap();
+    assert_eq!(quotes.recv_timeout(TIMEOUT).await.unwrap().symbol, "ETH-USD");
+    assert_eq!(trades.recv_timeout(Duration::from_millis(50)).await.unwrap_err(), RecvTimeout::Timeout);
+}
+
+#[tokio::test]
+async fn aliases_chain_and_cycles_are_rejected() {
+    let bus = MessageBus::new(16);
+    let mut prices = bus.subscribe::<LegacyPrice>().await;
+    let _quotes = bus.type_alias::<QuoteTick, TradeTick>().await.unwrap();
+    let _prices = bus.type_alias::<LegacyPrice, QuoteTick>().await.unwrap();
+
+    bus.publish(tick(99.0, 7)).await.unwrap();
+    assert_eq!(prices.recv_timeout(TIMEOUT).await.unwrap().0, 99.0);
+
+    let error = bus.type_alias::<TradeTick, QuoteTick>().await.unwrap_err();
+    assert!(matches!(error, BusError::CircularAlias { .. }), "{}", error);
+    assert!(error.to_string().contains("QuoteTick"), "{}", error);
+    let error = bus.type_alias::<TradeTick, LegacyPrice>().await.unwrap_err();
+    assert!(matches!(error, BusError::CircularAlias { .. }), "{}", error);
+    assert!(matches!(bus.type_alias::<TradeTick, TradeTick>().await, Err(BusError::CircularAlias { .. })));
+}
+
+#[tokio::test]
+async fn dropping_the_last_handle_stops_the_relay() {
+    let bus = MessageBus::new(16);
+    let mut quotes = bus.subscribe::<QuoteTick>().await;
+    let first = bus.type_alias::<QuoteTick, TradeTick>().await.unwrap();
+    let second = bus.type_alias::<QuoteTick, TradeTick>().await.unwrap();
+    assert_eq!(bus.subscriber_count::<TradeTick>().await, 1);
+
+    // 仍有句柄时转发继续
+    drop(first);
+    bus.publish(tick(1.0, 1)).await.unwrap();
+    assert_eq!(quotes.recv_timeout(TIMEOUT).await.unwrap().price, 1.0);
+
+    drop(second);
+    let deadline = tokio::time::Instant::now() + TIMEOUT;
+    while bus.subscriber_count::<TradeTick>().await > 0 {
+        assert!(tokio::time::Instant::now() < deadline, "alias relay still subscribed");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(bus.publish(tick(2.0, 2)).await.unwrap(), 0);
+    assert_eq!(quotes.recv_timeout(Duration::from_millis(50)).await.unwrap_err(), RecvTimeout::Timeout);
+
+    // 注销后可以重新登记，反方向的别名也不再构成环
+    let reverse = bus.type_alias::<TradeTick, QuoteTick>().await.unwrap();
+    drop(reverse);
+    let _alias = bus.type_alias::<QuoteTick, TradeTick>().await.unwrap();
+    bus.publish(tick(3.0, 3)).await.unwrap();
+    assert_eq!(quotes.recv_timeout(TIMEOUT).await.unwrap().price, 3.0);
+}
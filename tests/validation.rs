@@ -0,0 +1,182 @@
+// tests/validation.rs
+
+//! # 订单校验测试
+//!
+//! 模拟执行引擎能给出的每种 `RejectReason`（`VenueRejected` 只来自真实场所，见 `execution_client.rs`）各由一笔订单触发，
+//! 验证执行引擎发布带该原因的 `OrderRejected` 而不是成交；
+//! 被禁用的原因不再检查；暂停在两笔订单之间开启又关闭时，只有暂停期间的订单被拒绝。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, Receiver, ReceiverExt, RecvTimeout};
+use message_bus::execution::SimulatedExecutionEngine;
+use message_bus::message::{
+    FillEvent, Message, OrderRejected, OrderRequest, OrderSide, OrderType, RejectReason, TradingHalted, TradingResumed,
+};
+use message_bus::throttle::ThrottleConfig;
+use message_bus::validation::ValidationConfig;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const QUIET: Duration = Duration::from_millis(50);
+
+fn order(symbol: &str, side: OrderSide, price: f64, quantity: f64) -> OrderRequest {
+    OrderRequest {
+        id: Uuid::new_v4(),
+        symbol: symbol.to_string(),
+        side,
+        order_type: OrderType::Market,
+        price,
+        quantity,
+        trigger_price: None,
+    }
+}
+
+fn buy(quantity: f64) -> OrderRequest {
+    order("BTC-USD", OrderSide::Buy, 100.0, quantity)
+}
+
+struct Engine {
+    bus: MessageBus,
+    fills: Receiver<FillEvent>,
+    rejections: Receiver<OrderRejected>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl Engine {
+    async fn start(configure: impl FnOnce(SimulatedExecutionEngine) -> SimulatedExecutionEngine) -> Self {
+        let bus = MessageBus::new(64);
+        let fills = bus.subscribe::<FillEvent>().await;
+        let rejections = bus.subscribe::<OrderRejected>().await;
+        let engine = Arc::new(configure(SimulatedExecutionEngine::new(bus.clone())));
+        let handles = engine.start("EXECUTION").await;
+        Self { bus, fills, rejections, handles }
+    }
+
+    /// 发布一条暂停/恢复消息。它与订单走不同的通道，等引擎处理完再继续下单。
+    async fn control<M: Message>(&self, msg: M) {
+        self.bus.publish(msg).await.unwrap();
+        tokio::time::sleep(QUIET).await;
+    }
+
+    /// 提交一笔订单并断言它以 `reason` 被拒绝、没有成交。
+    async fn expect_rejected(&mut self, order: OrderRequest, reason: RejectReason) {
+        self.bus.publish(order.clone()).await.unwrap();
+        let rejected = self.rejections.recv_timeout(TIMEOUT).await.unwrap();
+        assert_eq!(rejected.order_id, order.id);
+        assert_eq!(rejected.reason, reason, "{}", rejected.detail);
+        assert!(matches!(self.fills.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+    }
+
+    /// 提交一笔订单并断言它完全成交。
+    async fn expect_filled(&mut self, order: OrderRequest) {
+        self.bus.publish(order.clone()).await.unwrap();
+        let fill = self.fills.recv_timeout(TIMEOUT).await.unwrap();
+        assert_eq!(fill.order_id, order.id);
+        assert!(fill.is_final);
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        for handle in &self.handles {
+            handle.abort();
+        }
+    }
+}
+
+#[tokio::test]
+async fn non_positive_quantity_is_rejected() {
+    let mut engine = Engine::start(|engine| engine).await;
+    engine.expect_rejected(buy(0.0), RejectReason::InvalidQuantity).await;
+    engine.expect_rejected(buy(-1.0), RejectReason::InvalidQuantity).await;
+    engine.expect_rejected(buy(f64::NAN), RejectReason::InvalidQuantity).await;
+}
+
+#[tokio::test]
+async fn non_positive_price_is_rejected() {
+    let mut engine = Engine::start(|engine| engine).await;
+    engine.expect_rejected(order("BTC-USD", OrderSide::Buy, -5.0, 1.0), RejectReason::InvalidPrice).await;
+    engine.expect_rejected(order("BTC-USD", OrderSide::Sell, 0.0, 1.0), RejectReason::InvalidPrice).await;
+}
+
+#[tokio::test]
+async fn symbol_outside_the_known_list_is_rejected() {
+    let validation = ValidationConfig::default().with_known_symbols(["BTC-USD"]);
+    let mut engine = Engine::start(|engine| engine.with_validation(validation)).await;
+    engine.expect_rejected(order("DOGE-USD", OrderSide::Buy, 1.0, 1.0), RejectReason::UnknownSymbol).await;
+    engine.expect_filled(buy(1.0)).await;
+}
+
+#[tokio::test]
+async fn reused_order_id_is_rejected() {
+    let mut engine = Engine::start(|engine| engine).await;
+    let original = buy(1.0);
+    engine.expect_filled(original.clone()).await;
+    engine.expect_rejected(OrderRequest { quantity: 2.0, ..original }, RejectReason::DuplicateOrderId).await;
+}
+
+#[tokio::test]
+async fn full_order_id_window_rejects_new_ids() {
+    let validation = ValidationConfig::default().with_order_id_window(Duration::from_secs(60), 1);
+    let mut engine = Engine::start(|engine| engine.with_validation(validation)).await;
+    engine.expect_filled(buy(1.0)).await;
+    engine.expect_rejected(buy(1.0), RejectReason::OrderIdWindowFull).await;
+}
+
+#[tokio::test]
+async fn buy_beyond_available_cash_is_rejected() {
+    let mut engine = Engine::start(|engine| engine.with_cash_balance(150.0)).await;
+    // 第一笔用掉 100，剩余 50 不够再买一个
+    engine.expect_filled(buy(1.0)).await;
+    engine.expect_rejected(buy(1.0), RejectReason::InsufficientFunds).await;
+    // 卖出不占用资金，成交后资金回到 150
+    engine.expect_filled(order("BTC-USD", OrderSide::Sell, 100.0, 1.0)).await;
+    engine.expect_filled(buy(1.0)).await;
+}
+
+#[tokio::test]
+async fn order_during_halt_is_rejected() {
+    let mut engine = Engine::start(|engine| engine).await;
+    engine.control(TradingHalted { symbol: Some("BTC-USD".to_string()), reason: "test".to_string() }).await;
+    engine.expect_rejected(buy(1.0), RejectReason::TradingHalted).await;
+    // 只暂停了 BTC-USD
+    engine.expect_filled(order("ETH-USD", OrderSide::Buy, 10.0, 1.0)).await;
+}
+
+#[tokio::test]
+async fn order_over_the_rate_limit_is_throttled() {
+    let throttle = ThrottleConfig { max_orders_per_second_per_symbol: Some(1), ..Default::default() };
+    let mut engine = Engine::start(|engine| engine.with_throttle(throttle)).await;
+    engine.expect_filled(buy(1.0)).await;
+    engine.expect_rejected(buy(1.0), RejectReason::Throttled).await;
+}
+
+#[tokio::test]
+async fn disabled_reasons_are_not_checked() {
+    let validation = ValidationConfig::default()
+        .with_known_symbols(["BTC-USD"])
+        .disable(RejectReason::UnknownSymbol)
+        .disable(RejectReason::InsufficientFunds);
+    let mut engine = Engine::start(|engine| engine.with_validation(validation).with_cash_balance(0.0)).await;
+    engine.expect_filled(order("DOGE-USD", OrderSide::Buy, 1.0, 1.0)).await;
+    engine.expect_filled(buy(1.0)).await;
+    // 其他原因仍然检查
+    engine.expect_rejected(buy(0.0), RejectReason::InvalidQuantity).await;
+}
+
+#[tokio::test]
+async fn halt_toggled_between_orders_only_rejects_orders_while_halted() {
+    let mut engine = Engine::start(|engine| engine).await;
+    engine.expect_filled(buy(1.0)).await;
+
+    engine.control(TradingHalted { symbol: None, reason: "test".to_string() }).await;
+    engine.expect_rejected(buy(1.0), RejectReason::TradingHalted).await;
+    engine.expect_rejected(order("ETH-USD", OrderSide::Sell, 10.0, 1.0), RejectReason::TradingHalted).await;
+
+    engine.control(TradingResumed { symbol: None }).await;
+    engine.expect_filled(buy(1.0)).await;
+    engine.expect_filled(order("ETH-USD", OrderSide::Sell, 10.0, 1.0)).await;
+}
@@ -0,0 +1,60 @@
+// tests/vwap.rs
+
+//! # 滚动 VWAP 测试
+//!
+//! `VwapActor` 每笔成交后发布窗口内的 `sum(price * volume) / sum(volume)` 与窗口成交量；
+//! 超出时间窗口的成交被移除，不同 symbol 各自计算。
+
+use message_bus::message::{TradeTick, VwapUpdate};
+use message_bus::testkit::{ActorTestHarness, TestBus};
+use message_bus::vwap::{VwapActor, VwapState};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const SECOND: u64 = 1_000_000_000;
+
+fn tick(symbol: &str, price: f64, size: f64, ts_event: u64) -> TradeTick {
+    TradeTick { id: Uuid::new_v4(), symbol: symbol.to_string(), price, size, ts_event }
+}
+
+#[tokio::test]
+async fn publishes_volume_weighted_price_over_the_window() {
+    let test_bus = TestBus::new(64);
+    let actor = Arc::new(VwapActor::new(test_bus.bus(), Duration::from_secs(10)));
+    let mut harness = ActorTestHarness::start(test_bus, actor.clone()).await;
+
+    harness.send(tick("BTC-USD", 100.0, 1.0, 0)).await;
+    let update: VwapUpdate = harness.expect_message(TIMEOUT).await;
+    assert_eq!((update.vwap, update.window_volume, update.ts_event), (100.0, 1.0, 0));
+
+    // (100 * 1 + 110 * 3) / 4 = 107.5
+    harness.send(tick("BTC-USD", 110.0, 3.0, 5 * SECOND)).await;
+    let update: VwapUpdate = harness.expect_message(TIMEOUT).await;
+    assert_eq!(update.symbol, "BTC-USD");
+    assert_eq!(update.vwap, 107.5);
+    assert_eq!(update.window_volume, 4.0);
+
+    // 第一笔成交在 10 秒时滑出窗口：(110 * 3 + 90 * 1) / 4 = 105
+    harness.send(tick("BTC-USD", 90.0, 1.0, 10 * SECOND)).await;
+    let update: VwapUpdate = harness.expect_message(TIMEOUT).await;
+    assert_eq!(update.vwap, 105.0);
+    assert_eq!(update.window_volume, 4.0);
+
+    // 其他 symbol 单独计算
+    harness.send(tick("ETH-USD", 50.0, 2.0, 10 * SECOND)).await;
+    let update: VwapUpdate = harness.expect_message(TIMEOUT).await;
+    assert_eq!((update.symbol.as_str(), update.vwap), ("ETH-USD", 50.0));
+    assert_eq!(actor.vwap("BTC-USD"), Some(105.0));
+}
+
+#[test]
+fn zero_volume_has_no_vwap() {
+    let mut state = VwapState::default();
+    assert_eq!(state.vwap(), None);
+    state.push(0, 100.0, 0.0, Duration::from_secs(1));
+    assert_eq!(state.vwap(), None);
+    state.push(1, 102.0, 2.0, Duration::from_secs(1));
+    assert_eq!(state.vwap(), Some(102.0));
+}
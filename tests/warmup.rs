@@ -0,0 +1,76 @@
+// tests/warmup.rs
+
+//! # 预热测试
+//!
+//! `WarmupGuard` 只统计自己 symbol 的 `Bar`，达到 `required_bars` 时发布一次 `WarmupComplete`，
+//! 之后的 `Bar` 不再重复发布；其他 symbol 的 `Bar` 不计入。
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt, RecvTimeout};
+use message_bus::message::WarmupComplete;
+use message_bus::testkit::BarBuilder;
+use message_bus::warmup::WarmupGuard;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const QUIET: Duration = Duration::from_millis(100);
+
+#[tokio::test]
+async fn completes_once_after_the_required_bars_of_its_own_symbol() {
+    let bus = MessageBus::new(64);
+    let mut completions = bus.subscribe::<WarmupComplete>().await;
+    let guard = WarmupGuard::new(bus.clone(), "BTC-USD", 3);
+    let handles = guard.clone().start("WARMUP.BTC-USD").await;
+
+    let btc = BarBuilder::new();
+    let eth = BarBuilder::new().symbol("ETH-USD");
+    bus.publish(btc.clone().ts_event(1).build()).await.unwrap();
+    bus.publish(btc.clone().ts_event(2).build()).await.unwrap();
+    // 其他 symbol 的 `Bar` 再多也不计入
+    for ts in 1..=5 {
+        bus.publish(eth.clone().ts_event(ts).build()).await.unwrap();
+    }
+    assert!(matches!(completions.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    bus.publish(btc.clone().ts_event(3).build()).await.unwrap();
+    let complete = completions.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(complete.symbol, "BTC-USD");
+    assert_eq!(complete.bars_seen, 3);
+    assert_eq!(complete.ts_event, 3);
+
+    // 达到阈值之后不再发布
+    for ts in 4..=6 {
+        bus.publish(btc.clone().ts_event(ts).build()).await.unwrap();
+    }
+    assert!(matches!(completions.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test]
+async fn guards_for_different_symbols_count_independently() {
+    let bus = MessageBus::new(64);
+    let mut completions = bus.subscribe::<WarmupComplete>().await;
+    let btc_guard = WarmupGuard::new(bus.clone(), "BTC-USD", 2);
+    let eth_guard = WarmupGuard::new(bus.clone(), "ETH-USD", 2);
+    let mut handles = btc_guard.start("WARMUP.BTC-USD").await;
+    handles.extend(eth_guard.start("WARMUP.ETH-USD").await);
+
+    let eth = BarBuilder::new().symbol("ETH-USD");
+    bus.publish(BarBuilder::new().ts_event(1).build()).await.unwrap();
+    bus.publish(eth.clone().ts_event(2).build()).await.unwrap();
+    bus.publish(eth.clone().ts_event(3).build()).await.unwrap();
+    let complete = completions.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((complete.symbol.as_str(), complete.bars_seen, complete.ts_event), ("ETH-USD", 2, 3));
+    assert!(matches!(completions.recv_timeout(QUIET).await, Err(RecvTimeout::Timeout)));
+
+    bus.publish(BarBuilder::new().ts_event(4).build()).await.unwrap();
+    let complete = completions.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!((complete.symbol.as_str(), complete.bars_seen, complete.ts_event), ("BTC-USD", 2, 4));
+
+    for handle in handles {
+        handle.abort();
+    }
+}
@@ -0,0 +1,165 @@
+// tests/zmq.rs
+
+//! # ZeroMQ 网桥测试
+//!
+//! 两端在同一进程内通过 `inproc://` 互通：出站网桥发布选定类型，入站网桥按主题前缀订阅后发布到另一条总线；
+//! 验证按类型与按 symbol 的主题过滤，以及畸形消息被计数丢弃而接收继续。
+//! 需要启用 `zmq` feature：`cargo test --features zmq --test zmq`。
+
+#![cfg(feature = "zmq")]
+
+use message_bus::actor::Actor;
+use message_bus::bus::{MessageBus, ReceiverExt};
+use message_bus::codec::{Codec, JsonCodec, WireMessage};
+use message_bus::message::{Bar, FillEvent, Liquidity, OrderSide};
+use message_bus::testkit::BarBuilder;
+use message_bus::zmq_bridge::{ZmqBridge, ZmqConfig, ZmqStats};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+const QUIET: Duration = Duration::from_millis(100);
+const SLOW_JOINER: Duration = Duration::from_millis(100);
+
+fn fill() -> FillEvent {
+    FillEvent {
+        order_id: Uuid::new_v4(),
+        symbol: "BTC-USD".to_string(),
+        side: OrderSide::Buy,
+        price: 100.0,
+        quantity: 1.0,
+        leaves_qty: 0.0,
+        is_final: true,
+        commission: 0.0,
+        commission_currency: "USD".to_string(),
+        liquidity: Liquidity::Taker,
+        ts_event: 0,
+        venue_fill_id: None,
+        correlation_id: None,
+    }
+}
+
+fn publisher_config(endpoint: &str) -> ZmqConfig {
+    ZmqConfig { publish_endpoint: Some(endpoint.to_string()), slow_joiner_delay: SLOW_JOINER, ..Default::default() }
+}
+
+fn subscriber_config(endpoint: &str, topics: &[&str]) -> ZmqConfig {
+    ZmqConfig {
+        subscribe_endpoints: vec![endpoint.to_string()],
+        topics: topics.iter().map(|topic| topic.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn forwards_selected_types_between_buses() {
+    let context = zmq::Context::new();
+    let local = MessageBus::new(64);
+    let remote = MessageBus::new(64);
+    let outbound = Arc::new(
+        ZmqBridge::new(local.clone(), &context, publisher_config("inproc://types"))
+            .unwrap()
+            .with_outbound::<Bar>()
+            .with_outbound::<FillEvent>(),
+    );
+    let inbound = Arc::new(ZmqBridge::new(remote.clone(), &context, subscriber_config("inproc://types", &["Bar"])).unwrap());
+    let mut bars = remote.subscribe::<Bar>().await;
+    let mut fills = remote.subscribe::<FillEvent>().await;
+    let mut handles = outbound.clone().start("ZMQ_OUT").await;
+    handles.extend(inbound.clone().start("ZMQ_IN").await);
+
+    let sent = BarBuilder::new().price(101.0).build();
+    local.publish(fill()).await.unwrap();
+    local.publish(sent.clone()).await.unwrap();
+
+    let received = bars.recv_timeout(TIMEOUT).await.unwrap();
+    assert_eq!(received.id, sent.id);
+    assert_eq!(received.close, 101.0);
+    // 只订阅了 Bar 主题
+    assert!(fills.recv_timeout(QUIET).await.is_err());
+    assert_eq!(outbound.stats().sent, 2);
+    assert_eq!(inbound.stats(), ZmqStats { sent: 0, received: 1, malformed: 0 });
+
+    outbound.stop();
+    inbound.stop();
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn per_symbol_topics_filter_by_prefix() {
+    let context = zmq::Context::new();
+    let local = MessageBus::new(64);
+    let remote = MessageBus::new(64);
+    let outbound = Arc::new(
+        ZmqBridge::new(
+            local.clone(),
+            &context,
+            ZmqConfig { topic_per_symbol: true, ..publisher_config("inproc://symbols") },
+        )
+        .unwrap()
+        .with_outbound::<Bar>(),
+    );
+    let inbound =
+        Arc::new(ZmqBridge::new(remote.clone(), &context, subscriber_config("inproc://symbols", &["Bar.ETH-USD"])).unwrap());
+    let mut bars = remote.subscribe::<Bar>().await;
+    let mut handles = outbound.clone().start("ZMQ_OUT").await;
+    handles.extend(inbound.clone().start("ZMQ_IN").await);
+
+    local.publish(BarBuilder::new().price(100.0).build()).await.unwrap();
+    local.publish(BarBuilder::new().symbol("ETH-USD").price(10.0).build()).await.unwrap();
+
+    assert_eq!(bars.recv_timeout(TIMEOUT).await.unwrap().symbol, "ETH-USD");
+    assert!(bars.recv_timeout(QUIET).await.is_err());
+
+    outbound.stop();
+    inbound.stop();
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn malformed_frames_are_counted_and_dropped() {
+    let context = zmq::Context::new();
+    let raw = context.socket(zmq::PUB).unwrap();
+    raw.set_linger(0).unwrap();
+    raw.bind("inproc://raw").unwrap();
+    let remote = MessageBus::new(64);
+    let inbound = Arc::new(ZmqBridge::new(remote.clone(), &context, subscriber_config("inproc://raw", &[])).unwrap());
+    let mut bars = remote.subscribe::<Bar>().await;
+    let handles = inbound.clone().start("ZMQ_IN").await;
+
+    // 裸 PUB socket 没有慢连接者等待，重复发送探测消息直到订阅生效
+    let probe = JsonCodec.encode(&WireMessage::Bar(BarBuilder::new().price(100.0).build())).unwrap();
+    loop {
+        raw.send_multipart(["Bar".as_bytes(), probe.as_slice()], 0).unwrap();
+        if bars.recv_timeout(SLOW_JOINER).await.is_ok() {
+            break;
+        }
+    }
+
+    let valid = JsonCodec.encode(&WireMessage::Bar(BarBuilder::new().price(200.0).build())).unwrap();
+    raw.send("Bar", 0).unwrap(); // 只有一帧
+    raw.send_multipart(["Bar".as_bytes(), b"not json"], 0).unwrap();
+    raw.send_multipart(["FillEvent".as_bytes(), valid.as_slice()], 0).unwrap(); // 主题与负载不符
+    raw.send_multipart(["Bar".as_bytes(), valid.as_slice()], 0).unwrap();
+
+    // 跳过迟到的探测消息；畸形消息排在有效消息之前，收到它时计数已经更新
+    while bars.recv_timeout(TIMEOUT).await.unwrap().close != 200.0 {}
+    assert_eq!(inbound.stats().malformed, 3);
+
+    inbound.stop();
+    for handle in handles {
+        handle.abort();
+    }
+}
+
+#[test]
+fn bind_errors_are_reported_at_construction() {
+    let context = zmq::Context::new();
+    let error = ZmqBridge::new(MessageBus::new(8), &context, publisher_config("bogus://nowhere")).err().unwrap();
+    assert!(error.to_string().contains("bogus://nowhere"), "{}", error);
+}